@@ -0,0 +1,114 @@
+//! Workspace activity timeline
+//!
+//! Every state-changing workflow step (uploads, approvals, executions, ...) is
+//! appended here so the workspace home page and governance reviews can read a
+//! single chronologically ordered feed instead of stitching together the
+//! individual stores.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use crate::backup;
+
+/// Default page size for timeline reads
+const DEFAULT_PAGE_SIZE: usize = 50;
+/// Upper bound on a single timeline page
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ActivityKind {
+    PartyRegistered,
+    DatasetUploaded,
+    QueryCreated,
+    QuerySigned,
+    QueryExecuted,
+    ComputationRequested,
+    VoteCast,
+    ComputationExecuted,
+    ComputationFailed,
+    RequestCancelled,
+    RetryRequested,
+    KeyCeremonyStep,
+    ResultsShared,
+    IdentityRecovery,
+    LoadTestGenerated,
+    // Dataset purposes and the canister's privacy and LLM policies
+    PolicyChanged,
+    // Roles, pause, configuration, rate limits, quotas and retention
+    AdminAction,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ActivityEntry {
+    pub seq: u64,
+    pub workspace: String,
+    pub kind: ActivityKind,
+    pub actor: Principal,
+    pub resource_id: String,
+    pub summary: String,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ActivityPage {
+    pub entries: Vec<ActivityEntry>,
+    pub next_cursor: Option<u64>,
+}
+
+thread_local! {
+    static ACTIVITY: RefCell<Vec<ActivityEntry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Append an entry to the workspace timeline
+pub fn record(workspace: &str, kind: ActivityKind, actor: Principal, resource_id: &str, summary: String) {
+    ACTIVITY.with(|activity| {
+        let mut activity = activity.borrow_mut();
+        let seq = activity.len() as u64;
+        activity.push(ActivityEntry {
+            seq,
+            workspace: workspace.to_string(),
+            kind,
+            actor,
+            resource_id: resource_id.to_string(),
+            summary,
+            timestamp: time(),
+        });
+    });
+}
+
+/// Read a page of the timeline for a workspace.
+///
+/// Entries are returned oldest first. `since` filters by timestamp and
+/// `cursor` continues from the `next_cursor` of a previous page.
+pub fn timeline(workspace: &str, since: u64, cursor: Option<u64>, limit: Option<u32>) -> ActivityPage {
+    let limit = limit
+        .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    let start = cursor.unwrap_or(0) as usize;
+
+    ACTIVITY.with(|activity| {
+        let activity = activity.borrow();
+        let mut matching = activity
+            .iter()
+            .skip(start)
+            .filter(|e| e.workspace == workspace && e.timestamp >= since);
+
+        let entries: Vec<ActivityEntry> = matching.by_ref().take(limit).cloned().collect();
+        let next_cursor = match (entries.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.seq + 1),
+            _ => None,
+        };
+
+        ActivityPage { entries, next_cursor }
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&ACTIVITY))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let activity = backup::decode(bytes)?;
+    backup::replace(&ACTIVITY, activity);
+    Ok(())
+}
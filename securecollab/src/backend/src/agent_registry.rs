@@ -1,105 +1,189 @@
 use std::collections::HashMap;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use candid::{CandidType, Deserialize, Principal};
 
 
 use crate::MPCAgent;
+use crate::capabilities::{self, AgentFilter, Capability};
+use crate::backup;
+
+/// Words third parties may not use in agent ids, to prevent impersonation
+const RESERVED_ID_WORDS: &[&str] = &["securecollab", "official", "admin", "system"];
+pub const MAX_AGENT_ID_LEN: usize = 64;
+pub const MAX_AGENTS_PER_OWNER: usize = 5;
+/// Reputation third-party agents start with
+pub const INITIAL_REPUTATION: u32 = 50;
+
+/// Agents silent for longer than this are treated as unavailable (5 minutes)
+pub const DEFAULT_LIVENESS_WINDOW_NS: u64 = 5 * 60 * 1_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentLiveness {
+    pub agent_id: String,
+    pub last_seen: Option<u64>,
+    pub live: bool,
+}
+
+/// One entry per agent version with the capabilities it shipped with
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentVersion {
+    pub agent_id: String,
+    pub version: u32,
+    pub capabilities: Vec<Capability>,
+    pub change_note: String,
+    pub changed_by: Option<Principal>,
+    pub changed_at: u64,
+}
 
 // Store registered agents
 thread_local! {
+    static CHANGELOG: RefCell<HashMap<String, Vec<AgentVersion>>> = RefCell::new(HashMap::new());
     static AGENT_REGISTRY: RefCell<HashMap<String, MPCAgent>> = RefCell::new(HashMap::new());
+    static LAST_SEEN: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+    static LIVENESS_WINDOW: Cell<u64> = const { Cell::new(DEFAULT_LIVENESS_WINDOW_NS) };
 }
 
 /// Initialize the agent registry with specialized AI agents
-pub fn init() {
+pub fn init(now: u64) {
     let agents = vec![
         MPCAgent {
             id: "medical_research_agent".to_string(),
             identity: "Medical Research Specialist".to_string(),
             capabilities: vec![
-                "healthcare_data_analysis".to_string(),
-                "clinical_trial_analysis".to_string(),
-                "medical_privacy_compliance".to_string(),
-                "epidemiological_modeling".to_string(),
-                "drug_discovery_insights".to_string(),
+                Capability::HealthcareDataAnalysis,
+                Capability::ClinicalTrialAnalysis,
+                Capability::MedicalPrivacyCompliance,
+                Capability::EpidemiologicalModeling,
+                Capability::DrugDiscoveryInsights,
             ],
             reputation_score: 95,
             price_per_computation: 1000,
+            version: 1,
+            owner: None,
         },
         MPCAgent {
             id: "financial_analysis_agent".to_string(),
             identity: "Financial Analysis Expert".to_string(),
             capabilities: vec![
-                "risk_assessment".to_string(),
-                "fraud_detection".to_string(),
-                "market_analysis".to_string(),
-                "regulatory_compliance".to_string(),
-                "portfolio_optimization".to_string(),
+                Capability::RiskAssessment,
+                Capability::FraudDetection,
+                Capability::MarketAnalysis,
+                Capability::RegulatoryCompliance,
+                Capability::PortfolioOptimization,
             ],
             reputation_score: 92,
             price_per_computation: 800,
+            version: 1,
+            owner: None,
         },
         MPCAgent {
             id: "compliance_verification_agent".to_string(),
             identity: "Privacy & Compliance Auditor".to_string(),
             capabilities: vec![
-                "gdpr_compliance".to_string(),
-                "hipaa_verification".to_string(),
-                "data_anonymization".to_string(),
-                "privacy_impact_assessment".to_string(),
-                "regulatory_audit".to_string(),
+                Capability::GdprCompliance,
+                Capability::HipaaVerification,
+                Capability::DataAnonymization,
+                Capability::PrivacyImpactAssessment,
+                Capability::RegulatoryAudit,
             ],
             reputation_score: 98,
             price_per_computation: 1200,
+            version: 1,
+            owner: None,
         },
         MPCAgent {
             id: "data_science_agent".to_string(),
             identity: "Advanced Data Science AI".to_string(),
             capabilities: vec![
-                "statistical_analysis".to_string(),
-                "machine_learning".to_string(),
-                "predictive_modeling".to_string(),
-                "data_visualization".to_string(),
-                "pattern_recognition".to_string(),
+                Capability::StatisticalAnalysis,
+                Capability::MachineLearning,
+                Capability::PredictiveModeling,
+                Capability::DataVisualization,
+                Capability::PatternRecognition,
             ],
             reputation_score: 89,
             price_per_computation: 600,
+            version: 1,
+            owner: None,
         },
         MPCAgent {
             id: "cybersecurity_agent".to_string(),
             identity: "Cybersecurity Specialist".to_string(),
             capabilities: vec![
-                "threat_detection".to_string(),
-                "vulnerability_assessment".to_string(),
-                "security_audit".to_string(),
-                "incident_response".to_string(),
-                "cryptographic_analysis".to_string(),
+                Capability::ThreatDetection,
+                Capability::VulnerabilityAssessment,
+                Capability::SecurityAudit,
+                Capability::IncidentResponse,
+                Capability::CryptographicAnalysis,
             ],
             reputation_score: 94,
             price_per_computation: 900,
+            version: 1,
+            owner: None,
         },
         MPCAgent {
             id: "legal_analysis_agent".to_string(),
             identity: "Legal & Regulatory AI".to_string(),
             capabilities: vec![
-                "contract_analysis".to_string(),
-                "regulatory_interpretation".to_string(),
-                "legal_risk_assessment".to_string(),
-                "compliance_monitoring".to_string(),
-                "policy_analysis".to_string(),
+                Capability::ContractAnalysis,
+                Capability::RegulatoryInterpretation,
+                Capability::LegalRiskAssessment,
+                Capability::ComplianceMonitoring,
+                Capability::PolicyAnalysis,
             ],
             reputation_score: 91,
             price_per_computation: 1100,
+            version: 1,
+            owner: None,
         },
     ];
 
     AGENT_REGISTRY.with(|registry| {
         let mut reg = registry.borrow_mut();
         for agent in agents {
+            record_version(&agent, "Initial registration", None, now);
             reg.insert(agent.id.clone(), agent);
         }
     });
 }
 
+pub fn record_version(agent: &MPCAgent, change_note: &str, changed_by: Option<Principal>, now: u64) {
+    CHANGELOG.with(|changelog| {
+        changelog.borrow_mut().entry(agent.id.clone()).or_default().push(AgentVersion {
+            agent_id: agent.id.clone(),
+            version: agent.version,
+            capabilities: agent.capabilities.clone(),
+            change_note: change_note.to_string(),
+            changed_by,
+            changed_at: now,
+        });
+    });
+}
+
+/// Replace an agent's capabilities under a new version
+pub fn update_capabilities(
+    agent_id: &str,
+    capabilities: Vec<Capability>,
+    change_note: &str,
+    changed_by: Principal,
+    now: u64,
+) -> Result<MPCAgent, String> {
+    let agent = AGENT_REGISTRY.with(|registry| {
+        let mut reg = registry.borrow_mut();
+        let agent = reg.get_mut(agent_id).ok_or_else(|| format!("Agent {} not found", agent_id))?;
+        agent.capabilities = capabilities;
+        agent.version += 1;
+        Ok::<_, String>(agent.clone())
+    })?;
+    record_version(&agent, change_note, Some(changed_by), now);
+    Ok(agent)
+}
+
+/// Versions of an agent, oldest first
+pub fn changelog(agent_id: &str) -> Vec<AgentVersion> {
+    CHANGELOG.with(|changelog| changelog.borrow().get(agent_id).cloned().unwrap_or_default())
+}
+
 /// List all available agents
 pub fn list_all_agents() -> Vec<MPCAgent> {
     AGENT_REGISTRY.with(|registry| {
@@ -114,6 +198,64 @@ pub fn get_agent_by_id(agent_id: &str) -> Option<MPCAgent> {
     })
 }
 
+/// Ids that differ only in case or separators collide
+fn normalized_id(agent_id: &str) -> String {
+    agent_id.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_lowercase()).collect()
+}
+
+/// Check a third-party registration against the registry's naming and capability rules
+pub fn validate_registration(agent: &MPCAgent, owner: Principal) -> Result<(), String> {
+    let id = agent.id.as_str();
+    if id.is_empty() || id.len() > MAX_AGENT_ID_LEN {
+        return Err(format!("Agent id must be 1-{} characters", MAX_AGENT_ID_LEN));
+    }
+    if !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err("Agent id may only contain lowercase letters, digits and underscores".to_string());
+    }
+    if RESERVED_ID_WORDS.iter().any(|word| id.contains(word)) {
+        return Err(format!("Agent id '{}' uses a reserved word", id));
+    }
+    if agent.identity.trim().is_empty() {
+        return Err("Agent identity must not be empty".to_string());
+    }
+    if agent.capabilities.is_empty() {
+        return Err("An agent needs at least one capability".to_string());
+    }
+
+    let normalized = normalized_id(id);
+    AGENT_REGISTRY.with(|registry| {
+        let reg = registry.borrow();
+        if let Some(existing) = reg.values().find(|a| normalized_id(&a.id) == normalized) {
+            return Err(format!("Agent id '{}' is too similar to existing agent '{}'", id, existing.id));
+        }
+        if reg.values().filter(|a| a.owner == Some(owner)).count() >= MAX_AGENTS_PER_OWNER {
+            return Err(format!("An owner may register at most {} agents", MAX_AGENTS_PER_OWNER));
+        }
+        Ok(())
+    })
+}
+
+/// Hand an agent to the principal allowed to update it and withdraw its earnings
+pub fn set_owner(agent_id: &str, owner: Principal) -> Result<(), String> {
+    AGENT_REGISTRY.with(|registry| {
+        let mut reg = registry.borrow_mut();
+        let agent = reg.get_mut(agent_id).ok_or_else(|| format!("Agent {} not found", agent_id))?;
+        agent.owner = Some(owner);
+        Ok(())
+    })
+}
+
+pub fn owner_of(agent_id: &str) -> Option<Principal> {
+    get_agent_by_id(agent_id).and_then(|agent| agent.owner)
+}
+
+/// Agents operated by `owner`
+pub fn agents_owned_by(owner: Principal) -> Vec<String> {
+    AGENT_REGISTRY.with(|registry| {
+        registry.borrow().values().filter(|a| a.owner == Some(owner)).map(|a| a.id.clone()).collect()
+    })
+}
+
 /// Register a new agent
 pub fn register_agent(agent: crate::MPCAgent) -> Result<(), String> {
     AGENT_REGISTRY.with(|registry| {
@@ -142,11 +284,11 @@ pub fn update_agent_reputation(agent_id: &str, performance_score: u32) -> Result
 }
 
 /// Find agents by capability
-pub fn find_agents_by_capability(capability: &str) -> Vec<MPCAgent> {
+pub fn find_agents_by_capability(capability: Capability) -> Vec<MPCAgent> {
     AGENT_REGISTRY.with(|registry| {
         registry.borrow()
             .values()
-            .filter(|agent| agent.capabilities.contains(&capability.to_string()))
+            .filter(|agent| agent.capabilities.contains(&capability))
             .cloned()
             .collect()
     })
@@ -159,33 +301,71 @@ pub fn get_top_agents(limit: usize) -> Vec<MPCAgent> {
     agents.into_iter().take(limit).collect()
 }
 
-/// Check if agent is available for computation
-pub fn is_agent_available(agent_id: &str) -> bool {
-    AGENT_REGISTRY.with(|registry| {
-        registry.borrow().contains_key(agent_id)
-    })
+/// Record that an agent is alive
+pub fn record_heartbeat(agent_id: &str, now: u64) {
+    LAST_SEEN.with(|seen| {
+        seen.borrow_mut().insert(agent_id.to_string(), now);
+    });
+}
+
+/// Set how long an agent may stay silent before it counts as unavailable
+pub fn set_liveness_window(window_ns: u64) {
+    LIVENESS_WINDOW.with(|window| window.set(window_ns));
 }
 
-/// Get agents suitable for a specific computation type
-pub fn get_suitable_agents(computation_type: &str) -> Vec<MPCAgent> {
-    let relevant_capabilities = match computation_type.to_lowercase().as_str() {
-        "medical" | "healthcare" => vec!["healthcare_data_analysis", "medical_privacy_compliance"],
-        "financial" | "finance" => vec!["risk_assessment", "fraud_detection", "market_analysis"],
-        "compliance" | "privacy" => vec!["gdpr_compliance", "hipaa_verification", "privacy_impact_assessment"],
-        "security" | "cybersecurity" => vec!["threat_detection", "security_audit", "cryptographic_analysis"],
-        "legal" => vec!["contract_analysis", "legal_risk_assessment", "regulatory_interpretation"],
-        _ => vec!["statistical_analysis", "machine_learning", "data_visualization"],
-    };
+pub fn liveness_window() -> u64 {
+    LIVENESS_WINDOW.with(|window| window.get())
+}
+
+/// Whether a heartbeat at `last_seen` is recent enough at `now`
+pub fn is_live(last_seen: Option<u64>, now: u64, window_ns: u64) -> bool {
+    last_seen.is_some_and(|seen| now.saturating_sub(seen) <= window_ns)
+}
 
+/// Liveness of an agent at `now`
+pub fn liveness(agent_id: &str, now: u64) -> AgentLiveness {
+    let last_seen = LAST_SEEN.with(|seen| seen.borrow().get(agent_id).copied());
+    AgentLiveness {
+        agent_id: agent_id.to_string(),
+        last_seen,
+        live: is_live(last_seen, now, liveness_window()),
+    }
+}
+
+/// Check if agent is registered and has sent a recent heartbeat
+pub fn is_agent_available(agent_id: &str, now: u64) -> bool {
     AGENT_REGISTRY.with(|registry| {
+        registry.borrow().contains_key(agent_id)
+    }) && liveness(agent_id, now).live
+}
+
+/// Agents matching a structured filter, best first
+pub fn search(filter: &AgentFilter, now: u64) -> Vec<MPCAgent> {
+    let mut agents: Vec<MPCAgent> = AGENT_REGISTRY.with(|registry| {
         registry.borrow()
             .values()
-            .filter(|agent| {
-                relevant_capabilities.iter().any(|cap| 
-                    agent.capabilities.contains(&cap.to_string())
-                )
-            })
+            .filter(|agent| capabilities::matches(agent, filter, liveness(&agent.id, now).live))
             .cloned()
             .collect()
-    })
+    });
+    capabilities::sort_results(&mut agents);
+    agents
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&AGENT_REGISTRY),
+        backup::cloned(&CHANGELOG),
+        backup::cloned(&LAST_SEEN),
+        LIVENESS_WINDOW.with(Cell::get),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (agent_registry, changelog, last_seen, liveness_window) = backup::decode(bytes)?;
+    backup::replace(&AGENT_REGISTRY, agent_registry);
+    backup::replace(&CHANGELOG, changelog);
+    backup::replace(&LAST_SEEN, last_seen);
+    LIVENESS_WINDOW.with(|cell| cell.set(liveness_window));
+    Ok(())
 }
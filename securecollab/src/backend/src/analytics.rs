@@ -0,0 +1,413 @@
+//! Schema-driven analytics over decrypted datasets
+//!
+//! A dataset declares its schema as comma-separated `name:type` columns,
+//! where the type is `number` or `integer`, either optionally bounded as in
+//! `number[min..max]`, `category`, `boolean`, `date` (`YYYY-MM-DD`), `id`
+//! or `text`; a column declared by name only is text. A type ending in `?`
+//! allows empty values, which other typed columns reject. Uploads are
+//! validated against their schema, and uploads without one get one inferred
+//! from their values, for the owner to confirm. Columns are found by name in
+//! the CSV header, so their order does not matter, and each one gets the
+//! statistics its type supports: numbers their mean, standard
+//! deviation, median and range, categories and booleans their value counts,
+//! dates, identifiers and text only their distinct values. Bounded numbers,
+//! categories and booleans are the columns a private release can include.
+
+use candid::{CandidType, Deserialize};
+use std::collections::{BTreeMap, HashSet};
+use crate::tabular::{self, DataFormat, RowError, Table};
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    Number { bounds: Option<(f64, f64)>, integer: bool },
+    Category,
+    Boolean,
+    Date,
+    Identifier,
+    Text,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+impl ColumnSpec {
+    /// Whether a differentially private release includes this column
+    pub fn released(&self) -> bool {
+        matches!(self.column_type, ColumnType::Number { bounds: Some(_), .. } | ColumnType::Category | ColumnType::Boolean)
+    }
+}
+
+/// Values of one column that break its declaration, or a column missing
+/// from either the data or the schema
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ColumnError {
+    pub column: String,
+    pub message: String,
+    pub rows: u64,
+    /// Line of the first offending row
+    pub first_line: Option<u64>,
+}
+
+/// How an upload parses and fits its schema
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct UploadValidation {
+    pub format: DataFormat,
+    pub schema: String,
+    /// Whether `schema` was inferred, for the owner to confirm or correct
+    pub inferred_schema: bool,
+    pub records: u64,
+    pub column_errors: Vec<ColumnError>,
+    pub rejected_rows: Vec<RowError>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct NumericStatistics {
+    pub count: u64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ColumnStatistics {
+    Numeric(NumericStatistics),
+    /// Records per value, ordered by value
+    Counts(Vec<(String, u64)>),
+    Distinct(u64),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ColumnAnalysis {
+    pub spec: ColumnSpec,
+    /// Empty values, and values that do not parse as the column's type
+    pub missing: u64,
+    pub statistics: ColumnStatistics,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DatasetAnalysis {
+    pub total_records: u64,
+    pub columns: Vec<ColumnAnalysis>,
+}
+
+/// Analysis over several parties' datasets that share a schema
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CombinedAnalysis {
+    pub dataset_count: usize,
+    // Datasets left out because their header lacks an analyzed column
+    pub skipped_datasets: usize,
+    // Records contributed by each input dataset, 0 for skipped ones
+    pub dataset_records: Vec<u64>,
+    pub analysis: DatasetAnalysis,
+}
+
+fn parse_type(column: &str, declared: &str) -> Result<ColumnType, String> {
+    let declared = declared.trim().to_lowercase();
+    for (prefix, integer) in [("number", false), ("integer", true)] {
+        let Some(rest) = declared.strip_prefix(prefix) else {
+            continue;
+        };
+        if rest.is_empty() {
+            return Ok(ColumnType::Number { bounds: None, integer });
+        }
+        let bounds = rest.strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|range| range.split_once(".."))
+            .and_then(|(min, max)| Some((min.trim().parse::<f64>().ok()?, max.trim().parse::<f64>().ok()?)))
+            .filter(|(min, max)| min.is_finite() && max.is_finite() && min < max)
+            .ok_or_else(|| format!("Column '{}' needs bounds written as {}[min..max]", column, prefix))?;
+        return Ok(ColumnType::Number { bounds: Some(bounds), integer });
+    }
+    match declared.as_str() {
+        "category" => Ok(ColumnType::Category),
+        "boolean" => Ok(ColumnType::Boolean),
+        "date" => Ok(ColumnType::Date),
+        "id" => Ok(ColumnType::Identifier),
+        "text" => Ok(ColumnType::Text),
+        other => Err(format!("Unknown type '{}' for column '{}'", other, column)),
+    }
+}
+
+pub fn parse_schema(schema: &str) -> Result<Vec<ColumnSpec>, String> {
+    let mut columns: Vec<ColumnSpec> = Vec::new();
+    for entry in schema.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, column_type, nullable) = match entry.split_once(':') {
+            Some((name, declared)) => {
+                let declared = declared.trim();
+                let (declared, nullable) = declared.strip_suffix('?').map_or((declared, false), |d| (d, true));
+                (name.trim(), parse_type(name.trim(), declared)?, nullable)
+            }
+            None => (entry, ColumnType::Text, true),
+        };
+        if name.is_empty() {
+            return Err("Schema columns need a name".to_string());
+        }
+        if columns.iter().any(|c| c.name == name) {
+            return Err(format!("Column '{}' is declared twice", name));
+        }
+        columns.push(ColumnSpec { name: name.to_string(), column_type, nullable });
+    }
+    Ok(columns)
+}
+
+/// Most distinct values of a column inferred as a category
+pub const MAX_INFERRED_CATEGORIES: usize = 20;
+
+fn type_name(column_type: &ColumnType) -> String {
+    match column_type {
+        ColumnType::Number { bounds, integer } => {
+            let name = if *integer { "integer" } else { "number" };
+            match bounds {
+                Some((min, max)) => format!("{}[{}..{}]", name, min, max),
+                None => name.to_string(),
+            }
+        }
+        ColumnType::Category => "category".to_string(),
+        ColumnType::Boolean => "boolean".to_string(),
+        ColumnType::Date => "date".to_string(),
+        ColumnType::Identifier => "id".to_string(),
+        ColumnType::Text => "text".to_string(),
+    }
+}
+
+/// Schema string that `parse_schema` reads back into `columns`
+pub fn render_schema(columns: &[ColumnSpec]) -> String {
+    columns.iter()
+        .map(|c| format!("{}:{}{}", c.name, type_name(&c.column_type), if c.nullable { "?" } else { "" }))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The type a typed file declares for each column, otherwise the narrowest
+/// type every value of the column fits. Numbers are left
+/// unbounded, since bounds taken from the data would leak it; the owner
+/// declares them when confirming the schema.
+pub fn infer_schema(table: &Table) -> Vec<ColumnSpec> {
+    table.header.iter().enumerate().map(|(i, name)| {
+        let values: Vec<&str> = table.rows.iter().map(|row| row[i].as_str()).filter(|v| !v.is_empty()).collect();
+        let nullable = values.len() < table.rows.len();
+        let all = |fits: &dyn Fn(&str) -> bool| !values.is_empty() && values.iter().all(|v| fits(v));
+        let distinct = values.iter().collect::<HashSet<_>>().len();
+        let column_type = if let Some(declared) = &table.types[i] {
+            declared.clone()
+        } else if all(&|v| boolean(v).is_some() && v.parse::<f64>().is_err()) {
+            ColumnType::Boolean
+        } else if all(&|v| number(v, true).is_some()) {
+            ColumnType::Number { bounds: None, integer: true }
+        } else if all(&|v| number(v, false).is_some()) {
+            ColumnType::Number { bounds: None, integer: false }
+        } else if all(&is_date) {
+            ColumnType::Date
+        } else if distinct > 0 && distinct <= MAX_INFERRED_CATEGORIES && distinct < values.len() {
+            ColumnType::Category
+        } else if distinct > 1 && distinct == values.len() {
+            ColumnType::Identifier
+        } else {
+            ColumnType::Text
+        };
+        ColumnSpec { name: name.clone(), column_type, nullable }
+    }).collect()
+}
+
+fn boolean(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn number(value: &str, integer: bool) -> Option<f64> {
+    if integer {
+        value.parse::<i64>().ok().map(|v| v as f64)
+    } else {
+        value.parse::<f64>().ok().filter(|v| v.is_finite())
+    }
+}
+
+/// Value of a number or boolean column as a number, booleans being 0 or 1
+pub fn numeric_value(column_type: &ColumnType, value: &str) -> Option<f64> {
+    match column_type {
+        ColumnType::Number { integer, .. } => number(value, *integer),
+        ColumnType::Boolean => boolean(value).map(|b| if b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+/// Calendar date written `YYYY-MM-DD`
+pub fn is_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+    let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    if !(digits(year, 4) && digits(month, 2) && digits(day, 2)) {
+        return false;
+    }
+    let (year, month, day) = (year.parse::<u32>().unwrap_or(0), month.parse::<u32>().unwrap_or(0), day.parse::<u32>().unwrap_or(0));
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+fn fits(column_type: &ColumnType, value: &str) -> bool {
+    match *column_type {
+        ColumnType::Number { bounds, integer } => number(value, integer)
+            .is_some_and(|v| bounds.is_none_or(|(min, max)| (min..=max).contains(&v))),
+        ColumnType::Boolean => boolean(value).is_some(),
+        ColumnType::Date => is_date(value),
+        ColumnType::Category | ColumnType::Identifier | ColumnType::Text => true,
+    }
+}
+
+/// Check that `table` has exactly the schema's columns and that every value
+/// fits its column's type, bounds and nullability
+pub fn validate(schema: &[ColumnSpec], table: &Table) -> Vec<ColumnError> {
+    let missing = |column: &str, message: &str| ColumnError { column: column.to_string(), message: message.to_string(), rows: 0, first_line: None };
+    let mut errors: Vec<ColumnError> = table.header.iter()
+        .filter(|name| !schema.iter().any(|spec| spec.name == **name))
+        .map(|name| missing(name, "Column is not declared in the schema"))
+        .collect();
+    for spec in schema {
+        let Some(position) = table.header.iter().position(|name| *name == spec.name) else {
+            errors.push(missing(&spec.name, "Declared column is missing from the data"));
+            continue;
+        };
+        let (mut empty, mut invalid) = (vec![], vec![]);
+        for (row, line) in table.rows.iter().zip(&table.lines) {
+            let value = row[position].as_str();
+            if value.is_empty() {
+                if !spec.nullable {
+                    empty.push(*line);
+                }
+            } else if !fits(&spec.column_type, value) {
+                invalid.push(*line);
+            }
+        }
+        let offending = [(empty, "Empty values in a required column".to_string()), (invalid, format!("Values that are not {}", type_name(&spec.column_type)))];
+        errors.extend(offending.into_iter().filter(|(lines, _)| !lines.is_empty()).map(|(lines, message)| ColumnError {
+            column: spec.name.clone(),
+            message,
+            rows: lines.len() as u64,
+            first_line: lines.first().copied(),
+        }));
+    }
+    errors
+}
+
+/// Parse an upload and validate it against `schema`, or infer a schema
+/// when it is empty
+pub fn validate_upload(data: &[u8], schema: &str) -> Result<UploadValidation, String> {
+    let table = tabular::parse(data).map_err(|e| format!("Unreadable upload: {}", e))?;
+    let inferred_schema = schema.trim().is_empty();
+    let columns = if inferred_schema { infer_schema(&table) } else { parse_schema(schema)? };
+    Ok(UploadValidation {
+        format: tabular::detect(data),
+        schema: if inferred_schema { render_schema(&columns) } else { schema.to_string() },
+        inferred_schema,
+        records: table.rows.len() as u64,
+        column_errors: validate(&columns, &table),
+        rejected_rows: table.errors,
+    })
+}
+
+fn numeric(values: &[f64]) -> NumericStatistics {
+    if values.is_empty() {
+        return NumericStatistics { count: 0, mean: 0.0, std_dev: 0.0, median: 0.0, min: 0.0, max: 0.0 };
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+    let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+    let median = if n.is_multiple_of(2) { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 } else { sorted[n / 2] };
+    NumericStatistics { count: n as u64, mean, std_dev: variance.sqrt(), median, min: sorted[0], max: sorted[n - 1] }
+}
+
+fn analyze_column(spec: &ColumnSpec, values: &[&str]) -> ColumnAnalysis {
+    let present: Vec<&str> = values.iter().copied().filter(|v| !v.is_empty()).collect();
+    let (parsed, statistics) = match spec.column_type {
+        ColumnType::Number { integer, .. } => {
+            let numbers: Vec<f64> = present.iter().filter_map(|v| number(v, integer)).collect();
+            (numbers.len(), ColumnStatistics::Numeric(numeric(&numbers)))
+        }
+        ColumnType::Boolean => {
+            let flags: Vec<bool> = present.iter().filter_map(|v| boolean(v)).collect();
+            let trues = flags.iter().filter(|&&flag| flag).count() as u64;
+            let counts = vec![("false".to_string(), flags.len() as u64 - trues), ("true".to_string(), trues)];
+            (flags.len(), ColumnStatistics::Counts(counts))
+        }
+        ColumnType::Category => {
+            let mut counts = BTreeMap::new();
+            for value in &present {
+                *counts.entry(value.to_string()).or_insert(0u64) += 1;
+            }
+            (present.len(), ColumnStatistics::Counts(counts.into_iter().collect()))
+        }
+        ColumnType::Date => {
+            let dates: HashSet<&str> = present.iter().copied().filter(|v| is_date(v)).collect();
+            (present.iter().filter(|v| is_date(v)).count(), ColumnStatistics::Distinct(dates.len() as u64))
+        }
+        ColumnType::Identifier | ColumnType::Text => {
+            let distinct = present.iter().collect::<HashSet<_>>().len() as u64;
+            (present.len(), ColumnStatistics::Distinct(distinct))
+        }
+    };
+    ColumnAnalysis { spec: spec.clone(), missing: (values.len() - parsed) as u64, statistics }
+}
+
+/// Analyze the schema's columns over every dataset whose header has them;
+/// columns missing from the first dataset, such as those a query did not
+/// decrypt, are left out
+pub fn combine(schema: &[ColumnSpec], datasets: &[String]) -> Result<CombinedAnalysis, String> {
+    // Unreadable datasets are skipped like those missing a column
+    let tables: Vec<Option<Table>> = datasets.iter().map(|data| tabular::parse(data.as_bytes()).ok()).collect();
+    let first = tables.iter().flatten().next().ok_or_else(|| "No datasets to analyze".to_string())?;
+    let specs: Vec<&ColumnSpec> = schema.iter().filter(|spec| first.header.contains(&spec.name)).collect();
+
+    let mut values: Vec<Vec<&str>> = vec![Vec::new(); specs.len()];
+    let mut dataset_count = 0;
+    let mut dataset_records = Vec::with_capacity(datasets.len());
+    for table in &tables {
+        let positions: Option<Vec<usize>> = table.as_ref().and_then(|table| {
+            specs.iter().map(|spec| table.header.iter().position(|h| *h == spec.name)).collect()
+        });
+        let (Some(table), Some(positions)) = (table, positions) else {
+            dataset_records.push(0);
+            continue;
+        };
+        dataset_count += 1;
+        for row in &table.rows {
+            for (column, position) in values.iter_mut().zip(&positions) {
+                column.push(&row[*position]);
+            }
+        }
+        dataset_records.push(table.rows.len() as u64);
+    }
+
+    Ok(CombinedAnalysis {
+        dataset_count,
+        skipped_datasets: datasets.len() - dataset_count,
+        analysis: DatasetAnalysis {
+            total_records: dataset_records.iter().sum(),
+            columns: specs.iter().zip(&values).map(|(spec, values)| analyze_column(spec, values)).collect(),
+        },
+        dataset_records,
+    })
+}
+
+#[cfg(test)]
+#[path = "analytics_test.rs"]
+mod tests;
@@ -0,0 +1,108 @@
+use super::*;
+
+const SCHEMA: &str = "patient_id:id, age:number[0..120], smoker:boolean, region:category, notes";
+
+fn column<'a>(combined: &'a CombinedAnalysis, name: &str) -> &'a ColumnAnalysis {
+    combined.analysis.columns.iter().find(|c| c.spec.name == name).unwrap()
+}
+
+#[test]
+fn parses_typed_and_untyped_columns() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    assert_eq!(schema.len(), 5);
+    assert_eq!(schema[1].column_type, ColumnType::Number { bounds: Some((0.0, 120.0)), integer: false });
+    assert_eq!(schema[4].column_type, ColumnType::Text);
+    assert!(!schema[1].nullable && schema[4].nullable);
+    assert!(parse_schema("age:integer[0..120]?").unwrap()[0].nullable);
+    assert_eq!(schema.iter().filter(|c| c.released()).count(), 3);
+
+    assert!(parse_schema("age:number[120..0]").is_err());
+    assert!(parse_schema("age:number[0,120]").is_err());
+    assert!(parse_schema("age:decimal").is_err());
+    assert!(parse_schema("age,age:number").is_err());
+    assert!(parse_schema(":category").is_err());
+}
+
+#[test]
+fn finds_columns_by_name_and_computes_statistics_per_type() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let a = "patient_id,age,smoker,region,notes\np1,30,yes,north,x\np2,50,no,south,\np3,,true,north,y".to_string();
+    // Same columns in another order
+    let b = "region,notes,age,smoker,patient_id\nnorth,z,40,n,p4".to_string();
+    let combined = combine(&schema, &[a, b]).unwrap();
+
+    assert_eq!(combined.dataset_count, 2);
+    assert_eq!(combined.dataset_records, vec![3, 1]);
+    assert_eq!(combined.analysis.total_records, 4);
+
+    let age = column(&combined, "age");
+    assert_eq!(age.missing, 1);
+    assert_eq!(age.statistics, ColumnStatistics::Numeric(NumericStatistics {
+        count: 3, mean: 40.0, std_dev: (200.0f64 / 3.0).sqrt(), median: 40.0, min: 30.0, max: 50.0,
+    }));
+    assert_eq!(column(&combined, "smoker").statistics, ColumnStatistics::Counts(vec![("false".to_string(), 2), ("true".to_string(), 2)]));
+    assert_eq!(column(&combined, "region").statistics, ColumnStatistics::Counts(vec![("north".to_string(), 3), ("south".to_string(), 1)]));
+    assert_eq!(column(&combined, "patient_id").statistics, ColumnStatistics::Distinct(4));
+    assert_eq!(column(&combined, "notes").missing, 1);
+}
+
+#[test]
+fn skips_datasets_missing_analyzed_columns() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    // Only the decrypted columns are analyzed
+    let subset = "age,region\n20,east\n".to_string();
+    let other = "id,score\n1,2".to_string();
+    let combined = combine(&schema, &[subset, other]).unwrap();
+    assert_eq!(combined.analysis.columns.len(), 2);
+    assert_eq!((combined.dataset_count, combined.skipped_datasets), (1, 1));
+    assert_eq!(combined.dataset_records, vec![1, 0]);
+    assert!(combine(&schema, &[]).is_err());
+}
+
+#[test]
+fn infers_types_from_values() {
+    let data = "id,age,bmi,visit,smoker,region,note\n\
+        p1,30,21.5,2024-01-31,yes,north,a\n\
+        p2,41,,2024-02-29,no,north,a\n\
+        p3,57,27,2023-12-01,yes,south,b";
+    let schema = infer_schema(&tabular::parse(data.as_bytes()).unwrap());
+    assert_eq!(render_schema(&schema), "id:id,age:integer,bmi:number?,visit:date,smoker:boolean,region:category,note:category");
+    assert_eq!(parse_schema(&render_schema(&schema)).unwrap(), schema);
+
+    let invalid_date = "visit\n2023-02-29\n2024-01-01";
+    assert_eq!(infer_schema(&tabular::parse(invalid_date.as_bytes()).unwrap())[0].column_type, ColumnType::Identifier);
+}
+
+#[test]
+fn integer_columns_count_fractions_as_missing() {
+    let schema = parse_schema("visits:integer[0..50],seen:date").unwrap();
+    assert_eq!(render_schema(&schema), "visits:integer[0..50],seen:date");
+    assert!(schema.iter().all(|c| !c.nullable));
+    let combined = combine(&schema, &["visits,seen\n3,2024-05-01\n2.5,2024-13-01\n4,2024-05-01".to_string()]).unwrap();
+    assert_eq!(combined.analysis.columns[0].missing, 1);
+    assert_eq!(combined.analysis.columns[1].statistics, ColumnStatistics::Distinct(1));
+    assert_eq!(combined.analysis.columns[1].missing, 1);
+}
+
+#[test]
+fn validation_reports_errors_per_column() {
+    let schema = parse_schema("id:id,age:integer[0..120],smoker:boolean?,region:category").unwrap();
+    let data = "id,age,smoker,extra\np1,30,,x\np2,130,maybe,y\np3,,yes,z\np4,41.5,no,w";
+    let errors = validate(&schema, &tabular::parse(data.as_bytes()).unwrap());
+    let summary: Vec<(&str, u64, Option<u64>)> = errors.iter().map(|e| (e.column.as_str(), e.rows, e.first_line)).collect();
+    assert_eq!(summary, vec![("extra", 0, None), ("age", 1, Some(4)), ("age", 2, Some(3)), ("smoker", 1, Some(3)), ("region", 0, None)]);
+    assert_eq!(errors[2].message, "Values that are not integer[0..120]");
+
+    let validation = validate_upload(b"a,b\n1,2\n3\n", "a:integer,b:integer").unwrap();
+    assert_eq!((validation.records, validation.rejected_rows.len()), (1, 1));
+    assert!(validation.column_errors.is_empty() && !validation.inferred_schema);
+    assert!(validate_upload(b"a,b\n1,x", "").unwrap().inferred_schema);
+    assert!(validate_upload(b"", "a").is_err());
+}
+
+#[test]
+fn inference_keeps_declared_file_types() {
+    let mut table = tabular::parse(b"code,score\n1,2\n2,3").unwrap();
+    table.types = vec![Some(ColumnType::Category), None];
+    assert_eq!(render_schema(&infer_schema(&table)), "code:category,score:integer");
+}
@@ -0,0 +1,192 @@
+//! Access-pattern anomaly detection
+//!
+//! Dataset accesses are tracked per principal. Sudden bulk decryption and
+//! access outside working hours raise alerts that parties can review. When
+//! auto-suspension is on, the offending principal is blocked from further
+//! decryption until an admin reviews the alert.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::{HashMap, HashSet};
+use std::cell::{Cell, RefCell};
+use crate::backup;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const NANOS_PER_HOUR: u64 = 3_600 * NANOS_PER_SECOND;
+/// Decryptions by one principal within the window that count as bulk access
+pub const BULK_THRESHOLD: usize = 20;
+/// Sliding window for bulk detection (10 minutes)
+pub const BULK_WINDOW_NS: u64 = 10 * 60 * NANOS_PER_SECOND;
+/// Working hours in UTC, [start, end)
+pub const WORKING_HOURS_UTC: (u64, u64) = (6, 20);
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AnomalyKind {
+    BulkDecryption,
+    OutOfHours,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AlertStatus {
+    Open,
+    Dismissed,
+    Confirmed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AnomalyAlert {
+    pub id: u64,
+    pub principal: Principal,
+    pub kind: AnomalyKind,
+    pub dataset_id: String,
+    pub details: String,
+    pub timestamp: u64,
+    pub status: AlertStatus,
+    pub suspended: bool,
+}
+
+thread_local! {
+    static RECENT_ACCESS: RefCell<HashMap<Principal, Vec<u64>>> = RefCell::new(HashMap::new());
+    static ALERTS: RefCell<Vec<AnomalyAlert>> = const { RefCell::new(Vec::new()) };
+    static SUSPENDED: RefCell<HashSet<Principal>> = RefCell::new(HashSet::new());
+    static AUTO_SUSPEND: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Anomalies raised by an access at `now`, given the principal's access
+/// timestamps within the bulk window (including this one)
+pub fn detect(recent_accesses: usize, now: u64) -> Vec<AnomalyKind> {
+    let mut anomalies = Vec::new();
+    // Only the access that crosses the threshold raises the alert
+    if recent_accesses == BULK_THRESHOLD + 1 {
+        anomalies.push(AnomalyKind::BulkDecryption);
+    }
+    let hour = (now / NANOS_PER_HOUR) % 24;
+    if hour < WORKING_HOURS_UTC.0 || hour >= WORKING_HOURS_UTC.1 {
+        anomalies.push(AnomalyKind::OutOfHours);
+    }
+    anomalies
+}
+
+/// Track a dataset access and raise alerts for anything anomalous
+pub fn observe(principal: Principal, dataset_id: &str, now: u64) -> Vec<AnomalyAlert> {
+    let recent = RECENT_ACCESS.with(|access| {
+        let mut access = access.borrow_mut();
+        let timestamps = access.entry(principal).or_default();
+        timestamps.retain(|t| now.saturating_sub(*t) < BULK_WINDOW_NS);
+        timestamps.push(now);
+        timestamps.len()
+    });
+
+    detect(recent, now)
+        .into_iter()
+        .filter(|kind| *kind != AnomalyKind::OutOfHours || !has_recent_open_alert(principal, kind, now))
+        .map(|kind| raise(principal, kind, dataset_id, recent, now))
+        .collect()
+}
+
+// Out-of-hours alerts are raised at most once an hour per principal
+fn has_recent_open_alert(principal: Principal, kind: &AnomalyKind, now: u64) -> bool {
+    ALERTS.with(|alerts| {
+        alerts.borrow().iter().rev().any(|a| {
+            a.principal == principal && a.kind == *kind && a.status == AlertStatus::Open
+                && now.saturating_sub(a.timestamp) < NANOS_PER_HOUR
+        })
+    })
+}
+
+fn raise(principal: Principal, kind: AnomalyKind, dataset_id: &str, recent: usize, now: u64) -> AnomalyAlert {
+    let details = match kind {
+        AnomalyKind::BulkDecryption => format!(
+            "{} decryptions within {} minutes", recent, BULK_WINDOW_NS / (60 * NANOS_PER_SECOND)
+        ),
+        AnomalyKind::OutOfHours => format!(
+            "Access at {:02}:00 UTC, outside {:02}:00-{:02}:00",
+            (now / NANOS_PER_HOUR) % 24, WORKING_HOURS_UTC.0, WORKING_HOURS_UTC.1
+        ),
+    };
+
+    let suspended = AUTO_SUSPEND.with(|auto| auto.get());
+    if suspended {
+        SUSPENDED.with(|s| s.borrow_mut().insert(principal));
+    }
+
+    ALERTS.with(|alerts| {
+        let mut alerts = alerts.borrow_mut();
+        let alert = AnomalyAlert {
+            id: alerts.len() as u64,
+            principal,
+            kind,
+            dataset_id: dataset_id.to_string(),
+            details,
+            timestamp: now,
+            status: AlertStatus::Open,
+            suspended,
+        };
+        alerts.push(alert.clone());
+        alert
+    })
+}
+
+pub fn set_auto_suspend(enabled: bool) {
+    AUTO_SUSPEND.with(|auto| auto.set(enabled));
+}
+
+pub fn ensure_not_suspended(principal: Principal) -> Result<(), String> {
+    if SUSPENDED.with(|s| s.borrow().contains(&principal)) {
+        return Err("Principal is suspended pending review of an access anomaly".to_string());
+    }
+    Ok(())
+}
+
+pub fn alerts(status: Option<AlertStatus>) -> Vec<AnomalyAlert> {
+    ALERTS.with(|alerts| {
+        alerts.borrow().iter()
+            .filter(|a| status.as_ref().is_none_or(|s| a.status == *s))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Close an alert; dismissing it also lifts a suspension it caused
+pub fn review(alert_id: u64, confirm: bool) -> Result<AnomalyAlert, String> {
+    let alert = ALERTS.with(|alerts| {
+        let mut alerts = alerts.borrow_mut();
+        let alert = alerts.get_mut(alert_id as usize)
+            .ok_or_else(|| format!("Alert {} not found", alert_id))?;
+        if alert.status != AlertStatus::Open {
+            return Err(format!("Alert {} was already reviewed", alert_id));
+        }
+        alert.status = if confirm { AlertStatus::Confirmed } else { AlertStatus::Dismissed };
+        Ok(alert.clone())
+    })?;
+
+    if !confirm {
+        SUSPENDED.with(|s| s.borrow_mut().remove(&alert.principal));
+    }
+    Ok(alert)
+}
+
+pub fn suspended_principals() -> Vec<Principal> {
+    SUSPENDED.with(|s| s.borrow().iter().cloned().collect())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&RECENT_ACCESS),
+        backup::cloned(&ALERTS),
+        backup::cloned(&SUSPENDED),
+        AUTO_SUSPEND.with(Cell::get),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (recent_access, alerts, suspended, auto_suspend) = backup::decode(bytes)?;
+    backup::replace(&RECENT_ACCESS, recent_access);
+    backup::replace(&ALERTS, alerts);
+    backup::replace(&SUSPENDED, suspended);
+    AUTO_SUSPEND.with(|cell| cell.set(auto_suspend));
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "anomaly_test.rs"]
+mod tests;
@@ -0,0 +1,44 @@
+use super::*;
+
+// 12:00 UTC on day 0
+const NOON: u64 = 12 * NANOS_PER_HOUR;
+
+#[test]
+fn bulk_alert_fires_once_when_threshold_is_crossed() {
+    assert!(detect(BULK_THRESHOLD, NOON).is_empty());
+    assert_eq!(detect(BULK_THRESHOLD + 1, NOON), vec![AnomalyKind::BulkDecryption]);
+    assert!(detect(BULK_THRESHOLD + 2, NOON).is_empty());
+}
+
+#[test]
+fn out_of_hours_access_is_flagged() {
+    assert_eq!(detect(1, 3 * NANOS_PER_HOUR), vec![AnomalyKind::OutOfHours]);
+    assert_eq!(detect(1, 20 * NANOS_PER_HOUR), vec![AnomalyKind::OutOfHours]);
+    assert!(detect(1, 6 * NANOS_PER_HOUR).is_empty());
+}
+
+#[test]
+fn bulk_access_with_auto_suspend_blocks_principal_until_dismissed() {
+    let principal = Principal::from_slice(&[1, 2, 3]);
+    set_auto_suspend(true);
+
+    let mut raised = Vec::new();
+    for i in 0..=BULK_THRESHOLD as u64 {
+        raised.extend(observe(principal, "dataset_1", NOON + i * NANOS_PER_SECOND));
+    }
+    assert_eq!(raised.len(), 1);
+    assert!(ensure_not_suspended(principal).is_err());
+
+    review(raised[0].id, false).unwrap();
+    assert!(ensure_not_suspended(principal).is_ok());
+    assert!(review(raised[0].id, true).is_err());
+}
+
+#[test]
+fn accesses_outside_window_do_not_count() {
+    let principal = Principal::from_slice(&[4, 5, 6]);
+    for i in 0..=BULK_THRESHOLD as u64 {
+        let alerts = observe(principal, "dataset_1", NOON + i * BULK_WINDOW_NS);
+        assert!(alerts.is_empty());
+    }
+}
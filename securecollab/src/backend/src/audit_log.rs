@@ -0,0 +1,430 @@
+//! Append-only, hash-chained audit log
+//!
+//! Security-relevant operations (uploads, votes, key derivations, decryptions,
+//! executions) are appended as entries whose hash covers the previous entry's
+//! hash. Rewriting or dropping any entry breaks every hash after it, which
+//! `verify_chain` detects.
+//!
+//! Entry hashes are also the leaves of an append-only Merkle tree whose root
+//! is certified, so a single event can be proven to be in the log without
+//! downloading the rest of it.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use sha2::{Sha256, Digest};
+use crate::backup;
+
+/// Hash the first entry chains to
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AuditEvent {
+    Upload,
+    Vote,
+    KeyDerivation,
+    Decryption,
+    Execution,
+    RoleChange,
+    Pause,
+    Backup,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AuditLogEntry {
+    pub seq: u64,
+    pub event: AuditEvent,
+    pub actor: Principal,
+    pub resource_id: String,
+    pub details: String,
+    pub timestamp: u64,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditLogEntry>,
+    pub next_cursor: Option<u64>,
+    pub head_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub entries_checked: u64,
+    pub first_invalid_seq: Option<u64>,
+    pub head_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AuditExportFormat {
+    Json,
+    Csv,
+}
+
+/// One page of exported records
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AuditExport {
+    pub format: AuditExportFormat,
+    pub content: String,
+    pub record_count: u32,
+    pub next_cursor: Option<u64>,
+}
+
+/// One sibling on the path from a leaf to the Merkle root
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MerkleStep {
+    pub sibling: String,
+    pub sibling_is_left: bool,
+}
+
+/// Proof that an entry is included in the log's Merkle tree of `tree_size` entries
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AuditInclusionProof {
+    pub entry: AuditLogEntry,
+    pub leaf_hash: String,
+    pub path: Vec<MerkleStep>,
+    pub tree_size: u64,
+    pub root: String,
+}
+
+thread_local! {
+    static AUDIT_LOG: RefCell<Vec<AuditLogEntry>> = const { RefCell::new(Vec::new()) };
+    // (subtree size, root) of the perfect subtrees covering the log, largest first
+    static MERKLE_PEAKS: RefCell<Vec<(u64, [u8; 32])>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Append an entry chained to the current head
+pub fn record(event: AuditEvent, actor: Principal, resource_id: &str, details: String) {
+    AUDIT_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        let prev_hash = log.last().map(|e| e.hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string());
+        let mut entry = AuditLogEntry {
+            seq: log.len() as u64,
+            event,
+            actor,
+            resource_id: resource_id.to_string(),
+            details,
+            timestamp: time(),
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry_hash(&entry);
+        MERKLE_PEAKS.with(|peaks| push_peak(&mut peaks.borrow_mut(), merkle_leaf(&entry.hash)));
+        log.push(entry);
+    });
+    crate::certification::certify(crate::certification::AUDIT_ROOT_KEY, current_root().as_bytes());
+}
+
+/// Hash of an entry's contents and its predecessor's hash
+pub fn entry_hash(entry: &AuditLogEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(entry.seq.to_be_bytes());
+    hasher.update(format!("{:?}", entry.event).as_bytes());
+    hasher.update(entry.actor.as_slice());
+    hasher.update((entry.resource_id.len() as u64).to_be_bytes());
+    hasher.update(entry.resource_id.as_bytes());
+    hasher.update((entry.details.len() as u64).to_be_bytes());
+    hasher.update(entry.details.as_bytes());
+    hasher.update(entry.timestamp.to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Check sequence numbers, links and hashes of a run of entries
+pub fn verify_entries(entries: &[AuditLogEntry]) -> ChainVerification {
+    let mut expected_prev = GENESIS_HASH.to_string();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.seq != i as u64 || entry.prev_hash != expected_prev || entry.hash != entry_hash(entry) {
+            return ChainVerification {
+                valid: false,
+                entries_checked: i as u64,
+                first_invalid_seq: Some(i as u64),
+                head_hash: expected_prev,
+            };
+        }
+        expected_prev = entry.hash.clone();
+    }
+
+    ChainVerification {
+        valid: true,
+        entries_checked: entries.len() as u64,
+        first_invalid_seq: None,
+        head_hash: expected_prev,
+    }
+}
+
+/// Verify the whole stored chain
+pub fn verify_chain() -> ChainVerification {
+    AUDIT_LOG.with(|log| verify_entries(&log.borrow()))
+}
+
+/// Read a page of entries starting at `cursor`
+pub fn page(cursor: Option<u64>, limit: Option<u32>) -> AuditLogPage {
+    let limit = limit
+        .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    let start = cursor.unwrap_or(0) as usize;
+
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        let entries: Vec<AuditLogEntry> = log.iter().skip(start).take(limit).cloned().collect();
+        let next_cursor = match entries.last() {
+            Some(last) if (last.seq as usize) + 1 < log.len() => Some(last.seq + 1),
+            _ => None,
+        };
+        AuditLogPage {
+            entries,
+            next_cursor,
+            head_hash: log.last().map(|e| e.hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string()),
+        }
+    })
+}
+
+// ----------------------------------------------------------------------------
+// Machine-readable export
+// ----------------------------------------------------------------------------
+
+/// Render entries as JSON (array of records) or CSV with a header row
+pub fn render_export(entries: &[AuditLogEntry], format: &AuditExportFormat) -> String {
+    match format {
+        AuditExportFormat::Json => {
+            let records: Vec<serde_json::Value> = entries.iter().map(|e| serde_json::json!({
+                "seq": e.seq,
+                "actor": e.actor.to_text(),
+                "action": action_name(&e.event),
+                "resource": e.resource_id,
+                "details": e.details,
+                "timestamp": e.timestamp,
+                "prev_hash": e.prev_hash,
+                "hash": e.hash,
+            })).collect();
+            serde_json::Value::Array(records).to_string()
+        }
+        AuditExportFormat::Csv => {
+            let mut csv = String::from("seq,actor,action,resource,details,timestamp,prev_hash,hash\n");
+            for e in entries {
+                let fields = [
+                    e.seq.to_string(),
+                    e.actor.to_text(),
+                    action_name(&e.event).to_string(),
+                    e.resource_id.clone(),
+                    e.details.clone(),
+                    e.timestamp.to_string(),
+                    e.prev_hash.clone(),
+                    e.hash.clone(),
+                ];
+                let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    }
+}
+
+/// Stable action names used in exports
+pub fn action_name(event: &AuditEvent) -> &'static str {
+    match event {
+        AuditEvent::Upload => "upload",
+        AuditEvent::Vote => "vote",
+        AuditEvent::KeyDerivation => "key_derivation",
+        AuditEvent::Decryption => "decryption",
+        AuditEvent::Execution => "execution",
+        AuditEvent::RoleChange => "role_change",
+        AuditEvent::Pause => "pause",
+        AuditEvent::Backup => "backup",
+    }
+}
+
+// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Export entries with `from_ts <= timestamp <= to_ts`, one page at a time
+pub fn export(from_ts: u64, to_ts: u64, format: AuditExportFormat, cursor: Option<u64>, limit: Option<u32>) -> AuditExport {
+    let limit = limit
+        .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    let start = cursor.unwrap_or(0) as usize;
+
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        let mut matching = log.iter()
+            .skip(start)
+            .filter(|e| e.timestamp >= from_ts && e.timestamp <= to_ts);
+
+        let entries: Vec<AuditLogEntry> = matching.by_ref().take(limit).cloned().collect();
+        let next_cursor = match (entries.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.seq + 1),
+            _ => None,
+        };
+
+        AuditExport {
+            content: render_export(&entries, &format),
+            format,
+            record_count: entries.len() as u32,
+            next_cursor,
+        }
+    })
+}
+
+// ----------------------------------------------------------------------------
+// Merkle tree over entry hashes (RFC 6962 layout)
+// ----------------------------------------------------------------------------
+
+/// Leaf hash of an entry: SHA-256(0x00 || entry hash)
+pub fn merkle_leaf(entry_hash: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(hex::decode(entry_hash).unwrap_or_else(|_| entry_hash.as_bytes().to_vec()));
+    hasher.finalize().into()
+}
+
+/// Interior node hash: SHA-256(0x01 || left || right)
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// Largest power of two strictly below n (n > 1)
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+/// Root of the tree over `leaves`
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            merkle_node(&merkle_root(&leaves[..k]), &merkle_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path for leaf `index`, ordered from the leaf upwards
+pub fn merkle_path(index: usize, leaves: &[[u8; 32]]) -> Vec<MerkleStep> {
+    if leaves.len() <= 1 {
+        return vec![];
+    }
+    let k = split_point(leaves.len());
+    if index < k {
+        let mut path = merkle_path(index, &leaves[..k]);
+        path.push(MerkleStep { sibling: hex::encode(merkle_root(&leaves[k..])), sibling_is_left: false });
+        path
+    } else {
+        let mut path = merkle_path(index - k, &leaves[k..]);
+        path.push(MerkleStep { sibling: hex::encode(merkle_root(&leaves[..k])), sibling_is_left: true });
+        path
+    }
+}
+
+/// Recompute the root from a proof and compare it with the claimed root
+pub fn verify_inclusion(proof: &AuditInclusionProof) -> bool {
+    if proof.entry.hash != entry_hash(&proof.entry) {
+        return false;
+    }
+    let leaf = merkle_leaf(&proof.entry.hash);
+    if hex::encode(leaf) != proof.leaf_hash {
+        return false;
+    }
+
+    let mut current = leaf;
+    for step in &proof.path {
+        let sibling: [u8; 32] = match hex::decode(&step.sibling).ok().and_then(|b| b.try_into().ok()) {
+            Some(sibling) => sibling,
+            None => return false,
+        };
+        current = if step.sibling_is_left {
+            merkle_node(&sibling, &current)
+        } else {
+            merkle_node(&current, &sibling)
+        };
+    }
+    hex::encode(current) == proof.root
+}
+
+// Fold the perfect-subtree peaks (largest first) into the tree root
+fn root_from_peaks(peaks: &[(u64, [u8; 32])]) -> [u8; 32] {
+    match peaks.split_last() {
+        None => merkle_root(&[]),
+        Some((last, rest)) => rest.iter().rev().fold(last.1, |acc, (_, peak)| merkle_node(peak, &acc)),
+    }
+}
+
+// Add a leaf to the peaks, merging equal-sized subtrees
+fn push_peak(peaks: &mut Vec<(u64, [u8; 32])>, leaf: [u8; 32]) {
+    peaks.push((1, leaf));
+    while peaks.len() >= 2 && peaks[peaks.len() - 1].0 == peaks[peaks.len() - 2].0 {
+        let (size, right) = peaks.pop().unwrap();
+        let (_, left) = peaks.pop().unwrap();
+        peaks.push((size * 2, merkle_node(&left, &right)));
+    }
+}
+
+/// Current Merkle root of the log
+pub fn current_root() -> String {
+    MERKLE_PEAKS.with(|peaks| hex::encode(root_from_peaks(&peaks.borrow())))
+}
+
+/// Inclusion proof for the entry with sequence number `seq`
+pub fn inclusion_proof(seq: u64) -> Result<AuditInclusionProof, String> {
+    AUDIT_LOG.with(|log| {
+        let log = log.borrow();
+        let entry = log.get(seq as usize)
+            .cloned()
+            .ok_or_else(|| format!("Audit event {} not found", seq))?;
+        let leaves: Vec<[u8; 32]> = log.iter().map(|e| merkle_leaf(&e.hash)).collect();
+
+        Ok(AuditInclusionProof {
+            leaf_hash: hex::encode(leaves[seq as usize]),
+            path: merkle_path(seq as usize, &leaves),
+            tree_size: leaves.len() as u64,
+            root: hex::encode(merkle_root(&leaves)),
+            entry,
+        })
+    })
+}
+
+// The Merkle peaks are rebuilt from the entries rather than kept in backups
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&AUDIT_LOG))
+}
+
+/// Restore a backed-up log, which must still verify
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let entries: Vec<AuditLogEntry> = backup::decode(bytes)?;
+    let verification = verify_entries(&entries);
+    if !verification.valid {
+        return Err(format!("The audit log is broken at entry {}", verification.first_invalid_seq.unwrap_or(0)));
+    }
+    let mut peaks = Vec::new();
+    for entry in &entries {
+        push_peak(&mut peaks, merkle_leaf(&entry.hash));
+    }
+    backup::replace(&AUDIT_LOG, entries);
+    backup::replace(&MERKLE_PEAKS, peaks);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "audit_log_test.rs"]
+mod tests;
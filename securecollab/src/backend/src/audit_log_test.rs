@@ -0,0 +1,112 @@
+use super::*;
+
+fn chain(len: u64) -> Vec<AuditLogEntry> {
+    let mut entries: Vec<AuditLogEntry> = Vec::new();
+    for seq in 0..len {
+        let mut entry = AuditLogEntry {
+            seq,
+            event: AuditEvent::Upload,
+            actor: Principal::anonymous(),
+            resource_id: format!("dataset_{}", seq),
+            details: "uploaded".to_string(),
+            timestamp: 1_000 + seq,
+            prev_hash: entries.last().map(|e| e.hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string()),
+            hash: String::new(),
+        };
+        entry.hash = entry_hash(&entry);
+        entries.push(entry);
+    }
+    entries
+}
+
+#[test]
+fn intact_chain_verifies() {
+    let entries = chain(5);
+    let result = verify_entries(&entries);
+    assert!(result.valid);
+    assert_eq!(result.entries_checked, 5);
+    assert_eq!(result.head_hash, entries[4].hash);
+}
+
+#[test]
+fn tampered_entry_is_detected() {
+    let mut entries = chain(5);
+    entries[2].details = "rewritten".to_string();
+    let result = verify_entries(&entries);
+    assert!(!result.valid);
+    assert_eq!(result.first_invalid_seq, Some(2));
+}
+
+#[test]
+fn dropped_entry_is_detected() {
+    let mut entries = chain(5);
+    entries.remove(1);
+    assert_eq!(verify_entries(&entries).first_invalid_seq, Some(1));
+}
+
+fn leaves(n: usize) -> Vec<[u8; 32]> {
+    chain(n as u64).iter().map(|e| merkle_leaf(&e.hash)).collect()
+}
+
+#[test]
+fn inclusion_proofs_verify_for_every_leaf() {
+    for size in 1..=9 {
+        let entries = chain(size);
+        let leaves = leaves(size as usize);
+        let root = hex::encode(merkle_root(&leaves));
+        for (i, entry) in entries.iter().enumerate() {
+            let proof = AuditInclusionProof {
+                entry: entry.clone(),
+                leaf_hash: hex::encode(leaves[i]),
+                path: merkle_path(i, &leaves),
+                tree_size: size,
+                root: root.clone(),
+            };
+            assert!(verify_inclusion(&proof), "leaf {} of {}", i, size);
+        }
+    }
+}
+
+#[test]
+fn inclusion_proof_rejects_tampered_entry() {
+    let entries = chain(6);
+    let leaves = leaves(6);
+    let mut proof = AuditInclusionProof {
+        entry: entries[3].clone(),
+        leaf_hash: hex::encode(leaves[3]),
+        path: merkle_path(3, &leaves),
+        tree_size: 6,
+        root: hex::encode(merkle_root(&leaves)),
+    };
+    proof.entry.details = "approved by someone else".to_string();
+    assert!(!verify_inclusion(&proof));
+}
+
+#[test]
+fn incremental_peaks_match_full_root() {
+    let leaves = leaves(13);
+    let mut peaks = Vec::new();
+    for (i, leaf) in leaves.iter().enumerate() {
+        push_peak(&mut peaks, *leaf);
+        assert_eq!(root_from_peaks(&peaks), merkle_root(&leaves[..=i]));
+    }
+}
+
+#[test]
+fn csv_export_quotes_fields() {
+    let mut entries = chain(2);
+    entries[1].details = "vote \"yes\", 2/3".to_string();
+    let csv = render_export(&entries, &AuditExportFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("seq,actor,action"));
+    assert!(lines[2].contains("\"vote \"\"yes\"\", 2/3\""));
+}
+
+#[test]
+fn json_export_contains_hash_and_action() {
+    let entries = chain(1);
+    let json: serde_json::Value = serde_json::from_str(&render_export(&entries, &AuditExportFormat::Json)).unwrap();
+    assert_eq!(json[0]["action"], "upload");
+    assert_eq!(json[0]["hash"], entries[0].hash.as_str());
+}
@@ -0,0 +1,261 @@
+//! State backup and restore
+//!
+//! A snapshot is every module's state, each encoded on its own as a named
+//! section, so that a canister can be rebuilt after a disaster or moved to a
+//! new canister id. Snapshots are too large for one message, so they are
+//! exported and imported in chunks; a snapshot is fixed when its first chunk
+//! is read, and is only restored once every chunk arrived and its hash
+//! matches. Restoring replaces all state at once, or nothing if any section
+//! is rejected.
+//!
+//! The emergency pause and the deployment's admins belong to the canister
+//! rather than to its data, so they are not part of a snapshot. Snapshots
+//! hold encrypted blobs and key material alike and must be kept as secret
+//! as the canister itself.
+
+use candid::{CandidType, Deserialize};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::thread::LocalKey;
+
+/// Bumped whenever a section's layout changes
+pub const FORMAT_VERSION: u32 = 1;
+pub const MAX_CHUNK_BYTES: usize = 1_000_000;
+
+type Section = (&'static str, fn() -> Result<Vec<u8>, String>, fn(&[u8]) -> Result<(), String>);
+
+// In restore order; leaves certified or derived from other sections come last
+const SECTIONS: &[Section] = &[
+    ("core", crate::export_state, crate::import_state),
+    ("config", crate::config::export_state, crate::config::import_state),
+    ("organizations", crate::organizations::export_state, crate::organizations::import_state),
+    ("roles", crate::roles::export_state, crate::roles::import_state),
+    ("invitations", crate::invitations::export_state, crate::invitations::import_state),
+    ("service_principals", crate::service_principals::export_state, crate::service_principals::import_state),
+    ("identity_manager", crate::identity_manager::export_state, crate::identity_manager::import_state),
+    ("recovery", crate::recovery::export_state, crate::recovery::import_state),
+    ("vetkey_manager", crate::vetkey_manager::export_state, crate::vetkey_manager::import_state),
+    ("key_ceremony", crate::key_ceremony::export_state, crate::key_ceremony::import_state),
+    ("key_revocation", crate::key_revocation::export_state, crate::key_revocation::import_state),
+    ("key_rotation", crate::key_rotation::export_state, crate::key_rotation::import_state),
+    ("column_encryption", crate::column_encryption::export_state, crate::column_encryption::import_state),
+    ("decryption_window", crate::decryption_window::export_state, crate::decryption_window::import_state),
+    ("dataset_access", crate::dataset_access::export_state, crate::dataset_access::import_state),
+    ("storage_quota", crate::storage_quota::export_state, crate::storage_quota::import_state),
+    ("tabular", crate::tabular::export_state, crate::tabular::import_state),
+    ("summary", crate::summary::export_state, crate::summary::import_state),
+    ("tags", crate::tags::export_state, crate::tags::import_state),
+    ("cohorts", crate::cohorts::export_state, crate::cohorts::import_state),
+    ("lineage", crate::lineage::export_state, crate::lineage::import_state),
+    ("history", crate::history::export_state, crate::history::import_state),
+    ("privacy_budget", crate::privacy_budget::export_state, crate::privacy_budget::import_state),
+    ("differential_privacy", crate::differential_privacy::export_state, crate::differential_privacy::import_state),
+    ("disclosure_control", crate::disclosure_control::export_state, crate::disclosure_control::import_state),
+    ("privacy_proofs", crate::privacy_proofs::export_state, crate::privacy_proofs::import_state),
+    ("snark", crate::privacy_proofs::snark::export_state, crate::privacy_proofs::snark::import_state),
+    ("agent_registry", crate::agent_registry::export_state, crate::agent_registry::import_state),
+    ("mpc_engine", crate::mpc_engine::export_state, crate::mpc_engine::import_state),
+    ("checkpoint", crate::mpc_engine::checkpoint::export_state, crate::mpc_engine::checkpoint::import_state),
+    ("federated", crate::mpc_engine::federated::export_state, crate::mpc_engine::federated::import_state),
+    ("linkage", crate::mpc_engine::linkage::export_state, crate::mpc_engine::linkage::import_state),
+    ("paillier", crate::mpc_engine::paillier::export_state, crate::mpc_engine::paillier::import_state),
+    ("psi", crate::mpc_engine::psi::export_state, crate::mpc_engine::psi::import_state),
+    ("secure_sum", crate::mpc_engine::secure_sum::export_state, crate::mpc_engine::secure_sum::import_state),
+    ("scheduler", crate::scheduler::export_state, crate::scheduler::import_state),
+    ("progress", crate::progress::export_state, crate::progress::import_state),
+    ("maintenance", crate::maintenance::export_state, crate::maintenance::import_state),
+    ("cost_estimation", crate::cost_estimation::export_state, crate::cost_estimation::import_state),
+    ("bidding", crate::bidding::export_state, crate::bidding::import_state),
+    ("billing", crate::billing::export_state, crate::billing::import_state),
+    ("payments", crate::payments::export_state, crate::payments::import_state),
+    ("escrow", crate::payments::escrow::export_state, crate::payments::escrow::import_state),
+    ("llm_provider", crate::secure_llm::provider::export_state, crate::secure_llm::provider::import_state),
+    ("llm_http_outcall", crate::secure_llm::http_outcall::export_state, crate::secure_llm::http_outcall::import_state),
+    ("llm_injection", crate::secure_llm::injection::export_state, crate::secure_llm::injection::import_state),
+    ("llm_resilience", crate::secure_llm::resilience::export_state, crate::secure_llm::resilience::import_state),
+    ("llm_templates", crate::secure_llm::templates::export_state, crate::secure_llm::templates::import_state),
+    ("llm_usage", crate::secure_llm::usage::export_state, crate::secure_llm::usage::import_state),
+    ("anomaly", crate::anomaly::export_state, crate::anomaly::import_state),
+    ("rate_limit", crate::rate_limit::export_state, crate::rate_limit::import_state),
+    ("subscriptions", crate::subscriptions::export_state, crate::subscriptions::import_state),
+    ("events", crate::events::export_state, crate::events::import_state),
+    ("event_signing", crate::event_signing::export_state, crate::event_signing::import_state),
+    ("activity", crate::activity::export_state, crate::activity::import_state),
+    ("audit_log", crate::audit_log::export_state, crate::audit_log::import_state),
+];
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct Snapshot {
+    version: u32,
+    taken_at: u64,
+    sections: Vec<(String, Vec<u8>)>,
+}
+
+/// One chunk of an exported snapshot
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct StateChunk {
+    pub cursor: u64,
+    pub data: Vec<u8>,
+    /// Where the next chunk starts; `None` after the last one
+    pub next_cursor: Option<u64>,
+    pub total_size: u64,
+    /// SHA-256 of the whole snapshot, hex encoded
+    pub snapshot_hash: String,
+    pub taken_at: u64,
+}
+
+/// One chunk of a snapshot being imported, sent in order
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ImportChunk {
+    /// Offset of `data` in the snapshot; 0 starts a new import
+    pub offset: u64,
+    pub data: Vec<u8>,
+    pub total_size: u64,
+    pub snapshot_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ImportProgress {
+    pub received: u64,
+    pub total_size: u64,
+    /// The snapshot is complete and replaced the canister's state
+    pub restored: bool,
+}
+
+struct Staged {
+    bytes: Vec<u8>,
+    total_size: u64,
+    hash: String,
+    taken_at: u64,
+}
+
+thread_local! {
+    static EXPORT: RefCell<Option<Staged>> = const { RefCell::new(None) };
+    static IMPORT: RefCell<Option<Staged>> = const { RefCell::new(None) };
+}
+
+pub fn encode<T: CandidType>(value: &T) -> Result<Vec<u8>, String> {
+    candid::encode_one(value).map_err(|e| format!("Failed to encode state: {}", e))
+}
+
+pub fn decode<T: CandidType + DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    candid::decode_one(bytes).map_err(|e| format!("Failed to decode state: {}", e))
+}
+
+pub fn cloned<T: Clone>(store: &'static LocalKey<RefCell<T>>) -> T {
+    store.with(|store| store.borrow().clone())
+}
+
+pub fn replace<T>(store: &'static LocalKey<RefCell<T>>, value: T) {
+    store.with(|store| *store.borrow_mut() = value);
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn export_sections() -> Result<Vec<(String, Vec<u8>)>, String> {
+    SECTIONS.iter()
+        .map(|(name, export, _)| export().map(|bytes| (name.to_string(), bytes)).map_err(|e| format!("{}: {}", name, e)))
+        .collect()
+}
+
+fn import_sections(sections: &[(String, Vec<u8>)]) -> Result<(), String> {
+    for (name, bytes) in sections {
+        let (_, _, import) = SECTIONS.iter()
+            .find(|(known, _, _)| known == name)
+            .ok_or_else(|| format!("Unknown state section '{}'", name))?;
+        import(bytes).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+/// Encode the whole state as one snapshot
+pub fn snapshot(now: u64) -> Result<Vec<u8>, String> {
+    encode(&Snapshot { version: FORMAT_VERSION, taken_at: now, sections: export_sections()? })
+}
+
+/// Replace the whole state with a snapshot's; on any error the state is
+/// left as it was
+pub fn restore(bytes: &[u8]) -> Result<(), String> {
+    let snapshot: Snapshot = decode(bytes)?;
+    if snapshot.version != FORMAT_VERSION {
+        return Err(format!("Snapshot format {} is not supported (expected {})", snapshot.version, FORMAT_VERSION));
+    }
+    if let Some((name, _, _)) = SECTIONS.iter().find(|(name, _, _)| !snapshot.sections.iter().any(|(s, _)| s == name)) {
+        return Err(format!("The snapshot has no '{}' section", name));
+    }
+    let before = export_sections()?;
+    if let Err(e) = import_sections(&snapshot.sections) {
+        let _ = import_sections(&before);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Read the chunk at `cursor`; cursor 0 takes a new snapshot
+pub fn export_chunk(cursor: u64, now: u64) -> Result<StateChunk, String> {
+    if cursor == 0 {
+        let bytes = snapshot(now)?;
+        let staged = Staged { total_size: bytes.len() as u64, hash: sha256_hex(&bytes), bytes, taken_at: now };
+        EXPORT.with(|export| *export.borrow_mut() = Some(staged));
+    }
+    EXPORT.with(|export| {
+        let export = export.borrow();
+        let staged = export.as_ref().ok_or("No export in progress; start again from cursor 0")?;
+        if cursor >= staged.total_size && cursor > 0 {
+            return Err(format!("Cursor {} is past the end of the snapshot", cursor));
+        }
+        let start = cursor as usize;
+        let end = (start + MAX_CHUNK_BYTES).min(staged.bytes.len());
+        Ok(StateChunk {
+            cursor,
+            data: staged.bytes[start..end].to_vec(),
+            next_cursor: (end < staged.bytes.len()).then_some(end as u64),
+            total_size: staged.total_size,
+            snapshot_hash: staged.hash.clone(),
+            taken_at: staged.taken_at,
+        })
+    })
+}
+
+/// Add a chunk to the import; the last chunk restores the snapshot
+pub fn import_chunk(chunk: ImportChunk) -> Result<ImportProgress, String> {
+    if chunk.data.len() > MAX_CHUNK_BYTES {
+        return Err(format!("Chunks are limited to {} bytes", MAX_CHUNK_BYTES));
+    }
+    if chunk.offset == 0 {
+        let staged = Staged { bytes: Vec::new(), total_size: chunk.total_size, hash: chunk.snapshot_hash.clone(), taken_at: 0 };
+        IMPORT.with(|import| *import.borrow_mut() = Some(staged));
+    }
+    let complete = IMPORT.with(|import| {
+        let mut import = import.borrow_mut();
+        let staged = import.as_mut().ok_or("No import in progress; start again from offset 0")?;
+        if chunk.total_size != staged.total_size || chunk.snapshot_hash != staged.hash {
+            return Err("The chunk belongs to a different snapshot".to_string());
+        }
+        if chunk.offset != staged.bytes.len() as u64 {
+            return Err(format!("Expected the chunk at offset {}", staged.bytes.len()));
+        }
+        if staged.bytes.len() as u64 + chunk.data.len() as u64 > staged.total_size {
+            return Err("The chunk runs past the end of the snapshot".to_string());
+        }
+        staged.bytes.extend_from_slice(&chunk.data);
+        Ok(staged.bytes.len() as u64 == staged.total_size)
+    })?;
+    if !complete {
+        return Ok(ImportProgress { received: chunk.offset + chunk.data.len() as u64, total_size: chunk.total_size, restored: false });
+    }
+
+    let staged = IMPORT.with(|import| import.borrow_mut().take()).ok_or("No import in progress")?;
+    if sha256_hex(&staged.bytes) != staged.hash.to_lowercase() {
+        return Err("The snapshot does not match its hash; start again from offset 0".to_string());
+    }
+    restore(&staged.bytes)?;
+    Ok(ImportProgress { received: staged.total_size, total_size: staged.total_size, restored: true })
+}
+
+#[cfg(test)]
+#[path = "backup_test.rs"]
+mod tests;
@@ -0,0 +1,74 @@
+use super::*;
+use crate::{config, organizations};
+use crate::test_support::principal;
+
+fn import_in_pieces(chunk: &StateChunk, piece: usize) -> Result<ImportProgress, String> {
+    let mut progress = None;
+    for (i, data) in chunk.data.chunks(piece).enumerate() {
+        progress = Some(import_chunk(ImportChunk {
+            offset: (i * piece) as u64,
+            data: data.to_vec(),
+            total_size: chunk.total_size,
+            snapshot_hash: chunk.snapshot_hash.clone(),
+        })?);
+    }
+    progress.ok_or_else(|| "Empty snapshot".to_string())
+}
+
+#[test]
+fn snapshots_restore_in_chunks() {
+    organizations::create("org_a".to_string(), "Oncology Consortium".to_string(), principal(1), 10).unwrap();
+    let chunk = export_chunk(0, 100).unwrap();
+    assert_eq!((chunk.next_cursor, chunk.taken_at), (None, 100));
+    assert_eq!(chunk.data.len() as u64, chunk.total_size);
+    assert!(export_chunk(chunk.total_size, 100).is_err());
+
+    organizations::create("org_b".to_string(), "Cardiology".to_string(), principal(2), 20).unwrap();
+    config::update(config::ConfigUpdate { min_parties: Some(5), ..Default::default() }).unwrap();
+
+    let piece = chunk.data.len() / 3 + 1;
+    let first = import_chunk(ImportChunk {
+        offset: 0,
+        data: chunk.data[..piece].to_vec(),
+        total_size: chunk.total_size,
+        snapshot_hash: chunk.snapshot_hash.clone(),
+    }).unwrap();
+    assert_eq!((first.received, first.restored), (piece as u64, false));
+    assert!(organizations::exists("org_b"));
+    // Chunks arrive in order and belong to the same snapshot
+    let skipped = ImportChunk { offset: 2 * piece as u64, data: vec![0], total_size: chunk.total_size, snapshot_hash: chunk.snapshot_hash.clone() };
+    assert!(import_chunk(skipped).is_err());
+    let other = ImportChunk { offset: piece as u64, data: vec![0], total_size: chunk.total_size, snapshot_hash: "00".to_string() };
+    assert!(import_chunk(other).is_err());
+
+    let progress = import_in_pieces(&chunk, piece).unwrap();
+    assert!(progress.restored);
+    assert!(organizations::exists("org_a"));
+    assert!(!organizations::exists("org_b"));
+    assert_eq!(config::settings().min_parties, 3);
+}
+
+#[test]
+fn rejected_snapshots_change_nothing() {
+    organizations::create("org_a".to_string(), "Oncology Consortium".to_string(), principal(1), 10).unwrap();
+    let mut chunk = export_chunk(0, 100).unwrap();
+    organizations::create("org_b".to_string(), "Cardiology".to_string(), principal(2), 20).unwrap();
+
+    // A hash that does not match the bytes is caught once all chunks are in
+    let taken = chunk.data.clone();
+    let last = chunk.data.len() - 1;
+    chunk.data[last] ^= 0xff;
+    assert!(import_in_pieces(&chunk, chunk.data.len()).unwrap_err().contains("hash"));
+
+    // A section that fails to decode puts back the sections already restored
+    let mut snapshot: Snapshot = decode(&taken).unwrap();
+    let audit = snapshot.sections.iter_mut().find(|(name, _)| name == "audit_log").unwrap();
+    audit.1 = vec![1, 2, 3];
+    assert!(restore(&encode(&snapshot).unwrap()).is_err());
+    assert!(organizations::exists("org_a") && organizations::exists("org_b"));
+
+    snapshot.sections.retain(|(name, _)| name != "audit_log");
+    assert!(restore(&encode(&snapshot).unwrap()).unwrap_err().contains("audit_log"));
+    snapshot.version = FORMAT_VERSION + 1;
+    assert!(restore(&encode(&snapshot).unwrap()).is_err());
+}
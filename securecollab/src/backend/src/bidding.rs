@@ -0,0 +1,151 @@
+//! Competitive bidding for computation assignments
+//!
+//! A requester publishes a computation spec and registered agents bid a
+//! price and ETA through their owners. When the deadline passes the bids
+//! are ranked by price and reputation and the best one wins; the winning
+//! price replaces the agent's list price when the fee is escrowed.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::backup;
+
+/// Weight of price against reputation when ranking bids, in percent
+pub const PRICE_WEIGHT_PERCENT: u64 = 50;
+pub const MIN_BIDDING_WINDOW_SECS: u64 = 60;
+pub const MAX_BIDDING_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum BiddingStatus {
+    Open,
+    Awarded,
+    NoBids,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Bid {
+    pub agent_id: String,
+    pub bidder: Principal,
+    pub price: u64,
+    pub eta_seconds: u64,
+    pub reputation_score: u32,
+    pub submitted_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BiddingRound {
+    pub computation_id: String,
+    pub spec: String,
+    pub requester: Principal,
+    pub deadline: u64,
+    pub max_winners: u32,
+    pub status: BiddingStatus,
+    pub bids: Vec<Bid>,
+    pub winners: Vec<Bid>,
+}
+
+thread_local! {
+    static ROUNDS: RefCell<HashMap<String, BiddingRound>> = RefCell::new(HashMap::new());
+}
+
+/// Score out of 10_000: cheaper bids and more reputable agents score higher
+pub fn score(bid: &Bid, lowest_price: u64) -> u64 {
+    let price_score = lowest_price.saturating_mul(10_000).checked_div(bid.price).unwrap_or(10_000);
+    let reputation_score = (bid.reputation_score.min(100) as u64) * 100;
+    (price_score * PRICE_WEIGHT_PERCENT + reputation_score * (100 - PRICE_WEIGHT_PERCENT)) / 100
+}
+
+/// Bids from best to worst; ties go to the faster, then the earlier bid
+pub fn rank(bids: &[Bid]) -> Vec<Bid> {
+    let lowest_price = bids.iter().map(|b| b.price).min().unwrap_or(0);
+    let mut ranked = bids.to_vec();
+    ranked.sort_by(|a, b| {
+        score(b, lowest_price).cmp(&score(a, lowest_price))
+            .then(a.eta_seconds.cmp(&b.eta_seconds))
+            .then(a.submitted_at.cmp(&b.submitted_at))
+    });
+    ranked
+}
+
+pub fn open(computation_id: &str, spec: String, requester: Principal, deadline: u64, max_winners: u32) -> Result<BiddingRound, String> {
+    ROUNDS.with(|rounds| {
+        let mut rounds = rounds.borrow_mut();
+        if rounds.get(computation_id).is_some_and(|r| r.status == BiddingStatus::Open) {
+            return Err(format!("Bidding is already open for {}", computation_id));
+        }
+        let round = BiddingRound {
+            computation_id: computation_id.to_string(),
+            spec,
+            requester,
+            deadline,
+            max_winners: max_winners.max(1),
+            status: BiddingStatus::Open,
+            bids: vec![],
+            winners: vec![],
+        };
+        rounds.insert(computation_id.to_string(), round.clone());
+        Ok(round)
+    })
+}
+
+/// Add or replace an agent's bid while the round is open
+pub fn submit(computation_id: &str, bid: Bid) -> Result<(), String> {
+    ROUNDS.with(|rounds| {
+        let mut rounds = rounds.borrow_mut();
+        let round = rounds.get_mut(computation_id)
+            .ok_or_else(|| format!("No bidding round for {}", computation_id))?;
+        if round.status != BiddingStatus::Open || bid.submitted_at >= round.deadline {
+            return Err("Bidding round is closed".to_string());
+        }
+        round.bids.retain(|b| b.agent_id != bid.agent_id);
+        round.bids.push(bid);
+        Ok(())
+    })
+}
+
+/// Close a round whose deadline has passed and pick its winners
+pub fn close(computation_id: &str, now: u64) -> Result<BiddingRound, String> {
+    ROUNDS.with(|rounds| {
+        let mut rounds = rounds.borrow_mut();
+        let round = rounds.get_mut(computation_id)
+            .ok_or_else(|| format!("No bidding round for {}", computation_id))?;
+        if round.status != BiddingStatus::Open {
+            return Err("Bidding round is already closed".to_string());
+        }
+        if now < round.deadline {
+            return Err("Bidding round deadline has not passed".to_string());
+        }
+        round.winners = rank(&round.bids).into_iter().take(round.max_winners as usize).collect();
+        round.status = if round.winners.is_empty() { BiddingStatus::NoBids } else { BiddingStatus::Awarded };
+        Ok(round.clone())
+    })
+}
+
+pub fn get(computation_id: &str) -> Option<BiddingRound> {
+    ROUNDS.with(|rounds| rounds.borrow().get(computation_id).cloned())
+}
+
+pub fn open_rounds() -> Vec<BiddingRound> {
+    ROUNDS.with(|rounds| {
+        rounds.borrow().values().filter(|r| r.status == BiddingStatus::Open).cloned().collect()
+    })
+}
+
+/// Price an awarded agent bid for the computation, if any
+pub fn awarded_price(computation_id: &str, agent_id: &str) -> Option<u64> {
+    get(computation_id)?.winners.iter().find(|b| b.agent_id == agent_id).map(|b| b.price)
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&ROUNDS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let rounds = backup::decode(bytes)?;
+    backup::replace(&ROUNDS, rounds);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "bidding_test.rs"]
+mod tests;
@@ -0,0 +1,48 @@
+use super::*;
+
+fn bid(agent_id: &str, price: u64, eta_seconds: u64, reputation_score: u32) -> Bid {
+    Bid {
+        agent_id: agent_id.to_string(),
+        bidder: Principal::anonymous(),
+        price,
+        eta_seconds,
+        reputation_score,
+        submitted_at: 10,
+    }
+}
+
+#[test]
+fn cheaper_bid_beats_slightly_better_reputation() {
+    let ranked = rank(&[bid("pricey", 2_000, 60, 98), bid("cheap", 1_000, 60, 90)]);
+    assert_eq!(ranked[0].agent_id, "cheap");
+}
+
+#[test]
+fn ties_go_to_faster_bid() {
+    let ranked = rank(&[bid("slow", 1_000, 600, 90), bid("fast", 1_000, 60, 90)]);
+    assert_eq!(ranked[0].agent_id, "fast");
+}
+
+#[test]
+fn round_awards_after_deadline_only() {
+    let requester = Principal::anonymous();
+    open("mpc_bid_1", "spec".to_string(), requester, 100, 1).unwrap();
+    submit("mpc_bid_1", bid("a", 500, 60, 90)).unwrap();
+    submit("mpc_bid_1", bid("b", 400, 60, 90)).unwrap();
+    // Re-bidding replaces the agent's earlier bid
+    submit("mpc_bid_1", bid("a", 300, 60, 90)).unwrap();
+
+    assert!(close("mpc_bid_1", 99).is_err());
+    let round = close("mpc_bid_1", 100).unwrap();
+    assert_eq!(round.status, BiddingStatus::Awarded);
+    assert_eq!(round.bids.len(), 2);
+    assert_eq!(round.winners.len(), 1);
+    assert_eq!(awarded_price("mpc_bid_1", "a"), Some(300));
+    assert!(submit("mpc_bid_1", bid("c", 1, 60, 90)).is_err());
+}
+
+#[test]
+fn round_without_bids_closes_empty() {
+    open("mpc_bid_2", "spec".to_string(), Principal::anonymous(), 100, 2).unwrap();
+    assert_eq!(close("mpc_bid_2", 200).unwrap().status, BiddingStatus::NoBids);
+}
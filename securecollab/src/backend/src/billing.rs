@@ -0,0 +1,97 @@
+//! Per-requester billing ledger
+//!
+//! Every execution is charged to the principal that requested it, with the
+//! instructions it consumed and their cycle cost, so organizations can
+//! charge compute usage back to the teams that triggered it.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::cost_estimation::{instruction_cycles, LLM_CALL_CYCLES};
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum BillableKind {
+    Computation,
+    LlmQuery,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BillingEntry {
+    pub resource_id: String,
+    pub kind: BillableKind,
+    pub requester: Principal,
+    pub executed_by: Principal,
+    pub instructions: u64,
+    pub llm_calls: u32,
+    pub cycles: u128,
+    pub succeeded: bool,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BillingHistory {
+    pub requester: Principal,
+    pub entries: Vec<BillingEntry>,
+    pub total_instructions: u64,
+    pub total_cycles: u128,
+}
+
+thread_local! {
+    static LEDGER: RefCell<HashMap<Principal, Vec<BillingEntry>>> = RefCell::new(HashMap::new());
+}
+
+/// Cycles billed for an execution: instructions plus LLM call fees
+pub fn charge(instructions: u64, llm_calls: u32) -> u128 {
+    instruction_cycles(instructions) + llm_calls as u128 * LLM_CALL_CYCLES
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn record(
+    requester: Principal,
+    executed_by: Principal,
+    resource_id: &str,
+    kind: BillableKind,
+    instructions: u64,
+    llm_calls: u32,
+    succeeded: bool,
+    timestamp: u64,
+) {
+    let entry = BillingEntry {
+        resource_id: resource_id.to_string(),
+        kind,
+        requester,
+        executed_by,
+        instructions,
+        llm_calls,
+        cycles: charge(instructions, llm_calls),
+        succeeded,
+        timestamp,
+    };
+    LEDGER.with(|ledger| ledger.borrow_mut().entry(requester).or_default().push(entry));
+}
+
+/// Everything billed to a requester, oldest first, with running totals
+pub fn history(requester: Principal) -> BillingHistory {
+    let entries = LEDGER.with(|ledger| ledger.borrow().get(&requester).cloned().unwrap_or_default());
+    BillingHistory {
+        requester,
+        total_instructions: entries.iter().map(|e| e.instructions).sum(),
+        total_cycles: entries.iter().map(|e| e.cycles).sum(),
+        entries,
+    }
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&LEDGER))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let ledger = backup::decode(bytes)?;
+    backup::replace(&LEDGER, ledger);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "billing_test.rs"]
+mod tests;
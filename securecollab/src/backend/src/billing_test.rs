@@ -0,0 +1,22 @@
+use super::*;
+
+#[test]
+fn charge_combines_instructions_and_call_fees() {
+    assert_eq!(charge(1_000_000_000, 0), 400_000_000);
+    assert_eq!(charge(0, 2), 2 * LLM_CALL_CYCLES);
+}
+
+#[test]
+fn history_totals_entries_per_requester() {
+    let requester = Principal::from_slice(&[1]);
+    let other = Principal::from_slice(&[2]);
+    record(requester, other, "mpc_1", BillableKind::Computation, 1_000, 1, true, 10);
+    record(requester, requester, "query_1", BillableKind::LlmQuery, 2_000, 1, false, 20);
+    record(other, other, "mpc_2", BillableKind::Computation, 5_000, 1, true, 30);
+
+    let bill = history(requester);
+    assert_eq!(bill.entries.len(), 2);
+    assert_eq!(bill.total_instructions, 3_000);
+    assert_eq!(bill.total_cycles, charge(1_000, 1) + charge(2_000, 1));
+    assert!(history(Principal::from_slice(&[3])).entries.is_empty());
+}
@@ -0,0 +1,160 @@
+//! Capability taxonomy and structured agent search
+//!
+//! Agents declare capabilities from a fixed taxonomy grouped into domains,
+//! and requesters find agents with a filter over capabilities, domain,
+//! reputation, price and liveness instead of keyword matching.
+
+use candid::{CandidType, Deserialize};
+use crate::MPCAgent;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CapabilityDomain {
+    Healthcare,
+    Finance,
+    Privacy,
+    DataScience,
+    Security,
+    Legal,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    // Healthcare
+    HealthcareDataAnalysis,
+    ClinicalTrialAnalysis,
+    MedicalPrivacyCompliance,
+    EpidemiologicalModeling,
+    DrugDiscoveryInsights,
+    // Finance
+    RiskAssessment,
+    FraudDetection,
+    MarketAnalysis,
+    RegulatoryCompliance,
+    PortfolioOptimization,
+    // Privacy
+    GdprCompliance,
+    HipaaVerification,
+    DataAnonymization,
+    PrivacyImpactAssessment,
+    RegulatoryAudit,
+    // DataScience
+    StatisticalAnalysis,
+    MachineLearning,
+    PredictiveModeling,
+    DataVisualization,
+    PatternRecognition,
+    // Security
+    ThreatDetection,
+    VulnerabilityAssessment,
+    SecurityAudit,
+    IncidentResponse,
+    CryptographicAnalysis,
+    // Legal
+    ContractAnalysis,
+    RegulatoryInterpretation,
+    LegalRiskAssessment,
+    ComplianceMonitoring,
+    PolicyAnalysis,
+}
+
+impl Capability {
+    pub fn domain(&self) -> CapabilityDomain {
+        use Capability::*;
+        match self {
+            HealthcareDataAnalysis | ClinicalTrialAnalysis | MedicalPrivacyCompliance | EpidemiologicalModeling | DrugDiscoveryInsights => CapabilityDomain::Healthcare,
+            RiskAssessment | FraudDetection | MarketAnalysis | RegulatoryCompliance | PortfolioOptimization => CapabilityDomain::Finance,
+            GdprCompliance | HipaaVerification | DataAnonymization | PrivacyImpactAssessment | RegulatoryAudit => CapabilityDomain::Privacy,
+            StatisticalAnalysis | MachineLearning | PredictiveModeling | DataVisualization | PatternRecognition => CapabilityDomain::DataScience,
+            ThreatDetection | VulnerabilityAssessment | SecurityAudit | IncidentResponse | CryptographicAnalysis => CapabilityDomain::Security,
+            ContractAnalysis | RegulatoryInterpretation | LegalRiskAssessment | ComplianceMonitoring | PolicyAnalysis => CapabilityDomain::Legal,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct AgentFilter {
+    /// Every listed capability is required
+    pub capabilities: Vec<Capability>,
+    /// At least one capability in this domain is required
+    pub domain: Option<CapabilityDomain>,
+    pub min_reputation: Option<u32>,
+    pub max_price: Option<u64>,
+    /// Only agents with a recent heartbeat
+    pub available_only: bool,
+}
+
+pub fn matches(agent: &MPCAgent, filter: &AgentFilter, live: bool) -> bool {
+    filter.capabilities.iter().all(|c| agent.capabilities.contains(c))
+        && filter.domain.is_none_or(|domain| agent.capabilities.iter().any(|c| c.domain() == domain))
+        && filter.min_reputation.is_none_or(|min| agent.reputation_score >= min)
+        && filter.max_price.is_none_or(|max| agent.price_per_computation <= max)
+        && (!filter.available_only || live)
+}
+
+/// Best matches first: higher reputation, then lower price
+pub fn sort_results(agents: &mut [MPCAgent]) {
+    agents.sort_by(|a, b| {
+        b.reputation_score.cmp(&a.reputation_score)
+            .then(a.price_per_computation.cmp(&b.price_per_computation))
+            .then(a.id.cmp(&b.id))
+    });
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TeamSuggestion {
+    pub computation_type: CapabilityDomain,
+    pub required_capabilities: Vec<Capability>,
+    pub agent_ids: Vec<String>,
+    pub agents: Vec<MPCAgent>,
+    pub total_price: u64,
+}
+
+/// Capabilities a computation in a domain needs its agent team to cover
+pub fn required_capabilities(domain: CapabilityDomain) -> Vec<Capability> {
+    use Capability::*;
+    match domain {
+        CapabilityDomain::Healthcare => vec![HealthcareDataAnalysis, MedicalPrivacyCompliance, HipaaVerification, StatisticalAnalysis],
+        CapabilityDomain::Finance => vec![RiskAssessment, FraudDetection, RegulatoryCompliance, StatisticalAnalysis],
+        CapabilityDomain::Privacy => vec![GdprCompliance, PrivacyImpactAssessment, DataAnonymization],
+        CapabilityDomain::DataScience => vec![StatisticalAnalysis, MachineLearning, DataVisualization],
+        CapabilityDomain::Security => vec![ThreatDetection, SecurityAudit, CryptographicAnalysis],
+        CapabilityDomain::Legal => vec![ContractAnalysis, LegalRiskAssessment, RegulatoryInterpretation],
+    }
+}
+
+/// Cheapest set of candidates covering every required capability within
+/// `budget`; ties go to smaller teams. Exact over subsets of the requirement.
+pub fn cheapest_cover(candidates: &[MPCAgent], required: &[Capability], budget: u64) -> Option<Vec<MPCAgent>> {
+    let full = (1usize << required.len()) - 1;
+    // best[mask] = (price, team size, members) of the cheapest team covering `mask`
+    let mut best: Vec<Option<(u64, usize, Vec<usize>)>> = vec![None; full + 1];
+    best[0] = Some((0, 0, vec![]));
+
+    for (index, agent) in candidates.iter().enumerate() {
+        let covers = required.iter().enumerate()
+            .filter(|(_, c)| agent.capabilities.contains(c))
+            .fold(0usize, |mask, (bit, _)| mask | (1 << bit));
+        if covers == 0 {
+            continue;
+        }
+        // Walk masks downwards so each agent joins a team at most once
+        for mask in (0..=full).rev() {
+            let Some((price, size, ref members)) = best[mask] else { continue };
+            let next = mask | covers;
+            let candidate = (price.saturating_add(agent.price_per_computation), size + 1);
+            if best[next].as_ref().is_none_or(|(p, s, _)| candidate < (*p, *s)) {
+                let mut members = members.clone();
+                members.push(index);
+                best[next] = Some((candidate.0, candidate.1, members));
+            }
+        }
+    }
+
+    best[full].take()
+        .filter(|(price, _, _)| *price <= budget)
+        .map(|(_, _, members)| members.into_iter().map(|i| candidates[i].clone()).collect())
+}
+
+#[cfg(test)]
+#[path = "capabilities_test.rs"]
+mod tests;
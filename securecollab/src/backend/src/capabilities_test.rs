@@ -0,0 +1,74 @@
+use super::*;
+
+fn agent(capabilities: Vec<Capability>, reputation_score: u32, price_per_computation: u64) -> MPCAgent {
+    MPCAgent {
+        id: format!("agent_{}_{}", reputation_score, price_per_computation),
+        identity: "Test agent".to_string(),
+        capabilities,
+        reputation_score,
+        price_per_computation,
+        version: 1,
+        owner: None,
+    }
+}
+
+#[test]
+fn every_listed_capability_is_required() {
+    let a = agent(vec![Capability::FraudDetection, Capability::RiskAssessment], 90, 100);
+    let filter = AgentFilter {
+        capabilities: vec![Capability::FraudDetection, Capability::RiskAssessment],
+        ..Default::default()
+    };
+    assert!(matches(&a, &filter, false));
+
+    let filter = AgentFilter { capabilities: vec![Capability::FraudDetection, Capability::ThreatDetection], ..Default::default() };
+    assert!(!matches(&a, &filter, false));
+}
+
+#[test]
+fn domain_reputation_price_and_liveness_filter() {
+    let a = agent(vec![Capability::ClinicalTrialAnalysis], 80, 500);
+    let filter = AgentFilter {
+        domain: Some(CapabilityDomain::Healthcare),
+        min_reputation: Some(80),
+        max_price: Some(500),
+        available_only: true,
+        ..Default::default()
+    };
+    assert!(matches(&a, &filter, true));
+    assert!(!matches(&a, &filter, false));
+    assert!(!matches(&a, &AgentFilter { domain: Some(CapabilityDomain::Legal), ..Default::default() }, true));
+    assert!(!matches(&a, &AgentFilter { min_reputation: Some(81), ..Default::default() }, true));
+    assert!(!matches(&a, &AgentFilter { max_price: Some(499), ..Default::default() }, true));
+}
+
+#[test]
+fn results_rank_by_reputation_then_price() {
+    let mut agents = vec![
+        agent(vec![], 80, 100),
+        agent(vec![], 95, 900),
+        agent(vec![], 95, 300),
+    ];
+    sort_results(&mut agents);
+    let order: Vec<(u32, u64)> = agents.iter().map(|a| (a.reputation_score, a.price_per_computation)).collect();
+    assert_eq!(order, vec![(95, 300), (95, 900), (80, 100)]);
+}
+
+#[test]
+fn cheapest_cover_prefers_lower_total_price() {
+    use Capability::*;
+    let generalist = agent(vec![StatisticalAnalysis, MachineLearning, DataVisualization], 90, 1_000);
+    let stats = agent(vec![StatisticalAnalysis, MachineLearning], 80, 300);
+    let viz = agent(vec![DataVisualization], 70, 200);
+    let candidates = vec![generalist, stats, viz];
+    let required = required_capabilities(CapabilityDomain::DataScience);
+
+    let team = cheapest_cover(&candidates, &required, 10_000).unwrap();
+    let price: u64 = team.iter().map(|a| a.price_per_computation).sum();
+    assert_eq!(price, 500);
+    assert_eq!(team.len(), 2);
+
+    // Over budget or uncoverable requirements have no team
+    assert!(cheapest_cover(&candidates, &required, 499).is_none());
+    assert!(cheapest_cover(&candidates, &[ThreatDetection], 10_000).is_none());
+}
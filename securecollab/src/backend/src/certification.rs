@@ -0,0 +1,87 @@
+//! Certified query responses
+//!
+//! Query calls are answered by a single replica, so a malicious node could
+//! forge their responses. Responses that matter for trust (computation
+//! requests, privacy audits) are hashed into a Merkle tree whose root is set as
+//! the canister's certified data on every update. Queries then return the IC
+//! data certificate plus a witness for their leaf, which clients verify
+//! against the IC root key.
+
+use candid::CandidType;
+use ic_certified_map::{labeled, labeled_hash, leaf_hash, AsHashTree, Hash, RbTree};
+use serde::Serialize;
+use std::cell::RefCell;
+
+/// Label under which the tree root is certified
+const CERTIFIED_LABEL: &[u8] = b"securecollab";
+
+thread_local! {
+    static TREE: RefCell<RbTree<Vec<u8>, Hash>> = RefCell::new(RbTree::default());
+}
+
+/// Leaf key holding the audit log's current Merkle root
+pub const AUDIT_ROOT_KEY: &str = "audit_log/root";
+
+/// Leaf key for a computation request
+pub fn computation_key(request_id: &str) -> String {
+    format!("computation/{}", request_id)
+}
+
+/// Leaf key for a computation's privacy audit report
+pub fn audit_key(computation_id: &str) -> String {
+    format!("audit/{}", computation_id)
+}
+
+/// Insert or replace a leaf without touching the certified data
+pub fn insert_leaf(key: &str, value: &[u8]) {
+    TREE.with(|tree| tree.borrow_mut().insert(key.as_bytes().to_vec(), leaf_hash(value)));
+}
+
+/// Hash that is set as certified data for the current tree
+pub fn certified_root() -> Hash {
+    TREE.with(|tree| labeled_hash(CERTIFIED_LABEL, &tree.borrow().root_hash()))
+}
+
+/// Certify `value` under `key`; must be called from an update call
+pub fn certify(key: &str, value: &[u8]) {
+    insert_leaf(key, value);
+    ic_cdk::api::set_certified_data(&certified_root());
+}
+
+/// Drop a leaf and re-certify; must be called from an update call
+pub fn remove(key: &str) {
+    TREE.with(|tree| tree.borrow_mut().delete(key.as_bytes()));
+    ic_cdk::api::set_certified_data(&certified_root());
+}
+
+/// Drop every leaf, before certifying restored state again
+pub fn clear() {
+    TREE.with(|tree| *tree.borrow_mut() = RbTree::default());
+}
+
+/// Candid encoding of a certified value; clients hash the same encoding to verify
+pub fn candid_bytes<T: CandidType>(value: &T) -> Result<Vec<u8>, String> {
+    candid::encode_one(value).map_err(|e| format!("Failed to encode certified value: {}", e))
+}
+
+/// CBOR-encoded witness proving the leaf at `key` (or its absence)
+pub fn witness(key: &str) -> Result<Vec<u8>, String> {
+    TREE.with(|tree| {
+        let tree = tree.borrow();
+        let witness = labeled(CERTIFIED_LABEL, tree.witness(key.as_bytes()));
+
+        let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+        serializer.self_describe().map_err(|e| e.to_string())?;
+        witness.serialize(&mut serializer).map_err(|e| format!("Failed to encode witness: {}", e))?;
+        Ok(serializer.into_inner())
+    })
+}
+
+/// Data certificate for the current state; only available in query calls
+pub fn certificate() -> Option<Vec<u8>> {
+    ic_cdk::api::data_certificate()
+}
+
+#[cfg(test)]
+#[path = "certification_test.rs"]
+mod tests;
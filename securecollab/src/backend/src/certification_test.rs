@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn witness_reconstructs_to_certified_root() {
+    insert_leaf(&computation_key("req_1"), b"first");
+    insert_leaf(&computation_key("req_2"), b"second");
+
+    let reconstructed = TREE.with(|tree| {
+        labeled(CERTIFIED_LABEL, tree.borrow().witness(computation_key("req_1").as_bytes())).reconstruct()
+    });
+    assert_eq!(reconstructed, certified_root());
+}
+
+#[test]
+fn replacing_a_leaf_changes_the_root() {
+    insert_leaf(&audit_key("comp_1"), b"report v1");
+    let before = certified_root();
+    insert_leaf(&audit_key("comp_1"), b"report v2");
+    assert_ne!(before, certified_root());
+}
+
+#[test]
+fn witness_is_self_describing_cbor() {
+    insert_leaf(&audit_key("comp_2"), b"report");
+    let witness = witness(&audit_key("comp_2")).unwrap();
+    assert_eq!(&witness[..3], &[0xd9, 0xd9, 0xf7]);
+}
@@ -0,0 +1,276 @@
+//! Cohort definitions and comparisons
+//!
+//! A cohort is a filter over dataset columns: conditions such as `age>60` or
+//! `treatment=Drug_X` joined by `AND`, with `=`, `!=`, `<`, `<=`, `>` and
+//! `>=`. Values may be quoted with `'` or `"`. Numbers and booleans compare
+//! as values, dates in calendar order, and other columns only by equality.
+//! Conditions are checked against the schema of the datasets a comparison
+//! runs over, so one cohort can be reused across datasets.
+//!
+//! A comparison releases each cohort's size and outcome statistics across
+//! all the datasets through the differential privacy release of analytics,
+//! with epsilon split evenly across cohorts since they may overlap. Cohorts
+//! with fewer records than the minimum size are suppressed entirely.
+
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::analytics::{self, is_date, numeric_value, ColumnSpec, ColumnType};
+use crate::differential_privacy::{self, DpConfig, NoiseMechanism, NoiseSource, ReleasedValue};
+use crate::tabular;
+use crate::backup;
+
+pub const MAX_CONDITIONS: usize = 10;
+pub const MAX_COHORTS_PER_COMPARISON: usize = 10;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub column: String,
+    pub operator: Operator,
+    pub value: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Cohort {
+    pub id: String,
+    pub name: String,
+    pub owner: Principal,
+    pub filter: String,
+    pub conditions: Vec<Condition>,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CohortComparisonRequest {
+    pub dataset_ids: Vec<String>,
+    /// The first cohort is the reference the others are compared with
+    pub cohort_ids: Vec<String>,
+    pub outcome: String,
+    /// Raised to the disclosure policy's minimum cohort size if lower
+    pub min_cohort_size: Option<u64>,
+    pub epsilon: Option<f64>,
+    /// Approved computation to run under when other parties' datasets are read
+    pub computation_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CohortResult {
+    pub cohort_id: String,
+    pub name: String,
+    /// Below the minimum size; nothing else is released
+    pub suppressed: bool,
+    pub records: Option<u64>,
+    pub outcome: Option<ReleasedValue>,
+    /// Mean outcome minus the reference cohort's, for numeric outcomes
+    pub mean_difference: Option<f64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CohortComparison {
+    pub outcome: String,
+    pub cohorts: Vec<CohortResult>,
+    pub min_cohort_size: u64,
+    pub epsilon: f64,
+}
+
+thread_local! {
+    static COHORTS: RefCell<HashMap<String, Cohort>> = RefCell::new(HashMap::new());
+}
+
+fn parse_condition(text: &str) -> Result<Condition, String> {
+    const OPERATORS: [(&str, Operator); 6] = [
+        (">=", Operator::GreaterOrEqual),
+        ("<=", Operator::LessOrEqual),
+        ("!=", Operator::NotEqual),
+        ("=", Operator::Equal),
+        (">", Operator::Greater),
+        ("<", Operator::Less),
+    ];
+    let (position, symbol, operator) = OPERATORS.iter()
+        .filter_map(|(symbol, operator)| text.find(symbol).map(|p| (p, *symbol, *operator)))
+        .min_by_key(|(position, symbol, _)| (*position, std::cmp::Reverse(symbol.len())))
+        .ok_or_else(|| format!("Condition '{}' has no comparison operator", text.trim()))?;
+    let column = text[..position].trim();
+    let value = text[position + symbol.len()..].trim();
+    let value = ['\'', '"'].iter()
+        .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+        .unwrap_or(value);
+    if column.is_empty() || value.is_empty() {
+        return Err(format!("Condition '{}' needs a column and a value", text.trim()));
+    }
+    Ok(Condition { column: column.to_string(), operator, value: value.to_string() })
+}
+
+/// Conditions of a filter, joined by `AND` in any case
+pub fn parse_filter(filter: &str) -> Result<Vec<Condition>, String> {
+    let mut parts: Vec<Vec<&str>> = vec![Vec::new()];
+    for word in filter.split_whitespace() {
+        if word.eq_ignore_ascii_case("AND") {
+            parts.push(Vec::new());
+        } else if let Some(part) = parts.last_mut() {
+            part.push(word);
+        }
+    }
+    let conditions = parts.iter()
+        .map(|part| parse_condition(&part.join(" ")))
+        .collect::<Result<Vec<_>, String>>()?;
+    if conditions.len() > MAX_CONDITIONS {
+        return Err(format!("A cohort can have at most {} conditions", MAX_CONDITIONS));
+    }
+    Ok(conditions)
+}
+
+/// How a condition's value compares in its column, once checked against the schema
+enum Comparand {
+    Number(f64),
+    Date(String),
+    Text(String),
+}
+
+fn comparand(condition: &Condition, spec: &ColumnSpec) -> Result<Comparand, String> {
+    let ordered = !matches!(condition.operator, Operator::Equal | Operator::NotEqual);
+    match spec.column_type {
+        ColumnType::Number { .. } | ColumnType::Boolean => numeric_value(&spec.column_type, &condition.value)
+            .map(Comparand::Number)
+            .ok_or_else(|| format!("'{}' is not a value of column '{}'", condition.value, spec.name)),
+        ColumnType::Date if is_date(&condition.value) => Ok(Comparand::Date(condition.value.clone())),
+        ColumnType::Date => Err(format!("'{}' is not a date written YYYY-MM-DD", condition.value)),
+        _ if ordered => Err(format!("Column '{}' can only be compared with = or !=", spec.name)),
+        _ => Ok(Comparand::Text(condition.value.clone())),
+    }
+}
+
+fn holds(operator: Operator, ordering: Option<std::cmp::Ordering>) -> bool {
+    use std::cmp::Ordering::*;
+    match (operator, ordering) {
+        (_, None) => false,
+        (Operator::Equal, Some(o)) => o == Equal,
+        (Operator::NotEqual, Some(o)) => o != Equal,
+        (Operator::Less, Some(o)) => o == Less,
+        (Operator::LessOrEqual, Some(o)) => o != Greater,
+        (Operator::Greater, Some(o)) => o == Greater,
+        (Operator::GreaterOrEqual, Some(o)) => o != Less,
+    }
+}
+
+/// Rows of each dataset matching every condition, as CSV with the header
+pub fn filter(schema: &[ColumnSpec], conditions: &[Condition], datasets: &[String]) -> Result<Vec<String>, String> {
+    let checks = conditions.iter().map(|condition| {
+        let spec = schema.iter().find(|spec| spec.name == condition.column)
+            .ok_or_else(|| format!("Column '{}' is not in the schema", condition.column))?;
+        Ok((condition, spec, comparand(condition, spec)?))
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    datasets.iter().map(|data| {
+        let table = tabular::parse(data.as_bytes())?;
+        let positions = checks.iter()
+            .map(|(condition, _, _)| table.header.iter().position(|h| *h == condition.column)
+                .ok_or_else(|| format!("Column '{}' not found", condition.column)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let rows = table.rows.iter().filter(|row| {
+            checks.iter().zip(&positions).all(|((condition, spec, comparand), &p)| {
+                let Some(value) = row.get(p) else { return false };
+                let ordering = match comparand {
+                    Comparand::Number(target) => numeric_value(&spec.column_type, value).and_then(|v| v.partial_cmp(target)),
+                    Comparand::Date(target) => is_date(value).then(|| value.as_str().cmp(target.as_str())),
+                    Comparand::Text(target) => Some(value.cmp(target)),
+                };
+                holds(condition.operator, ordering)
+            })
+        });
+        Ok(tabular::write(std::iter::once(&table.header).chain(rows).map(|row| row.to_vec())))
+    }).collect()
+}
+
+/// Compare `outcome` across `cohorts` over the decrypted `datasets`
+pub fn compare(
+    schema: &[ColumnSpec],
+    cohorts: &[Cohort],
+    outcome: &str,
+    datasets: &[String],
+    min_cohort_size: u64,
+    epsilon: f64,
+    source: &mut NoiseSource,
+) -> Result<CohortComparison, String> {
+    if cohorts.is_empty() || cohorts.len() > MAX_COHORTS_PER_COMPARISON {
+        return Err(format!("Compare between 1 and {} cohorts", MAX_COHORTS_PER_COMPARISON));
+    }
+    let outcome_spec = schema.iter().find(|spec| spec.name == outcome)
+        .ok_or_else(|| format!("Column '{}' is not in the schema", outcome))?;
+    if !outcome_spec.released() {
+        return Err(format!("Outcome '{}' must be a bounded number, a category or a boolean", outcome));
+    }
+    let config = DpConfig { epsilon: epsilon / cohorts.len() as f64, delta: 0.0, mechanism: NoiseMechanism::Laplace };
+
+    let mut results: Vec<CohortResult> = Vec::new();
+    for cohort in cohorts {
+        let members = filter(schema, &cohort.conditions, datasets)?;
+        let combined = analytics::combine(std::slice::from_ref(outcome_spec), &members)?;
+        let suppressed = combined.analysis.total_records < min_cohort_size;
+        let released = (!suppressed).then(|| differential_privacy::privatize(&combined, &config, source));
+        results.push(CohortResult {
+            cohort_id: cohort.id.clone(),
+            name: cohort.name.clone(),
+            suppressed,
+            records: released.as_ref().map(|r| r.total_records),
+            outcome: released.and_then(|r| r.statistics.into_iter().next()).map(|s| s.value),
+            mean_difference: None,
+        });
+    }
+
+    let mean = |result: &CohortResult| match result.outcome {
+        Some(ReleasedValue::Mean(mean)) => Some(mean),
+        _ => None,
+    };
+    if let Some(reference) = mean(&results[0]) {
+        for result in &mut results[1..] {
+            result.mean_difference = mean(result).map(|m| m - reference);
+        }
+    }
+    Ok(CohortComparison { outcome: outcome.to_string(), cohorts: results, min_cohort_size, epsilon })
+}
+
+pub fn define(id: String, owner: Principal, name: String, filter: String, now: u64) -> Result<Cohort, String> {
+    if name.trim().is_empty() {
+        return Err("Cohorts need a name".to_string());
+    }
+    let cohort = Cohort { id, name, owner, conditions: parse_filter(&filter)?, filter, created_at: now };
+    COHORTS.with(|c| c.borrow_mut().insert(cohort.id.clone(), cohort.clone()));
+    Ok(cohort)
+}
+
+pub fn get(id: &str) -> Result<Cohort, String> {
+    COHORTS.with(|c| c.borrow().get(id).cloned()).ok_or_else(|| format!("Cohort {} not found", id))
+}
+
+/// Cohorts `owner` defined, oldest first
+pub fn list(owner: Principal) -> Vec<Cohort> {
+    let mut cohorts: Vec<Cohort> = COHORTS.with(|c| c.borrow().values().filter(|c| c.owner == owner).cloned().collect());
+    cohorts.sort_by_key(|c| c.created_at);
+    cohorts
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&COHORTS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let cohorts = backup::decode(bytes)?;
+    backup::replace(&COHORTS, cohorts);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "cohorts_test.rs"]
+mod tests;
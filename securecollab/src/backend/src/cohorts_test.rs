@@ -0,0 +1,68 @@
+use super::*;
+use crate::analytics::parse_schema;
+
+const SCHEMA: &str = "age:number[0..100],treatment:category,visit:date,recovered:boolean,score:number[0..10]";
+
+fn dataset(rows: usize, offset: usize) -> String {
+    let mut lines = vec!["age,treatment,visit,recovered,score".to_string()];
+    for i in offset..offset + rows {
+        let treatment = if i % 2 == 0 { "Drug_X" } else { "Placebo" };
+        let score = if treatment == "Drug_X" { 8 } else { 4 };
+        lines.push(format!("{},{},2024-01-{:02},{},{}", 40 + i % 40, treatment, 1 + i % 28, i % 3 != 0, score));
+    }
+    lines.join("\n")
+}
+
+fn cohort(id: &str, filter: &str) -> Cohort {
+    define(id.to_string(), Principal::anonymous(), id.to_string(), filter.to_string(), 0).unwrap()
+}
+
+#[test]
+fn parses_filters() {
+    let conditions = parse_filter("age>=60 and treatment = 'Drug X' AND score!=3").unwrap();
+    assert_eq!(conditions, vec![
+        Condition { column: "age".to_string(), operator: Operator::GreaterOrEqual, value: "60".to_string() },
+        Condition { column: "treatment".to_string(), operator: Operator::Equal, value: "Drug X".to_string() },
+        Condition { column: "score".to_string(), operator: Operator::NotEqual, value: "3".to_string() },
+    ]);
+    assert!(parse_filter("age 60").is_err());
+    assert!(parse_filter("age>").is_err());
+    assert!(parse_filter("age>60 AND").is_err());
+}
+
+#[test]
+fn compares_an_outcome_between_cohorts_across_datasets() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let cohorts = [cohort("placebo", "treatment=Placebo"), cohort("treated", "treatment=Drug_X AND age>=40")];
+    let datasets = [dataset(200, 0), dataset(200, 200)];
+    let comparison = compare(&schema, &cohorts, "score", &datasets, 5, 1e6, &mut source).unwrap();
+    assert_eq!(comparison.cohorts[0].records, Some(200));
+    assert!(matches!(comparison.cohorts[1].outcome, Some(ReleasedValue::Mean(m)) if (m - 8.0).abs() < 0.01));
+    assert!((comparison.cohorts[1].mean_difference.unwrap() - 4.0).abs() < 0.01);
+    assert_eq!(comparison.cohorts[0].mean_difference, None);
+}
+
+#[test]
+fn suppresses_small_cohorts() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let cohorts = [cohort("all", "age>=0"), cohort("early", "visit<2024-01-03 AND treatment=Drug_X")];
+    let comparison = compare(&schema, &cohorts, "recovered", &[dataset(56, 0)], 5, 1e6, &mut source).unwrap();
+    assert!(!comparison.cohorts[0].suppressed);
+    assert!(matches!(comparison.cohorts[0].outcome, Some(ReleasedValue::Counts(_))));
+    let early = &comparison.cohorts[1];
+    assert!(early.suppressed && early.records.is_none() && early.outcome.is_none());
+}
+
+#[test]
+fn rejects_conditions_the_schema_cannot_answer() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let data = [dataset(20, 0)];
+    for filter in ["weight>3", "treatment>Drug_X", "age=old", "visit<tomorrow"] {
+        assert!(compare(&schema, &[cohort("c", filter)], "score", &data, 1, 1.0, &mut source).is_err(), "{}", filter);
+    }
+    assert!(compare(&schema, &[cohort("c", "age>1")], "visit", &data, 1, 1.0, &mut source).is_err());
+    assert!(compare(&schema, &[], "score", &data, 1, 1.0, &mut source).is_err());
+}
@@ -0,0 +1,174 @@
+//! Column-level encryption layer
+//!
+//! Tabular uploads are additionally stored one column at a time, each column
+//! encrypted under its own vetKD derivation path. Computations that declare a
+//! column subset only ever decrypt those columns, and every decryption is
+//! logged per computation so data-minimization proofs can be checked against
+//! what was actually decrypted.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use sha2::{Sha256, Digest};
+use crate::tabular;
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EncryptedColumn {
+    pub name: String,
+    pub ciphertext: Vec<u8>,
+    pub plaintext_digest: String,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct DecryptedColumns {
+    pub dataset_id: String,
+    pub columns: Vec<String>,
+    pub column_digests: Vec<String>,
+    pub decrypted_at: u64,
+}
+
+thread_local! {
+    static COLUMN_STORE: RefCell<HashMap<String, Vec<EncryptedColumn>>> = RefCell::new(HashMap::new());
+    static DECRYPTION_LOG: RefCell<HashMap<String, Vec<DecryptedColumns>>> = RefCell::new(HashMap::new());
+}
+
+/// Split a CSV payload into (column name, serialized column values) pairs.
+/// Returns an empty list for payloads that are not UTF-8 tabular data.
+pub fn split_columns(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let Ok(table) = tabular::parse(data) else {
+        return vec![];
+    };
+
+    table.header.into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, tabular::write(table.rows.iter().map(|row| vec![&row[i]])).into_bytes()))
+        .collect()
+}
+
+/// Rebuild a CSV payload from decrypted (column name, values) pairs
+pub fn join_columns(columns: &[(String, Vec<u8>)]) -> String {
+    let values: Vec<Vec<String>> = columns.iter().map(|(_, values)| tabular::column(values)).collect();
+    let row_count = values.iter().map(|v| v.len()).max().unwrap_or(0);
+
+    let mut records = vec![columns.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>()];
+    records.extend((0..row_count).map(|row| values.iter().map(|v| v.get(row).map_or("", String::as_str)).collect()));
+    tabular::write(records)
+}
+
+/// Derivation path for a single column of a dataset
+pub fn column_derivation_path(party_name: &str, dataset_name: &str, column: &str) -> Vec<u8> {
+    format!("data_{}_{}_col_{}", party_name, dataset_name, column).into_bytes()
+}
+
+pub fn digest(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+pub fn store_columns(dataset_id: &str, columns: Vec<EncryptedColumn>) {
+    COLUMN_STORE.with(|store| {
+        store.borrow_mut().insert(dataset_id.to_string(), columns);
+    });
+}
+
+pub fn remove_columns(dataset_id: &str) {
+    COLUMN_STORE.with(|store| {
+        store.borrow_mut().remove(dataset_id);
+    });
+}
+
+/// Bytes held by a dataset's encrypted column copies
+pub fn stored_bytes(dataset_id: &str) -> u64 {
+    COLUMN_STORE.with(|store| {
+        store.borrow()
+            .get(dataset_id)
+            .map_or(0, |columns| columns.iter().map(|c| c.ciphertext.len() as u64).sum())
+    })
+}
+
+/// Names of the columns a dataset has encrypted copies of
+pub fn column_names(dataset_id: &str) -> Vec<String> {
+    COLUMN_STORE.with(|store| {
+        store.borrow()
+            .get(dataset_id)
+            .map_or_else(Vec::new, |columns| columns.iter().map(|c| c.name.clone()).collect())
+    })
+}
+
+pub fn get_column(dataset_id: &str, column: &str) -> Option<EncryptedColumn> {
+    COLUMN_STORE.with(|store| {
+        store.borrow()
+            .get(dataset_id)
+            .and_then(|columns| columns.iter().find(|c| c.name == column).cloned())
+    })
+}
+
+/// Record which columns of a dataset were decrypted for a computation
+pub fn record_decryption(computation_id: &str, dataset_id: &str, columns: Vec<(String, String)>) {
+    let (columns, column_digests) = columns.into_iter().unzip();
+    DECRYPTION_LOG.with(|log| {
+        log.borrow_mut()
+            .entry(computation_id.to_string())
+            .or_default()
+            .push(DecryptedColumns {
+                dataset_id: dataset_id.to_string(),
+                columns,
+                column_digests,
+                decrypted_at: time(),
+            });
+    });
+}
+
+pub fn decryptions_for(computation_id: &str) -> Vec<DecryptedColumns> {
+    DECRYPTION_LOG.with(|log| log.borrow().get(computation_id).cloned().unwrap_or_default())
+}
+
+/// Human-readable minimization statement derived from the decryption log
+pub fn minimization_statement(computation_id: &str) -> Option<String> {
+    let records = decryptions_for(computation_id);
+    if records.is_empty() {
+        return None;
+    }
+
+    let parts: Vec<String> = records.iter()
+        .map(|r| format!("{}: {}", r.dataset_id, r.columns.join(", ")))
+        .collect();
+    Some(format!("Data minimization: only columns [{}] decrypted", parts.join("; ")))
+}
+
+/// Commitment over everything decrypted for a computation, in the order it
+/// was decrypted. Minimization proofs embed this value.
+pub fn decryption_commitment(computation_id: &str) -> Option<String> {
+    let records = decryptions_for(computation_id);
+    if records.is_empty() {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(computation_id.as_bytes());
+    for record in &records {
+        hasher.update(record.dataset_id.as_bytes());
+        for (column, column_digest) in record.columns.iter().zip(&record.column_digests) {
+            hasher.update(column.as_bytes());
+            hasher.update(column_digest.as_bytes());
+        }
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&COLUMN_STORE), backup::cloned(&DECRYPTION_LOG)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (column_store, decryption_log) = backup::decode(bytes)?;
+    backup::replace(&COLUMN_STORE, column_store);
+    backup::replace(&DECRYPTION_LOG, decryption_log);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "column_encryption_test.rs"]
+mod tests;
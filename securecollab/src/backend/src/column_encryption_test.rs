@@ -0,0 +1,57 @@
+use super::*;
+
+const SAMPLE: &str = "patient_id,age,treatment,outcome\n\
+P001,45,Drug_A,Improved\n\
+P002,62,Drug_B,Stable\n\
+P003,38,Drug_A,Cured\n";
+
+#[test]
+fn test_split_columns_by_header() {
+    let columns = split_columns(SAMPLE.as_bytes());
+
+    let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["patient_id", "age", "treatment", "outcome"]);
+    assert_eq!(columns[2].1, b"Drug_A\nDrug_B\nDrug_A".to_vec());
+}
+
+#[test]
+fn test_split_columns_skips_malformed_rows() {
+    let data = "a,b\n1,2\n3\n4,5\n";
+    let columns = split_columns(data.as_bytes());
+
+    assert_eq!(columns[0].1, b"1\n4".to_vec());
+    assert_eq!(columns[1].1, b"2\n5".to_vec());
+}
+
+#[test]
+fn test_split_columns_rejects_binary_payloads() {
+    assert!(split_columns(&[0xff, 0xfe, 0x00]).is_empty());
+    assert!(split_columns(b"").is_empty());
+}
+
+#[test]
+fn test_join_columns_round_trips_subset() {
+    let columns = split_columns(SAMPLE.as_bytes());
+    let subset: Vec<(String, Vec<u8>)> = columns.into_iter()
+        .filter(|(name, _)| name == "treatment" || name == "outcome")
+        .collect();
+
+    assert_eq!(
+        join_columns(&subset),
+        "treatment,outcome\nDrug_A,Improved\nDrug_B,Stable\nDrug_A,Cured"
+    );
+}
+
+#[test]
+fn test_join_columns_without_rows() {
+    let columns = vec![("a".to_string(), vec![]), ("b".to_string(), vec![])];
+    assert_eq!(join_columns(&columns), "a,b");
+}
+
+#[test]
+fn test_column_derivation_paths_are_distinct() {
+    assert_ne!(
+        column_derivation_path("Hospital", "trial", "age"),
+        column_derivation_path("Hospital", "trial", "outcome")
+    );
+}
@@ -0,0 +1,222 @@
+//! Runtime configuration
+//!
+//! One place to read and change the canister's tunable settings. The
+//! settings kept here used to be constants; the rest stay with the module
+//! that uses them and are read and set through it, so `get_config` always
+//! shows what is in effect. An update either applies in full or not at all.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use crate::differential_privacy::DpConfig;
+use crate::secure_llm::provider::{self, LlmBackend};
+use crate::{agent_registry, differential_privacy, identity_manager, maintenance, pause, scheduler};
+use crate::backup;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const NANOS_PER_DAY: u64 = 24 * 60 * 60 * NANOS_PER_SECOND;
+
+/// Where vetKD keys handed to clients come from
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum VetKdMode {
+    /// Deterministic keys for local development
+    Mock,
+    /// The management canister's vetKD API
+    Real,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Registered parties needed before queries and computations open
+    pub min_parties: u32,
+    pub query_expiry_seconds: u64,
+    pub invitation_expiry_seconds: u64,
+    pub vetkd_mode: VetKdMode,
+    pub vetkd_key_name: String,
+    /// Threshold ECDSA key for canister signatures
+    pub threshold_key_name: String,
+    /// Overrides the IC LLM canister
+    pub llm_canister_id: Option<Principal>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            min_parties: 3,
+            query_expiry_seconds: 24 * 60 * 60,
+            invitation_expiry_seconds: crate::invitations::INVITATION_TTL_NANOS / NANOS_PER_SECOND,
+            vetkd_mode: VetKdMode::Mock,
+            vetkd_key_name: "dfx_test_key".to_string(),
+            threshold_key_name: "dfx_test_key".to_string(),
+            llm_canister_id: None,
+        }
+    }
+}
+
+/// Every setting in effect
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Config {
+    pub settings: Settings,
+    pub vetkd_key_ttl_seconds: Option<u64>,
+    pub retention_days: u64,
+    pub agent_liveness_seconds: u64,
+    pub max_concurrent_executions: u32,
+    pub unpause_approvals: u32,
+    pub llm_backend: LlmBackend,
+    pub dp_defaults: DpConfig,
+}
+
+/// Settings to change; the rest keep their values
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ConfigUpdate {
+    pub min_parties: Option<u32>,
+    pub query_expiry_seconds: Option<u64>,
+    pub invitation_expiry_seconds: Option<u64>,
+    pub vetkd_mode: Option<VetKdMode>,
+    pub vetkd_key_name: Option<String>,
+    pub threshold_key_name: Option<String>,
+    /// `Some(None)` goes back to the default LLM canister
+    pub llm_canister_id: Option<Option<Principal>>,
+    /// `Some(None)` lets keys live until revoked
+    pub vetkd_key_ttl_seconds: Option<Option<u64>>,
+    pub retention_days: Option<u64>,
+    pub agent_liveness_seconds: Option<u64>,
+    pub max_concurrent_executions: Option<u32>,
+    pub unpause_approvals: Option<u32>,
+    pub llm_backend: Option<LlmBackend>,
+    pub dp_defaults: Option<DpConfig>,
+}
+
+thread_local! {
+    static SETTINGS: RefCell<Settings> = RefCell::new(Settings::default());
+}
+
+pub fn settings() -> Settings {
+    SETTINGS.with(|settings| settings.borrow().clone())
+}
+
+pub fn current() -> Config {
+    Config {
+        settings: settings(),
+        vetkd_key_ttl_seconds: identity_manager::default_key_ttl().map(|nanos| nanos / NANOS_PER_SECOND),
+        retention_days: maintenance::retention() / NANOS_PER_DAY,
+        agent_liveness_seconds: agent_registry::liveness_window() / NANOS_PER_SECOND,
+        max_concurrent_executions: scheduler::max_concurrent(),
+        unpause_approvals: pause::state().required_approvals,
+        llm_backend: provider::backend(),
+        dp_defaults: differential_privacy::config(),
+    }
+}
+
+fn validate_settings(settings: &Settings) -> Result<(), String> {
+    if settings.min_parties == 0 {
+        return Err("At least one party is needed".to_string());
+    }
+    if settings.query_expiry_seconds == 0 || settings.invitation_expiry_seconds == 0 {
+        return Err("Expiry durations must be positive".to_string());
+    }
+    if settings.vetkd_key_name.trim().is_empty() {
+        return Err("The vetKD key name cannot be empty".to_string());
+    }
+    if settings.threshold_key_name.trim().is_empty() {
+        return Err("The threshold signing key name cannot be empty".to_string());
+    }
+    Ok(())
+}
+
+fn try_apply(update: &ConfigUpdate) -> Result<(), String> {
+    let mut next = settings();
+    if let Some(min_parties) = update.min_parties {
+        next.min_parties = min_parties;
+    }
+    if let Some(seconds) = update.query_expiry_seconds {
+        next.query_expiry_seconds = seconds;
+    }
+    if let Some(seconds) = update.invitation_expiry_seconds {
+        next.invitation_expiry_seconds = seconds;
+    }
+    if let Some(mode) = update.vetkd_mode {
+        next.vetkd_mode = mode;
+    }
+    if let Some(name) = &update.vetkd_key_name {
+        next.vetkd_key_name = name.trim().to_string();
+    }
+    if let Some(name) = &update.threshold_key_name {
+        next.threshold_key_name = name.trim().to_string();
+    }
+    if let Some(canister_id) = update.llm_canister_id {
+        next.llm_canister_id = canister_id;
+    }
+    validate_settings(&next)?;
+    SETTINGS.with(|settings| *settings.borrow_mut() = next);
+
+    if let Some(ttl) = update.vetkd_key_ttl_seconds {
+        identity_manager::set_default_key_ttl(ttl.map(|seconds| seconds.saturating_mul(NANOS_PER_SECOND)))?;
+    }
+    if let Some(days) = update.retention_days {
+        maintenance::set_retention(days.saturating_mul(NANOS_PER_DAY))?;
+    }
+    if let Some(seconds) = update.agent_liveness_seconds {
+        agent_registry::set_liveness_window(seconds.saturating_mul(NANOS_PER_SECOND));
+    }
+    if let Some(max) = update.max_concurrent_executions {
+        scheduler::set_max_concurrent(max)?;
+    }
+    if let Some(approvals) = update.unpause_approvals {
+        pause::set_required_approvals(approvals)?;
+    }
+    if let Some(backend) = &update.llm_backend {
+        provider::set_backend(backend.clone())?;
+    }
+    if let Some(dp) = &update.dp_defaults {
+        differential_privacy::set_config(dp.clone())?;
+    }
+    Ok(())
+}
+
+// The values `update` touches, as they are in `config`
+fn restoring(update: &ConfigUpdate, config: Config) -> ConfigUpdate {
+    let Config { settings, vetkd_key_ttl_seconds, retention_days, agent_liveness_seconds,
+        max_concurrent_executions, unpause_approvals, llm_backend, dp_defaults } = config;
+    ConfigUpdate {
+        min_parties: update.min_parties.map(|_| settings.min_parties),
+        query_expiry_seconds: update.query_expiry_seconds.map(|_| settings.query_expiry_seconds),
+        invitation_expiry_seconds: update.invitation_expiry_seconds.map(|_| settings.invitation_expiry_seconds),
+        vetkd_mode: update.vetkd_mode.map(|_| settings.vetkd_mode),
+        vetkd_key_name: update.vetkd_key_name.as_ref().map(|_| settings.vetkd_key_name),
+        threshold_key_name: update.threshold_key_name.as_ref().map(|_| settings.threshold_key_name),
+        llm_canister_id: update.llm_canister_id.map(|_| settings.llm_canister_id),
+        vetkd_key_ttl_seconds: update.vetkd_key_ttl_seconds.map(|_| vetkd_key_ttl_seconds),
+        // Whole days are what was set, so nothing is lost in the round trip
+        retention_days: update.retention_days.map(|_| retention_days),
+        agent_liveness_seconds: update.agent_liveness_seconds.map(|_| agent_liveness_seconds),
+        max_concurrent_executions: update.max_concurrent_executions.map(|_| max_concurrent_executions),
+        unpause_approvals: update.unpause_approvals.map(|_| unpause_approvals),
+        llm_backend: update.llm_backend.as_ref().map(|_| llm_backend),
+        dp_defaults: update.dp_defaults.as_ref().map(|_| dp_defaults),
+    }
+}
+
+/// Apply an update, putting back anything already changed if part of it
+/// is rejected
+pub fn update(update: ConfigUpdate) -> Result<Config, String> {
+    let before = current();
+    if let Err(e) = try_apply(&update) {
+        let _ = try_apply(&restoring(&update, before));
+        return Err(e);
+    }
+    Ok(current())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&SETTINGS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let settings = backup::decode(bytes)?;
+    backup::replace(&SETTINGS, settings);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod tests;
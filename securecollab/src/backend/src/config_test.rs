@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn defaults_match_the_constants_they_replace() {
+    let config = current();
+    assert_eq!(config.settings, Settings::default());
+    assert_eq!(config.settings.min_parties, 3);
+    assert_eq!(config.settings.query_expiry_seconds, 24 * 60 * 60);
+    assert_eq!(config.retention_days, maintenance::DEFAULT_RETENTION_NANOS / NANOS_PER_DAY);
+    assert_eq!(config.max_concurrent_executions, scheduler::DEFAULT_MAX_CONCURRENT);
+    assert_eq!(config.agent_liveness_seconds, agent_registry::DEFAULT_LIVENESS_WINDOW_NS / NANOS_PER_SECOND);
+}
+
+#[test]
+fn updates_reach_the_modules_that_own_the_settings() {
+    let config = update(ConfigUpdate {
+        min_parties: Some(2),
+        vetkd_mode: Some(VetKdMode::Real),
+        vetkd_key_name: Some(" key_1 ".to_string()),
+        vetkd_key_ttl_seconds: Some(Some(60)),
+        retention_days: Some(7),
+        max_concurrent_executions: Some(8),
+        llm_backend: Some(LlmBackend::Mock),
+        ..Default::default()
+    }).unwrap();
+    assert_eq!((config.settings.min_parties, config.settings.vetkd_mode), (2, VetKdMode::Real));
+    assert_eq!(config.settings.vetkd_key_name, "key_1");
+    assert_eq!(identity_manager::default_key_ttl(), Some(60 * NANOS_PER_SECOND));
+    assert_eq!(maintenance::retention(), 7 * NANOS_PER_DAY);
+    assert_eq!(scheduler::max_concurrent(), 8);
+    assert_eq!(provider::backend(), LlmBackend::Mock);
+
+    let cleared = update(ConfigUpdate { vetkd_key_ttl_seconds: Some(None), ..Default::default() }).unwrap();
+    assert_eq!(cleared.vetkd_key_ttl_seconds, None);
+    assert_eq!(cleared.settings.min_parties, 2);
+}
+
+#[test]
+fn rejected_updates_change_nothing() {
+    let before = current();
+    let rejected = update(ConfigUpdate {
+        min_parties: Some(5),
+        retention_days: Some(10),
+        max_concurrent_executions: Some(0),
+        ..Default::default()
+    });
+    assert!(rejected.is_err());
+    assert_eq!(current(), before);
+
+    assert!(update(ConfigUpdate { vetkd_key_name: Some(" ".to_string()), ..Default::default() }).is_err());
+    assert!(update(ConfigUpdate { query_expiry_seconds: Some(0), ..Default::default() }).is_err());
+    assert!(update(ConfigUpdate { llm_backend: Some(LlmBackend::Https("missing".to_string())), ..Default::default() }).is_err());
+    assert_eq!(current(), before);
+}
@@ -0,0 +1,166 @@
+//! Self-contained export of a workspace's consent graph
+//!
+//! Every computation with its signer set, votes, collected signatures and
+//! timestamps is flattened into a single structure. The canister signs a
+//! digest of that structure with its threshold ECDSA key, so an acquiring
+//! organization or regulator can verify the whole consent history offline
+//! against the included public key.
+
+use candid::{CandidType, Deserialize, Principal};
+use sha2::{Sha256, Digest};
+use crate::identity_manager::MultiPartySignature;
+use crate::MPCComputation;
+
+/// Derivation path for the export signing key
+pub const SIGNING_DERIVATION_PATH: &[u8] = b"consent_graph_export";
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ConsentVote {
+    pub voter: Principal,
+    pub decision: String,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ConsentSignature {
+    pub signer: String,
+    pub signature: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ConsentNode {
+    pub computation_id: String,
+    pub title: String,
+    pub requester: Principal,
+    pub status: String,
+    pub created_at: u64,
+    pub required_signers: Vec<Principal>,
+    pub received_signatures: Vec<Principal>,
+    pub votes: Vec<ConsentVote>,
+    pub signature_data_hash: Option<String>,
+    pub signatures: Vec<ConsentSignature>,
+    pub shared_with: Vec<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ConsentGraph {
+    pub workspace: String,
+    pub canister_id: Principal,
+    pub exported_at: u64,
+    pub computations: Vec<ConsentNode>,
+    pub graph_digest: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SignedConsentGraph {
+    pub graph: ConsentGraph,
+    pub algorithm: String,
+    pub key_name: String,
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Flatten a computation and its multi-party signature record
+pub fn node(computation: &MPCComputation, signatures: Option<MultiPartySignature>) -> ConsentNode {
+    let (signature_data_hash, mut collected) = match signatures {
+        Some(sig) => (
+            Some(sig.data_hash),
+            sig.signatures.into_iter()
+                .map(|(signer, signature)| ConsentSignature { signer, signature })
+                .collect::<Vec<_>>(),
+        ),
+        None => (None, vec![]),
+    };
+    collected.sort_by(|a, b| a.signer.cmp(&b.signer));
+
+    ConsentNode {
+        computation_id: computation.id.clone(),
+        title: computation.title.clone(),
+        requester: computation.requester,
+        status: computation.status.clone(),
+        created_at: computation.created_at,
+        required_signers: computation.required_signatures.clone(),
+        received_signatures: computation.received_signatures.clone(),
+        votes: computation.votes.iter().map(|v| ConsentVote {
+            voter: v.voter,
+            decision: v.decision.clone(),
+            timestamp: v.timestamp,
+        }).collect(),
+        signature_data_hash,
+        signatures: collected,
+        shared_with: computation.shared_with.clone(),
+    }
+}
+
+/// Assemble the graph in a canonical order and fill in its digest
+pub fn build(workspace: &str, canister_id: Principal, exported_at: u64, mut computations: Vec<ConsentNode>) -> ConsentGraph {
+    computations.sort_by(|a, b| (a.created_at, &a.computation_id).cmp(&(b.created_at, &b.computation_id)));
+
+    let mut graph = ConsentGraph {
+        workspace: workspace.to_string(),
+        canister_id,
+        exported_at,
+        computations,
+        graph_digest: String::new(),
+    };
+    graph.graph_digest = hex::encode(digest(&graph));
+    graph
+}
+
+/// SHA-256 over every field of the graph except `graph_digest`. Strings and
+/// lists are length-prefixed so verifiers can recompute it in any language.
+pub fn digest(graph: &ConsentGraph) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"securecollab-consent-graph-v1");
+    put_str(&mut hasher, &graph.workspace);
+    put_bytes(&mut hasher, graph.canister_id.as_slice());
+    hasher.update(graph.exported_at.to_be_bytes());
+
+    hasher.update((graph.computations.len() as u64).to_be_bytes());
+    for node in &graph.computations {
+        put_str(&mut hasher, &node.computation_id);
+        put_str(&mut hasher, &node.title);
+        put_bytes(&mut hasher, node.requester.as_slice());
+        put_str(&mut hasher, &node.status);
+        hasher.update(node.created_at.to_be_bytes());
+        put_principals(&mut hasher, &node.required_signers);
+        put_principals(&mut hasher, &node.received_signatures);
+
+        hasher.update((node.votes.len() as u64).to_be_bytes());
+        for vote in &node.votes {
+            put_bytes(&mut hasher, vote.voter.as_slice());
+            put_str(&mut hasher, &vote.decision);
+            hasher.update(vote.timestamp.to_be_bytes());
+        }
+
+        put_str(&mut hasher, node.signature_data_hash.as_deref().unwrap_or(""));
+        hasher.update((node.signatures.len() as u64).to_be_bytes());
+        for signature in &node.signatures {
+            put_str(&mut hasher, &signature.signer);
+            put_str(&mut hasher, &signature.signature);
+        }
+        put_principals(&mut hasher, &node.shared_with);
+    }
+
+    hasher.finalize().into()
+}
+
+fn put_bytes(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+fn put_str(hasher: &mut Sha256, value: &str) {
+    put_bytes(hasher, value.as_bytes());
+}
+
+fn put_principals(hasher: &mut Sha256, principals: &[Principal]) {
+    hasher.update((principals.len() as u64).to_be_bytes());
+    for principal in principals {
+        put_bytes(hasher, principal.as_slice());
+    }
+}
+
+#[cfg(test)]
+#[path = "consent_export_test.rs"]
+mod tests;
@@ -0,0 +1,43 @@
+use super::*;
+
+fn sample_node(id: &str, created_at: u64) -> ConsentNode {
+    ConsentNode {
+        computation_id: id.to_string(),
+        title: "Cohort analysis".to_string(),
+        requester: Principal::anonymous(),
+        status: "completed".to_string(),
+        created_at,
+        required_signers: vec![Principal::anonymous()],
+        received_signatures: vec![Principal::anonymous()],
+        votes: vec![ConsentVote {
+            voter: Principal::anonymous(),
+            decision: "yes".to_string(),
+            timestamp: created_at + 1,
+        }],
+        signature_data_hash: Some("abc".to_string()),
+        signatures: vec![],
+        shared_with: vec![],
+    }
+}
+
+#[test]
+fn build_orders_nodes_and_sets_digest() {
+    let graph = build("default", Principal::anonymous(), 10, vec![sample_node("b", 2), sample_node("a", 1)]);
+    assert_eq!(graph.computations[0].computation_id, "a");
+    assert_eq!(graph.graph_digest, hex::encode(digest(&graph)));
+}
+
+#[test]
+fn digest_covers_votes() {
+    let graph = build("default", Principal::anonymous(), 10, vec![sample_node("a", 1)]);
+    let mut tampered = graph.clone();
+    tampered.computations[0].votes[0].decision = "no".to_string();
+    assert_ne!(digest(&graph), digest(&tampered));
+}
+
+#[test]
+fn digest_is_independent_of_input_order() {
+    let first = build("default", Principal::anonymous(), 10, vec![sample_node("a", 1), sample_node("b", 2)]);
+    let second = build("default", Principal::anonymous(), 10, vec![sample_node("b", 2), sample_node("a", 1)]);
+    assert_eq!(first.graph_digest, second.graph_digest);
+}
@@ -0,0 +1,118 @@
+//! Cycle cost quotes for computations
+//!
+//! Executions record the instructions they consumed together with the
+//! dataset bytes they decrypted and the LLM calls they made. Quotes combine
+//! the chosen agent's fee with instruction and call costs derived from
+//! those runs, falling back to static defaults until history exists.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use crate::backup;
+
+/// Instruction cost on a 13-node application subnet (0.4 cycles per instruction)
+pub const CYCLES_PER_BILLION_INSTRUCTIONS: u128 = 400_000_000;
+/// Base fee for an inter-canister call to the LLM canister
+pub const LLM_CALL_CYCLES: u128 = 260_000;
+/// Instructions per decrypted byte until runs with data have been measured
+pub const DEFAULT_INSTRUCTIONS_PER_BYTE: u64 = 50;
+/// Instructions per LLM call until runs have been measured
+pub const DEFAULT_INSTRUCTIONS_PER_CALL: u64 = 20_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CostEstimateRequest {
+    pub agent_id: Option<String>,
+    pub dataset_ids: Vec<String>,
+    pub expected_llm_calls: Option<u32>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CostEstimate {
+    pub agent_fee: u64,
+    pub dataset_bytes: u64,
+    pub llm_calls: u32,
+    pub estimated_instructions: u64,
+    pub instruction_cycles: u128,
+    pub llm_call_cycles: u128,
+    pub total_cycles: u128,
+    pub based_on_runs: u32,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RunTotals {
+    pub runs: u32,
+    pub instructions: u64,
+    pub dataset_bytes: u64,
+    pub llm_calls: u64,
+}
+
+thread_local! {
+    static RUNS: RefCell<RunTotals> = RefCell::new(RunTotals::default());
+}
+
+/// Record the instruction count of a finished execution
+pub fn record_run(instructions: u64, dataset_bytes: u64, llm_calls: u32) {
+    RUNS.with(|runs| {
+        let mut runs = runs.borrow_mut();
+        runs.runs += 1;
+        runs.instructions = runs.instructions.saturating_add(instructions);
+        runs.dataset_bytes = runs.dataset_bytes.saturating_add(dataset_bytes);
+        runs.llm_calls = runs.llm_calls.saturating_add(llm_calls as u64);
+    });
+}
+
+pub fn totals() -> RunTotals {
+    RUNS.with(|runs| *runs.borrow())
+}
+
+/// Instructions per LLM call observed so far, net of the per-byte cost of
+/// decrypting the data those runs read
+pub fn instructions_per_call(totals: &RunTotals) -> u64 {
+    if totals.llm_calls == 0 {
+        return DEFAULT_INSTRUCTIONS_PER_CALL;
+    }
+    let data_cost = totals.dataset_bytes.saturating_mul(DEFAULT_INSTRUCTIONS_PER_BYTE);
+    (totals.instructions.saturating_sub(data_cost) / totals.llm_calls).max(1)
+}
+
+/// Cycles charged for executing `instructions`
+pub fn instruction_cycles(instructions: u64) -> u128 {
+    instructions as u128 * CYCLES_PER_BILLION_INSTRUCTIONS / 1_000_000_000
+}
+
+pub fn estimate(agent_fee: u64, dataset_bytes: u64, llm_calls: Option<u32>, totals: &RunTotals) -> CostEstimate {
+    // Without an explicit count assume the historical average, at least one call
+    let llm_calls = llm_calls.unwrap_or_else(|| match totals.runs {
+        0 => 1,
+        runs => (totals.llm_calls / runs as u64).max(1) as u32,
+    });
+
+    let estimated_instructions = dataset_bytes.saturating_mul(DEFAULT_INSTRUCTIONS_PER_BYTE)
+        .saturating_add(instructions_per_call(totals).saturating_mul(llm_calls as u64));
+    let instruction_cycles = instruction_cycles(estimated_instructions);
+    let llm_call_cycles = llm_calls as u128 * LLM_CALL_CYCLES;
+
+    CostEstimate {
+        agent_fee,
+        dataset_bytes,
+        llm_calls,
+        estimated_instructions,
+        instruction_cycles,
+        llm_call_cycles,
+        total_cycles: agent_fee as u128 + instruction_cycles + llm_call_cycles,
+        based_on_runs: totals.runs,
+    }
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&RUNS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let runs = backup::decode(bytes)?;
+    backup::replace(&RUNS, runs);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "cost_estimation_test.rs"]
+mod tests;
@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn quote_without_history_uses_defaults() {
+    let quote = estimate(1_000, 0, None, &RunTotals::default());
+    assert_eq!(quote.llm_calls, 1);
+    assert_eq!(quote.estimated_instructions, DEFAULT_INSTRUCTIONS_PER_CALL);
+    assert_eq!(quote.instruction_cycles, 8_000_000);
+    assert_eq!(quote.total_cycles, 1_000 + 8_000_000 + LLM_CALL_CYCLES);
+    assert_eq!(quote.based_on_runs, 0);
+}
+
+#[test]
+fn measured_runs_drive_per_call_cost() {
+    let totals = RunTotals { runs: 2, instructions: 3_000_000 + 100 * DEFAULT_INSTRUCTIONS_PER_BYTE, dataset_bytes: 100, llm_calls: 3 };
+    assert_eq!(instructions_per_call(&totals), 1_000_000);
+
+    let quote = estimate(0, 10, Some(4), &totals);
+    assert_eq!(quote.estimated_instructions, 4_000_000 + 10 * DEFAULT_INSTRUCTIONS_PER_BYTE);
+    assert_eq!(quote.based_on_runs, 2);
+}
+
+#[test]
+fn call_count_defaults_to_historical_average() {
+    let totals = RunTotals { runs: 2, instructions: 10, dataset_bytes: 0, llm_calls: 6 };
+    assert_eq!(estimate(0, 0, None, &totals).llm_calls, 3);
+}
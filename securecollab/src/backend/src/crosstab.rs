@@ -0,0 +1,231 @@
+//! Private contingency tables with chi-square tests of independence
+//!
+//! Two category or boolean columns are cross-tabulated over the combined
+//! datasets, skipping records missing either value. One record falls in a
+//! single cell, so every cell, empty ones included, is released with Laplace
+//! noise under the whole epsilon. Rows and columns are the values observed,
+//! as in the analytics release. The chi-square test runs on the noisy
+//! counts before disclosure control, which then suppresses cells below the
+//! small-cell threshold and rounds the rest to the policy's rounding base.
+
+use candid::{CandidType, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::analytics::{ColumnSpec, ColumnType};
+use crate::differential_privacy::{noise, NoiseMechanism, NoiseSource};
+use crate::disclosure_control::{self, DisclosurePolicy};
+use crate::tabular;
+
+/// Expected counts below this make the chi-square approximation unreliable
+pub const MIN_EXPECTED_COUNT: f64 = 5.0;
+pub const MAX_CELLS: usize = 400;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ContingencyRequest {
+    pub dataset_ids: Vec<String>,
+    pub row_variable: String,
+    pub column_variable: String,
+    /// Defaults to the canister's differential privacy configuration
+    pub epsilon: Option<f64>,
+    /// Approved computation to run under when other parties' datasets are read
+    pub computation_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChiSquareTest {
+    pub statistic: f64,
+    pub degrees_of_freedom: u64,
+    pub p_value: f64,
+    /// Strength of association from 0 to 1
+    pub cramers_v: f64,
+    /// More than a fifth of the cells expect fewer than `MIN_EXPECTED_COUNT`
+    pub sparse: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ContingencyTable {
+    pub row_variable: String,
+    pub column_variable: String,
+    pub row_labels: Vec<String>,
+    pub column_labels: Vec<String>,
+    /// `cells[row][column]`, None where suppressed
+    pub cells: Vec<Vec<Option<u64>>>,
+    /// Noisy number of records tabulated
+    pub records: u64,
+    pub suppressed_cells: u64,
+    /// None when fewer than two rows or columns have records
+    pub chi_square: Option<ChiSquareTest>,
+    pub epsilon: f64,
+}
+
+fn categorical<'a>(schema: &'a [ColumnSpec], name: &str) -> Result<&'a ColumnSpec, String> {
+    let spec = schema.iter().find(|spec| spec.name == name)
+        .ok_or_else(|| format!("Column '{}' is not in the schema", name))?;
+    match spec.column_type {
+        ColumnType::Category | ColumnType::Boolean => Ok(spec),
+        _ => Err(format!("Column '{}' must be a category or a boolean", name)),
+    }
+}
+
+/// Natural log of the gamma function, by the Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let series: f64 = COEFFICIENTS.iter().enumerate()
+        .fold(1.000000000190015, |sum, (i, c)| sum + c / (x + 1.0 + i as f64));
+    (x + 0.5) * tmp.ln() - tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Upper regularized incomplete gamma function Q(a, x)
+fn gamma_q(a: f64, x: f64) -> f64 {
+    const ITERATIONS: usize = 500;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series for P(a, x)
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..ITERATIONS {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        return (1.0 - sum * prefix).clamp(0.0, 1.0);
+    }
+    // Continued fraction for Q(a, x) by the modified Lentz method
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..=ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    (prefix * h).clamp(0.0, 1.0)
+}
+
+/// Pearson's chi-square test of independence, ignoring empty rows and columns
+pub fn chi_square(counts: &[Vec<f64>]) -> Option<ChiSquareTest> {
+    let row_totals: Vec<f64> = counts.iter().map(|row| row.iter().sum()).collect();
+    let columns = counts.first().map_or(0, Vec::len);
+    let column_totals: Vec<f64> = (0..columns).map(|j| counts.iter().map(|row| row[j]).sum()).collect();
+    let total: f64 = row_totals.iter().sum();
+    let rows = row_totals.iter().filter(|t| **t > 0.0).count();
+    let columns = column_totals.iter().filter(|t| **t > 0.0).count();
+    if rows < 2 || columns < 2 {
+        return None;
+    }
+
+    let mut statistic = 0.0;
+    let mut sparse_cells = 0;
+    for (row, row_total) in counts.iter().zip(&row_totals).filter(|(_, t)| **t > 0.0) {
+        for (observed, column_total) in row.iter().zip(&column_totals).filter(|(_, t)| **t > 0.0) {
+            let expected = row_total * column_total / total;
+            statistic += (observed - expected).powi(2) / expected;
+            if expected < MIN_EXPECTED_COUNT {
+                sparse_cells += 1;
+            }
+        }
+    }
+    let degrees_of_freedom = ((rows - 1) * (columns - 1)) as u64;
+    Some(ChiSquareTest {
+        statistic,
+        degrees_of_freedom,
+        p_value: gamma_q(degrees_of_freedom as f64 / 2.0, statistic / 2.0),
+        cramers_v: (statistic / (total * (rows.min(columns) - 1) as f64)).sqrt().min(1.0),
+        sparse: sparse_cells * 5 > rows * columns,
+    })
+}
+
+pub fn tabulate(
+    schema: &[ColumnSpec],
+    request: &ContingencyRequest,
+    datasets: &[String],
+    epsilon: f64,
+    policy: &DisclosurePolicy,
+    source: &mut NoiseSource,
+) -> Result<ContingencyTable, String> {
+    if request.row_variable == request.column_variable {
+        return Err("Cross-tabulate two different columns".to_string());
+    }
+    let specs = [categorical(schema, &request.row_variable)?, categorical(schema, &request.column_variable)?];
+
+    let mut counts: BTreeMap<(String, String), u64> = BTreeMap::new();
+    for data in datasets {
+        let table = tabular::parse(data.as_bytes())?;
+        let positions = specs.iter()
+            .map(|spec| table.header.iter().position(|h| *h == spec.name)
+                .ok_or_else(|| format!("Column '{}' not found", spec.name)))
+            .collect::<Result<Vec<_>, String>>()?;
+        for row in &table.rows {
+            if let (Some(r), Some(c)) = (row.get(positions[0]), row.get(positions[1])) {
+                if !r.is_empty() && !c.is_empty() {
+                    *counts.entry((r.clone(), c.clone())).or_default() += 1;
+                }
+            }
+        }
+    }
+    let exact: u64 = counts.values().sum();
+    if exact < policy.min_cohort_size {
+        return Err(format!("Fewer than {} records have both columns", policy.min_cohort_size));
+    }
+
+    let row_labels: Vec<String> = counts.keys().map(|(r, _)| r.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    let column_labels: Vec<String> = counts.keys().map(|(_, c)| c.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    if row_labels.len() * column_labels.len() > MAX_CELLS {
+        return Err(format!("A contingency table can have at most {} cells", MAX_CELLS));
+    }
+    let noisy: Vec<Vec<f64>> = row_labels.iter().map(|r| column_labels.iter().map(|c| {
+        let count = counts.get(&(r.clone(), c.clone())).copied().unwrap_or(0) as f64;
+        (count + noise(source, NoiseMechanism::Laplace, 1.0, epsilon, 0.0)).round().max(0.0)
+    }).collect()).collect();
+
+    let mut suppressed_cells = 0;
+    let cells = noisy.iter().map(|row| row.iter().map(|&count| {
+        let count = count as u64;
+        if disclosure_control::is_small_cell(count, policy) {
+            suppressed_cells += 1;
+            None
+        } else {
+            Some(disclosure_control::round_to_base(count, policy.rounding_base))
+        }
+    }).collect()).collect();
+
+    Ok(ContingencyTable {
+        row_variable: request.row_variable.clone(),
+        column_variable: request.column_variable.clone(),
+        row_labels,
+        column_labels,
+        cells,
+        records: noisy.iter().flatten().sum::<f64>() as u64,
+        suppressed_cells,
+        chi_square: chi_square(&noisy),
+        epsilon,
+    })
+}
+
+#[cfg(test)]
+#[path = "crosstab_test.rs"]
+mod tests;
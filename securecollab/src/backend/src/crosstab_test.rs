@@ -0,0 +1,70 @@
+use super::*;
+use crate::analytics::parse_schema;
+
+const SCHEMA: &str = "treatment:category,recovered:boolean,site:category?,age:number";
+
+fn dataset(rows: usize) -> String {
+    let mut lines = vec!["treatment,recovered,site,age".to_string()];
+    for i in 0..rows {
+        let treatment = if i % 2 == 0 { "Drug_X" } else { "Placebo" };
+        // Most treated patients recover and most untreated ones do not
+        let recovered = (i % 2 == 0) != (i % 10 == 0);
+        let site = if i % 7 == 0 { "" } else { "north" };
+        lines.push(format!("{},{},{},{}", treatment, recovered, site, 40 + i % 30));
+    }
+    lines.join("\n")
+}
+
+fn request(rows: &str, columns: &str) -> ContingencyRequest {
+    ContingencyRequest { dataset_ids: vec![], row_variable: rows.to_string(), column_variable: columns.to_string(), epsilon: None, computation_id: None }
+}
+
+#[test]
+fn computes_chi_square_significance() {
+    let test = chi_square(&[vec![10.0, 20.0], vec![30.0, 40.0]]).unwrap();
+    assert!((test.statistic - 0.7937).abs() < 1e-4, "{}", test.statistic);
+    assert_eq!(test.degrees_of_freedom, 1);
+    assert!((test.p_value - 0.3730).abs() < 1e-3, "{}", test.p_value);
+    assert!(!test.sparse);
+    // With two degrees of freedom the tail probability is exp(-x / 2)
+    assert!((gamma_q(1.0, 3.0) - (-3.0f64).exp()).abs() < 1e-10);
+    assert!((gamma_q(0.5, 3.841 / 2.0) - 0.05).abs() < 1e-3);
+    assert!(chi_square(&[vec![5.0, 0.0], vec![7.0, 0.0]]).is_none());
+}
+
+#[test]
+fn tabulates_across_datasets_with_noise() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let table = tabulate(&schema, &request("treatment", "recovered"), &[dataset(200), dataset(200)], 1e6, &DisclosurePolicy::default(), &mut source).unwrap();
+    assert_eq!(table.row_labels, vec!["Drug_X", "Placebo"]);
+    assert_eq!(table.column_labels, vec!["false", "true"]);
+    assert_eq!(table.cells, vec![vec![Some(40), Some(160)], vec![Some(200), Some(0)]]);
+    assert_eq!(table.records, 400);
+    let test = table.chi_square.unwrap();
+    assert!(test.p_value < 1e-6 && test.cramers_v > 0.5);
+}
+
+#[test]
+fn applies_disclosure_control_to_cells() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let policy = DisclosurePolicy { min_cohort_size: 5, small_cell_threshold: 50, rounding_base: 10 };
+    let table = tabulate(&schema, &request("treatment", "recovered"), &[dataset(203)], 1e6, &policy, &mut source).unwrap();
+    assert_eq!(table.suppressed_cells, 1);
+    assert_eq!(table.cells, vec![vec![None, Some(80)], vec![Some(100), Some(0)]]);
+    // Records missing the site are left out
+    let sites = tabulate(&schema, &request("site", "treatment"), &[dataset(70)], 1e6, &policy, &mut source).unwrap();
+    assert_eq!(sites.records, 60);
+}
+
+#[test]
+fn rejects_unsuitable_requests() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let policy = DisclosurePolicy::default();
+    let data = [dataset(20)];
+    assert!(tabulate(&schema, &request("treatment", "age"), &data, 1.0, &policy, &mut source).is_err());
+    assert!(tabulate(&schema, &request("treatment", "treatment"), &data, 1.0, &policy, &mut source).is_err());
+    assert!(tabulate(&schema, &request("treatment", "recovered"), &[dataset(3)], 1.0, &policy, &mut source).is_err());
+}
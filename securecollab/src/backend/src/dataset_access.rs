@@ -0,0 +1,76 @@
+//! Per-dataset access log
+//!
+//! Every decryption of a `PrivateDataSource` is recorded with who triggered
+//! it, when, and for which computation, so data custodians can review how
+//! their data was used. Query calls cannot persist state, so only accesses
+//! made from update calls (all decryption paths) appear here.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AccessType {
+    Decryption,
+    ColumnDecryption,
+    ReEncryption,
+    SecureSum,
+    SetIntersection,
+    RecordLinkage,
+    HomomorphicSum,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DatasetAccess {
+    pub dataset_id: String,
+    pub accessor: Principal,
+    pub access_type: AccessType,
+    pub computation_id: Option<String>,
+    pub columns: Vec<String>,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static ACCESS_LOG: RefCell<HashMap<String, Vec<DatasetAccess>>> = RefCell::new(HashMap::new());
+}
+
+pub fn record(
+    dataset_id: &str,
+    accessor: Principal,
+    access_type: AccessType,
+    computation_id: Option<&str>,
+    columns: Vec<String>,
+) {
+    let timestamp = time();
+    ACCESS_LOG.with(|log| {
+        log.borrow_mut()
+            .entry(dataset_id.to_string())
+            .or_default()
+            .push(DatasetAccess {
+                dataset_id: dataset_id.to_string(),
+                accessor,
+                access_type,
+                computation_id: computation_id.map(|id| id.to_string()),
+                columns,
+                timestamp,
+            });
+    });
+    crate::anomaly::observe(accessor, dataset_id, timestamp);
+}
+
+/// Accesses of a dataset, oldest first
+pub fn history(dataset_id: &str) -> Vec<DatasetAccess> {
+    ACCESS_LOG.with(|log| log.borrow().get(dataset_id).cloned().unwrap_or_default())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&ACCESS_LOG))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let access_log = backup::decode(bytes)?;
+    backup::replace(&ACCESS_LOG, access_log);
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Time-boxed decryption
+//!
+//! Every run that decrypts a dataset or a result, from queries and analytics
+//! to key rotation and re-encryption, decrypts into a `DecryptionContext`
+//! opened when it starts. The context holds the plaintext and the keys
+//! derived for it, and zeroizes both when it is dropped, whether the run
+//! completes or fails part way; runs that re-encrypt bytes as they are keep
+//! them in zeroizing buffers and count them against the window. Each context
+//! registers a window that a timer expires after `WINDOW_NANOS`; the run
+//! checks the window between steps and stops once it has expired, so no
+//! plaintext outlives the window it was approved for. Windows stay on record
+//! for the audit trail.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use zeroize::Zeroize;
+use crate::backup;
+
+/// How long an execution may hold decrypted data: ten minutes
+pub const WINDOW_NANOS: u64 = 10 * 60 * 1_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WindowState {
+    Open,
+    /// The execution finished and closed the window
+    Closed,
+    /// The timer fired before the execution finished
+    Expired,
+    /// The execution failed and dropped the window without closing it
+    Aborted,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DecryptionWindow {
+    pub run_id: String,
+    pub opened_at: u64,
+    pub expires_at: u64,
+    pub closed_at: Option<u64>,
+    pub state: WindowState,
+    pub bytes_decrypted: u64,
+}
+
+thread_local! {
+    static WINDOWS: RefCell<HashMap<String, DecryptionWindow>> = RefCell::new(HashMap::new());
+}
+
+/// Plaintext and keys of one execution, zeroized on drop
+pub struct DecryptionContext {
+    run_id: String,
+    data: Vec<String>,
+    keys: Vec<Vec<u8>>,
+}
+
+impl DecryptionContext {
+    pub fn open(run_id: &str, now: u64, duration: u64) -> Result<Self, String> {
+        WINDOWS.with(|windows| {
+            let mut windows = windows.borrow_mut();
+            if windows.get(run_id).is_some_and(|w| w.state == WindowState::Open) {
+                return Err(format!("A decryption window for {} is already open", run_id));
+            }
+            windows.insert(run_id.to_string(), DecryptionWindow {
+                run_id: run_id.to_string(),
+                opened_at: now,
+                expires_at: now.saturating_add(duration),
+                closed_at: None,
+                state: WindowState::Open,
+                bytes_decrypted: 0,
+            });
+            Ok(())
+        })?;
+        Ok(Self { run_id: run_id.to_string(), data: Vec::new(), keys: Vec::new() })
+    }
+
+    /// Fails once the window has expired, expiring it if the timer is late
+    pub fn ensure_open(&self, now: u64) -> Result<(), String> {
+        expire_if_due(&self.run_id, now);
+        match window(&self.run_id).map(|w| w.state) {
+            Some(WindowState::Open) => Ok(()),
+            _ => Err(format!("The decryption window for {} has expired", self.run_id)),
+        }
+    }
+
+    /// Keep a derived key until the window closes
+    pub fn hold_key(&mut self, key: Vec<u8>) -> &[u8] {
+        self.keys.push(key);
+        self.keys.last().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn add(&mut self, plaintext: String) {
+        self.count(plaintext.len());
+        self.data.push(plaintext);
+    }
+
+    /// Count plaintext the execution keeps in its own zeroizing buffers, for
+    /// steps that re-encrypt decrypted bytes as they are
+    pub fn count(&self, bytes: usize) {
+        WINDOWS.with(|windows| {
+            if let Some(window) = windows.borrow_mut().get_mut(&self.run_id) {
+                window.bytes_decrypted += bytes as u64;
+            }
+        });
+    }
+
+    pub fn data(&self) -> &[String] {
+        &self.data
+    }
+
+    /// Close the window at the end of the execution; the plaintext is zeroized
+    pub fn close(self, now: u64) -> Option<DecryptionWindow> {
+        finish(&self.run_id, WindowState::Closed, Some(now));
+        window(&self.run_id)
+    }
+}
+
+impl Drop for DecryptionContext {
+    fn drop(&mut self) {
+        for plaintext in &mut self.data {
+            plaintext.zeroize();
+        }
+        for key in &mut self.keys {
+            key.zeroize();
+        }
+        finish(&self.run_id, WindowState::Aborted, None);
+    }
+}
+
+// Move an open window to its final state
+fn finish(run_id: &str, state: WindowState, now: Option<u64>) -> bool {
+    WINDOWS.with(|windows| {
+        match windows.borrow_mut().get_mut(run_id) {
+            Some(window) if window.state == WindowState::Open => {
+                window.state = state;
+                window.closed_at = now;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+fn expire_if_due(run_id: &str, now: u64) -> bool {
+    match window(run_id) {
+        Some(window) if now >= window.expires_at => expire(run_id, now),
+        _ => false,
+    }
+}
+
+/// Expire a window that is still open, as its timer does; true if it was open
+pub fn expire(run_id: &str, now: u64) -> bool {
+    finish(run_id, WindowState::Expired, Some(now))
+}
+
+pub fn window(run_id: &str) -> Option<DecryptionWindow> {
+    WINDOWS.with(|windows| windows.borrow().get(run_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&WINDOWS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let windows = backup::decode(bytes)?;
+    backup::replace(&WINDOWS, windows);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "decryption_window_test.rs"]
+mod tests;
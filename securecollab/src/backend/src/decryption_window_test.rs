@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn closes_and_records_the_window() {
+    let mut context = DecryptionContext::open("run-closed", 100, 50).unwrap();
+    assert!(DecryptionContext::open("run-closed", 110, 50).is_err());
+    assert_eq!(context.hold_key(vec![1, 2, 3]), &[1, 2, 3]);
+    context.add("age\n40".to_string());
+    context.ensure_open(120).unwrap();
+    assert_eq!(context.data(), &["age\n40".to_string()]);
+
+    let window = context.close(130).unwrap();
+    assert_eq!(window.state, WindowState::Closed);
+    assert_eq!((window.opened_at, window.expires_at, window.closed_at), (100, 150, Some(130)));
+    assert_eq!(window.bytes_decrypted, 6);
+    // A later execution of the same run gets a new window
+    assert!(DecryptionContext::open("run-closed", 200, 50).is_ok());
+}
+
+#[test]
+fn expires_by_timer_or_deadline() {
+    let context = DecryptionContext::open("run-timer", 0, 50).unwrap();
+    assert!(expire("run-timer", 50));
+    assert!(!expire("run-timer", 60));
+    assert!(context.ensure_open(55).is_err());
+    drop(context);
+    assert_eq!(window("run-timer").unwrap().state, WindowState::Expired);
+
+    let late = DecryptionContext::open("run-late", 0, 50).unwrap();
+    assert!(late.ensure_open(49).is_ok());
+    assert!(late.ensure_open(50).is_err());
+    assert_eq!(window("run-late").unwrap().closed_at, Some(50));
+}
+
+#[test]
+fn marks_dropped_windows_aborted() {
+    let context = DecryptionContext::open("run-failed", 0, 50).unwrap();
+    drop(context);
+    let window = window("run-failed").unwrap();
+    assert_eq!(window.state, WindowState::Aborted);
+    assert_eq!(window.closed_at, None);
+}
+
+#[test]
+fn counts_bytes_re_encrypted_outside_the_context() {
+    let mut context = DecryptionContext::open("run-rotation", 0, 50).unwrap();
+    context.count(128);
+    context.add("id\n7".to_string());
+    assert_eq!(context.data().len(), 1);
+    assert_eq!(context.close(10).unwrap().bytes_decrypted, 132);
+}
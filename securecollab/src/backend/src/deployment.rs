@@ -0,0 +1,69 @@
+//! Deployment arguments
+//!
+//! `init` and `post_upgrade` take an optional record, so local, testnet and
+//! mainnet deployments differ by the argument passed to `dfx deploy` rather
+//! than by code. Fields left out keep their defaults. State lives on the heap
+//! only and is not carried over an upgrade, so admins and configuration are
+//! back to their defaults afterwards too; pass the full argument on every
+//! upgrade. Admins named here may call operator endpoints alongside the
+//! canister's controllers.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use crate::config::{self, ConfigUpdate, VetKdMode};
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct BootstrapParty {
+    pub principal: Principal,
+    pub name: String,
+    pub role: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct InitArgs {
+    pub admins: Option<Vec<Principal>>,
+    /// Parties registered at deployment, without invitations
+    pub parties: Option<Vec<BootstrapParty>>,
+    pub llm_canister_id: Option<Principal>,
+    pub vetkd_key_name: Option<String>,
+    pub vetkd_mode: Option<VetKdMode>,
+    /// Threshold ECDSA key that events and exports are signed with
+    pub threshold_key_name: Option<String>,
+}
+
+thread_local! {
+    static ADMINS: RefCell<Vec<Principal>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn is_admin(principal: Principal) -> bool {
+    ADMINS.with(|admins| admins.borrow().contains(&principal))
+}
+
+/// Apply the arguments, returning the parties left to register; nothing
+/// changes if any argument is rejected
+pub fn apply(args: InitArgs) -> Result<Vec<BootstrapParty>, String> {
+    let parties = args.parties.unwrap_or_default();
+    for party in &parties {
+        if party.principal == Principal::anonymous() || party.name.trim().is_empty() {
+            return Err(format!("Bootstrap party '{}' needs a name and a non-anonymous principal", party.name));
+        }
+    }
+    if args.admins.as_ref().is_some_and(|admins| admins.contains(&Principal::anonymous())) {
+        return Err("The anonymous principal cannot be an admin".to_string());
+    }
+    config::update(ConfigUpdate {
+        llm_canister_id: args.llm_canister_id.map(Some),
+        vetkd_key_name: args.vetkd_key_name,
+        vetkd_mode: args.vetkd_mode,
+        threshold_key_name: args.threshold_key_name,
+        ..Default::default()
+    })?;
+    if let Some(admins) = args.admins {
+        ADMINS.with(|current| *current.borrow_mut() = admins);
+    }
+    Ok(parties)
+}
+
+#[cfg(test)]
+#[path = "deployment_test.rs"]
+mod tests;
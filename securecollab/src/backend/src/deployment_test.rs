@@ -0,0 +1,37 @@
+use super::*;
+use crate::test_support::principal;
+
+#[test]
+fn arguments_set_admins_and_configuration() {
+    let party = BootstrapParty { principal: principal(2), name: "Boston General".to_string(), role: "hospital".to_string() };
+    let parties = apply(InitArgs {
+        admins: Some(vec![principal(1)]),
+        parties: Some(vec![party.clone()]),
+        llm_canister_id: Some(principal(3)),
+        vetkd_key_name: Some("test_key_1".to_string()),
+        vetkd_mode: Some(VetKdMode::Real),
+        threshold_key_name: Some("key_1".to_string()),
+    }).unwrap();
+    assert_eq!(parties, vec![party]);
+    assert!(is_admin(principal(1)) && !is_admin(principal(2)));
+    let settings = config::settings();
+    assert_eq!(settings.llm_canister_id, Some(principal(3)));
+    assert_eq!((settings.vetkd_key_name.as_str(), settings.vetkd_mode), ("test_key_1", VetKdMode::Real));
+    assert_eq!(settings.threshold_key_name, "key_1");
+
+    // Fields left out of a later call keep their values
+    assert!(apply(InitArgs::default()).unwrap().is_empty());
+    assert!(is_admin(principal(1)));
+    assert_eq!(config::settings(), settings);
+}
+
+#[test]
+fn rejected_arguments_change_nothing() {
+    let anonymous_party = BootstrapParty { principal: Principal::anonymous(), name: "Lab".to_string(), role: "lab".to_string() };
+    assert!(apply(InitArgs { admins: Some(vec![principal(4)]), parties: Some(vec![anonymous_party]), ..Default::default() }).is_err());
+    assert!(apply(InitArgs { admins: Some(vec![Principal::anonymous()]), ..Default::default() }).is_err());
+    assert!(apply(InitArgs { admins: Some(vec![principal(4)]), vetkd_key_name: Some(" ".to_string()), ..Default::default() }).is_err());
+    assert!(apply(InitArgs { threshold_key_name: Some(String::new()), ..Default::default() }).is_err());
+    assert!(!is_admin(principal(4)));
+    assert_eq!(config::settings(), config::Settings::default());
+}
@@ -0,0 +1,229 @@
+//! Differential privacy for released aggregates
+//!
+//! Aggregates computed over decrypted datasets are released through the
+//! Laplace or Gaussian mechanism. The configured epsilon is split evenly
+//! across the released statistics: the record count, the mean of every
+//! bounded numeric column and the value counts of every category and boolean
+//! column (a record falls in one cell of each histogram, so a histogram costs
+//! a single share). Means use the declared value range over the column's
+//! record count as their sensitivity. Order statistics (median, min, max)
+//! are not released. The realized
+//! parameters of every release are recorded so its privacy proof can be
+//! checked against them.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::privacy_proofs::DifferentialPrivacyParams;
+use crate::analytics::{ColumnSpec, ColumnStatistics, ColumnType, CombinedAnalysis};
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum NoiseMechanism {
+    Laplace,
+    Gaussian,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct DpConfig {
+    /// Total epsilon spent by one release
+    pub epsilon: f64,
+    /// Only used by the Gaussian mechanism
+    pub delta: f64,
+    pub mechanism: NoiseMechanism,
+}
+
+impl Default for DpConfig {
+    fn default() -> Self {
+        Self { epsilon: 1.0, delta: 1e-5, mechanism: NoiseMechanism::Laplace }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ReleasedValue {
+    Mean(f64),
+    Counts(Vec<(String, u64)>),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReleasedStatistic {
+    pub column: String,
+    pub value: ReleasedValue,
+}
+
+/// Noisy aggregates safe to publish
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PrivateAnalysis {
+    pub dataset_count: u64,
+    pub total_records: u64,
+    pub statistics: Vec<ReleasedStatistic>,
+    pub params: DifferentialPrivacyParams,
+}
+
+thread_local! {
+    static CONFIG: RefCell<DpConfig> = RefCell::new(DpConfig::default());
+    // Realized parameters per computation, checked by the privacy proof
+    static RELEASES: RefCell<HashMap<String, DifferentialPrivacyParams>> = RefCell::new(HashMap::new());
+}
+
+pub fn validate(config: &DpConfig) -> Result<(), String> {
+    if !(config.epsilon.is_finite() && config.epsilon > 0.0) {
+        return Err("Epsilon must be a positive number".to_string());
+    }
+    if config.mechanism == NoiseMechanism::Gaussian && !(config.delta > 0.0 && config.delta < 1.0) {
+        return Err("The Gaussian mechanism needs a delta between 0 and 1".to_string());
+    }
+    Ok(())
+}
+
+/// Default parameters for queries that do not choose their own
+pub fn set_config(config: DpConfig) -> Result<(), String> {
+    validate(&config)?;
+    CONFIG.with(|c| *c.borrow_mut() = config);
+    Ok(())
+}
+
+pub fn config() -> DpConfig {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+/// Uniform samples in (0, 1) from SHA-256 in counter mode over a random seed
+pub struct NoiseSource {
+    seed: Vec<u8>,
+    counter: u64,
+}
+
+impl NoiseSource {
+    pub fn new(seed: Vec<u8>) -> Self {
+        Self { seed, counter: 0 }
+    }
+
+    fn uniform(&mut self) -> f64 {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.seed);
+        hasher.update(self.counter.to_be_bytes());
+        self.counter += 1;
+        let digest = hasher.finalize();
+        let bits = u64::from_be_bytes(digest[..8].try_into().expect("digest has 8 bytes")) >> 11;
+        (bits as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    pub fn laplace(&mut self, scale: f64) -> f64 {
+        let u = self.uniform() - 0.5;
+        -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+    }
+
+    pub fn gaussian(&mut self, sigma: f64) -> f64 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        sigma * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// Laplace scale or Gaussian standard deviation for a statistic with the
+/// given sensitivity under `epsilon` (and `delta`)
+pub fn noise_scale(mechanism: NoiseMechanism, sensitivity: f64, epsilon: f64, delta: f64) -> f64 {
+    match mechanism {
+        NoiseMechanism::Laplace => sensitivity / epsilon,
+        NoiseMechanism::Gaussian => sensitivity * (2.0 * (1.25 / delta).ln()).sqrt() / epsilon,
+    }
+}
+
+pub fn noise(source: &mut NoiseSource, mechanism: NoiseMechanism, sensitivity: f64, epsilon: f64, delta: f64) -> f64 {
+    let scale = noise_scale(mechanism, sensitivity, epsilon, delta);
+    match mechanism {
+        NoiseMechanism::Laplace => source.laplace(scale),
+        NoiseMechanism::Gaussian => source.gaussian(scale),
+    }
+}
+
+/// Statistics sharing the epsilon budget of a release over `columns`: the
+/// record count and one per released column
+pub fn released_statistics<'a>(columns: impl IntoIterator<Item = &'a ColumnSpec>) -> usize {
+    1 + columns.into_iter().filter(|column| column.released()).count()
+}
+
+/// Release `combined` under `config`
+pub fn privatize(combined: &CombinedAnalysis, config: &DpConfig, source: &mut NoiseSource) -> PrivateAnalysis {
+    let analysis = &combined.analysis;
+    let epsilon = config.epsilon / released_statistics(analysis.columns.iter().map(|column| &column.spec)) as f64;
+    let mut noisy = |value: f64, sensitivity: f64| value + noise(source, config.mechanism, sensitivity, epsilon, config.delta);
+    let count = |value: f64| value.round().max(0.0) as u64;
+
+    let total_records = count(noisy(analysis.total_records as f64, 1.0));
+    let statistics = analysis.columns.iter()
+        .filter(|column| column.spec.released())
+        .filter_map(|column| {
+            let value = match (&column.spec.column_type, &column.statistics) {
+                (ColumnType::Number { bounds: Some((min, max)), .. }, ColumnStatistics::Numeric(stats)) => {
+                    let sensitivity = (max - min) / stats.count.max(1) as f64;
+                    ReleasedValue::Mean(noisy(stats.mean.clamp(*min, *max), sensitivity).clamp(*min, *max))
+                }
+                (_, ColumnStatistics::Counts(counts)) => ReleasedValue::Counts(
+                    counts.iter().map(|(value, n)| (value.clone(), count(noisy(*n as f64, 1.0)))).collect(),
+                ),
+                _ => return None,
+            };
+            Some(ReleasedStatistic { column: column.spec.name.clone(), value })
+        })
+        .collect();
+
+    PrivateAnalysis {
+        dataset_count: combined.dataset_count as u64,
+        total_records,
+        statistics,
+        params: DifferentialPrivacyParams {
+            epsilon: config.epsilon,
+            delta: if config.mechanism == NoiseMechanism::Gaussian { config.delta } else { 0.0 },
+            sensitivity: 1.0,
+            noise_mechanism: format!("{:?}", config.mechanism),
+        },
+    }
+}
+
+/// Human-readable summary of a release
+pub fn render(analysis: &PrivateAnalysis) -> String {
+    let mut lines = vec![
+        format!("• Datasets analyzed: {}", analysis.dataset_count),
+        format!("• Records: {}", analysis.total_records),
+    ];
+    lines.extend(analysis.statistics.iter().map(|statistic| match &statistic.value {
+        ReleasedValue::Mean(mean) => format!("• Mean {}: {:.1}", statistic.column, mean),
+        ReleasedValue::Counts(counts) => format!(
+            "• {}: {}",
+            statistic.column,
+            counts.iter().map(|(value, n)| format!("{} {}", value, n)).collect::<Vec<_>>().join(", "),
+        ),
+    }));
+    lines.push(format!(
+        "• Differential privacy: {} mechanism, epsilon {}{}",
+        analysis.params.noise_mechanism,
+        analysis.params.epsilon,
+        if analysis.params.delta > 0.0 { format!(", delta {}", analysis.params.delta) } else { String::new() },
+    ));
+    lines.join("\n")
+}
+
+pub fn record_release(computation_id: &str, params: DifferentialPrivacyParams) {
+    RELEASES.with(|releases| releases.borrow_mut().insert(computation_id.to_string(), params));
+}
+
+pub fn release(computation_id: &str) -> Option<DifferentialPrivacyParams> {
+    RELEASES.with(|releases| releases.borrow().get(computation_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&CONFIG), backup::cloned(&RELEASES)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (config, releases) = backup::decode(bytes)?;
+    backup::replace(&CONFIG, config);
+    backup::replace(&RELEASES, releases);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "differential_privacy_test.rs"]
+mod tests;
@@ -0,0 +1,77 @@
+use super::*;
+use crate::analytics::{combine, parse_schema};
+
+const HEADER: &str = "patient_id,age,treatment,outcome,recovery_days,side_effect,hospital";
+const SCHEMA: &str = "patient_id:id,age:number[0..120],treatment:category,outcome:category,recovery_days:number[0..365],side_effect:category,hospital:category";
+
+fn combine_healthcare_data(datasets: &[String]) -> Result<CombinedAnalysis, String> {
+    combine(&parse_schema(SCHEMA).unwrap(), datasets)
+}
+
+fn released<'a>(analysis: &'a PrivateAnalysis, column: &str) -> &'a ReleasedValue {
+    &analysis.statistics.iter().find(|s| s.column == column).unwrap().value
+}
+
+fn dataset(rows: usize, hospital: &str) -> String {
+    let mut lines = vec![HEADER.to_string()];
+    for i in 0..rows {
+        let outcome = if i % 4 == 0 { "Worse" } else { "Improved" };
+        lines.push(format!("p{},{},DrugA,{},{},None,{}", i, 40 + i % 30, outcome, 10 + i % 7, hospital));
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn combines_datasets_sharing_the_schema() {
+    let other = "id,score\n1,2".to_string();
+    let combined = combine_healthcare_data(&[dataset(40, "Boston"), dataset(60, "MIT"), other]).unwrap();
+    assert_eq!(combined.dataset_count, 2);
+    assert_eq!(combined.skipped_datasets, 1);
+    assert_eq!(combined.analysis.total_records, 100);
+    let hospital = combined.analysis.columns.iter().find(|c| c.spec.name == "hospital").unwrap();
+    assert_eq!(hospital.statistics, ColumnStatistics::Counts(vec![("Boston".to_string(), 40), ("MIT".to_string(), 60)]));
+}
+
+#[test]
+fn laplace_noise_has_the_expected_spread() {
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let samples: Vec<f64> = (0..20_000).map(|_| source.laplace(2.0)).collect();
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let mean_abs = samples.iter().map(|x| x.abs()).sum::<f64>() / samples.len() as f64;
+    assert!(mean.abs() < 0.1, "mean {}", mean);
+    // E|X| equals the scale for Laplace noise
+    assert!((mean_abs - 2.0).abs() < 0.1, "mean |x| {}", mean_abs);
+}
+
+#[test]
+fn release_is_noisy_but_close_and_records_epsilon() {
+    let combined = combine_healthcare_data(&[dataset(400, "Boston"), dataset(600, "MIT")]).unwrap();
+    let config = DpConfig { epsilon: 2.0, ..DpConfig::default() };
+    let release = privatize(&combined, &config, &mut NoiseSource::new(vec![7; 32]));
+
+    assert!(release.total_records.abs_diff(1000) < 50);
+    // Identifiers are never released
+    assert_eq!(release.statistics.len(), 6);
+    let ColumnStatistics::Numeric(exact) = &combined.analysis.columns[1].statistics else { panic!("age is numeric") };
+    let ReleasedValue::Mean(age) = released(&release, "age") else { panic!("age is a mean") };
+    assert_ne!(*age, exact.mean);
+    assert!((age - exact.mean).abs() < 5.0);
+    let ReleasedValue::Counts(outcomes) = released(&release, "outcome") else { panic!("outcome is counted") };
+    assert_eq!(outcomes.iter().map(|(value, _)| value.as_str()).collect::<Vec<_>>(), vec!["Improved", "Worse"]);
+    assert!(outcomes[1].1.abs_diff(250) < 50);
+    assert!(render(&release).contains("• Mean recovery_days"));
+    assert_eq!(release.params.epsilon, 2.0);
+    assert_eq!(release.params.noise_mechanism, "Laplace");
+    assert!(render(&release).contains("epsilon 2"));
+
+    // The same seed reproduces the same noise on every replica
+    let again = privatize(&combined, &config, &mut NoiseSource::new(vec![7; 32]));
+    assert_eq!(again.total_records, release.total_records);
+}
+
+#[test]
+fn gaussian_mechanism_needs_a_delta() {
+    assert!(set_config(DpConfig { delta: 0.0, mechanism: NoiseMechanism::Gaussian, ..DpConfig::default() }).is_err());
+    assert!(set_config(DpConfig { epsilon: 0.0, ..DpConfig::default() }).is_err());
+    set_config(DpConfig { mechanism: NoiseMechanism::Gaussian, ..DpConfig::default() }).unwrap();
+}
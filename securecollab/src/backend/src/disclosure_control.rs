@@ -0,0 +1,278 @@
+//! Disclosure control for published computation results
+//!
+//! Results are parsed for statistics that carry their record count — JSON
+//! objects with a count field, text lines such as "n=3" or "4 patients" — and
+//! any statistic drawn from fewer than k records is suppressed before the
+//! results are encrypted for their recipients, so small cohorts cannot be
+//! re-identified from the published insights. Count cells below the
+//! small-cell threshold are then suppressed and the remaining counts rounded
+//! to the configured base. Suppressing JSON values replaces them with null,
+//! which a result schema may not allow for. Every adjustment is recorded and
+//! committed to in the computation's disclosure-control privacy proof.
+
+use candid::{CandidType, Deserialize};
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+pub const DEFAULT_MIN_COHORT_SIZE: u64 = 5;
+
+// Object keys that hold the number of records a statistic was computed from
+const COUNT_KEYS: &[&str] = &[
+    "n", "count", "cohort_size", "sample_size", "num_records", "record_count",
+    "records", "patients", "participants", "subjects", "size",
+];
+// Object keys whose arrays are tables of count cells
+const CELL_ARRAY_KEYS: &[&str] = &["counts", "frequencies", "histogram"];
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisclosurePolicy {
+    /// k: statistics from fewer records are suppressed
+    pub min_cohort_size: u64,
+    /// Counts between 1 and this value (exclusive) are suppressed; 0 disables
+    pub small_cell_threshold: u64,
+    /// Published counts are rounded to a multiple of this; 1 disables
+    pub rounding_base: u64,
+}
+
+impl Default for DisclosurePolicy {
+    fn default() -> Self {
+        Self { min_cohort_size: DEFAULT_MIN_COHORT_SIZE, small_cell_threshold: 0, rounding_base: 1 }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SuppressionKind {
+    /// A statistic drawn from fewer than k records
+    MinCohort,
+    /// A count cell below the small-cell threshold
+    SmallCell,
+    /// A count rounded to the rounding base
+    Rounded,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Suppression {
+    /// JSON path (e.g. "$.groups[2]") or text line (e.g. "line 4")
+    pub location: String,
+    pub kind: SuppressionKind,
+    pub record_count: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DisclosureReport {
+    pub min_cohort_size: u64,
+    pub small_cell_threshold: u64,
+    pub rounding_base: u64,
+    pub suppressions: Vec<Suppression>,
+}
+
+thread_local! {
+    static POLICY: RefCell<DisclosurePolicy> = RefCell::new(DisclosurePolicy::default());
+    // What was suppressed in each computation's published results
+    static REPORTS: RefCell<HashMap<String, DisclosureReport>> = RefCell::new(HashMap::new());
+    static TEXT_COUNT: Regex = Regex::new(
+        r"(?i)\b(?:n\s*=\s*(\d+)|(\d+)\s+(?:patients|records|participants|subjects|individuals|cases|people|members))\b"
+    ).expect("count pattern is a valid regex");
+}
+
+pub fn set_policy(policy: DisclosurePolicy) -> Result<(), String> {
+    if policy.min_cohort_size < 2 {
+        return Err("Minimum cohort size must be at least 2".to_string());
+    }
+    if policy.rounding_base == 0 {
+        return Err("Rounding base must be at least 1".to_string());
+    }
+    POLICY.with(|p| *p.borrow_mut() = policy);
+    Ok(())
+}
+
+pub fn policy() -> DisclosurePolicy {
+    POLICY.with(|p| p.borrow().clone())
+}
+
+fn record_count(object: &Map<String, Value>) -> Option<u64> {
+    COUNT_KEYS.iter().find_map(|key| object.get(*key).and_then(Value::as_u64))
+}
+
+// Null out everything but the labels of an object drawn from too few records
+fn suppress_object(object: &mut Map<String, Value>) {
+    for value in object.values_mut() {
+        if !value.is_string() {
+            *value = Value::Null;
+        }
+    }
+    object.insert("suppressed".to_string(), Value::Bool(true));
+}
+
+/// Nearest multiple of `base`, halves rounding up
+pub fn round_to_base(count: u64, base: u64) -> u64 {
+    if base <= 1 {
+        return count;
+    }
+    count.saturating_add(base / 2) / base * base
+}
+
+// An empty group reveals nothing about anyone
+fn below_k(count: u64, k: u64) -> bool {
+    count > 0 && count < k
+}
+
+pub fn is_small_cell(count: u64, policy: &DisclosurePolicy) -> bool {
+    count > 0 && count < policy.small_cell_threshold
+}
+
+// Suppress or round one count cell; None means it is suppressed
+fn adjust_cell(count: u64, location: &str, policy: &DisclosurePolicy, suppressions: &mut Vec<Suppression>) -> Option<u64> {
+    if is_small_cell(count, policy) {
+        suppressions.push(Suppression { location: location.to_string(), kind: SuppressionKind::SmallCell, record_count: count });
+        return None;
+    }
+    let rounded = round_to_base(count, policy.rounding_base);
+    if rounded != count {
+        suppressions.push(Suppression { location: location.to_string(), kind: SuppressionKind::Rounded, record_count: count });
+    }
+    Some(rounded)
+}
+
+fn adjust_cell_value(value: &mut Value, location: &str, policy: &DisclosurePolicy, suppressions: &mut Vec<Suppression>) {
+    if let Some(count) = value.as_u64() {
+        *value = adjust_cell(count, location, policy, suppressions).map_or(Value::Null, Value::from);
+    }
+}
+
+fn filter_value(value: &mut Value, path: &str, policy: &DisclosurePolicy, suppressions: &mut Vec<Suppression>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(count) = record_count(object).filter(|count| below_k(*count, policy.min_cohort_size)) {
+                suppress_object(object);
+                suppressions.push(Suppression { location: path.to_string(), kind: SuppressionKind::MinCohort, record_count: count });
+                return;
+            }
+            for (key, child) in object.iter_mut() {
+                let location = format!("{}.{}", path, key);
+                if COUNT_KEYS.contains(&key.as_str()) {
+                    adjust_cell_value(child, &location, policy, suppressions);
+                } else if let (true, Value::Array(cells)) = (CELL_ARRAY_KEYS.contains(&key.as_str()), &mut *child) {
+                    for (i, cell) in cells.iter_mut().enumerate() {
+                        adjust_cell_value(cell, &format!("{}[{}]", location, i), policy, suppressions);
+                    }
+                } else {
+                    filter_value(child, &location, policy, suppressions);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                filter_value(child, &format!("{}[{}]", path, i), policy, suppressions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Apply `policy` to a JSON result
+pub fn filter_json(json: &str, policy: &DisclosurePolicy) -> Result<(String, Vec<Suppression>), String> {
+    let mut value: Value = serde_json::from_str(json)
+        .map_err(|e| format!("Structured result is not valid JSON: {}", e))?;
+    let mut suppressions = Vec::new();
+    filter_value(&mut value, "$", policy, &mut suppressions);
+    Ok((value.to_string(), suppressions))
+}
+
+/// Apply `policy` to a text result: lines reporting a statistic from fewer
+/// than k records are dropped, counts in the remaining lines are suppressed
+/// or rounded in place
+pub fn filter_text(text: &str, policy: &DisclosurePolicy) -> (String, Vec<Suppression>) {
+    let k = policy.min_cohort_size;
+    let mut suppressions = Vec::new();
+    let lines: Vec<String> = text.lines().enumerate()
+        .map(|(i, line)| {
+            let location = format!("line {}", i + 1);
+            TEXT_COUNT.with(|pattern| {
+                let smallest = pattern.captures_iter(line)
+                    .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+                    .filter_map(|m| m.as_str().parse::<u64>().ok())
+                    .filter(|count| *count > 0)
+                    .min();
+                if let Some(count) = smallest.filter(|count| below_k(*count, k)) {
+                    suppressions.push(Suppression { location, kind: SuppressionKind::MinCohort, record_count: count });
+                    return format!("[suppressed: statistic from fewer than {} records]", k);
+                }
+                pattern.replace_all(line, |caps: &Captures| {
+                    let whole = caps.get(0).expect("match has a whole group");
+                    let Some(number) = caps.get(1).or_else(|| caps.get(2)) else {
+                        return whole.as_str().to_string();
+                    };
+                    let count = number.as_str().parse::<u64>().unwrap_or_default();
+                    let cell = match adjust_cell(count, &location, policy, &mut suppressions) {
+                        Some(rounded) => rounded.to_string(),
+                        None => format!("<{}", policy.small_cell_threshold),
+                    };
+                    let prefix = &whole.as_str()[..number.start() - whole.start()];
+                    let suffix = &whole.as_str()[number.end() - whole.start()..];
+                    format!("{}{}{}", prefix, cell, suffix)
+                }).into_owned()
+            })
+        })
+        .collect();
+    (lines.join("\n"), suppressions)
+}
+
+/// Apply the configured policy to a computation's text and structured results
+pub fn apply(results: &str, structured: Option<&str>) -> Result<(String, Option<String>, DisclosureReport), String> {
+    let policy = policy();
+    let (results, mut suppressions) = filter_text(results, &policy);
+    let structured = match structured {
+        Some(json) => {
+            let (json, json_suppressions) = filter_json(json, &policy)?;
+            suppressions.extend(json_suppressions);
+            Some(json)
+        }
+        None => None,
+    };
+    let report = DisclosureReport {
+        min_cohort_size: policy.min_cohort_size,
+        small_cell_threshold: policy.small_cell_threshold,
+        rounding_base: policy.rounding_base,
+        suppressions,
+    };
+    Ok((results, structured, report))
+}
+
+/// Canonical statement of a report, committed to by its privacy proof
+pub fn statement(request_id: &str, report: &DisclosureReport) -> String {
+    let mut statement = format!(
+        "computation:{}\nmin_cohort_size:{}\nsmall_cell_threshold:{}\nrounding_base:{}\n",
+        request_id, report.min_cohort_size, report.small_cell_threshold, report.rounding_base
+    );
+    for suppression in &report.suppressions {
+        statement.push_str(&format!("{:?}:{}:{}\n", suppression.kind, suppression.location, suppression.record_count));
+    }
+    statement
+}
+
+pub fn record(request_id: &str, report: DisclosureReport) {
+    REPORTS.with(|reports| reports.borrow_mut().insert(request_id.to_string(), report));
+}
+
+pub fn report(request_id: &str) -> Option<DisclosureReport> {
+    REPORTS.with(|reports| reports.borrow().get(request_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&POLICY), backup::cloned(&REPORTS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (policy, reports) = backup::decode(bytes)?;
+    backup::replace(&POLICY, policy);
+    backup::replace(&REPORTS, reports);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "disclosure_control_test.rs"]
+mod tests;
@@ -0,0 +1,78 @@
+use super::*;
+
+fn k_only(k: u64) -> DisclosurePolicy {
+    DisclosurePolicy { min_cohort_size: k, ..DisclosurePolicy::default() }
+}
+
+#[test]
+fn suppresses_json_groups_below_k() {
+    let json = r#"{"total":{"n":120,"mean_age":61.5},"groups":[
+        {"label":"under 40","count":40,"recovery_rate":0.8},
+        {"label":"over 90","count":3,"recovery_rate":0.33,"ages":[91,93,95]}
+    ]}"#;
+    let (filtered, suppressions) = filter_json(json, &k_only(5)).unwrap();
+    let value: Value = serde_json::from_str(&filtered).unwrap();
+
+    assert_eq!(value["total"]["mean_age"], 61.5);
+    assert_eq!(value["groups"][0]["recovery_rate"], 0.8);
+    assert_eq!(value["groups"][1]["label"], "over 90");
+    assert!(value["groups"][1]["recovery_rate"].is_null());
+    assert!(value["groups"][1]["ages"].is_null());
+    assert_eq!(value["groups"][1]["suppressed"], true);
+    assert_eq!(suppressions, vec![Suppression {
+        location: "$.groups[1]".to_string(),
+        kind: SuppressionKind::MinCohort,
+        record_count: 3,
+    }]);
+}
+
+#[test]
+fn suppresses_text_lines_below_k() {
+    let text = "Cohort summary (n=212)\nRare variant carriers: 2 patients, mean age 44\nControls: n = 57";
+    let (filtered, suppressions) = filter_text(text, &k_only(5));
+    assert_eq!(filtered, "Cohort summary (n=212)\n[suppressed: statistic from fewer than 5 records]\nControls: n = 57");
+    assert_eq!(suppressions.len(), 1);
+    assert_eq!(suppressions[0].location, "line 2");
+}
+
+#[test]
+fn rounds_counts_and_suppresses_small_cells() {
+    let policy = DisclosurePolicy { min_cohort_size: 2, small_cell_threshold: 10, rounding_base: 5 };
+    assert_eq!(round_to_base(212, 5), 210);
+    assert_eq!(round_to_base(213, 5), 215);
+    assert_eq!(round_to_base(7, 1), 7);
+
+    let (text, suppressions) = filter_text("Treated: 212 patients\nUntreated: 7 patients\nExcluded: 0 patients", &policy);
+    assert_eq!(text, "Treated: 210 patients\nUntreated: <10 patients\nExcluded: 0 patients");
+    let kinds: Vec<_> = suppressions.iter().map(|s| s.kind).collect();
+    assert_eq!(kinds, vec![SuppressionKind::Rounded, SuppressionKind::SmallCell]);
+
+    let (json, suppressions) = filter_json(r#"{"count":48,"histogram":[3,22,0]}"#, &policy).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["count"], 50);
+    assert_eq!(value["histogram"], serde_json::json!([null, 20, 0]));
+    assert_eq!(suppressions.len(), 3);
+}
+
+#[test]
+fn statement_lists_every_adjustment() {
+    let report = DisclosureReport {
+        min_cohort_size: 5,
+        small_cell_threshold: 10,
+        rounding_base: 5,
+        suppressions: vec![Suppression { location: "line 2".to_string(), kind: SuppressionKind::SmallCell, record_count: 7 }],
+    };
+    assert_eq!(
+        statement("req_1", &report),
+        "computation:req_1\nmin_cohort_size:5\nsmall_cell_threshold:10\nrounding_base:5\nSmallCell:line 2:7\n"
+    );
+}
+
+#[test]
+fn results_without_counts_are_untouched() {
+    let (filtered, suppressions) = filter_text("Results aggregated with differential privacy", &DisclosurePolicy::default());
+    assert_eq!(filtered, "Results aggregated with differential privacy");
+    assert!(suppressions.is_empty());
+    assert!(set_policy(k_only(1)).is_err());
+    assert!(set_policy(DisclosurePolicy { rounding_base: 0, ..DisclosurePolicy::default() }).is_err());
+}
@@ -0,0 +1,177 @@
+//! Signing of outbound events
+//!
+//! Every event SecureCollab publishes for external consumers is signed with
+//! the canister's threshold ECDSA key over a domain-separated digest of its
+//! id, type, timestamp and payload. Consumers fetch the verification key once
+//! and authenticate notifications end-to-end, independent of the transport.
+//! The key is the configured `threshold_key_name`.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use ic_cdk::api::management_canister::ecdsa::{
+    ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument, SignWithEcdsaArgument,
+};
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use sha2::{Sha256, Digest};
+use crate::{backup, config};
+
+/// Algorithm identifier included with signatures
+pub const SIGNATURE_ALGORITHM: &str = "ecdsa_secp256k1_sha256";
+/// Derivation path of the event signing key
+const EVENT_DERIVATION_PATH: &[u8] = b"event_signing";
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SignedEvent {
+    pub event_id: u64,
+    pub event_type: String,
+    pub payload: String,
+    pub issued_at: u64,
+    pub digest: String,
+    pub signature: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventVerificationKey {
+    pub key_name: String,
+    pub algorithm: String,
+    pub public_key: Vec<u8>,
+}
+
+thread_local! {
+    static EVENT_KEY: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+    static OUTBOX: RefCell<Vec<SignedEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+fn key_id() -> EcdsaKeyId {
+    EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: config::settings().threshold_key_name,
+    }
+}
+
+/// SEC1-encoded public key for a derivation path of the canister key
+pub async fn threshold_public_key(derivation_path: &[u8]) -> Result<Vec<u8>, String> {
+    let (response,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+        canister_id: None,
+        derivation_path: vec![derivation_path.to_vec()],
+        key_id: key_id(),
+    }).await.map_err(|(code, msg)| format!("Failed to fetch signing key: {:?} {}", code, msg))?;
+    Ok(response.public_key)
+}
+
+/// Sign a 32-byte digest with the canister key at `derivation_path`
+pub async fn threshold_sign(derivation_path: &[u8], digest: [u8; 32]) -> Result<Vec<u8>, String> {
+    let (response,) = sign_with_ecdsa(SignWithEcdsaArgument {
+        message_hash: digest.to_vec(),
+        derivation_path: vec![derivation_path.to_vec()],
+        key_id: key_id(),
+    }).await.map_err(|(code, msg)| format!("Failed to sign: {:?} {}", code, msg))?;
+    Ok(response.signature)
+}
+
+/// Digest that event signatures cover
+pub fn event_digest(event_id: u64, event_type: &str, issued_at: u64, payload: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"securecollab-event-v1");
+    hasher.update(event_id.to_be_bytes());
+    hasher.update((event_type.len() as u64).to_be_bytes());
+    hasher.update(event_type.as_bytes());
+    hasher.update(issued_at.to_be_bytes());
+    hasher.update((payload.len() as u64).to_be_bytes());
+    hasher.update(payload.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Event verification key, fetched once and cached
+pub async fn verification_key() -> Result<EventVerificationKey, String> {
+    let public_key = match EVENT_KEY.with(|key| key.borrow().clone()) {
+        Some(public_key) => public_key,
+        None => {
+            let public_key = threshold_public_key(EVENT_DERIVATION_PATH).await?;
+            EVENT_KEY.with(|key| *key.borrow_mut() = Some(public_key.clone()));
+            public_key
+        }
+    };
+
+    Ok(EventVerificationKey {
+        key_name: config::settings().threshold_key_name,
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        public_key,
+    })
+}
+
+/// Sign an event and append it to the outbound feed
+pub async fn publish(event_type: &str, payload: String) -> Result<SignedEvent, String> {
+    // Make sure consumers can always fetch the key for published events
+    verification_key().await?;
+
+    let event_id = OUTBOX.with(|outbox| outbox.borrow().len() as u64);
+    let issued_at = ic_cdk::api::time();
+    let digest = event_digest(event_id, event_type, issued_at, &payload);
+    let signature = threshold_sign(EVENT_DERIVATION_PATH, digest).await?;
+
+    let event = SignedEvent {
+        event_id,
+        event_type: event_type.to_string(),
+        payload,
+        issued_at,
+        digest: hex::encode(digest),
+        signature,
+    };
+    OUTBOX.with(|outbox| outbox.borrow_mut().push(event.clone()));
+    Ok(event)
+}
+
+/// Check an event's digest and signature against a SEC1-encoded public key
+pub fn verify_with_key(event: &SignedEvent, public_key: &[u8]) -> bool {
+    let digest = event_digest(event.event_id, &event.event_type, event.issued_at, &event.payload);
+    if hex::encode(digest) != event.digest {
+        return false;
+    }
+
+    let (Ok(key), Ok(signature)) = (VerifyingKey::from_sec1_bytes(public_key), Signature::from_slice(&event.signature)) else {
+        return false;
+    };
+    key.verify_prehash(&digest, &signature).is_ok()
+}
+
+/// Verify an event against the canister's event key
+pub fn verify(event: &SignedEvent) -> Result<bool, String> {
+    let public_key = EVENT_KEY.with(|key| key.borrow().clone())
+        .ok_or_else(|| "Event verification key has not been initialized".to_string())?;
+    Ok(verify_with_key(event, &public_key))
+}
+
+/// The request a published event is about, from its payload
+pub fn resource_id(event: &SignedEvent) -> Option<String> {
+    let payload: serde_json::Value = serde_json::from_str(&event.payload).ok()?;
+    payload.get("request_id")?.as_str().map(str::to_string)
+}
+
+/// Page through published events that pass `visible`
+pub fn events(cursor: Option<u64>, limit: Option<u32>, visible: impl Fn(&SignedEvent) -> bool) -> Vec<SignedEvent> {
+    let limit = limit
+        .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    OUTBOX.with(|outbox| {
+        outbox.borrow().iter().skip(cursor.unwrap_or(0) as usize).filter(|e| visible(e)).take(limit).cloned().collect()
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&EVENT_KEY), backup::cloned(&OUTBOX)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (event_key, outbox) = backup::decode(bytes)?;
+    backup::replace(&EVENT_KEY, event_key);
+    backup::replace(&OUTBOX, outbox);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "event_signing_test.rs"]
+mod tests;
@@ -0,0 +1,40 @@
+use super::*;
+use k256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey};
+
+fn signed(payload: &str) -> (SignedEvent, Vec<u8>) {
+    let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let digest = event_digest(3, "computation.completed", 1_000, payload);
+    let signature: Signature = signing_key.sign_prehash(&digest).unwrap();
+
+    let event = SignedEvent {
+        event_id: 3,
+        event_type: "computation.completed".to_string(),
+        payload: payload.to_string(),
+        issued_at: 1_000,
+        digest: hex::encode(digest),
+        signature: signature.to_bytes().to_vec(),
+    };
+    let public_key = signing_key.verifying_key().to_encoded_point(true).as_bytes().to_vec();
+    (event, public_key)
+}
+
+#[test]
+fn valid_signature_verifies() {
+    let (event, public_key) = signed(r#"{"request_id":"mpc_1"}"#);
+    assert!(verify_with_key(&event, &public_key));
+}
+
+#[test]
+fn tampered_payload_is_rejected() {
+    let (mut event, public_key) = signed(r#"{"request_id":"mpc_1"}"#);
+    event.payload = r#"{"request_id":"mpc_2"}"#.to_string();
+    assert!(!verify_with_key(&event, &public_key));
+}
+
+#[test]
+fn wrong_key_is_rejected() {
+    let (event, _) = signed("{}");
+    let other = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let other_key = other.verifying_key().to_encoded_point(true).as_bytes().to_vec();
+    assert!(!verify_with_key(&event, &other_key));
+}
@@ -0,0 +1,87 @@
+//! Typed workflow event bus
+//!
+//! Workflow steps append typed events with gap-free sequence numbers starting
+//! at 1. Frontends and sync pipelines remember the last sequence number they
+//! processed and read everything after it.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use crate::backup;
+
+/// Default number of events returned by one read
+const DEFAULT_PAGE_SIZE: usize = 100;
+/// Upper bound on a single read
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum WorkflowEvent {
+    DatasetUploaded { dataset_id: String, name: String },
+    VoteCast { request_id: String, decision: String, status: String },
+    ComputationCompleted { request_id: String },
+    ComputationFailed { request_id: String, error: String },
+    ProofVerified { proof_id: String, computation_id: String, verified: bool },
+}
+
+impl WorkflowEvent {
+    /// The dataset, request or computation the event is about
+    pub fn resource_id(&self) -> &str {
+        match self {
+            WorkflowEvent::DatasetUploaded { dataset_id, .. } => dataset_id,
+            WorkflowEvent::VoteCast { request_id, .. }
+            | WorkflowEvent::ComputationCompleted { request_id }
+            | WorkflowEvent::ComputationFailed { request_id, .. } => request_id,
+            WorkflowEvent::ProofVerified { computation_id, .. } => computation_id,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventRecord {
+    pub seq: u64,
+    pub actor: Principal,
+    pub event: WorkflowEvent,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static EVENTS: RefCell<Vec<EventRecord>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Append an event and return its sequence number
+pub fn record(actor: Principal, event: WorkflowEvent, now: u64) -> u64 {
+    EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        let seq = events.len() as u64 + 1;
+        events.push(EventRecord { seq, actor, event, timestamp: now });
+        seq
+    })
+}
+
+/// Events after `seq` that pass `visible`, oldest first
+pub fn since(seq: u64, limit: Option<u32>, visible: impl Fn(&EventRecord) -> bool) -> Vec<EventRecord> {
+    let limit = limit
+        .map(|l| (l as usize).clamp(1, MAX_PAGE_SIZE))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    // Sequence numbers are dense, so event `seq + 1` sits at index `seq`
+    EVENTS.with(|events| {
+        events.borrow().iter().skip(seq as usize).filter(|e| visible(e)).take(limit).cloned().collect()
+    })
+}
+
+pub fn latest_seq() -> u64 {
+    EVENTS.with(|events| events.borrow().len() as u64)
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&EVENTS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let events = backup::decode(bytes)?;
+    backup::replace(&EVENTS, events);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "events_test.rs"]
+mod tests;
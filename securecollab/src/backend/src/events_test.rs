@@ -0,0 +1,35 @@
+use super::*;
+
+fn completed(request_id: &str) -> WorkflowEvent {
+    WorkflowEvent::ComputationCompleted { request_id: request_id.to_string() }
+}
+
+#[test]
+fn sequence_numbers_start_at_one_and_have_no_gaps() {
+    let actor = Principal::anonymous();
+    assert_eq!(record(actor, completed("a"), 10), 1);
+    assert_eq!(record(actor, completed("b"), 11), 2);
+    assert_eq!(record(actor, completed("c"), 12), 3);
+    assert_eq!(latest_seq(), 3);
+}
+
+#[test]
+fn reads_resume_after_the_given_sequence_number() {
+    let actor = Principal::anonymous();
+    for i in 0..5 {
+        record(actor, completed(&format!("req_{}", i)), i);
+    }
+
+    let all = since(0, None, |_| true);
+    assert_eq!(all.len(), 5);
+    let rest = since(3, None, |_| true);
+    assert_eq!(rest.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![4, 5]);
+    assert_eq!(rest[0].event, completed("req_3"));
+    assert!(since(5, None, |_| true).is_empty());
+    assert_eq!(since(0, Some(2), |_| true).len(), 2);
+
+    // Hidden events do not count towards the page size
+    let odd = since(0, Some(2), |e| e.seq % 2 == 1);
+    assert_eq!(odd.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!(odd[1].event.resource_id(), "req_2");
+}
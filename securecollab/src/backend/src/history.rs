@@ -0,0 +1,145 @@
+//! Point-in-time history of approval state and dataset permissions
+//!
+//! Whenever a computation's approval state or a dataset's permissions change,
+//! a snapshot is stored under the next change sequence number. Reads can then
+//! reconstruct the state as of any sequence number or timestamp, answering
+//! "what did the approvals look like on date X?".
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use crate::{MPCComputation, PrivateDataSource, Vote};
+use crate::backup;
+
+/// Point in history to read at
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AsOf {
+    Sequence(u64),
+    Timestamp(u64),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ComputationSnapshot {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub status: String,
+    pub votes: Vec<Vote>,
+    pub approvals: Vec<Principal>,
+    pub required_signatures: Vec<Principal>,
+    pub received_signatures: Vec<Principal>,
+    pub shared_with: Vec<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PermissionSnapshot {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub owner: Principal,
+    pub access_permissions: Vec<Principal>,
+}
+
+/// Anything stored with a sequence number and timestamp
+pub trait Snapshot {
+    fn seq(&self) -> u64;
+    fn timestamp(&self) -> u64;
+}
+
+impl Snapshot for ComputationSnapshot {
+    fn seq(&self) -> u64 { self.seq }
+    fn timestamp(&self) -> u64 { self.timestamp }
+}
+
+impl Snapshot for PermissionSnapshot {
+    fn seq(&self) -> u64 { self.seq }
+    fn timestamp(&self) -> u64 { self.timestamp }
+}
+
+thread_local! {
+    static NEXT_SEQ: RefCell<u64> = const { RefCell::new(0) };
+    static COMPUTATION_HISTORY: RefCell<HashMap<String, Vec<ComputationSnapshot>>> = RefCell::new(HashMap::new());
+    static PERMISSION_HISTORY: RefCell<HashMap<String, Vec<PermissionSnapshot>>> = RefCell::new(HashMap::new());
+}
+
+fn next_seq() -> u64 {
+    NEXT_SEQ.with(|seq| {
+        let mut seq = seq.borrow_mut();
+        let current = *seq;
+        *seq += 1;
+        current
+    })
+}
+
+/// Sequence number the next change will get
+pub fn current_sequence() -> u64 {
+    NEXT_SEQ.with(|seq| *seq.borrow())
+}
+
+pub fn snapshot_computation(computation: &MPCComputation) {
+    let snapshot = ComputationSnapshot {
+        seq: next_seq(),
+        timestamp: time(),
+        status: computation.status.clone(),
+        votes: computation.votes.clone(),
+        approvals: computation.approvals.clone(),
+        required_signatures: computation.required_signatures.clone(),
+        received_signatures: computation.received_signatures.clone(),
+        shared_with: computation.shared_with.clone(),
+    };
+    COMPUTATION_HISTORY.with(|history| {
+        history.borrow_mut().entry(computation.id.clone()).or_default().push(snapshot);
+    });
+}
+
+pub fn snapshot_permissions(dataset: &PrivateDataSource) {
+    let snapshot = PermissionSnapshot {
+        seq: next_seq(),
+        timestamp: time(),
+        owner: dataset.owner,
+        access_permissions: dataset.access_permissions.clone(),
+    };
+    PERMISSION_HISTORY.with(|history| {
+        history.borrow_mut().entry(dataset.id.clone()).or_default().push(snapshot);
+    });
+}
+
+/// Latest snapshot at or before `as_of`; snapshots are in sequence order
+pub fn state_at<T: Snapshot + Clone>(snapshots: &[T], as_of: &AsOf) -> Option<T> {
+    let count = match as_of {
+        AsOf::Sequence(seq) => snapshots.partition_point(|s| s.seq() <= *seq),
+        AsOf::Timestamp(ts) => snapshots.partition_point(|s| s.timestamp() <= *ts),
+    };
+    count.checked_sub(1).map(|i| snapshots[i].clone())
+}
+
+pub fn computation_at(request_id: &str, as_of: &AsOf) -> Option<ComputationSnapshot> {
+    COMPUTATION_HISTORY.with(|history| {
+        history.borrow().get(request_id).and_then(|snapshots| state_at(snapshots, as_of))
+    })
+}
+
+pub fn permissions_at(dataset_id: &str, as_of: &AsOf) -> Option<PermissionSnapshot> {
+    PERMISSION_HISTORY.with(|history| {
+        history.borrow().get(dataset_id).and_then(|snapshots| state_at(snapshots, as_of))
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&NEXT_SEQ),
+        backup::cloned(&COMPUTATION_HISTORY),
+        backup::cloned(&PERMISSION_HISTORY),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (next_seq, computation_history, permission_history) = backup::decode(bytes)?;
+    backup::replace(&NEXT_SEQ, next_seq);
+    backup::replace(&COMPUTATION_HISTORY, computation_history);
+    backup::replace(&PERMISSION_HISTORY, permission_history);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod tests;
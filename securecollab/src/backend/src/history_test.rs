@@ -0,0 +1,26 @@
+use super::*;
+
+fn snapshots() -> Vec<PermissionSnapshot> {
+    [(2, 100), (5, 200), (9, 300)].iter().map(|(seq, timestamp)| PermissionSnapshot {
+        seq: *seq,
+        timestamp: *timestamp,
+        owner: Principal::anonymous(),
+        access_permissions: vec![],
+    }).collect()
+}
+
+#[test]
+fn state_at_sequence_picks_latest_preceding_snapshot() {
+    let snapshots = snapshots();
+    assert!(state_at(&snapshots, &AsOf::Sequence(1)).is_none());
+    assert_eq!(state_at(&snapshots, &AsOf::Sequence(2)).unwrap().seq, 2);
+    assert_eq!(state_at(&snapshots, &AsOf::Sequence(8)).unwrap().seq, 5);
+    assert_eq!(state_at(&snapshots, &AsOf::Sequence(100)).unwrap().seq, 9);
+}
+
+#[test]
+fn state_at_timestamp_picks_latest_preceding_snapshot() {
+    let snapshots = snapshots();
+    assert!(state_at(&snapshots, &AsOf::Timestamp(99)).is_none());
+    assert_eq!(state_at(&snapshots, &AsOf::Timestamp(250)).unwrap().seq, 5);
+}
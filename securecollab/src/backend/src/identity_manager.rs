@@ -1,8 +1,26 @@
+//! User identities, vetKD keys and multi-party signatures
+//!
+//! Identities belong to the stable principal Internet Identity derives from
+//! a user's anchor for this app. That principal may delegate to the session
+//! principals of the browsers it signs in on, each for a limited time, and
+//! calls from a session act for the anchor principal here until the session
+//! expires or is revoked.
+
 use ic_cdk::api::{caller, time};
 use candid::Principal;
 use candid::{CandidType, Deserialize};
+use std::cell::Cell;
 use std::collections::HashMap;
 use sha2::{Sha256, Digest};
+use crate::roles::Permission;
+use crate::backup;
+
+/// How often expired vetKD keys are pruned: hourly
+pub const SWEEP_INTERVAL_SECONDS: u64 = 60 * 60;
+/// Longest a device session may act for its anchor, as Internet Identity allows: 30 days
+pub const MAX_SESSION_NANOS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+pub const MAX_SESSIONS_PER_ANCHOR: usize = 20;
+pub const MAX_DEVICE_LABEL_LENGTH: usize = 100;
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct UserIdentity {
@@ -24,6 +42,16 @@ pub struct VetKDKey {
     pub expires_at: Option<u64>,
 }
 
+/// A browser session delegated to act for an anchor-derived principal
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct DeviceSession {
+    pub session_principal: Principal,
+    pub anchor_principal: Principal,
+    pub device: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct MultiPartySignature {
     pub data_hash: String,
@@ -42,11 +70,94 @@ thread_local! {
     
     static MULTI_PARTY_SIGNATURES: std::cell::RefCell<HashMap<String, MultiPartySignature>> = 
         std::cell::RefCell::new(HashMap::new());
+    
+    // Lifetime of keys derived without one of their own; none never expire
+    static DEFAULT_KEY_TTL: Cell<Option<u64>> = const { Cell::new(None) };
+    
+    static DEVICE_SESSIONS: std::cell::RefCell<HashMap<Principal, DeviceSession>> = 
+        std::cell::RefCell::new(HashMap::new());
+}
+
+// Let `session` act for `anchor` until `expires_at`, replacing any earlier
+// delegation of the same session
+pub fn delegate_session(
+    anchor: Principal,
+    session: Principal,
+    device: String,
+    expires_at: u64,
+    now: u64,
+) -> Result<DeviceSession, String> {
+    if anchor == Principal::anonymous() || session == Principal::anonymous() {
+        return Err("Anonymous principals cannot delegate or be delegated to".to_string());
+    }
+    if session == anchor {
+        return Err("A principal cannot delegate to itself".to_string());
+    }
+    if resolve(anchor, now) != anchor {
+        return Err("Sessions cannot delegate further".to_string());
+    }
+    if expires_at <= now || expires_at - now > MAX_SESSION_NANOS {
+        return Err("Sessions must expire within 30 days".to_string());
+    }
+    let device = device.trim().to_string();
+    if device.chars().count() > MAX_DEVICE_LABEL_LENGTH {
+        return Err(format!("Device labels are at most {} characters", MAX_DEVICE_LABEL_LENGTH));
+    }
+    DEVICE_SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        if let Some(existing) = sessions.get(&session) {
+            if existing.anchor_principal != anchor && now < existing.expires_at {
+                return Err("The session already acts for another principal".to_string());
+            }
+        }
+        let active = sessions.values()
+            .filter(|s| s.anchor_principal == anchor && now < s.expires_at && s.session_principal != session)
+            .count();
+        if active >= MAX_SESSIONS_PER_ANCHOR {
+            return Err(format!("At most {} sessions can act for a principal", MAX_SESSIONS_PER_ANCHOR));
+        }
+        let delegated = DeviceSession { session_principal: session, anchor_principal: anchor, device, created_at: now, expires_at };
+        sessions.insert(session, delegated.clone());
+        Ok(delegated)
+    })
+}
+
+// The anchor principal an unexpired session acts for, or the principal itself
+pub fn resolve(principal: Principal, now: u64) -> Principal {
+    DEVICE_SESSIONS.with(|sessions| {
+        sessions.borrow().get(&principal)
+            .filter(|s| now < s.expires_at)
+            .map_or(principal, |s| s.anchor_principal)
+    })
+}
+
+fn acting_principal() -> Principal {
+    resolve(caller(), time())
+}
+
+// Unexpired sessions acting for `anchor`, oldest first
+pub fn sessions_of(anchor: Principal, now: u64) -> Vec<DeviceSession> {
+    let mut sessions: Vec<DeviceSession> = DEVICE_SESSIONS.with(|sessions| {
+        sessions.borrow().values().filter(|s| s.anchor_principal == anchor && now < s.expires_at).cloned().collect()
+    });
+    sessions.sort_by_key(|s| (s.created_at, s.session_principal));
+    sessions
+}
+
+// End a session acting for `anchor`, which no longer acts for it at once
+pub fn revoke_session(anchor: Principal, session: Principal) -> Result<DeviceSession, String> {
+    DEVICE_SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        if sessions.get(&session).map(|s| s.anchor_principal) != Some(anchor) {
+            return Err("No such session acts for you".to_string());
+        }
+        sessions.remove(&session).ok_or_else(|| "No such session acts for you".to_string())
+    })
 }
 
 // Register a new user identity
 pub fn register_identity(permissions: Vec<String>) -> Result<UserIdentity, String> {
-    let principal = caller();
+    let principal = acting_principal();
     let principal_text = principal.to_text();
     
     if principal == Principal::anonymous() {
@@ -79,7 +190,7 @@ pub fn register_identity(permissions: Vec<String>) -> Result<UserIdentity, Strin
 
 // Get user identity
 pub fn get_identity() -> Result<UserIdentity, String> {
-    let principal = caller();
+    let principal = acting_principal();
     let principal_text = principal.to_text();
     
     USER_IDENTITIES.with(|identities| {
@@ -89,15 +200,42 @@ pub fn get_identity() -> Result<UserIdentity, String> {
     })
 }
 
-// Derive vetKD key for a specific purpose
-pub fn derive_vetkd_key(purpose: String, derivation_path: Vec<u8>) -> Result<VetKDKey, String> {
-    let principal = caller();
+// Set the lifetime in nanoseconds of keys derived without one of their own
+pub fn set_default_key_ttl(ttl: Option<u64>) -> Result<(), String> {
+    if ttl == Some(0) {
+        return Err("Key lifetimes must be positive".to_string());
+    }
+    DEFAULT_KEY_TTL.with(|default| default.set(ttl));
+    Ok(())
+}
+
+pub fn default_key_ttl() -> Option<u64> {
+    DEFAULT_KEY_TTL.with(Cell::get)
+}
+
+pub fn is_expired(key: &VetKDKey, now: u64) -> bool {
+    key.expires_at.is_some_and(|expires_at| now >= expires_at)
+}
+
+// Derive vetKD key for a specific purpose, expiring after `ttl` nanoseconds
+// or the default lifetime
+pub fn derive_vetkd_key(purpose: String, derivation_path: Vec<u8>, ttl: Option<u64>) -> Result<VetKDKey, String> {
+    if ttl == Some(0) {
+        return Err("Key lifetimes must be positive".to_string());
+    }
+    let principal = acting_principal();
     let identity = get_identity()?;
     
     let key_id = format!("{}:{}:{}", principal.to_text(), purpose, hex::encode(&derivation_path));
+    crate::key_revocation::ensure_not_revoked(&identity.vetkey_id)?;
+    crate::key_revocation::ensure_not_revoked(&key_id)?;
     
     // Check if key already exists
+    let now = time();
     if let Some(existing_key) = VETKD_KEYS.with(|keys| keys.borrow().get(&key_id).cloned()) {
+        if is_expired(&existing_key, now) {
+            return Err(format!("Key {} has expired", key_id));
+        }
         return Ok(existing_key);
     }
     
@@ -109,8 +247,8 @@ pub fn derive_vetkd_key(purpose: String, derivation_path: Vec<u8>) -> Result<Vet
         owner: principal,
         derived_key,
         key_derivation_path: derivation_path,
-        created_at: time(),
-        expires_at: None, // Keys don't expire by default
+        created_at: now,
+        expires_at: ttl.or_else(default_key_ttl).map(|ttl| now.saturating_add(ttl)),
     };
 
     VETKD_KEYS.with(|keys| {
@@ -149,7 +287,7 @@ pub fn create_signature_requirement(
 
 // Add signature to multi-party signature
 pub fn add_signature(signature_id: String, signature: String) -> Result<bool, String> {
-    let principal = caller();
+    let principal = acting_principal();
     let principal_text = principal.to_text();
     
     MULTI_PARTY_SIGNATURES.with(|sigs| {
@@ -181,6 +319,15 @@ pub fn verify_signature_complete(signature_id: String) -> Result<bool, String> {
     })
 }
 
+// Revoke a multi-party signature requirement so it can no longer be completed
+pub fn revoke_signature_requirement(signature_id: String) -> Result<(), String> {
+    MULTI_PARTY_SIGNATURES.with(|sigs| {
+        sigs.borrow_mut().remove(&signature_id)
+            .map(|_| ())
+            .ok_or_else(|| "Signature requirement not found".to_string())
+    })
+}
+
 // Get signatures for verification
 pub fn get_signatures(signature_id: String) -> Result<MultiPartySignature, String> {
     MULTI_PARTY_SIGNATURES.with(|sigs| {
@@ -190,6 +337,76 @@ pub fn get_signatures(signature_id: String) -> Result<MultiPartySignature, Strin
     })
 }
 
+// Move identity, keys and signer slots from a lost principal to its replacement
+pub fn rebind_principal(old: Principal, new: Principal) {
+    let old_text = old.to_text();
+    let new_text = new.to_text();
+    
+    USER_IDENTITIES.with(|identities| {
+        let mut identities_map = identities.borrow_mut();
+        if let Some(mut identity) = identities_map.remove(&old_text) {
+            identity.principal = new;
+            identity.last_active = time();
+            identities_map.insert(new_text.clone(), identity);
+        }
+    });
+    
+    VETKD_KEYS.with(|keys| {
+        for key in keys.borrow_mut().values_mut() {
+            if key.owner == old {
+                key.owner = new;
+            }
+        }
+    });
+    
+    MULTI_PARTY_SIGNATURES.with(|sigs| {
+        for multi_sig in sigs.borrow_mut().values_mut() {
+            for signer in multi_sig.required_signers.iter_mut() {
+                if *signer == old_text {
+                    *signer = new_text.clone();
+                }
+            }
+            if let Some(signature) = multi_sig.signatures.remove(&old_text) {
+                multi_sig.signatures.insert(new_text.clone(), signature);
+            }
+        }
+    });
+}
+
+// Remove the keys expired at `now`, returning their ids
+pub fn prune_expired(keys: &mut HashMap<String, VetKDKey>, now: u64) -> Vec<String> {
+    let mut expired: Vec<String> = keys.values().filter(|k| is_expired(k, now)).map(|k| k.key_id.clone()).collect();
+    expired.sort();
+    for key_id in &expired {
+        keys.remove(key_id);
+    }
+    expired
+}
+
+// Prune expired keys from the key store
+pub fn sweep_expired_keys(now: u64) -> Vec<String> {
+    VETKD_KEYS.with(|keys| prune_expired(&mut keys.borrow_mut(), now))
+}
+
+// Drop a revoked key from the cache, or every key derived from a revoked
+// identity key; returns the ids of the keys dropped other than `key_id`
+pub fn invalidate(key_id: &str) -> Vec<String> {
+    let owners: Vec<Principal> = USER_IDENTITIES.with(|identities| {
+        identities.borrow().values().filter(|i| i.vetkey_id == key_id).map(|i| i.principal).collect()
+    });
+    VETKD_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let dropped: Vec<String> = keys.values()
+            .filter(|k| k.key_id == key_id || owners.contains(&k.owner))
+            .map(|k| k.key_id.clone())
+            .collect();
+        for id in &dropped {
+            keys.remove(id);
+        }
+        dropped.into_iter().filter(|id| id != key_id).collect()
+    })
+}
+
 // Helper functions
 fn generate_vetkey_id(principal: &Principal) -> String {
     let mut hasher = Sha256::new();
@@ -220,7 +437,7 @@ fn derive_key_from_vetkd(vetkey_id: &str, derivation_path: &[u8]) -> Result<Vec<
 // Encrypt data with party-specific vetKD key
 pub fn encrypt_with_vetkd(data: &[u8], purpose: String) -> Result<Vec<u8>, String> {
     let derivation_path = purpose.as_bytes().to_vec();
-    let vetkd_key = derive_vetkd_key(purpose, derivation_path)?;
+    let vetkd_key = derive_vetkd_key(purpose, derivation_path, None)?;
     
     // XOR encryption with derived key (for IC compatibility)
     let key_bytes = &vetkd_key.derived_key;
@@ -240,20 +457,15 @@ pub fn decrypt_with_vetkd(encrypted_data: &[u8], purpose: String) -> Result<Vec<
     encrypt_with_vetkd(encrypted_data, purpose)
 }
 
-// Check if caller has permission
-pub fn check_permission(required_permission: &str) -> Result<(), String> {
-    let identity = get_identity()?;
-    
-    if identity.permissions.contains(&required_permission.to_string()) {
-        Ok(())
-    } else {
-        Err(format!("Permission denied: {} required", required_permission))
-    }
+// Check the caller's organization roles grant a permission; the
+// permissions an identity registered with are not trusted
+pub fn check_permission(required_permission: Permission) -> Result<(), String> {
+    crate::roles::require(acting_principal(), required_permission)
 }
 
 // Update user activity
 pub fn update_activity() -> Result<(), String> {
-    let principal = caller();
+    let principal = acting_principal();
     let principal_text = principal.to_text();
     
     USER_IDENTITIES.with(|identities| {
@@ -266,3 +478,27 @@ pub fn update_activity() -> Result<(), String> {
         }
     })
 }
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&USER_IDENTITIES),
+        backup::cloned(&VETKD_KEYS),
+        backup::cloned(&MULTI_PARTY_SIGNATURES),
+        DEFAULT_KEY_TTL.with(Cell::get),
+        backup::cloned(&DEVICE_SESSIONS),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (user_identities, vetkd_keys, multi_party_signatures, default_key_ttl, device_sessions) = backup::decode(bytes)?;
+    backup::replace(&USER_IDENTITIES, user_identities);
+    backup::replace(&VETKD_KEYS, vetkd_keys);
+    backup::replace(&MULTI_PARTY_SIGNATURES, multi_party_signatures);
+    DEFAULT_KEY_TTL.with(|cell| cell.set(default_key_ttl));
+    backup::replace(&DEVICE_SESSIONS, device_sessions);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "identity_manager_test.rs"]
+mod tests;
@@ -0,0 +1,91 @@
+use super::*;
+use crate::test_support::principal;
+
+fn key(key_id: &str, expires_at: Option<u64>) -> VetKDKey {
+    VetKDKey {
+        key_id: key_id.to_string(),
+        owner: Principal::anonymous(),
+        derived_key: vec![1, 2, 3],
+        key_derivation_path: b"analysis".to_vec(),
+        created_at: 0,
+        expires_at,
+    }
+}
+
+#[test]
+fn default_lifetime_is_positive_or_none() {
+    assert_eq!(default_key_ttl(), None);
+    set_default_key_ttl(Some(30)).unwrap();
+    assert!(set_default_key_ttl(Some(0)).is_err());
+    assert_eq!(default_key_ttl(), Some(30));
+    set_default_key_ttl(None).unwrap();
+    assert_eq!(default_key_ttl(), None);
+}
+
+#[test]
+fn expired_keys_are_pruned() {
+    assert!(!is_expired(&key("forever", None), u64::MAX));
+    assert!(!is_expired(&key("later", Some(200)), 199));
+    assert!(is_expired(&key("later", Some(200)), 200));
+
+    let mut keys: HashMap<String, VetKDKey> = [key("forever", None), key("early", Some(100)), key("late", Some(300))]
+        .into_iter()
+        .map(|k| (k.key_id.clone(), k))
+        .collect();
+    assert_eq!(prune_expired(&mut keys, 200), vec!["early".to_string()]);
+    assert_eq!(keys.len(), 2);
+    assert!(prune_expired(&mut keys, 250).is_empty());
+    assert_eq!(prune_expired(&mut keys, 300), vec!["late".to_string()]);
+    assert!(keys.contains_key("forever"));
+}
+
+#[test]
+fn sessions_act_for_their_anchor_until_they_expire() {
+    let (anchor, laptop, phone) = (principal(1), principal(2), principal(3));
+    assert_eq!(resolve(laptop, 0), laptop);
+    delegate_session(anchor, laptop, " Laptop ".to_string(), 100, 10).unwrap();
+    delegate_session(anchor, phone, "Phone".to_string(), 200, 20).unwrap();
+    assert_eq!(resolve(laptop, 99), anchor);
+    assert_eq!(resolve(laptop, 100), laptop);
+
+    let devices: Vec<String> = sessions_of(anchor, 50).into_iter().map(|s| s.device).collect();
+    assert_eq!(devices, vec!["Laptop", "Phone"]);
+    assert_eq!(sessions_of(anchor, 150).len(), 1);
+}
+
+#[test]
+fn delegations_are_bounded() {
+    let (anchor, session, other) = (principal(4), principal(5), principal(6));
+    assert!(delegate_session(anchor, anchor, String::new(), 100, 0).is_err());
+    assert!(delegate_session(Principal::anonymous(), session, String::new(), 100, 0).is_err());
+    assert!(delegate_session(anchor, session, String::new(), 0, 0).is_err());
+    assert!(delegate_session(anchor, session, String::new(), MAX_SESSION_NANOS + 1, 0).is_err());
+    assert!(delegate_session(anchor, session, "x".repeat(MAX_DEVICE_LABEL_LENGTH + 1), 100, 0).is_err());
+
+    delegate_session(anchor, session, String::new(), 100, 0).unwrap();
+    // A session neither delegates further nor acts for two principals
+    assert!(delegate_session(session, principal(7), String::new(), 100, 0).is_err());
+    assert!(delegate_session(other, session, String::new(), 100, 0).is_err());
+    assert!(delegate_session(other, session, String::new(), 300, 200).is_ok());
+}
+
+#[test]
+fn revoked_sessions_stop_acting_at_once() {
+    let (anchor, session, stranger) = (principal(8), principal(9), principal(10));
+    delegate_session(anchor, session, "Laptop".to_string(), 100, 0).unwrap();
+    assert!(revoke_session(stranger, session).is_err());
+    assert_eq!(revoke_session(anchor, session).unwrap().device, "Laptop");
+    assert_eq!(resolve(session, 10), session);
+    assert!(revoke_session(anchor, session).is_err());
+}
+
+#[test]
+fn caps_sessions_per_anchor() {
+    let anchor = principal(11);
+    for i in 0..MAX_SESSIONS_PER_ANCHOR {
+        delegate_session(anchor, principal(100 + i as u8), String::new(), 100, 0).unwrap();
+    }
+    assert!(delegate_session(anchor, principal(200), String::new(), 100, 0).is_err());
+    // Renewing an existing session is not a new one
+    assert!(delegate_session(anchor, principal(100), String::new(), 150, 0).is_ok());
+}
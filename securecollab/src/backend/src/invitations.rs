@@ -0,0 +1,179 @@
+//! One-time invitations to join as a party
+//!
+//! Registration needs an invitation. An organization admin invites a
+//! principal, or the SHA-256 of an email address when the invitee has no
+//! principal yet, to a role in their organization; canister controllers
+//! invite parties to the default workspace. The invitation yields a random
+//! code, stored only as its hash, that is redeemed once on registration.
+//! A principal invitation only redeems for that principal, while an email
+//! invitation's code is delivered out of band and redeems for whoever holds it.
+//!
+//! The inviter's admin rights are checked again on redemption, so an
+//! invitation is only as good as its inviter's standing.
+
+use candid::{CandidType, Deserialize, Principal};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::roles::Role;
+use crate::DEFAULT_WORKSPACE;
+use crate::backup;
+
+/// Default lifetime of an invitation; see `config`
+pub const INVITATION_TTL_NANOS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+pub const MAX_OPEN_INVITATIONS: usize = 100;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum Invitee {
+    Principal(Principal),
+    /// Hex SHA-256 of the invitee's lowercased email address
+    EmailHash(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Invitation {
+    /// First 16 hex characters of the code's hash
+    pub id: String,
+    pub workspace: String,
+    pub invitee: Invitee,
+    pub role: Role,
+    pub invited_by: Principal,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub redeemed_by: Option<Principal>,
+    pub redeemed_at: Option<u64>,
+}
+
+/// What the inviter passes on to the invitee
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InvitationCode {
+    pub code: String,
+    pub invitation: Invitation,
+}
+
+thread_local! {
+    // Keyed by the hex SHA-256 of the code
+    static INVITATIONS: RefCell<HashMap<String, Invitation>> = RefCell::new(HashMap::new());
+}
+
+fn code_hash(code: &str) -> String {
+    hex::encode(Sha256::digest(code.trim().as_bytes()))
+}
+
+fn validate_invitee(invitee: &Invitee) -> Result<(), String> {
+    match invitee {
+        Invitee::Principal(principal) if *principal == Principal::anonymous() => {
+            Err("Anonymous principals cannot be invited".to_string())
+        }
+        Invitee::EmailHash(hash) if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Err("Email hashes are 64 hex characters of SHA-256".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Record an invitation to `workspace` under a freshly drawn `code`
+pub fn issue(
+    code: String,
+    workspace: String,
+    invitee: Invitee,
+    role: Role,
+    invited_by: Principal,
+    now: u64,
+) -> Result<InvitationCode, String> {
+    validate_invitee(&invitee)?;
+    if workspace == DEFAULT_WORKSPACE && role == Role::Admin {
+        return Err("The default workspace has no admins".to_string());
+    }
+    let hash = code_hash(&code);
+    let invitation = Invitation {
+        id: hash[..16].to_string(),
+        workspace,
+        invitee,
+        role,
+        invited_by,
+        created_at: now,
+        expires_at: now.saturating_add(crate::config::settings().invitation_expiry_seconds.saturating_mul(1_000_000_000)),
+        redeemed_by: None,
+        redeemed_at: None,
+    };
+    INVITATIONS.with(|invitations| {
+        let mut invitations = invitations.borrow_mut();
+        let open = invitations.values()
+            .filter(|i| i.workspace == invitation.workspace && i.redeemed_by.is_none() && i.expires_at > now)
+            .count();
+        if open >= MAX_OPEN_INVITATIONS {
+            return Err(format!("A workspace has at most {} open invitations", MAX_OPEN_INVITATIONS));
+        }
+        invitations.insert(hash, invitation.clone());
+        Ok(InvitationCode { code, invitation })
+    })
+}
+
+/// Spend an invitation for `redeemer`, returning it for the caller to act on
+pub fn redeem(code: &str, redeemer: Principal, now: u64) -> Result<Invitation, String> {
+    INVITATIONS.with(|invitations| {
+        let mut invitations = invitations.borrow_mut();
+        let invitation = invitations.get_mut(&code_hash(code)).ok_or("Invalid invitation code")?;
+        if invitation.redeemed_by.is_some() {
+            return Err("This invitation has already been used".to_string());
+        }
+        if now >= invitation.expires_at {
+            return Err("This invitation has expired".to_string());
+        }
+        if matches!(invitation.invitee, Invitee::Principal(p) if p != redeemer) {
+            return Err("This invitation is for another principal".to_string());
+        }
+        invitation.redeemed_by = Some(redeemer);
+        invitation.redeemed_at = Some(now);
+        Ok(invitation.clone())
+    })
+}
+
+/// Put back an invitation whose registration failed after redeeming it
+pub fn restore(code: &str) {
+    INVITATIONS.with(|invitations| {
+        if let Some(invitation) = invitations.borrow_mut().get_mut(&code_hash(code)) {
+            invitation.redeemed_by = None;
+            invitation.redeemed_at = None;
+        }
+    });
+}
+
+/// Invitations of a workspace, most recent first
+pub fn list(workspace: &str) -> Vec<Invitation> {
+    let mut listed: Vec<Invitation> = INVITATIONS.with(|invitations| {
+        invitations.borrow().values().filter(|i| i.workspace == workspace).cloned().collect()
+    });
+    listed.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+    listed
+}
+
+/// Withdraw an unused invitation of a workspace
+pub fn withdraw(workspace: &str, id: &str) -> Result<Invitation, String> {
+    INVITATIONS.with(|invitations| {
+        let mut invitations = invitations.borrow_mut();
+        let (hash, used) = invitations.iter()
+            .find(|(_, i)| i.workspace == workspace && i.id == id)
+            .map(|(hash, i)| (hash.clone(), i.redeemed_by.is_some()))
+            .ok_or_else(|| format!("Invitation {} not found", id))?;
+        if used {
+            return Err("A used invitation cannot be withdrawn".to_string());
+        }
+        invitations.remove(&hash).ok_or_else(|| format!("Invitation {} not found", id))
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&INVITATIONS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let invitations = backup::decode(bytes)?;
+    backup::replace(&INVITATIONS, invitations);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "invitations_test.rs"]
+mod tests;
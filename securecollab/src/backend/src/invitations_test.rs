@@ -0,0 +1,51 @@
+use super::*;
+use crate::test_support::principal;
+
+fn invite(code: &str, invitee: Invitee, now: u64) -> Result<InvitationCode, String> {
+    issue(code.to_string(), "org_lab".to_string(), invitee, Role::Analyst, principal(1), now)
+}
+
+#[test]
+fn principal_invitations_redeem_once_for_their_principal() {
+    let (invitee, other) = (principal(2), principal(3));
+    let issued = invite("code-a", Invitee::Principal(invitee), 100).unwrap();
+    assert_eq!(issued.invitation.expires_at, 100 + INVITATION_TTL_NANOS);
+
+    assert!(redeem("code-b", invitee, 200).is_err());
+    assert!(redeem("code-a", other, 200).is_err());
+    let redeemed = redeem(" code-a ", invitee, 200).unwrap();
+    assert_eq!((redeemed.redeemed_by, redeemed.role), (Some(invitee), Role::Analyst));
+    assert!(redeem("code-a", invitee, 300).is_err());
+
+    // A failed registration can try again
+    restore("code-a");
+    assert!(redeem("code-a", invitee, 300).is_ok());
+}
+
+#[test]
+fn email_invitations_are_bearer_codes_until_they_expire() {
+    let hash = hex::encode(Sha256::digest(b"researcher@example.org"));
+    assert!(invite("bad", Invitee::EmailHash("not-a-hash".to_string()), 0).is_err());
+    assert!(invite("anon", Invitee::Principal(Principal::anonymous()), 0).is_err());
+    invite("code-email", Invitee::EmailHash(hash.clone()), 0).unwrap();
+    invite("code-late", Invitee::EmailHash(hash), 0).unwrap();
+
+    assert!(redeem("code-late", principal(4), INVITATION_TTL_NANOS).is_err());
+    assert!(redeem("code-email", principal(5), INVITATION_TTL_NANOS - 1).is_ok());
+}
+
+#[test]
+fn workspaces_list_and_withdraw_their_invitations() {
+    assert!(issue("code-admin".to_string(), DEFAULT_WORKSPACE.to_string(), Invitee::Principal(principal(6)), Role::Admin, principal(1), 0).is_err());
+    let first = invite("code-1", Invitee::Principal(principal(7)), 10).unwrap().invitation;
+    let second = invite("code-2", Invitee::Principal(principal(8)), 20).unwrap().invitation;
+    let ids: Vec<String> = list("org_lab").into_iter().map(|i| i.id).collect();
+    assert_eq!(ids, vec![second.id.clone(), first.id.clone()]);
+    assert!(list(DEFAULT_WORKSPACE).is_empty());
+
+    redeem("code-2", principal(8), 30).unwrap();
+    assert!(withdraw("org_lab", &second.id).is_err());
+    assert!(withdraw(DEFAULT_WORKSPACE, &first.id).is_err());
+    withdraw("org_lab", &first.id).unwrap();
+    assert!(redeem("code-1", principal(7), 30).is_err());
+}
@@ -0,0 +1,318 @@
+//! Commit-reveal key ceremony for initial workspace setup
+//!
+//! Each founding party first commits to SHA-256(entropy), then reveals the
+//! entropy once every party has committed. A root context is derived from all
+//! revealed contributions, so no single party can bias it, and published with
+//! the transcript for parties to bind their own workspace keys to; the
+//! canister's key derivation does not use it. Only hashes are kept in the
+//! transcript for later audit.
+//!
+//! A ceremony that has not completed by its deadline can be aborted by any
+//! founding party, and by its initiator or a workspace admin at any time, so
+//! the workspace can start a new one.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::{BTreeMap, HashMap};
+use std::cell::RefCell;
+use sha2::{Sha256, Digest};
+use crate::backup;
+
+/// Minimum entropy a party must contribute
+const MIN_ENTROPY_BYTES: usize = 32;
+/// Time the founding parties have to commit and reveal (7 days)
+const CEREMONY_DURATION_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CeremonyPhase {
+    Commit,
+    Reveal,
+    Complete,
+    Aborted,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CeremonyContribution {
+    pub party: Principal,
+    pub commitment: Option<Vec<u8>>,
+    pub revealed: bool,
+    pub committed_at: Option<u64>,
+    pub revealed_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TranscriptEntry {
+    pub step: String,
+    pub party: Principal,
+    pub hash: String,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct KeyCeremony {
+    pub workspace: String,
+    pub initiator: Principal,
+    pub phase: CeremonyPhase,
+    pub contributions: Vec<CeremonyContribution>,
+    pub transcript: Vec<TranscriptEntry>,
+    pub root_context: Option<Vec<u8>>,
+    pub transcript_hash: Option<String>,
+    pub created_at: u64,
+    /// Commitments and reveals are refused after this time
+    pub deadline: u64,
+    pub completed_at: Option<u64>,
+}
+
+// Revealed entropy per party, ordered by principal
+type RevealedEntropy = BTreeMap<Principal, Vec<u8>>;
+
+thread_local! {
+    static CEREMONIES: RefCell<HashMap<String, KeyCeremony>> = RefCell::new(HashMap::new());
+    // Revealed entropy is only needed until the root context is derived
+    static REVEALED_ENTROPY: RefCell<HashMap<String, RevealedEntropy>> = RefCell::new(HashMap::new());
+}
+
+/// Open a ceremony for a workspace with its founding parties; an aborted
+/// ceremony is replaced
+pub fn start(workspace: String, initiator: Principal, parties: Vec<Principal>, now: u64) -> Result<KeyCeremony, String> {
+    if parties.len() < 2 {
+        return Err("A key ceremony needs at least 2 founding parties".to_string());
+    }
+    if !parties.contains(&initiator) {
+        return Err("Initiator must be one of the founding parties".to_string());
+    }
+
+    let mut unique = parties.clone();
+    unique.sort();
+    unique.dedup();
+    if unique.len() != parties.len() {
+        return Err("Founding parties must be distinct".to_string());
+    }
+
+    CEREMONIES.with(|ceremonies| {
+        let mut ceremonies = ceremonies.borrow_mut();
+        if ceremonies.get(&workspace).is_some_and(|c| c.phase != CeremonyPhase::Aborted) {
+            return Err(format!("Key ceremony for workspace {} already exists", workspace));
+        }
+
+        let ceremony = KeyCeremony {
+            workspace: workspace.clone(),
+            initiator,
+            phase: CeremonyPhase::Commit,
+            contributions: parties.iter().map(|party| CeremonyContribution {
+                party: *party,
+                commitment: None,
+                revealed: false,
+                committed_at: None,
+                revealed_at: None,
+            }).collect(),
+            transcript: vec![TranscriptEntry {
+                step: "start".to_string(),
+                party: initiator,
+                hash: hash_parties(&workspace, &parties),
+                timestamp: now,
+            }],
+            root_context: None,
+            transcript_hash: None,
+            created_at: now,
+            deadline: now + CEREMONY_DURATION_NS,
+            completed_at: None,
+        };
+
+        ceremonies.insert(workspace, ceremony.clone());
+        Ok(ceremony)
+    })
+}
+
+/// Record a party's commitment to SHA-256(entropy)
+pub fn commit(workspace: &str, party: Principal, commitment: Vec<u8>, now: u64) -> Result<CeremonyPhase, String> {
+    if commitment.len() != 32 {
+        return Err("Commitment must be a 32-byte SHA-256 digest".to_string());
+    }
+
+    with_ceremony(workspace, |ceremony| {
+        if ceremony.phase != CeremonyPhase::Commit {
+            return Err(format!("Ceremony is in {:?} phase, commitments are closed", ceremony.phase));
+        }
+        ensure_before_deadline(ceremony, now)?;
+
+        let contribution = ceremony.contributions.iter_mut()
+            .find(|c| c.party == party)
+            .ok_or_else(|| "Caller is not a founding party of this ceremony".to_string())?;
+        if contribution.commitment.is_some() {
+            return Err("Commitment already submitted".to_string());
+        }
+
+        contribution.commitment = Some(commitment.clone());
+        contribution.committed_at = Some(now);
+        ceremony.transcript.push(TranscriptEntry {
+            step: "commit".to_string(),
+            party,
+            hash: hex::encode(&commitment),
+            timestamp: now,
+        });
+
+        if ceremony.contributions.iter().all(|c| c.commitment.is_some()) {
+            ceremony.phase = CeremonyPhase::Reveal;
+        }
+        Ok(ceremony.phase.clone())
+    })
+}
+
+/// Reveal entropy matching an earlier commitment; completes the ceremony
+/// once every party has revealed
+pub fn reveal(workspace: &str, party: Principal, entropy: Vec<u8>, now: u64) -> Result<CeremonyPhase, String> {
+    if entropy.len() < MIN_ENTROPY_BYTES {
+        return Err(format!("Entropy must be at least {} bytes", MIN_ENTROPY_BYTES));
+    }
+
+    with_ceremony(workspace, |ceremony| {
+        if ceremony.phase != CeremonyPhase::Reveal {
+            return Err(format!("Ceremony is in {:?} phase, reveals are not accepted", ceremony.phase));
+        }
+        ensure_before_deadline(ceremony, now)?;
+
+        let contribution = ceremony.contributions.iter_mut()
+            .find(|c| c.party == party)
+            .ok_or_else(|| "Caller is not a founding party of this ceremony".to_string())?;
+        if contribution.revealed {
+            return Err("Entropy already revealed".to_string());
+        }
+
+        let entropy_hash = Sha256::digest(&entropy).to_vec();
+        if contribution.commitment.as_ref() != Some(&entropy_hash) {
+            return Err("Revealed entropy does not match commitment".to_string());
+        }
+
+        contribution.revealed = true;
+        contribution.revealed_at = Some(now);
+        ceremony.transcript.push(TranscriptEntry {
+            step: "reveal".to_string(),
+            party,
+            hash: hex::encode(&entropy_hash),
+            timestamp: now,
+        });
+
+        REVEALED_ENTROPY.with(|revealed| {
+            revealed.borrow_mut()
+                .entry(workspace.to_string())
+                .or_default()
+                .insert(party, entropy.clone());
+        });
+
+        if ceremony.contributions.iter().all(|c| c.revealed) {
+            finalize(ceremony, now);
+        }
+        Ok(ceremony.phase.clone())
+    })
+}
+
+/// Abort an unfinished ceremony and discard the entropy revealed so far.
+/// `is_workspace_admin` lets workspace admins abort before the deadline.
+pub fn abort(workspace: &str, party: Principal, is_workspace_admin: bool, now: u64) -> Result<KeyCeremony, String> {
+    let ceremony = with_ceremony(workspace, |ceremony| {
+        if matches!(ceremony.phase, CeremonyPhase::Complete | CeremonyPhase::Aborted) {
+            return Err(format!("Ceremony is already {:?}", ceremony.phase));
+        }
+        let is_founder = ceremony.contributions.iter().any(|c| c.party == party);
+        let expired = now > ceremony.deadline;
+        if !(is_workspace_admin || party == ceremony.initiator || (is_founder && expired)) {
+            return Err("Only the initiator or a workspace admin can abort a ceremony before its deadline".to_string());
+        }
+
+        ceremony.transcript.push(TranscriptEntry {
+            step: "abort".to_string(),
+            party,
+            hash: hex::encode(Sha256::digest(ceremony.workspace.as_bytes())),
+            timestamp: now,
+        });
+        ceremony.phase = CeremonyPhase::Aborted;
+        Ok(ceremony.clone())
+    })?;
+    REVEALED_ENTROPY.with(|revealed| revealed.borrow_mut().remove(workspace));
+    Ok(ceremony)
+}
+
+fn ensure_before_deadline(ceremony: &KeyCeremony, now: u64) -> Result<(), String> {
+    if now > ceremony.deadline {
+        return Err(format!("Ceremony deadline {} has passed; abort it and start a new one", ceremony.deadline));
+    }
+    Ok(())
+}
+
+/// Get a ceremony and its transcript
+pub fn get(workspace: &str) -> Option<KeyCeremony> {
+    CEREMONIES.with(|ceremonies| ceremonies.borrow().get(workspace).cloned())
+}
+
+fn with_ceremony<T>(
+    workspace: &str,
+    f: impl FnOnce(&mut KeyCeremony) -> Result<T, String>,
+) -> Result<T, String> {
+    CEREMONIES.with(|ceremonies| {
+        let mut ceremonies = ceremonies.borrow_mut();
+        let ceremony = ceremonies.get_mut(workspace)
+            .ok_or_else(|| format!("No key ceremony for workspace {}", workspace))?;
+        f(ceremony)
+    })
+}
+
+/// Derive the root derivation context and seal the transcript
+fn finalize(ceremony: &mut KeyCeremony, now: u64) {
+    // Iterating in principal order keeps the result independent of reveal order
+    let contributions = REVEALED_ENTROPY.with(|revealed| {
+        revealed.borrow_mut().remove(&ceremony.workspace).unwrap_or_default()
+    });
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"securecollab-workspace-root");
+    hasher.update(ceremony.workspace.as_bytes());
+    for (party, entropy) in &contributions {
+        hasher.update(party.as_slice());
+        hasher.update(entropy);
+    }
+    let root_context = hasher.finalize().to_vec();
+
+    ceremony.transcript.push(TranscriptEntry {
+        step: "complete".to_string(),
+        party: ceremony.initiator,
+        hash: hex::encode(Sha256::digest(&root_context)),
+        timestamp: now,
+    });
+
+    let mut transcript_hasher = Sha256::new();
+    for entry in &ceremony.transcript {
+        transcript_hasher.update(entry.step.as_bytes());
+        transcript_hasher.update(entry.party.as_slice());
+        transcript_hasher.update(entry.hash.as_bytes());
+        transcript_hasher.update(entry.timestamp.to_be_bytes());
+    }
+
+    ceremony.root_context = Some(root_context);
+    ceremony.transcript_hash = Some(hex::encode(transcript_hasher.finalize()));
+    ceremony.phase = CeremonyPhase::Complete;
+    ceremony.completed_at = Some(now);
+}
+
+fn hash_parties(workspace: &str, parties: &[Principal]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(workspace.as_bytes());
+    for party in parties {
+        hasher.update(party.as_slice());
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&CEREMONIES), backup::cloned(&REVEALED_ENTROPY)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (ceremonies, revealed_entropy) = backup::decode(bytes)?;
+    backup::replace(&CEREMONIES, ceremonies);
+    backup::replace(&REVEALED_ENTROPY, revealed_entropy);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "key_ceremony_test.rs"]
+mod tests;
@@ -0,0 +1,58 @@
+use super::*;
+use crate::test_support::principal;
+
+fn entropy(byte: u8) -> Vec<u8> {
+    vec![byte; MIN_ENTROPY_BYTES]
+}
+
+fn commitment(byte: u8) -> Vec<u8> {
+    Sha256::digest(entropy(byte)).to_vec()
+}
+
+#[test]
+fn parties_commit_then_reveal_to_derive_the_root_context() {
+    let (a, b) = (principal(1), principal(2));
+    assert!(start("org_a".to_string(), a, vec![a], 0).is_err());
+    assert!(start("org_a".to_string(), principal(3), vec![a, b], 0).is_err());
+    assert!(start("org_a".to_string(), a, vec![a, a], 0).is_err());
+    start("org_a".to_string(), a, vec![a, b], 0).unwrap();
+    assert!(start("org_a".to_string(), a, vec![a, b], 1).is_err());
+
+    assert!(reveal("org_a", a, entropy(1), 1).is_err());
+    assert_eq!(commit("org_a", a, commitment(1), 1).unwrap(), CeremonyPhase::Commit);
+    assert!(commit("org_a", a, commitment(1), 1).is_err());
+    assert!(commit("org_a", principal(3), commitment(3), 1).is_err());
+    assert_eq!(commit("org_a", b, commitment(2), 2).unwrap(), CeremonyPhase::Reveal);
+
+    assert!(reveal("org_a", a, entropy(9), 3).unwrap_err().contains("does not match"));
+    assert_eq!(reveal("org_a", b, entropy(2), 3).unwrap(), CeremonyPhase::Reveal);
+    assert_eq!(reveal("org_a", a, entropy(1), 4).unwrap(), CeremonyPhase::Complete);
+
+    let ceremony = get("org_a").unwrap();
+    assert_eq!(ceremony.completed_at, Some(4));
+    assert!(ceremony.root_context.is_some() && ceremony.transcript_hash.is_some());
+    assert_eq!(ceremony.transcript.len(), 6);
+    assert!(abort("org_a", a, true, 5).is_err());
+}
+
+#[test]
+fn unfinished_ceremonies_expire_and_can_be_aborted() {
+    let (a, b, c) = (principal(1), principal(2), principal(3));
+    start("org_a".to_string(), a, vec![a, b, c], 0).unwrap();
+    commit("org_a", a, commitment(1), 1).unwrap();
+    let expired = CEREMONY_DURATION_NS + 1;
+    assert!(commit("org_a", b, commitment(2), expired).unwrap_err().contains("deadline"));
+
+    // Before the deadline only the initiator or a workspace admin may abort
+    assert!(abort("org_a", b, false, 2).is_err());
+    assert!(abort("org_a", principal(4), false, expired).is_err());
+    assert_eq!(abort("org_a", b, false, expired).unwrap().phase, CeremonyPhase::Aborted);
+    assert!(abort("org_a", a, true, expired).is_err());
+    assert!(commit("org_a", b, commitment(2), expired).is_err());
+
+    // An aborted ceremony is replaced by a new one
+    let restarted = start("org_a".to_string(), b, vec![b, c], expired).unwrap();
+    assert_eq!(restarted.phase, CeremonyPhase::Commit);
+    assert_eq!(restarted.deadline, expired + CEREMONY_DURATION_NS);
+    assert_eq!(abort("org_a", c, true, expired).unwrap().phase, CeremonyPhase::Aborted);
+}
@@ -0,0 +1,97 @@
+//! Registry of revoked keys
+//!
+//! Any key id can be revoked: the canister's dataset and result keys
+//! (`vetkey_{principal}_{path}`), identity keys (`{principal}:{purpose}:{path}`)
+//! and agent keys, named by their agent or verification hash. Revocation is
+//! permanent. Revoking a key drops it from every cache holding it, along
+//! with the session keys combined from it, and those sessions count as
+//! revoked too; every derivation and decryption path checks the registry
+//! before using a key.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::{identity_manager, vetkey_manager};
+use crate::backup;
+
+pub const MAX_REASON_LENGTH: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RevokedKey {
+    pub key_id: String,
+    pub revoked_by: Principal,
+    pub reason: String,
+    pub revoked_at: u64,
+    /// Cached keys and sessions dropped along with it
+    pub invalidated: Vec<String>,
+}
+
+thread_local! {
+    static REVOKED: RefCell<HashMap<String, RevokedKey>> = RefCell::new(HashMap::new());
+}
+
+/// Whether `key_id` names a key derived for `principal`
+pub fn owned_by(key_id: &str, principal: Principal) -> bool {
+    let principal = principal.to_text();
+    key_id == principal
+        || key_id.starts_with(&format!("vetkey_{}_", principal))
+        || key_id.starts_with(&format!("{}:", principal))
+}
+
+pub fn revoke(key_id: &str, revoked_by: Principal, reason: &str, now: u64) -> Result<RevokedKey, String> {
+    if key_id.trim().is_empty() {
+        return Err("Name the key to revoke".to_string());
+    }
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        return Err(format!("Revocation reasons are at most {} characters", MAX_REASON_LENGTH));
+    }
+    if REVOKED.with(|revoked| revoked.borrow().contains_key(key_id)) {
+        return Err(format!("Key {} is already revoked", key_id));
+    }
+    let mut invalidated = vetkey_manager::invalidate(key_id);
+    invalidated.extend(identity_manager::invalidate(key_id));
+    let record = RevokedKey {
+        key_id: key_id.to_string(),
+        revoked_by,
+        reason: reason.to_string(),
+        revoked_at: now,
+        invalidated,
+    };
+    REVOKED.with(|revoked| revoked.borrow_mut().insert(key_id.to_string(), record.clone()));
+    Ok(record)
+}
+
+pub fn is_revoked(key_id: &str) -> bool {
+    REVOKED.with(|revoked| {
+        let revoked = revoked.borrow();
+        revoked.contains_key(key_id) || revoked.values().any(|r| r.invalidated.iter().any(|id| id == key_id))
+    })
+}
+
+pub fn ensure_not_revoked(key_id: &str) -> Result<(), String> {
+    if is_revoked(key_id) {
+        return Err(format!("Key {} has been revoked", key_id));
+    }
+    Ok(())
+}
+
+/// Every revocation, most recent first
+pub fn list() -> Vec<RevokedKey> {
+    let mut revoked: Vec<RevokedKey> = REVOKED.with(|revoked| revoked.borrow().values().cloned().collect());
+    revoked.sort_by(|a, b| b.revoked_at.cmp(&a.revoked_at).then_with(|| a.key_id.cmp(&b.key_id)));
+    revoked
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&REVOKED))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let revoked = backup::decode(bytes)?;
+    backup::replace(&REVOKED, revoked);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "key_revocation_test.rs"]
+mod tests;
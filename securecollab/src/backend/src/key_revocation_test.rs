@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn revoked_keys_stay_revoked() {
+    let admin = Principal::anonymous();
+    assert!(ensure_not_revoked("vetkey_a_01").is_ok());
+    let record = revoke("vetkey_a_01", admin, "Key exposed in a log", 100).unwrap();
+    assert_eq!(record.revoked_at, 100);
+    assert!(record.invalidated.is_empty());
+    assert!(is_revoked("vetkey_a_01"));
+    assert_eq!(ensure_not_revoked("vetkey_a_01").unwrap_err(), "Key vetkey_a_01 has been revoked");
+    assert!(revoke("vetkey_a_01", admin, "again", 200).is_err());
+    assert!(!is_revoked("vetkey_a_02"));
+}
+
+#[test]
+fn rejects_unnamed_keys_and_long_reasons() {
+    let admin = Principal::anonymous();
+    assert!(revoke(" ", admin, "", 0).is_err());
+    assert!(revoke("vetkey_b_01", admin, &"x".repeat(MAX_REASON_LENGTH + 1), 0).is_err());
+    assert!(!is_revoked("vetkey_b_01"));
+}
+
+#[test]
+fn lists_most_recent_first() {
+    let admin = Principal::anonymous();
+    revoke("vetkey_c_01", admin, "", 10).unwrap();
+    revoke("vetkey_c_02", admin, "", 20).unwrap();
+    let listed: Vec<String> = list().into_iter()
+        .filter(|r| r.key_id.starts_with("vetkey_c_"))
+        .map(|r| r.key_id)
+        .collect();
+    assert_eq!(listed, vec!["vetkey_c_02", "vetkey_c_01"]);
+}
+
+#[test]
+fn recognizes_keys_derived_for_a_principal() {
+    let owner = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let text = owner.to_text();
+    assert!(owned_by(&text, owner));
+    assert!(owned_by(&format!("vetkey_{}_6461746", text), owner));
+    assert!(owned_by(&format!("{}:analysis:6461746", text), owner));
+    assert!(!owned_by("vetkey_other_6461746", owner));
+    assert!(!owned_by(&format!("{}x:analysis", text), owner));
+}
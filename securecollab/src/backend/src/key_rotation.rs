@@ -0,0 +1,90 @@
+//! Dataset key rotation
+//!
+//! A dataset and its column copies are encrypted under keys derived from
+//! paths that carry the dataset's key epoch. Uploads start at epoch 0, whose
+//! paths are the original `data_{party}_{name}` ones; later epochs also name
+//! the dataset, so its rotated keys are its own. Rotating re-encrypts the
+//! stored data under the next epoch's keys and revokes the key ids of the
+//! previous epoch in the revocation registry, so a suspected compromise does
+//! not require uploading the data again.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyRotation {
+    pub dataset_id: String,
+    /// Epoch of the keys the dataset is now encrypted under
+    pub epoch: u32,
+    pub vetkey_id: String,
+    pub revoked_keys: Vec<String>,
+    pub rotated_by: Principal,
+    pub rotated_at: u64,
+}
+
+thread_local! {
+    static EPOCHS: RefCell<HashMap<String, u32>> = RefCell::new(HashMap::new());
+    static ROTATIONS: RefCell<HashMap<String, Vec<KeyRotation>>> = RefCell::new(HashMap::new());
+}
+
+/// Identifier of the key `owner` derives from `path`
+pub fn key_id(owner: Principal, path: &[u8]) -> String {
+    format!("vetkey_{}_{}", owner.to_text(), hex::encode(path))
+}
+
+pub fn epoch(dataset_id: &str) -> u32 {
+    EPOCHS.with(|epochs| epochs.borrow().get(dataset_id).copied().unwrap_or(0))
+}
+
+/// Derivation path of a dataset's key at `epoch`
+pub fn dataset_path(party_name: &str, dataset_name: &str, dataset_id: &str, epoch: u32) -> Vec<u8> {
+    match epoch {
+        0 => format!("data_{}_{}", party_name, dataset_name).into_bytes(),
+        _ => format!("data_{}_{}_{}_v{}", party_name, dataset_name, dataset_id, epoch).into_bytes(),
+    }
+}
+
+/// Derivation path of one column's key at `epoch`
+pub fn column_path(party_name: &str, dataset_name: &str, dataset_id: &str, epoch: u32, column: &str) -> Vec<u8> {
+    let mut path = dataset_path(party_name, dataset_name, dataset_id, epoch);
+    path.extend_from_slice(format!("_col_{}", column).as_bytes());
+    path
+}
+
+/// Move a dataset to the next epoch once it is re-encrypted under its keys
+pub fn advance(dataset_id: &str) -> u32 {
+    EPOCHS.with(|epochs| {
+        let mut epochs = epochs.borrow_mut();
+        let epoch = epochs.entry(dataset_id.to_string()).or_insert(0);
+        *epoch += 1;
+        *epoch
+    })
+}
+
+pub fn record(rotation: KeyRotation) {
+    ROTATIONS.with(|rotations| {
+        rotations.borrow_mut().entry(rotation.dataset_id.clone()).or_default().push(rotation);
+    });
+}
+
+/// Rotations of a dataset's key, oldest first
+pub fn rotations(dataset_id: &str) -> Vec<KeyRotation> {
+    ROTATIONS.with(|rotations| rotations.borrow().get(dataset_id).cloned().unwrap_or_default())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&EPOCHS), backup::cloned(&ROTATIONS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (epochs, rotations) = backup::decode(bytes)?;
+    backup::replace(&EPOCHS, epochs);
+    backup::replace(&ROTATIONS, rotations);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "key_rotation_test.rs"]
+mod tests;
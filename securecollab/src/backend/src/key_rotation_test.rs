@@ -0,0 +1,42 @@
+use super::*;
+use crate::column_encryption::column_derivation_path;
+
+#[test]
+fn first_epoch_keeps_the_upload_paths() {
+    assert_eq!(dataset_path("Boston", "trial", "dataset_1", 0), b"data_Boston_trial".to_vec());
+    assert_eq!(column_path("Boston", "trial", "dataset_1", 0, "age"), column_derivation_path("Boston", "trial", "age"));
+}
+
+#[test]
+fn later_epochs_derive_keys_of_their_own() {
+    let first = dataset_path("Boston", "trial", "dataset_1", 1);
+    assert_eq!(first, b"data_Boston_trial_dataset_1_v1".to_vec());
+    assert_ne!(first, dataset_path("Boston", "trial", "dataset_1", 2));
+    assert_ne!(first, dataset_path("Boston", "trial", "dataset_2", 1));
+    assert_eq!(column_path("Boston", "trial", "dataset_1", 1, "age"), b"data_Boston_trial_dataset_1_v1_col_age".to_vec());
+}
+
+#[test]
+fn rotation_advances_the_epoch() {
+    let owner = Principal::anonymous();
+    let old_key = key_id(owner, &dataset_path("Boston", "trial", "dataset_rotated", 0));
+    assert_eq!(epoch("dataset_rotated"), 0);
+
+    assert_eq!(advance("dataset_rotated"), 1);
+    let new_key = key_id(owner, &dataset_path("Boston", "trial", "dataset_rotated", 1));
+    record(KeyRotation {
+        dataset_id: "dataset_rotated".to_string(),
+        epoch: 1,
+        vetkey_id: new_key.clone(),
+        revoked_keys: vec![old_key.clone()],
+        rotated_by: owner,
+        rotated_at: 100,
+    });
+    assert_eq!(epoch("dataset_rotated"), 1);
+
+    assert_eq!(advance("dataset_rotated"), 2);
+    let history = rotations("dataset_rotated");
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].vetkey_id, new_key);
+    assert!(rotations("dataset_unrotated").is_empty());
+}
@@ -4,6 +4,7 @@ use ic_cdk::{api, caller};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use zeroize::{Zeroize, Zeroizing};
 
 // Import our new modules
 mod mpc_engine;
@@ -12,10 +13,146 @@ mod agent_registry;
 mod privacy_proofs;
 mod identity_manager;
 mod secure_llm;
+mod activity;
+mod key_ceremony;
+mod column_encryption;
+mod recovery;
+mod certification;
+mod load_test;
+mod audit_log;
+mod consent_export;
+mod history;
+mod dataset_access;
+mod event_signing;
+mod anomaly;
+mod rate_limit;
+mod storage_quota;
+mod cost_estimation;
+mod billing;
+mod payments;
+mod bidding;
+mod capabilities;
+mod progress;
+mod scheduler;
+mod subscriptions;
+mod events;
+mod disclosure_control;
+mod differential_privacy;
+mod privacy_budget;
+mod sensitivity;
+mod analytics;
+mod tabular;
+mod metadata;
+mod lineage;
+mod summary;
+mod tags;
+mod regression;
+mod cohorts;
+mod crosstab;
+mod sql;
+mod query_policy;
+mod decryption_window;
+mod key_rotation;
+mod key_revocation;
+mod maintenance;
+mod transport_key;
+mod organizations;
+mod roles;
+mod invitations;
+mod service_principals;
+mod pause;
+mod config;
+mod vetkd_api;
+mod deployment;
+mod backup;
+mod run_approval;
+#[cfg(test)]
+mod test_support;
 
 // Re-export identity types for Candid
-pub use identity_manager::{UserIdentity, VetKDKey, MultiPartySignature};
+pub use identity_manager::{DeviceSession, UserIdentity, VetKDKey, MultiPartySignature};
 pub use secure_llm::SecureComputationRequest;
+pub use activity::{ActivityEntry, ActivityKind, ActivityPage};
+pub use key_ceremony::{KeyCeremony, CeremonyPhase};
+pub use column_encryption::DecryptedColumns;
+pub use privacy_proofs::{HashScheme, PrivacyProof, ProofVerification};
+pub use privacy_proofs::export::{ProofExport, ProofExportFormat};
+pub use privacy_proofs::range::RangeCheck;
+pub use differential_privacy::{DpConfig, NoiseMechanism};
+use differential_privacy::PrivateAnalysis;
+pub use privacy_budget::{BudgetCharge, DpBounds, PrivacyBudget};
+pub use sensitivity::{AggregateAnalysis, AggregateKind, SensitivityAnalysis};
+pub use disclosure_control::{DisclosurePolicy, DisclosureReport, Suppression, SuppressionKind};
+pub use recovery::{GuardianConfig, RecoveryRequest, RecoveryNotification};
+pub use load_test::LoadTestReport;
+pub use audit_log::{AuditLogEntry, AuditLogPage, ChainVerification, AuditInclusionProof, MerkleStep, AuditExport, AuditExportFormat};
+use audit_log::AuditEvent;
+pub use secure_llm::http_outcall::{ApiStyle, HttpProvider, HttpProviderInfo};
+pub use secure_llm::provider::LlmBackend;
+pub use secure_llm::injection::InjectionPolicy;
+pub use secure_llm::resilience::{CircuitState, LlmHealth, RetryPolicy};
+use secure_llm::provider::{CallContext, LlmProvider};
+pub use secure_llm::templates::{PromptTemplate, TemplateBinding, TemplateStatus, TemplateVariable, VariableType};
+pub use secure_llm::usage::{LlmBudget, LlmUsage, LlmUsageStats, PrincipalLlmUsage, ComputationLlmUsage};
+use ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
+use ic_cdk::api::management_canister::main::raw_rand;
+pub use history::{AsOf, ComputationSnapshot, PermissionSnapshot};
+pub use dataset_access::{DatasetAccess, AccessType};
+pub use event_signing::{SignedEvent, EventVerificationKey};
+pub use anomaly::{AnomalyAlert, AnomalyKind, AlertStatus};
+pub use rate_limit::{RateLimit, RateLimits};
+pub use storage_quota::StorageUsage;
+pub use cost_estimation::{CostEstimateRequest, CostEstimate};
+pub use billing::{BillingEntry, BillingHistory, BillableKind};
+pub use payments::{AgentPayment, AgentWithdrawal, PendingTransfer};
+pub use payments::escrow::{Escrow, EscrowStatus};
+pub use bidding::{Bid, BiddingRound, BiddingStatus};
+pub use agent_registry::{AgentLiveness, AgentVersion};
+pub use capabilities::{AgentFilter, Capability, CapabilityDomain, TeamSuggestion};
+pub use mpc_engine::{AgentSubstitution, AgentTiming};
+pub use mpc_engine::checkpoint::{CheckpointJob, JobStatus};
+pub use mpc_engine::secure_sum::{SecureStatistics, SecureSumResult};
+pub use mpc_engine::psi::{PsiMode, PsiResult, PsiSession, PsiStatus};
+pub use mpc_engine::linkage::{LinkageQuality, LinkageRun, RecordMatch};
+pub use mpc_engine::join::SecureJoinResult;
+pub use mpc_engine::time_series::{Decomposition, Period, PeriodChange, PeriodValue, TimeSeriesAnalysis, TimeSeriesRequest, Trend};
+pub use mpc_engine::federated::{FederatedModel, ModelVersion};
+pub use mpc_engine::paillier::{AggregateStatus, EncryptedAggregate, PaillierKey};
+pub use progress::{ComputationProgress, ComputationPhase, AgentProgress, AgentState};
+use progress::ProgressEvent;
+pub use scheduler::{Priority, QueuedComputation, QueuePosition};
+pub use subscriptions::{ComputationSubscription, ComputationNotification};
+pub use events::{EventRecord, WorkflowEvent};
+pub use consent_export::{ConsentGraph, ConsentNode, ConsentVote, ConsentSignature, SignedConsentGraph};
+pub use tabular::{DataFormat, RowError, UploadReport};
+pub use analytics::{ColumnError, UploadValidation};
+pub use metadata::DatasetMetadata;
+pub use lineage::{LineageEdge, LineageGraph, LineageKind, LineageNode};
+pub use summary::{ColumnSummary, DatasetSummary};
+pub use regression::{Coefficient, CorrelationMatrix, CorrelationRequest, RegressionKind, RegressionModel, RegressionRequest};
+pub use cohorts::{Cohort, CohortComparison, CohortComparisonRequest, CohortResult, Condition, Operator};
+pub use crosstab::{ChiSquareTest, ContingencyRequest, ContingencyTable};
+pub use sql::{SelectColumn, SelectItem, SqlPlan, SqlResult, SqlValue};
+pub use query_policy::{PolicyDecision, PolicyFinding, PolicyRule};
+pub use decryption_window::{DecryptionWindow, WindowState};
+pub use key_rotation::KeyRotation;
+pub use key_revocation::RevokedKey;
+pub use maintenance::{MaintenanceReport, MaintenanceStats};
+pub use transport_key::WrappedKey;
+pub use organizations::Organization;
+pub use roles::{Permission, Role};
+pub use invitations::{Invitation, InvitationCode, Invitee};
+pub use service_principals::ServicePrincipal;
+pub use pause::PauseState;
+pub use config::{Config, ConfigUpdate, Settings, VetKdMode};
+pub use deployment::{BootstrapParty, InitArgs};
+pub use backup::{ImportChunk, ImportProgress, StateChunk};
+
+// Principals outside any organization, and everything created before
+// organizations, share this workspace
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+const MAX_PARTY_NAME_LENGTH: usize = 100;
 
 // VetKD response types
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -46,6 +183,12 @@ pub struct PrivateDataSource {
     pub record_count: u32,
     pub created_at: u64,
     pub access_permissions: Vec<Principal>,
+    /// Provenance and terms of use, shown to approvers
+    pub metadata: DatasetMetadata,
+    /// Normalized free-form and vocabulary tags for search
+    pub tags: Vec<String>,
+    /// Purposes requests must declare to use the dataset; None allows any
+    pub allowed_purposes: Option<Vec<String>>,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -60,6 +203,20 @@ pub struct LLMQueryRequest {
     pub created_at: u64,
     pub expires_at: u64,
     pub result: Option<String>,
+    // Column subset to decrypt; empty means whole datasets
+    pub columns: Vec<String>,
+    pub decrypted_columns: Vec<DecryptedColumns>,
+    pub minimization_proof_id: Option<String>,
+    // Differential privacy parameters chosen by the requester; None uses the canister default
+    pub dp_params: Option<DpConfig>,
+    // Aggregate plan of a structured SQL query, run instead of the free-text analysis
+    pub sql_plan: Option<SqlPlan>,
+    pub sql_result: Option<SqlResult>,
+    // Why the query policy let the query go to a vote
+    pub policy_decision: Option<PolicyDecision>,
+    // Declared use of the data, allowed by every purpose-bound target dataset
+    pub purpose: String,
+    pub last_error: Option<String>,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -70,6 +227,9 @@ pub enum QueryStatus {
     Executing,
     Completed,
     Expired,
+    Cancelled,
+    // The run stopped part way; `last_error` says why
+    Failed,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -86,9 +246,12 @@ pub struct PartyInfo {
 pub struct MPCAgent {
     pub id: String,
     pub identity: String,
-    pub capabilities: Vec<String>,
+    pub capabilities: Vec<Capability>,
     pub reputation_score: u32,
     pub price_per_computation: u64,
+    pub version: u32,
+    // Principal allowed to update the agent and withdraw its earnings
+    pub owner: Option<Principal>,
 }
 
 #[derive(CandidType, candid::Deserialize, Clone, Debug)]
@@ -96,6 +259,10 @@ pub struct ComputationResult {
     pub insights: String,
     pub privacy_proof: String,
     pub timestamp: u64,
+    // Team members replaced by equivalent agents after failing
+    pub substitutions: Vec<AgentSubstitution>,
+    // When each team slot started and finished
+    pub agent_timings: Vec<AgentTiming>,
 }
 
 #[derive(CandidType, candid::Deserialize, Clone, Debug)]
@@ -113,6 +280,25 @@ pub struct Vote {
     pub timestamp: u64,
 }
 
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct RetryAttempt {
+    pub attempt: u32,
+    pub requested_by: candid::Principal,
+    pub previous_error: Option<String>,
+    pub signatures_preserved: bool,
+    pub timestamp: u64,
+}
+
+// Result copy encrypted for a single recipient under their vetKD-derived key
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct EncryptedResult {
+    pub recipient: candid::Principal,
+    pub ciphertext: Vec<u8>,
+    pub derivation_path: Vec<u8>,
+    pub result_digest: String,
+    pub created_at: u64,
+}
+
 #[derive(CandidType, candid::Deserialize, Clone, Debug)]
 pub struct MPCComputation {
     pub id: String,
@@ -130,6 +316,56 @@ pub struct MPCComputation {
     pub required_signatures: Vec<candid::Principal>,
     pub received_signatures: Vec<candid::Principal>,
     pub vetkey_derivation_complete: bool,
+    // Failure tracking for retries
+    pub last_error: Option<String>,
+    pub retry_attempts: Vec<RetryAttempt>,
+    // Principals granted result access beyond the requester and signers
+    pub shared_with: Vec<candid::Principal>,
+    // Per-recipient encrypted copies replace plaintext `results`
+    pub encrypted_results: Vec<EncryptedResult>,
+    // Optional JSON Schema the LLM output must conform to
+    pub output_schema: Option<String>,
+    // Per-recipient encrypted copies of the validated JSON output
+    pub structured_results: Vec<EncryptedResult>,
+    // Agent paid its `price_per_computation` when the computation runs
+    pub agent_id: Option<String>,
+    // Agent version pinned at assignment; execution fails if the agent changed since
+    pub agent_version: Option<u32>,
+    // Execution queue priority once the computation is ready to execute
+    pub priority: Priority,
+    // HTTPS LLM provider used instead of the IC LLM canister
+    pub llm_provider: Option<String>,
+    // Approved prompt template used for the structured analysis instead of the built-in prompt
+    pub prompt_template: Option<TemplateBinding>,
+    // Declared use of the data, allowed by every purpose-bound dataset of the parties
+    pub purpose: String,
+    // Column subset of the parties' datasets to decrypt; empty means whole datasets
+    pub columns: Vec<String>,
+}
+
+// Query response with an IC data certificate and a witness for its leaf.
+// The certified leaf is the Candid encoding of `computation` with `results` cleared.
+#[derive(CandidType, candid::Deserialize, Clone, Debug)]
+pub struct CertifiedComputation {
+    pub computation: MPCComputation,
+    pub certificate: Option<Vec<u8>>,
+    pub witness: Vec<u8>,
+}
+
+// Audit event inclusion proof; the proof root is certified under "audit_log/root"
+#[derive(CandidType, candid::Deserialize, Clone, Debug)]
+pub struct CertifiedInclusionProof {
+    pub proof: AuditInclusionProof,
+    pub certificate: Option<Vec<u8>>,
+    pub witness: Vec<u8>,
+}
+
+// Privacy audit report certified under "audit/<computation_id>"
+#[derive(CandidType, candid::Deserialize, Clone, Debug)]
+pub struct CertifiedAudit {
+    pub report: String,
+    pub certificate: Option<Vec<u8>>,
+    pub witness: Vec<u8>,
 }
 
 // Define ChatMessage struct for our mock implementation
@@ -149,12 +385,67 @@ thread_local! {
 }
 
 // Initialize the 3 parties for Vibhathon demo
+// Deployments pass their admins, bootstrap parties, LLM canister and vetKD
+// key as an optional argument
 #[ic_cdk::init]
-fn init() {
-    // This would be called during canister deployment
+fn init(args: Option<InitArgs>) {
+    start_deployment(args);
     ic_cdk::println!("SecureCollab Vibhathon Demo initialized");
 }
 
+// Heap state is not kept across upgrades, so upgrades take the full deployment
+// arguments again and the built-in agents are registered again; fields left
+// out get their defaults
+#[ic_cdk::post_upgrade]
+fn post_upgrade(args: Option<InitArgs>) {
+    start_deployment(args);
+}
+
+fn start_deployment(args: Option<InitArgs>) {
+    agent_registry::init(api::time());
+    let parties = deployment::apply(args.unwrap_or_default())
+        .unwrap_or_else(|e| ic_cdk::trap(&format!("Invalid deployment arguments: {}", e)));
+    if !parties.is_empty() {
+        ic_cdk_timers::set_timer(std::time::Duration::ZERO, move || ic_cdk::spawn(register_bootstrap_parties(parties)));
+    }
+    start_timers();
+}
+
+fn start_timers() {
+    ic_cdk_timers::set_timer_interval(std::time::Duration::from_secs(identity_manager::SWEEP_INTERVAL_SECONDS), || {
+        identity_manager::sweep_expired_keys(api::time());
+        vetkey_manager::cleanup_sessions(api::time(), |agent_id| agent_registry::get_agent_by_id(agent_id).is_some());
+    });
+    ic_cdk_timers::set_timer_interval(std::time::Duration::from_secs(maintenance::MAINTENANCE_INTERVAL_SECONDS), || {
+        run_maintenance(api::time());
+    });
+}
+
+// Register the deployment's parties in the default workspace
+async fn register_bootstrap_parties(parties: Vec<BootstrapParty>) {
+    for party in parties {
+        let name = party.name.trim().to_string();
+        let vetkey = match derive_vetkey_for_party(party.principal, format!("user_{}", name).into_bytes()).await {
+            Ok(vetkey) => vetkey,
+            Err(e) => {
+                ic_cdk::println!("Bootstrap party '{}' not registered: {}", name, e);
+                continue;
+            }
+        };
+        let party_info = PartyInfo {
+            principal: party.principal,
+            name: name.clone(),
+            role: party.role,
+            vetkey_id: format!("vetkey_{}_{}", name, hex::encode(&vetkey[..8])),
+            is_active: true,
+            last_seen: current_timestamp(),
+        };
+        PARTIES.with(|parties| parties.borrow_mut().insert(party.principal, party_info));
+        activity::record(&organizations::workspace_of(party.principal), ActivityKind::PartyRegistered, api::id(),
+            &party.principal.to_text(), format!("Party '{}' registered at deployment", name));
+    }
+}
+
 // Generate unique IDs
 fn generate_id(prefix: &str) -> String {
     let timestamp = api::time();
@@ -166,18 +457,47 @@ fn current_timestamp() -> u64 {
     api::time()
 }
 
+// Operator endpoints are restricted to canister controllers and deployment admins
+fn require_admin() -> Result<Principal, String> {
+    let caller = caller();
+    if !api::is_controller(&caller) && !deployment::is_admin(caller) {
+        return Err("Only canister controllers and admins can call this endpoint".to_string());
+    }
+    Ok(caller)
+}
+
+// Canister-wide changes go on the timeline of the workspace the admin acts from
+fn record_admin_activity(kind: ActivityKind, admin: Principal, resource_id: &str, summary: String) {
+    activity::record(&organizations::workspace_of(admin), kind, admin, resource_id, summary);
+}
+
+// Datasets a principal owns and the bytes they occupy against its quota
+fn storage_usage_of(principal: Principal) -> StorageUsage {
+    let (count, bytes) = DATA_SOURCES.with(|sources| {
+        sources.borrow().values()
+            .filter(|ds| ds.owner == principal)
+            .fold((0u32, 0u64), |(count, bytes), ds| {
+                (count + 1, bytes + ds.encrypted_data.len() as u64 + column_encryption::stored_bytes(&ds.id))
+            })
+    });
+    storage_quota::usage(principal, count, bytes)
+}
+
 // Derive vetKD key for a party
 async fn derive_vetkey_for_party(party_principal: Principal, derivation_path: Vec<u8>) -> Result<Vec<u8>, String> {
     // In a real implementation, this would use ic-vetkeys
     // For demo purposes, we'll simulate key derivation
-    let key_id = format!("vetkey_{}_{}", party_principal.to_text(), hex::encode(&derivation_path));
+    let key_id = key_rotation::key_id(party_principal, &derivation_path);
+    key_revocation::ensure_not_revoked(&key_id)?;
     
-    // Simulate vetKD key derivation
-    let derived_key = format!("derived_key_for_{}", party_principal.to_text()).into_bytes();
+    // Simulate vetKD key derivation; each path gives its own key
+    let derived_key = format!("derived_key_for_{}_{}", party_principal.to_text(), &sha256_hex(&derivation_path)[..16]).into_bytes();
     
     VETKEY_DERIVATIONS.with(|keys| {
         keys.borrow_mut().insert(key_id.clone(), derived_key.clone());
     });
+    audit_log::record(AuditEvent::KeyDerivation, caller(), &key_id,
+        format!("Key derived for {}", party_principal.to_text()));
     
     Ok(derived_key)
 }
@@ -188,6 +508,168 @@ fn encrypt_with_vetkey(data: &[u8], key: &[u8]) -> Vec<u8> {
     data.iter().zip(key.iter().cycle()).map(|(d, k)| d ^ k).collect()
 }
 
+// Derivation path for a recipient's copy of a computation result
+fn result_derivation_path(request_id: &str, recipient: Principal) -> Vec<u8> {
+    format!("result_{}_{}", request_id, recipient.to_text()).into_bytes()
+}
+
+// Derivation path of a dataset's key at its current key epoch
+fn dataset_key_path(dataset: &PrivateDataSource) -> Vec<u8> {
+    key_rotation::dataset_path(&dataset.party_name, &dataset.name, &dataset.id, key_rotation::epoch(&dataset.id))
+}
+
+fn column_key_path(dataset: &PrivateDataSource, column: &str) -> Vec<u8> {
+    key_rotation::column_path(&dataset.party_name, &dataset.name, &dataset.id, key_rotation::epoch(&dataset.id), column)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(data))
+}
+
+// Encrypt a result once per recipient with their own derived key
+async fn encrypt_result_for(request_id: &str, results: &str, recipients: &[Principal]) -> Result<Vec<EncryptedResult>, String> {
+    let result_digest = sha256_hex(results.as_bytes());
+    let mut encrypted = Vec::with_capacity(recipients.len());
+    
+    for recipient in recipients {
+        let derivation_path = result_derivation_path(request_id, *recipient);
+        let key = derive_vetkey_for_party(*recipient, derivation_path.clone()).await?;
+        encrypted.push(EncryptedResult {
+            recipient: *recipient,
+            ciphertext: encrypt_with_vetkey(results.as_bytes(), &key),
+            derivation_path,
+            result_digest: result_digest.clone(),
+            created_at: current_timestamp(),
+        });
+    }
+    
+    Ok(encrypted)
+}
+
+// Recover the plaintext of a recipient's result copy
+async fn decrypt_result_copy(
+    copy: &EncryptedResult,
+    window: &decryption_window::DecryptionContext,
+) -> Result<Zeroizing<String>, String> {
+    pause::ensure_running()?;
+    let key = Zeroizing::new(derive_vetkey_for_party(copy.recipient, copy.derivation_path.clone()).await?);
+    window.ensure_open(current_timestamp())?;
+    let plaintext = Zeroizing::new(decrypt_with_vetkey(&copy.ciphertext, &key));
+    window.count(plaintext.len());
+    audit_log::record(AuditEvent::Decryption, caller(), &String::from_utf8_lossy(&copy.derivation_path),
+        format!("Result copy decrypted for {}", copy.recipient.to_text()));
+    
+    if sha256_hex(&plaintext) != copy.result_digest {
+        return Err("Result integrity check failed".to_string());
+    }
+    
+    std::str::from_utf8(&plaintext)
+        .map(|results| Zeroizing::new(results.to_string()))
+        .map_err(|_| "Result is not valid UTF-8".to_string())
+}
+
+// Everyone entitled to a copy of a computation's results
+fn result_recipients(computation: &MPCComputation) -> Vec<Principal> {
+    let mut recipients = vec![computation.requester];
+    for principal in computation.received_signatures.iter().chain(&computation.shared_with) {
+        if !recipients.contains(principal) {
+            recipients.push(*principal);
+        }
+    }
+    recipients
+}
+
+// Key id under which structured output copies are derived, kept apart from the narrative
+fn structured_result_id(request_id: &str) -> String {
+    format!("{}/structured", request_id)
+}
+
+// Store encrypted result copies (and structured output, if any) and mark a computation completed
+async fn store_encrypted_results(request_id: &str, results: &str, structured: Option<&str>) -> Result<(), String> {
+    let recipients = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(request_id).map(result_recipients)
+    }).ok_or_else(|| "Computation request not found".to_string())?;
+    
+    let encrypted = encrypt_result_for(request_id, results, &recipients).await?;
+    let structured = match structured {
+        Some(json) => encrypt_result_for(&structured_result_id(request_id), json, &recipients).await?,
+        None => vec![],
+    };
+    
+    COMPUTATION_REQUESTS.with(|requests| {
+        if let Some(computation) = requests.borrow_mut().get_mut(request_id) {
+            computation.results = None;
+            computation.encrypted_results = encrypted;
+            computation.structured_results = structured;
+            computation.status = "completed".to_string();
+        }
+    });
+    computation_changed(request_id);
+    
+    Ok(())
+}
+
+// Re-encrypt the copies addressed to `old` for `new`
+async fn rewrap_result_copies(
+    key_id: &str,
+    copies: Vec<EncryptedResult>,
+    old: Principal,
+    new: Principal,
+    window: &decryption_window::DecryptionContext,
+) -> Result<Vec<EncryptedResult>, String> {
+    let mut rewrapped = Vec::with_capacity(copies.len());
+    for copy in copies {
+        if copy.recipient == old {
+            let results = decrypt_result_copy(&copy, window).await?;
+            rewrapped.extend(encrypt_result_for(key_id, &results, &[new]).await?);
+        } else {
+            rewrapped.push(copy);
+        }
+    }
+    Ok(rewrapped)
+}
+
+// Snapshot and re-certify a computation request after it changed
+fn computation_changed(request_id: &str) {
+    let computation = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(request_id).cloned()
+    });
+    
+    if let Some(computation) = computation {
+        history::snapshot_computation(&computation);
+        schedule_computation(&computation);
+        maintenance::computation_status(&computation.id, &computation.status, current_timestamp());
+        subscriptions::status_changed(&computation.id, &computation.status, computation.last_error.clone(), current_timestamp());
+        certify_computation(computation);
+    }
+}
+
+// Keep the execution queue in step with the computation's status
+fn schedule_computation(computation: &MPCComputation) {
+    match computation.status.as_str() {
+        "ready_to_execute" => scheduler::enqueue(&computation.id, computation.priority, current_timestamp()),
+        "completed" | "failed" | "rejected" | "cancelled" => scheduler::remove(&computation.id),
+        _ => {}
+    }
+}
+
+// Refresh the certified leaf of a computation request.
+// Results are cleared so the leaf is the same for every caller.
+fn certify_computation(mut public_view: MPCComputation) {
+    public_view.results = None;
+    if let Ok(bytes) = certification::candid_bytes(&public_view) {
+        certification::certify(&certification::computation_key(&public_view.id), &bytes);
+    }
+}
+
+// Refresh the certified privacy audit report after its proofs changed
+fn certify_privacy_audit(computation_id: &str) {
+    if let Ok(report) = privacy_proofs::refresh_audit(computation_id) {
+        certification::certify(&certification::audit_key(computation_id), report.as_bytes());
+    }
+}
+
 // Decrypt data with vetKD
 fn decrypt_with_vetkey(encrypted_data: &[u8], key: &[u8]) -> Vec<u8> {
     // XOR decryption (same as encryption for XOR)
@@ -198,10 +680,11 @@ fn decrypt_with_vetkey(encrypted_data: &[u8], key: &[u8]) -> Vec<u8> {
 // VIBHATHON ICP DEMO API - 3-Party Secure Multi-Party Computation
 // ============================================================================
 
-// Register a party for the demo
-#[ic_cdk::update]
+// Register a party for the demo (controllers only; parties otherwise
+// register with an invitation)
+#[ic_cdk::update(guard = "rate_limited")]
 async fn register_party(name: String, role: String) -> Result<String, String> {
-    let caller_principal = caller();
+    let caller_principal = require_admin()?;
     let derivation_path = format!("party_{}", name).into_bytes();
     
     // For demo purposes, create mock principals for different parties
@@ -229,23 +712,44 @@ async fn register_party(name: String, role: String) -> Result<String, String> {
         parties.borrow_mut().insert(party_principal, party_info);
     });
     
+    activity::record(&organizations::workspace_of(caller_principal), ActivityKind::PartyRegistered, caller_principal,
+        &party_principal.to_text(), format!("Party '{}' registered", name));
+    
     Ok(format!("Party '{}' registered with vetKD key: {}", name, vetkey_id))
 }
 
-// Register user identity for authentication
-#[ic_cdk::update]
-async fn register_user_identity(name: String, role: String) -> Result<String, String> {
+// Register user identity for authentication, redeeming an invitation that
+// sets the caller's organization and role
+#[ic_cdk::update(guard = "rate_limited")]
+async fn register_user_identity(name: String, invitation_code: String) -> Result<String, String> {
     let caller_principal = caller();
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_PARTY_NAME_LENGTH {
+        return Err(format!("Names are 1 to {} characters", MAX_PARTY_NAME_LENGTH));
+    }
+    let invitation = invitations::redeem(&invitation_code, caller_principal, current_timestamp())?;
     let derivation_path = format!("user_{}", name).into_bytes();
     
     // Derive vetKD key for this user
-    let vetkey = derive_vetkey_for_party(caller_principal, derivation_path).await?;
+    let vetkey = match derive_vetkey_for_party(caller_principal, derivation_path).await {
+        Ok(vetkey) => vetkey,
+        Err(e) => {
+            invitations::restore(&invitation_code);
+            return Err(e);
+        }
+    };
+    if let Err(e) = join_invited_workspace(&invitation, caller_principal) {
+        invitations::restore(&invitation_code);
+        return Err(e);
+    }
     let vetkey_id = format!("vetkey_{}_{}", name, hex::encode(&vetkey[..8]));
+    audit_log::record(AuditEvent::RoleChange, invitation.invited_by, &caller_principal.to_text(),
+        format!("Invitation {} redeemed as {:?} in {}", invitation.id, invitation.role, invitation.workspace));
     
     let party_info = PartyInfo {
         principal: caller_principal,
         name: name.clone(),
-        role,
+        role: format!("{:?}", invitation.role).to_lowercase(),
         vetkey_id: vetkey_id.clone(),
         is_active: true,
         last_seen: current_timestamp(),
@@ -255,23 +759,278 @@ async fn register_user_identity(name: String, role: String) -> Result<String, St
         parties.borrow_mut().insert(caller_principal, party_info);
     });
     
+    activity::record(&organizations::workspace_of(caller_principal), ActivityKind::PartyRegistered, caller_principal,
+        &caller_principal.to_text(), format!("User identity '{}' registered", name));
+    
     Ok(format!("User identity '{}' registered with vetKD key: {}", name, vetkey_id))
 }
 
+// Add an invited principal to the inviter's organization with its role; the
+// default workspace has no members to add
+fn join_invited_workspace(invitation: &Invitation, member: Principal) -> Result<(), String> {
+    if invitation.workspace == DEFAULT_WORKSPACE {
+        return Ok(());
+    }
+    let as_admin = invitation.role == Role::Admin;
+    organizations::add_member(&invitation.workspace, invitation.invited_by, member, as_admin)?;
+    if !as_admin {
+        roles::grant(invitation.invited_by, member, invitation.role)?;
+    }
+    Ok(())
+}
+
+// Workspace whose invitations the caller manages: its organization, or the
+// default workspace for canister controllers
+fn require_inviter() -> Result<(Principal, String), String> {
+    let caller = caller();
+    let workspace = organizations::workspace_of(caller);
+    if workspace == DEFAULT_WORKSPACE {
+        require_admin()?;
+    } else {
+        roles::require(caller, Permission::ManageRoles)?;
+    }
+    Ok((caller, workspace))
+}
+
+// Invite a principal, or the SHA-256 of an email address, to join the
+// caller's workspace with a role; the code is shown only once
+#[ic_cdk::update(guard = "rate_limited")]
+async fn invite_party(invitee: Invitee, role: Role) -> Result<InvitationCode, String> {
+    let (caller, workspace) = require_inviter()?;
+    let (random,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for the invitation code: {:?} - {}", code, msg))?;
+    let issued = invitations::issue(hex::encode(random), workspace, invitee, role, caller, current_timestamp())?;
+    audit_log::record(AuditEvent::RoleChange, caller, &issued.invitation.id,
+        format!("Invited {:?} to {} as {:?}", issued.invitation.invitee, issued.invitation.workspace, role));
+    Ok(issued)
+}
+
+// Invitations to the caller's workspace, most recent first
+#[ic_cdk::query]
+fn list_invitations() -> Result<Vec<Invitation>, String> {
+    let (_, workspace) = require_inviter()?;
+    Ok(invitations::list(&workspace))
+}
+
+// Withdraw an unused invitation to the caller's workspace
+#[ic_cdk::update(guard = "rate_limited")]
+fn withdraw_invitation(invitation_id: String) -> Result<Invitation, String> {
+    let (_, workspace) = require_inviter()?;
+    invitations::withdraw(&workspace, &invitation_id)
+}
+
+// Let a browser's session principal act for the caller's identity, for at
+// most 30 days (the Internet Identity principal itself, not a session)
+#[ic_cdk::update(guard = "rate_limited")]
+fn delegate_session(session_principal: Principal, device: String, expires_in_seconds: u64) -> Result<DeviceSession, String> {
+    let now = current_timestamp();
+    identity_manager::delegate_session(caller(), session_principal, device, now.saturating_add(expires_in_seconds.saturating_mul(1_000_000_000)), now)
+}
+
+// The principal an endpoint needing `permission` acts for: a service
+// principal acts for its owner within its scopes, anyone else for itself
+fn require_permission(caller: Principal, permission: Permission) -> Result<Principal, String> {
+    match service_principals::authorize(caller, permission, current_timestamp()) {
+        Some(owner) => owner,
+        None => roles::require(caller, permission).map(|_| caller),
+    }
+}
+
+// Let a machine identity, such as an ETL pipeline, act for the caller within
+// some scopes until it expires (at most a year)
+#[ic_cdk::update(guard = "rate_limited")]
+fn register_service_principal(
+    principal: Principal,
+    label: String,
+    scopes: Vec<Permission>,
+    expires_in_seconds: u64,
+) -> Result<ServicePrincipal, String> {
+    let owner = caller();
+    let now = current_timestamp();
+    let service = service_principals::register(owner, principal, label, scopes,
+        now.saturating_add(expires_in_seconds.saturating_mul(1_000_000_000)), now)?;
+    audit_log::record(AuditEvent::RoleChange, owner, &principal.to_text(),
+        format!("Service principal '{}' scoped to {:?}", service.label, service.scopes));
+    Ok(service)
+}
+
+// Service principals acting for the caller
+#[ic_cdk::query]
+fn list_my_service_principals() -> Vec<ServicePrincipal> {
+    service_principals::list(caller())
+}
+
+// Stop a service principal acting for the caller
+#[ic_cdk::update(guard = "rate_limited")]
+fn revoke_service_principal(principal: Principal) -> Result<ServicePrincipal, String> {
+    let owner = caller();
+    let service = service_principals::revoke(owner, principal)?;
+    audit_log::record(AuditEvent::RoleChange, owner, &principal.to_text(),
+        format!("Service principal '{}' revoked", service.label));
+    Ok(service)
+}
+
+// Sessions that can act for the caller's identity
+#[ic_cdk::query]
+fn list_my_sessions() -> Vec<DeviceSession> {
+    let now = current_timestamp();
+    identity_manager::sessions_of(identity_manager::resolve(caller(), now), now)
+}
+
+// Stop a session acting for the caller's identity, from any of its sessions
+#[ic_cdk::update(guard = "rate_limited")]
+fn revoke_session(session_principal: Principal) -> Result<DeviceSession, String> {
+    let anchor = identity_manager::resolve(caller(), current_timestamp());
+    identity_manager::revoke_session(anchor, session_principal)
+}
+
+// ============================================================================
+// ORGANIZATIONS
+// ============================================================================
+
+fn require_workspace_member(workspace: &str) -> Result<(), String> {
+    if !organizations::exists(workspace) {
+        return Err(format!("Unknown workspace: {}", workspace));
+    }
+    if organizations::workspace_of(caller()) != workspace {
+        return Err(format!("Not a member of workspace {}", workspace));
+    }
+    Ok(())
+}
+
+// Registered parties in the same workspace as `principal`
+fn workspace_parties(principal: Principal) -> Vec<Principal> {
+    let workspace = organizations::workspace_of(principal);
+    PARTIES.with(|parties| {
+        parties.borrow().keys().filter(|p| organizations::workspace_of(**p) == workspace).cloned().collect()
+    })
+}
+
+// Datasets in, or shared with, the workspace of `principal`
+fn visible_datasets(principal: Principal) -> Vec<PrivateDataSource> {
+    DATA_SOURCES.with(|sources| {
+        sources.borrow().values().filter(|ds| organizations::visible_to(&ds.id, principal)).cloned().collect()
+    })
+}
+
+// Found an organization, with its own workspace, administered by the caller
+#[ic_cdk::update(guard = "rate_limited")]
+fn create_organization(name: String) -> Result<Organization, String> {
+    organizations::create(generate_id("org"), name, caller(), current_timestamp())
+}
+
+// Add a principal to the caller's organization (organization admins only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn add_organization_member(member: Principal, as_admin: bool) -> Result<Organization, String> {
+    let caller = caller();
+    let organization = organizations::add_member(&organizations::workspace_of(caller), caller, member, as_admin)?;
+    audit_log::record(AuditEvent::RoleChange, caller, &organization.id,
+        format!("{} joined as {}", member.to_text(), if as_admin { "admin" } else { "member" }));
+    Ok(organization)
+}
+
+// Remove a principal from the caller's organization (organization admins only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn remove_organization_member(member: Principal) -> Result<Organization, String> {
+    let caller = caller();
+    let organization = organizations::remove_member(&organizations::workspace_of(caller), caller, member)?;
+    audit_log::record(AuditEvent::RoleChange, caller, &organization.id,
+        format!("{} removed with all roles", member.to_text()));
+    Ok(organization)
+}
+
+// Roles of the caller in its organization
+#[ic_cdk::query]
+fn get_my_roles() -> Vec<Role> {
+    roles::roles_of(caller())
+}
+
+// Endpoint families each role unlocks
+#[ic_cdk::query]
+fn get_permission_matrix() -> Vec<(Role, Vec<Permission>)> {
+    roles::ROLES.iter().map(|role| (*role, roles::permissions(*role).to_vec())).collect()
+}
+
+// Grant a member of the caller's organization a role (organization admins only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn grant_role(member: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let caller = caller();
+    let roles = roles::grant(caller, member, role)?;
+    audit_log::record(AuditEvent::RoleChange, caller, &member.to_text(), format!("Granted {:?}", role));
+    record_admin_activity(ActivityKind::AdminAction, caller, &member.to_text(), format!("Granted {:?} to {}", role, member));
+    Ok(roles)
+}
+
+// Take a role from a member of the caller's organization (organization admins only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn revoke_role(member: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let caller = caller();
+    let roles = roles::revoke(caller, member, role)?;
+    audit_log::record(AuditEvent::RoleChange, caller, &member.to_text(), format!("Revoked {:?}", role));
+    record_admin_activity(ActivityKind::AdminAction, caller, &member.to_text(), format!("Revoked {:?} from {}", role, member));
+    Ok(roles)
+}
+
+// The caller's organization, if it belongs to one
+#[ic_cdk::query]
+fn get_my_organization() -> Option<Organization> {
+    organizations::get(&organizations::workspace_of(caller())).ok()
+}
+
+fn require_resource_owner(resource_id: &str) -> Result<(), String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(resource_id).map(|ds| ds.owner))
+        .or_else(|| LLM_QUERIES.with(|queries| queries.borrow().get(resource_id).map(|q| q.requester)))
+        .or_else(|| COMPUTATION_REQUESTS.with(|requests| requests.borrow().get(resource_id).map(|c| c.requester)))
+        .ok_or_else(|| format!("Resource {} not found", resource_id))?;
+    if owner != caller() {
+        return Err("Only the owner of a dataset, query or computation can share it".to_string());
+    }
+    Ok(())
+}
+
+// Let another workspace see and use a dataset, query or computation (owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn share_with_workspace(resource_id: String, workspace: String) -> Result<Vec<String>, String> {
+    require_resource_owner(&resource_id)?;
+    organizations::share(&resource_id, &workspace)
+}
+
+// Stop sharing a resource with another workspace (owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn unshare_from_workspace(resource_id: String, workspace: String) -> Result<Vec<String>, String> {
+    require_resource_owner(&resource_id)?;
+    Ok(organizations::unshare(&resource_id, &workspace))
+}
+
 // Upload encrypted CSV data
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn upload_private_data(
     name: String,
     data: Vec<u8>,
     schema: String,
+    metadata: Option<DatasetMetadata>,
 ) -> Result<String, String> {
-    let caller_principal = caller();
+    let caller_principal = require_permission(caller(), Permission::Upload)?;
+    let metadata = metadata.unwrap_or_default();
+    metadata::validate(&metadata)?;
     
     // Get party info
     let party_info = PARTIES.with(|parties| {
         parties.borrow().get(&caller_principal).cloned()
     }).ok_or("Party not registered. Please register first.")?;
     
+    // Nothing is encrypted unless every value fits the declared schema; rows
+    // that cannot be parsed are reported back to the uploader, along with
+    // the schema inferred when none is given
+    let validation = analytics::validate_upload(&data, &schema)?;
+    ensure_schema_fits(&validation)?;
+    let data = tabular::normalize(data)?;
+    
+    // The dataset and its column copies are stored at plaintext size
+    let columns = column_encryption::split_columns(&data);
+    let incoming = data.len() as u64 + columns.iter().map(|(_, values)| values.len() as u64).sum::<u64>();
+    storage_quota::check(&storage_usage_of(caller_principal), incoming)?;
+    
     // Derive encryption key
     let derivation_path = format!("data_{}_{}", party_info.name, name).into_bytes();
     let encryption_key = derive_vetkey_for_party(caller_principal, derivation_path).await?;
@@ -286,137 +1045,880 @@ async fn upload_private_data(
         name,
         encrypted_data,
         vetkey_id: party_info.vetkey_id,
-        schema,
-        record_count: data.len() as u32 / 100, // Estimate records
+        schema: validation.schema.clone(),
+        record_count: validation.records as u32,
         created_at: current_timestamp(),
         access_permissions: vec![caller_principal],
+        metadata,
+        tags: vec![],
+        allowed_purposes: None,
     };
     
+    // Column-level copies let computations decrypt only the columns they need
     let data_id = data_source.id.clone();
+    tabular::record_report(UploadReport { dataset_id: data_id.clone(), validation });
+    store_encrypted_columns(&data_source, columns).await?;
+    let summary = format!("Dataset '{}' uploaded by {}", data_source.name, data_source.party_name);
+    let uploaded = WorkflowEvent::DatasetUploaded { dataset_id: data_id.clone(), name: data_source.name.clone() };
+    history::snapshot_permissions(&data_source);
+    lineage::new_version(&data_id, data_source.created_at);
     DATA_SOURCES.with(|sources| {
         sources.borrow_mut().insert(data_id.clone(), data_source);
     });
+    organizations::assign(&data_id, caller_principal);
+    
+    audit_log::record(AuditEvent::Upload, caller_principal, &data_id, summary.clone());
+    activity::record(&organizations::resource_workspace(&data_id), ActivityKind::DatasetUploaded, caller_principal, &data_id, summary);
+    events::record(caller_principal, uploaded, current_timestamp());
     
     Ok(data_id)
 }
 
-// Create LLM query request requiring multi-party approval
-#[ic_cdk::update]
-async fn create_llm_query(
-    query: String,
-    target_datasets: Vec<String>,
-) -> Result<String, String> {
-    let caller_principal = caller();
-    
-    // Get all registered parties for required signatures
-    let all_parties: Vec<Principal> = PARTIES.with(|parties| {
-        parties.borrow().keys().cloned().collect()
-    });
-    
-    if all_parties.len() < 3 {
-        return Err("Need at least 3 parties registered for multi-party queries".to_string());
+// Check data against a schema before uploading it, with errors per column
+#[ic_cdk::query]
+fn validate_upload(data: Vec<u8>, schema: String) -> Result<UploadValidation, String> {
+    analytics::validate_upload(&data, &schema)
+}
+
+// Records parsed from an upload and the rows left out (dataset owner only)
+#[ic_cdk::query]
+fn get_upload_report(dataset_id: String) -> Result<UploadReport, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| "Dataset not found".to_string())?;
+    if owner != caller() {
+        return Err("Only the dataset owner can view its upload report".to_string());
     }
-    
-    let query_request = LLMQueryRequest {
-        id: generate_id("query"),
-        requester: caller_principal,
-        query,
-        target_datasets,
-        required_signatures: all_parties,
-        received_signatures: vec![caller_principal], // Requester auto-signs
-        status: QueryStatus::Pending,
-        created_at: current_timestamp(),
-        expires_at: current_timestamp() + (24 * 60 * 60 * 1_000_000_000), // 24 hours
-        result: None,
-    };
-    
-    let query_id = query_request.id.clone();
-    LLM_QUERIES.with(|queries| {
-        queries.borrow_mut().insert(query_id.clone(), query_request);
-    });
-    
-    Ok(query_id)
+    tabular::report(&dataset_id).ok_or_else(|| "No upload report for this dataset".to_string())
 }
 
-// Sign/approve an LLM query request
-#[ic_cdk::update]
-async fn sign_llm_query(query_id: String) -> Result<String, String> {
-    let caller_principal = caller();
-    
-    LLM_QUERIES.with(|queries| {
-        let mut queries_map = queries.borrow_mut();
-        let query = queries_map.get_mut(&query_id)
-            .ok_or("Query not found")?;
-        
-        // Check if already signed
-        if query.received_signatures.contains(&caller_principal) {
-            return Err("Already signed this query".to_string());
+// Confirm or correct a dataset's schema, such as one inferred on upload
+// (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn update_dataset_schema(dataset_id: String, schema: String) -> Result<(), String> {
+    let caller = caller();
+    let columns = analytics::parse_schema(&schema)?;
+    if columns.is_empty() {
+        return Err("The schema declares no columns".to_string());
+    }
+    DATA_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let source = sources.get_mut(&dataset_id).ok_or("Dataset not found")?;
+        if source.owner != caller {
+            return Err("Only the dataset owner can change its schema".to_string());
         }
-        
-        // Add signature
-        query.received_signatures.push(caller_principal);
-        
-        // Check if all required signatures received
-        if query.received_signatures.len() >= query.required_signatures.len() {
-            query.status = QueryStatus::Approved;
+        source.schema = analytics::render_schema(&columns);
+        Ok(())
+    })?;
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id, format!("Schema of dataset {} set to {}", dataset_id, schema));
+    Ok(())
+}
+
+// Describe a dataset's steward, collection period, license, data use
+// agreement and geography (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn update_dataset_metadata(dataset_id: String, metadata: DatasetMetadata) -> Result<(), String> {
+    let caller = caller();
+    metadata::validate(&metadata)?;
+    DATA_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let source = sources.get_mut(&dataset_id).ok_or("Dataset not found")?;
+        if source.owner != caller {
+            return Err("Only the dataset owner can change its metadata".to_string());
         }
-        
-        Ok(format!("Query signed. {}/{} signatures received", 
-                  query.received_signatures.len(), 
-                  query.required_signatures.len()))
-    })
+        source.metadata = metadata;
+        Ok(())
+    })?;
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id, format!("Metadata of dataset {} updated", dataset_id));
+    Ok(())
 }
 
-// Execute approved LLM query with temporary decryption
-#[ic_cdk::update]
-async fn execute_llm_query(query_id: String) -> Result<String, String> {
-    let query = LLM_QUERIES.with(|queries| {
-        queries.borrow().get(&query_id).cloned()
-    }).ok_or("Query not found")?;
-    
-    // Check if approved
-    if !matches!(query.status, QueryStatus::Approved) {
-        return Err("Query not approved by all parties".to_string());
+// Datasets whose contents are being re-encrypted
+thread_local! {
+    static DATASET_LOCKS: RefCell<std::collections::HashSet<String>> = RefCell::new(std::collections::HashSet::new());
+}
+
+// Lock held while a dataset is decrypted and written back, so appends and key
+// rotations of the same dataset cannot overwrite each other; released on drop
+struct DatasetGuard {
+    dataset_id: String,
+}
+
+impl DatasetGuard {
+    fn acquire(dataset_id: &str) -> Result<Self, String> {
+        DATASET_LOCKS.with(|locks| {
+            if !locks.borrow_mut().insert(dataset_id.to_string()) {
+                return Err(format!("Dataset {} is being updated; try again shortly", dataset_id));
+            }
+            Ok(DatasetGuard { dataset_id: dataset_id.to_string() })
+        })
     }
-    
-    // Update status to executing
-    LLM_QUERIES.with(|queries| {
-        if let Some(q) = queries.borrow_mut().get_mut(&query_id) {
-            q.status = QueryStatus::Executing;
+}
+
+impl Drop for DatasetGuard {
+    fn drop(&mut self) {
+        DATASET_LOCKS.with(|locks| {
+            locks.borrow_mut().remove(&self.dataset_id);
+        });
+    }
+}
+
+// Re-encrypt a dataset under newly derived keys and revoke the old ones, for
+// when a key may have been compromised (dataset owner only). Datasets sharing
+// the key, such as earlier uploads under the same name, are rotated with it.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn rotate_dataset_key(dataset_id: String) -> Result<Vec<KeyRotation>, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).cloned())
+        .ok_or_else(|| "Dataset not found".to_string())?;
+    if dataset.owner != caller {
+        return Err("Only the dataset owner can rotate its key".to_string());
+    }
+    let old_path = dataset_key_path(&dataset);
+    let sharing: Vec<PrivateDataSource> = DATA_SOURCES.with(|sources| {
+        sources.borrow().values().filter(|ds| ds.owner == caller && dataset_key_path(ds) == old_path).cloned().collect()
+    });
+    let _guards = sharing.iter()
+        .map(|ds| DatasetGuard::acquire(&ds.id))
+        .collect::<Result<Vec<_>, String>>()?;
+    
+    let window = open_decryption_window(&generate_id("rotation"))?;
+    let mut rotations = Vec::new();
+    for mut dataset in sharing {
+        let old_key = Zeroizing::new(derive_vetkey_for_party(caller, old_path.clone()).await?);
+        window.ensure_open(current_timestamp())?;
+        let plaintext = Zeroizing::new(decrypt_with_vetkey(&dataset.encrypted_data, &old_key));
+        window.count(plaintext.len());
+        let mut revoked_keys = vec![key_rotation::key_id(caller, &old_path)];
+        let epoch = key_rotation::epoch(&dataset.id) + 1;
+        let path = key_rotation::dataset_path(&dataset.party_name, &dataset.name, &dataset.id, epoch);
+        let new_key = Zeroizing::new(derive_vetkey_for_party(caller, path.clone()).await?);
+        window.ensure_open(current_timestamp())?;
+        dataset.encrypted_data = encrypt_with_vetkey(&plaintext, &new_key);
+        
+        let mut columns = Vec::new();
+        for (column, mut copy) in column_encryption::split_columns(&plaintext) {
+            copy.zeroize();
+            if let Some(mut encrypted) = column_encryption::get_column(&dataset.id, &column) {
+                let old_column_path = column_key_path(&dataset, &column);
+                let old_key = Zeroizing::new(derive_vetkey_for_party(caller, old_column_path.clone()).await?);
+                let column_path = key_rotation::column_path(&dataset.party_name, &dataset.name, &dataset.id, epoch, &column);
+                let new_key = Zeroizing::new(derive_vetkey_for_party(caller, column_path).await?);
+                window.ensure_open(current_timestamp())?;
+                let values = Zeroizing::new(decrypt_with_vetkey(&encrypted.ciphertext, &old_key));
+                window.count(values.len());
+                encrypted.ciphertext = encrypt_with_vetkey(&values, &new_key);
+                revoked_keys.push(key_rotation::key_id(caller, &old_column_path));
+                columns.push(encrypted);
+            }
+        }
+        if !columns.is_empty() {
+            column_encryption::store_columns(&dataset.id, columns);
+        }
+        
+        key_rotation::advance(&dataset.id);
+        dataset.vetkey_id = key_rotation::key_id(caller, &path);
+        audit_log::record(AuditEvent::KeyDerivation, caller, &dataset.id,
+            format!("Dataset key rotated to epoch {}", epoch));
+        dataset_access::record(&dataset.id, caller, AccessType::ReEncryption, None, vec![]);
+        rotations.push(KeyRotation {
+            dataset_id: dataset.id.clone(),
+            epoch,
+            vetkey_id: dataset.vetkey_id.clone(),
+            revoked_keys,
+            rotated_by: caller,
+            rotated_at: current_timestamp(),
+        });
+        DATA_SOURCES.with(|sources| {
+            if let Some(stored) = sources.borrow_mut().get_mut(&dataset.id) {
+                stored.encrypted_data = dataset.encrypted_data;
+                stored.vetkey_id = dataset.vetkey_id;
+            }
+        });
+    }
+    close_decryption_window(window);
+    
+    // Revoke only once every dataset sharing the old keys is re-encrypted
+    for rotation in &rotations {
+        for key_id in &rotation.revoked_keys {
+            if !key_revocation::is_revoked(key_id) {
+                revoke_key_everywhere(key_id, caller, &format!("Key of dataset {} rotated", rotation.dataset_id))?;
+            }
+        }
+        key_rotation::record(rotation.clone());
+    }
+    rotations.sort_by_key(|rotation| rotation.dataset_id != dataset_id);
+    Ok(rotations)
+}
+
+// Revoke a key and drop it from the canister's derivation cache
+fn revoke_key_everywhere(key_id: &str, revoked_by: Principal, reason: &str) -> Result<RevokedKey, String> {
+    let revoked = key_revocation::revoke(key_id, revoked_by, reason, current_timestamp())?;
+    VETKEY_DERIVATIONS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        keys.remove(key_id);
+        for id in &revoked.invalidated {
+            keys.remove(id);
+        }
+    });
+    audit_log::record(AuditEvent::KeyDerivation, revoked_by, key_id, format!("Key revoked: {}", reason));
+    Ok(revoked)
+}
+
+// Revoke a key that may be compromised, along with the cached keys and
+// sessions derived from it (the key's owner or a controller)
+#[ic_cdk::update(guard = "rate_limited")]
+fn revoke_key(key_id: String, reason: String) -> Result<RevokedKey, String> {
+    let caller = caller();
+    if !key_revocation::owned_by(&key_id, caller) {
+        require_admin()?;
+    }
+    revoke_key_everywhere(&key_id, caller, &reason)
+}
+
+// The key revocation list, most recent first
+#[ic_cdk::query]
+fn list_revoked_keys() -> Vec<RevokedKey> {
+    key_revocation::list()
+}
+
+// Lifetime of identity vetKD keys derived without one of their own; none
+// means they never expire (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_vetkd_key_ttl(ttl_seconds: Option<u64>) -> Result<(), String> {
+    let admin = require_admin()?;
+    identity_manager::set_default_key_ttl(ttl_seconds.map(|seconds| seconds.saturating_mul(1_000_000_000)))?;
+    audit_log::record(AuditEvent::KeyDerivation, admin, "vetkd_key_ttl",
+        format!("Default vetKD key lifetime set to {:?} seconds", ttl_seconds));
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_vetkd_key_ttl() -> Option<u64> {
+    identity_manager::default_key_ttl().map(|nanos| nanos / 1_000_000_000)
+}
+
+// Past key rotations of a dataset, oldest first (dataset owner only)
+#[ic_cdk::query]
+fn get_dataset_key_rotations(dataset_id: String) -> Result<Vec<KeyRotation>, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| "Dataset not found".to_string())?;
+    if owner != caller() {
+        return Err("Only the dataset owner can view its key rotations".to_string());
+    }
+    Ok(key_rotation::rotations(&dataset_id))
+}
+
+// Replace a dataset's tags (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_dataset_tags(dataset_id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let caller = caller();
+    let tags = tags::normalize_all(&tags)?;
+    DATA_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let source = sources.get_mut(&dataset_id).ok_or("Dataset not found")?;
+        if source.owner != caller {
+            return Err("Only the dataset owner can tag it".to_string());
+        }
+        source.tags = tags.clone();
+        Ok(())
+    })?;
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id, format!("Dataset {} tagged {}", dataset_id, tags.join(", ")));
+    Ok(tags)
+}
+
+// Bind a dataset to the purposes requests may use it for; no purposes lifts
+// the restriction (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_dataset_purposes(dataset_id: String, purposes: Vec<String>) -> Result<Option<Vec<String>>, String> {
+    let caller = caller();
+    let purposes = query_policy::normalize_purposes(&purposes)?;
+    DATA_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let source = sources.get_mut(&dataset_id).ok_or("Dataset not found")?;
+        if source.owner != caller {
+            return Err("Only the dataset owner can set its purposes".to_string());
+        }
+        source.allowed_purposes = purposes.clone();
+        Ok(())
+    })?;
+    let summary = match &purposes {
+        Some(purposes) => format!("Dataset {} bound to {}", dataset_id, purposes.join(", ")),
+        None => format!("Dataset {} usable for any purpose", dataset_id),
+    };
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id, summary.clone());
+    activity::record(&organizations::resource_workspace(&dataset_id), ActivityKind::PolicyChanged, caller, &dataset_id, summary);
+    Ok(purposes)
+}
+
+// Datasets holding every tag whose name, party, schema, metadata or tags
+// contain the text; an empty text matches any dataset
+#[ic_cdk::query]
+fn search_datasets(tags: Vec<String>, text: String) -> Result<Vec<PrivateDataSource>, String> {
+    let datasets = visible_datasets(caller());
+    tags::search(datasets, &tags, &text)
+}
+
+// Controlled tag vocabulary offered to dataset owners
+#[ic_cdk::query]
+fn get_tag_vocabulary() -> Vec<String> {
+    tags::vocabulary()
+}
+
+// Replace the controlled tag vocabulary (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_tag_vocabulary(terms: Vec<String>) -> Result<Vec<String>, String> {
+    require_admin()?;
+    tags::set_vocabulary(&terms)
+}
+
+fn ensure_schema_fits(validation: &UploadValidation) -> Result<(), String> {
+    if validation.column_errors.is_empty() {
+        return Ok(());
+    }
+    let errors: Vec<String> = validation.column_errors.iter()
+        .map(|e| format!("{}: {} ({} rows)", e.column, e.message, e.rows))
+        .collect();
+    Err(format!("Data does not match the schema: {}", errors.join("; ")))
+}
+
+// Append records to an owner's dataset. The dataset and its column copies
+// are re-encrypted with the new rows, and the linkage encodings and
+// Paillier columns that only cover the previous records are dropped.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn append_to_dataset(dataset_id: String, rows: Vec<u8>) -> Result<UploadValidation, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    let _guard = DatasetGuard::acquire(&dataset_id)?;
+    let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).cloned())
+        .ok_or_else(|| "Dataset not found".to_string())?;
+    if dataset.owner != caller {
+        return Err("Only the dataset owner can append to it".to_string());
+    }
+    if dataset.schema.trim().is_empty() {
+        return Err("Declare the dataset's schema before appending to it".to_string());
+    }
+    let validation = analytics::validate_upload(&rows, &dataset.schema)?;
+    ensure_schema_fits(&validation)?;
+    let appended = tabular::parse(&rows)?;
+    
+    let window = open_decryption_window(&generate_id("append"))?;
+    let derivation_path = dataset_key_path(&dataset);
+    let key = Zeroizing::new(derive_vetkey_for_party(dataset.owner, derivation_path).await?);
+    window.ensure_open(current_timestamp())?;
+    let plaintext = Zeroizing::new(decrypt_with_vetkey(&dataset.encrypted_data, &key));
+    window.count(plaintext.len());
+    let current = tabular::parse(&plaintext)?;
+    audit_log::record(AuditEvent::Decryption, caller, &dataset_id, "Dataset decrypted to append to it".to_string());
+    
+    // Appended rows follow the stored column order
+    let positions = current.header.iter()
+        .map(|column| appended.header.iter().position(|h| h == column))
+        .collect::<Option<Vec<usize>>>()
+        .ok_or_else(|| "The appended rows must have the dataset's columns".to_string())?;
+    let record_count = current.rows.len() + appended.rows.len();
+    let mut records = vec![current.header];
+    records.extend(current.rows);
+    records.extend(appended.rows.iter().map(|row| positions.iter().map(|&p| row[p].clone()).collect()));
+    let data = Zeroizing::new(tabular::write(records).into_bytes());
+    
+    let columns = column_encryption::split_columns(&data);
+    let stored = dataset.encrypted_data.len() as u64 + column_encryption::stored_bytes(&dataset_id);
+    let incoming = data.len() as u64 + columns.iter().map(|(_, values)| values.len() as u64).sum::<u64>();
+    storage_quota::check(&storage_usage_of(caller), incoming.saturating_sub(stored))?;
+    
+    let updated = PrivateDataSource {
+        encrypted_data: encrypt_with_vetkey(&data, &key),
+        record_count: record_count as u32,
+        ..dataset
+    };
+    store_encrypted_columns(&updated, columns).await?;
+    window.ensure_open(current_timestamp())?;
+    // Other fields, such as permissions, may have changed while the keys were derived
+    DATA_SOURCES.with(|sources| {
+        if let Some(stored) = sources.borrow_mut().get_mut(&dataset_id) {
+            stored.encrypted_data = updated.encrypted_data;
+            stored.record_count = updated.record_count;
+        }
+    });
+    close_decryption_window(window);
+    mpc_engine::linkage::invalidate(&dataset_id);
+    mpc_engine::paillier::invalidate(&dataset_id);
+    lineage::new_version(&dataset_id, current_timestamp());
+    
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id,
+        format!("{} records appended to dataset {}", appended.rows.len(), dataset_id));
+    Ok(validation)
+}
+
+// Encrypt each column of a dataset under its own derivation path
+async fn store_encrypted_columns(dataset: &PrivateDataSource, columns: Vec<(String, Vec<u8>)>) -> Result<(), String> {
+    let mut encrypted_columns = Vec::new();
+    for (column, values) in columns {
+        let path = column_key_path(dataset, &column);
+        let column_key = derive_vetkey_for_party(dataset.owner, path).await?;
+        encrypted_columns.push(column_encryption::EncryptedColumn {
+            name: column,
+            ciphertext: encrypt_with_vetkey(&values, &column_key),
+            plaintext_digest: column_encryption::digest(&values),
+        });
+    }
+    column_encryption::store_columns(&dataset.id, encrypted_columns);
+    Ok(())
+}
+
+// Create LLM query request requiring multi-party approval
+#[ic_cdk::update(guard = "rate_limited")]
+async fn create_llm_query(
+    query: String,
+    target_datasets: Vec<String>,
+    columns: Option<Vec<String>>,
+    dp_params: Option<DpConfig>,
+    purpose: String,
+) -> Result<String, String> {
+    let columns = columns.unwrap_or_default();
+    let (purpose, purpose_finding) = check_purpose(&purpose, &target_datasets)?;
+    // Queries without datasets, such as a computation's own, have no columns to check
+    let schema = if target_datasets.is_empty() { vec![] } else { target_schema(&target_datasets)? };
+    let decision = query_policy::evaluate_columns(&columns, &schema, purpose_finding);
+    submit_query(query, target_datasets, columns, dp_params, None, decision, purpose)
+}
+
+// Create a query from restricted SQL; only aggregate plans are accepted and
+// only the columns the plan reads are decrypted
+#[ic_cdk::update(guard = "rate_limited")]
+fn create_sql_query(
+    sql: String,
+    target_datasets: Vec<String>,
+    dp_params: Option<DpConfig>,
+    purpose: String,
+) -> Result<String, String> {
+    let plan = sql::parse(&sql)?;
+    let schema = target_schema(&target_datasets)?;
+    plan.check(&schema)?;
+    let (purpose, purpose_finding) = check_purpose(&purpose, &target_datasets)?;
+    let decision = query_policy::evaluate_plan(&plan, &schema, purpose_finding);
+    // A bare COUNT(*) still needs one column to count the records of
+    let mut columns = plan.columns();
+    if columns.is_empty() {
+        columns.extend(schema.first().map(|column| column.name.clone()));
+    }
+    submit_query(sql, target_datasets, columns, dp_params, Some(plan), decision, purpose)
+}
+
+// Normalize a declared purpose and check it against the datasets a request reads
+fn check_purpose(purpose: &str, dataset_ids: &[String]) -> Result<(String, PolicyFinding), String> {
+    let purpose = query_policy::normalize_purpose(purpose)?;
+    let datasets: Vec<PrivateDataSource> = DATA_SOURCES.with(|sources| {
+        let sources = sources.borrow();
+        dataset_ids.iter().filter_map(|id| sources.get(id).cloned()).collect()
+    });
+    let finding = query_policy::check_purpose(&purpose, &datasets);
+    Ok((purpose, finding))
+}
+
+// Declared schema of the first of a query's datasets that exists
+fn target_schema(target_datasets: &[String]) -> Result<Vec<analytics::ColumnSpec>, String> {
+    let schema = DATA_SOURCES.with(|sources| {
+        let sources = sources.borrow();
+        target_datasets.iter().find_map(|id| sources.get(id).map(|source| source.schema.clone()))
+    }).ok_or("None of the query's datasets exist")?;
+    analytics::parse_schema(&schema)
+}
+
+fn submit_query(
+    query: String,
+    target_datasets: Vec<String>,
+    columns: Vec<String>,
+    dp_params: Option<DpConfig>,
+    sql_plan: Option<SqlPlan>,
+    policy_decision: PolicyDecision,
+    purpose: String,
+) -> Result<String, String> {
+    let caller_principal = require_permission(caller(), Permission::Compute)?;
+    // Automation submits queries, but only people sign them
+    let requester_signature = if caller_principal == caller() { vec![caller_principal] } else { vec![] };
+    if !policy_decision.approved {
+        return Err(policy_decision.rationale);
+    }
+    
+    // Every registered party of the requester's workspace signs
+    let all_parties = workspace_parties(caller_principal);
+    for dataset_id in &target_datasets {
+        organizations::ensure_visible(dataset_id, caller_principal)?;
+    }
+    
+    let min_parties = config::settings().min_parties;
+    if all_parties.len() < min_parties as usize {
+        return Err(format!("Need at least {} parties registered for multi-party queries", min_parties));
+    }
+    if let Some(params) = &dp_params {
+        differential_privacy::validate(params)?;
+    }
+    let params = dp_params.clone().unwrap_or_else(differential_privacy::config);
+    privacy_budget::validate(&target_datasets, &params)?;
+    privacy_budget::check(&target_datasets, params.epsilon)?;
+    
+    let query_request = LLMQueryRequest {
+        id: generate_id("query"),
+        requester: caller_principal,
+        query,
+        target_datasets,
+        required_signatures: all_parties,
+        received_signatures: requester_signature, // Requester auto-signs
+        status: QueryStatus::Pending,
+        created_at: current_timestamp(),
+        expires_at: current_timestamp() + config::settings().query_expiry_seconds.saturating_mul(1_000_000_000),
+        result: None,
+        columns,
+        decrypted_columns: vec![],
+        minimization_proof_id: None,
+        dp_params,
+        sql_plan,
+        sql_result: None,
+        policy_decision: Some(policy_decision),
+        purpose,
+        last_error: None,
+    };
+    
+    let query_id = query_request.id.clone();
+    LLM_QUERIES.with(|queries| {
+        queries.borrow_mut().insert(query_id.clone(), query_request);
+    });
+    organizations::assign(&query_id, caller_principal);
+    
+    activity::record(&organizations::resource_workspace(&query_id), ActivityKind::QueryCreated, caller_principal, &query_id,
+        "LLM query submitted for multi-party approval".to_string());
+    
+    Ok(query_id)
+}
+
+// Sign/approve an LLM query request
+#[ic_cdk::update(guard = "rate_limited")]
+async fn sign_llm_query(query_id: String) -> Result<String, String> {
+    let caller_principal = require_permission(caller(), Permission::Approve)?;
+    
+    let message = LLM_QUERIES.with(|queries| {
+        let mut queries_map = queries.borrow_mut();
+        let query = queries_map.get_mut(&query_id)
+            .ok_or("Query not found")?;
+        
+        // Only pending queries can collect signatures
+        if !matches!(query.status, QueryStatus::Pending) {
+            return Err(format!("Query is no longer pending. Current status: {:?}", query.status));
+        }
+        
+        // Check if already signed
+        if query.received_signatures.contains(&caller_principal) {
+            return Err("Already signed this query".to_string());
+        }
+        
+        // Add signature
+        query.received_signatures.push(caller_principal);
+        
+        // Check if all required signatures received
+        if query.received_signatures.len() >= query.required_signatures.len() {
+            query.status = QueryStatus::Approved;
+        }
+        
+        Ok(format!("Query signed. {}/{} signatures received", 
+                  query.received_signatures.len(), 
+                  query.required_signatures.len()))
+    })?;
+    
+    audit_log::record(AuditEvent::Vote, caller_principal, &query_id, message.clone());
+    activity::record(&organizations::resource_workspace(&query_id), ActivityKind::QuerySigned, caller_principal, &query_id, message.clone());
+    
+    Ok(message)
+}
+
+// Execute approved LLM query with temporary decryption
+#[ic_cdk::update(guard = "rate_limited")]
+async fn execute_llm_query(query_id: String) -> Result<String, String> {
+    anomaly::ensure_not_suspended(caller())?;
+    let _guard = ExecutionGuard::acquire(&query_id)?;
+    
+    // Check approval, pay for the release out of each dataset's privacy budget
+    // and move to executing in one step; bounds may have tightened since creation
+    let query = LLM_QUERIES.with(|queries| {
+        let mut queries_map = queries.borrow_mut();
+        let q = queries_map.get_mut(&query_id).ok_or("Query not found")?;
+        
+        if !matches!(q.status, QueryStatus::Approved) {
+            return Err("Query not approved by all parties".to_string());
+        }
+        let params = q.dp_params.clone().unwrap_or_else(differential_privacy::config);
+        privacy_budget::validate(&q.target_datasets, &params)?;
+        privacy_budget::debit(&q.target_datasets, &query_id, params.epsilon, current_timestamp())?;
+        
+        q.status = QueryStatus::Executing;
+        Ok(q.clone())
+    })?;
+    
+    let mut released = false;
+    let outcome = run_llm_query(&query_id, &query, &mut released).await;
+    if let Err(e) = &outcome {
+        fail_llm_query(&query_id, &query, e, released);
+    }
+    outcome
+}
+
+// Give a failed run's epsilon back unless aggregates were already released,
+// and leave the query failed instead of executing
+fn fail_llm_query(query_id: &str, query: &LLMQueryRequest, error: &str, released: bool) {
+    if !released {
+        privacy_budget::credit(&query.target_datasets, query_id);
+    }
+    LLM_QUERIES.with(|queries| {
+        if let Some(q) = queries.borrow_mut().get_mut(query_id) {
+            q.status = QueryStatus::Failed;
+            q.last_error = Some(error.to_string());
         }
     });
+    audit_log::record(AuditEvent::Execution, caller(), query_id, format!("Query execution failed: {}", error));
+}
+
+// Decrypt, release and store the result of a query already moved to executing
+async fn run_llm_query(query_id: &str, query: &LLMQueryRequest, released: &mut bool) -> Result<String, String> {
+    let query_id = query_id.to_string();
     
-    // Temporarily decrypt data for computation (10 minute window)
-    let mut decrypted_data = Vec::new();
+    // Decrypt only within a window that hard-expires after ten minutes; the
+    // plaintext and keys are zeroized when the window closes
+    let mut window = open_decryption_window(&query_id)?;
+    let mut ciphertexts = Vec::new();
     
     for dataset_id in &query.target_datasets {
         if let Some(dataset) = DATA_SOURCES.with(|sources| {
             sources.borrow().get(dataset_id).cloned()
         }) {
+            window.ensure_open(current_timestamp())?;
+            // Declared column subsets are decrypted column by column
+            ciphertexts.push(dataset.encrypted_data.clone());
+            if !query.columns.is_empty() {
+                window.add(decrypt_dataset_columns(&query_id, &dataset, &query.columns).await?);
+                continue;
+            }
+            
             // Derive decryption key
-            let derivation_path = format!("data_{}_{}", dataset.party_name, dataset.name).into_bytes();
+            let derivation_path = dataset_key_path(&dataset);
             let decryption_key = derive_vetkey_for_party(dataset.owner, derivation_path).await?;
+            window.ensure_open(current_timestamp())?;
             
             // Decrypt data
-            let decrypted = decrypt_with_vetkey(&dataset.encrypted_data, &decryption_key);
-            decrypted_data.push(String::from_utf8_lossy(&decrypted).to_string());
+            let mut decrypted = decrypt_with_vetkey(&dataset.encrypted_data, window.hold_key(decryption_key));
+            audit_log::record(AuditEvent::Decryption, caller(), &dataset.id,
+                format!("Dataset decrypted for query {}", query_id));
+            dataset_access::record(&dataset.id, caller(), AccessType::Decryption, Some(&query_id), vec![]);
+            window.add(String::from_utf8_lossy(&decrypted).to_string());
+            decrypted.zeroize();
         }
     }
+    let decrypted_data = window.data();
     
-    // Execute LLM query on decrypted data
-    let llm_result = execute_secure_llm_query(&query.query, &decrypted_data).await;
+    // Release the aggregates over the decrypted data with differential privacy noise
+    let params = query.dp_params.clone().unwrap_or_else(differential_privacy::config);
+    let mut sql_result = None;
+    let release = match (&query.sql_plan, query_schema(query)) {
+        (Some(plan), Ok(schema)) => run_sql_plan(plan, &schema, decrypted_data, &params).await.map(|result| {
+            let rendered = sql::render(&result);
+            sql_result = Some(result);
+            rendered
+        }),
+        (None, Ok(schema)) => release_private_analysis(&query_id, &schema, decrypted_data, &ciphertexts, &params).await
+            .map(|analysis| differential_privacy::render(&analysis)),
+        (_, Err(e)) => Err(e),
+    };
+    *released = release.is_ok();
+    window.ensure_open(current_timestamp())?;
+    let llm_result = render_query_result(&query.query, release);
+    
+    // Prove that only the declared columns were decrypted
+    let minimization_proof = if query.columns.is_empty() {
+        None
+    } else {
+        let proof = privacy_proofs::generate_minimization_proof(query_id.clone())?;
+        certify_privacy_audit(&query_id);
+        Some(proof.proof_id)
+    };
     
     // Store result and update status
     LLM_QUERIES.with(|queries| {
         if let Some(q) = queries.borrow_mut().get_mut(&query_id) {
             q.result = Some(llm_result.clone());
             q.status = QueryStatus::Completed;
+            q.decrypted_columns = column_encryption::decryptions_for(&query_id);
+            q.minimization_proof_id = minimization_proof;
+            q.sql_result = sql_result;
         }
     });
     
+    let instructions = api::performance_counter(1);
+    let dataset_bytes = decrypted_data.iter().map(|data| data.len() as u64).sum();
+    cost_estimation::record_run(instructions, dataset_bytes, 1);
+    billing::record(query.requester, caller(), &query_id, BillableKind::LlmQuery, instructions, 1, true, current_timestamp());
+    
+    lineage::record_inputs(&query_id, query.query.clone(), &query.target_datasets, current_timestamp());
+    lineage::record_result(&query_id, &format!("{}/result", query_id), "Query result".to_string(), current_timestamp());
+    
+    let summary = format!("LLM query executed over {} datasets", decrypted_data.len());
+    close_decryption_window(window);
+    audit_log::record(AuditEvent::Execution, caller(), &query_id, summary.clone());
+    activity::record(&organizations::resource_workspace(&query_id), ActivityKind::QueryExecuted, caller(), &query_id, summary);
+    
     Ok(llm_result)
 }
 
+// When a run's decryption window opened and how it ended
+#[ic_cdk::query]
+fn get_decryption_window(run_id: String) -> Option<DecryptionWindow> {
+    decryption_window::window(&run_id)
+}
+
+// Open an execution's decryption window and schedule its hard expiry
+fn open_decryption_window(run_id: &str) -> Result<decryption_window::DecryptionContext, String> {
+    let now = current_timestamp();
+    let window = decryption_window::DecryptionContext::open(run_id, now, decryption_window::WINDOW_NANOS)?;
+    let expiring = run_id.to_string();
+    ic_cdk_timers::set_timer(std::time::Duration::from_nanos(decryption_window::WINDOW_NANOS), move || {
+        if decryption_window::expire(&expiring, current_timestamp()) {
+            audit_log::record(AuditEvent::Decryption, ic_cdk::id(), &expiring,
+                "Decryption window expired before the execution finished".to_string());
+        }
+    });
+    audit_log::record(AuditEvent::Decryption, caller(), run_id,
+        format!("Decryption window opened until {}", now + decryption_window::WINDOW_NANOS));
+    Ok(window)
+}
+
+// Close an execution's decryption window, zeroizing what it held
+fn close_decryption_window(window: decryption_window::DecryptionContext) {
+    if let Some(closed) = window.close(current_timestamp()) {
+        audit_log::record(AuditEvent::Decryption, caller(), &closed.run_id,
+            format!("Decryption window closed after {}s; {} bytes of plaintext zeroized",
+                closed.closed_at.unwrap_or(closed.opened_at).saturating_sub(closed.opened_at) / 1_000_000_000, closed.bytes_decrypted));
+    }
+}
+
+// Decrypt only the requested columns of a dataset and log them for the query
+async fn decrypt_dataset_columns(
+    query_id: &str,
+    dataset: &PrivateDataSource,
+    columns: &[String],
+) -> Result<String, String> {
+    pause::ensure_running()?;
+    let mut decrypted_columns = Vec::new();
+    let mut log_entries = Vec::new();
+    
+    for column in columns {
+        let encrypted = column_encryption::get_column(&dataset.id, column)
+            .ok_or_else(|| format!("Column '{}' is not available in dataset {}", column, dataset.id))?;
+        
+        let path = column_key_path(dataset, column);
+        let mut column_key = derive_vetkey_for_party(dataset.owner, path).await?;
+        let values = decrypt_with_vetkey(&encrypted.ciphertext, &column_key);
+        column_key.zeroize();
+        audit_log::record(AuditEvent::Decryption, caller(), &dataset.id,
+            format!("Column '{}' decrypted for query {}", column, query_id));
+        
+        let values_digest = column_encryption::digest(&values);
+        if values_digest != encrypted.plaintext_digest {
+            return Err(format!("Integrity check failed for column '{}' of dataset {}", column, dataset.id));
+        }
+        
+        log_entries.push((column.clone(), values_digest));
+        decrypted_columns.push((column.clone(), values));
+    }
+    
+    dataset_access::record(&dataset.id, caller(), AccessType::ColumnDecryption, Some(query_id), columns.to_vec());
+    column_encryption::record_decryption(query_id, &dataset.id, log_entries);
+    let joined = column_encryption::join_columns(&decrypted_columns);
+    for (_, values) in &mut decrypted_columns {
+        values.zeroize();
+    }
+    Ok(joined)
+}
+
+// Declared schema a query is analyzed under: its first target dataset's,
+// narrowed to the query's column subset
+fn query_schema(query: &LLMQueryRequest) -> Result<Vec<analytics::ColumnSpec>, String> {
+    let mut columns = target_schema(&query.target_datasets)?;
+    if !query.columns.is_empty() {
+        columns.retain(|column| query.columns.contains(&column.name));
+    }
+    Ok(columns)
+}
+
+// Combine the datasets' aggregates and add noise under the query's
+// parameters; the realized parameters are proven for the query
+async fn release_private_analysis(
+    query_id: &str,
+    schema: &[analytics::ColumnSpec],
+    decrypted_data: &[String],
+    ciphertexts: &[Vec<u8>],
+    params: &DpConfig,
+) -> Result<PrivateAnalysis, String> {
+    let combined = analytics::combine(schema, decrypted_data)?;
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for noise: {:?} - {}", code, msg))?;
+    
+    // Commit each dataset's record count so the total can be proven in zero knowledge
+    let inputs: Vec<(&[u8], u64)> = ciphertexts.iter().map(Vec::as_slice).zip(combined.dataset_records.iter().copied()).collect();
+    privacy_proofs::snark::commit_inputs(query_id, &inputs, &seed);
+    let mut source = differential_privacy::NoiseSource::new(seed.clone());
+    let analysis = differential_privacy::privatize(&combined, params, &mut source);
+    
+    differential_privacy::record_release(query_id, analysis.params.clone());
+    privacy_proofs::generate_dp_release_proof(query_id.to_string())?;
+    
+    // Prove the exact statistics lie in the ranges their sensitivities assume
+    // (bounds below zero cannot be committed to)
+    let statistics: Vec<(String, f64, f64, f64)> = combined.analysis.columns.iter()
+        .filter_map(|column| match (&column.spec.column_type, &column.statistics) {
+            (analytics::ColumnType::Number { bounds: Some((min, max)), .. }, analytics::ColumnStatistics::Numeric(stats)) if *min >= 0.0 =>
+                Some((format!("Mean {}", column.spec.name), stats.mean, *min, *max)),
+            _ => None,
+        })
+        .collect();
+    if let Err(e) = privacy_proofs::generate_range_proof(query_id.to_string(), &statistics, &seed) {
+        progress::report(query_id, ProgressEvent::Note(format!("No range proofs: {}", e)), current_timestamp());
+    }
+    certify_privacy_audit(query_id);
+    Ok(analysis)
+}
+
+// Run a SQL query's aggregate plan over its decrypted columns with noise
+// under the query's parameters
+async fn run_sql_plan(
+    plan: &SqlPlan,
+    schema: &[analytics::ColumnSpec],
+    decrypted_data: &[String],
+    params: &DpConfig,
+) -> Result<SqlResult, String> {
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for noise: {:?} - {}", code, msg))?;
+    let mut source = differential_privacy::NoiseSource::new(seed);
+    sql::execute(plan, schema, decrypted_data, params, disclosure_control::policy().min_cohort_size, &mut source)
+}
+
+// Result of an executed query; only noisy aggregates are ever reported
+fn render_query_result(query: &str, release: Result<String, String>) -> String {
+    let analysis = match release {
+        Ok(analysis) => analysis,
+        Err(e) => format!("No aggregates were released: {}", e),
+    };
+    format!(
+        "🔒 SECURE MPC ANALYSIS RESULT 🔒\n\n\
+        Query: {}\n\n\
+        Analysis:\n{}\n\n\
+        🛡️ Privacy Guarantees:\n\
+        ✅ Data remained encrypted outside this computation\n\
+        ✅ No raw data exposed to any party\n\
+        ✅ Multi-party signatures verified\n\
+        ✅ Computation auditable via privacy proofs",
+        query, analysis
+    )
+}
+
 // Execute secure LLM query (mock implementation)
 async fn execute_secure_llm_query(query: &str, _data: &[String]) -> String {
     format!(
@@ -425,8 +1927,7 @@ async fn execute_secure_llm_query(query: &str, _data: &[String]) -> String {
         Analysis: Based on the encrypted multi-party datasets, our secure computation reveals:\n\
         • Treatment effectiveness: 78.5% success rate\n\
         • Patient recovery time: Average 14.2 days\n\
-        • Side effects: Minimal in 92% of cases\n\
-        • Statistical significance: p < 0.001\n\n\
+        • Side effects: Minimal in 92% of cases\n\n\
         🛡️ Privacy Guarantees:\n\
         ✅ Data remained encrypted during computation\n\
         ✅ No raw data exposed to any party\n\
@@ -442,8 +1943,13 @@ async fn execute_secure_llm_query(query: &str, _data: &[String]) -> String {
 
 #[ic_cdk::query]
 fn get_registered_parties() -> Vec<PartyInfo> {
+    let workspace = organizations::workspace_of(caller());
     PARTIES.with(|parties| {
-        parties.borrow().values().cloned().collect()
+        parties.borrow()
+            .values()
+            .filter(|p| organizations::workspace_of(p.principal) == workspace)
+            .cloned()
+            .collect()
     })
 }
 
@@ -461,25 +1967,23 @@ fn get_data_sources_for_user() -> Vec<PrivateDataSource> {
 
 #[ic_cdk::query]
 fn get_all_data_sources() -> Vec<PrivateDataSource> {
-    DATA_SOURCES.with(|sources| {
-        sources.borrow()
-            .values()
-            .cloned()
-            .collect()
-    })
+    visible_datasets(caller())
 }
 
 #[ic_cdk::query]
 fn get_all_datasets() -> Vec<PrivateDataSource> {
-    DATA_SOURCES.with(|sources| {
-        sources.borrow().values().cloned().collect()
-    })
+    visible_datasets(caller())
 }
 
 #[ic_cdk::query]
 fn get_llm_queries() -> Vec<LLMQueryRequest> {
+    let caller_principal = caller();
     LLM_QUERIES.with(|queries| {
-        queries.borrow().values().cloned().collect()
+        queries.borrow()
+            .values()
+            .filter(|q| organizations::visible_to(&q.id, caller_principal))
+            .cloned()
+            .collect()
     })
 }
 
@@ -490,6 +1994,7 @@ fn get_pending_queries_for_user() -> Vec<LLMQueryRequest> {
         queries.borrow()
             .values()
             .filter(|q| {
+                organizations::visible_to(&q.id, caller_principal) &&
                 q.required_signatures.contains(&caller_principal) &&
                 !q.received_signatures.contains(&caller_principal) &&
                 matches!(q.status, QueryStatus::Pending)
@@ -501,18 +2006,21 @@ fn get_pending_queries_for_user() -> Vec<LLMQueryRequest> {
 
 #[ic_cdk::query]
 fn get_query_by_id(query_id: String) -> Option<LLMQueryRequest> {
+    if !organizations::visible_to(&query_id, caller()) {
+        return None;
+    }
     LLM_QUERIES.with(|queries| {
         queries.borrow().get(&query_id).cloned()
     })
 }
 
 // Legacy compatibility functions for existing frontend
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn prompt(prompt_str: String) -> String {
     execute_secure_llm_query(&prompt_str, &[]).await
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn chat(messages: Vec<ChatMessage>) -> String {
     let last_message = messages.last()
         .map(|msg| msg.content.clone())
@@ -521,39 +2029,945 @@ async fn chat(messages: Vec<ChatMessage>) -> String {
     execute_secure_llm_query(&last_message, &[]).await
 }
 
-#[ic_cdk::update]
-async fn generate_privacy_proof(
-    computation_id: String,
-) -> Result<String, String> {
-    let proof = privacy_proofs::generate_proof(computation_id, "zk-SNARK".to_string());
-    Ok(proof.proof_id)
-}
-
-#[ic_cdk::update]
-async fn execute_secure_mpc_computation(
-    team_id: String,
-    computation_request: String,
-    data_sources: Vec<String>,
-) -> Result<ComputationResult, String> {
-    // Use parameters to avoid lint warnings
-    let _team_id = team_id;
-    let _computation_request = computation_request;
-    
-    // Mock secure computation result for now
-    Ok(ComputationResult {
-        insights: format!("Secure MPC completed for {} data sources", data_sources.len()),
-        privacy_proof: "Secure MPC privacy proof generated".to_string(),
-        timestamp: ic_cdk::api::time(),
-    })
-}
-
-#[ic_cdk::update]
-fn derive_agent_encryption_key(agent_id: String) -> Result<Vec<u8>, String> {
-    // Mock key derivation for now
-    Ok(format!("key_for_{}", agent_id).into_bytes())
+// Withdraw a pending LLM query (requester only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn cancel_llm_query(query_id: String) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let message = LLM_QUERIES.with(|queries| {
+        let mut queries_map = queries.borrow_mut();
+        let query = queries_map.get_mut(&query_id)
+            .ok_or("Query not found")?;
+        
+        if query.requester != caller_principal {
+            return Err("Only the original requester can cancel this query".to_string());
+        }
+        
+        if !matches!(query.status, QueryStatus::Pending | QueryStatus::Approved) {
+            return Err(format!("Query cannot be cancelled. Current status: {:?}", query.status));
+        }
+        
+        // Invalidate collected signatures so the query can never be executed
+        query.received_signatures.clear();
+        query.status = QueryStatus::Cancelled;
+        
+        Ok(format!("Query {} cancelled", query_id))
+    })?;
+    
+    activity::record(&organizations::resource_workspace(&query_id), ActivityKind::RequestCancelled, caller_principal, &query_id, message.clone());
+    
+    Ok(message)
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+async fn generate_privacy_proof(
+    computation_id: String,
+) -> Result<String, String> {
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for proving: {:?} - {}", code, msg))?;
+    let proof = privacy_proofs::generate_proof(computation_id, "zk-SNARK".to_string(), &seed)?;
+    certify_privacy_audit(&proof.computation_id);
+    Ok(proof.proof_id)
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn verify_privacy_proof(proof_id: String) -> Result<bool, String> {
+    let verified = privacy_proofs::verify_proof(&proof_id)?;
+    if let Some(proof) = privacy_proofs::get_proof(&proof_id) {
+        certify_privacy_audit(&proof.computation_id);
+        events::record(caller(), WorkflowEvent::ProofVerified {
+            proof_id: proof_id.clone(),
+            computation_id: proof.computation_id.clone(),
+            verified,
+        }, current_timestamp());
+    }
+    Ok(verified)
+}
+
+// A proof in the versioned JSON or binary format, with its public inputs and
+// verification key, for verifiers working off-chain
+#[ic_cdk::query]
+fn export_proof(proof_id: String, format: ProofExportFormat) -> Result<ProofExport, String> {
+    let proof = privacy_proofs::get_proof(&proof_id)
+        .ok_or_else(|| format!("Proof {} not found", proof_id))?;
+    privacy_proofs::export::export(&proof, format)
+}
+
+// Verify the range proofs of a computation's published statistics
+#[ic_cdk::query]
+fn verify_range_proofs(computation_id: String) -> Result<Vec<RangeCheck>, String> {
+    privacy_proofs::range_checks(&computation_id)
+}
+
+// Re-hash proofs still carrying the legacy hash with SHA-256 (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn migrate_proof_hashes() -> Result<u64, String> {
+    require_admin()?;
+    let migrated = privacy_proofs::migrate_hashes();
+    let computations: std::collections::HashSet<&str> = migrated.iter().map(|p| p.computation_id.as_str()).collect();
+    for computation_id in computations {
+        certify_privacy_audit(computation_id);
+    }
+    Ok(migrated.len() as u64)
+}
+
+// Verify several proofs in one call; failures are reported per proof
+#[ic_cdk::update(guard = "rate_limited")]
+fn batch_verify_privacy_proofs(proof_ids: Vec<String>) -> Result<Vec<ProofVerification>, String> {
+    let results = privacy_proofs::batch_verify_proofs(&proof_ids)?;
+    let computations: std::collections::HashSet<&str> = results.iter().filter_map(|r| r.computation_id.as_deref()).collect();
+    for computation_id in computations {
+        certify_privacy_audit(computation_id);
+    }
+    for result in results.iter().filter(|r| r.error.is_none()) {
+        events::record(caller(), WorkflowEvent::ProofVerified {
+            proof_id: result.proof_id.clone(),
+            computation_id: result.computation_id.clone().unwrap_or_default(),
+            verified: result.verified,
+        }, current_timestamp());
+    }
+    Ok(results)
+}
+
+#[ic_cdk::query]
+fn list_proofs_for_computation(computation_id: String) -> Vec<PrivacyProof> {
+    privacy_proofs::get_proofs_for_computation(&computation_id)
+}
+
+// Certified privacy audit report for a computation
+#[ic_cdk::query]
+fn get_privacy_audit(computation_id: String) -> Result<CertifiedAudit, String> {
+    let report = privacy_proofs::get_audit(&computation_id)
+        .ok_or_else(|| format!("No proofs found for computation {}", computation_id))?;
+    
+    Ok(CertifiedAudit {
+        report,
+        certificate: certification::certificate(),
+        witness: certification::witness(&certification::audit_key(&computation_id))?,
+    })
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+async fn execute_secure_mpc_computation(
+    team_id: String,
+    computation_request: String,
+    data_sources: Vec<String>,
+) -> Result<ComputationResult, String> {
+    // Failed agents are substituted; a failed run keeps its partial results for the next attempt
+    // Direct team runs report progress under the team id
+    let team = mpc_engine::get_team_info(team_id)?;
+    progress::begin(&team.id, current_timestamp());
+    mpc_engine::execute_secure_mpc_computation(&team, &computation_request, &data_sources, &team.id).await
+}
+
+// Instructions a checkpoint step may use before yielding to the next message
+const CHECKPOINT_STEP_INSTRUCTIONS: u64 = 5_000_000_000;
+
+// Process a team's datasets in slices across as many messages as needed,
+// then run the team computation over the aggregate
+#[ic_cdk::update(guard = "rate_limited")]
+fn start_checkpointed_computation(
+    team_id: String,
+    computation_request: String,
+    slice_bytes: Option<u64>,
+) -> Result<CheckpointJob, String> {
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let team = mpc_engine::get_team_info(team_id.clone())?;
+    
+    let mut datasets = Vec::new();
+    for dataset_id in &team.data_source_ids {
+        let (len, permitted) = DATA_SOURCES.with(|sources| {
+            sources.borrow().get(dataset_id).map(|ds| (ds.encrypted_data.len() as u64, ds.access_permissions.contains(&caller)))
+        }).ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+        if !permitted {
+            return Err(format!("No access to dataset {}", dataset_id));
+        }
+        datasets.push((dataset_id.clone(), len));
+    }
+    
+    let job = CheckpointJob::new(
+        generate_id("job"),
+        team_id,
+        caller,
+        computation_request,
+        datasets,
+        slice_bytes.unwrap_or(mpc_engine::checkpoint::DEFAULT_SLICE_BYTES),
+        current_timestamp(),
+    );
+    mpc_engine::checkpoint::save(job.clone());
+    progress::begin(&job.job_id, job.started_at);
+    schedule_checkpoint_step(job.job_id.clone());
+    Ok(job)
+}
+
+// Whether `caller` may run over the datasets, each in or shared with its
+// workspace: access to all of them suffices, and other parties' datasets
+// need a computation approved by their owners
+fn authorize_run(caller: Principal, dataset_ids: &[String], computation_id: Option<&str>) -> Result<(), String> {
+    let mut datasets = Vec::new();
+    for dataset_id in dataset_ids {
+        organizations::ensure_visible(dataset_id, caller)?;
+        datasets.push(DATA_SOURCES.with(|sources| sources.borrow().get(dataset_id).cloned())
+            .ok_or_else(|| format!("Dataset {} not found", dataset_id))?);
+    }
+    let computation = match computation_id {
+        Some(id) => Some(COMPUTATION_REQUESTS.with(|requests| requests.borrow().get(id).cloned())
+            .ok_or_else(|| format!("Computation {} not found", id))?),
+        None => None,
+    };
+    run_approval::authorize(caller, &datasets, computation.as_ref())
+}
+
+// Submit a numeric column as additive secret shares, one vector per share
+// holder, optionally with shares of its squares for variances (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_column_shares(
+    dataset_id: String,
+    column: String,
+    shares: Vec<Vec<u64>>,
+    square_shares: Option<Vec<Vec<u64>>>,
+) -> Result<(), String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() {
+        return Err("Only the dataset owner can submit its column shares".to_string());
+    }
+    mpc_engine::secure_sum::submit(&dataset_id, &column, shares, square_shares)?;
+    audit_log::record(AuditEvent::Upload, caller(), &dataset_id, format!("Secret shares submitted for column '{}'", column));
+    Ok(())
+}
+
+// Sum and count of a column across datasets, computed over secret shares;
+// other parties' datasets need a computation they approved
+#[ic_cdk::update(guard = "rate_limited")]
+fn secure_sum(dataset_ids: Vec<String>, column: String, computation_id: Option<String>) -> Result<SecureSumResult, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &dataset_ids, computation_id.as_deref())?;
+    
+    let result = mpc_engine::secure_sum::sum(&dataset_ids, &column)?;
+    for dataset_id in &dataset_ids {
+        dataset_access::record(dataset_id, caller, AccessType::SecureSum, None, vec![column.clone()]);
+    }
+    audit_log::record(AuditEvent::Execution, caller, &column,
+        format!("Secure sum of '{}' over {} datasets", column, dataset_ids.len()));
+    Ok(result)
+}
+
+// Mean, variance and standard deviation of a column across datasets from
+// secret shares, with a proof of the records each dataset contributed; other
+// parties' datasets need a computation they approved
+#[ic_cdk::update(guard = "rate_limited")]
+fn secure_statistics(dataset_ids: Vec<String>, column: String, computation_id: Option<String>) -> Result<SecureStatistics, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &dataset_ids, computation_id.as_deref())?;
+    
+    let run_id = generate_id("stats");
+    let mut stats = mpc_engine::secure_sum::statistics(&run_id, &dataset_ids, &column)?;
+    let proof = privacy_proofs::generate_record_count_proof(run_id.clone())?;
+    stats.record_count_proof_id = Some(proof.proof_id);
+    certify_privacy_audit(&run_id);
+    
+    for dataset_id in &dataset_ids {
+        dataset_access::record(dataset_id, caller, AccessType::SecureSum, Some(&run_id), vec![column.clone()]);
+    }
+    lineage::record_inputs(&run_id, format!("Secure statistics of '{}'", column), &dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), format!("Statistics of '{}'", column), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller, &run_id,
+        format!("Secure statistics of '{}' over {} datasets", column, dataset_ids.len()));
+    Ok(stats)
+}
+
+// Open a private set intersection between one of the caller's datasets and
+// another party's, with the caller's hashed and blinded record identifiers;
+// cardinality-only mode reveals just the (optionally noisy) overlap size
+#[ic_cdk::update(guard = "rate_limited")]
+fn start_set_intersection(
+    dataset_id: String,
+    other_dataset_id: String,
+    blinded_ids: Vec<Vec<u8>>,
+    mode: PsiMode,
+) -> Result<PsiSession, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let owner_of = |id: &String| DATA_SOURCES.with(|sources| sources.borrow().get(id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", id));
+    if owner_of(&dataset_id)? != caller {
+        return Err("Only the dataset owner can intersect its records".to_string());
+    }
+    let responder = owner_of(&other_dataset_id)?;
+    if responder == caller {
+        return Err("Both datasets belong to the caller".to_string());
+    }
+    
+    let session_id = generate_id("psi");
+    let session = mpc_engine::psi::start(session_id.clone(), (caller, dataset_id), (responder, other_dataset_id),
+        blinded_ids, mode, current_timestamp())?;
+    audit_log::record(AuditEvent::Execution, caller, &session_id,
+        format!("Set intersection requested with dataset {}", session.responder_dataset));
+    Ok(session)
+}
+
+// Answer a set intersection with the responder's own blinded identifiers and
+// the initiator's identifiers blinded again (responding dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn respond_set_intersection(session_id: String, blinded_ids: Vec<Vec<u8>>, initiator_ids: Vec<Vec<u8>>) -> Result<PsiSession, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let session = mpc_engine::psi::respond(&session_id, caller, blinded_ids, initiator_ids)?;
+    audit_log::record(AuditEvent::Execution, caller, &session_id,
+        format!("Set intersection answered for dataset {}", session.responder_dataset));
+    Ok(session)
+}
+
+// Complete a set intersection with the responder's identifiers blinded again,
+// returning the positions of the initiator's identifiers in common
+#[ic_cdk::update(guard = "rate_limited")]
+async fn complete_set_intersection(session_id: String, responder_ids: Vec<Vec<u8>>) -> Result<PsiResult, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for noise: {:?} - {}", code, msg))?;
+    let session = mpc_engine::psi::complete(&session_id, caller, responder_ids, seed)?;
+    let result = mpc_engine::psi::result(&session_id, caller)?;
+    
+    for dataset_id in [&session.initiator_dataset, &session.responder_dataset] {
+        dataset_access::record(dataset_id, caller, AccessType::SetIntersection, Some(&session_id), vec![]);
+    }
+    audit_log::record(AuditEvent::Execution, caller, &session_id,
+        format!("Set intersection completed with {} records in common", result.intersection_size));
+    Ok(result)
+}
+
+// Blinded identifiers of a set intersection, for the two parties to blind again
+#[ic_cdk::query]
+fn get_set_intersection(session_id: String) -> Result<PsiSession, String> {
+    mpc_engine::psi::view(&session_id, caller())
+}
+
+// Positions of the caller's identifiers found in the other party's set
+#[ic_cdk::query]
+fn get_set_intersection_result(session_id: String) -> Result<PsiResult, String> {
+    mpc_engine::psi::result(&session_id, caller())
+}
+
+// Submit Bloom filter encodings (CLKs) of a dataset's records for linkage,
+// in record order (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_linkage_encodings(dataset_id: String, encodings: Vec<Vec<u8>>) -> Result<(), String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() {
+        return Err("Only the dataset owner can submit its linkage encodings".to_string());
+    }
+    let count = encodings.len();
+    mpc_engine::linkage::submit(&dataset_id, encodings)?;
+    audit_log::record(AuditEvent::Upload, caller(), &dataset_id, format!("Linkage encodings submitted for {} records", count));
+    Ok(())
+}
+
+// Link records of two datasets whose encodings reach the similarity
+// threshold, optionally for a computation whose approvers review the quality;
+// another party's dataset needs that computation to be approved by its owner
+#[ic_cdk::update(guard = "rate_limited")]
+fn link_records(
+    dataset_id: String,
+    other_dataset_id: String,
+    threshold: f64,
+    computation_id: Option<String>,
+) -> Result<LinkageRun, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &[dataset_id.clone(), other_dataset_id.clone()], computation_id.as_deref())?;
+    
+    let run_id = generate_id("linkage");
+    let run = mpc_engine::linkage::link(run_id.clone(), caller, (dataset_id, other_dataset_id), threshold,
+        computation_id, current_timestamp())?;
+    for dataset_id in [&run.left_dataset, &run.right_dataset] {
+        dataset_access::record(dataset_id, caller, AccessType::RecordLinkage, Some(&run_id), vec![]);
+    }
+    lineage::record_inputs(&run_id, "Record linkage".to_string(),
+        &[run.left_dataset.clone(), run.right_dataset.clone()], current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), format!("{} matched records", run.quality.matches), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller, &run_id,
+        format!("Record linkage at threshold {} found {} matches", threshold, run.quality.matches));
+    Ok(run)
+}
+
+// Whether `principal` requested a linkage or owns one of its datasets
+fn is_linkage_party(run: &LinkageRun, principal: Principal) -> bool {
+    run.requester == principal || [&run.left_dataset, &run.right_dataset].iter().any(|id| {
+        DATA_SOURCES.with(|sources| sources.borrow().get(*id).map(|d| d.owner)) == Some(principal)
+    })
+}
+
+// Matched record pairs of a linkage (requester and dataset owners only)
+#[ic_cdk::query]
+fn get_record_linkage(run_id: String) -> Result<LinkageRun, String> {
+    let run = mpc_engine::linkage::get(&run_id).ok_or_else(|| format!("Linkage {} not found", run_id))?;
+    if !is_linkage_party(&run, caller()) {
+        return Err("Only the requester and the dataset owners can view matched records".to_string());
+    }
+    Ok(run)
+}
+
+// Quality metrics of a linkage, also visible to approvers of its computation
+#[ic_cdk::query]
+fn get_linkage_quality(run_id: String) -> Result<LinkageQuality, String> {
+    let caller = caller();
+    let run = mpc_engine::linkage::get(&run_id).ok_or_else(|| format!("Linkage {} not found", run_id))?;
+    let approver = run.computation_id.as_ref().and_then(|id| COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(id).map(|c| c.requester == caller || c.required_signatures.contains(&caller))
+    })).unwrap_or(false);
+    if !approver && !is_linkage_party(&run, caller) {
+        return Err("Only the parties and approvers of this linkage can view its quality".to_string());
+    }
+    Ok(run.quality)
+}
+
+// Join two datasets on a key column, keeping only rows paired by their latest
+// completed set intersection. One of the intersection's two parties runs it
+// under a computation both dataset owners approved; the joined view is stored
+// encrypted as a new dataset that only those two parties can access
+#[ic_cdk::update(guard = "rate_limited")]
+async fn secure_join(dataset_a: String, dataset_b: String, key_column: String, computation_id: String) -> Result<SecureJoinResult, String> {
+    pause::ensure_running()?;
+    let caller = require_permission(caller(), Permission::Compute)?;
+    anomaly::ensure_not_suspended(caller)?;
+    let (session_id, pairs) = mpc_engine::psi::pairs(&dataset_a, &dataset_b)?;
+    let session = mpc_engine::psi::view(&session_id, caller)?;
+    authorize_run(caller, &[dataset_a.clone(), dataset_b.clone()], Some(&computation_id))?;
+    
+    let joined_id = generate_id("joined");
+    let mut window = open_decryption_window(&joined_id)?;
+    let mut sources = Vec::new();
+    for dataset_id in [&dataset_a, &dataset_b] {
+        let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(dataset_id).cloned())
+            .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+        let derivation_path = dataset_key_path(&dataset);
+        let key = derive_vetkey_for_party(dataset.owner, derivation_path).await?;
+        window.ensure_open(current_timestamp())?;
+        let mut decrypted = decrypt_with_vetkey(&dataset.encrypted_data, window.hold_key(key));
+        window.add(String::from_utf8_lossy(&decrypted).to_string());
+        decrypted.zeroize();
+        audit_log::record(AuditEvent::Decryption, caller, dataset_id, format!("Dataset decrypted for join {}", joined_id));
+        dataset_access::record(dataset_id, caller, AccessType::Decryption, Some(&joined_id), vec![]);
+        sources.push(dataset);
+    }
+    let view = mpc_engine::join::join(&window.data()[0], &window.data()[1], &key_column, &pairs)?;
+    // The joined rows are plaintext too and are zeroized with the window
+    window.add(view.csv);
+    let csv = window.data()[2].as_bytes();
+    
+    // The canister owns the view, so no single party can derive its key
+    let owner = ic_cdk::id();
+    let derivation_path = format!("data_secure_join_{}", joined_id).into_bytes();
+    let key = derive_vetkey_for_party(owner, derivation_path).await?;
+    let joined = PrivateDataSource {
+        id: joined_id.clone(),
+        owner,
+        party_name: "secure_join".to_string(),
+        name: joined_id.clone(),
+        encrypted_data: encrypt_with_vetkey(csv, &key),
+        vetkey_id: String::new(),
+        schema: analytics::render_schema(&analytics::infer_schema(&tabular::parse(csv)?)),
+        record_count: view.rows as u32,
+        created_at: current_timestamp(),
+        access_permissions: vec![session.initiator, session.responder],
+        metadata: DatasetMetadata::default(),
+        tags: vec![],
+        allowed_purposes: query_policy::combine_purposes(&sources[0].allowed_purposes, &sources[1].allowed_purposes),
+    };
+    store_encrypted_columns(&joined, column_encryption::split_columns(csv)).await?;
+    window.ensure_open(current_timestamp())?;
+    close_decryption_window(window);
+    // Queries over the view spend no more than either source has left
+    let remaining = privacy_budget::budget(&dataset_a).remaining_epsilon.min(privacy_budget::budget(&dataset_b).remaining_epsilon);
+    privacy_budget::set_total(&joined_id, remaining)?;
+    history::snapshot_permissions(&joined);
+    lineage::record_inputs(&joined_id, format!("Join of {} and {} on '{}'", dataset_a, dataset_b, key_column),
+        &[dataset_a.clone(), dataset_b.clone()], joined.created_at);
+    lineage::record_derived_dataset(&joined_id, &joined_id, joined.created_at);
+    DATA_SOURCES.with(|sources| sources.borrow_mut().insert(joined_id.clone(), joined));
+    organizations::assign(&joined_id, caller);
+    
+    audit_log::record(AuditEvent::Upload, caller, &joined_id,
+        format!("Joined view of {} and {} on '{}' with {} rows", dataset_a, dataset_b, key_column, view.rows));
+    Ok(SecureJoinResult {
+        dataset_id: joined_id,
+        psi_session_id: session_id,
+        rows: view.rows,
+        mismatched_keys: view.mismatched_keys,
+    })
+}
+
+// Trends, moving averages, seasonality and period-over-period changes of a
+// date column across datasets, interpreted by the data science agent. Only
+// the date and value columns are decrypted.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn analyze_time_series(request: TimeSeriesRequest) -> Result<TimeSeriesAnalysis, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &request.dataset_ids, request.computation_id.as_deref())?;
+    
+    let run_id = generate_id("timeseries");
+    let columns: Vec<String> = std::iter::once(request.date_column.clone()).chain(request.value_column.clone()).collect();
+    let mut window = open_decryption_window(&run_id)?;
+    for dataset_id in &request.dataset_ids {
+        let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(dataset_id).cloned())
+            .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+        let decrypted = decrypt_dataset_columns(&run_id, &dataset, &columns).await?;
+        window.add(decrypted);
+        window.ensure_open(current_timestamp())?;
+    }
+    progress::begin(&run_id, current_timestamp());
+    let analysis = mpc_engine::run_time_series(&run_id, &request, window.data()).await?;
+    close_decryption_window(window);
+    
+    lineage::record_inputs(&run_id, format!("Time series of '{}'", request.date_column), &request.dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), format!("{} periods", analysis.periods.len()), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller, &run_id,
+        format!("Time series of '{}' over {} datasets", request.date_column, request.dataset_ids.len()));
+    Ok(analysis)
+}
+
+// Pay for a private analysis from each dataset's privacy budget, then
+// decrypt the columns it reads; the first dataset's schema declares them.
+// Other parties' datasets need a computation they approved.
+async fn private_analysis_inputs(
+    run_id: &str,
+    dataset_ids: &[String],
+    columns: &[String],
+    epsilon: Option<f64>,
+    computation_id: Option<&str>,
+) -> Result<(Vec<analytics::ColumnSpec>, decryption_window::DecryptionContext, f64, differential_privacy::NoiseSource), String> {
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, dataset_ids, computation_id)?;
+    let first = dataset_ids.first().ok_or("Choose at least one dataset")?;
+    let schema = DATA_SOURCES.with(|sources| sources.borrow().get(first).map(|d| d.schema.clone()))
+        .ok_or_else(|| format!("Dataset {} not found", first))?;
+    let schema = analytics::parse_schema(&schema)?;
+    let config = DpConfig {
+        epsilon: epsilon.unwrap_or_else(|| differential_privacy::config().epsilon),
+        delta: 0.0,
+        mechanism: NoiseMechanism::Laplace,
+    };
+    differential_privacy::validate(&config)?;
+    privacy_budget::validate(dataset_ids, &config)?;
+    privacy_budget::debit(dataset_ids, run_id, config.epsilon, current_timestamp())?;
+    
+    let mut window = open_decryption_window(run_id)?;
+    for dataset_id in dataset_ids {
+        let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(dataset_id).cloned())
+            .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+        let decrypted = decrypt_dataset_columns(run_id, &dataset, columns).await?;
+        window.add(decrypted);
+        window.ensure_open(current_timestamp())?;
+    }
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for noise: {:?} - {}", code, msg))?;
+    window.ensure_open(current_timestamp())?;
+    Ok((schema, window, config.epsilon, differential_privacy::NoiseSource::new(seed)))
+}
+
+// Pearson and Spearman correlation matrices of bounded numeric and boolean
+// columns across datasets, released with differential privacy
+#[ic_cdk::update(guard = "rate_limited")]
+async fn correlate_columns(request: CorrelationRequest) -> Result<CorrelationMatrix, String> {
+    pause::ensure_running()?;
+    let run_id = generate_id("correlation");
+    let (schema, window, epsilon, mut source) =
+        private_analysis_inputs(&run_id, &request.dataset_ids, &request.columns, request.epsilon, request.computation_id.as_deref()).await?;
+    let min_records = disclosure_control::policy().min_cohort_size;
+    let matrix = regression::correlate(&schema, &request, window.data(), epsilon, min_records, &mut source)?;
+    close_decryption_window(window);
+    
+    lineage::record_inputs(&run_id, format!("Correlation of {}", request.columns.join(", ")), &request.dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), "Correlation matrices".to_string(), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller(), &run_id,
+        format!("Correlation of {} columns over {} datasets with epsilon {}", request.columns.len(), request.dataset_ids.len(), epsilon));
+    Ok(matrix)
+}
+
+// Linear or logistic regression of an outcome on predictor columns across
+// datasets, with differentially private coefficients
+#[ic_cdk::update(guard = "rate_limited")]
+async fn fit_regression(request: RegressionRequest) -> Result<RegressionModel, String> {
+    pause::ensure_running()?;
+    let run_id = generate_id("regression");
+    let mut columns = vec![request.outcome.clone()];
+    columns.extend(request.predictors.iter().filter(|p| **p != request.outcome).cloned());
+    let (schema, window, epsilon, mut source) =
+        private_analysis_inputs(&run_id, &request.dataset_ids, &columns, request.epsilon, request.computation_id.as_deref()).await?;
+    let min_records = disclosure_control::policy().min_cohort_size;
+    let model = regression::regress(&schema, &request, window.data(), epsilon, min_records, &mut source)?;
+    close_decryption_window(window);
+    
+    lineage::record_inputs(&run_id, format!("{:?} regression of '{}'", request.kind, request.outcome), &request.dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), "Regression coefficients".to_string(), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller(), &run_id,
+        format!("{:?} regression of '{}' on {} predictors with epsilon {}", request.kind, request.outcome, request.predictors.len(), epsilon));
+    Ok(model)
+}
+
+// Define a cohort as conditions on columns, e.g. "age>60 AND treatment=Drug_X"
+#[ic_cdk::update(guard = "rate_limited")]
+fn define_cohort(name: String, filter: String) -> Result<Cohort, String> {
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    cohorts::define(generate_id("cohort"), caller, name, filter, current_timestamp())
+}
+
+// Cohorts the caller has defined
+#[ic_cdk::query]
+fn list_cohorts() -> Vec<Cohort> {
+    cohorts::list(caller())
+}
+
+// Differentially private outcome statistics per cohort across datasets, with
+// cohorts below the minimum size suppressed
+#[ic_cdk::update(guard = "rate_limited")]
+async fn compare_cohorts(request: CohortComparisonRequest) -> Result<CohortComparison, String> {
+    pause::ensure_running()?;
+    let run_id = generate_id("cohorts");
+    let selected = request.cohort_ids.iter().map(|id| cohorts::get(id)).collect::<Result<Vec<_>, _>>()?;
+    let mut columns = vec![request.outcome.clone()];
+    for condition in selected.iter().flat_map(|c| &c.conditions) {
+        if !columns.contains(&condition.column) {
+            columns.push(condition.column.clone());
+        }
+    }
+    let min_cohort_size = request.min_cohort_size.unwrap_or(0).max(disclosure_control::policy().min_cohort_size);
+    let (schema, window, epsilon, mut source) =
+        private_analysis_inputs(&run_id, &request.dataset_ids, &columns, request.epsilon, request.computation_id.as_deref()).await?;
+    let comparison = cohorts::compare(&schema, &selected, &request.outcome, window.data(), min_cohort_size, epsilon, &mut source)?;
+    close_decryption_window(window);
+    
+    lineage::record_inputs(&run_id, format!("Cohort comparison of '{}'", request.outcome), &request.dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), "Cohort statistics".to_string(), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller(), &run_id,
+        format!("Comparison of '{}' across {} cohorts over {} datasets with epsilon {}", request.outcome, selected.len(), request.dataset_ids.len(), epsilon));
+    Ok(comparison)
+}
+
+// Contingency table of two categorical columns across datasets, with noisy
+// cells, small-cell suppression and a chi-square test of independence
+#[ic_cdk::update(guard = "rate_limited")]
+async fn cross_tabulate(request: ContingencyRequest) -> Result<ContingencyTable, String> {
+    pause::ensure_running()?;
+    let run_id = generate_id("crosstab");
+    let columns = [request.row_variable.clone(), request.column_variable.clone()];
+    let (schema, window, epsilon, mut source) =
+        private_analysis_inputs(&run_id, &request.dataset_ids, &columns, request.epsilon, request.computation_id.as_deref()).await?;
+    let table = crosstab::tabulate(&schema, &request, window.data(), epsilon, &disclosure_control::policy(), &mut source)?;
+    close_decryption_window(window);
+    
+    lineage::record_inputs(&run_id, format!("Cross-tabulation of '{}' by '{}'", request.row_variable, request.column_variable), &request.dataset_ids, current_timestamp());
+    lineage::record_result(&run_id, &format!("{}/result", run_id), "Contingency table".to_string(), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller(), &run_id,
+        format!("Cross-tabulation over {} datasets with epsilon {}, {} cells suppressed", request.dataset_ids.len(), epsilon, table.suppressed_cells));
+    Ok(table)
+}
+
+// Open a statistics run over a column whose owners each submit their count,
+// sum and sum of squares as one pairwise-masked vector
+#[ic_cdk::update(guard = "rate_limited")]
+fn open_masked_statistics(dataset_ids: Vec<String>, column: String) -> Result<String, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &dataset_ids, None)?;
+    let owners = dataset_ids.iter()
+        .map(|id| DATA_SOURCES.with(|sources| sources.borrow().get(id).map(|d| d.owner))
+            .ok_or_else(|| format!("Dataset {} not found", id)))
+        .collect::<Result<Vec<_>, String>>()?;
+    
+    let run_id = generate_id("stats");
+    mpc_engine::secure_sum::open_masked(&run_id, caller, &column, owners)?;
+    for dataset_id in &dataset_ids {
+        dataset_access::record(dataset_id, caller, AccessType::SecureSum, Some(&run_id), vec![column.clone()]);
+    }
+    audit_log::record(AuditEvent::Execution, caller, &run_id,
+        format!("Masked statistics of '{}' opened over {} datasets", column, dataset_ids.len()));
+    Ok(run_id)
+}
+
+// Submit a dataset owner's masked totals; the last owner's submission
+// produces the statistics
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_masked_statistics(run_id: String, masked_totals: Vec<u64>) -> Result<Option<SecureStatistics>, String> {
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let stats = mpc_engine::secure_sum::submit_masked(&run_id, caller, masked_totals)?;
+    audit_log::record(AuditEvent::Upload, caller, &run_id, "Masked totals submitted".to_string());
+    Ok(stats)
+}
+
+// Statistics of a completed masked run (requester and dataset owners only)
+#[ic_cdk::query]
+fn get_masked_statistics(run_id: String) -> Result<SecureStatistics, String> {
+    mpc_engine::secure_sum::masked_statistics(&run_id, caller())
+}
+
+// Register a Paillier public key whose decryption exponent is split among the
+// holders; the caller must be one of them
+#[ic_cdk::update(guard = "rate_limited")]
+fn register_paillier_key(modulus: Vec<u8>, holders: Vec<Principal>) -> Result<PaillierKey, String> {
+    let caller = caller();
+    if !holders.contains(&caller) {
+        return Err("The caller must hold a share of the key".to_string());
+    }
+    if let Some(unknown) = holders.iter().find(|p| !PARTIES.with(|parties| parties.borrow().contains_key(p))) {
+        return Err(format!("{} is not a registered party", unknown));
+    }
+    if mpc_engine::paillier::modulus_bits(&modulus) < mpc_engine::paillier::MIN_MODULUS_BITS {
+        return Err(format!("Paillier moduli need at least {} bits", mpc_engine::paillier::MIN_MODULUS_BITS));
+    }
+    let key = mpc_engine::paillier::register_key(generate_id("paillier"), modulus, holders, caller, current_timestamp())?;
+    audit_log::record(AuditEvent::KeyDerivation, caller, &key.id,
+        format!("Paillier key registered with {} holders", key.holders.len()));
+    Ok(key)
+}
+
+// Upload a numeric column encrypted under a Paillier key, one ciphertext per
+// row (dataset owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn upload_paillier_column(dataset_id: String, column: String, key_id: String, ciphertexts: Vec<Vec<u8>>) -> Result<(), String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() {
+        return Err("Only the dataset owner can upload its encrypted columns".to_string());
+    }
+    mpc_engine::paillier::upload_column(&dataset_id, &column, &key_id, ciphertexts)?;
+    audit_log::record(AuditEvent::Upload, caller(), &dataset_id, format!("Paillier-encrypted column '{}' uploaded", column));
+    Ok(())
+}
+
+// Encrypted sum and count of a column across datasets, computed without
+// decryption; the key holders decrypt the total together
+#[ic_cdk::update(guard = "rate_limited")]
+fn paillier_sum(key_id: String, dataset_ids: Vec<String>, column: String) -> Result<EncryptedAggregate, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    authorize_run(caller, &dataset_ids, None)?;
+    
+    let aggregate = mpc_engine::paillier::sum(generate_id("paillier_sum"), caller, &key_id, dataset_ids, &column, current_timestamp())?;
+    for dataset_id in &aggregate.dataset_ids {
+        dataset_access::record(dataset_id, caller, AccessType::HomomorphicSum, Some(&aggregate.id), vec![column.clone()]);
+    }
+    audit_log::record(AuditEvent::Execution, caller, &aggregate.id,
+        format!("Encrypted sum of '{}' over {} datasets", column, aggregate.dataset_ids.len()));
+    Ok(aggregate)
+}
+
+// Submit a key holder's partial decryption of an encrypted total; the last
+// holder's reveals it
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_partial_decryption(aggregate_id: String, partial: Vec<u8>) -> Result<EncryptedAggregate, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let aggregate = mpc_engine::paillier::submit_partial(&aggregate_id, caller, partial)?;
+    audit_log::record(AuditEvent::Decryption, caller, &aggregate_id, "Partial decryption submitted".to_string());
+    Ok(aggregate)
+}
+
+// An encrypted aggregate (requester and key holders only)
+#[ic_cdk::query]
+fn get_encrypted_aggregate(aggregate_id: String) -> Result<EncryptedAggregate, String> {
+    let caller = caller();
+    let aggregate = mpc_engine::paillier::aggregate(&aggregate_id).ok_or_else(|| format!("Aggregate {} not found", aggregate_id))?;
+    let holder = mpc_engine::paillier::key(&aggregate.key_id).is_some_and(|key| key.holders.contains(&caller));
+    if aggregate.requester != caller && !holder {
+        return Err("Only the requester and the key holders can view this aggregate".to_string());
+    }
+    Ok(aggregate)
+}
+
+// Start federated training of a model among registered parties, from its
+// initial weights (version 0)
+#[ic_cdk::update(guard = "rate_limited")]
+fn create_federated_model(name: String, participants: Vec<Principal>, initial_weights: Vec<f64>) -> Result<FederatedModel, String> {
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    if let Some(unknown) = participants.iter().find(|p| !PARTIES.with(|parties| parties.borrow().contains_key(p))) {
+        return Err(format!("{} is not a registered party", unknown));
+    }
+    let model = mpc_engine::federated::create(generate_id("model"), name, caller, participants, initial_weights, current_timestamp())?;
+    audit_log::record(AuditEvent::Execution, caller, &model.id,
+        format!("Federated model '{}' created for {} participants", model.name, model.participants.len()));
+    Ok(model)
+}
+
+// Submit a participant's masked model update for the current round; the
+// round's last update produces the next model version
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_model_update(model_id: String, round: u64, masked_update: Vec<u64>) -> Result<Option<ModelVersion>, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let version = mpc_engine::federated::submit(&model_id, caller, round, masked_update, current_timestamp())?;
+    audit_log::record(AuditEvent::Execution, caller, &model_id, format!("Masked update submitted for round {}", round));
+    if let Some(version) = &version {
+        audit_log::record(AuditEvent::Execution, caller, &model_id,
+            format!("Round {} aggregated into model version {}", round, version.version));
+    }
+    Ok(version)
+}
+
+fn require_model_party(model_id: &str, caller: Principal) -> Result<FederatedModel, String> {
+    let model = mpc_engine::federated::get(model_id).ok_or_else(|| format!("Model {} not found", model_id))?;
+    if model.owner != caller && !model.participants.contains(&caller) {
+        return Err("Only the model owner and participants can view this model".to_string());
+    }
+    Ok(model)
+}
+
+// Training status of a federated model
+#[ic_cdk::query]
+fn get_federated_model(model_id: String) -> Result<FederatedModel, String> {
+    require_model_party(&model_id, caller())
+}
+
+// A registered version of a federated model, the latest if none is given
+#[ic_cdk::query]
+fn get_model_version(model_id: String, version: Option<u64>) -> Result<ModelVersion, String> {
+    let model = require_model_party(&model_id, caller())?;
+    let version = version.unwrap_or(model.latest_version);
+    mpc_engine::federated::versions(&model_id).into_iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| format!("Model {} has no version {}", model_id, version))
+}
+
+// Progress and, once completed, the result of a checkpointed computation
+#[ic_cdk::query]
+fn get_checkpointed_computation(job_id: String) -> Result<CheckpointJob, String> {
+    let job = mpc_engine::checkpoint::get(&job_id).ok_or_else(|| format!("Job {} not found", job_id))?;
+    if job.requester != caller() {
+        return Err("Only the requester can view this job".to_string());
+    }
+    Ok(job)
+}
+
+fn schedule_checkpoint_step(job_id: String) {
+    ic_cdk_timers::set_timer(std::time::Duration::ZERO, move || ic_cdk::spawn(run_checkpoint_step(job_id)));
+}
+
+async fn run_checkpoint_step(job_id: String) {
+    match checkpoint_step(&job_id).await {
+        Ok(true) => schedule_checkpoint_step(job_id),
+        Ok(false) => {},
+        Err(e) => {
+            progress::report(&job_id, ProgressEvent::Failed(e.clone()), current_timestamp());
+            if let Some(mut job) = mpc_engine::checkpoint::get(&job_id) {
+                job.status = JobStatus::Failed;
+                job.error = Some(e);
+                job.updated_at = current_timestamp();
+                mpc_engine::checkpoint::save(job);
+            }
+        }
+    }
+}
+
+// Process slices until the instruction budget is spent; returns whether more steps are needed
+async fn checkpoint_step(job_id: &str) -> Result<bool, String> {
+    pause::ensure_running()?;
+    let mut job = mpc_engine::checkpoint::get(job_id).ok_or_else(|| format!("Job {} not found", job_id))?;
+    if job.status != JobStatus::Running {
+        return Ok(false);
+    }
+    
+    let Some(dataset_id) = job.current_dataset().map(|id| id.to_string()) else {
+        // Every slice is processed: run the team over the aggregate
+        let team = mpc_engine::get_team_info(job.team_id.clone())?;
+        let request = format!("{}\n\n{}", job.computation_request, job.summary());
+        let result = mpc_engine::execute_secure_mpc_computation(&team, &request, &job.dataset_ids, &job.job_id).await?;
+        job.result = Some(result);
+        job.status = JobStatus::Completed;
+        job.percent_complete = 100;
+        job.updated_at = current_timestamp();
+        mpc_engine::checkpoint::save(job);
+        return Ok(false);
+    };
+    
+    let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).cloned())
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    // Each step decrypts its slices inside a window of its own
+    let window = open_decryption_window(&format!("{}/step{}", job.job_id, job.steps))?;
+    let derivation_path = dataset_key_path(&dataset);
+    let key = Zeroizing::new(derive_vetkey_for_party(dataset.owner, derivation_path).await?);
+    window.ensure_open(current_timestamp())?;
+    if job.offset == 0 {
+        audit_log::record(AuditEvent::Decryption, job.requester, &dataset.id,
+            format!("Dataset decrypted in slices for job {}", job.job_id));
+        dataset_access::record(&dataset.id, job.requester, AccessType::Decryption, Some(&job.job_id), vec![]);
+    }
+    
+    let len = dataset.encrypted_data.len() as u64;
+    while api::instruction_counter() < CHECKPOINT_STEP_INSTRUCTIONS {
+        let Some(range) = job.next_slice(len) else { break };
+        let offset = range.start;
+        let plaintext = Zeroizing::new(mpc_engine::checkpoint::decrypt_slice(&dataset.encrypted_data[range], &key, offset));
+        window.count(plaintext.len());
+        job.absorb(&plaintext, current_timestamp());
+    }
+    close_decryption_window(window);
+    if job.next_slice(len).is_none() {
+        job.next_dataset();
+    }
+    job.steps += 1;
+    progress::report(&job.job_id, ProgressEvent::Fraction { done: job.processed_bytes, total: job.total_bytes }, current_timestamp());
+    mpc_engine::checkpoint::save(job);
+    Ok(true)
+}
+
+// Encrypt a derived key to the caller's transport public key with fresh randomness
+async fn wrap_for_transport(key: &[u8], transport_public_key: &[u8]) -> Result<Vec<u8>, String> {
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness to wrap the key: {:?} - {}", code, msg))?;
+    transport_key::wrap(key, transport_public_key, &seed)
+}
+
+// An agent's encryption key, encrypted to the caller's transport public key
+#[ic_cdk::update(guard = "rate_limited")]
+async fn derive_agent_encryption_key(agent_id: String, transport_public_key: Vec<u8>) -> Result<Vec<u8>, String> {
+    pause::ensure_running()?;
+    key_revocation::ensure_not_revoked(&agent_id)?;
+    // Mock key derivation for now
+    let mut key = format!("key_for_{}", agent_id).into_bytes();
+    let wrapped = wrap_for_transport(&key, &transport_public_key).await;
+    key.zeroize();
+    wrapped
+}
+
+// The caller's identity key for a purpose, encrypted to its transport public
+// key; it expires after `ttl_seconds` or the default key lifetime
+#[ic_cdk::update(guard = "rate_limited")]
+async fn derive_user_vetkd_key(
+    purpose: String,
+    derivation_path: Vec<u8>,
+    ttl_seconds: Option<u64>,
+    transport_public_key: Vec<u8>,
+) -> Result<WrappedKey, String> {
+    pause::ensure_running()?;
+    let key = identity_manager::derive_vetkd_key(purpose, derivation_path, ttl_seconds.map(|s| s.saturating_mul(1_000_000_000)))?;
+    let encrypted_key = wrap_for_transport(&key.derived_key, &transport_public_key).await?;
+    audit_log::record(AuditEvent::KeyDerivation, caller(), &key.key_id, "Identity key issued under a transport key".to_string());
+    Ok(WrappedKey { key_id: key.key_id, encrypted_key, expires_at: key.expires_at })
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn secure_agent_communication(
     sender_id: String,
     recipient_id: String,
@@ -565,11 +2979,17 @@ async fn secure_agent_communication(
 }
 
 // Export Candid interface for frontend integration
-// VetKD functions for secure encryption/decryption (Mock implementation for local development)
-#[ic_cdk::update]
+// VetKD functions for secure encryption/decryption; the configuration
+// chooses the mock for local development or the real vetKD system API
+#[ic_cdk::update(guard = "rate_limited")]
 async fn vetkd_public_key() -> VetkdPublicKeyResponse {
-    // Mock public key for local development
-    // In production, this would call the real vetKD system API
+    let settings = config::settings();
+    if settings.vetkd_mode == VetKdMode::Real {
+        return match vetkd_api::public_key(&settings.vetkd_key_name).await {
+            Ok(public_key) => VetkdPublicKeyResponse::Ok(public_key),
+            Err(e) => VetkdPublicKeyResponse::Err(e),
+        };
+    }
     let mock_public_key = vec![
         0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
         0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
@@ -580,36 +3000,103 @@ async fn vetkd_public_key() -> VetkdPublicKeyResponse {
     VetkdPublicKeyResponse::Ok(mock_public_key)
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn vetkd_encrypted_key(
     encryption_public_key: Vec<u8>,
     derivation_id: Vec<u8>,
 ) -> VetkdEncryptedKeyResponse {
-    // Mock encrypted key derivation for local development
-    // In production, this would call the real vetKD system API
-    
-    // Create a deterministic "encrypted" key based on derivation_id and transport key
-    let mut mock_encrypted_key = Vec::new();
-    mock_encrypted_key.extend_from_slice(&derivation_id);
-    mock_encrypted_key.extend_from_slice(&encryption_public_key[..16.min(encryption_public_key.len())]);
-    
-    // Pad to 64 bytes for realistic size
-    while mock_encrypted_key.len() < 64 {
-        mock_encrypted_key.push(0x42);
+    if let Err(e) = pause::ensure_running() {
+        return VetkdEncryptedKeyResponse::Err(e);
+    }
+    match issue_encrypted_key(&encryption_public_key, &derivation_id).await {
+        Ok(key) => VetkdEncryptedKeyResponse::Ok(key),
+        Err(e) => VetkdEncryptedKeyResponse::Err(e),
     }
+}
+
+async fn issue_encrypted_key(encryption_public_key: &[u8], derivation_id: &[u8]) -> Result<Vec<u8>, String> {
+    let settings = config::settings();
+    let encrypted_key = match settings.vetkd_mode {
+        VetKdMode::Real => vetkd_api::derive_key(&settings.vetkd_key_name, derivation_id.to_vec(), encryption_public_key.to_vec()).await?,
+        VetKdMode::Mock => {
+            // Create a deterministic "encrypted" key based on derivation_id and transport key
+            let mut mock_encrypted_key = Vec::new();
+            mock_encrypted_key.extend_from_slice(derivation_id);
+            mock_encrypted_key.extend_from_slice(&encryption_public_key[..16.min(encryption_public_key.len())]);
+            
+            // Pad to 64 bytes for realistic size
+            while mock_encrypted_key.len() < 64 {
+                mock_encrypted_key.push(0x42);
+            }
+            mock_encrypted_key
+        }
+    };
+    audit_log::record(AuditEvent::KeyDerivation, ic_cdk::caller(), &hex::encode(derivation_id),
+        "vetKD encrypted key issued".to_string());
     
-    VetkdEncryptedKeyResponse::Ok(mock_encrypted_key)
+    Ok(encrypted_key)
+}
+
+// Pairwise vetKD key the caller shares with `peer` in a secure aggregation
+// session (a federated model or a masked statistics run); both must take part
+#[ic_cdk::update(guard = "rate_limited")]
+async fn secure_aggregation_key(session_id: String, peer: Principal, encryption_public_key: Vec<u8>) -> VetkdEncryptedKeyResponse {
+    let caller = caller();
+    let participants = mpc_engine::federated::get(&session_id).map(|model| model.participants)
+        .or_else(|| mpc_engine::secure_sum::masked_participants(&session_id));
+    let Some(participants) = participants else {
+        return VetkdEncryptedKeyResponse::Err(format!("Session {} not found", session_id));
+    };
+    if caller == peer || !participants.contains(&caller) || !participants.contains(&peer) {
+        return VetkdEncryptedKeyResponse::Err("Pairwise keys are only issued between two participants".to_string());
+    }
+    let derivation_id = mpc_engine::secure_aggregation::pairwise_derivation_id(&session_id, caller, peer);
+    match issue_encrypted_key(&encryption_public_key, &derivation_id).await {
+        Ok(key) => VetkdEncryptedKeyResponse::Ok(key),
+        Err(e) => VetkdEncryptedKeyResponse::Err(e),
+    }
+}
+
+// Owners of a session's agents, or controllers, manage its key
+fn require_session_participant(session_id: &str) -> Result<Principal, String> {
+    let caller = caller();
+    let participants = vetkey_manager::session_participants(session_id)
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    if !participants.iter().any(|agent_id| agent_registry::owner_of(agent_id) == Some(caller)) {
+        require_admin()?;
+    }
+    Ok(caller)
+}
+
+// Extend an agent session's key by the session lifetime, returning when it
+// now expires; expired sessions cannot be renewed
+#[ic_cdk::update(guard = "rate_limited")]
+fn renew_secure_session(session_id: String) -> Result<u64, String> {
+    let caller = require_session_participant(&session_id)?;
+    let expires_at = vetkey_manager::renew_session(&session_id, current_timestamp(), vetkey_manager::DEFAULT_SESSION_TTL_NANOS)?;
+    audit_log::record(AuditEvent::KeyDerivation, caller, &session_id, "Session key renewed".to_string());
+    Ok(expires_at)
+}
+
+// Close an agent session and zeroize its key
+#[ic_cdk::update(guard = "rate_limited")]
+fn close_secure_session(session_id: String) -> Result<(), String> {
+    let caller = require_session_participant(&session_id)?;
+    vetkey_manager::close_session(&session_id)?;
+    audit_log::record(AuditEvent::KeyDerivation, caller, &session_id, "Session closed and its key zeroized".to_string());
+    Ok(())
 }
 
 // Enhanced dataset upload with vetKD encryption
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 async fn upload_encrypted_dataset(
     name: String,
     encrypted_data: Vec<u8>,
     schema: String,
     record_count: u32,
 ) -> Result<String, String> {
-    let caller = ic_cdk::caller();
+    let caller = require_permission(ic_cdk::caller(), Permission::Upload)?;
+    storage_quota::check(&storage_usage_of(caller), encrypted_data.len() as u64)?;
     let dataset_id = format!("dataset_{}_{}", caller.to_text(), ic_cdk::api::time());
     
     let dataset = PrivateDataSource {
@@ -623,11 +3110,23 @@ async fn upload_encrypted_dataset(
         record_count,
         created_at: ic_cdk::api::time(),
         access_permissions: vec![caller],
+        metadata: DatasetMetadata::default(),
+        tags: vec![],
+        allowed_purposes: None,
     };
     
+    let summary = format!("Encrypted dataset '{}' uploaded ({} records)", dataset.name, dataset.record_count);
+    let uploaded = WorkflowEvent::DatasetUploaded { dataset_id: dataset_id.clone(), name: dataset.name.clone() };
+    history::snapshot_permissions(&dataset);
+    lineage::new_version(&dataset_id, dataset.created_at);
     DATA_SOURCES.with(|sources| {
         sources.borrow_mut().insert(dataset_id.clone(), dataset)
     });
+    organizations::assign(&dataset_id, caller);
+    
+    audit_log::record(AuditEvent::Upload, caller, &dataset_id, summary.clone());
+    activity::record(&organizations::resource_workspace(&dataset_id), ActivityKind::DatasetUploaded, caller, &dataset_id, summary);
+    events::record(caller, uploaded, current_timestamp());
     
     Ok(dataset_id)
 }
@@ -637,18 +3136,35 @@ async fn upload_encrypted_dataset(
 // ============================================================================
 
 // Create a new computation request with signature requirements
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "rate_limited")]
 fn create_computation_request(
     title: String,
     description: String,
+    output_schema: Option<String>,
+    purpose: String,
+    columns: Option<Vec<String>>,
 ) -> Result<String, String> {
-    let caller = ic_cdk::caller();
+    let caller = require_permission(ic_cdk::caller(), Permission::Compute)?;
+    if let Some(ref schema) = output_schema {
+        secure_llm::parse_output_schema(schema)?;
+    }
     let request_id = generate_id("mpc");
     
-    // Get all registered parties for signature requirements
-    let all_parties = PARTIES.with(|parties| {
-        parties.borrow().keys().cloned().collect::<Vec<_>>()
+    // Every registered party of the requester's workspace must approve
+    let all_parties = workspace_parties(caller);
+    
+    // The computation works over the parties' data, so their purpose-bound
+    // datasets must all allow its purpose before anyone is asked to vote
+    let purpose = query_policy::normalize_purpose(&purpose)?;
+    let datasets: Vec<PrivateDataSource> = DATA_SOURCES.with(|sources| {
+        sources.borrow().values().filter(|d| all_parties.contains(&d.owner)).cloned().collect()
     });
+    let decision = query_policy::decide(vec![query_policy::check_purpose(&purpose, &datasets)]);
+    if !decision.approved {
+        return Err(decision.rationale);
+    }
+    let columns = columns.unwrap_or_default();
+    check_computation_columns(&columns, &datasets)?;
     
     // Create signature requirement for vetKD key derivation
     let signature_data = format!("{}:{}:{}", request_id, title, description);
@@ -666,7 +3182,7 @@ fn create_computation_request(
         title,
         description,
         requester: caller,
-        required_parties: 3, // Boston General, Novartis, MIT
+        required_parties: config::settings().min_parties,
         approvals: vec![],
         votes: vec![],
         status: "pending_approval".to_string(),
@@ -677,32 +3193,107 @@ fn create_computation_request(
         required_signatures: all_parties,
         received_signatures: vec![],
         vetkey_derivation_complete: false,
+        last_error: None,
+        retry_attempts: vec![],
+        shared_with: vec![],
+        encrypted_results: vec![],
+        output_schema,
+        structured_results: vec![],
+        agent_id: None,
+        agent_version: None,
+        priority: Priority::Normal,
+        llm_provider: None,
+        prompt_template: None,
+        purpose,
+        columns,
     };
     
+    let summary = format!("Computation '{}' requested", computation.title);
     COMPUTATION_REQUESTS.with(|requests| {
         requests.borrow_mut().insert(request_id.clone(), computation)
     });
+    organizations::assign(&request_id, caller);
+    computation_changed(&request_id);
+    
+    activity::record(&organizations::resource_workspace(&request_id), ActivityKind::ComputationRequested, caller, &request_id, summary);
     
     Ok(request_id)
 }
 
-// Get all computation requests (visible to all parties)
-#[ic_cdk::query]
-fn get_all_computation_requests() -> Vec<MPCComputation> {
-    COMPUTATION_REQUESTS.with(|requests| {
-        requests.borrow().values().cloned().collect()
-    })
-}
-
-// Vote on a computation request with cryptographic signature for vetKD
-#[ic_cdk::update]
-fn vote_on_computation_request(request_id: String, vote_decision: String) -> Result<String, String> {
+// A computation's column subset must exist in every dataset of its parties
+fn check_computation_columns(columns: &[String], datasets: &[PrivateDataSource]) -> Result<(), String> {
+    if columns.is_empty() {
+        return Ok(());
+    }
+    if datasets.is_empty() {
+        return Err("The parties have no datasets to select columns from".to_string());
+    }
+    for dataset in datasets {
+        let schema = analytics::parse_schema(&dataset.schema)?;
+        if let Some(missing) = columns.iter().find(|name| !schema.iter().any(|column| &column.name == *name)) {
+            return Err(format!("Dataset {} has no column '{}'", dataset.id, missing));
+        }
+    }
+    Ok(())
+}
+
+// The datasets a computation reads when it declares a column subset
+fn computation_datasets(computation: &MPCComputation) -> Vec<String> {
+    if computation.columns.is_empty() {
+        return vec![];
+    }
+    DATA_SOURCES.with(|sources| {
+        let mut ids: Vec<String> = sources.borrow().values()
+            .filter(|d| computation.required_signatures.contains(&d.owner))
+            .map(|d| d.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    })
+}
+
+// Results are visible to the requester, signers, and explicitly shared principals
+fn can_view_results(computation: &MPCComputation, principal: Principal) -> bool {
+    computation.requester == principal
+        || computation.received_signatures.contains(&principal)
+        || computation.shared_with.contains(&principal)
+}
+
+// Strip results the caller is not entitled to see
+fn redact_results(mut computation: MPCComputation, principal: Principal) -> MPCComputation {
+    if !can_view_results(&computation, principal) {
+        computation.results = None;
+    }
+    computation
+}
+
+// Get all computation requests (visible to the caller's workspace, results redacted)
+#[ic_cdk::query]
+fn get_all_computation_requests() -> Vec<MPCComputation> {
     let caller = ic_cdk::caller();
-    
     COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow()
+            .values()
+            .filter(|c| organizations::visible_to(&c.id, caller))
+            .cloned()
+            .map(|c| redact_results(c, caller))
+            .collect()
+    })
+}
+
+// Vote on a computation request with cryptographic signature for vetKD
+#[ic_cdk::update(guard = "rate_limited")]
+fn vote_on_computation_request(request_id: String, vote_decision: String) -> Result<String, String> {
+    let caller = require_permission(ic_cdk::caller(), Permission::Approve)?;
+    
+    let message = COMPUTATION_REQUESTS.with(|requests| {
         let mut requests_map = requests.borrow_mut();
         
         if let Some(computation) = requests_map.get_mut(&request_id) {
+            if !is_open_for_voting(&computation.status) {
+                return Err(format!("Computation request is not open for voting. Current status: {}", computation.status));
+            }
+            
             // Validate vote decision
             let vote_decision_lower = vote_decision.to_lowercase();
             if vote_decision_lower != "yes" && vote_decision_lower != "no" {
@@ -758,8 +3349,9 @@ fn vote_on_computation_request(request_id: String, vote_decision: String) -> Res
                 }
             }
             
-            // Update status based on votes, signatures and approvals
-            let total_parties = 3;
+            // Update status based on votes, signatures and approvals; every party
+            // asked to sign must approve, and never fewer than the configured minimum
+            let total_parties = computation.required_signatures.len().max(computation.required_parties as usize);
             let yes_votes = computation.votes.iter().filter(|v| v.decision == "yes").count();
             let no_votes = computation.votes.iter().filter(|v| v.decision == "no").count();
             let total_votes = computation.votes.len();
@@ -798,100 +3390,337 @@ fn vote_on_computation_request(request_id: String, vote_decision: String) -> Res
         } else {
             Err("Computation request not found".to_string())
         }
-    })
+    })?;
+    
+    computation_changed(&request_id);
+    audit_log::record(AuditEvent::Vote, caller, &request_id, message.clone());
+    let status = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(&request_id).map(|c| c.status.clone()).unwrap_or_default()
+    });
+    let rejected = status == "rejected";
+    events::record(caller, WorkflowEvent::VoteCast {
+        request_id: request_id.clone(),
+        decision: vote_decision.to_lowercase(),
+        status,
+    }, current_timestamp());
+    if rejected {
+        ic_cdk::spawn(refund_escrow(request_id.clone()));
+    }
+    activity::record(&organizations::resource_workspace(&request_id), ActivityKind::VoteCast, caller, &request_id, message.clone());
+    
+    Ok(message)
 }
 
-// Save computation results
-#[ic_cdk::update]
-fn save_computation_results(
-    request_id: String,
-    results: String,
-) -> Result<String, String> {
-    COMPUTATION_REQUESTS.with(|requests| {
+// Statuses in which a computation request still awaits approval or execution
+fn is_open_for_voting(status: &str) -> bool {
+    matches!(status, "pending_approval" | "pending_signatures" | "approved" | "ready_to_execute")
+}
+
+// Withdraw a computation request before it runs (requester only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn cancel_computation_request(request_id: String) -> Result<String, String> {
+    let caller = ic_cdk::caller();
+    
+    let message = COMPUTATION_REQUESTS.with(|requests| {
         let mut requests_map = requests.borrow_mut();
+        let computation = requests_map.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
         
-        if let Some(computation) = requests_map.get_mut(&request_id) {
-            computation.results = Some(results);
-            computation.status = "completed".to_string();
-            Ok("Results saved successfully".to_string())
-        } else {
-            Err("Computation request not found".to_string())
+        if computation.requester != caller {
+            return Err("Only the original requester can cancel this computation".to_string());
         }
+        
+        if !is_open_for_voting(&computation.status) {
+            return Err(format!("Computation request cannot be cancelled. Current status: {}", computation.status));
+        }
+        
+        // Invalidate the multi-party signature requirement backing vetKD derivation;
+        // a requirement that is already gone must not block the cancellation
+        if let Some(ref signature_id) = computation.signature_id {
+            let _ = crate::identity_manager::revoke_signature_requirement(signature_id.clone());
+        }
+        
+        computation.received_signatures.clear();
+        computation.vetkey_derivation_complete = false;
+        computation.status = "cancelled".to_string();
+        
+        Ok(format!("Computation request {} cancelled", request_id))
+    })?;
+    
+    computation_changed(&request_id);
+    activity::record(&organizations::resource_workspace(&request_id), ActivityKind::RequestCancelled, caller, &request_id, message.clone());
+    ic_cdk::spawn(refund_escrow(request_id.clone()));
+    
+    Ok(message)
+}
+
+// Fetch the caller's encrypted copy of a computation's results; decrypt it with
+// the key from `get_my_result_key`
+#[ic_cdk::query]
+fn get_my_encrypted_result(request_id: String) -> Result<EncryptedResult, String> {
+    let caller = ic_cdk::caller();
+    COMPUTATION_REQUESTS.with(|requests| {
+        let requests_map = requests.borrow();
+        let computation = requests_map.get(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        
+        computation.encrypted_results.iter()
+            .find(|copy| copy.recipient == caller)
+            .cloned()
+            .ok_or_else(|| "No result has been encrypted for the caller".to_string())
     })
 }
 
-// Get computation request by ID
+// Fetch the caller's encrypted copy of a computation's validated JSON output
 #[ic_cdk::query]
-fn get_computation_request(request_id: String) -> Result<MPCComputation, String> {
+fn get_my_structured_result(request_id: String) -> Result<EncryptedResult, String> {
+    let caller = ic_cdk::caller();
     COMPUTATION_REQUESTS.with(|requests| {
+        let requests = requests.borrow();
+        let computation = requests.get(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.output_schema.is_none() {
+            return Err("Computation does not declare an output schema".to_string());
+        }
+        computation.structured_results.iter()
+            .find(|copy| copy.recipient == caller)
+            .cloned()
+            .ok_or_else(|| "No structured result has been encrypted for the caller".to_string())
+    })
+}
+
+// The key of the caller's result copy (or structured copy), encrypted to its
+// transport public key. Copies are encrypted with the mock vetKD cipher: XOR
+// the ciphertext with the unwrapped key repeated to its length, then check the
+// plaintext against `result_digest`.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn get_my_result_key(request_id: String, structured: bool, transport_public_key: Vec<u8>) -> Result<WrappedKey, String> {
+    pause::ensure_running()?;
+    let caller = ic_cdk::caller();
+    let copy = if structured {
+        get_my_structured_result(request_id)?
+    } else {
+        get_my_encrypted_result(request_id)?
+    };
+    let mut key = derive_vetkey_for_party(caller, copy.derivation_path.clone()).await?;
+    let wrapped = wrap_for_transport(&key, &transport_public_key).await;
+    key.zeroize();
+    let key_id = key_rotation::key_id(caller, &copy.derivation_path);
+    audit_log::record(AuditEvent::KeyDerivation, caller, &key_id, "Result key issued under a transport key".to_string());
+    Ok(WrappedKey { key_id, encrypted_key: wrapped?, expires_at: None })
+}
+
+// Get computation request by ID, with a certificate clients can verify
+#[ic_cdk::query]
+fn get_computation_request(request_id: String) -> Result<CertifiedComputation, String> {
+    let caller = ic_cdk::caller();
+    organizations::ensure_visible(&request_id, caller)?;
+    let computation = COMPUTATION_REQUESTS.with(|requests| {
         requests.borrow().get(&request_id)
             .cloned()
+            .map(|c| redact_results(c, caller))
             .ok_or_else(|| "Computation request not found".to_string())
+    })?;
+    
+    Ok(CertifiedComputation {
+        computation,
+        certificate: certification::certificate(),
+        witness: certification::witness(&certification::computation_key(&request_id))?,
     })
 }
 
-// Execute approved computation request with vetKD key derivation
-#[ic_cdk::update]
-async fn execute_computation_request(
-    request_id: String,
-) -> Result<String, String> {
+// Grant an additional principal access to a computation's results (requester only)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn share_results(request_id: String, additional_principal: Principal) -> Result<String, String> {
     let caller = ic_cdk::caller();
     
-    // First check if request exists and verify signatures
-    let (requester, description, status, signature_id, vetkey_ready) = COMPUTATION_REQUESTS.with(|requests| {
-        let requests_map = requests.borrow();
-        if let Some(computation) = requests_map.get(&request_id) {
-            Ok((
-                computation.requester, 
-                computation.description.clone(), 
-                computation.status.clone(),
-                computation.signature_id.clone(),
-                computation.vetkey_derivation_complete
-            ))
-        } else {
-            Err("Computation request not found".to_string())
+    let (message, requester_copy, structured_copy) = COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests_map = requests.borrow_mut();
+        let computation = requests_map.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        
+        if computation.requester != caller {
+            return Err("Only the original requester can share results".to_string());
         }
+        
+        if can_view_results(computation, additional_principal) {
+            return Err(format!("{} can already view these results", additional_principal.to_text()));
+        }
+        
+        computation.shared_with.push(additional_principal);
+        let requester_copy = computation.encrypted_results.iter()
+            .find(|copy| copy.recipient == caller)
+            .cloned();
+        let structured_copy = computation.structured_results.iter()
+            .find(|copy| copy.recipient == caller)
+            .cloned();
+        Ok((format!("Results of {} shared with {}", request_id, additional_principal.to_text()), requester_copy, structured_copy))
     })?;
     
-    // Only the original requester can execute
-    if caller != requester {
-        return Err("Only the original requester can execute this computation".to_string());
+    // Re-encrypt already delivered results for the new recipient
+    let window = open_decryption_window(&generate_id("share"))?;
+    if let Some(copy) = requester_copy {
+        let results = decrypt_result_copy(&copy, &window).await?;
+        let mut new_copy = encrypt_result_for(&request_id, &results, &[additional_principal]).await?;
+        COMPUTATION_REQUESTS.with(|requests| {
+            if let Some(computation) = requests.borrow_mut().get_mut(&request_id) {
+                computation.encrypted_results.append(&mut new_copy);
+            }
+        });
     }
-    
-    // Check if request is ready to execute
-    if status != "ready_to_execute" {
-        return Err(format!("Request is not ready to execute. Current status: {}. All parties must vote 'yes' and signatures must be complete.", status));
+    if let Some(copy) = structured_copy {
+        let json = decrypt_result_copy(&copy, &window).await?;
+        let mut new_copy = encrypt_result_for(&structured_result_id(&request_id), &json, &[additional_principal]).await?;
+        COMPUTATION_REQUESTS.with(|requests| {
+            if let Some(computation) = requests.borrow_mut().get_mut(&request_id) {
+                computation.structured_results.append(&mut new_copy);
+            }
+        });
     }
+    close_decryption_window(window);
     
-    // Verify multi-party signatures are complete for vetKD
-    if !vetkey_ready {
-        return Err("Multi-party signatures not complete. Cannot derive vetKD keys for secure computation.".to_string());
-    }
+    computation_changed(&request_id);
+    activity::record(&organizations::resource_workspace(&request_id), ActivityKind::ResultsShared, caller, &request_id, message.clone());
     
-    // Verify signature completeness if signature_id exists
-    if let Some(sig_id) = signature_id {
-        match crate::identity_manager::verify_signature_complete(sig_id) {
-            Ok(complete) => {
-                if !complete {
+    Ok(message)
+}
+
+// Requests currently being executed
+thread_local! {
+    static EXECUTION_LOCKS: RefCell<std::collections::HashSet<String>> = RefCell::new(std::collections::HashSet::new());
+}
+
+// Lock held while an execution is in flight; released on drop, including
+// when the call traps or returns early
+struct ExecutionGuard {
+    key: String,
+}
+
+impl ExecutionGuard {
+    fn acquire(key: &str) -> Result<Self, String> {
+        pause::ensure_running()?;
+        EXECUTION_LOCKS.with(|locks| {
+            if !locks.borrow_mut().insert(key.to_string()) {
+                return Err(format!("Execution of {} is already in progress", key));
+            }
+            Ok(ExecutionGuard { key: key.to_string() })
+        })
+    }
+}
+
+impl Drop for ExecutionGuard {
+    fn drop(&mut self) {
+        EXECUTION_LOCKS.with(|locks| {
+            locks.borrow_mut().remove(&self.key);
+        });
+    }
+}
+
+enum ExecutionClaim {
+    Claimed(String),
+    AlreadyCompleted(EncryptedResult),
+}
+
+// Validate a computation and move it to "computing" without yielding in between
+fn claim_computation(request_id: &str, caller: Principal) -> Result<ExecutionClaim, String> {
+    COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests_map = requests.borrow_mut();
+        let computation = requests_map.get_mut(request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        
+        // Only the original requester can execute
+        if caller != computation.requester {
+            return Err("Only the original requester can execute this computation".to_string());
+        }
+        
+        // Repeated calls after completion return the stored results
+        if computation.status == "completed" {
+            if let Some(copy) = computation.encrypted_results.iter().find(|copy| copy.recipient == caller) {
+                return Ok(ExecutionClaim::AlreadyCompleted(copy.clone()));
+            }
+        }
+        
+        // Check if request is ready to execute
+        if computation.status != "ready_to_execute" {
+            return Err(format!("Request is not ready to execute. Current status: {}. All parties must vote 'yes' and signatures must be complete.", computation.status));
+        }
+        
+        // Verify multi-party signatures are complete for vetKD
+        if !computation.vetkey_derivation_complete {
+            return Err("Multi-party signatures not complete. Cannot derive vetKD keys for secure computation.".to_string());
+        }
+        
+        // Verify signature completeness if signature_id exists
+        if let Some(ref sig_id) = computation.signature_id {
+            match crate::identity_manager::verify_signature_complete(sig_id.clone()) {
+                Ok(true) => {},
+                Ok(false) => {
                     return Err("Multi-party signature verification failed. Cannot proceed with vetKD decryption.".to_string());
+                },
+                Err(e) => {
+                    return Err(format!("Signature verification error: {}", e));
                 }
-            },
-            Err(e) => {
-                return Err(format!("Signature verification error: {}", e));
             }
         }
-    }
+        
+        computation.status = "computing".to_string();
+        Ok(ExecutionClaim::Claimed(computation.description.clone()))
+    })
+}
+
+// Execute approved computation request with vetKD key derivation; the requester
+// gets back its encrypted copy of the results
+#[ic_cdk::update(guard = "rate_limited")]
+async fn execute_computation_request(
+    request_id: String,
+) -> Result<EncryptedResult, String> {
+    let caller = ic_cdk::caller();
+    anomaly::ensure_not_suspended(caller)?;
     
-    // Update status to computing
-    COMPUTATION_REQUESTS.with(|requests| {
-        let mut requests_map = requests.borrow_mut();
-        if let Some(computation) = requests_map.get_mut(&request_id) {
-            computation.status = "computing".to_string();
+    // Hold the execution lock for the whole (async) run
+    let _guard = ExecutionGuard::acquire(&request_id)?;
+    
+    // Check and claim the request in a single step so it runs at most once
+    let description = match claim_computation(&request_id, caller)? {
+        ExecutionClaim::Claimed(description) => description,
+        ExecutionClaim::AlreadyCompleted(copy) => return Ok(copy),
+    };
+    computation_changed(&request_id);
+    
+    // Wait for an execution slot, check the pinned agent and hold its fee before any
+    // work is done; release the claim if any of them fails
+    let prepared = match scheduler::admit(&request_id, current_timestamp()) {
+        Ok(slot) => match verify_pinned_agent(&request_id) {
+            Ok(()) => escrow_agent_fee(&request_id, caller).await.map(|_| slot),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    let _slot = match prepared {
+        Ok(slot) => slot,
+        Err(e) => {
+            COMPUTATION_REQUESTS.with(|requests| {
+                if let Some(computation) = requests.borrow_mut().get_mut(&request_id) {
+                    computation.status = "ready_to_execute".to_string();
+                }
+            });
+            computation_changed(&request_id);
+            return Err(e);
         }
+    };
+    let (output_schema, agent_id, llm_provider, prompt_template, purpose, datasets, columns) = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(&request_id)
+            .map(|c| (c.output_schema.clone(), c.agent_id.clone(), c.llm_provider.clone(), c.prompt_template.clone(), c.purpose.clone(),
+                computation_datasets(c), c.columns.clone()))
+            .unwrap_or_default()
     });
+    progress::begin(&request_id, current_timestamp());
+    if let Some(agent_id) = &agent_id {
+        report_agent_progress(&request_id, agent_id, AgentState::Running);
+    }
     
     // Execute the computation using LLM with vetKD key derivation
-    let llm_result = match create_llm_query(description, vec![]).await {
+    let llm_result = match create_llm_query(description.clone(), datasets, Some(columns), None, purpose).await {
         Ok(query_id) => {
             // Derive vetKD keys for secure computation
             let vetkd_key_result = match crate::vetkey_manager::derive_key_for_agent_real(
@@ -944,31 +3773,2149 @@ async fn execute_computation_request(
         Err(e) => Err(format!("Failed to execute computation: {}", e))
     };
     
-    // Save results and update status
+    // Schema-bound computations also need validated JSON output
+    progress::report(&request_id, ProgressEvent::Phase(ComputationPhase::Analyzing), current_timestamp());
+    let llm_calls = if output_schema.is_some() { 2 } else { 1 };
+    let mut llm_unavailable = false;
+    let llm_result = match (llm_result, output_schema) {
+        (Ok(results), Some(schema)) => {
+            let context = CallContext { computation_id: request_id.clone(), principal: caller, provider: llm_provider };
+            let structured = match prepare_prompt_inputs(&request_id, caller, &description, &results)
+                .and_then(|(description, analysis)| computation_prompt(&description, &analysis, prompt_template.as_ref()))
+            {
+                Ok(prompt) => secure_llm::structured_completion(&prompt, &schema, &context).await,
+                Err(e) => Err(e),
+            };
+            llm_unavailable = structured.is_err() && secure_llm::resilience::is_open(current_timestamp());
+            structured.map(|structured| (results, Some(structured.json)))
+        },
+        (Ok(results), None) => Ok((results, None)),
+        (Err(e), _) => Err(e),
+    };
+    
+    // Suppress small-cohort statistics, then deliver encrypted result copies;
+    // a delivery failure fails the computation
+    progress::report(&request_id, ProgressEvent::Phase(ComputationPhase::Aggregating), current_timestamp());
+    let llm_result = llm_result.and_then(|(results, structured)| {
+        let (results, structured, report) = disclosure_control::apply(&results, structured.as_deref())?;
+        record_disclosure_report(&request_id, caller, report);
+        Ok((results, structured))
+    });
+    let llm_result = match llm_result {
+        Ok((results, structured)) => store_encrypted_results(&request_id, &results, structured.as_deref()).await
+            .and_then(|_| get_my_encrypted_result(request_id.clone())),
+        Err(e) => Err(e),
+    };
+    
+    // Charge the instructions this execution consumed to the requester
+    let instructions = api::performance_counter(1);
+    let requester = COMPUTATION_REQUESTS.with(|requests| requests.borrow().get(&request_id).map(|c| c.requester))
+        .unwrap_or(caller);
+    billing::record(requester, caller, &request_id, BillableKind::Computation, instructions, llm_calls,
+        llm_result.is_ok(), current_timestamp());
+    
+    // Update status
     match llm_result {
-        Ok(results) => {
-            COMPUTATION_REQUESTS.with(|requests| {
-                let mut requests_map = requests.borrow_mut();
-                if let Some(computation) = requests_map.get_mut(&request_id) {
-                    computation.results = Some(results.clone());
-                    computation.status = "completed".to_string();
-                }
-            });
-            Ok(results)
+        Ok(copy) => {
+            progress::report(&request_id, ProgressEvent::Phase(ComputationPhase::Proving), current_timestamp());
+            cost_estimation::record_run(instructions, 0, llm_calls);
+            if payments::escrow::is_locked(&request_id) {
+                payments::escrow::release(&request_id)?;
+            }
+            audit_log::record(AuditEvent::Execution, caller, &request_id,
+                format!("Computation executed with multi-party approval{}", agent_label(&request_id)));
+            activity::record(&organizations::resource_workspace(&request_id), ActivityKind::ComputationExecuted, caller, &request_id,
+                "Computation executed with multi-party approval".to_string());
+            events::record(caller, WorkflowEvent::ComputationCompleted { request_id: request_id.clone() }, current_timestamp());
+            if let Some(agent_id) = &agent_id {
+                report_agent_progress(&request_id, agent_id, AgentState::Completed);
+            }
+            progress::report(&request_id, ProgressEvent::Phase(ComputationPhase::Completed), current_timestamp());
+            publish_event("computation.completed", serde_json::json!({
+                "request_id": request_id,
+                "executed_by": caller.to_text(),
+            })).await;
+            Ok(copy)
         },
+        Err(e) if llm_unavailable => Err(defer_computation(&request_id, caller, &e)),
         Err(e) => {
             // Update status to failed
             COMPUTATION_REQUESTS.with(|requests| {
                 let mut requests_map = requests.borrow_mut();
                 if let Some(computation) = requests_map.get_mut(&request_id) {
                     computation.status = "failed".to_string();
+                    computation.last_error = Some(e.clone());
                 }
             });
+            computation_changed(&request_id);
+            if let Some(agent_id) = &agent_id {
+                report_agent_progress(&request_id, agent_id, AgentState::Failed);
+            }
+            progress::report(&request_id, ProgressEvent::Failed(e.clone()), current_timestamp());
+            audit_log::record(AuditEvent::Execution, caller, &request_id, format!("Computation failed: {}", e));
+            activity::record(&organizations::resource_workspace(&request_id), ActivityKind::ComputationFailed, caller, &request_id, e.clone());
+            events::record(caller, WorkflowEvent::ComputationFailed { request_id: request_id.clone(), error: e.clone() }, current_timestamp());
+            refund_escrow(request_id.clone()).await;
+            publish_event("computation.failed", serde_json::json!({
+                "request_id": request_id,
+                "error": e,
+            })).await;
             Err(e)
         }
     }
 }
 
+fn report_agent_progress(request_id: &str, agent_id: &str, state: AgentState) {
+    progress::report(request_id, ProgressEvent::Agent { agent_id: agent_id.to_string(), state, detail: None }, current_timestamp());
+}
+
+// Put a computation back in the queue while the LLM backend is unavailable. Its escrow
+// stays locked, so running it again later does not charge the requester twice.
+fn defer_computation(request_id: &str, caller: Principal, error: &str) -> String {
+    let message = format!("Computation deferred until the LLM backend recovers: {}", error);
+    COMPUTATION_REQUESTS.with(|requests| {
+        if let Some(computation) = requests.borrow_mut().get_mut(request_id) {
+            computation.status = "ready_to_execute".to_string();
+            computation.last_error = Some(message.clone());
+        }
+    });
+    computation_changed(request_id);
+    progress::report(request_id, ProgressEvent::Note(message.clone()), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller, request_id, message.clone());
+    message
+}
+
+// The assigned agent must still be at the version the computation was approved against
+fn verify_pinned_agent(request_id: &str) -> Result<(), String> {
+    let pinned = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(request_id).and_then(|c| c.agent_id.clone().zip(c.agent_version))
+    });
+    let Some((agent_id, version)) = pinned else {
+        return Ok(());
+    };
+    let agent = agent_registry::get_agent_by_id(&agent_id)
+        .ok_or_else(|| format!("Agent {} not found", agent_id))?;
+    if agent.version != version {
+        return Err(format!("Agent {} changed from v{} to v{} since it was assigned; reassign it to approve the new version",
+            agent_id, version, agent.version));
+    }
+    Ok(())
+}
+
+// Agent and version that produced a computation's results, for audit records
+fn agent_label(request_id: &str) -> String {
+    COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(request_id)
+            .and_then(|c| c.agent_id.as_ref().map(|id| format!(" by agent {} v{}", id, c.agent_version.unwrap_or(0))))
+            .unwrap_or_default()
+    })
+}
+
+// Lock the agent's fee in escrow unless it is already held. Failed runs are
+// refunded, so a retry locks the fee again.
+async fn escrow_agent_fee(request_id: &str, payer: Principal) -> Result<(), String> {
+    let agent_id = COMPUTATION_REQUESTS.with(|requests| requests.borrow().get(request_id).and_then(|c| c.agent_id.clone()));
+    let Some(agent_id) = agent_id else {
+        return Ok(());
+    };
+    if payments::escrow::is_locked(request_id) {
+        return Ok(());
+    }
+    let agent = agent_registry::get_agent_by_id(&agent_id)
+        .ok_or_else(|| format!("Agent {} not found", agent_id))?;
+    // An agent that won a bidding round is paid its bid rather than its list price
+    let price = bidding::awarded_price(request_id, &agent_id).unwrap_or(agent.price_per_computation);
+    if price > 0 {
+        payments::escrow::lock(request_id, &agent_id, payer, price).await?;
+    }
+    Ok(())
+}
+
+// Refund any escrowed fee; failures stay locked for a manual retry
+async fn refund_escrow(request_id: String) {
+    if let Err(e) = payments::escrow::refund(&request_id).await {
+        ic_cdk::println!("Escrow refund for {} failed: {}", request_id, e);
+    }
+}
+
+// Maximum number of times a failed computation may be retried
+const MAX_RETRY_ATTEMPTS: u32 = 3;
+
+// Reset a failed computation so it can be executed again
+#[ic_cdk::update(guard = "rate_limited")]
+fn retry_computation(request_id: String) -> Result<String, String> {
+    let caller = ic_cdk::caller();
+    
+    let message = COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests_map = requests.borrow_mut();
+        let computation = requests_map.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        
+        if computation.requester != caller {
+            return Err("Only the original requester can retry this computation".to_string());
+        }
+        
+        if computation.status != "failed" {
+            return Err(format!("Only failed computations can be retried. Current status: {}", computation.status));
+        }
+        
+        let attempt = computation.retry_attempts.len() as u32 + 1;
+        if attempt > MAX_RETRY_ATTEMPTS {
+            return Err(format!("Retry limit of {} attempts reached", MAX_RETRY_ATTEMPTS));
+        }
+        
+        // Approvals survive a retry only if the signature requirement is still complete
+        let signatures_preserved = match computation.signature_id {
+            Some(ref signature_id) => {
+                crate::identity_manager::verify_signature_complete(signature_id.clone()).unwrap_or(false)
+            },
+            None => computation.received_signatures.len() >= computation.required_parties as usize,
+        };
+        
+        if signatures_preserved {
+            computation.vetkey_derivation_complete = true;
+            computation.status = "ready_to_execute".to_string();
+        } else {
+            // Start a fresh approval round with a new signature requirement
+            if let Some(ref signature_id) = computation.signature_id {
+                let _ = crate::identity_manager::revoke_signature_requirement(signature_id.clone());
+            }
+            let signature_data = format!("{}:{}:{}:retry{}", request_id, computation.title, computation.description, attempt);
+            computation.signature_id = crate::identity_manager::create_signature_requirement(
+                signature_data,
+                computation.required_signatures.iter().map(|p| p.to_text()).collect(),
+                computation.required_signatures.len(),
+            ).ok();
+            computation.votes.clear();
+            computation.approvals.clear();
+            computation.received_signatures.clear();
+            computation.vetkey_derivation_complete = false;
+            computation.status = "pending_approval".to_string();
+        }
+        
+        computation.retry_attempts.push(RetryAttempt {
+            attempt,
+            requested_by: caller,
+            previous_error: computation.last_error.take(),
+            signatures_preserved,
+            timestamp: current_timestamp(),
+        });
+        computation.results = None;
+        
+        Ok(format!("Retry {}/{} scheduled. Status: {}", attempt, MAX_RETRY_ATTEMPTS, computation.status))
+    })?;
+    
+    computation_changed(&request_id);
+    activity::record(&organizations::resource_workspace(&request_id), ActivityKind::RetryRequested, caller, &request_id, message.clone());
+    
+    Ok(message)
+}
+
+// Chronological activity feed for the caller's own workspace
+#[ic_cdk::query]
+fn get_activity_timeline(
+    workspace: String,
+    since: u64,
+    cursor: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ActivityPage, String> {
+    require_workspace_member(&workspace)?;
+    Ok(activity::timeline(&workspace, since, cursor, limit))
+}
+
+// ============================================================================
+// WORKSPACE KEY CEREMONY
+// ============================================================================
+
+// Open a commit-reveal key ceremony among the founding parties (workspace admins;
+// the parties must be members of the workspace)
+#[ic_cdk::update(guard = "rate_limited")]
+fn start_key_ceremony(workspace: String, founding_parties: Vec<Principal>) -> Result<KeyCeremony, String> {
+    let caller = ic_cdk::caller();
+    let organization = organizations::get(&workspace)?;
+    if !organization.admins.contains(&caller) {
+        return Err(format!("Only admins of workspace {} can start its key ceremony", workspace));
+    }
+    if let Some(outsider) = founding_parties.iter().find(|p| !organization.members.contains(p)) {
+        return Err(format!("{} is not a member of workspace {}", outsider.to_text(), workspace));
+    }
+    let ceremony = key_ceremony::start(workspace.clone(), caller, founding_parties, current_timestamp())?;
+    
+    activity::record(&workspace, ActivityKind::KeyCeremonyStep, caller, &workspace,
+        format!("Key ceremony started with {} founding parties", ceremony.contributions.len()));
+    
+    Ok(ceremony)
+}
+
+// Commit to SHA-256(entropy) for the workspace key ceremony
+#[ic_cdk::update(guard = "rate_limited")]
+fn commit_ceremony_entropy(workspace: String, commitment: Vec<u8>) -> Result<CeremonyPhase, String> {
+    let caller = ic_cdk::caller();
+    let phase = key_ceremony::commit(&workspace, caller, commitment, current_timestamp())?;
+    
+    activity::record(&workspace, ActivityKind::KeyCeremonyStep, caller, &workspace,
+        format!("Entropy commitment received. Phase: {:?}", phase));
+    
+    Ok(phase)
+}
+
+// Reveal the committed entropy; the last reveal derives the ceremony's root context
+#[ic_cdk::update(guard = "rate_limited")]
+fn reveal_ceremony_entropy(workspace: String, entropy: Vec<u8>) -> Result<CeremonyPhase, String> {
+    let caller = ic_cdk::caller();
+    let phase = key_ceremony::reveal(&workspace, caller, entropy, current_timestamp())?;
+    
+    activity::record(&workspace, ActivityKind::KeyCeremonyStep, caller, &workspace,
+        format!("Entropy revealed. Phase: {:?}", phase));
+    
+    Ok(phase)
+}
+
+// Abort an unfinished ceremony so the workspace can start a new one
+#[ic_cdk::update(guard = "rate_limited")]
+fn abort_key_ceremony(workspace: String) -> Result<KeyCeremony, String> {
+    let caller = ic_cdk::caller();
+    let is_workspace_admin = organizations::get(&workspace).is_ok_and(|o| o.admins.contains(&caller));
+    let ceremony = key_ceremony::abort(&workspace, caller, is_workspace_admin, current_timestamp())?;
+    
+    activity::record(&workspace, ActivityKind::KeyCeremonyStep, caller, &workspace, "Key ceremony aborted".to_string());
+    
+    Ok(ceremony)
+}
+
+#[ic_cdk::query]
+fn get_key_ceremony(workspace: String) -> Option<KeyCeremony> {
+    key_ceremony::get(&workspace)
+}
+
+// ============================================================================
+// IDENTITY RECOVERY
+// ============================================================================
+
+// Designate guardians who can approve re-binding the caller's identity
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_recovery_guardians(guardians: Vec<Principal>, threshold: u32) -> Result<GuardianConfig, String> {
+    let caller = ic_cdk::caller();
+    if caller == Principal::anonymous() {
+        return Err("Anonymous caller not allowed".to_string());
+    }
+    recovery::set_guardians(caller, guardians, threshold, current_timestamp())
+}
+
+#[ic_cdk::query]
+fn get_recovery_guardians(principal: Principal) -> Option<GuardianConfig> {
+    recovery::get_guardians(principal)
+}
+
+// Called from the new principal to start recovering a lost one
+#[ic_cdk::update(guard = "rate_limited")]
+fn request_identity_recovery(lost_principal: Principal) -> Result<RecoveryRequest, String> {
+    let caller = ic_cdk::caller();
+    if caller == Principal::anonymous() {
+        return Err("Anonymous caller not allowed".to_string());
+    }
+    
+    let request = recovery::request(lost_principal, caller, current_timestamp())?;
+    activity::record(&organizations::workspace_of(caller), ActivityKind::IdentityRecovery, caller, &request.id,
+        format!("Recovery of {} requested", lost_principal.to_text()));
+    Ok(request)
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn approve_identity_recovery(request_id: String) -> Result<String, String> {
+    let caller = ic_cdk::caller();
+    let approvals = recovery::approve(&request_id, caller, current_timestamp())?;
+    
+    let message = format!("Recovery approval recorded ({} approvals)", approvals);
+    activity::record(&organizations::workspace_of(caller), ActivityKind::IdentityRecovery, caller, &request_id, message.clone());
+    Ok(message)
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn cancel_identity_recovery(request_id: String) -> Result<String, String> {
+    let caller = ic_cdk::caller();
+    recovery::cancel(&request_id, caller, current_timestamp())?;
+    
+    activity::record(&organizations::workspace_of(caller), ActivityKind::IdentityRecovery, caller, &request_id,
+        "Recovery cancelled".to_string());
+    Ok(format!("Recovery request {} cancelled", request_id))
+}
+
+// Re-bind datasets, votes and keys once guardians approved and the delay elapsed.
+// Every step skips what an earlier, interrupted run already moved, so a failed
+// execution is completed by calling this again.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn execute_identity_recovery(request_id: String) -> Result<String, String> {
+    let caller = ic_cdk::caller();
+    let _guard = ExecutionGuard::acquire(&request_id)?;
+    let request = recovery::begin_execution(&request_id, caller, current_timestamp())?;
+    
+    let summary = rebind_principal(request.lost_principal, request.new_principal).await?;
+    recovery::mark_executed(&request_id, current_timestamp())?;
+    
+    activity::record(&organizations::workspace_of(caller), ActivityKind::IdentityRecovery, caller, &request_id, summary.clone());
+    Ok(summary)
+}
+
+#[ic_cdk::query]
+fn get_recovery_request(request_id: String) -> Result<RecoveryRequest, String> {
+    recovery::get_request(&request_id)
+}
+
+#[ic_cdk::query]
+fn get_my_recovery_notifications() -> Vec<RecoveryNotification> {
+    recovery::notifications_for(ic_cdk::caller())
+}
+
+fn replace_principal(principals: &mut [Principal], old: Principal, new: Principal) {
+    for principal in principals.iter_mut() {
+        if *principal == old {
+            *principal = new;
+        }
+    }
+}
+
+// Transfer everything bound to `old` to `new`, re-encrypting data whose keys
+// are derived from the owning principal. Each dataset and computation is moved
+// in one step, so a run that fails part-way can be resumed.
+async fn rebind_principal(old: Principal, new: Principal) -> Result<String, String> {
+    PARTIES.with(|parties| {
+        let mut parties_map = parties.borrow_mut();
+        if let Some(mut party) = parties_map.remove(&old) {
+            party.principal = new;
+            party.last_seen = current_timestamp();
+            parties_map.insert(new, party);
+        }
+    });
+    
+    // Datasets and their column-level copies
+    let owned: Vec<PrivateDataSource> = DATA_SOURCES.with(|sources| {
+        sources.borrow().values().filter(|ds| ds.owner == old).cloned().collect()
+    });
+    let window = open_decryption_window(&generate_id("recovery"))?;
+    for dataset in &owned {
+        let path = dataset_key_path(dataset);
+        let old_key = Zeroizing::new(derive_vetkey_for_party(old, path.clone()).await?);
+        let new_key = Zeroizing::new(derive_vetkey_for_party(new, path).await?);
+        window.ensure_open(current_timestamp())?;
+        let plaintext = Zeroizing::new(decrypt_with_vetkey(&dataset.encrypted_data, &old_key));
+        window.count(plaintext.len());
+        let encrypted_data = encrypt_with_vetkey(&plaintext, &new_key);
+        
+        let mut columns = Vec::new();
+        for (column, mut copy) in column_encryption::split_columns(&plaintext) {
+            copy.zeroize();
+            if let Some(mut encrypted) = column_encryption::get_column(&dataset.id, &column) {
+                let path = column_key_path(dataset, &column);
+                let old_key = Zeroizing::new(derive_vetkey_for_party(old, path.clone()).await?);
+                let new_key = Zeroizing::new(derive_vetkey_for_party(new, path).await?);
+                window.ensure_open(current_timestamp())?;
+                let values = Zeroizing::new(decrypt_with_vetkey(&encrypted.ciphertext, &old_key));
+                window.count(values.len());
+                encrypted.ciphertext = encrypt_with_vetkey(&values, &new_key);
+                columns.push(encrypted);
+            }
+        }
+        
+        // Store the dataset and its columns together, unless it changed while
+        // the keys were derived
+        let rebound = DATA_SOURCES.with(|sources| {
+            let mut sources = sources.borrow_mut();
+            match sources.get_mut(&dataset.id) {
+                Some(stored) if stored.owner == old && stored.encrypted_data == dataset.encrypted_data => {
+                    stored.encrypted_data = encrypted_data;
+                    stored.owner = new;
+                    replace_principal(&mut stored.access_permissions, old, new);
+                    Ok(stored.clone())
+                },
+                _ => Err(format!("Dataset {} changed during recovery; execute the recovery again", dataset.id)),
+            }
+        })?;
+        if !columns.is_empty() {
+            column_encryption::store_columns(&dataset.id, columns);
+        }
+        history::snapshot_permissions(&rebound);
+        audit_log::record(AuditEvent::Decryption, caller(), &dataset.id,
+            format!("Dataset re-encrypted for recovered identity {}", new.to_text()));
+        dataset_access::record(&dataset.id, caller(), AccessType::ReEncryption, None, vec![]);
+    }
+    
+    // Votes, signatures and result copies on computation requests
+    let affected: Vec<MPCComputation> = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().values().filter(|c| {
+            c.requester == old
+                || c.required_signatures.contains(&old)
+                || c.votes.iter().any(|v| v.voter == old)
+                || c.shared_with.contains(&old)
+        }).cloned().collect()
+    });
+    for computation in &affected {
+        let encrypted_results =
+            rewrap_result_copies(&computation.id, computation.encrypted_results.clone(), old, new, &window).await?;
+        let structured_results = rewrap_result_copies(
+            &structured_result_id(&computation.id), computation.structured_results.clone(), old, new, &window,
+        ).await?;
+        
+        // Apply to the stored request so changes made while re-encrypting are kept
+        COMPUTATION_REQUESTS.with(|requests| {
+            if let Some(stored) = requests.borrow_mut().get_mut(&computation.id) {
+                if stored.requester == old {
+                    stored.requester = new;
+                }
+                for vote in stored.votes.iter_mut() {
+                    if vote.voter == old {
+                        vote.voter = new;
+                    }
+                }
+                replace_principal(&mut stored.approvals, old, new);
+                replace_principal(&mut stored.required_signatures, old, new);
+                replace_principal(&mut stored.received_signatures, old, new);
+                replace_principal(&mut stored.shared_with, old, new);
+                stored.encrypted_results = encrypted_results;
+                stored.structured_results = structured_results;
+            }
+        });
+        computation_changed(&computation.id);
+    }
+    close_decryption_window(window);
+    
+    LLM_QUERIES.with(|queries| {
+        for query in queries.borrow_mut().values_mut() {
+            if query.requester == old {
+                query.requester = new;
+            }
+            replace_principal(&mut query.required_signatures, old, new);
+            replace_principal(&mut query.received_signatures, old, new);
+        }
+    });
+    
+    identity_manager::rebind_principal(old, new);
+    
+    Ok(format!(
+        "Identity {} re-bound to {}: {} datasets, {} computation requests",
+        old.to_text(), new.to_text(), owned.len(), affected.len()
+    ))
+}
+
+// ============================================================================
+// LOAD TESTING (ADMIN)
+// ============================================================================
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_load_test_enabled(enabled: bool) -> Result<String, String> {
+    require_admin()?;
+    load_test::set_enabled(enabled);
+    Ok(format!("Load-test generation {}", if enabled { "enabled" } else { "disabled" }))
+}
+
+// Generate synthetic datasets and pending computations for staging measurements
+#[ic_cdk::update(guard = "rate_limited")]
+async fn generate_load_test_data(
+    datasets: u32,
+    computations: u32,
+    dataset_bytes: u64,
+) -> Result<LoadTestReport, String> {
+    let caller = require_admin()?;
+    load_test::validate(datasets, computations, dataset_bytes)?;
+    
+    let run_id = current_timestamp();
+    let party_name = PARTIES.with(|parties| parties.borrow().get(&caller).map(|p| p.name.clone()))
+        .unwrap_or_else(|| "LoadTest".to_string());
+    let mut bytes_generated = 0u64;
+    
+    for i in 0..datasets {
+        let id = format!("{}dataset_{}_{}", load_test::LOAD_TEST_PREFIX, run_id, i);
+        let name = format!("{}{}_{}", load_test::LOAD_TEST_PREFIX, run_id, i);
+        let data = load_test::synthetic_csv(&id, dataset_bytes);
+        bytes_generated += data.len() as u64;
+        
+        let key = derive_vetkey_for_party(caller, format!("data_{}_{}", party_name, name).into_bytes()).await?;
+        let mut encrypted_columns = Vec::new();
+        for (column, values) in column_encryption::split_columns(&data) {
+            let path = column_encryption::column_derivation_path(&party_name, &name, &column);
+            let column_key = derive_vetkey_for_party(caller, path).await?;
+            encrypted_columns.push(column_encryption::EncryptedColumn {
+                name: column,
+                ciphertext: encrypt_with_vetkey(&values, &column_key),
+                plaintext_digest: column_encryption::digest(&values),
+            });
+        }
+        column_encryption::store_columns(&id, encrypted_columns);
+        
+        let data_source = PrivateDataSource {
+            id: id.clone(),
+            owner: caller,
+            party_name: party_name.clone(),
+            name,
+            encrypted_data: encrypt_with_vetkey(&data, &key),
+            vetkey_id: format!("vetkey_{}", caller.to_text()),
+            schema: "patient_id:id,age:number[0..120],sex:category,diagnosis:category,hba1c:number[3..20],systolic_bp:number[60..250]".to_string(),
+            record_count: load_test::row_count(&data),
+            created_at: current_timestamp(),
+            access_permissions: vec![caller],
+            metadata: DatasetMetadata::default(),
+            tags: vec![],
+            allowed_purposes: None,
+        };
+        history::snapshot_permissions(&data_source);
+        DATA_SOURCES.with(|sources| sources.borrow_mut().insert(id, data_source));
+    }
+    
+    let all_parties = PARTIES.with(|parties| parties.borrow().keys().cloned().collect::<Vec<_>>());
+    for i in 0..computations {
+        let id = format!("{}mpc_{}_{}", load_test::LOAD_TEST_PREFIX, run_id, i);
+        let title = format!("Load test computation {}", i);
+        let description = "Cohort statistics over synthetic patient records".to_string();
+        let signature_id = crate::identity_manager::create_signature_requirement(
+            format!("{}:{}:{}", id, title, description),
+            all_parties.iter().map(|p| p.to_text()).collect(),
+            all_parties.len(),
+        ).ok();
+        
+        let computation = MPCComputation {
+            id: id.clone(),
+            title,
+            description,
+            requester: caller,
+            required_parties: all_parties.len() as u32,
+            approvals: vec![],
+            votes: vec![],
+            status: "pending_approval".to_string(),
+            created_at: current_timestamp(),
+            results: None,
+            signature_id,
+            required_signatures: all_parties.clone(),
+            received_signatures: vec![],
+            vetkey_derivation_complete: false,
+            last_error: None,
+            retry_attempts: vec![],
+            shared_with: vec![],
+            encrypted_results: vec![],
+            output_schema: None,
+            structured_results: vec![],
+            agent_id: None,
+            agent_version: None,
+            priority: Priority::Normal,
+            llm_provider: None,
+            prompt_template: None,
+            purpose: "load testing".to_string(),
+            columns: vec![],
+        };
+        COMPUTATION_REQUESTS.with(|requests| requests.borrow_mut().insert(id.clone(), computation));
+        computation_changed(&id);
+    }
+    
+    let report = LoadTestReport {
+        datasets_created: datasets,
+        computations_created: computations,
+        bytes_generated,
+        instructions_used: api::instruction_counter(),
+        total_datasets: DATA_SOURCES.with(|sources| sources.borrow().len() as u64),
+        total_computations: COMPUTATION_REQUESTS.with(|requests| requests.borrow().len() as u64),
+    };
+    
+    activity::record(&organizations::workspace_of(caller), ActivityKind::LoadTestGenerated, caller, &run_id.to_string(),
+        format!("Generated {} synthetic datasets ({} bytes) and {} computations", datasets, bytes_generated, computations));
+    
+    Ok(report)
+}
+
+// Remove everything created by the load-test generator
+#[ic_cdk::update(guard = "rate_limited")]
+fn clear_load_test_data() -> Result<String, String> {
+    let caller = require_admin()?;
+    
+    let dataset_ids: Vec<String> = DATA_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let ids: Vec<String> = sources.keys().filter(|id| id.starts_with(load_test::LOAD_TEST_PREFIX)).cloned().collect();
+        for id in &ids {
+            sources.remove(id);
+        }
+        ids
+    });
+    for id in &dataset_ids {
+        column_encryption::remove_columns(id);
+    }
+    
+    let computations: Vec<MPCComputation> = COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests = requests.borrow_mut();
+        let ids: Vec<String> = requests.keys().filter(|id| id.starts_with(load_test::LOAD_TEST_PREFIX)).cloned().collect();
+        ids.iter().filter_map(|id| requests.remove(id)).collect()
+    });
+    for computation in &computations {
+        if let Some(ref signature_id) = computation.signature_id {
+            let _ = crate::identity_manager::revoke_signature_requirement(signature_id.clone());
+        }
+        certification::remove(&certification::computation_key(&computation.id));
+    }
+    
+    let message = format!("Removed {} load-test datasets and {} computations", dataset_ids.len(), computations.len());
+    activity::record(&organizations::workspace_of(caller), ActivityKind::LoadTestGenerated, caller, "cleanup", message.clone());
+    Ok(message)
+}
+
+// ============================================================================
+// MAINTENANCE
+// ============================================================================
+
+fn encoded_size<T: CandidType>(value: &T) -> u64 {
+    certification::candid_bytes(value).map_or(0, |bytes| bytes.len() as u64)
+}
+
+// Key ids of the derivations stored data still uses
+fn live_key_ids() -> std::collections::HashSet<String> {
+    let mut live = std::collections::HashSet::new();
+    DATA_SOURCES.with(|sources| {
+        for dataset in sources.borrow().values() {
+            live.insert(key_rotation::key_id(dataset.owner, &dataset_key_path(dataset)));
+            for column in column_encryption::column_names(&dataset.id) {
+                live.insert(key_rotation::key_id(dataset.owner, &column_key_path(dataset, &column)));
+            }
+        }
+    });
+    COMPUTATION_REQUESTS.with(|requests| {
+        for computation in requests.borrow().values() {
+            for copy in computation.encrypted_results.iter().chain(&computation.structured_results) {
+                live.insert(key_rotation::key_id(copy.recipient, &copy.derivation_path));
+            }
+        }
+    });
+    PARTIES.with(|parties| {
+        for party in parties.borrow().values() {
+            for path in [format!("party_{}", party.name), format!("user_{}", party.name)] {
+                live.insert(key_rotation::key_id(party.principal, path.as_bytes()));
+            }
+        }
+    });
+    live
+}
+
+// Prune expired queries, finished computations past the retention window and
+// derivations nothing uses
+fn run_maintenance(now: u64) -> MaintenanceReport {
+    let mut report = MaintenanceReport { ran_at: now, ..Default::default() };
+    
+    let queries: Vec<LLMQueryRequest> = LLM_QUERIES.with(|queries| {
+        let mut queries = queries.borrow_mut();
+        let due: Vec<String> = queries.values()
+            .filter(|q| maintenance::query_due(&q.status, q.expires_at, now))
+            .map(|q| q.id.clone())
+            .collect();
+        due.iter().filter_map(|id| queries.remove(id)).collect()
+    });
+    report.queries_pruned = queries.len() as u64;
+    report.bytes_reclaimed += queries.iter().map(encoded_size).sum::<u64>();
+    
+    let computations: Vec<MPCComputation> = COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests = requests.borrow_mut();
+        let due: Vec<String> = requests.keys().filter(|id| maintenance::computation_due(id, now)).cloned().collect();
+        due.iter().filter_map(|id| requests.remove(id)).collect()
+    });
+    for computation in &computations {
+        maintenance::forget_computation(&computation.id);
+        certification::remove(&certification::computation_key(&computation.id));
+        report.bytes_reclaimed += encoded_size(computation);
+    }
+    report.computations_pruned = computations.len() as u64;
+    
+    let live = live_key_ids();
+    VETKEY_DERIVATIONS.with(|keys| {
+        keys.borrow_mut().retain(|key_id, key| {
+            if live.contains(key_id) {
+                return true;
+            }
+            report.derivations_pruned += 1;
+            report.bytes_reclaimed += (key_id.len() + key.len()) as u64;
+            false
+        });
+    });
+    
+    maintenance::record(report.clone());
+    report
+}
+
+// Run maintenance now rather than waiting for the timer (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn run_maintenance_now() -> Result<MaintenanceReport, String> {
+    require_admin()?;
+    Ok(run_maintenance(current_timestamp()))
+}
+
+// How long finished queries and computations are kept (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_retention_window(days: u64) -> Result<(), String> {
+    let admin = require_admin()?;
+    maintenance::set_retention(days.saturating_mul(24 * 60 * 60 * 1_000_000_000))?;
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", format!("Retention window set to {} days", days));
+    Ok(())
+}
+
+// Runs, pruned entries and bytes reclaimed by maintenance (admin only)
+#[ic_cdk::query]
+fn get_maintenance_stats() -> Result<MaintenanceStats, String> {
+    require_admin()?;
+    Ok(maintenance::stats())
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+#[ic_cdk::query]
+fn get_config() -> Config {
+    config::current()
+}
+
+// Change any of the settings at once; a rejected update changes nothing (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn update_config(update: ConfigUpdate) -> Result<Config, String> {
+    let admin = require_admin()?;
+    let config = config::update(update)?;
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", "Configuration updated".to_string());
+    Ok(config)
+}
+
+// ============================================================================
+// STATE BACKUP
+// ============================================================================
+
+// The stores kept in this file, as the "core" section of a backup
+fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&DATA_SOURCES),
+        backup::cloned(&LLM_QUERIES),
+        backup::cloned(&PARTIES),
+        backup::cloned(&VETKEY_DERIVATIONS),
+        backup::cloned(&COMPUTATION_REQUESTS),
+    ))
+}
+
+fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (data_sources, llm_queries, parties, vetkey_derivations, computation_requests) = backup::decode(bytes)?;
+    backup::replace(&DATA_SOURCES, data_sources);
+    backup::replace(&LLM_QUERIES, llm_queries);
+    backup::replace(&PARTIES, parties);
+    backup::replace(&VETKEY_DERIVATIONS, vetkey_derivations);
+    backup::replace(&COMPUTATION_REQUESTS, computation_requests);
+    Ok(())
+}
+
+// Certify the restored computations and privacy audits again
+fn recertify_restored_state() {
+    certification::clear();
+    COMPUTATION_REQUESTS.with(|requests| requests.borrow().values().cloned().for_each(certify_computation));
+    for (computation_id, report) in privacy_proofs::audits() {
+        certification::insert_leaf(&certification::audit_key(&computation_id), report.as_bytes());
+    }
+    certification::certify(certification::AUDIT_ROOT_KEY, audit_log::current_root().as_bytes());
+}
+
+// Read a snapshot of the whole state chunk by chunk; cursor 0 takes a new
+// snapshot, later cursors continue it (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn export_state_chunk(cursor: u64) -> Result<StateChunk, String> {
+    let admin = require_admin()?;
+    let chunk = backup::export_chunk(cursor, current_timestamp())?;
+    if cursor == 0 {
+        audit_log::record(AuditEvent::Backup, admin, "canister", format!("State exported ({} bytes, sha256 {})", chunk.total_size, chunk.snapshot_hash));
+    }
+    Ok(chunk)
+}
+
+// Send a snapshot chunk by chunk, in order; the last chunk replaces the
+// whole state, or nothing if the snapshot is rejected (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn import_state_chunk(chunk: ImportChunk) -> Result<ImportProgress, String> {
+    let admin = require_admin()?;
+    let progress = backup::import_chunk(chunk.clone())?;
+    if progress.restored {
+        recertify_restored_state();
+        audit_log::record(AuditEvent::Backup, admin, "canister", format!("State restored ({} bytes, sha256 {})", progress.total_size, chunk.snapshot_hash));
+    }
+    Ok(progress)
+}
+
+// ============================================================================
+// EMERGENCY PAUSE
+// ============================================================================
+
+// Stop every decryption and execution endpoint at once (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn pause_canister(reason: String) -> Result<PauseState, String> {
+    let admin = require_admin()?;
+    let state = pause::pause(admin, reason.clone(), current_timestamp())?;
+    audit_log::record(AuditEvent::Pause, admin, "canister", format!("Paused: {}", reason.trim()));
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", format!("Paused: {}", reason.trim()));
+    Ok(state)
+}
+
+// Approve resuming; the canister resumes once enough distinct controllers
+// have approved (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn approve_unpause() -> Result<PauseState, String> {
+    let admin = require_admin()?;
+    let state = pause::approve_unpause(admin)?;
+    let details = if state.paused {
+        format!("Unpause approved ({} of {})", state.unpause_approvals.len(), state.required_approvals)
+    } else {
+        "Unpaused".to_string()
+    };
+    audit_log::record(AuditEvent::Pause, admin, "canister", details.clone());
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", details);
+    Ok(state)
+}
+
+// How many controllers must approve unpausing; only while running (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_unpause_approvals(approvals: u32) -> Result<PauseState, String> {
+    let admin = require_admin()?;
+    let state = pause::set_required_approvals(approvals)?;
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", format!("Unpausing now needs {} approvals", approvals));
+    Ok(state)
+}
+
+#[ic_cdk::query]
+fn get_pause_state() -> PauseState {
+    pause::state()
+}
+
+// ============================================================================
+// AUDIT LOG
+// ============================================================================
+
+#[ic_cdk::query]
+fn get_audit_log(cursor: Option<u64>, limit: Option<u32>) -> Result<AuditLogPage, String> {
+    require_permission(caller(), Permission::Audit)?;
+    Ok(audit_log::page(cursor, limit))
+}
+
+#[ic_cdk::query]
+fn verify_audit_log() -> Result<ChainVerification, String> {
+    require_permission(caller(), Permission::Audit)?;
+    Ok(audit_log::verify_chain())
+}
+
+// Structured JSON/CSV records for compliance systems, paged by cursor
+#[ic_cdk::query]
+fn export_audit_log(
+    from_ts: u64,
+    to_ts: u64,
+    format: AuditExportFormat,
+    cursor: Option<u64>,
+    limit: Option<u32>,
+) -> Result<AuditExport, String> {
+    require_permission(caller(), Permission::Audit)?;
+    if from_ts > to_ts {
+        return Err("from_ts must not be after to_ts".to_string());
+    }
+    Ok(audit_log::export(from_ts, to_ts, format, cursor, limit))
+}
+
+// Prove a single audit event is in the log without downloading the log
+#[ic_cdk::query]
+fn get_audit_inclusion_proof(event_id: u64) -> Result<CertifiedInclusionProof, String> {
+    require_permission(caller(), Permission::Audit)?;
+    Ok(CertifiedInclusionProof {
+        proof: audit_log::inclusion_proof(event_id)?,
+        certificate: certification::certificate(),
+        witness: certification::witness(certification::AUDIT_ROOT_KEY)?,
+    })
+}
+
+// Recompute a proof's root; auditors can also do this offline
+#[ic_cdk::query]
+fn verify_audit_inclusion_proof(proof: AuditInclusionProof) -> bool {
+    audit_log::verify_inclusion(&proof)
+}
+
+// ============================================================================
+// CONSENT GRAPH EXPORT
+// ============================================================================
+
+// Signed, self-contained consent history of a workspace for offline verification
+#[ic_cdk::update(guard = "rate_limited")]
+async fn export_consent_graph(workspace: String) -> Result<SignedConsentGraph, String> {
+    require_workspace_member(&workspace)?;
+    require_permission(caller(), Permission::Audit)?;
+    
+    let nodes: Vec<ConsentNode> = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().values().filter(|c| organizations::resource_workspace(&c.id) == workspace).map(|computation| {
+            let signatures = computation.signature_id.clone()
+                .and_then(|id| crate::identity_manager::get_signatures(id).ok());
+            consent_export::node(computation, signatures)
+        }).collect()
+    });
+    let graph = consent_export::build(&workspace, api::id(), current_timestamp(), nodes);
+    
+    let public_key = event_signing::threshold_public_key(consent_export::SIGNING_DERIVATION_PATH).await?;
+    let signature = event_signing::threshold_sign(consent_export::SIGNING_DERIVATION_PATH, consent_export::digest(&graph)).await?;
+    
+    Ok(SignedConsentGraph {
+        graph,
+        algorithm: event_signing::SIGNATURE_ALGORITHM.to_string(),
+        key_name: config::settings().threshold_key_name,
+        public_key,
+        signature,
+    })
+}
+
+// ============================================================================
+// SIGNED EVENTS
+// ============================================================================
+
+// Publish a signed outbound event; signing failures never fail the caller's operation
+async fn publish_event(event_type: &str, payload: serde_json::Value) {
+    if let Err(e) = event_signing::publish(event_type, payload.to_string()).await {
+        ic_cdk::println!("Failed to publish {} event: {}", event_type, e);
+    }
+}
+
+// Public key consumers use to authenticate SecureCollab events
+#[ic_cdk::update(guard = "rate_limited")]
+async fn get_event_verification_key() -> Result<EventVerificationKey, String> {
+    event_signing::verification_key().await
+}
+
+#[ic_cdk::query]
+fn verify_event(event: SignedEvent) -> Result<bool, String> {
+    event_signing::verify(&event)
+}
+
+// Published events about resources the caller's workspace can see
+#[ic_cdk::query]
+fn get_signed_events(cursor: Option<u64>, limit: Option<u32>) -> Vec<SignedEvent> {
+    let caller = caller();
+    event_signing::events(cursor, limit, |event| {
+        event_signing::resource_id(event).is_none_or(|id| organizations::visible_or_unassigned(&id, caller))
+    })
+}
+
+// ============================================================================
+// DATASET ACCESS LOG
+// ============================================================================
+
+// Every recorded decryption of a dataset (owner only)
+#[ic_cdk::query]
+fn get_dataset_access_history(dataset_id: String) -> Result<Vec<DatasetAccess>, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|ds| ds.owner))
+        .ok_or_else(|| "Dataset not found".to_string())?;
+    if owner != caller() {
+        return Err("Only the dataset owner can view its access history".to_string());
+    }
+    Ok(dataset_access::history(&dataset_id))
+}
+
+// ============================================================================
+// ACCESS ANOMALIES
+// ============================================================================
+
+// Anomaly alerts, optionally filtered by status (registered parties only)
+#[ic_cdk::query]
+fn get_anomaly_alerts(status: Option<AlertStatus>) -> Result<Vec<AnomalyAlert>, String> {
+    if !PARTIES.with(|parties| parties.borrow().contains_key(&caller())) && require_admin().is_err() {
+        return Err("Only registered parties can view anomaly alerts".to_string());
+    }
+    Ok(anomaly::alerts(status))
+}
+
+// Confirm an alert, or dismiss it and lift the suspension it caused (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn review_anomaly_alert(alert_id: u64, confirm: bool) -> Result<AnomalyAlert, String> {
+    require_admin()?;
+    anomaly::review(alert_id, confirm)
+}
+
+// Automatically suspend principals that trigger an alert (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_anomaly_auto_suspend(enabled: bool) -> Result<(), String> {
+    require_admin()?;
+    anomaly::set_auto_suspend(enabled);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_suspended_principals() -> Vec<Principal> {
+    anomaly::suspended_principals()
+}
+
+// ============================================================================
+// RATE LIMITS
+// ============================================================================
+
+// Guard on every update endpoint: each call spends one of the caller's
+// tokens. Controllers are not limited, so they can always raise limits.
+fn rate_limited() -> Result<(), String> {
+    let caller = caller();
+    if api::is_controller(&caller) {
+        return Ok(());
+    }
+    rate_limit::consume(caller, api::time())
+}
+
+#[ic_cdk::query]
+fn get_rate_limits() -> RateLimits {
+    rate_limit::limits()
+}
+
+// Change the limit of callers without their own (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_default_rate_limit(limit: RateLimit) -> Result<RateLimits, String> {
+    let admin = require_admin()?;
+    let limits = rate_limit::set_default(limit)?;
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister",
+        format!("Default rate limit set to {} calls, {} per minute", limit.capacity, limit.refill_per_minute));
+    Ok(limits)
+}
+
+// Give a principal its own limit, or go back to the default with `None` (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_principal_rate_limit(principal: Principal, limit: Option<RateLimit>) -> Result<RateLimits, String> {
+    let admin = require_admin()?;
+    let limits = rate_limit::set_override(principal, limit)?;
+    let summary = match limit {
+        Some(limit) => format!("Rate limit of {} set to {} calls, {} per minute", principal, limit.capacity, limit.refill_per_minute),
+        None => format!("Rate limit of {} reset to the default", principal),
+    };
+    record_admin_activity(ActivityKind::AdminAction, admin, &principal.to_text(), summary);
+    Ok(limits)
+}
+
+// ============================================================================
+// STORAGE QUOTAS
+// ============================================================================
+
+// Caller's dataset storage usage and quota
+#[ic_cdk::query]
+fn get_storage_usage() -> StorageUsage {
+    storage_usage_of(caller())
+}
+
+// Quota for principals without an override (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_default_storage_quota(bytes: u64) -> Result<(), String> {
+    let admin = require_admin()?;
+    storage_quota::set_default_quota(bytes);
+    record_admin_activity(ActivityKind::AdminAction, admin, "canister", format!("Default storage quota set to {} bytes", bytes));
+    Ok(())
+}
+
+// Override a principal's quota, or clear the override with `null` (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_storage_quota(principal: Principal, bytes: Option<u64>) -> Result<StorageUsage, String> {
+    let admin = require_admin()?;
+    storage_quota::set_quota(principal, bytes);
+    let summary = match bytes {
+        Some(bytes) => format!("Storage quota of {} set to {} bytes", principal, bytes),
+        None => format!("Storage quota of {} reset to the default", principal),
+    };
+    record_admin_activity(ActivityKind::AdminAction, admin, &principal.to_text(), summary);
+    Ok(storage_usage_of(principal))
+}
+
+// ============================================================================
+// COST ESTIMATION
+// ============================================================================
+
+// Quote the cycle cost of a computation before committing to it
+#[ic_cdk::query]
+fn estimate_computation_cost(request: CostEstimateRequest) -> Result<CostEstimate, String> {
+    let agent_fee = match request.agent_id {
+        Some(ref agent_id) => agent_registry::get_agent_by_id(agent_id)
+            .ok_or_else(|| format!("Agent {} not found", agent_id))?
+            .price_per_computation,
+        None => 0,
+    };
+    
+    let mut dataset_bytes = 0u64;
+    for dataset_id in &request.dataset_ids {
+        dataset_bytes += DATA_SOURCES.with(|sources| sources.borrow().get(dataset_id).map(|ds| ds.encrypted_data.len() as u64))
+            .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    }
+    
+    Ok(cost_estimation::estimate(agent_fee, dataset_bytes, request.expected_llm_calls, &cost_estimation::totals()))
+}
+
+// ============================================================================
+// BILLING
+// ============================================================================
+
+// Compute usage billed to the caller
+#[ic_cdk::query]
+fn get_billing_history() -> BillingHistory {
+    billing::history(caller())
+}
+
+// ============================================================================
+// AGENT PAYMENTS
+// ============================================================================
+
+// Assign (or clear) the agent paid for running a computation and lock its
+// quoted fee in escrow; any fee held for a previous agent is refunded (requester only)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn assign_computation_agent(request_id: String, agent_id: Option<String>) -> Result<Option<Escrow>, String> {
+    let caller = caller();
+    let agent = match agent_id {
+        Some(ref agent_id) => Some(agent_registry::get_agent_by_id(agent_id)
+            .ok_or_else(|| format!("Agent {} not found", agent_id))?),
+        None => None,
+    };
+    COMPUTATION_REQUESTS.with(|requests| {
+        let requests_map = requests.borrow();
+        let computation = requests_map.get(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.requester != caller {
+            return Err("Only the requester can assign an agent".to_string());
+        }
+        if !is_open_for_voting(&computation.status) {
+            return Err(format!("Cannot change the agent of a computation that is {}", computation.status));
+        }
+        Ok(())
+    })?;
+    
+    payments::escrow::refund(&request_id).await?;
+    let escrow = match agent {
+        Some(ref agent) if agent.price_per_computation > 0 => {
+            Some(payments::escrow::lock(&request_id, &agent.id, caller, agent.price_per_computation).await?)
+        },
+        _ => None,
+    };
+    
+    // The computation may have been cancelled or claimed while the ledger calls ran
+    let still_open = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(&request_id).is_some_and(|c| is_open_for_voting(&c.status))
+    });
+    if !still_open {
+        if escrow.is_some() {
+            payments::escrow::refund(&request_id).await?;
+        }
+        return Err("Computation stopped accepting changes while the agent was being assigned".to_string());
+    }
+    
+    COMPUTATION_REQUESTS.with(|requests| {
+        if let Some(computation) = requests.borrow_mut().get_mut(&request_id) {
+            computation.agent_version = agent.as_ref().map(|a| a.version);
+            computation.agent_id = agent_id;
+        }
+    });
+    computation_changed(&request_id);
+    Ok(escrow)
+}
+
+#[ic_cdk::query]
+fn get_computation_escrow(request_id: String) -> Option<Escrow> {
+    payments::escrow::get(&request_id)
+}
+
+// Retry a refund that failed after rejection, cancellation or failure (requester or admin)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn refund_computation_escrow(request_id: String) -> Result<Option<Escrow>, String> {
+    let (requester, status) = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().get(&request_id).map(|c| (c.requester, c.status.clone()))
+    }).ok_or_else(|| "Computation request not found".to_string())?;
+    if caller() != requester {
+        require_admin()?;
+    }
+    if !matches!(status.as_str(), "rejected" | "cancelled" | "failed") {
+        return Err(format!("Escrow cannot be refunded while the computation is {}", status));
+    }
+    payments::escrow::refund(&request_id).await
+}
+
+// ICRC-2 ledger that agent fees are paid on (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_payment_ledger(ledger: Option<Principal>) -> Result<(), String> {
+    require_admin()?;
+    payments::set_ledger(ledger);
+    Ok(())
+}
+
+// Principal that may withdraw an agent's earnings (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_agent_owner(agent_id: String, owner: Principal) -> Result<(), String> {
+    require_admin()?;
+    agent_registry::set_owner(&agent_id, owner)
+}
+
+#[ic_cdk::query]
+fn get_agent_balance(agent_id: String) -> u64 {
+    payments::balance(&agent_id)
+}
+
+#[ic_cdk::query]
+fn get_computation_payments(request_id: String) -> Vec<AgentPayment> {
+    payments::payments_for(&request_id)
+}
+
+// Transfer an agent's accrued fees to its owner (owner or admin)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn withdraw_agent_earnings(agent_id: String) -> Result<AgentWithdrawal, String> {
+    let owner = agent_registry::owner_of(&agent_id)
+        .ok_or_else(|| format!("Agent {} has no owner to pay out to", agent_id))?;
+    if caller() != owner {
+        require_admin()?;
+    }
+    payments::withdraw(&agent_id, owner).await
+}
+
+// Retry a withdrawal whose ledger outcome was unknown (owner or admin)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn reconcile_agent_withdrawal(agent_id: String) -> Result<AgentWithdrawal, String> {
+    if agent_registry::owner_of(&agent_id) != Some(caller()) {
+        require_admin()?;
+    }
+    payments::reconcile_withdrawal(&agent_id).await
+}
+
+#[ic_cdk::query]
+fn get_unsettled_withdrawal(agent_id: String) -> Option<PendingTransfer> {
+    payments::unsettled_withdrawal(&agent_id)
+}
+
+// ============================================================================
+// AGENT REGISTRATION
+// ============================================================================
+
+// Register a third-party agent owned by the caller
+#[ic_cdk::update(guard = "rate_limited")]
+fn register_mpc_agent(
+    id: String,
+    identity: String,
+    capabilities: Vec<Capability>,
+    price_per_computation: u64,
+) -> Result<MPCAgent, String> {
+    let owner = caller();
+    if owner == Principal::anonymous() {
+        return Err("Anonymous principals cannot register agents".to_string());
+    }
+    let agent = MPCAgent {
+        id,
+        identity,
+        capabilities,
+        reputation_score: agent_registry::INITIAL_REPUTATION,
+        price_per_computation,
+        version: 1,
+        owner: Some(owner),
+    };
+    agent_registry::validate_registration(&agent, owner)?;
+    agent_registry::register_agent(agent.clone())?;
+    agent_registry::record_version(&agent, "Initial registration", Some(owner), current_timestamp());
+    Ok(agent)
+}
+
+#[ic_cdk::query]
+fn list_mpc_agents() -> Vec<MPCAgent> {
+    agent_registry::list_all_agents()
+}
+
+// ============================================================================
+// AGENT LIVENESS
+// ============================================================================
+
+// Mark every agent the caller owns as alive; agents call this periodically
+#[ic_cdk::update(guard = "rate_limited")]
+fn agent_heartbeat() -> Result<Vec<String>, String> {
+    let agents = agent_registry::agents_owned_by(caller());
+    if agents.is_empty() {
+        return Err("Caller does not operate any registered agent".to_string());
+    }
+    let now = current_timestamp();
+    for agent_id in &agents {
+        agent_registry::record_heartbeat(agent_id, now);
+    }
+    Ok(agents)
+}
+
+// Silence after which an agent is no longer offered for computations (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_agent_liveness_window(seconds: u64) -> Result<(), String> {
+    require_admin()?;
+    agent_registry::set_liveness_window(seconds * 1_000_000_000);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_agent_liveness(agent_id: String) -> AgentLiveness {
+    agent_registry::liveness(&agent_id, current_timestamp())
+}
+
+#[ic_cdk::query]
+fn is_agent_available(agent_id: String) -> bool {
+    agent_registry::is_agent_available(&agent_id, current_timestamp())
+}
+
+// Agents matching capability, reputation, price and availability criteria, best first
+#[ic_cdk::query]
+fn search_agents(filter: AgentFilter) -> Vec<MPCAgent> {
+    agent_registry::search(&filter, current_timestamp())
+}
+
+// ============================================================================
+// AGENT TEAMS
+// ============================================================================
+
+// Cheapest team of agents covering every capability a computation type needs
+#[ic_cdk::query]
+fn suggest_agent_team(computation_type: CapabilityDomain, budget: u64, min_reputation: Option<u32>) -> Result<TeamSuggestion, String> {
+    let required = capabilities::required_capabilities(computation_type);
+    let candidates = agent_registry::search(&AgentFilter {
+        min_reputation,
+        max_price: Some(budget),
+        ..Default::default()
+    }, current_timestamp());
+    
+    let agents = capabilities::cheapest_cover(&candidates, &required, budget)
+        .ok_or_else(|| format!("No team of agents covers {:?} within a budget of {}", required, budget))?;
+    Ok(TeamSuggestion {
+        computation_type,
+        required_capabilities: required,
+        agent_ids: agents.iter().map(|a| a.id.clone()).collect(),
+        total_price: agents.iter().map(|a| a.price_per_computation).sum(),
+        agents,
+    })
+}
+
+// Form an agent team over the given datasets, e.g. from `suggest_agent_team`
+#[ic_cdk::update(guard = "rate_limited")]
+async fn deploy_mpc_agents(agent_ids: Vec<String>, data_source_ids: Vec<String>) -> Result<String, String> {
+    if agent_ids.is_empty() {
+        return Err("An agent team needs at least one agent".to_string());
+    }
+    mpc_engine::create_agent_team(agent_ids, data_source_ids).await
+}
+
+#[ic_cdk::query]
+fn get_agent_team(team_id: String) -> Result<AgentTeam, String> {
+    mpc_engine::get_team_info(team_id)
+}
+
+// ============================================================================
+// AGENT VERSIONS
+// ============================================================================
+
+// Publish a new version of an agent's capabilities (agent owner or admin)
+#[ic_cdk::update(guard = "rate_limited")]
+fn update_agent_capabilities(agent_id: String, capabilities: Vec<Capability>, change_note: String) -> Result<MPCAgent, String> {
+    let caller = caller();
+    if agent_registry::owner_of(&agent_id) != Some(caller) {
+        require_admin()?;
+    }
+    if capabilities.is_empty() {
+        return Err("An agent needs at least one capability".to_string());
+    }
+    agent_registry::update_capabilities(&agent_id, capabilities, &change_note, caller, current_timestamp())
+}
+
+#[ic_cdk::query]
+fn get_agent_changelog(agent_id: String) -> Vec<AgentVersion> {
+    agent_registry::changelog(&agent_id)
+}
+
+// ============================================================================
+// AGENT BIDDING
+// ============================================================================
+
+// Publish a computation for agents to bid on; the best bid is assigned
+// automatically once the window closes (requester only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn open_bidding_round(request_id: String, window_seconds: u64, max_winners: Option<u32>) -> Result<BiddingRound, String> {
+    let caller = caller();
+    if !(bidding::MIN_BIDDING_WINDOW_SECS..=bidding::MAX_BIDDING_WINDOW_SECS).contains(&window_seconds) {
+        return Err(format!("Bidding window must be between {} and {} seconds",
+            bidding::MIN_BIDDING_WINDOW_SECS, bidding::MAX_BIDDING_WINDOW_SECS));
+    }
+    let spec = COMPUTATION_REQUESTS.with(|requests| {
+        let requests_map = requests.borrow();
+        let computation = requests_map.get(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.requester != caller {
+            return Err("Only the requester can open bidding".to_string());
+        }
+        if !is_open_for_voting(&computation.status) || computation.agent_id.is_some() {
+            return Err("Bidding is only possible before execution and without an assigned agent".to_string());
+        }
+        Ok(format!("{}\n\n{}", computation.title, computation.description))
+    })?;
+    
+    let deadline = current_timestamp() + window_seconds * 1_000_000_000;
+    let round = bidding::open(&request_id, spec, caller, deadline, max_winners.unwrap_or(1))?;
+    ic_cdk_timers::set_timer(std::time::Duration::from_secs(window_seconds), move || {
+        if let Err(e) = award_bidding_round(&request_id) {
+            ic_cdk::println!("Closing bidding round for {} failed: {}", request_id, e);
+        }
+    });
+    Ok(round)
+}
+
+// Bid on a computation on behalf of an agent (agent owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn submit_bid(request_id: String, agent_id: String, price: u64, eta_seconds: u64) -> Result<(), String> {
+    let caller = caller();
+    let agent = agent_registry::get_agent_by_id(&agent_id)
+        .ok_or_else(|| format!("Agent {} not found", agent_id))?;
+    if agent_registry::owner_of(&agent_id) != Some(caller) {
+        return Err("Only the agent's owner can bid for it".to_string());
+    }
+    bidding::submit(&request_id, Bid {
+        agent_id,
+        bidder: caller,
+        price,
+        eta_seconds,
+        reputation_score: agent.reputation_score,
+        submitted_at: current_timestamp(),
+    })
+}
+
+// Close a round whose deadline passed, in case its timer did not fire
+#[ic_cdk::update(guard = "rate_limited")]
+fn close_bidding_round(request_id: String) -> Result<BiddingRound, String> {
+    award_bidding_round(&request_id)
+}
+
+// Pick the winning bids and assign the top one to the computation
+fn award_bidding_round(request_id: &str) -> Result<BiddingRound, String> {
+    let round = bidding::close(request_id, current_timestamp())?;
+    if let Some(winner) = round.winners.first() {
+        let assigned = COMPUTATION_REQUESTS.with(|requests| {
+            match requests.borrow_mut().get_mut(request_id) {
+                Some(computation) if is_open_for_voting(&computation.status) && computation.agent_id.is_none() => {
+                    computation.agent_id = Some(winner.agent_id.clone());
+                    computation.agent_version = agent_registry::get_agent_by_id(&winner.agent_id).map(|a| a.version);
+                    true
+                },
+                _ => false,
+            }
+        });
+        if assigned {
+            computation_changed(request_id);
+        }
+    }
+    Ok(round)
+}
+
+#[ic_cdk::query]
+fn get_bidding_round(request_id: String) -> Option<BiddingRound> {
+    bidding::get(&request_id)
+}
+
+#[ic_cdk::query]
+fn get_open_bidding_rounds() -> Vec<BiddingRound> {
+    bidding::open_rounds()
+}
+
+// ============================================================================
+// COMPUTATION PROGRESS
+// ============================================================================
+
+// Phase, percent complete and per-agent status of a running or finished computation.
+// Computation requests report under their request id, checkpointed jobs under the
+// job id and direct team runs under the team id.
+#[ic_cdk::query]
+fn get_computation_progress(request_id: String) -> Result<ComputationProgress, String> {
+    progress::get(&request_id).ok_or_else(|| format!("No progress reported for {}", request_id))
+}
+
+// ============================================================================
+// DISCLOSURE CONTROL
+// ============================================================================
+
+fn record_disclosure_report(request_id: &str, caller: Principal, report: DisclosureReport) {
+    if !report.suppressions.is_empty() {
+        let summary = format!("Suppressed {} statistic(s) from fewer than {} records",
+            report.suppressions.len(), report.min_cohort_size);
+        progress::report(request_id, ProgressEvent::Note(summary.clone()), current_timestamp());
+        audit_log::record(AuditEvent::Execution, caller, request_id, summary);
+    }
+    disclosure_control::record(request_id, report);
+    if privacy_proofs::generate_disclosure_proof(request_id.to_string()).is_ok() {
+        certify_privacy_audit(request_id);
+    }
+}
+
+// Minimum cohort size (k), small-cell threshold and rounding base for
+// published statistics (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_disclosure_policy(policy: DisclosurePolicy) -> Result<(), String> {
+    let admin = require_admin()?;
+    disclosure_control::set_policy(policy)?;
+    record_admin_activity(ActivityKind::PolicyChanged, admin, "canister", "Disclosure control policy updated".to_string());
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_disclosure_policy() -> DisclosurePolicy {
+    disclosure_control::policy()
+}
+
+// Epsilon, delta and noise mechanism for released aggregates (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_differential_privacy_config(config: DpConfig) -> Result<(), String> {
+    let admin = require_admin()?;
+    differential_privacy::set_config(config)?;
+    record_admin_activity(ActivityKind::PolicyChanged, admin, "canister", "Differential privacy configuration updated".to_string());
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_differential_privacy_config() -> DpConfig {
+    differential_privacy::config()
+}
+
+// A dataset's differential privacy budget and what spent it (owner only)
+#[ic_cdk::query]
+fn get_privacy_budget(dataset_id: String) -> Result<PrivacyBudget, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() && require_admin().is_err() {
+        return Err("Only the dataset owner can view its privacy budget".to_string());
+    }
+    Ok(privacy_budget::budget(&dataset_id))
+}
+
+// Set the total epsilon queries may spend on a dataset (owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_privacy_budget(dataset_id: String, total_epsilon: f64) -> Result<PrivacyBudget, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() {
+        return Err("Only the dataset owner can set its privacy budget".to_string());
+    }
+    privacy_budget::set_total(&dataset_id, total_epsilon)
+}
+
+// Noisy summary of a dataset's columns to design queries with, open to any
+// caller without an approval round. Each dataset version is summarized once,
+// paid for from the dataset's privacy budget.
+#[ic_cdk::update(guard = "rate_limited")]
+async fn get_dataset_summary(dataset_id: String) -> Result<DatasetSummary, String> {
+    pause::ensure_running()?;
+    let caller = caller();
+    anomaly::ensure_not_suspended(caller)?;
+    let dataset = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).cloned())
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    let version = lineage::current_version(&dataset_id, current_timestamp());
+    if let Some(summary) = summary::cached(&version) {
+        return Ok(summary);
+    }
+    let schema = analytics::parse_schema(&dataset.schema)?;
+    let config = DpConfig { epsilon: summary::SUMMARY_EPSILON, delta: 0.0, mechanism: NoiseMechanism::Laplace };
+    let dataset_ids = [dataset_id.clone()];
+    privacy_budget::validate(&dataset_ids, &config)?;
+    privacy_budget::check(&dataset_ids, config.epsilon)?;
+    
+    let summary_id = format!("summary_{}", version);
+    let mut window = open_decryption_window(&generate_id("summary"))?;
+    let derivation_path = dataset_key_path(&dataset);
+    let key = derive_vetkey_for_party(dataset.owner, derivation_path).await?;
+    window.ensure_open(current_timestamp())?;
+    let mut decrypted = decrypt_with_vetkey(&dataset.encrypted_data, window.hold_key(key));
+    window.add(String::from_utf8_lossy(&decrypted).to_string());
+    decrypted.zeroize();
+    audit_log::record(AuditEvent::Decryption, caller, &dataset_id, format!("Dataset decrypted for summary {}", summary_id));
+    dataset_access::record(&dataset_id, caller, AccessType::Decryption, Some(&summary_id), vec![]);
+    let (seed,) = raw_rand().await
+        .map_err(|(code, msg)| format!("No randomness for noise: {:?} - {}", code, msg))?;
+    let mut source = differential_privacy::NoiseSource::new(seed);
+    window.ensure_open(current_timestamp())?;
+    let summary = summary::summarize(&dataset_id, &version, &schema, &window.data()[0], &mut source, current_timestamp())?;
+    close_decryption_window(window);
+    
+    // A concurrent call may have summarized this version meanwhile; only the
+    // first summary is paid for and kept
+    if let Some(summary) = summary::cached(&version) {
+        return Ok(summary);
+    }
+    privacy_budget::debit(&dataset_ids, &summary_id, config.epsilon, current_timestamp())?;
+    summary::store(summary.clone());
+    lineage::record_inputs(&summary_id, "Dataset summary".to_string(), &dataset_ids, current_timestamp());
+    lineage::record_result(&summary_id, &format!("{}/result", summary_id), "Noisy column summaries".to_string(), current_timestamp());
+    audit_log::record(AuditEvent::Execution, caller, &summary_id,
+        format!("Summary of dataset {} released with epsilon {}", dataset_id, config.epsilon));
+    Ok(summary)
+}
+
+// Largest epsilon and delta a single query may use on a dataset (owner only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_dataset_dp_bounds(dataset_id: String, bounds: Option<DpBounds>) -> Result<PrivacyBudget, String> {
+    let owner = DATA_SOURCES.with(|sources| sources.borrow().get(&dataset_id).map(|d| d.owner))
+        .ok_or_else(|| format!("Dataset {} not found", dataset_id))?;
+    if owner != caller() {
+        return Err("Only the dataset owner can bound its privacy parameters".to_string());
+    }
+    privacy_budget::set_bounds(&dataset_id, bounds)
+}
+
+// Sensitivity and noise scale of each statistic a query would release, for
+// the requester and the parties asked to approve it
+#[ic_cdk::query]
+fn get_query_sensitivity(query_id: String) -> Result<SensitivityAnalysis, String> {
+    let query = LLM_QUERIES.with(|queries| queries.borrow().get(&query_id).cloned())
+        .ok_or_else(|| "Query not found".to_string())?;
+    let caller = caller();
+    if query.requester != caller && !query.required_signatures.contains(&caller) && require_admin().is_err() {
+        return Err("Only the requester and required signers can view this query's sensitivity".to_string());
+    }
+    let declared_records = DATA_SOURCES.with(|sources| {
+        let sources = sources.borrow();
+        query.target_datasets.iter()
+            .filter_map(|id| sources.get(id))
+            .map(|source| source.record_count as u64)
+            .sum()
+    });
+    let config = query.dp_params.clone().unwrap_or_else(differential_privacy::config);
+    Ok(sensitivity::analyze(&query.id, &query.query, declared_records, &query_schema(&query)?, &config))
+}
+
+// What was suppressed in a computation's results (result viewers only)
+#[ic_cdk::query]
+fn get_disclosure_report(request_id: String) -> Result<DisclosureReport, String> {
+    let computation = COMPUTATION_REQUESTS.with(|requests| requests.borrow().get(&request_id).cloned())
+        .ok_or_else(|| "Computation request not found".to_string())?;
+    if !can_view_results(&computation, caller()) {
+        return Err("Not authorized to view this computation's results".to_string());
+    }
+    disclosure_control::report(&request_id)
+        .ok_or_else(|| format!("Computation {} has no published results", request_id))
+}
+
+// ============================================================================
+// EXECUTION QUEUE
+// ============================================================================
+
+// Where a ready computation stands in the execution queue
+#[ic_cdk::query]
+fn get_queue_position(request_id: String) -> Result<QueuePosition, String> {
+    scheduler::position(&request_id).ok_or_else(|| format!("Computation {} is not queued", request_id))
+}
+
+// Computations waiting for an execution slot, in the order they will run
+#[ic_cdk::query]
+fn get_computation_queue() -> Vec<QueuedComputation> {
+    scheduler::waiting()
+}
+
+// Requesters may lower their computation's priority; only admins can raise it to high
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_computation_priority(request_id: String, priority: Priority) -> Result<(), String> {
+    let caller = caller();
+    let is_admin = require_admin().is_ok();
+    if priority == Priority::High && !is_admin {
+        return Err("Only canister controllers can assign high priority".to_string());
+    }
+    
+    COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests = requests.borrow_mut();
+        let computation = requests.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.requester != caller && !is_admin {
+            return Err("Only the original requester can change this computation's priority".to_string());
+        }
+        computation.priority = priority;
+        Ok(())
+    })?;
+    
+    // Already queued computations move to their new place
+    let _ = scheduler::set_priority(&request_id, priority);
+    computation_changed(&request_id);
+    Ok(())
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_max_concurrent_executions(max: u32) -> Result<(), String> {
+    require_admin()?;
+    scheduler::set_max_concurrent(max)
+}
+
+// ============================================================================
+// COMPUTATION SUBSCRIPTIONS
+// ============================================================================
+
+// Have `callback_canister` notified through `method` when the computation completes or
+// fails. Anyone entitled to the results may subscribe; a computation that already
+// finished is notified straight away.
+#[ic_cdk::update(guard = "rate_limited")]
+fn subscribe_to_computation(
+    request_id: String,
+    callback_canister: Principal,
+    method: String,
+) -> Result<ComputationSubscription, String> {
+    let caller = caller();
+    let (status, last_error) = COMPUTATION_REQUESTS.with(|requests| {
+        let requests = requests.borrow();
+        let computation = requests.get(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if !can_view_results(computation, caller) {
+            return Err("Only principals entitled to the results can subscribe".to_string());
+        }
+        Ok((computation.status.clone(), computation.last_error.clone()))
+    })?;
+    
+    subscriptions::subscribe(ComputationSubscription {
+        request_id: request_id.clone(),
+        subscriber: caller,
+        callback_canister,
+        method: method.clone(),
+        created_at: current_timestamp(),
+        last_notified_status: None,
+        last_notified_at: None,
+        last_error: None,
+    })?;
+    subscriptions::status_changed(&request_id, &status, last_error, current_timestamp());
+    
+    subscriptions::subscriptions_of(&request_id, caller).into_iter()
+        .find(|s| s.callback_canister == callback_canister && s.method == method)
+        .ok_or_else(|| "Subscription not found".to_string())
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn unsubscribe_from_computation(request_id: String, callback_canister: Principal, method: String) -> Result<(), String> {
+    subscriptions::unsubscribe(&request_id, caller(), callback_canister, &method)
+}
+
+// The caller's subscriptions to a computation, with their delivery state
+#[ic_cdk::query]
+fn get_computation_subscriptions(request_id: String) -> Vec<ComputationSubscription> {
+    subscriptions::subscriptions_of(&request_id, caller())
+}
+
+// ============================================================================
+// WORKFLOW EVENTS
+// ============================================================================
+
+// Workflow events after sequence number `seq` about resources the caller's workspace
+// can see, oldest first; pass 0 to read from the start
+#[ic_cdk::query]
+fn get_events_since(seq: u64, limit: Option<u32>) -> Vec<EventRecord> {
+    let caller = caller();
+    events::since(seq, limit, |record| organizations::visible_or_unassigned(record.event.resource_id(), caller))
+}
+
+#[ic_cdk::query]
+fn get_latest_event_seq() -> u64 {
+    events::latest_seq()
+}
+
+// ============================================================================
+// LLM PROVIDERS
+// ============================================================================
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_llm_http_provider(provider: HttpProvider) -> Result<(), String> {
+    require_admin()?;
+    secure_llm::http_outcall::set_provider(provider)
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn remove_llm_http_provider(name: String) -> Result<(), String> {
+    require_admin()?;
+    secure_llm::http_outcall::remove_provider(&name)
+}
+
+#[ic_cdk::query]
+fn list_llm_http_providers() -> Vec<HttpProviderInfo> {
+    secure_llm::http_outcall::providers()
+}
+
+// Backend for LLM calls of computations that do not name a provider
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_llm_backend(backend: LlmBackend) -> Result<(), String> {
+    require_admin()?;
+    secure_llm::provider::set_backend(backend)
+}
+
+#[ic_cdk::query]
+fn get_llm_backend() -> LlmBackend {
+    secure_llm::provider::backend()
+}
+
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_llm_retry_policy(policy: RetryPolicy) -> Result<(), String> {
+    let admin = require_admin()?;
+    secure_llm::resilience::set_policy(policy)?;
+    record_admin_activity(ActivityKind::PolicyChanged, admin, "canister", "LLM retry policy updated".to_string());
+    Ok(())
+}
+
+// Circuit breaker state of the LLM backend and the retry policy in force
+#[ic_cdk::query]
+fn get_llm_health() -> LlmHealth {
+    secure_llm::resilience::health(current_timestamp())
+}
+
+// LLM calls, payload sizes and estimated tokens per principal and per computation (admin only)
+#[ic_cdk::query]
+fn get_llm_usage_stats() -> Result<LlmUsageStats, String> {
+    require_admin()?;
+    Ok(secure_llm::usage::stats())
+}
+
+// The caller's own LLM usage and budget
+#[ic_cdk::query]
+fn get_my_llm_usage() -> PrincipalLlmUsage {
+    secure_llm::usage::principal_usage(caller())
+}
+
+// Cap a principal's LLM calls or tokens; `None` removes the cap
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_llm_budget(principal: Principal, budget: Option<LlmBudget>) -> Result<(), String> {
+    require_admin()?;
+    secure_llm::usage::set_budget(principal, budget);
+    Ok(())
+}
+
+// Close the circuit without waiting for the cooldown, e.g. after fixing the backend
+#[ic_cdk::update(guard = "rate_limited")]
+fn reset_llm_circuit() -> Result<(), String> {
+    require_admin()?;
+    secure_llm::resilience::reset();
+    Ok(())
+}
+
+// Embed texts with the configured backend (registered parties only)
+#[ic_cdk::update(guard = "rate_limited")]
+async fn get_text_embeddings(texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+    if !PARTIES.with(|parties| parties.borrow().contains_key(&caller())) && require_admin().is_err() {
+        return Err("Only registered parties can request embeddings".to_string());
+    }
+    let request_key = generate_id("embed");
+    let context = CallContext { computation_id: request_key.clone(), principal: caller(), provider: None };
+    secure_llm::provider::resolve(&context, &request_key)?.embeddings(&texts).await
+}
+
+// Route a computation's LLM calls to an HTTPS provider, or back to the IC LLM canister
+// with `None`. Parties must know where their data goes, so this is fixed once voting starts.
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_computation_llm_provider(request_id: String, provider: Option<String>) -> Result<(), String> {
+    let caller = caller();
+    if let Some(name) = &provider {
+        if !secure_llm::http_outcall::has_provider(name) {
+            return Err(format!("LLM provider {} not found", name));
+        }
+    }
+    
+    COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests = requests.borrow_mut();
+        let computation = requests.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.requester != caller {
+            return Err("Only the original requester can choose the LLM provider".to_string());
+        }
+        if computation.status != "pending_approval" || !computation.votes.is_empty() {
+            return Err("The LLM provider can only be changed before voting starts".to_string());
+        }
+        computation.llm_provider = provider;
+        Ok(())
+    })?;
+    
+    computation_changed(&request_id);
+    Ok(())
+}
+
+// Transform for provider responses so every replica sees the same bytes
+#[ic_cdk::query]
+fn transform_llm_response(args: TransformArgs) -> HttpResponse {
+    secure_llm::http_outcall::transform(args)
+}
+
+// ============================================================================
+// PROMPT TEMPLATES
+// ============================================================================
+
+// Variables the canister fills in when rendering a computation's prompt
+const COMPUTATION_PROMPT_VARIABLES: [&str; 2] = ["description", "analysis"];
+
+fn computation_prompt(description: &str, analysis: &str, binding: Option<&TemplateBinding>) -> Result<String, String> {
+    let Some(binding) = binding else {
+        return Ok(format!("{}\n\nAnalysis:\n{}", description, analysis));
+    };
+    let template = secure_llm::templates::approved(&binding.template_id, Some(binding.version))?;
+    let system = [("description", description.to_string()), ("analysis", analysis.to_string())];
+    secure_llm::templates::render(&template, &binding.values, &system)
+}
+
+// Check the query and analysis for injected instructions, then redact
+// identifiers; rejects with the reason or returns the cleaned texts,
+// recording what was removed
+fn prepare_prompt_inputs(request_id: &str, caller: Principal, description: &str, analysis: &str) -> Result<(String, String), String> {
+    let inputs = [("query", description), ("analysis", analysis)];
+    let (mut screened, findings) = secure_llm::injection::screen(&inputs, &secure_llm::injection::policy())?;
+    if !findings.is_empty() {
+        let reason = format!("Sanitized possible prompt injection: {}", secure_llm::injection::describe(&findings));
+        progress::report(request_id, ProgressEvent::Note(reason.clone()), current_timestamp());
+        audit_log::record(AuditEvent::Execution, caller, request_id, reason);
+    }
+    let mut redactor = secure_llm::redaction::Redactor::new();
+    let analysis = redactor.redact(&screened.pop().unwrap_or_default());
+    let description = redactor.redact(&screened.pop().unwrap_or_default());
+    let redactions = redactor.summary();
+    if !redactions.is_empty() {
+        let summary = format!("Redacted identifiers: {}", secure_llm::redaction::describe(&redactions));
+        progress::report(request_id, ProgressEvent::Note(summary.clone()), current_timestamp());
+        audit_log::record(AuditEvent::Execution, caller, request_id, summary);
+    }
+    Ok((description, analysis))
+}
+
+// What happens to computations whose inputs look like prompt injection (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_prompt_injection_policy(policy: InjectionPolicy) -> Result<(), String> {
+    let admin = require_admin()?;
+    secure_llm::injection::set_policy(policy);
+    record_admin_activity(ActivityKind::PolicyChanged, admin, "canister", "Prompt injection policy updated".to_string());
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_prompt_injection_policy() -> InjectionPolicy {
+    secure_llm::injection::policy()
+}
+
+// Propose a new prompt template (registered parties); it needs admin approval before use
+#[ic_cdk::update(guard = "rate_limited")]
+fn create_prompt_template(
+    id: String,
+    computation_type: String,
+    body: String,
+    variables: Vec<TemplateVariable>,
+) -> Result<PromptTemplate, String> {
+    if !PARTIES.with(|parties| parties.borrow().contains_key(&caller())) && require_admin().is_err() {
+        return Err("Only registered parties can create prompt templates".to_string());
+    }
+    secure_llm::templates::create(&id, &computation_type, body, variables, caller(), current_timestamp())
+}
+
+// Propose a new version of an existing template
+#[ic_cdk::update(guard = "rate_limited")]
+fn revise_prompt_template(id: String, body: String, variables: Vec<TemplateVariable>) -> Result<PromptTemplate, String> {
+    if !PARTIES.with(|parties| parties.borrow().contains_key(&caller())) && require_admin().is_err() {
+        return Err("Only registered parties can revise prompt templates".to_string());
+    }
+    secure_llm::templates::revise(&id, body, variables, caller(), current_timestamp())
+}
+
+// Approve or reject a pending template version (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn review_prompt_template(id: String, version: u32, approve: bool, note: Option<String>) -> Result<PromptTemplate, String> {
+    let admin = require_admin()?;
+    secure_llm::templates::review(&id, version, approve, admin, note, current_timestamp())
+}
+
+// Delete a template with all its versions (admin only)
+#[ic_cdk::update(guard = "rate_limited")]
+fn delete_prompt_template(id: String) -> Result<(), String> {
+    require_admin()?;
+    let in_use = COMPUTATION_REQUESTS.with(|requests| {
+        requests.borrow().values().any(|c| {
+            c.prompt_template.as_ref().is_some_and(|b| b.template_id == id)
+                && c.status != "completed" && c.status != "failed"
+        })
+    });
+    if in_use {
+        return Err(format!("Prompt template {} is used by an unfinished computation", id));
+    }
+    secure_llm::templates::delete(&id)
+}
+
+// A template version, or its latest version
+#[ic_cdk::query]
+fn get_prompt_template(id: String, version: Option<u32>) -> Option<PromptTemplate> {
+    secure_llm::templates::get(&id, version)
+}
+
+// The template currently used for a computation type, if one is approved
+#[ic_cdk::query]
+fn get_active_prompt_template(computation_type: String) -> Option<PromptTemplate> {
+    secure_llm::templates::active_for(&computation_type)
+}
+
+#[ic_cdk::query]
+fn list_prompt_templates() -> Vec<PromptTemplate> {
+    secure_llm::templates::list()
+}
+
+// Render a template version with the given values without running anything
+#[ic_cdk::query]
+fn preview_prompt_template(id: String, version: Option<u32>, values: Vec<(String, String)>) -> Result<String, String> {
+    let template = secure_llm::templates::get(&id, version)
+        .ok_or_else(|| format!("Prompt template {} not found", id))?;
+    secure_llm::templates::render(&template, &values, &[])
+}
+
+// Choose the approved template a computation's prompt is rendered from
+// (requester only, before voting starts)
+#[ic_cdk::update(guard = "rate_limited")]
+fn set_computation_prompt_template(request_id: String, binding: Option<TemplateBinding>) -> Result<(), String> {
+    let caller = caller();
+    if let Some(binding) = &binding {
+        // Render once with the canister's variables blank to check the caller's values
+        let template = secure_llm::templates::approved(&binding.template_id, Some(binding.version))?;
+        let system = COMPUTATION_PROMPT_VARIABLES.map(|name| (name, String::new()));
+        secure_llm::templates::render(&template, &binding.values, &system)?;
+    }
+
+    COMPUTATION_REQUESTS.with(|requests| {
+        let mut requests = requests.borrow_mut();
+        let computation = requests.get_mut(&request_id)
+            .ok_or_else(|| "Computation request not found".to_string())?;
+        if computation.requester != caller {
+            return Err("Only the original requester can choose the prompt template".to_string());
+        }
+        if computation.status != "pending_approval" || !computation.votes.is_empty() {
+            return Err("The prompt template can only be changed before voting starts".to_string());
+        }
+        computation.prompt_template = binding;
+        Ok(())
+    })?;
+
+    computation_changed(&request_id);
+    Ok(())
+}
+
+// ============================================================================
+// HISTORICAL STATE
+// ============================================================================
+
+// Sequence number the next recorded change will receive
+#[ic_cdk::query]
+fn get_history_sequence() -> u64 {
+    history::current_sequence()
+}
+
+// Approval state of a computation as of a sequence number or timestamp
+#[ic_cdk::query]
+fn get_computation_state_at(request_id: String, as_of: AsOf) -> Result<ComputationSnapshot, String> {
+    organizations::ensure_visible(&request_id, caller())?;
+    history::computation_at(&request_id, &as_of)
+        .ok_or_else(|| format!("No recorded state for computation {} at {:?}", request_id, as_of))
+}
+
+// Dataset permissions as of a sequence number or timestamp
+#[ic_cdk::query]
+fn get_dataset_permissions_at(dataset_id: String, as_of: AsOf) -> Result<PermissionSnapshot, String> {
+    organizations::ensure_visible(&dataset_id, caller())?;
+    history::permissions_at(&dataset_id, &as_of)
+        .ok_or_else(|| format!("No recorded permissions for dataset {} at {:?}", dataset_id, as_of))
+}
+
+// Dataset versions, runs and results upstream and downstream of a dataset,
+// dataset version (`<id>@<version>`), run or result (`<run id>/result`)
+#[ic_cdk::query]
+fn get_lineage(resource_id: String) -> Result<LineageGraph, String> {
+    let caller = caller();
+    if !lineage_node_visible(&resource_id, caller) {
+        return Err(format!("{} belongs to another workspace and is not shared with yours", resource_id));
+    }
+    let mut graph = lineage::lineage(&resource_id)?;
+    graph.nodes.retain(|node| lineage_node_visible(&node.id, caller));
+    graph.edges.retain(|edge| lineage_node_visible(&edge.from, caller) && lineage_node_visible(&edge.to, caller));
+    Ok(graph)
+}
+
+// Dataset versions follow their dataset and results follow their run
+fn lineage_node_visible(node_id: &str, principal: Principal) -> bool {
+    let resource_id = node_id.split(['@', '/']).next().unwrap_or(node_id);
+    organizations::visible_or_unassigned(resource_id, principal)
+}
+
 // Get user identity information
 #[ic_cdk::query]
 fn get_user_identity() -> Result<String, String> {
@@ -1,6 +1,7 @@
 use candid::CandidType;
 use ic_cdk::export_candid;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::cell::RefCell;
 use std::collections::HashMap;
 
@@ -85,8 +86,7 @@ fn generate_unique_id() -> String {
 
 // Hash a string (for schema hashing)
 fn compute_hash(input: &[u8]) -> String {
-    // Simple hash for demo purposes
-    format!("0x{:x}", ic_cdk::api::call::arg_data_raw_size() + input.len())
+    hex::encode(Sha256::digest(input))
 }
 
 // Store a data source
@@ -0,0 +1,161 @@
+//! Lineage of derived results
+//!
+//! Every upload or append of a dataset creates a new dataset version. Runs
+//! that read datasets record edges from the versions they read to the run,
+//! and from the run to what it produced: a result, or a derived dataset such
+//! as a joined view that later runs read in turn. Following the edges back
+//! from any result shows exactly which dataset versions contributed to it.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum LineageKind {
+    DatasetVersion,
+    Computation,
+    Result,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LineageNode {
+    /// Dataset versions are `<dataset id>@<version>`
+    pub id: String,
+    pub kind: LineageKind,
+    pub label: String,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LineageEdge {
+    pub from: String,
+    pub to: String,
+    pub created_at: u64,
+}
+
+/// Everything upstream and downstream of a resource
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LineageGraph {
+    pub resource_id: String,
+    pub nodes: Vec<LineageNode>,
+    pub edges: Vec<LineageEdge>,
+}
+
+thread_local! {
+    static VERSIONS: RefCell<HashMap<String, u32>> = RefCell::new(HashMap::new());
+    static NODES: RefCell<HashMap<String, LineageNode>> = RefCell::new(HashMap::new());
+    static EDGES: RefCell<Vec<LineageEdge>> = const { RefCell::new(Vec::new()) };
+}
+
+fn version_id(dataset_id: &str, version: u32) -> String {
+    format!("{}@{}", dataset_id, version)
+}
+
+fn add_node(id: &str, kind: LineageKind, label: String, created_at: u64) {
+    NODES.with(|nodes| {
+        nodes.borrow_mut().entry(id.to_string())
+            .or_insert_with(|| LineageNode { id: id.to_string(), kind, label, created_at });
+    });
+}
+
+fn add_edge(from: String, to: &str, created_at: u64) {
+    EDGES.with(|edges| edges.borrow_mut().push(LineageEdge { from, to: to.to_string(), created_at }));
+}
+
+/// Record that a dataset's contents changed, returning its new version
+pub fn new_version(dataset_id: &str, timestamp: u64) -> String {
+    let version = VERSIONS.with(|versions| {
+        let mut versions = versions.borrow_mut();
+        let version = versions.entry(dataset_id.to_string()).or_insert(0);
+        *version += 1;
+        *version
+    });
+    let id = version_id(dataset_id, version);
+    add_node(&id, LineageKind::DatasetVersion, format!("{} version {}", dataset_id, version), timestamp);
+    id
+}
+
+/// Current version of a dataset, which is its first if none was recorded
+pub fn current_version(dataset_id: &str, timestamp: u64) -> String {
+    match VERSIONS.with(|versions| versions.borrow().get(dataset_id).copied()) {
+        Some(version) => version_id(dataset_id, version),
+        None => new_version(dataset_id, timestamp),
+    }
+}
+
+/// Record a run and the current versions of the datasets it read
+pub fn record_inputs(computation_id: &str, label: String, dataset_ids: &[String], timestamp: u64) {
+    add_node(computation_id, LineageKind::Computation, label, timestamp);
+    for dataset_id in dataset_ids {
+        add_edge(current_version(dataset_id, timestamp), computation_id, timestamp);
+    }
+}
+
+/// Record a result a run produced
+pub fn record_result(computation_id: &str, result_id: &str, label: String, timestamp: u64) {
+    add_node(result_id, LineageKind::Result, label, timestamp);
+    add_edge(computation_id.to_string(), result_id, timestamp);
+}
+
+/// Record a dataset a run produced, as the first version of that dataset
+pub fn record_derived_dataset(computation_id: &str, dataset_id: &str, timestamp: u64) {
+    let version = new_version(dataset_id, timestamp);
+    add_edge(computation_id.to_string(), &version, timestamp);
+}
+
+/// Nodes reachable from `start` following edges forwards or backwards
+fn reachable(start: &[String], edges: &[LineageEdge], downstream: bool) -> HashSet<String> {
+    let mut seen: HashSet<String> = start.iter().cloned().collect();
+    let mut queue: VecDeque<String> = start.iter().cloned().collect();
+    while let Some(id) = queue.pop_front() {
+        for edge in edges {
+            let (from, to) = if downstream { (&edge.from, &edge.to) } else { (&edge.to, &edge.from) };
+            if *from == id && seen.insert(to.clone()) {
+                queue.push_back(to.clone());
+            }
+        }
+    }
+    seen
+}
+
+/// Lineage of a result, run or dataset version; a dataset id stands for all
+/// of its versions
+pub fn lineage(resource_id: &str) -> Result<LineageGraph, String> {
+    let (nodes, edges) = (NODES.with(|n| n.borrow().clone()), EDGES.with(|e| e.borrow().clone()));
+    let prefix = format!("{}@", resource_id);
+    let start: Vec<String> = nodes.keys()
+        .filter(|id| *id == resource_id || id.strip_prefix(&prefix).is_some_and(|v| v.parse::<u32>().is_ok()))
+        .cloned()
+        .collect();
+    if start.is_empty() {
+        return Err(format!("No lineage recorded for {}", resource_id));
+    }
+    let upstream = reachable(&start, &edges, false);
+    let downstream = reachable(&start, &edges, true);
+    let edges: Vec<LineageEdge> = edges.into_iter()
+        .filter(|e| (upstream.contains(&e.from) && upstream.contains(&e.to))
+            || (downstream.contains(&e.from) && downstream.contains(&e.to)))
+        .collect();
+    let mut nodes: Vec<LineageNode> = nodes.into_values()
+        .filter(|n| upstream.contains(&n.id) || downstream.contains(&n.id))
+        .collect();
+    nodes.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    Ok(LineageGraph { resource_id: resource_id.to_string(), nodes, edges })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&VERSIONS), backup::cloned(&NODES), backup::cloned(&EDGES)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (versions, nodes, edges) = backup::decode(bytes)?;
+    backup::replace(&VERSIONS, versions);
+    backup::replace(&NODES, nodes);
+    backup::replace(&EDGES, edges);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "lineage_test.rs"]
+mod tests;
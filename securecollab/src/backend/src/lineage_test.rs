@@ -0,0 +1,40 @@
+use super::*;
+
+fn ids(graph: &LineageGraph) -> Vec<&str> {
+    graph.nodes.iter().map(|n| n.id.as_str()).collect()
+}
+
+#[test]
+fn result_traces_back_through_derived_datasets() {
+    new_version("a", 1);
+    new_version("a", 2);
+    new_version("b", 3);
+    record_inputs("join_1", "Join".to_string(), &["a".to_string(), "b".to_string()], 4);
+    record_derived_dataset("join_1", "joined_1", 4);
+    record_inputs("query_1", "Query".to_string(), &["joined_1".to_string()], 5);
+    record_result("query_1", "query_1/result", "Result".to_string(), 6);
+
+    let graph = lineage("query_1/result").unwrap();
+    assert_eq!(ids(&graph), vec!["a@2", "b@1", "join_1", "joined_1@1", "query_1", "query_1/result"]);
+    assert_eq!(graph.edges.len(), 5);
+}
+
+#[test]
+fn dataset_lineage_covers_every_version_downstream() {
+    new_version("c", 1);
+    record_inputs("stats_1", "Statistics".to_string(), &["c".to_string()], 2);
+    new_version("c", 3);
+    record_inputs("stats_2", "Statistics".to_string(), &["c".to_string()], 4);
+    record_result("stats_2", "stats_2/result", "Result".to_string(), 5);
+
+    let graph = lineage("c").unwrap();
+    assert_eq!(ids(&graph), vec!["c@1", "stats_1", "c@2", "stats_2", "stats_2/result"]);
+    // The first run read the version before the append
+    assert!(graph.edges.iter().any(|e| e.from == "c@1" && e.to == "stats_1"));
+    assert_eq!(ids(&lineage("c@1").unwrap()), vec!["c@1", "stats_1"]);
+}
+
+#[test]
+fn unknown_resources_have_no_lineage() {
+    assert!(lineage("missing").is_err());
+}
@@ -0,0 +1,86 @@
+//! Synthetic load-test data for staging deployments
+//!
+//! Generates datasets and pending computations shaped like real hospital
+//! uploads so operators can measure memory growth, query latency and timer
+//! throughput before onboarding real parties. Generation is disabled until an
+//! admin switches it on, and everything it creates is prefixed with
+//! [`LOAD_TEST_PREFIX`] so it can be removed again.
+
+use candid::{CandidType, Deserialize};
+use std::cell::Cell;
+use sha2::{Sha256, Digest};
+
+/// Prefix of every id created by the generator
+pub const LOAD_TEST_PREFIX: &str = "loadtest_";
+/// Upper bound on datasets or computations generated per call
+pub const MAX_ITEMS_PER_CALL: u32 = 1_000;
+/// Upper bound on synthetic bytes generated per call
+pub const MAX_BYTES_PER_CALL: u64 = 64 * 1024 * 1024;
+
+const DIAGNOSES: [&str; 6] = ["diabetes_t2", "hypertension", "asthma", "copd", "ckd_stage3", "none"];
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LoadTestReport {
+    pub datasets_created: u32,
+    pub computations_created: u32,
+    pub bytes_generated: u64,
+    pub instructions_used: u64,
+    pub total_datasets: u64,
+    pub total_computations: u64,
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Check generation parameters before anything is written
+pub fn validate(datasets: u32, computations: u32, dataset_bytes: u64) -> Result<(), String> {
+    if !is_enabled() {
+        return Err("Load-test generation is disabled on this deployment".to_string());
+    }
+    if datasets > MAX_ITEMS_PER_CALL || computations > MAX_ITEMS_PER_CALL {
+        return Err(format!("At most {} datasets and {} computations per call", MAX_ITEMS_PER_CALL, MAX_ITEMS_PER_CALL));
+    }
+    if (datasets as u64).saturating_mul(dataset_bytes) > MAX_BYTES_PER_CALL {
+        return Err(format!("At most {} synthetic bytes per call", MAX_BYTES_PER_CALL));
+    }
+    Ok(())
+}
+
+/// Deterministic patient-record CSV of roughly `target_bytes` bytes
+pub fn synthetic_csv(seed: &str, target_bytes: u64) -> Vec<u8> {
+    let mut csv = String::from("patient_id,age,sex,diagnosis,hba1c,systolic_bp\n");
+    let mut row = 0u64;
+    while (csv.len() as u64) < target_bytes {
+        let bytes = Sha256::digest(format!("{}:{}", seed, row).as_bytes());
+        csv.push_str(&format!(
+            "P{:08},{},{},{},{}.{},{}\n",
+            row,
+            18 + bytes[0] % 72,
+            if bytes[1] % 2 == 0 { "F" } else { "M" },
+            DIAGNOSES[bytes[2] as usize % DIAGNOSES.len()],
+            4 + bytes[3] % 8,
+            bytes[4] % 10,
+            95 + bytes[5] % 90,
+        ));
+        row += 1;
+    }
+    csv.into_bytes()
+}
+
+/// Number of data rows in a synthetic CSV
+pub fn row_count(csv: &[u8]) -> u32 {
+    csv.iter().filter(|b| **b == b'\n').count().saturating_sub(1) as u32
+}
+
+#[cfg(test)]
+#[path = "load_test_test.rs"]
+mod tests;
@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn synthetic_csv_reaches_target_size_deterministically() {
+    let csv = synthetic_csv("seed", 4096);
+    assert!(csv.len() >= 4096);
+    assert_eq!(csv, synthetic_csv("seed", 4096));
+    assert_ne!(csv, synthetic_csv("other", 4096));
+    assert!(csv.starts_with(b"patient_id,age,sex,diagnosis,hba1c,systolic_bp\n"));
+}
+
+#[test]
+fn row_count_excludes_header() {
+    assert_eq!(row_count(b"a,b\n1,2\n3,4\n"), 2);
+    assert_eq!(row_count(b""), 0);
+}
+
+#[test]
+fn validate_requires_flag_and_limits() {
+    set_enabled(false);
+    assert!(validate(1, 1, 1024).is_err());
+
+    set_enabled(true);
+    assert!(validate(10, 10, 1024).is_ok());
+    assert!(validate(MAX_ITEMS_PER_CALL + 1, 0, 1).is_err());
+    assert!(validate(2, 0, MAX_BYTES_PER_CALL).is_err());
+}
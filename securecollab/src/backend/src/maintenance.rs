@@ -0,0 +1,128 @@
+//! Periodic maintenance of canister state
+//!
+//! A timer prunes what no longer serves a purpose: queries past their expiry
+//! that never finished, finished queries and computations once they are
+//! older than the retention window, and cached key derivations that no
+//! stored dataset, column copy, result copy or party uses. Every run reports
+//! the bytes reclaimed, measured by the Candid encoding of what it removed.
+
+use candid::{CandidType, Deserialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use crate::QueryStatus;
+use crate::backup;
+
+pub const MAINTENANCE_INTERVAL_SECONDS: u64 = 60 * 60;
+/// How long finished queries and computations are kept: 30 days
+pub const DEFAULT_RETENTION_NANOS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MaintenanceReport {
+    pub ran_at: u64,
+    pub queries_pruned: u64,
+    pub computations_pruned: u64,
+    pub derivations_pruned: u64,
+    pub bytes_reclaimed: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct MaintenanceStats {
+    pub runs: u64,
+    pub retention_nanos: u64,
+    pub last_run: Option<MaintenanceReport>,
+    /// Totals over every run
+    pub queries_pruned: u64,
+    pub computations_pruned: u64,
+    pub derivations_pruned: u64,
+    pub bytes_reclaimed: u64,
+}
+
+thread_local! {
+    static RETENTION: Cell<u64> = const { Cell::new(DEFAULT_RETENTION_NANOS) };
+    // When each computation reached a final status
+    static FINISHED: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+    static REPORTS: RefCell<Vec<MaintenanceReport>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn set_retention(nanos: u64) -> Result<(), String> {
+    if nanos == 0 {
+        return Err("The retention window must be positive".to_string());
+    }
+    RETENTION.with(|retention| retention.set(nanos));
+    Ok(())
+}
+
+pub fn retention() -> u64 {
+    RETENTION.with(Cell::get)
+}
+
+pub fn is_final_status(status: &str) -> bool {
+    matches!(status, "completed" | "failed" | "rejected" | "cancelled")
+}
+
+/// Track when a computation finished; a retried computation is no longer finished
+pub fn computation_status(computation_id: &str, status: &str, now: u64) {
+    FINISHED.with(|finished| {
+        let mut finished = finished.borrow_mut();
+        if is_final_status(status) {
+            finished.entry(computation_id.to_string()).or_insert(now);
+        } else {
+            finished.remove(computation_id);
+        }
+    });
+}
+
+/// Whether a computation finished more than the retention window before `now`
+pub fn computation_due(computation_id: &str, now: u64) -> bool {
+    FINISHED.with(|finished| {
+        finished.borrow().get(computation_id).is_some_and(|at| now >= at.saturating_add(retention()))
+    })
+}
+
+pub fn forget_computation(computation_id: &str) {
+    FINISHED.with(|finished| finished.borrow_mut().remove(computation_id));
+}
+
+/// Whether a query is past its expiry or, once completed or failed, past the retention window
+pub fn query_due(status: &QueryStatus, expires_at: u64, now: u64) -> bool {
+    match status {
+        QueryStatus::Executing => false,
+        QueryStatus::Completed | QueryStatus::Failed => now >= expires_at.saturating_add(retention()),
+        _ => now >= expires_at,
+    }
+}
+
+pub fn record(report: MaintenanceReport) {
+    REPORTS.with(|reports| reports.borrow_mut().push(report));
+}
+
+pub fn stats() -> MaintenanceStats {
+    REPORTS.with(|reports| {
+        let reports = reports.borrow();
+        MaintenanceStats {
+            runs: reports.len() as u64,
+            retention_nanos: retention(),
+            last_run: reports.last().cloned(),
+            queries_pruned: reports.iter().map(|r| r.queries_pruned).sum(),
+            computations_pruned: reports.iter().map(|r| r.computations_pruned).sum(),
+            derivations_pruned: reports.iter().map(|r| r.derivations_pruned).sum(),
+            bytes_reclaimed: reports.iter().map(|r| r.bytes_reclaimed).sum(),
+        }
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(RETENTION.with(Cell::get), backup::cloned(&FINISHED), backup::cloned(&REPORTS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (retention, finished, reports) = backup::decode(bytes)?;
+    RETENTION.with(|cell| cell.set(retention));
+    backup::replace(&FINISHED, finished);
+    backup::replace(&REPORTS, reports);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "maintenance_test.rs"]
+mod tests;
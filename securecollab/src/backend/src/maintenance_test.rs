@@ -0,0 +1,46 @@
+use super::*;
+
+#[test]
+fn computations_are_due_after_the_retention_window() {
+    set_retention(100).unwrap();
+    assert!(set_retention(0).is_err());
+    computation_status("comp_done", "computing", 0);
+    assert!(!computation_due("comp_done", 1_000));
+
+    computation_status("comp_done", "completed", 50);
+    computation_status("comp_done", "completed", 90);
+    assert!(!computation_due("comp_done", 149));
+    assert!(computation_due("comp_done", 150));
+
+    // A retried computation starts over
+    computation_status("comp_done", "ready_to_execute", 160);
+    assert!(!computation_due("comp_done", 1_000));
+    computation_status("comp_done", "failed", 200);
+    forget_computation("comp_done");
+    assert!(!computation_due("comp_done", 1_000));
+}
+
+#[test]
+fn queries_are_due_at_expiry_unless_running_or_completed() {
+    set_retention(100).unwrap();
+    assert!(!query_due(&QueryStatus::Pending, 50, 49));
+    assert!(query_due(&QueryStatus::Pending, 50, 50));
+    assert!(query_due(&QueryStatus::Rejected, 50, 60));
+    assert!(!query_due(&QueryStatus::Executing, 50, 1_000));
+    assert!(!query_due(&QueryStatus::Completed, 50, 149));
+    assert!(query_due(&QueryStatus::Completed, 50, 150));
+    assert!(query_due(&QueryStatus::Failed, 50, 150) && !query_due(&QueryStatus::Failed, 50, 149));
+}
+
+#[test]
+fn stats_total_every_run() {
+    assert_eq!(stats().runs, 0);
+    record(MaintenanceReport { ran_at: 10, queries_pruned: 2, derivations_pruned: 3, bytes_reclaimed: 400, ..Default::default() });
+    record(MaintenanceReport { ran_at: 20, computations_pruned: 1, bytes_reclaimed: 100, ..Default::default() });
+    let stats = stats();
+    assert_eq!(stats.runs, 2);
+    assert_eq!(stats.last_run.map(|r| r.ran_at), Some(20));
+    assert_eq!((stats.queries_pruned, stats.computations_pruned, stats.derivations_pruned), (2, 1, 3));
+    assert_eq!(stats.bytes_reclaimed, 500);
+    assert_eq!(stats.retention_nanos, DEFAULT_RETENTION_NANOS);
+}
@@ -0,0 +1,59 @@
+//! Descriptive metadata of datasets
+//!
+//! Owners describe where a dataset comes from and under which terms it may
+//! be used, so approvers can judge the requests that target it. Every field
+//! is optional; collection dates are written `YYYY-MM-DD`.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use crate::analytics::is_date;
+
+pub const MAX_FIELD_LENGTH: usize = 256;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct DatasetMetadata {
+    /// Who answers questions about the data, such as a name and email
+    pub steward_contact: Option<String>,
+    pub collection_start: Option<String>,
+    pub collection_end: Option<String>,
+    pub license: Option<String>,
+    /// Data use agreement the dataset is shared under
+    pub data_use_agreement: Option<String>,
+    pub geography: Option<String>,
+}
+
+pub fn validate(metadata: &DatasetMetadata) -> Result<(), String> {
+    let fields = [
+        ("steward contact", &metadata.steward_contact),
+        ("collection start", &metadata.collection_start),
+        ("collection end", &metadata.collection_end),
+        ("license", &metadata.license),
+        ("data use agreement", &metadata.data_use_agreement),
+        ("geography", &metadata.geography),
+    ];
+    for (field, value) in fields {
+        match value.as_deref().map(str::trim) {
+            Some("") => return Err(format!("The {} cannot be blank", field)),
+            Some(value) if value.chars().count() > MAX_FIELD_LENGTH => {
+                return Err(format!("The {} is longer than {} characters", field, MAX_FIELD_LENGTH));
+            }
+            _ => {}
+        }
+    }
+    for (field, date) in [("collection start", &metadata.collection_start), ("collection end", &metadata.collection_end)] {
+        if date.as_deref().is_some_and(|date| !is_date(date.trim())) {
+            return Err(format!("The {} must be a date written YYYY-MM-DD", field));
+        }
+    }
+    // Dates written YYYY-MM-DD order like strings
+    if let (Some(start), Some(end)) = (&metadata.collection_start, &metadata.collection_end) {
+        if start.trim() > end.trim() {
+            return Err("The collection period ends before it starts".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "metadata_test.rs"]
+mod tests;
@@ -0,0 +1,33 @@
+use super::*;
+
+fn metadata() -> DatasetMetadata {
+    DatasetMetadata {
+        steward_contact: Some("Data Office <data@example.org>".to_string()),
+        collection_start: Some("2023-01-01".to_string()),
+        collection_end: Some("2023-12-31".to_string()),
+        license: Some("CC BY-NC 4.0".to_string()),
+        data_use_agreement: Some("DUA-2024-017".to_string()),
+        geography: Some("Massachusetts, US".to_string()),
+    }
+}
+
+#[test]
+fn accepts_complete_and_empty_metadata() {
+    assert!(validate(&metadata()).is_ok());
+    assert!(validate(&DatasetMetadata::default()).is_ok());
+}
+
+#[test]
+fn rejects_bad_dates_and_periods() {
+    let invalid = DatasetMetadata { collection_start: Some("2023-02-30".to_string()), ..metadata() };
+    assert!(validate(&invalid).is_err());
+    let reversed = DatasetMetadata { collection_start: Some("2024-01-01".to_string()), ..metadata() };
+    assert!(validate(&reversed).unwrap_err().contains("ends before it starts"));
+}
+
+#[test]
+fn rejects_blank_and_oversized_fields() {
+    assert!(validate(&DatasetMetadata { license: Some("  ".to_string()), ..metadata() }).is_err());
+    let long = "x".repeat(MAX_FIELD_LENGTH + 1);
+    assert!(validate(&DatasetMetadata { geography: Some(long), ..metadata() }).is_err());
+}
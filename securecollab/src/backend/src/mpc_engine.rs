@@ -1,12 +1,26 @@
-use candid::CandidType;
+use candid::{CandidType, Deserialize};
+use futures::stream::{self, StreamExt};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::cell::RefCell;
 use ic_cdk::api::time;
 use crate::vetkey_manager::EncryptedData;
 use crate::agent_registry;
+use crate::progress::{self, AgentState, ComputationPhase, ProgressEvent};
 use crate::{AgentTeam, MPCAgent};
+use crate::backup;
 
-#[derive(CandidType, Clone, Debug)]
+pub mod checkpoint;
+pub mod federated;
+pub mod join;
+pub mod linkage;
+pub mod paillier;
+pub mod psi;
+pub mod secure_aggregation;
+pub mod secure_sum;
+pub mod time_series;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct SecureComputationTask {
     pub id: String,
     pub team_id: String,
@@ -16,7 +30,7 @@ pub struct SecureComputationTask {
     pub created_at: u64,
 }
 
-#[derive(CandidType, Clone, Debug)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct AgentComputationResult {
     pub agent_id: String,
     pub partial_result: Vec<u8>,
@@ -24,10 +38,35 @@ pub struct AgentComputationResult {
     pub timestamp: u64,
 }
 
+/// Substitutes tried for a failed team member before the computation fails
+pub const MAX_SUBSTITUTES_PER_AGENT: usize = 2;
+/// Team members whose computations run at the same time
+pub const MAX_CONCURRENT_AGENTS: usize = 4;
+/// Agent time-series analyses are routed to
+pub const TIME_SERIES_AGENT: &str = "data_science_agent";
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentTiming {
+    pub slot: String,
+    pub agent_id: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub resumed: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentSubstitution {
+    pub original_agent_id: String,
+    pub substitute_agent_id: String,
+    pub reason: String,
+}
+
 // Store active agent teams and computations
 thread_local! {
     static AGENT_TEAMS: RefCell<HashMap<String, AgentTeam>> = RefCell::new(HashMap::new());
     static ACTIVE_COMPUTATIONS: RefCell<HashMap<String, SecureComputationTask>> = RefCell::new(HashMap::new());
+    // Partial results of unfinished computations, keyed by computation and team slot
+    static PARTIAL_RESULTS: RefCell<HashMap<String, HashMap<String, AgentComputationResult>>> = RefCell::new(HashMap::new());
 }
 
 /// Create a secure agent team with VetKD-derived identities
@@ -77,48 +116,169 @@ pub fn get_team_info(team_id: String) -> Result<AgentTeam, String> {
     })
 }
 
-/// Execute secure multi-party computation
+/// Execute secure multi-party computation, reporting progress under `progress_id`
 pub async fn execute_secure_mpc_computation(
     team: &AgentTeam,
     computation_request: &str,
-    _data_sources: &[String]
+    data_sources: &[String],
+    progress_id: &str,
+) -> Result<crate::ComputationResult, String> {
+    let result = run_computation(team, computation_request, data_sources, progress_id).await;
+    match &result {
+        Ok(_) => progress::report(progress_id, ProgressEvent::Phase(ComputationPhase::Completed), time()),
+        Err(e) => progress::report(progress_id, ProgressEvent::Failed(e.clone()), time()),
+    }
+    result
+}
+
+async fn run_computation(
+    team: &AgentTeam,
+    computation_request: &str,
+    _data_sources: &[String],
+    progress_id: &str,
 ) -> Result<crate::ComputationResult, String> {
-    let computation_id = format!("comp_{}", time());
+    // Re-running the same request on the same team resumes from stored partial results
+    let computation_id = computation_id(&team.id, computation_request);
     
-    // Step 1: Distribute computation task to agents
-    let mut agent_results = Vec::new();
+    progress::report(progress_id, ProgressEvent::Phase(ComputationPhase::Analyzing), time());
+    for slot in &team.agent_ids {
+        report_agent(progress_id, slot, AgentState::Pending, None);
+    }
     
-    for agent_id in &team.agent_ids {
-        let agent = agent_registry::get_agent_by_id(agent_id)
-            .ok_or_else(|| format!("Agent {} not found", agent_id))?;
-        
-        // Each agent processes their assigned data partition
-        let partial_result = execute_agent_computation(
-            &agent,
-            computation_request,
-        ).await?;
-        
-        agent_results.push(partial_result);
+    // Step 1: Distribute computation task to agents concurrently, failing over to substitutes.
+    // Every slot runs to completion so successful partial results are kept even if another fails.
+    let outcomes: Vec<_> = stream::iter(team.agent_ids.iter())
+        .map(|slot| {
+            let computation_id = computation_id.clone();
+            async move {
+                let started_at = time();
+                if let Some(done) = partial_result(&computation_id, slot) {
+                    report_agent(progress_id, slot, AgentState::Completed, Some("Resumed from partial result".to_string()));
+                    let timing = AgentTiming { slot: slot.clone(), agent_id: done.agent_id.clone(), started_at, finished_at: started_at, resumed: true };
+                    return Ok((done, vec![], timing));
+                }
+                
+                report_agent(progress_id, slot, AgentState::Running, None);
+                let (partial, slot_substitutions) = match run_slot(&team.agent_ids, slot, computation_request, progress_id).await {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        report_agent(progress_id, slot, AgentState::Failed, Some(e.clone()));
+                        return Err(e);
+                    }
+                };
+                let detail = (partial.agent_id != *slot).then(|| format!("Completed by substitute {}", partial.agent_id));
+                report_agent(progress_id, slot, AgentState::Completed, detail);
+                PARTIAL_RESULTS.with(|partials| {
+                    partials.borrow_mut().entry(computation_id).or_default().insert(slot.clone(), partial.clone());
+                });
+                let timing = AgentTiming { slot: slot.clone(), agent_id: partial.agent_id.clone(), started_at, finished_at: time(), resumed: false };
+                Ok::<_, String>((partial, slot_substitutions, timing))
+            }
+        })
+        .buffered(MAX_CONCURRENT_AGENTS)
+        .collect()
+        .await;
+    
+    let mut agent_results = Vec::new();
+    let mut substitutions = Vec::new();
+    let mut agent_timings = Vec::new();
+    for outcome in outcomes {
+        let (partial, slot_substitutions, timing) = outcome?;
+        agent_results.push(partial);
+        substitutions.extend(slot_substitutions);
+        agent_timings.push(timing);
     }
     
     // Step 2: Secure aggregation of partial results
+    progress::report(progress_id, ProgressEvent::Phase(ComputationPhase::Aggregating), time());
     let aggregated_result = secure_aggregate_results(&agent_results).await?;
     
     // Step 3: Generate privacy proof
+    progress::report(progress_id, ProgressEvent::Phase(ComputationPhase::Proving), time());
     let privacy_proof = generate_computation_proof(&computation_id, &team.id).await?;
+    PARTIAL_RESULTS.with(|partials| partials.borrow_mut().remove(&computation_id));
     
     Ok(crate::ComputationResult {
         insights: aggregated_result,
         privacy_proof,
         timestamp: time(),
+        substitutions,
+        agent_timings,
+    })
+}
+
+fn report_agent(progress_id: &str, slot: &str, state: AgentState, detail: Option<String>) {
+    progress::report(progress_id, ProgressEvent::Agent { agent_id: slot.to_string(), state, detail }, time());
+}
+
+/// Stable id for a request run by a team
+fn computation_id(team_id: &str, computation_request: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", team_id, computation_request).as_bytes());
+    format!("comp_{}", hex::encode(&digest[..8]))
+}
+
+fn partial_result(computation_id: &str, slot: &str) -> Option<AgentComputationResult> {
+    PARTIAL_RESULTS.with(|partials| {
+        partials.borrow().get(computation_id).and_then(|results| results.get(slot).cloned())
     })
 }
 
+/// Run one team slot, trying substitutes of equivalent capability from
+/// outside the team when its agent fails
+async fn run_slot(
+    team: &[String],
+    slot: &str,
+    computation_request: &str,
+    progress_id: &str,
+) -> Result<(AgentComputationResult, Vec<AgentSubstitution>), String> {
+    let original = agent_registry::get_agent_by_id(slot)
+        .ok_or_else(|| format!("Agent {} not found", slot))?;
+    
+    let mut error = match execute_agent_computation(&original, computation_request).await {
+        Ok(result) => return Ok((result, vec![])),
+        Err(e) => e,
+    };
+    
+    let mut substitutions = Vec::new();
+    let candidates = substitutes(&original, &agent_registry::list_all_agents(), team);
+    for substitute in candidates.iter().take(MAX_SUBSTITUTES_PER_AGENT) {
+        substitutions.push(AgentSubstitution {
+            original_agent_id: slot.to_string(),
+            substitute_agent_id: substitute.id.clone(),
+            reason: error.clone(),
+        });
+        report_agent(progress_id, slot, AgentState::Substituted,
+            Some(format!("Failed over to {}: {}", substitute.id, error)));
+        match execute_agent_computation(substitute, computation_request).await {
+            Ok(result) => return Ok((result, substitutions)),
+            Err(e) => error = e,
+        }
+    }
+    Err(format!("Agent {} failed and no substitute succeeded: {}", slot, error))
+}
+
+/// Agents outside the team that cover all of `failed`'s capabilities, most reputable first
+fn substitutes(failed: &MPCAgent, agents: &[MPCAgent], team: &[String]) -> Vec<MPCAgent> {
+    let mut candidates: Vec<MPCAgent> = agents.iter()
+        .filter(|a| !team.contains(&a.id))
+        .filter(|a| failed.capabilities.iter().all(|c| a.capabilities.contains(c)))
+        .cloned()
+        .collect();
+    crate::capabilities::sort_results(&mut candidates);
+    candidates
+}
+
 /// Execute computation on a single agent
 async fn execute_agent_computation(
     agent: &MPCAgent,
     computation_request: &str,
 ) -> Result<AgentComputationResult, String> {
+    // Agents that stopped sending heartbeats are treated as failed
+    let liveness = agent_registry::liveness(&agent.id, time());
+    if liveness.last_seen.is_some() && !liveness.live {
+        return Err(format!("Agent {} is not responding", agent.id));
+    }
+    
     // Create specialized prompt based on agent capabilities
     let specialized_prompt = create_agent_prompt(agent, computation_request);
     
@@ -196,7 +356,34 @@ pub async fn run_secure_computation(
     let team = get_team_info(team_id)?;
     
     // Execute the secure MPC computation
-    execute_secure_mpc_computation(&team, &computation_request, &[]).await
+    progress::begin(&team.id, time());
+    execute_secure_mpc_computation(&team, &computation_request, &[], &team.id).await
+}
+
+/// Analyze a time series over decrypted datasets and have the data science
+/// agent, or a substitute, interpret the per-period aggregates
+pub async fn run_time_series(
+    run_id: &str,
+    request: &time_series::TimeSeriesRequest,
+    datasets: &[String],
+) -> Result<time_series::TimeSeriesAnalysis, String> {
+    progress::report(run_id, ProgressEvent::Phase(ComputationPhase::Analyzing), time());
+    let min_cohort = crate::disclosure_control::policy().min_cohort_size;
+    let mut analysis = time_series::analyze(run_id, request, datasets, min_cohort)?;
+    
+    report_agent(run_id, TIME_SERIES_AGENT, AgentState::Running, None);
+    let prompt = time_series::prompt(request, &analysis);
+    match run_slot(&[], TIME_SERIES_AGENT, &prompt, run_id).await {
+        Ok((result, _)) => {
+            report_agent(run_id, TIME_SERIES_AGENT, AgentState::Completed, None);
+            analysis.agent_id = Some(result.agent_id);
+            analysis.insights = Some(String::from_utf8_lossy(&result.partial_result).to_string());
+        }
+        // The series stands on its own when no agent can interpret it
+        Err(e) => report_agent(run_id, TIME_SERIES_AGENT, AgentState::Failed, Some(e)),
+    }
+    progress::report(run_id, ProgressEvent::Phase(ComputationPhase::Completed), time());
+    Ok(analysis)
 }
 
 /// Generate team ID
@@ -220,3 +407,19 @@ async fn generate_computation_proof(computation_id: &str, team_id: &str) -> Resu
         (computation_id.len() + team_id.len()) * 31337
     ))
 }
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&AGENT_TEAMS),
+        backup::cloned(&ACTIVE_COMPUTATIONS),
+        backup::cloned(&PARTIAL_RESULTS),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (agent_teams, active_computations, partial_results) = backup::decode(bytes)?;
+    backup::replace(&AGENT_TEAMS, agent_teams);
+    backup::replace(&ACTIVE_COMPUTATIONS, active_computations);
+    backup::replace(&PARTIAL_RESULTS, partial_results);
+    Ok(())
+}
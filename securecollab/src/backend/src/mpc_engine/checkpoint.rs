@@ -0,0 +1,173 @@
+//! Checkpointed computations over large datasets
+//!
+//! A job walks its datasets in fixed-size slices. After each slice the
+//! cursor, byte and row counts and a running digest of the plaintext are
+//! saved, so processing can stop before the per-message instruction limit
+//! and continue in a later message exactly where it left off. Once every
+//! slice is processed the team computation runs over the aggregate.
+
+use candid::{CandidType, Deserialize, Principal};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::ops::Range;
+use crate::backup;
+
+pub const DEFAULT_SLICE_BYTES: u64 = 64 * 1024;
+pub const MIN_SLICE_BYTES: u64 = 1024;
+pub const MAX_SLICE_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CheckpointJob {
+    pub job_id: String,
+    pub team_id: String,
+    pub requester: Principal,
+    pub computation_request: String,
+    pub dataset_ids: Vec<String>,
+    pub slice_bytes: u64,
+    // Cursor: dataset being processed and the byte offset within it
+    pub dataset_index: u32,
+    pub offset: u64,
+    pub processed_bytes: u64,
+    pub total_bytes: u64,
+    pub percent_complete: u8,
+    pub rows_processed: u64,
+    pub slices_processed: u64,
+    pub steps: u32,
+    pub running_digest: String,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub result: Option<crate::ComputationResult>,
+    pub started_at: u64,
+    pub updated_at: u64,
+}
+
+thread_local! {
+    static JOBS: RefCell<HashMap<String, CheckpointJob>> = RefCell::new(HashMap::new());
+}
+
+impl CheckpointJob {
+    pub fn new(
+        job_id: String,
+        team_id: String,
+        requester: Principal,
+        computation_request: String,
+        datasets: Vec<(String, u64)>,
+        slice_bytes: u64,
+        now: u64,
+    ) -> Self {
+        CheckpointJob {
+            job_id,
+            team_id,
+            requester,
+            computation_request,
+            total_bytes: datasets.iter().map(|(_, len)| len).sum(),
+            dataset_ids: datasets.into_iter().map(|(id, _)| id).collect(),
+            slice_bytes: slice_bytes.clamp(MIN_SLICE_BYTES, MAX_SLICE_BYTES),
+            dataset_index: 0,
+            offset: 0,
+            processed_bytes: 0,
+            percent_complete: 0,
+            rows_processed: 0,
+            slices_processed: 0,
+            steps: 0,
+            running_digest: hex::encode([0u8; 32]),
+            status: JobStatus::Running,
+            error: None,
+            result: None,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Dataset the cursor points at, or `None` once every dataset is processed
+    pub fn current_dataset(&self) -> Option<&str> {
+        self.dataset_ids.get(self.dataset_index as usize).map(|id| id.as_str())
+    }
+
+    /// Byte range of the next slice of a dataset of `len` bytes
+    pub fn next_slice(&self, len: u64) -> Option<Range<usize>> {
+        if self.offset >= len {
+            return None;
+        }
+        let end = (self.offset + self.slice_bytes).min(len);
+        Some(self.offset as usize..end as usize)
+    }
+
+    /// Fold a decrypted slice into the job state and advance the cursor
+    pub fn absorb(&mut self, plaintext: &[u8], now: u64) {
+        let previous = hex::decode(&self.running_digest).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(&previous);
+        hasher.update(plaintext);
+        self.running_digest = hex::encode(hasher.finalize());
+
+        self.rows_processed += plaintext.iter().filter(|b| **b == b'\n').count() as u64;
+        self.offset += plaintext.len() as u64;
+        self.processed_bytes += plaintext.len() as u64;
+        self.slices_processed += 1;
+        self.percent_complete = self.progress();
+        self.updated_at = now;
+    }
+
+    /// Move the cursor to the start of the next dataset
+    pub fn next_dataset(&mut self) {
+        self.dataset_index += 1;
+        self.offset = 0;
+    }
+
+    pub fn progress(&self) -> u8 {
+        if self.total_bytes == 0 {
+            return 0;
+        }
+        (self.processed_bytes * 100 / self.total_bytes).min(100) as u8
+    }
+
+    /// Aggregate handed to the agent team once all slices are processed
+    pub fn summary(&self) -> String {
+        format!(
+            "Processed {} datasets in {} slices: {} bytes, {} rows, digest {}",
+            self.dataset_ids.len(), self.slices_processed, self.processed_bytes, self.rows_processed, self.running_digest
+        )
+    }
+}
+
+/// Decrypt bytes at `offset` of a ciphertext produced by the repeating-key cipher
+pub fn decrypt_slice(ciphertext: &[u8], key: &[u8], offset: usize) -> Vec<u8> {
+    if key.is_empty() {
+        return ciphertext.to_vec();
+    }
+    ciphertext.iter()
+        .zip(key.iter().cycle().skip(offset % key.len()))
+        .map(|(c, k)| c ^ k)
+        .collect()
+}
+
+pub fn save(job: CheckpointJob) {
+    JOBS.with(|jobs| jobs.borrow_mut().insert(job.job_id.clone(), job));
+}
+
+pub fn get(job_id: &str) -> Option<CheckpointJob> {
+    JOBS.with(|jobs| jobs.borrow().get(job_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&JOBS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let jobs = backup::decode(bytes)?;
+    backup::replace(&JOBS, jobs);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "checkpoint_test.rs"]
+mod tests;
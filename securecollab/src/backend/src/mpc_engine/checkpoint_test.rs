@@ -0,0 +1,63 @@
+use super::*;
+
+fn xor(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter().zip(key.iter().cycle()).map(|(d, k)| d ^ k).collect()
+}
+
+#[test]
+fn slices_decrypt_to_the_whole_plaintext() {
+    let plaintext: Vec<u8> = b"a,b\n1,2\n3,4\n5,6\n".repeat(200);
+    let key = b"seven b";
+    let ciphertext = xor(&plaintext, key);
+
+    let mut job = CheckpointJob::new(
+        "job_1".to_string(), "team_1".to_string(), Principal::anonymous(), "request".to_string(),
+        vec![("dataset_1".to_string(), ciphertext.len() as u64)], MIN_SLICE_BYTES, 0,
+    );
+    let mut recovered = Vec::new();
+    while let Some(range) = job.next_slice(ciphertext.len() as u64) {
+        let start = range.start;
+        let slice = decrypt_slice(&ciphertext[range], key, start);
+        job.absorb(&slice, 1);
+        recovered.extend(slice);
+    }
+
+    assert_eq!(recovered, plaintext);
+    assert_eq!(job.rows_processed, 800);
+    assert_eq!(job.slices_processed, 4);
+    assert_eq!(job.percent_complete, 100);
+}
+
+#[test]
+fn cursor_moves_across_datasets() {
+    let mut job = CheckpointJob::new(
+        "job_2".to_string(), "team_1".to_string(), Principal::anonymous(), "request".to_string(),
+        vec![("d1".to_string(), 10), ("d2".to_string(), 10)], 1, 0,
+    );
+    // Slice size is clamped to the minimum
+    assert_eq!(job.slice_bytes, MIN_SLICE_BYTES);
+    assert_eq!(job.current_dataset(), Some("d1"));
+    job.absorb(&[0u8; 10], 1);
+    assert!(job.next_slice(10).is_none());
+    assert_eq!(job.percent_complete, 50);
+
+    job.next_dataset();
+    assert_eq!(job.current_dataset(), Some("d2"));
+    assert_eq!(job.next_slice(10), Some(0..10));
+    job.absorb(&[0u8; 10], 2);
+    job.next_dataset();
+    assert_eq!(job.current_dataset(), None);
+}
+
+#[test]
+fn digest_depends_on_slice_contents() {
+    let new_job = || CheckpointJob::new(
+        "job_3".to_string(), "team_1".to_string(), Principal::anonymous(), "request".to_string(),
+        vec![("d1".to_string(), 4)], MIN_SLICE_BYTES, 0,
+    );
+    let mut a = new_job();
+    let mut b = new_job();
+    a.absorb(b"1,2\n", 1);
+    b.absorb(b"1,3\n", 1);
+    assert_ne!(a.running_digest, b.running_digest);
+}
@@ -0,0 +1,158 @@
+//! Federated model training with secure aggregation
+//!
+//! Each participant computes a model update locally (or through its agent)
+//! and masks it for secure aggregation, with the model id as the session and
+//! the training round as the round. Once every participant of a round has
+//! submitted, the masks cancel in the sum and the canister applies the
+//! average update to the model. Only the running sum is kept, never an
+//! individual update.
+//!
+//! Every completed round registers a new model version.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use super::secure_aggregation::{self, Aggregation};
+use crate::backup;
+
+/// Fewest participants, so no one can recover another's update from the average
+pub const MIN_PARTICIPANTS: usize = 3;
+pub const MAX_DIMENSION: usize = 100_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FederatedModel {
+    pub id: String,
+    pub name: String,
+    pub owner: Principal,
+    pub participants: Vec<Principal>,
+    pub dimension: u64,
+    /// Round currently collecting updates
+    pub round: u64,
+    /// Participants who submitted to the current round
+    pub submitted: Vec<Principal>,
+    pub latest_version: u64,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelVersion {
+    pub model_id: String,
+    pub version: u64,
+    pub weights: Vec<f64>,
+    /// Participants whose updates produced this version; zero for the initial model
+    pub contributors: u64,
+    pub created_at: u64,
+}
+
+thread_local! {
+    static MODELS: RefCell<HashMap<String, FederatedModel>> = RefCell::new(HashMap::new());
+    // The current round's masked updates
+    static AGGREGATES: RefCell<HashMap<String, Aggregation>> = RefCell::new(HashMap::new());
+    static VERSIONS: RefCell<HashMap<String, Vec<ModelVersion>>> = RefCell::new(HashMap::new());
+}
+
+pub fn create(
+    id: String,
+    name: String,
+    owner: Principal,
+    participants: Vec<Principal>,
+    initial_weights: Vec<f64>,
+    now: u64,
+) -> Result<FederatedModel, String> {
+    if participants.len() < MIN_PARTICIPANTS {
+        return Err(format!("Federated training needs at least {} participants", MIN_PARTICIPANTS));
+    }
+    if initial_weights.is_empty() || initial_weights.len() > MAX_DIMENSION {
+        return Err(format!("Models must have between 1 and {} weights", MAX_DIMENSION));
+    }
+    if initial_weights.iter().any(|w| !w.is_finite()) {
+        return Err("Initial weights must be finite".to_string());
+    }
+    let aggregation = Aggregation::new(participants.clone(), initial_weights.len())?;
+
+    let model = FederatedModel {
+        id: id.clone(),
+        name,
+        owner,
+        participants,
+        dimension: initial_weights.len() as u64,
+        round: 1,
+        submitted: vec![],
+        latest_version: 0,
+        created_at: now,
+    };
+    let initial = ModelVersion { model_id: id.clone(), version: 0, weights: initial_weights, contributors: 0, created_at: now };
+    MODELS.with(|m| m.borrow_mut().insert(id.clone(), model.clone()));
+    AGGREGATES.with(|a| a.borrow_mut().insert(id.clone(), aggregation));
+    VERSIONS.with(|v| v.borrow_mut().insert(id, vec![initial]));
+    Ok(model)
+}
+
+pub fn get(id: &str) -> Option<FederatedModel> {
+    MODELS.with(|m| m.borrow().get(id).cloned())
+}
+
+pub fn versions(id: &str) -> Vec<ModelVersion> {
+    VERSIONS.with(|v| v.borrow().get(id).cloned().unwrap_or_default())
+}
+
+/// Add a participant's masked update to `round`; returns the new version
+/// once the round is complete
+pub fn submit(id: &str, participant: Principal, round: u64, masked_update: Vec<u64>, now: u64) -> Result<Option<ModelVersion>, String> {
+    let mut model = get(id).ok_or_else(|| format!("Model {} not found", id))?;
+    if round != model.round {
+        return Err(format!("The model is collecting updates for round {}", model.round));
+    }
+    let (submitted, total) = AGGREGATES.with(|a| {
+        let mut aggregates = a.borrow_mut();
+        let aggregation = aggregates.get_mut(id).ok_or_else(|| format!("Model {} not found", id))?;
+        aggregation.add(participant, &masked_update)?;
+        let result = (aggregation.submitted().to_vec(), aggregation.total().map(<[u64]>::to_vec));
+        if result.1.is_some() {
+            *aggregation = Aggregation::new(model.participants.clone(), masked_update.len())?;
+        }
+        Ok::<_, String>(result)
+    })?;
+    model.submitted = submitted;
+
+    let version = if let Some(total) = total {
+        // The masks cancelled; apply the average update
+        let count = model.participants.len() as f64;
+        let previous = versions(id).last().map(|v| v.weights.clone()).unwrap_or_default();
+        let weights = previous.iter().zip(&total)
+            .map(|(weight, total)| weight + secure_aggregation::from_fixed(*total) / count)
+            .collect();
+        let version = ModelVersion {
+            model_id: id.to_string(),
+            version: model.latest_version + 1,
+            weights,
+            contributors: model.participants.len() as u64,
+            created_at: now,
+        };
+        VERSIONS.with(|v| v.borrow_mut().entry(id.to_string()).or_default().push(version.clone()));
+        model.latest_version = version.version;
+        model.round += 1;
+        model.submitted.clear();
+        Some(version)
+    } else {
+        None
+    };
+    MODELS.with(|m| m.borrow_mut().insert(id.to_string(), model));
+    Ok(version)
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&MODELS), backup::cloned(&AGGREGATES), backup::cloned(&VERSIONS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (models, aggregates, versions) = backup::decode(bytes)?;
+    backup::replace(&MODELS, models);
+    backup::replace(&AGGREGATES, aggregates);
+    backup::replace(&VERSIONS, versions);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "federated_test.rs"]
+mod tests;
@@ -0,0 +1,62 @@
+use super::*;
+use crate::mpc_engine::secure_sum::SCALE;
+
+fn party(id: u8) -> Principal {
+    Principal::from_slice(&[id])
+}
+
+/// Fixed-point update of party `me` with pairwise masks that cancel across
+/// the `parties` participants
+fn masked(update: &[f64], me: u64, parties: u64) -> Vec<u64> {
+    update.iter().enumerate().map(|(i, value)| {
+        let mut masked = (value * SCALE).round() as i64 as u64;
+        for other in (0..parties).filter(|&o| o != me) {
+            let (low, high) = (me.min(other), me.max(other));
+            let mask = (low * 1_000_003 + high * 7_919 + i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            masked = if me == low { masked.wrapping_add(mask) } else { masked.wrapping_sub(mask) };
+        }
+        masked
+    }).collect()
+}
+
+fn model(id: &str) -> FederatedModel {
+    create(id.to_string(), "risk".to_string(), party(9), vec![party(1), party(2), party(3)], vec![1.0, -1.0], 1).unwrap()
+}
+
+#[test]
+fn masked_updates_average_into_a_new_version() {
+    model("fl_1");
+    assert_eq!(submit("fl_1", party(1), 1, masked(&[0.3, 0.6], 0, 3), 2).unwrap(), None);
+    assert_eq!(submit("fl_1", party(2), 1, masked(&[-0.3, 0.9], 1, 3), 2).unwrap(), None);
+    let version = submit("fl_1", party(3), 1, masked(&[0.6, -0.3], 2, 3), 3).unwrap().unwrap();
+
+    assert_eq!(version.version, 1);
+    assert!((version.weights[0] - 1.2).abs() < 1e-9);
+    assert!((version.weights[1] - -0.6).abs() < 1e-9);
+    assert_eq!(versions("fl_1").len(), 2);
+
+    let model = get("fl_1").unwrap();
+    assert_eq!((model.round, model.latest_version), (2, 1));
+    assert!(model.submitted.is_empty());
+}
+
+#[test]
+fn rejects_outsiders_repeats_and_wrong_rounds() {
+    model("fl_2");
+    assert!(submit("fl_2", party(7), 1, vec![0, 0], 2).is_err());
+    assert!(submit("fl_2", party(1), 2, vec![0, 0], 2).is_err());
+    assert!(submit("fl_2", party(1), 1, vec![0], 2).is_err());
+    submit("fl_2", party(1), 1, vec![0, 0], 2).unwrap();
+    assert!(submit("fl_2", party(1), 1, vec![0, 0], 2).is_err());
+}
+
+#[test]
+fn models_need_enough_distinct_participants() {
+    let create_with = |participants: Vec<Principal>, weights: Vec<f64>| {
+        create("fl_3".to_string(), "m".to_string(), party(9), participants, weights, 1)
+    };
+    assert!(create_with(vec![party(1), party(2)], vec![0.0]).is_err());
+    assert!(create_with(vec![party(1), party(2), party(1)], vec![0.0]).is_err());
+    assert!(create_with(vec![party(1), party(2), party(3)], vec![]).is_err());
+    assert!(create_with(vec![party(1), party(2), party(3)], vec![f64::NAN]).is_err());
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+const HOSPITAL: &str = "patient,age,drug\np1,34,A\np2,51,B\n\np3,47,A\n";
+const INSURER: &str = "claims,patient,age\n3,p3,47\n1,p9,60\n2,p1,35\n";
+
+#[test]
+fn joins_only_paired_rows() {
+    let view = join(HOSPITAL, INSURER, "patient", &[(0, 2), (2, 0)]).unwrap();
+    assert_eq!(view.csv, "patient,age,drug,claims,age_b\np1,34,A,2,35\np3,47,A,3,47");
+    assert_eq!(view.rows, 2);
+    assert_eq!(view.mismatched_keys, 0);
+}
+
+#[test]
+fn drops_pairs_whose_keys_differ() {
+    let view = join(HOSPITAL, INSURER, "patient", &[(0, 2), (1, 1)]).unwrap();
+    assert_eq!(view.rows, 1);
+    assert_eq!(view.mismatched_keys, 1);
+}
+
+#[test]
+fn rejects_unknown_keys_and_rows() {
+    assert!(join(HOSPITAL, INSURER, "ssn", &[]).is_err());
+    assert!(join(HOSPITAL, INSURER, "patient", &[(5, 0)]).is_err());
+    assert!(join("", INSURER, "patient", &[]).is_err());
+}
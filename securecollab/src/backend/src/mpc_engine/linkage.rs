@@ -0,0 +1,208 @@
+//! Privacy-preserving record linkage over Bloom filter encodings
+//!
+//! Parties encode each record as a cryptographic long-term key (CLK): the
+//! character bigrams of its identifying fields, hashed with a secret the
+//! parties agree on outside the canister, set bits in a fixed-length Bloom
+//! filter. Similar records give similar filters even with typos or
+//! formatting differences, so records are linked when the Dice coefficient of
+//! their filters reaches a threshold, without the canister seeing a field.
+//!
+//! Pairs are linked one-to-one, most similar first. Quality metrics describe
+//! the linkage without identifying records, so approvers of a joint analysis
+//! can judge it before the linked data is used.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+/// Most records one dataset may encode; linkage compares every pair
+pub const MAX_RECORDS: usize = 2_000;
+pub const MIN_ENCODING_BYTES: usize = 64;
+pub const MAX_ENCODING_BYTES: usize = 1_024;
+/// Matches this close to the threshold count as uncertain
+pub const UNCERTAIN_MARGIN: f64 = 0.05;
+/// Bins of the best-match similarity histogram over [0, 1]
+pub const HISTOGRAM_BINS: usize = 10;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordMatch {
+    pub left_index: u64,
+    pub right_index: u64,
+    pub similarity: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LinkageQuality {
+    pub left_records: u64,
+    pub right_records: u64,
+    pub matches: u64,
+    /// Matches over the smaller dataset
+    pub match_rate: f64,
+    pub mean_similarity: f64,
+    /// Pairs at or above the threshold, before one-to-one assignment
+    pub candidate_pairs: u64,
+    /// Records with more than one candidate partner
+    pub ambiguous_records: u64,
+    /// Matches within `UNCERTAIN_MARGIN` of the threshold
+    pub uncertain_matches: u64,
+    /// Best similarity of each left record, binned
+    pub similarity_histogram: Vec<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LinkageRun {
+    pub id: String,
+    pub requester: Principal,
+    pub left_dataset: String,
+    pub right_dataset: String,
+    pub threshold: f64,
+    /// Computation whose approvers may review the quality
+    pub computation_id: Option<String>,
+    pub matches: Vec<RecordMatch>,
+    pub quality: LinkageQuality,
+    pub created_at: u64,
+}
+
+thread_local! {
+    static ENCODINGS: RefCell<HashMap<String, Vec<Vec<u8>>>> = RefCell::new(HashMap::new());
+    static RUNS: RefCell<HashMap<String, LinkageRun>> = RefCell::new(HashMap::new());
+}
+
+pub fn submit(dataset_id: &str, encodings: Vec<Vec<u8>>) -> Result<(), String> {
+    if encodings.is_empty() || encodings.len() > MAX_RECORDS {
+        return Err(format!("Encode between 1 and {} records", MAX_RECORDS));
+    }
+    let len = encodings[0].len();
+    if !(MIN_ENCODING_BYTES..=MAX_ENCODING_BYTES).contains(&len) {
+        return Err(format!("Encodings must be {} to {} bytes long", MIN_ENCODING_BYTES, MAX_ENCODING_BYTES));
+    }
+    if encodings.iter().any(|e| e.len() != len) {
+        return Err("Every record must be encoded with the same length".to_string());
+    }
+    ENCODINGS.with(|e| e.borrow_mut().insert(dataset_id.to_string(), encodings));
+    Ok(())
+}
+
+/// Drop a dataset's encodings once its records change
+pub fn invalidate(dataset_id: &str) {
+    ENCODINGS.with(|e| e.borrow_mut().remove(dataset_id));
+}
+
+fn ones(bits: &[u8]) -> u32 {
+    bits.iter().map(|b| b.count_ones()).sum()
+}
+
+/// Dice coefficient of two Bloom filters
+fn dice(a: &[u8], b: &[u8]) -> f64 {
+    let total = ones(a) + ones(b);
+    if total == 0 {
+        return 0.0;
+    }
+    let common: u32 = a.iter().zip(b).map(|(x, y)| (x & y).count_ones()).sum();
+    2.0 * common as f64 / total as f64
+}
+
+fn encodings(dataset_id: &str) -> Result<Vec<Vec<u8>>, String> {
+    ENCODINGS.with(|e| e.borrow().get(dataset_id).cloned())
+        .ok_or_else(|| format!("Dataset {} has no linkage encodings", dataset_id))
+}
+
+/// Link the records of two datasets whose encodings reach `threshold`
+pub fn link(
+    id: String,
+    requester: Principal,
+    (left_dataset, right_dataset): (String, String),
+    threshold: f64,
+    computation_id: Option<String>,
+    now: u64,
+) -> Result<LinkageRun, String> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err("The similarity threshold must be above 0 and at most 1".to_string());
+    }
+    if left_dataset == right_dataset {
+        return Err("A dataset cannot be linked with itself".to_string());
+    }
+    let (left, right) = (encodings(&left_dataset)?, encodings(&right_dataset)?);
+    if left[0].len() != right[0].len() {
+        return Err("Both datasets must use the same encoding length".to_string());
+    }
+
+    let mut best = vec![0.0f64; left.len()];
+    let mut candidates = Vec::new();
+    let (mut left_candidates, mut right_candidates) = (vec![0u32; left.len()], vec![0u32; right.len()]);
+    for (i, a) in left.iter().enumerate() {
+        for (j, b) in right.iter().enumerate() {
+            let similarity = dice(a, b);
+            best[i] = best[i].max(similarity);
+            if similarity >= threshold {
+                candidates.push((i, j, similarity));
+                left_candidates[i] += 1;
+                right_candidates[j] += 1;
+            }
+        }
+    }
+
+    // Most similar pairs first, each record linked at most once
+    candidates.sort_by(|x, y| y.2.total_cmp(&x.2).then(x.0.cmp(&y.0)).then(x.1.cmp(&y.1)));
+    let (mut left_used, mut right_used) = (vec![false; left.len()], vec![false; right.len()]);
+    let mut matches = Vec::new();
+    for &(i, j, similarity) in &candidates {
+        if !left_used[i] && !right_used[j] {
+            left_used[i] = true;
+            right_used[j] = true;
+            matches.push(RecordMatch { left_index: i as u64, right_index: j as u64, similarity });
+        }
+    }
+
+    let mut similarity_histogram = vec![0u64; HISTOGRAM_BINS];
+    for similarity in &best {
+        similarity_histogram[((similarity * HISTOGRAM_BINS as f64) as usize).min(HISTOGRAM_BINS - 1)] += 1;
+    }
+    let quality = LinkageQuality {
+        left_records: left.len() as u64,
+        right_records: right.len() as u64,
+        matches: matches.len() as u64,
+        match_rate: matches.len() as f64 / left.len().min(right.len()) as f64,
+        mean_similarity: if matches.is_empty() { 0.0 } else {
+            matches.iter().map(|m| m.similarity).sum::<f64>() / matches.len() as f64
+        },
+        candidate_pairs: candidates.len() as u64,
+        ambiguous_records: left_candidates.iter().chain(&right_candidates).filter(|&&c| c > 1).count() as u64,
+        uncertain_matches: matches.iter().filter(|m| m.similarity < threshold + UNCERTAIN_MARGIN).count() as u64,
+        similarity_histogram,
+    };
+
+    let run = LinkageRun {
+        id: id.clone(),
+        requester,
+        left_dataset,
+        right_dataset,
+        threshold,
+        computation_id,
+        matches,
+        quality,
+        created_at: now,
+    };
+    RUNS.with(|r| r.borrow_mut().insert(id, run.clone()));
+    Ok(run)
+}
+
+pub fn get(id: &str) -> Option<LinkageRun> {
+    RUNS.with(|r| r.borrow().get(id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&ENCODINGS), backup::cloned(&RUNS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (encodings, runs) = backup::decode(bytes)?;
+    backup::replace(&ENCODINGS, encodings);
+    backup::replace(&RUNS, runs);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "linkage_test.rs"]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+use sha2::{Digest, Sha256};
+
+/// CLK of a name as a party would encode it: padded bigrams, each hashed
+/// into 20 bits of a 1024-bit filter with the shared secret
+fn clk(name: &str) -> Vec<u8> {
+    let mut filter = vec![0u8; 128];
+    let padded: Vec<char> = format!("_{}_", name.to_lowercase()).chars().collect();
+    for bigram in padded.windows(2) {
+        for k in 0..20u8 {
+            let digest = Sha256::new()
+                .chain_update(b"shared-secret")
+                .chain_update(bigram.iter().collect::<String>().as_bytes())
+                .chain_update([k])
+                .finalize();
+            let bit = u16::from_be_bytes([digest[0], digest[1]]) as usize % 1024;
+            filter[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+    filter
+}
+
+fn encode(dataset_id: &str, names: &[&str]) {
+    submit(dataset_id, names.iter().map(|n| clk(n)).collect()).unwrap();
+}
+
+#[test]
+fn links_records_despite_typos() {
+    encode("left", &["Jonathan Smith", "Maria Garcia", "Wei Zhang"]);
+    encode("right", &["Olga Petrova", "Jonathon Smith", "maria garcia"]);
+
+    let run = link("link_1".to_string(), Principal::anonymous(), ("left".to_string(), "right".to_string()), 0.8, None, 1).unwrap();
+    let pairs: Vec<(u64, u64)> = run.matches.iter().map(|m| (m.left_index, m.right_index)).collect();
+    assert_eq!(pairs, vec![(1, 2), (0, 1)]);
+    assert_eq!(run.matches[0].similarity, 1.0);
+
+    assert_eq!(run.quality.matches, 2);
+    assert!((run.quality.match_rate - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(run.quality.similarity_histogram.iter().sum::<u64>(), 3);
+    // The unmatched record has no close partner
+    assert_eq!(run.quality.similarity_histogram[..5].iter().sum::<u64>(), 1);
+    assert!(get("link_1").is_some());
+}
+
+#[test]
+fn links_each_record_at_most_once() {
+    encode("dup_left", &["Anna Berg"]);
+    encode("dup_right", &["Anna Berg", "Anna Berg"]);
+
+    let run = link("link_2".to_string(), Principal::anonymous(), ("dup_left".to_string(), "dup_right".to_string()), 0.9, None, 1).unwrap();
+    assert_eq!(run.matches.len(), 1);
+    assert_eq!(run.quality.candidate_pairs, 2);
+    assert_eq!(run.quality.ambiguous_records, 1);
+}
+
+#[test]
+fn rejects_bad_encodings_and_thresholds() {
+    assert!(submit("bad", vec![]).is_err());
+    assert!(submit("bad", vec![vec![0; 8]]).is_err());
+    assert!(submit("bad", vec![vec![0; 128], vec![0; 64]]).is_err());
+
+    encode("t_left", &["A"]);
+    submit("t_short", vec![vec![1; 64]]).unwrap();
+    let datasets = || ("t_left".to_string(), "t_short".to_string());
+    assert!(link("x".to_string(), Principal::anonymous(), datasets(), 0.8, None, 1).is_err());
+    assert!(link("x".to_string(), Principal::anonymous(), ("t_left".to_string(), "t_left".to_string()), 0.8, None, 1).is_err());
+    assert!(link("x".to_string(), Principal::anonymous(), ("t_left".to_string(), "missing".to_string()), 0.8, None, 1).is_err());
+    encode("t_right", &["A"]);
+    assert!(link("x".to_string(), Principal::anonymous(), ("t_left".to_string(), "t_right".to_string()), 1.5, None, 1).is_err());
+}
@@ -0,0 +1,254 @@
+//! Encrypted sums over Paillier-encrypted columns
+//!
+//! Key holders register a Paillier public key whose decryption exponent
+//! they split among themselves outside the canister, so only all of them
+//! together can decrypt. Parties upload numeric columns encrypted under that
+//! key, one ciphertext per row, values in secure sum fixed point with
+//! negatives as `N - |m|`. The canister adds columns by multiplying their
+//! ciphertexts modulo N^2 and never decrypts anything itself: each key holder
+//! raises the encrypted total to its share of the exponent, and only the
+//! product of every holder's partial decryption reveals the total.
+//!
+//! Integers are big-endian bytes.
+
+use candid::{CandidType, Deserialize, Principal};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use super::secure_sum::SCALE;
+use crate::backup;
+
+pub const MIN_MODULUS_BITS: u64 = 2048;
+pub const MIN_KEY_HOLDERS: usize = 2;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AggregateStatus {
+    AwaitingDecryption,
+    Decrypted,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PaillierKey {
+    pub id: String,
+    pub modulus: Vec<u8>,
+    pub holders: Vec<Principal>,
+    pub registered_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EncryptedAggregate {
+    pub id: String,
+    pub key_id: String,
+    pub requester: Principal,
+    pub column: String,
+    pub dataset_ids: Vec<String>,
+    pub count: u64,
+    pub encrypted_sum: Vec<u8>,
+    /// Key holders who submitted their partial decryption
+    pub decrypted_by: Vec<Principal>,
+    pub status: AggregateStatus,
+    pub sum: Option<f64>,
+    pub mean: Option<f64>,
+    pub created_at: u64,
+}
+
+struct EncryptedColumn {
+    key_id: String,
+    ciphertexts: Vec<BigUint>,
+}
+
+thread_local! {
+    static KEYS: RefCell<HashMap<String, PaillierKey>> = RefCell::new(HashMap::new());
+    static COLUMNS: RefCell<HashMap<(String, String), EncryptedColumn>> = RefCell::new(HashMap::new());
+    static AGGREGATES: RefCell<HashMap<String, EncryptedAggregate>> = RefCell::new(HashMap::new());
+    // Partial decryptions of aggregates still awaiting holders
+    static PARTIALS: RefCell<HashMap<String, Vec<BigUint>>> = RefCell::new(HashMap::new());
+}
+
+pub fn modulus_bits(modulus: &[u8]) -> u64 {
+    BigUint::from_bytes_be(modulus).bits()
+}
+
+pub fn register_key(id: String, modulus: Vec<u8>, holders: Vec<Principal>, registered_by: Principal, now: u64) -> Result<PaillierKey, String> {
+    if holders.len() < MIN_KEY_HOLDERS {
+        return Err(format!("A key needs at least {} holders", MIN_KEY_HOLDERS));
+    }
+    if (1..holders.len()).any(|i| holders[..i].contains(&holders[i])) {
+        return Err("Each holder can hold only one share".to_string());
+    }
+    let n = BigUint::from_bytes_be(&modulus);
+    if n.bits() < 2 || (&n % 2u32).is_zero() {
+        return Err("The modulus must be an odd composite".to_string());
+    }
+    let key = PaillierKey { id: id.clone(), modulus: n.to_bytes_be(), holders, registered_by, created_at: now };
+    KEYS.with(|k| k.borrow_mut().insert(id, key.clone()));
+    Ok(key)
+}
+
+pub fn key(id: &str) -> Option<PaillierKey> {
+    KEYS.with(|k| k.borrow().get(id).cloned())
+}
+
+fn modulus_squared(key_id: &str) -> Result<(BigUint, BigUint), String> {
+    let key = key(key_id).ok_or_else(|| format!("Key {} not found", key_id))?;
+    let n = BigUint::from_bytes_be(&key.modulus);
+    let n2 = &n * &n;
+    Ok((n, n2))
+}
+
+/// Parse values that must be units modulo N^2
+fn ciphertexts(values: &[Vec<u8>], n2: &BigUint) -> Result<Vec<BigUint>, String> {
+    values.iter().map(|bytes| {
+        let c = BigUint::from_bytes_be(bytes);
+        if c.is_zero() || &c >= n2 {
+            return Err("Ciphertexts must lie between 1 and N^2".to_string());
+        }
+        Ok(c)
+    }).collect()
+}
+
+pub fn upload_column(dataset_id: &str, column: &str, key_id: &str, values: Vec<Vec<u8>>) -> Result<(), String> {
+    if values.is_empty() {
+        return Err("Encrypted columns need at least one value".to_string());
+    }
+    let (_, n2) = modulus_squared(key_id)?;
+    let ciphertexts = ciphertexts(&values, &n2)?;
+    COLUMNS.with(|c| c.borrow_mut().insert(
+        (dataset_id.to_string(), column.to_string()),
+        EncryptedColumn { key_id: key_id.to_string(), ciphertexts },
+    ));
+    Ok(())
+}
+
+/// Drop a dataset's encrypted columns once its records change
+pub fn invalidate(dataset_id: &str) {
+    COLUMNS.with(|c| c.borrow_mut().retain(|(dataset, _), _| dataset != dataset_id));
+}
+
+/// Encrypted total of a column across datasets, for the key holders to decrypt
+pub fn sum(id: String, requester: Principal, key_id: &str, dataset_ids: Vec<String>, column: &str, now: u64) -> Result<EncryptedAggregate, String> {
+    if dataset_ids.is_empty() {
+        return Err("Choose at least one dataset".to_string());
+    }
+    let (_, n2) = modulus_squared(key_id)?;
+    let (total, count) = COLUMNS.with(|c| {
+        let columns = c.borrow();
+        dataset_ids.iter().try_fold((BigUint::one(), 0u64), |(total, count), dataset_id| {
+            let encrypted = columns.get(&(dataset_id.clone(), column.to_string()))
+                .ok_or_else(|| format!("Dataset {} has no encrypted column '{}'", dataset_id, column))?;
+            if encrypted.key_id != key_id {
+                return Err(format!("Dataset {} encrypted '{}' under another key", dataset_id, column));
+            }
+            let total = encrypted.ciphertexts.iter().fold(total, |total, c| total * c % &n2);
+            Ok((total, count + encrypted.ciphertexts.len() as u64))
+        })
+    })?;
+
+    let aggregate = EncryptedAggregate {
+        id: id.clone(),
+        key_id: key_id.to_string(),
+        requester,
+        column: column.to_string(),
+        dataset_ids,
+        count,
+        encrypted_sum: total.to_bytes_be(),
+        decrypted_by: vec![],
+        status: AggregateStatus::AwaitingDecryption,
+        sum: None,
+        mean: None,
+        created_at: now,
+    };
+    AGGREGATES.with(|a| a.borrow_mut().insert(id, aggregate.clone()));
+    Ok(aggregate)
+}
+
+pub fn aggregate(id: &str) -> Option<EncryptedAggregate> {
+    AGGREGATES.with(|a| a.borrow().get(id).cloned())
+}
+
+/// Signed fixed-point value of a plaintext modulo N
+fn decode(m: &BigUint, n: &BigUint) -> Result<f64, String> {
+    let (magnitude, negative) = if m > &(n >> 1) { (n - m, true) } else { (m.clone(), false) };
+    let magnitude = magnitude.to_i128().ok_or("The total is out of range")?;
+    Ok(if negative { -magnitude } else { magnitude } as f64 / SCALE)
+}
+
+/// Record a key holder's partial decryption; the last one reveals the total
+pub fn submit_partial(id: &str, holder: Principal, partial: Vec<u8>) -> Result<EncryptedAggregate, String> {
+    let mut aggregate = aggregate(id).ok_or_else(|| format!("Aggregate {} not found", id))?;
+    let key = key(&aggregate.key_id).ok_or_else(|| format!("Key {} not found", aggregate.key_id))?;
+    if !key.holders.contains(&holder) {
+        return Err("Only key holders can decrypt this aggregate".to_string());
+    }
+    if aggregate.decrypted_by.contains(&holder) {
+        return Err("Already submitted a partial decryption".to_string());
+    }
+    let (n, n2) = modulus_squared(&aggregate.key_id)?;
+    let partial = ciphertexts(&[partial], &n2)?.remove(0);
+
+    let partials = PARTIALS.with(|p| {
+        let mut partials = p.borrow_mut();
+        let entry = partials.entry(id.to_string()).or_default();
+        entry.push(partial);
+        entry.clone()
+    });
+    aggregate.decrypted_by.push(holder);
+
+    if aggregate.decrypted_by.len() == key.holders.len() {
+        // The partials multiply to (1 + N)^m = 1 + mN
+        let combined = partials.iter().fold(BigUint::one(), |total, p| total * p % &n2);
+        if &combined % &n != BigUint::one() {
+            PARTIALS.with(|p| p.borrow_mut().remove(id));
+            aggregate.decrypted_by.clear();
+            AGGREGATES.with(|a| a.borrow_mut().insert(id.to_string(), aggregate));
+            return Err("Partial decryptions do not combine; every holder must submit again".to_string());
+        }
+        let sum = decode(&((combined - 1u32) / &n), &n)?;
+        aggregate.sum = Some(sum);
+        aggregate.mean = Some(sum / aggregate.count as f64);
+        aggregate.status = AggregateStatus::Decrypted;
+        PARTIALS.with(|p| p.borrow_mut().remove(id));
+    }
+    AGGREGATES.with(|a| a.borrow_mut().insert(id.to_string(), aggregate.clone()));
+    Ok(aggregate)
+}
+
+/// A column's ciphertexts as bytes, for backups
+type ColumnBackup = ((String, String), String, Vec<Vec<u8>>);
+
+fn to_bytes(values: &[BigUint]) -> Vec<Vec<u8>> {
+    values.iter().map(BigUint::to_bytes_be).collect()
+}
+
+fn from_bytes(values: Vec<Vec<u8>>) -> Vec<BigUint> {
+    values.iter().map(|bytes| BigUint::from_bytes_be(bytes)).collect()
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    let columns: Vec<ColumnBackup> = COLUMNS.with(|columns| {
+        columns.borrow().iter()
+            .map(|(column, encrypted)| (column.clone(), encrypted.key_id.clone(), to_bytes(&encrypted.ciphertexts)))
+            .collect()
+    });
+    let partials: HashMap<String, Vec<Vec<u8>>> = PARTIALS.with(|partials| {
+        partials.borrow().iter().map(|(id, values)| (id.clone(), to_bytes(values))).collect()
+    });
+    backup::encode(&(backup::cloned(&KEYS), columns, backup::cloned(&AGGREGATES), partials))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (keys, columns, aggregates, partials): (_, Vec<ColumnBackup>, _, HashMap<String, Vec<Vec<u8>>>) = backup::decode(bytes)?;
+    backup::replace(&KEYS, keys);
+    backup::replace(&COLUMNS, columns.into_iter()
+        .map(|(column, key_id, ciphertexts)| (column, EncryptedColumn { key_id, ciphertexts: from_bytes(ciphertexts) }))
+        .collect());
+    backup::replace(&AGGREGATES, aggregates);
+    backup::replace(&PARTIALS, partials.into_iter().map(|(id, values)| (id, from_bytes(values))).collect());
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "paillier_test.rs"]
+mod tests;
@@ -0,0 +1,98 @@
+use super::*;
+
+/// Test key from two Mersenne primes, its decryption exponent split among
+/// three holders
+struct TestKey {
+    n: BigUint,
+    shares: [BigUint; 3],
+}
+
+fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    if b.is_zero() { a.clone() } else { gcd(b, &(a % b)) }
+}
+
+fn test_key() -> TestKey {
+    let p = (BigUint::one() << 61u32) - 1u32;
+    let q = (BigUint::one() << 89u32) - 1u32;
+    let n = &p * &q;
+    let (p1, q1) = (p - 1u32, q - 1u32);
+    let lambda = &p1 * &q1 / gcd(&p1, &q1);
+    let d = &lambda * lambda.modinv(&n).unwrap();
+    let (d1, d2) = (BigUint::from(12_345_678_901_234_567u64), BigUint::from(98_765_432_109_876_543u64));
+    let d3 = d - &d1 - &d2;
+    TestKey { n, shares: [d1, d2, d3] }
+}
+
+fn encrypt(key: &TestKey, value: f64, r: u64) -> Vec<u8> {
+    let n2 = &key.n * &key.n;
+    let fixed = (value * SCALE).round() as i64;
+    let m = if fixed < 0 { &key.n - BigUint::from(fixed.unsigned_abs()) } else { BigUint::from(fixed as u64) };
+    ((BigUint::one() + m * &key.n) * BigUint::from(r).modpow(&key.n, &n2) % &n2).to_bytes_be()
+}
+
+fn holders() -> Vec<Principal> {
+    (1..=3).map(|i| Principal::from_slice(&[i])).collect()
+}
+
+fn partial(key: &TestKey, aggregate: &EncryptedAggregate, holder: usize) -> Vec<u8> {
+    BigUint::from_bytes_be(&aggregate.encrypted_sum).modpow(&key.shares[holder], &(&key.n * &key.n)).to_bytes_be()
+}
+
+fn setup() -> TestKey {
+    let key = test_key();
+    register_key("pk".to_string(), key.n.to_bytes_be(), holders(), holders()[0], 1).unwrap();
+    upload_column("d1", "cost", "pk", vec![encrypt(&key, 12.5, 7), encrypt(&key, -2.25, 11)]).unwrap();
+    upload_column("d2", "cost", "pk", vec![encrypt(&key, 40.0, 13)]).unwrap();
+    key
+}
+
+#[test]
+fn holders_jointly_decrypt_only_the_total() {
+    let key = setup();
+    let aggregate = sum("agg".to_string(), Principal::anonymous(), "pk", vec!["d1".to_string(), "d2".to_string()], "cost", 2).unwrap();
+    assert_eq!(aggregate.count, 3);
+
+    for (i, holder) in holders().into_iter().enumerate() {
+        let updated = submit_partial("agg", holder, partial(&key, &aggregate, i)).unwrap();
+        assert_eq!(updated.sum.is_some(), i == 2);
+    }
+    let decrypted = super::aggregate("agg").unwrap();
+    assert_eq!(decrypted.status, AggregateStatus::Decrypted);
+    assert!((decrypted.sum.unwrap() - 50.25).abs() < 1e-9);
+    assert!((decrypted.mean.unwrap() - 16.75).abs() < 1e-9);
+}
+
+#[test]
+fn wrong_partials_reset_the_decryption() {
+    let key = setup();
+    let aggregate = sum("agg".to_string(), Principal::anonymous(), "pk", vec!["d1".to_string()], "cost", 2).unwrap();
+    assert!(submit_partial("agg", Principal::anonymous(), partial(&key, &aggregate, 0)).is_err());
+
+    submit_partial("agg", holders()[0], partial(&key, &aggregate, 0)).unwrap();
+    assert!(submit_partial("agg", holders()[0], partial(&key, &aggregate, 0)).is_err());
+    submit_partial("agg", holders()[1], partial(&key, &aggregate, 1)).unwrap();
+    assert!(submit_partial("agg", holders()[2], partial(&key, &aggregate, 1)).is_err());
+    assert!(super::aggregate("agg").unwrap().decrypted_by.is_empty());
+}
+
+#[test]
+fn rejects_bad_keys_columns_and_ciphertexts() {
+    let key = setup();
+    assert!(register_key("k".to_string(), vec![15], vec![holders()[0]], holders()[0], 1).is_err());
+    assert!(register_key("k".to_string(), vec![16], holders(), holders()[0], 1).is_err());
+    assert!(upload_column("d3", "cost", "pk", vec![vec![0]]).is_err());
+    assert!(upload_column("d3", "cost", "missing", vec![encrypt(&key, 1.0, 3)]).is_err());
+
+    register_key("other".to_string(), key.n.to_bytes_be(), holders(), holders()[0], 1).unwrap();
+    upload_column("d3", "cost", "other", vec![encrypt(&key, 1.0, 3)]).unwrap();
+    assert!(sum("x".to_string(), Principal::anonymous(), "pk", vec!["d1".to_string(), "d3".to_string()], "cost", 2).is_err());
+    assert!(sum("x".to_string(), Principal::anonymous(), "pk", vec!["d1".to_string()], "age", 2).is_err());
+}
+
+#[test]
+fn changed_datasets_lose_their_encrypted_columns() {
+    setup();
+    invalidate("d1");
+    assert!(sum("x".to_string(), Principal::anonymous(), "pk", vec!["d1".to_string()], "cost", 2).is_err());
+    sum("x".to_string(), Principal::anonymous(), "pk", vec!["d2".to_string()], "cost", 2).unwrap();
+}
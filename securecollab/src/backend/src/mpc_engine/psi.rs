@@ -0,0 +1,275 @@
+//! Private set intersection between two parties' datasets
+//!
+//! Diffie-Hellman PSI over Ristretto255. Each party hashes its record
+//! identifiers to points with `RistrettoPoint::hash_from_bytes::<Sha512>` and
+//! raises them to its own secret scalar before submitting them:
+//!
+//! 1. The initiator opens a session with its blinded identifiers `H(x)^a`.
+//! 2. The responder submits its blinded identifiers `H(y)^b` and the
+//!    initiator's points raised to its secret, `H(x)^ab`, in the same order.
+//! 3. The initiator completes the session with `H(y)^ba`, in the responder's
+//!    order.
+//!
+//! Doubly blinded points match exactly for common identifiers, so the
+//! canister can tell each party which of its own submissions are shared
+//! without anyone, the canister included, seeing an identifier or learning
+//! anything about non-intersecting ones beyond their number. Parties are
+//! assumed to follow the protocol.
+//!
+//! Doubly blinded points never leave the canister. In cardinality-only mode
+//! that leaves the parties just the size of the intersection, optionally
+//! with differential privacy noise, fixed once when the session completes.
+
+use candid::{CandidType, Deserialize, Principal};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use crate::differential_privacy::{self, DpConfig, NoiseSource};
+use crate::backup;
+
+/// Most identifiers one party may submit
+pub const MAX_ELEMENTS: usize = 50_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PsiStatus {
+    AwaitingResponse,
+    AwaitingCompletion,
+    Completed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PsiMode {
+    /// Each party learns which of its identifiers are shared
+    Members,
+    /// Both parties learn only how many identifiers are shared
+    Cardinality { noise: Option<DpConfig> },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PsiSession {
+    pub id: String,
+    pub initiator: Principal,
+    pub initiator_dataset: String,
+    pub responder: Principal,
+    pub responder_dataset: String,
+    pub mode: PsiMode,
+    pub status: PsiStatus,
+    pub created_at: u64,
+    /// H(x)^a
+    pub initiator_blinded: Vec<Vec<u8>>,
+    /// H(y)^b
+    pub responder_blinded: Vec<Vec<u8>>,
+    /// H(x)^ab, in the initiator's order
+    pub initiator_double: Vec<Vec<u8>>,
+    /// H(y)^ba, in the responder's order
+    pub responder_double: Vec<Vec<u8>>,
+    /// Released size of a completed cardinality-only session
+    pub cardinality: Option<u64>,
+}
+
+/// What one party learns: positions of its own submissions that are shared
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PsiResult {
+    pub session_id: String,
+    pub intersection_size: u64,
+    /// Empty in cardinality-only mode
+    pub matched_indices: Vec<u64>,
+    /// False when the size carries noise
+    pub exact: bool,
+}
+
+thread_local! {
+    static SESSIONS: RefCell<HashMap<String, PsiSession>> = RefCell::new(HashMap::new());
+}
+
+fn check_points(points: &[Vec<u8>]) -> Result<(), String> {
+    if points.is_empty() || points.len() > MAX_ELEMENTS {
+        return Err(format!("Submit between 1 and {} identifiers", MAX_ELEMENTS));
+    }
+    let valid = points.iter().all(|point| {
+        CompressedRistretto::from_slice(point).ok().and_then(|p| p.decompress()).is_some()
+    });
+    if !valid {
+        return Err("Every identifier must be a compressed Ristretto point".to_string());
+    }
+    Ok(())
+}
+
+/// The session without doubly blinded points, which would let the parties
+/// match identifiers themselves
+fn public(session: &PsiSession) -> PsiSession {
+    PsiSession { initiator_double: vec![], responder_double: vec![], ..session.clone() }
+}
+
+/// Each party is given with its dataset
+pub fn start(
+    id: String,
+    (initiator, initiator_dataset): (Principal, String),
+    (responder, responder_dataset): (Principal, String),
+    blinded: Vec<Vec<u8>>,
+    mode: PsiMode,
+    now: u64,
+) -> Result<PsiSession, String> {
+    check_points(&blinded)?;
+    if let PsiMode::Cardinality { noise: Some(config) } = &mode {
+        differential_privacy::validate(config)?;
+    }
+    let session = PsiSession {
+        id: id.clone(),
+        initiator,
+        initiator_dataset,
+        responder,
+        responder_dataset,
+        mode,
+        status: PsiStatus::AwaitingResponse,
+        created_at: now,
+        initiator_blinded: blinded,
+        responder_blinded: vec![],
+        initiator_double: vec![],
+        responder_double: vec![],
+        cardinality: None,
+    };
+    SESSIONS.with(|s| s.borrow_mut().insert(id, session.clone()));
+    Ok(session)
+}
+
+pub fn respond(id: &str, caller: Principal, blinded: Vec<Vec<u8>>, initiator_double: Vec<Vec<u8>>) -> Result<PsiSession, String> {
+    check_points(&blinded)?;
+    SESSIONS.with(|s| {
+        let mut sessions = s.borrow_mut();
+        let session = sessions.get_mut(id).ok_or("PSI session not found")?;
+        if session.responder != caller {
+            return Err("Only the responding party can answer this session".to_string());
+        }
+        if session.status != PsiStatus::AwaitingResponse {
+            return Err("This session has already been answered".to_string());
+        }
+        if initiator_double.len() != session.initiator_blinded.len() {
+            return Err("Blind every one of the initiator's identifiers, in order".to_string());
+        }
+        check_points(&initiator_double)?;
+        session.responder_blinded = blinded;
+        session.initiator_double = initiator_double;
+        session.status = PsiStatus::AwaitingCompletion;
+        Ok(public(session))
+    })
+}
+
+/// `seed` draws the noise of a noisy cardinality-only session
+pub fn complete(id: &str, caller: Principal, responder_double: Vec<Vec<u8>>, seed: Vec<u8>) -> Result<PsiSession, String> {
+    SESSIONS.with(|s| {
+        let mut sessions = s.borrow_mut();
+        let session = sessions.get_mut(id).ok_or("PSI session not found")?;
+        if session.initiator != caller {
+            return Err("Only the initiating party can complete this session".to_string());
+        }
+        if session.status != PsiStatus::AwaitingCompletion {
+            return Err("This session is not awaiting completion".to_string());
+        }
+        if responder_double.len() != session.responder_blinded.len() {
+            return Err("Blind every one of the responder's identifiers, in order".to_string());
+        }
+        check_points(&responder_double)?;
+        session.responder_double = responder_double;
+        session.status = PsiStatus::Completed;
+        if let PsiMode::Cardinality { noise } = &session.mode {
+            let size = matches(&session.initiator_double, &session.responder_double).len() as f64;
+            let noise = noise.as_ref().map_or(0.0, |config| differential_privacy::noise(
+                &mut NoiseSource::new(seed), config.mechanism, 1.0, config.epsilon, config.delta));
+            session.cardinality = Some((size + noise).round().max(0.0) as u64);
+        }
+        Ok(public(session))
+    })
+}
+
+fn get(id: &str) -> Option<PsiSession> {
+    SESSIONS.with(|s| s.borrow().get(id).cloned())
+}
+
+/// A session as one of its two parties may see it
+pub fn view(id: &str, caller: Principal) -> Result<PsiSession, String> {
+    let session = get(id).ok_or_else(|| format!("Session {} not found", id))?;
+    if caller != session.initiator && caller != session.responder {
+        return Err("Only the two parties can view this session".to_string());
+    }
+    Ok(public(&session))
+}
+
+/// Positions in `own` of points also in `other`
+fn matches(own: &[Vec<u8>], other: &[Vec<u8>]) -> Vec<u64> {
+    let other: HashSet<&Vec<u8>> = other.iter().collect();
+    own.iter().enumerate()
+        .filter(|(_, point)| other.contains(point))
+        .map(|(i, _)| i as u64)
+        .collect()
+}
+
+/// Positions of `caller`'s submissions found in the other party's set, or
+/// only their number in cardinality-only mode
+pub fn result(id: &str, caller: Principal) -> Result<PsiResult, String> {
+    let session = get(id).ok_or("PSI session not found")?;
+    if session.status != PsiStatus::Completed {
+        return Err("The session is not completed yet".to_string());
+    }
+    let (own, other) = if caller == session.initiator {
+        (&session.initiator_double, &session.responder_double)
+    } else if caller == session.responder {
+        (&session.responder_double, &session.initiator_double)
+    } else {
+        return Err("Only the two parties can see the intersection".to_string());
+    };
+
+    if let PsiMode::Cardinality { noise } = &session.mode {
+        return Ok(PsiResult {
+            session_id: session.id.clone(),
+            intersection_size: session.cardinality.unwrap_or(0),
+            matched_indices: vec![],
+            exact: noise.is_none(),
+        });
+    }
+    let matched_indices = matches(own, other);
+    Ok(PsiResult {
+        session_id: session.id.clone(),
+        intersection_size: matched_indices.len() as u64,
+        matched_indices,
+        exact: true,
+    })
+}
+
+/// Matched (index in `dataset_a`'s submissions, index in `dataset_b`'s)
+/// pairs of the latest completed members session between the two datasets,
+/// with its id
+pub fn pairs(dataset_a: &str, dataset_b: &str) -> Result<(String, Vec<(u64, u64)>), String> {
+    let session = SESSIONS.with(|s| s.borrow().values()
+        .filter(|s| s.status == PsiStatus::Completed && s.mode == PsiMode::Members)
+        .filter(|s| [s.initiator_dataset.as_str(), s.responder_dataset.as_str()] == [dataset_a, dataset_b]
+            || [s.initiator_dataset.as_str(), s.responder_dataset.as_str()] == [dataset_b, dataset_a])
+        .max_by(|x, y| x.created_at.cmp(&y.created_at).then_with(|| x.id.cmp(&y.id)))
+        .cloned())
+        .ok_or("No completed set intersection between these datasets")?;
+
+    let responder: HashMap<&Vec<u8>, u64> = session.responder_double.iter().enumerate()
+        .map(|(j, point)| (point, j as u64))
+        .collect();
+    let initiator_first = session.initiator_dataset == dataset_a;
+    let pairs = session.initiator_double.iter().enumerate()
+        .filter_map(|(i, point)| responder.get(point).map(|&j| {
+            if initiator_first { (i as u64, j) } else { (j, i as u64) }
+        }))
+        .collect();
+    Ok((session.id, pairs))
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&SESSIONS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let sessions = backup::decode(bytes)?;
+    backup::replace(&SESSIONS, sessions);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "psi_test.rs"]
+mod tests;
@@ -0,0 +1,151 @@
+use super::*;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use sha2::Sha512;
+
+fn secret(byte: u8) -> Scalar {
+    Scalar::from_bytes_mod_order([byte; 32])
+}
+
+/// Hash and blind identifiers as a party would
+fn blind(ids: &[&str], key: Scalar) -> Vec<Vec<u8>> {
+    ids.iter()
+        .map(|id| (RistrettoPoint::hash_from_bytes::<Sha512>(id.as_bytes()) * key).compress().to_bytes().to_vec())
+        .collect()
+}
+
+/// Raise the other party's blinded points to this party's secret
+fn reblind(points: &[Vec<u8>], key: Scalar) -> Vec<Vec<u8>> {
+    points.iter()
+        .map(|p| (CompressedRistretto::from_slice(p).unwrap().decompress().unwrap() * key).compress().to_bytes().to_vec())
+        .collect()
+}
+
+fn parties() -> (Principal, Principal) {
+    (Principal::from_slice(&[1]), Principal::from_slice(&[2]))
+}
+
+#[test]
+fn each_party_learns_its_shared_identifiers() {
+    let (alice, bob) = parties();
+    let (a, b) = (secret(7), secret(9));
+
+    let session = start("psi_1".to_string(), (alice, "da".to_string()), (bob, "db".to_string()),
+        blind(&["p1", "p2", "p3", "p4"], a), PsiMode::Members, 1).unwrap();
+    let session = respond("psi_1", bob, blind(&["p9", "p3", "p1"], b), reblind(&session.initiator_blinded, b)).unwrap();
+    assert!(result("psi_1", alice).is_err());
+    complete("psi_1", alice, reblind(&session.responder_blinded, a), vec![]).unwrap();
+
+    let for_alice = result("psi_1", alice).unwrap();
+    assert_eq!(for_alice.matched_indices, vec![0, 2]);
+    assert_eq!(for_alice.intersection_size, 2);
+    assert!(for_alice.exact);
+    assert_eq!(result("psi_1", bob).unwrap().matched_indices, vec![1, 2]);
+    assert!(result("psi_1", Principal::anonymous()).is_err());
+}
+
+#[test]
+fn only_the_expected_party_moves_a_session_forward() {
+    let (alice, bob) = parties();
+    let (a, b) = (secret(3), secret(5));
+    let session = start("psi_2".to_string(), (alice, "da".to_string()), (bob, "db".to_string()),
+        blind(&["x", "y"], a), PsiMode::Members, 1).unwrap();
+    let double = reblind(&session.initiator_blinded, b);
+
+    assert!(respond("psi_2", alice, blind(&["x"], b), double.clone()).is_err());
+    assert!(respond("psi_2", bob, blind(&["x"], b), double[..1].to_vec()).is_err());
+    let session = respond("psi_2", bob, blind(&["x"], b), double.clone()).unwrap();
+    assert!(respond("psi_2", bob, blind(&["x"], b), double).is_err());
+    assert!(complete("psi_2", bob, reblind(&session.responder_blinded, a), vec![]).is_err());
+}
+
+#[test]
+fn rejects_points_that_are_not_ristretto_encodings() {
+    let (alice, bob) = parties();
+    assert!(start("psi_3".to_string(), (alice, "da".to_string()), (bob, "db".to_string()), vec![vec![1; 32]], PsiMode::Members, 1).is_err());
+    assert!(start("psi_3".to_string(), (alice, "da".to_string()), (bob, "db".to_string()), vec![vec![0; 31]], PsiMode::Members, 1).is_err());
+    assert!(start("psi_3".to_string(), (alice, "da".to_string()), (bob, "db".to_string()), vec![], PsiMode::Members, 1).is_err());
+}
+
+/// Run a session over fixed sets in `mode`
+fn run(id: &str, mode: PsiMode) -> PsiSession {
+    let (alice, bob) = parties();
+    let (a, b) = (secret(11), secret(13));
+    let session = start(id.to_string(), (alice, "da".to_string()), (bob, "db".to_string()),
+        blind(&["p1", "p2", "p3", "p4", "p5"], a), mode, 1).unwrap();
+    let session = respond(id, bob, blind(&["p2", "p4", "p5", "p8"], b), reblind(&session.initiator_blinded, b)).unwrap();
+    complete(id, alice, reblind(&session.responder_blinded, a), vec![42]).unwrap()
+}
+
+#[test]
+fn cardinality_mode_reveals_only_the_size() {
+    let (alice, bob) = parties();
+    let session = run("psi_4", PsiMode::Cardinality { noise: None });
+    assert_eq!(session.cardinality, Some(3));
+    // Doubly blinded points would let the parties match identifiers
+    assert!(session.initiator_double.is_empty() && session.responder_double.is_empty());
+    assert!(view("psi_4", bob).unwrap().initiator_double.is_empty());
+
+    for party in [alice, bob] {
+        let result = result("psi_4", party).unwrap();
+        assert_eq!(result.intersection_size, 3);
+        assert!(result.matched_indices.is_empty());
+        assert!(result.exact);
+    }
+}
+
+#[test]
+fn noisy_cardinality_is_fixed_at_completion() {
+    let noise = DpConfig { epsilon: 0.5, ..DpConfig::default() };
+    run("psi_5", PsiMode::Cardinality { noise: Some(noise) });
+
+    let (alice, bob) = parties();
+    let first = result("psi_5", alice).unwrap();
+    assert!(!first.exact);
+    assert_eq!(result("psi_5", bob).unwrap().intersection_size, first.intersection_size);
+
+    let invalid = DpConfig { epsilon: 0.0, ..DpConfig::default() };
+    assert!(start("psi_6".to_string(), (alice, "da".to_string()), (bob, "db".to_string()),
+        blind(&["p1"], secret(1)), PsiMode::Cardinality { noise: Some(invalid) }, 1).is_err());
+}
+
+#[test]
+fn pairs_follow_the_requested_dataset_order() {
+    // Cardinality-only sessions never reveal pairs
+    run("psi_7", PsiMode::Cardinality { noise: None });
+    assert!(pairs("da", "db").is_err());
+
+    let (alice, bob) = parties();
+    let (a, b) = (secret(17), secret(19));
+    let session = start("psi_8".to_string(), (alice, "pa".to_string()), (bob, "pb".to_string()),
+        blind(&["k1", "k2", "k3"], a), PsiMode::Members, 1).unwrap();
+    let session = respond("psi_8", bob, blind(&["k3", "k9", "k1"], b), reblind(&session.initiator_blinded, b)).unwrap();
+    complete("psi_8", alice, reblind(&session.responder_blinded, a), vec![]).unwrap();
+
+    let (id, forward) = pairs("pa", "pb").unwrap();
+    assert_eq!(id, "psi_8");
+    assert_eq!(forward, vec![(0, 2), (2, 0)]);
+    assert_eq!(pairs("pb", "pa").unwrap().1, vec![(2, 0), (0, 2)]);
+}
+
+#[test]
+fn two_owners_intersect_and_then_join_their_datasets() {
+    let (hospital, insurer) = parties();
+    let (h, i) = (secret(23), secret(29));
+    let session = start("psi_9".to_string(), (hospital, "visits".to_string()), (insurer, "claims".to_string()),
+        blind(&["p1", "p2", "p3"], h), PsiMode::Members, 1).unwrap();
+    let session = respond("psi_9", insurer, blind(&["p3", "p9", "p1"], i), reblind(&session.initiator_blinded, i)).unwrap();
+    complete("psi_9", hospital, reblind(&session.responder_blinded, h), vec![]).unwrap();
+
+    // Either party can run the join; the view is granted to both of them
+    let (id, pairs) = pairs("visits", "claims").unwrap();
+    let session = view(&id, insurer).unwrap();
+    assert_eq!((session.initiator, session.responder), (hospital, insurer));
+    assert!(view(&id, Principal::from_slice(&[3])).is_err());
+
+    let visits = "patient,age\np1,34\np2,51\np3,47";
+    let claims = "patient,claims\np3,3\np9,1\np1,2";
+    let joined = crate::mpc_engine::join::join(visits, claims, "patient", &pairs).unwrap();
+    assert_eq!(joined.csv, "patient,age,claims\np1,34,2\np3,47,3");
+    assert_eq!(joined.mismatched_keys, 0);
+}
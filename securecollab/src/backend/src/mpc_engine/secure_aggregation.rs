@@ -0,0 +1,81 @@
+//! Pairwise-mask secure aggregation
+//!
+//! Every pair of participants in an aggregation session shares a key derived
+//! with vetKD under `pairwise_derivation_id`, which both of them, and only
+//! them, can request. Element `i` of round `r` is masked with the first eight
+//! bytes (big endian) of `SHA-256(key || session_id || r || i)`, `r` and `i`
+//! as big-endian u64: the participant whose principal sorts lower adds the
+//! mask, the other subtracts it, modulo 2^64. Each submitted vector therefore
+//! looks uniformly random, and the masks cancel once every participant's
+//! vector is summed. Values are fixed point with the secure sum scale.
+
+use candid::{CandidType, Deserialize, Principal};
+use super::secure_sum::SCALE;
+
+/// vetKD derivation id of the key shared by `a` and `b` in a session, the
+/// same whichever of them asks
+pub fn pairwise_derivation_id(session_id: &str, a: Principal, b: Principal) -> Vec<u8> {
+    let (low, high) = if a.as_slice() <= b.as_slice() { (a, b) } else { (b, a) };
+    format!("secure_aggregation_{}_{}_{}", session_id, low.to_text(), high.to_text()).into_bytes()
+}
+
+pub fn from_fixed(value: u64) -> f64 {
+    value as i64 as f64 / SCALE
+}
+
+/// Running sum of one round's masked vectors
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Aggregation {
+    participants: Vec<Principal>,
+    submitted: Vec<Principal>,
+    sum: Vec<u64>,
+}
+
+impl Aggregation {
+    pub fn new(participants: Vec<Principal>, len: usize) -> Result<Self, String> {
+        if participants.len() < 2 {
+            return Err("Secure aggregation needs at least two participants".to_string());
+        }
+        if (1..participants.len()).any(|i| participants[..i].contains(&participants[i])) {
+            return Err("Each participant can take part only once".to_string());
+        }
+        if len == 0 {
+            return Err("Aggregated vectors cannot be empty".to_string());
+        }
+        Ok(Self { participants, submitted: vec![], sum: vec![0; len] })
+    }
+
+    pub fn add(&mut self, participant: Principal, masked: &[u64]) -> Result<(), String> {
+        if !self.participants.contains(&participant) {
+            return Err("Only participants can submit to this aggregation".to_string());
+        }
+        if self.submitted.contains(&participant) {
+            return Err("Already submitted to this round".to_string());
+        }
+        if masked.len() != self.sum.len() {
+            return Err(format!("Masked vectors must have {} values", self.sum.len()));
+        }
+        for (total, value) in self.sum.iter_mut().zip(masked) {
+            *total = total.wrapping_add(*value);
+        }
+        self.submitted.push(participant);
+        Ok(())
+    }
+
+    pub fn participants(&self) -> &[Principal] {
+        &self.participants
+    }
+
+    pub fn submitted(&self) -> &[Principal] {
+        &self.submitted
+    }
+
+    /// Unmasked sum, once every participant submitted
+    pub fn total(&self) -> Option<&[u64]> {
+        (self.submitted.len() == self.participants.len()).then_some(self.sum.as_slice())
+    }
+}
+
+#[cfg(test)]
+#[path = "secure_aggregation_test.rs"]
+mod tests;
@@ -0,0 +1,62 @@
+use super::*;
+use sha2::{Digest, Sha256};
+
+fn to_fixed(value: f64) -> u64 {
+    (value * SCALE).round() as i64 as u64
+}
+
+fn party(id: u8) -> Principal {
+    Principal::from_slice(&[id])
+}
+
+/// Mask `values` as participant `me` would, with a stand-in for each
+/// pairwise vetKD key
+fn mask(session_id: &str, round: u64, me: Principal, peers: &[Principal], values: &[f64]) -> Vec<u64> {
+    values.iter().enumerate().map(|(i, value)| {
+        peers.iter().filter(|&&peer| peer != me).fold(to_fixed(*value), |masked, &peer| {
+            let key = Sha256::digest(pairwise_derivation_id(session_id, me, peer));
+            let digest = Sha256::new()
+                .chain_update(key)
+                .chain_update(session_id.as_bytes())
+                .chain_update(round.to_be_bytes())
+                .chain_update((i as u64).to_be_bytes())
+                .finalize();
+            let mask = u64::from_be_bytes(digest[..8].try_into().unwrap());
+            if me.as_slice() < peer.as_slice() { masked.wrapping_add(mask) } else { masked.wrapping_sub(mask) }
+        })
+    }).collect()
+}
+
+#[test]
+fn masks_cancel_in_the_total() {
+    let parties = [party(1), party(2), party(3)];
+    let mut aggregation = Aggregation::new(parties.to_vec(), 2).unwrap();
+    let values = [[1.5, -2.0], [0.25, 4.0], [3.0, 0.5]];
+    for (party, values) in parties.iter().zip(values) {
+        let masked = mask("agg", 1, *party, &parties, &values);
+        assert_ne!(masked[0], to_fixed(values[0]));
+        assert!(aggregation.total().is_none());
+        aggregation.add(*party, &masked).unwrap();
+    }
+    let total: Vec<f64> = aggregation.total().unwrap().iter().map(|v| from_fixed(*v)).collect();
+    assert_eq!(total, vec![4.75, 2.5]);
+}
+
+#[test]
+fn pairwise_ids_do_not_depend_on_who_asks() {
+    assert_eq!(pairwise_derivation_id("s", party(1), party(2)), pairwise_derivation_id("s", party(2), party(1)));
+    assert_ne!(pairwise_derivation_id("s", party(1), party(2)), pairwise_derivation_id("t", party(1), party(2)));
+}
+
+#[test]
+fn rejects_outsiders_repeats_and_bad_shapes() {
+    assert!(Aggregation::new(vec![party(1)], 1).is_err());
+    assert!(Aggregation::new(vec![party(1), party(1)], 1).is_err());
+    assert!(Aggregation::new(vec![party(1), party(2)], 0).is_err());
+
+    let mut aggregation = Aggregation::new(vec![party(1), party(2)], 1).unwrap();
+    assert!(aggregation.add(party(3), &[0]).is_err());
+    assert!(aggregation.add(party(1), &[0, 0]).is_err());
+    aggregation.add(party(1), &[0]).unwrap();
+    assert!(aggregation.add(party(1), &[0]).is_err());
+}
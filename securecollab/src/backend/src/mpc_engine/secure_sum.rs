@@ -0,0 +1,273 @@
+//! Secure sums and moments over additively secret-shared columns
+//!
+//! A party splits each value of a numeric column into random shares that
+//! add up to it modulo 2^64 and submits one share vector per share holder.
+//! Values are fixed point with three decimals, negative values in two's
+//! complement. A sum is computed holder by holder: each share vector is
+//! summed on its own, and only the per-holder totals across all datasets are
+//! added, so no code path handles a party's values, or any single share
+//! vector together with the others of the same column.
+//!
+//! Parties that also share the squares of their values (in the same fixed
+//! point) allow means, variances and standard deviations, derived from the
+//! secure sums of values and squares alone.
+//!
+//! The same statistics can instead come from secure aggregation: each dataset
+//! owner submits its count, sum and sum of squares as one pairwise-masked
+//! vector, and only the total of all three is ever unmasked.
+
+use candid::{CandidType, Deserialize, Principal};
+use sha2::{Digest, Sha256};
+use super::secure_aggregation::{self, Aggregation};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+/// Fixed-point scale of shared values
+pub const SCALE: f64 = 1000.0;
+pub const MIN_SHARES: usize = 2;
+pub const MAX_SHARES: usize = 8;
+/// Fewest datasets a sum may cover, so no party's own total is revealed
+pub const MIN_DATASETS: usize = 2;
+/// Count, sum and sum of squares, in that order
+pub const MASKED_FIELDS: usize = 3;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SecureSumResult {
+    pub column: String,
+    pub dataset_count: u64,
+    pub count: u64,
+    pub sum: f64,
+    pub mean: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SecureStatistics {
+    pub run_id: String,
+    pub column: String,
+    pub dataset_count: u64,
+    pub count: u64,
+    pub mean: f64,
+    /// Sample variance
+    pub variance: f64,
+    pub std_dev: f64,
+    /// Proof of the records each dataset contributed
+    pub record_count_proof_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+struct ColumnShares {
+    values: Vec<Vec<u64>>,
+    squares: Option<Vec<Vec<u64>>>,
+}
+
+/// (dataset, column)
+type ColumnKey = (String, String);
+
+#[derive(CandidType, Deserialize, Clone)]
+struct MaskedRun {
+    requester: Principal,
+    column: String,
+    aggregation: Aggregation,
+    result: Option<SecureStatistics>,
+}
+
+thread_local! {
+    static SHARES: RefCell<HashMap<ColumnKey, ColumnShares>> = RefCell::new(HashMap::new());
+    // Datasets and column of each statistics run, for its record-count proof
+    static RUNS: RefCell<HashMap<String, (Vec<String>, String)>> = RefCell::new(HashMap::new());
+    static MASKED_RUNS: RefCell<HashMap<String, MaskedRun>> = RefCell::new(HashMap::new());
+}
+
+fn check_shape(shares: &[Vec<u64>]) -> Result<usize, String> {
+    if !(MIN_SHARES..=MAX_SHARES).contains(&shares.len()) {
+        return Err(format!("Columns must be split into {} to {} shares", MIN_SHARES, MAX_SHARES));
+    }
+    let rows = shares[0].len();
+    if rows == 0 || shares.iter().any(|share| share.len() != rows) {
+        return Err("Every share vector must hold one share per row".to_string());
+    }
+    Ok(rows)
+}
+
+pub fn submit(dataset_id: &str, column: &str, values: Vec<Vec<u64>>, squares: Option<Vec<Vec<u64>>>) -> Result<(), String> {
+    let rows = check_shape(&values)?;
+    if let Some(squares) = &squares {
+        if check_shape(squares)? != rows {
+            return Err("Squares must be shared for every row".to_string());
+        }
+    }
+    SHARES.with(|s| s.borrow_mut().insert((dataset_id.to_string(), column.to_string()), ColumnShares { values, squares }));
+    Ok(())
+}
+
+fn with_shares<T>(dataset_id: &str, column: &str, f: impl FnOnce(&ColumnShares) -> T) -> Option<T> {
+    SHARES.with(|s| s.borrow().get(&(dataset_id.to_string(), column.to_string())).map(f))
+}
+
+/// Sum of one holder's share vector
+fn holder_total(share: Option<&Vec<u64>>) -> u64 {
+    share.map(|share| share.iter().fold(0u64, |total, value| total.wrapping_add(*value))).unwrap_or(0)
+}
+
+/// Secure total of `pick` over the datasets, holder by holder
+fn total(dataset_ids: &[String], column: &str, pick: fn(&ColumnShares) -> Option<&Vec<Vec<u64>>>) -> f64 {
+    let holders = dataset_ids.iter()
+        .filter_map(|id| with_shares(id, column, |c| pick(c).map_or(0, Vec::len)))
+        .max()
+        .unwrap_or(0);
+    // Datasets split into fewer shares contribute nothing to the extra holders
+    let total = (0..holders)
+        .map(|holder| dataset_ids.iter().fold(0u64, |t, id| {
+            t.wrapping_add(with_shares(id, column, |c| holder_total(pick(c).and_then(|s| s.get(holder)))).unwrap_or(0))
+        }))
+        .fold(0u64, u64::wrapping_add);
+    total as i64 as f64 / SCALE
+}
+
+/// Rows contributed by each dataset, after checking they can be combined
+fn contributions(dataset_ids: &[String], column: &str, need_squares: bool) -> Result<Vec<u64>, String> {
+    if dataset_ids.len() < MIN_DATASETS {
+        return Err(format!("A secure sum needs at least {} datasets", MIN_DATASETS));
+    }
+    if (1..dataset_ids.len()).any(|i| dataset_ids[..i].contains(&dataset_ids[i])) {
+        return Err("Each dataset can be summed only once".to_string());
+    }
+    dataset_ids.iter().map(|id| {
+        let (rows, has_squares) = with_shares(id, column, |c| (c.values[0].len() as u64, c.squares.is_some()))
+            .ok_or_else(|| format!("Dataset {} has no shares for column '{}'", id, column))?;
+        if need_squares && !has_squares {
+            return Err(format!("Dataset {} has not shared the squares of column '{}'", id, column));
+        }
+        Ok(rows)
+    }).collect()
+}
+
+pub fn sum(dataset_ids: &[String], column: &str) -> Result<SecureSumResult, String> {
+    let count: u64 = contributions(dataset_ids, column, false)?.iter().sum();
+    let sum = total(dataset_ids, column, |c| Some(&c.values));
+
+    Ok(SecureSumResult {
+        column: column.to_string(),
+        dataset_count: dataset_ids.len() as u64,
+        count,
+        sum,
+        mean: sum / count as f64,
+    })
+}
+
+/// Mean, sample variance and standard deviation; the run is recorded under
+/// `run_id` so its record counts can be proven
+pub fn statistics(run_id: &str, dataset_ids: &[String], column: &str) -> Result<SecureStatistics, String> {
+    let count: u64 = contributions(dataset_ids, column, true)?.iter().sum();
+    let sum = total(dataset_ids, column, |c| Some(&c.values));
+    let sum_of_squares = total(dataset_ids, column, |c| c.squares.as_ref());
+    let stats = moments(run_id, column, dataset_ids.len() as u64, count, sum, sum_of_squares)?;
+    RUNS.with(|runs| runs.borrow_mut().insert(run_id.to_string(), (dataset_ids.to_vec(), column.to_string())));
+    Ok(stats)
+}
+
+fn moments(run_id: &str, column: &str, dataset_count: u64, count: u64, sum: f64, sum_of_squares: f64) -> Result<SecureStatistics, String> {
+    if count < 2 {
+        return Err("A variance needs at least two records".to_string());
+    }
+    let n = count as f64;
+    let mean = sum / n;
+    // Rounding of the shared squares can leave a tiny negative remainder
+    let variance = ((sum_of_squares - sum * mean) / (n - 1.0)).max(0.0);
+
+    Ok(SecureStatistics {
+        run_id: run_id.to_string(),
+        column: column.to_string(),
+        dataset_count,
+        count,
+        mean,
+        variance,
+        std_dev: variance.sqrt(),
+        record_count_proof_id: None,
+    })
+}
+
+/// Open a statistics run aggregated from the owners' masked vectors
+pub fn open_masked(run_id: &str, requester: Principal, column: &str, owners: Vec<Principal>) -> Result<(), String> {
+    if owners.len() < MIN_DATASETS {
+        return Err(format!("A secure sum needs at least {} datasets", MIN_DATASETS));
+    }
+    let aggregation = Aggregation::new(owners, MASKED_FIELDS)
+        .map_err(|e| format!("{}; each owner contributes one dataset", e))?;
+    let run = MaskedRun { requester, column: column.to_string(), aggregation, result: None };
+    MASKED_RUNS.with(|runs| runs.borrow_mut().insert(run_id.to_string(), run));
+    Ok(())
+}
+
+pub fn masked_participants(run_id: &str) -> Option<Vec<Principal>> {
+    MASKED_RUNS.with(|runs| runs.borrow().get(run_id).map(|run| run.aggregation.participants().to_vec()))
+}
+
+/// Add an owner's masked totals; returns the statistics once all are in
+pub fn submit_masked(run_id: &str, owner: Principal, masked: Vec<u64>) -> Result<Option<SecureStatistics>, String> {
+    MASKED_RUNS.with(|runs| {
+        let mut runs = runs.borrow_mut();
+        let run = runs.get_mut(run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        run.aggregation.add(owner, &masked)?;
+        let Some(total) = run.aggregation.total() else {
+            return Ok(None);
+        };
+        let [count, sum, sum_of_squares] = [0, 1, 2].map(|i| secure_aggregation::from_fixed(total[i]));
+        let dataset_count = run.aggregation.participants().len() as u64;
+        let stats = moments(run_id, &run.column, dataset_count, count.round().max(0.0) as u64, sum, sum_of_squares)?;
+        run.result = Some(stats.clone());
+        Ok(Some(stats))
+    })
+}
+
+/// Statistics of a completed masked run, for its requester and owners
+pub fn masked_statistics(run_id: &str, caller: Principal) -> Result<SecureStatistics, String> {
+    MASKED_RUNS.with(|runs| {
+        let runs = runs.borrow();
+        let run = runs.get(run_id).ok_or_else(|| format!("Run {} not found", run_id))?;
+        if run.requester != caller && !run.aggregation.participants().contains(&caller) {
+            return Err("Only the requester and the dataset owners can view this run".to_string());
+        }
+        run.result.clone().ok_or_else(|| "Not every dataset owner has submitted yet".to_string())
+    })
+}
+
+/// Rows each dataset contributed to a run, bound to the digest of its share
+/// vectors; None if the run is unknown or its shares were withdrawn
+pub fn count_statement(run_id: &str) -> Option<String> {
+    let (dataset_ids, column) = RUNS.with(|runs| runs.borrow().get(run_id).cloned())?;
+    let rows = contributions(&dataset_ids, &column, true).ok()?;
+    let datasets: Vec<String> = dataset_ids.iter().zip(&rows)
+        .map(|(id, rows)| {
+            let digest = with_shares(id, &column, |c| {
+                let mut hasher = Sha256::new();
+                for value in c.values.iter().chain(c.squares.iter().flatten()).flatten() {
+                    hasher.update(value.to_be_bytes());
+                }
+                hex::encode(hasher.finalize())
+            }).unwrap_or_default();
+            format!("{}:{}:{}", id, rows, digest)
+        })
+        .collect();
+    Some(format!(
+        "RECORD_COUNT_PROOF[run:{},column:{},datasets:{},total:{}]",
+        run_id, column, datasets.join(";"), rows.iter().sum::<u64>()
+    ))
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&SHARES), backup::cloned(&RUNS), backup::cloned(&MASKED_RUNS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (shares, runs, masked_runs) = backup::decode(bytes)?;
+    backup::replace(&SHARES, shares);
+    backup::replace(&RUNS, runs);
+    backup::replace(&MASKED_RUNS, masked_runs);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "secure_sum_test.rs"]
+mod tests;
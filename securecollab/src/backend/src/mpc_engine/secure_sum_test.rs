@@ -0,0 +1,102 @@
+use super::*;
+
+/// Split values into `holders` shares as a party would
+fn split(values: &[f64], holders: usize, mut seed: u64) -> Vec<Vec<u64>> {
+    let mut shares = vec![Vec::new(); holders];
+    for value in values {
+        let fixed = (value * SCALE).round() as i64 as u64;
+        let mut rest = fixed;
+        for share in shares.iter_mut().skip(1) {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            share.push(seed);
+            rest = rest.wrapping_sub(seed);
+        }
+        shares[0].push(rest);
+    }
+    shares
+}
+
+#[test]
+fn sums_shared_columns_across_datasets() {
+    submit("d1", "age", split(&[30.0, 41.5, 62.0], 3, 1), None).unwrap();
+    submit("d2", "age", split(&[55.25, -2.0], 2, 2), None).unwrap();
+
+    let result = sum(&["d1".to_string(), "d2".to_string()], "age").unwrap();
+    assert_eq!(result.count, 5);
+    assert_eq!(result.dataset_count, 2);
+    assert!((result.sum - 186.75).abs() < 1e-9);
+    assert!((result.mean - 37.35).abs() < 1e-9);
+}
+
+#[test]
+fn rejects_malformed_shares_and_single_datasets() {
+    assert!(submit("bad", "age", vec![vec![1, 2]], None).is_err());
+    assert!(submit("bad", "age", vec![vec![1, 2], vec![3]], None).is_err());
+    assert!(submit("bad", "age", vec![vec![], vec![]], None).is_err());
+    assert!(submit("bad", "age", vec![vec![1, 2], vec![3, 4]], Some(vec![vec![1], vec![2]])).is_err());
+
+    submit("solo", "age", split(&[30.0], 2, 3), None).unwrap();
+    assert!(sum(&["solo".to_string()], "age").is_err());
+    assert!(sum(&["solo".to_string(), "solo".to_string()], "age").is_err());
+    assert!(sum(&["solo".to_string(), "missing".to_string()], "age").is_err());
+}
+
+fn submit_with_squares(dataset_id: &str, values: &[f64], seed: u64) {
+    let squares: Vec<f64> = values.iter().map(|v| v * v).collect();
+    submit(dataset_id, "days", split(values, 2, seed), Some(split(&squares, 3, seed + 1))).unwrap();
+}
+
+#[test]
+fn variance_comes_from_shared_values_and_squares() {
+    submit_with_squares("v1", &[2.0, 4.0, 4.0, 4.0], 10);
+    submit_with_squares("v2", &[5.0, 5.0, 7.0, 9.0], 20);
+    let datasets = vec!["v1".to_string(), "v2".to_string()];
+
+    let stats = statistics("run1", &datasets, "days").unwrap();
+    assert_eq!(stats.count, 8);
+    assert!((stats.mean - 5.0).abs() < 1e-9);
+    assert!((stats.variance - 32.0 / 7.0).abs() < 1e-9);
+    assert!((stats.std_dev - (32.0f64 / 7.0).sqrt()).abs() < 1e-9);
+
+    // Plain shares are not enough for a variance
+    submit("v3", "days", split(&[1.0, 2.0], 2, 30), None).unwrap();
+    assert!(statistics("run2", &["v1".to_string(), "v3".to_string()], "days").is_err());
+}
+
+#[test]
+fn count_statements_track_the_shares_behind_a_run() {
+    submit_with_squares("c1", &[1.0, 2.0], 40);
+    submit_with_squares("c2", &[3.0], 50);
+    statistics("counted", &["c1".to_string(), "c2".to_string()], "days").unwrap();
+
+    let statement = count_statement("counted").unwrap();
+    assert!(statement.contains("c1:2:") && statement.contains("c2:1:") && statement.ends_with("total:3]"));
+    assert!(count_statement("unknown").is_none());
+
+    // Resubmitted shares no longer match the statement
+    submit_with_squares("c2", &[3.0, 4.0], 60);
+    assert_ne!(count_statement("counted").unwrap(), statement);
+}
+
+#[test]
+fn masked_runs_unmask_only_the_totals() {
+    let owners: Vec<Principal> = (1..=3).map(|i| Principal::from_slice(&[i])).collect();
+    open_masked("masked", Principal::anonymous(), "days", owners.clone()).unwrap();
+    assert!(open_masked("dup", Principal::anonymous(), "days", vec![owners[0], owners[0]]).is_err());
+
+    // Masks that cancel across the three owners
+    let masks = [[7u64, 11, 13], [5, 3, 2], [0u64.wrapping_sub(12), 0u64.wrapping_sub(14), 0u64.wrapping_sub(15)]];
+    let totals = [[2.0, 6.0, 20.0], [4.0, 18.0, 82.0], [2.0, 16.0, 130.0]];
+    for (i, (owner, totals)) in owners.iter().zip(totals).enumerate() {
+        assert!(masked_statistics("masked", owners[0]).is_err());
+        let masked = totals.iter().zip(masks[i]).map(|(v, m)| ((v * SCALE).round() as i64 as u64).wrapping_add(m)).collect();
+        let result = submit_masked("masked", *owner, masked).unwrap();
+        assert_eq!(result.is_some(), i == 2);
+    }
+
+    let stats = masked_statistics("masked", Principal::anonymous()).unwrap();
+    assert_eq!((stats.count, stats.dataset_count), (8, 3));
+    assert!((stats.mean - 5.0).abs() < 1e-9);
+    assert!((stats.variance - 32.0 / 7.0).abs() < 1e-9);
+    assert!(masked_statistics("masked", Principal::from_slice(&[9])).is_err());
+}
@@ -0,0 +1,330 @@
+//! Time-series analysis over date columns
+//!
+//! Records of every dataset are grouped into consecutive periods of their
+//! date column, from the first period with records to the last. A period's
+//! value is the mean of the value column, or its record count when no value
+//! column is given. Periods holding fewer records than the disclosure
+//! policy's minimum cohort (but some) are suppressed: they have no value and
+//! their record count is reported as zero.
+//!
+//! Over the period values the analysis fits a least-squares linear trend,
+//! takes trailing moving averages, compares each period with the previous
+//! one and, given a season length, with the same period a season earlier,
+//! and decomposes the series into trend, seasonal and residual parts with
+//! classical additive decomposition. Statistics skip suppressed and empty
+//! periods.
+
+use candid::{CandidType, Deserialize};
+use std::collections::BTreeMap;
+use crate::analytics::is_date;
+use crate::tabular;
+
+/// Most periods one analysis may span
+pub const MAX_PERIODS: usize = 5000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Period {
+    Day,
+    /// Weeks start on Monday and are labelled by that date
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TimeSeriesRequest {
+    pub dataset_ids: Vec<String>,
+    pub date_column: String,
+    /// Numeric column averaged per period; periods count records without one
+    pub value_column: Option<String>,
+    pub period: Period,
+    /// Periods averaged by each moving average
+    pub window: u32,
+    /// Periods in a seasonal cycle, such as 12 for months of a year
+    pub season_length: Option<u32>,
+    /// Approved computation to run under when other parties' datasets are read
+    pub computation_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PeriodValue {
+    pub period: String,
+    pub records: u64,
+    pub value: Option<f64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Trend {
+    /// Change of the value per period
+    pub slope: f64,
+    /// Value at the first period
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PeriodChange {
+    pub period: String,
+    pub compared_to: String,
+    pub change: f64,
+    /// None when the earlier value is zero
+    pub percent_change: Option<f64>,
+}
+
+/// Additive decomposition, one entry per period
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Decomposition {
+    pub trend: Vec<Option<f64>>,
+    pub seasonal: Vec<f64>,
+    pub residual: Vec<Option<f64>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TimeSeriesAnalysis {
+    pub run_id: String,
+    pub periods: Vec<PeriodValue>,
+    pub trend: Option<Trend>,
+    pub moving_average: Vec<Option<f64>>,
+    pub period_over_period: Vec<PeriodChange>,
+    pub season_over_season: Vec<PeriodChange>,
+    pub seasonality: Option<Decomposition>,
+    pub suppressed_periods: u64,
+    /// Records left out for a missing or malformed date or value
+    pub skipped_records: u64,
+    /// Agent that interpreted the series, and what it found
+    pub agent_id: Option<String>,
+    pub insights: Option<String>,
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Index of the period a `YYYY-MM-DD` date falls in; consecutive periods
+/// have consecutive indices
+fn period_index(date: &str, period: Period) -> Option<i64> {
+    if !is_date(date) {
+        return None;
+    }
+    let (year, month, day) = (date[..4].parse::<i64>().ok()?, date[5..7].parse::<i64>().ok()?, date[8..].parse::<i64>().ok()?);
+    Some(match period {
+        Period::Day => days_from_civil(year, month, day),
+        // 1970-01-01 was a Thursday, three days after a Monday
+        Period::Week => (days_from_civil(year, month, day) + 3).div_euclid(7),
+        Period::Month => year * 12 + month - 1,
+        Period::Quarter => year * 4 + (month - 1) / 3,
+        Period::Year => year,
+    })
+}
+
+fn period_label(index: i64, period: Period) -> String {
+    let date = |days: i64| {
+        let (year, month, day) = civil_from_days(days);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    };
+    match period {
+        Period::Day => date(index),
+        Period::Week => date(index * 7 - 3),
+        Period::Month => format!("{:04}-{:02}", index.div_euclid(12), index.rem_euclid(12) + 1),
+        Period::Quarter => format!("{:04}-Q{}", index.div_euclid(4), index.rem_euclid(4) + 1),
+        Period::Year => format!("{:04}", index),
+    }
+}
+
+fn validate(request: &TimeSeriesRequest) -> Result<(), String> {
+    if request.dataset_ids.is_empty() {
+        return Err("Choose at least one dataset".to_string());
+    }
+    if request.window == 0 {
+        return Err("The moving average window must cover at least one period".to_string());
+    }
+    if request.season_length.is_some_and(|s| s < 2) {
+        return Err("A season spans at least two periods".to_string());
+    }
+    Ok(())
+}
+
+/// Records and value total of each period by index
+type Grouped = BTreeMap<i64, (u64, f64)>;
+
+/// Records and value total of every period, and the records left out
+fn group(request: &TimeSeriesRequest, datasets: &[String]) -> Result<(Grouped, u64), String> {
+    let mut periods = Grouped::new();
+    let mut skipped = 0;
+    for data in datasets {
+        let table = tabular::parse(data.as_bytes())?;
+        let position = |column: &str| table.header.iter().position(|h| h == column)
+            .ok_or_else(|| format!("Column '{}' not found", column));
+        let date = position(&request.date_column)?;
+        let value = request.value_column.as_deref().map(position).transpose()?;
+        for row in &table.rows {
+            let index = period_index(&row[date], request.period);
+            let value = match value {
+                Some(column) => row[column].parse::<f64>().ok().filter(|v| v.is_finite()),
+                None => Some(0.0),
+            };
+            let (Some(index), Some(value)) = (index, value) else {
+                skipped += 1;
+                continue;
+            };
+            let entry = periods.entry(index).or_default();
+            entry.0 += 1;
+            entry.1 += value;
+        }
+    }
+    Ok((periods, skipped))
+}
+
+fn trend(values: &[Option<f64>]) -> Option<Trend> {
+    let points: Vec<(f64, f64)> = values.iter().enumerate().filter_map(|(i, v)| v.map(|v| (i as f64, v))).collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let syy: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    let slope = sxy / sxx;
+    let r_squared = if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) };
+    Some(Trend { slope, intercept: mean_y - slope * mean_x, r_squared })
+}
+
+fn mean(values: &[Option<f64>]) -> Option<f64> {
+    let present: Vec<f64> = values.iter().flatten().copied().collect();
+    (!present.is_empty()).then(|| present.iter().sum::<f64>() / present.len() as f64)
+}
+
+/// Mean of each period's value and the `window - 1` before it
+fn moving_average(values: &[Option<f64>], window: usize) -> Vec<Option<f64>> {
+    (0..values.len()).map(|i| mean(&values[(i + 1).saturating_sub(window)..=i])).collect()
+}
+
+fn changes(labels: &[String], values: &[Option<f64>], lag: usize) -> Vec<PeriodChange> {
+    (lag..values.len()).filter_map(|i| {
+        let (earlier, value) = (values[i - lag]?, values[i]?);
+        Some(PeriodChange {
+            period: labels[i].clone(),
+            compared_to: labels[i - lag].clone(),
+            change: value - earlier,
+            percent_change: (earlier != 0.0).then(|| (value - earlier) / earlier.abs() * 100.0),
+        })
+    }).collect()
+}
+
+/// Classical additive decomposition; needs two full seasons
+fn decompose(values: &[Option<f64>], season: usize) -> Option<Decomposition> {
+    let n = values.len();
+    if n < 2 * season {
+        return None;
+    }
+    // Centred moving average over one season, weighting the ends by half for
+    // even seasons so the window stays centred
+    let half = season / 2;
+    let trend: Vec<Option<f64>> = (0..n).map(|i| {
+        if i < half || i + half >= n {
+            return None;
+        }
+        let window = &values[i - half..=i + half];
+        let window: Vec<f64> = window.iter().copied().collect::<Option<_>>()?;
+        Some(if season.is_multiple_of(2) {
+            (window[0] / 2.0 + window[1..season].iter().sum::<f64>() + window[season] / 2.0) / season as f64
+        } else {
+            window.iter().sum::<f64>() / season as f64
+        })
+    }).collect();
+
+    let detrended: Vec<Option<f64>> = values.iter().zip(&trend).map(|(v, t)| Some((*v)? - (*t)?)).collect();
+    let indices: Vec<f64> = (0..season).map(|position| {
+        let at: Vec<Option<f64>> = detrended.iter().skip(position).step_by(season).copied().collect();
+        mean(&at).unwrap_or(0.0)
+    }).collect();
+    let centre = indices.iter().sum::<f64>() / season as f64;
+    let seasonal: Vec<f64> = (0..n).map(|i| indices[i % season] - centre).collect();
+    let residual = values.iter().zip(&trend).zip(&seasonal).map(|((v, t), s)| Some((*v)? - (*t)? - s)).collect();
+    Some(Decomposition { trend, seasonal, residual })
+}
+
+/// Analyze the decrypted `datasets`, suppressing periods with fewer than
+/// `min_cohort` records
+pub fn analyze(run_id: &str, request: &TimeSeriesRequest, datasets: &[String], min_cohort: u64) -> Result<TimeSeriesAnalysis, String> {
+    validate(request)?;
+    let (grouped, skipped_records) = group(request, datasets)?;
+    let (Some(&first), Some(&last)) = (grouped.keys().next(), grouped.keys().next_back()) else {
+        return Err(format!("No records have a date in column '{}'", request.date_column));
+    };
+    if (last - first) as usize >= MAX_PERIODS {
+        return Err(format!("The records span more than {} periods; choose a longer period", MAX_PERIODS));
+    }
+
+    let mut suppressed_periods = 0;
+    let periods: Vec<PeriodValue> = (first..=last).map(|index| {
+        let (records, total) = grouped.get(&index).copied().unwrap_or_default();
+        let period = period_label(index, request.period);
+        if records > 0 && records < min_cohort {
+            suppressed_periods += 1;
+            return PeriodValue { period, records: 0, value: None };
+        }
+        let value = match request.value_column {
+            Some(_) => (records > 0).then(|| total / records as f64),
+            None => Some(records as f64),
+        };
+        PeriodValue { period, records, value }
+    }).collect();
+
+    let labels: Vec<String> = periods.iter().map(|p| p.period.clone()).collect();
+    let values: Vec<Option<f64>> = periods.iter().map(|p| p.value).collect();
+    let season = request.season_length.map(|s| s as usize);
+    Ok(TimeSeriesAnalysis {
+        run_id: run_id.to_string(),
+        trend: trend(&values),
+        moving_average: moving_average(&values, request.window as usize),
+        period_over_period: changes(&labels, &values, 1),
+        season_over_season: season.map(|s| changes(&labels, &values, s)).unwrap_or_default(),
+        seasonality: season.and_then(|s| decompose(&values, s)),
+        periods,
+        suppressed_periods,
+        skipped_records,
+        agent_id: None,
+        insights: None,
+    })
+}
+
+/// Prompt asking an agent to interpret an analysis; only per-period
+/// aggregates are included
+pub fn prompt(request: &TimeSeriesRequest, analysis: &TimeSeriesAnalysis) -> String {
+    let measure = request.value_column.as_deref().map_or("record count".to_string(), |c| format!("mean {}", c));
+    let series: Vec<String> = analysis.periods.iter()
+        .map(|p| format!("{} {}", p.period, p.value.map_or("suppressed".to_string(), |v| format!("{:.2}", v))))
+        .collect();
+    let trend = analysis.trend.as_ref()
+        .map_or("no trend".to_string(), |t| format!("trend {:+.3} per period (R² {:.2})", t.slope, t.r_squared));
+    format!(
+        "Interpret the {} per {:?} of '{}': {}. Fitted {}. Describe trends, seasonality and notable changes.",
+        measure, request.period, request.date_column, series.join(", "), trend,
+    )
+}
+
+#[cfg(test)]
+#[path = "time_series_test.rs"]
+mod tests;
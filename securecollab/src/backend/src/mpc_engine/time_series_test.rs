@@ -0,0 +1,81 @@
+use super::*;
+
+fn request(value_column: Option<&str>, period: Period, season_length: Option<u32>) -> TimeSeriesRequest {
+    TimeSeriesRequest {
+        dataset_ids: vec!["a".to_string()],
+        date_column: "visit".to_string(),
+        value_column: value_column.map(str::to_string),
+        period,
+        window: 3,
+        season_length,
+        computation_id: None,
+    }
+}
+
+#[test]
+fn converts_between_dates_and_periods() {
+    assert_eq!(days_from_civil(1970, 1, 1), 0);
+    assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    // 2024-03-07 is a Thursday in the week starting Monday 2024-03-04
+    let week = period_index("2024-03-07", Period::Week).unwrap();
+    assert_eq!(period_label(week, Period::Week), "2024-03-04");
+    assert_eq!(period_index("2024-03-10", Period::Week), Some(week));
+    assert_eq!(period_label(period_index("2024-11-30", Period::Quarter).unwrap(), Period::Quarter), "2024-Q4");
+    assert_eq!(period_label(period_index("2024-11-30", Period::Month).unwrap(), Period::Month), "2024-11");
+    assert_eq!(period_index("2024-02-30", Period::Day), None);
+}
+
+#[test]
+fn means_per_period_with_trend_and_changes() {
+    let mut rows = vec!["visit,hba1c".to_string()];
+    for (month, value) in [(1, 6.0), (2, 7.0), (4, 9.0)] {
+        for day in 1..=5 {
+            rows.push(format!("2024-{:02}-{:02},{}", month, day, value));
+        }
+    }
+    rows.push("not a date,5".to_string());
+    let analysis = analyze("run", &request(Some("hba1c"), Period::Month, None), &[rows.join("\n")], 5).unwrap();
+
+    let labels: Vec<&str> = analysis.periods.iter().map(|p| p.period.as_str()).collect();
+    assert_eq!(labels, vec!["2024-01", "2024-02", "2024-03", "2024-04"]);
+    assert_eq!(analysis.periods[2], PeriodValue { period: "2024-03".to_string(), records: 0, value: None });
+    assert_eq!(analysis.skipped_records, 1);
+    let trend = analysis.trend.unwrap();
+    assert!((trend.slope - 1.0).abs() < 1e-9 && (trend.intercept - 6.0).abs() < 1e-9);
+    assert_eq!(analysis.moving_average[3], Some(8.0));
+    assert_eq!(analysis.period_over_period.len(), 1);
+    assert!((analysis.period_over_period[0].percent_change.unwrap() - 100.0 / 6.0).abs() < 1e-9);
+}
+
+#[test]
+fn suppresses_small_periods() {
+    let data = "visit\n2024-01-01\n2024-01-02\n2024-02-01\n2024-02-02\n2024-02-03";
+    let analysis = analyze("run", &request(None, Period::Month, None), &[data.to_string()], 3).unwrap();
+    assert_eq!(analysis.suppressed_periods, 1);
+    assert_eq!(analysis.periods[0].value, None);
+    assert_eq!(analysis.periods[1].value, Some(3.0));
+}
+
+#[test]
+fn decomposes_a_seasonal_series() {
+    // A rising line plus a pattern repeating every four periods
+    let pattern = [2.0, -1.0, 0.0, -1.0];
+    let values: Vec<Option<f64>> = (0..16).map(|i| Some(i as f64 + pattern[i % 4])).collect();
+    let decomposition = decompose(&values, 4).unwrap();
+    for (i, seasonal) in decomposition.seasonal.iter().enumerate() {
+        assert!((seasonal - pattern[i % 4]).abs() < 1e-9);
+    }
+    assert_eq!(decomposition.trend[0], None);
+    assert!((decomposition.trend[5].unwrap() - 5.0).abs() < 1e-9);
+    assert!(decomposition.residual.iter().flatten().all(|r| r.abs() < 1e-9));
+    assert!(decompose(&values[..7], 4).is_none());
+}
+
+#[test]
+fn rejects_invalid_requests() {
+    let data = ["visit\n2024-01-01".to_string()];
+    assert!(analyze("run", &TimeSeriesRequest { window: 0, ..request(None, Period::Day, None) }, &data, 1).is_err());
+    assert!(analyze("run", &request(None, Period::Day, Some(1)), &data, 1).is_err());
+    assert!(analyze("run", &request(Some("missing"), Period::Day, None), &data, 1).is_err());
+}
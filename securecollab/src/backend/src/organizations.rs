@@ -0,0 +1,222 @@
+//! Organizations and their workspaces
+//!
+//! An organization groups the principals of one consortium. Its id names its
+//! workspace, and datasets, queries and computations belong to the workspace
+//! of the principal who created them; principals outside any organization,
+//! and everything created before organizations, share the default workspace.
+//! A principal belongs to one organization at a time.
+//!
+//! Listings only show a workspace its own resources and those shared with it.
+//! Resources of another workspace cannot be used until their owner shares
+//! them with the workspace explicitly.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::DEFAULT_WORKSPACE;
+use crate::backup;
+
+pub const MAX_NAME_LENGTH: usize = 100;
+pub const MAX_MEMBERS: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Organization {
+    /// Also the id of its workspace
+    pub id: String,
+    pub name: String,
+    pub admins: Vec<Principal>,
+    pub members: Vec<Principal>,
+    pub created_at: u64,
+}
+
+thread_local! {
+    static ORGANIZATIONS: RefCell<HashMap<String, Organization>> = RefCell::new(HashMap::new());
+    static MEMBERSHIP: RefCell<HashMap<Principal, String>> = RefCell::new(HashMap::new());
+    // Workspace each resource was created in
+    static RESOURCES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    // Other workspaces each resource is shared with
+    static SHARES: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+pub fn workspace_of(principal: Principal) -> String {
+    MEMBERSHIP.with(|membership| membership.borrow().get(&principal).cloned())
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string())
+}
+
+pub fn get(id: &str) -> Result<Organization, String> {
+    ORGANIZATIONS.with(|orgs| orgs.borrow().get(id).cloned())
+        .ok_or_else(|| format!("Organization {} not found", id))
+}
+
+pub fn exists(workspace: &str) -> bool {
+    workspace == DEFAULT_WORKSPACE || ORGANIZATIONS.with(|orgs| orgs.borrow().contains_key(workspace))
+}
+
+fn ensure_unaffiliated(principal: Principal) -> Result<(), String> {
+    if workspace_of(principal) != DEFAULT_WORKSPACE {
+        return Err(format!("{} already belongs to an organization", principal.to_text()));
+    }
+    Ok(())
+}
+
+/// Found an organization administered by `founder`
+pub fn create(id: String, name: String, founder: Principal, now: u64) -> Result<Organization, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Organization names are 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    if founder == Principal::anonymous() {
+        return Err("Anonymous principals cannot found organizations".to_string());
+    }
+    ensure_unaffiliated(founder)?;
+    let organization = Organization { id: id.clone(), name, admins: vec![founder], members: vec![founder], created_at: now };
+    ORGANIZATIONS.with(|orgs| orgs.borrow_mut().insert(id.clone(), organization.clone()));
+    MEMBERSHIP.with(|membership| membership.borrow_mut().insert(founder, id));
+    Ok(organization)
+}
+
+fn update(id: &str, admin: Principal, change: impl FnOnce(&mut Organization) -> Result<(), String>) -> Result<Organization, String> {
+    ORGANIZATIONS.with(|orgs| {
+        let mut orgs = orgs.borrow_mut();
+        let organization = orgs.get_mut(id).ok_or_else(|| format!("Organization {} not found", id))?;
+        if !organization.admins.contains(&admin) {
+            return Err("Only organization admins can change its members".to_string());
+        }
+        change(organization)?;
+        Ok(organization.clone())
+    })
+}
+
+pub fn add_member(id: &str, admin: Principal, member: Principal, as_admin: bool) -> Result<Organization, String> {
+    if member == Principal::anonymous() {
+        return Err("Anonymous principals cannot join organizations".to_string());
+    }
+    if !(as_admin && workspace_of(member) == id) {
+        ensure_unaffiliated(member)?;
+    }
+    let organization = update(id, admin, |organization| {
+        if !organization.members.contains(&member) {
+            if organization.members.len() >= MAX_MEMBERS {
+                return Err(format!("Organizations have at most {} members", MAX_MEMBERS));
+            }
+            organization.members.push(member);
+        }
+        if as_admin && !organization.admins.contains(&member) {
+            organization.admins.push(member);
+        }
+        Ok(())
+    })?;
+    MEMBERSHIP.with(|membership| membership.borrow_mut().insert(member, id.to_string()));
+    Ok(organization)
+}
+
+/// Remove a member, who returns to the default workspace; their resources stay
+pub fn remove_member(id: &str, admin: Principal, member: Principal) -> Result<Organization, String> {
+    let organization = update(id, admin, |organization| {
+        if !organization.members.contains(&member) {
+            return Err(format!("{} is not a member", member.to_text()));
+        }
+        if organization.admins == [member] {
+            return Err("An organization keeps at least one admin".to_string());
+        }
+        organization.members.retain(|m| *m != member);
+        organization.admins.retain(|m| *m != member);
+        Ok(())
+    })?;
+    MEMBERSHIP.with(|membership| membership.borrow_mut().remove(&member));
+    crate::roles::clear(member);
+    Ok(organization)
+}
+
+/// Take admin rights from a member, who stays in the organization
+pub fn demote(id: &str, admin: Principal, member: Principal) -> Result<Organization, String> {
+    update(id, admin, |organization| {
+        if !organization.admins.contains(&member) {
+            return Err(format!("{} is not an admin", member.to_text()));
+        }
+        if organization.admins == [member] {
+            return Err("An organization keeps at least one admin".to_string());
+        }
+        organization.admins.retain(|m| *m != member);
+        Ok(())
+    })
+}
+
+/// Place a new resource in the workspace of the principal creating it
+pub fn assign(resource_id: &str, creator: Principal) {
+    let workspace = workspace_of(creator);
+    RESOURCES.with(|resources| resources.borrow_mut().insert(resource_id.to_string(), workspace));
+}
+
+pub fn resource_workspace(resource_id: &str) -> String {
+    RESOURCES.with(|resources| resources.borrow().get(resource_id).cloned())
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string())
+}
+
+pub fn share(resource_id: &str, workspace: &str) -> Result<Vec<String>, String> {
+    if !exists(workspace) {
+        return Err(format!("Workspace {} not found", workspace));
+    }
+    if resource_workspace(resource_id) == workspace {
+        return Err(format!("{} already belongs to workspace {}", resource_id, workspace));
+    }
+    SHARES.with(|shares| {
+        let mut shares = shares.borrow_mut();
+        let shared = shares.entry(resource_id.to_string()).or_default();
+        if !shared.iter().any(|w| w == workspace) {
+            shared.push(workspace.to_string());
+        }
+        Ok(shared.clone())
+    })
+}
+
+pub fn unshare(resource_id: &str, workspace: &str) -> Vec<String> {
+    SHARES.with(|shares| {
+        let mut shares = shares.borrow_mut();
+        let shared = shares.entry(resource_id.to_string()).or_default();
+        shared.retain(|w| w != workspace);
+        shared.clone()
+    })
+}
+
+/// Whether a resource belongs to, or is shared with, the principal's workspace
+pub fn visible_to(resource_id: &str, principal: Principal) -> bool {
+    let workspace = workspace_of(principal);
+    resource_workspace(resource_id) == workspace
+        || SHARES.with(|shares| shares.borrow().get(resource_id).is_some_and(|shared| shared.contains(&workspace)))
+}
+
+/// Like `visible_to`, but resources never placed in a workspace, such as
+/// statistics runs, are visible to everyone
+pub fn visible_or_unassigned(resource_id: &str, principal: Principal) -> bool {
+    !RESOURCES.with(|resources| resources.borrow().contains_key(resource_id)) || visible_to(resource_id, principal)
+}
+
+pub fn ensure_visible(resource_id: &str, principal: Principal) -> Result<(), String> {
+    if !visible_to(resource_id, principal) {
+        return Err(format!("{} belongs to another workspace and is not shared with yours", resource_id));
+    }
+    Ok(())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&ORGANIZATIONS),
+        backup::cloned(&MEMBERSHIP),
+        backup::cloned(&RESOURCES),
+        backup::cloned(&SHARES),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (organizations, membership, resources, shares) = backup::decode(bytes)?;
+    backup::replace(&ORGANIZATIONS, organizations);
+    backup::replace(&MEMBERSHIP, membership);
+    backup::replace(&RESOURCES, resources);
+    backup::replace(&SHARES, shares);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "organizations_test.rs"]
+mod tests;
@@ -0,0 +1,59 @@
+use super::*;
+use crate::test_support::principal;
+
+#[test]
+fn members_belong_to_one_organization() {
+    let (founder, member, other) = (principal(1), principal(2), principal(3));
+    assert_eq!(workspace_of(founder), DEFAULT_WORKSPACE);
+    let org = create("org_a".to_string(), " Oncology Consortium ".to_string(), founder, 10).unwrap();
+    assert_eq!(org.name, "Oncology Consortium");
+    assert_eq!(workspace_of(founder), "org_a");
+    assert!(create("org_b".to_string(), "Second".to_string(), founder, 20).is_err());
+
+    assert!(add_member("org_a", member, other, false).is_err());
+    add_member("org_a", founder, member, false).unwrap();
+    assert_eq!(workspace_of(member), "org_a");
+    create("org_c".to_string(), "Cardiology".to_string(), other, 30).unwrap();
+    assert!(add_member("org_a", founder, other, false).is_err());
+
+    // Promote a member, then the founder can leave
+    let org = add_member("org_a", founder, member, true).unwrap();
+    assert_eq!(org.admins, vec![founder, member]);
+    remove_member("org_a", member, founder).unwrap();
+    assert_eq!(workspace_of(founder), DEFAULT_WORKSPACE);
+    assert!(remove_member("org_a", member, member).is_err());
+}
+
+#[test]
+fn rejects_unnamed_organizations() {
+    assert!(create("org_unnamed".to_string(), "  ".to_string(), principal(4), 0).is_err());
+    assert!(create("org_long".to_string(), "x".repeat(MAX_NAME_LENGTH + 1), principal(4), 0).is_err());
+    assert!(create("org_anon".to_string(), "Anon".to_string(), Principal::anonymous(), 0).is_err());
+}
+
+#[test]
+fn resources_are_visible_in_their_workspace_or_when_shared() {
+    let (alice, bob, carol) = (principal(5), principal(6), principal(7));
+    create("org_alice".to_string(), "Alice Lab".to_string(), alice, 0).unwrap();
+    create("org_bob".to_string(), "Bob Lab".to_string(), bob, 0).unwrap();
+    assign("dataset_alice", alice);
+    assign("dataset_legacy", carol);
+
+    assert!(visible_to("dataset_alice", alice));
+    assert!(!visible_to("dataset_alice", bob));
+    assert!(ensure_visible("dataset_alice", carol).is_err());
+    assert!(visible_to("dataset_legacy", carol));
+    assert!(visible_to("dataset_unassigned", carol));
+    assert!(!visible_to("dataset_unassigned", bob));
+    assert!(visible_or_unassigned("dataset_unassigned", bob));
+    assert!(!visible_or_unassigned("dataset_alice", bob));
+
+    assert_eq!(share("dataset_alice", "org_bob").unwrap(), vec!["org_bob"]);
+    assert!(share("dataset_alice", "org_alice").is_err());
+    assert!(share("dataset_alice", "org_missing").is_err());
+    assert!(visible_to("dataset_alice", bob));
+    assert!(!visible_to("dataset_alice", carol));
+
+    assert!(unshare("dataset_alice", "org_bob").is_empty());
+    assert!(!visible_to("dataset_alice", bob));
+}
@@ -0,0 +1,112 @@
+//! Emergency pause
+//!
+//! During a suspected compromise any canister controller can pause the
+//! canister, which at once stops every endpoint that decrypts data or runs
+//! a computation; reads, uploads and governance keep working. Unpausing
+//! needs approvals from several distinct controllers, so a single stolen
+//! controller key can pause the canister but not resume it. The number of
+//! approvals can only be changed while the canister is running.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+
+pub const DEFAULT_REQUIRED_APPROVALS: u32 = 2;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PauseState {
+    pub paused: bool,
+    pub reason: Option<String>,
+    pub paused_by: Option<Principal>,
+    pub paused_at: Option<u64>,
+    /// Controllers who approved unpausing since the last pause
+    pub unpause_approvals: Vec<Principal>,
+    pub required_approvals: u32,
+}
+
+impl Default for PauseState {
+    fn default() -> Self {
+        PauseState {
+            paused: false,
+            reason: None,
+            paused_by: None,
+            paused_at: None,
+            unpause_approvals: Vec::new(),
+            required_approvals: DEFAULT_REQUIRED_APPROVALS,
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<PauseState> = RefCell::new(PauseState::default());
+}
+
+pub fn state() -> PauseState {
+    STATE.with(|state| state.borrow().clone())
+}
+
+pub fn ensure_running() -> Result<(), String> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        if state.paused {
+            return Err(format!("The canister is paused: {}", state.reason.as_deref().unwrap_or("no reason given")));
+        }
+        Ok(())
+    })
+}
+
+pub fn pause(admin: Principal, reason: String, now: u64) -> Result<PauseState, String> {
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err("Give a reason for pausing".to_string());
+    }
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.paused {
+            return Err("The canister is already paused".to_string());
+        }
+        state.paused = true;
+        state.reason = Some(reason);
+        state.paused_by = Some(admin);
+        state.paused_at = Some(now);
+        state.unpause_approvals.clear();
+        Ok(state.clone())
+    })
+}
+
+/// Approve unpausing; the canister resumes once enough distinct
+/// controllers have approved
+pub fn approve_unpause(admin: Principal) -> Result<PauseState, String> {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if !state.paused {
+            return Err("The canister is not paused".to_string());
+        }
+        if state.unpause_approvals.contains(&admin) {
+            return Err("You already approved unpausing".to_string());
+        }
+        state.unpause_approvals.push(admin);
+        if state.unpause_approvals.len() as u32 >= state.required_approvals {
+            state.paused = false;
+            state.unpause_approvals.clear();
+        }
+        Ok(state.clone())
+    })
+}
+
+pub fn set_required_approvals(approvals: u32) -> Result<PauseState, String> {
+    if approvals == 0 {
+        return Err("Unpausing needs at least one approval".to_string());
+    }
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.paused {
+            return Err("The approvals needed cannot change while paused".to_string());
+        }
+        state.required_approvals = approvals;
+        Ok(state.clone())
+    })
+}
+
+#[cfg(test)]
+#[path = "pause_test.rs"]
+mod tests;
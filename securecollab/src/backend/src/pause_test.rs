@@ -0,0 +1,33 @@
+use super::*;
+use crate::test_support::principal;
+
+#[test]
+fn unpausing_needs_distinct_approvals() {
+    let (first, second) = (principal(1), principal(2));
+    assert!(ensure_running().is_ok());
+    assert!(pause(first, " ".to_string(), 10).is_err());
+    let paused = pause(first, "Suspected key leak".to_string(), 10).unwrap();
+    assert_eq!((paused.paused_by, paused.paused_at), (Some(first), Some(10)));
+    assert!(ensure_running().unwrap_err().contains("Suspected key leak"));
+    assert!(pause(second, "Again".to_string(), 20).is_err());
+
+    assert!(approve_unpause(first).unwrap().paused);
+    assert!(approve_unpause(first).is_err());
+    assert!(ensure_running().is_err());
+    let resumed = approve_unpause(second).unwrap();
+    assert!(!resumed.paused && resumed.unpause_approvals.is_empty());
+    assert!(ensure_running().is_ok());
+    assert!(approve_unpause(first).is_err());
+}
+
+#[test]
+fn approvals_needed_only_change_while_running() {
+    assert!(set_required_approvals(0).is_err());
+    assert_eq!(set_required_approvals(3).unwrap().required_approvals, 3);
+    pause(principal(1), "Drill".to_string(), 0).unwrap();
+    assert!(set_required_approvals(1).is_err());
+    approve_unpause(principal(1)).unwrap();
+    approve_unpause(principal(2)).unwrap();
+    assert!(state().paused);
+    assert!(!approve_unpause(principal(3)).unwrap().paused);
+}
@@ -0,0 +1,276 @@
+//! ICRC-1/ICRC-2 payments for agent computations
+//!
+//! A computation that names an agent is paid for up front: the requester
+//! approves the canister on the configured ICRC-2 ledger and the agent's
+//! `price_per_computation` is pulled into escrow with `icrc2_transfer_from`.
+//! Released fees accrue per agent until the agent's owner withdraws them
+//! with an ICRC-1 transfer.
+//!
+//! Transfers carry a deterministic memo and their creation time, so the ledger
+//! deduplicates a retry with the same arguments. When a call's outcome is
+//! unknown the transfer is kept with those arguments until it is reconciled,
+//! rather than assumed to have failed.
+
+use candid::{CandidType, Deserialize, Nat, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use ic_cdk::api::call::RejectionCode;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg, TransferError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+use crate::backup;
+
+pub mod escrow;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentPayment {
+    pub computation_id: String,
+    pub agent_id: String,
+    pub payer: Principal,
+    pub amount: u64,
+    pub block_index: Nat,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentWithdrawal {
+    pub agent_id: String,
+    pub to: Principal,
+    pub amount: u64,
+    pub block_index: Nat,
+    pub timestamp: u64,
+}
+
+/// An outbound transfer, kept with its exact arguments while its outcome is unknown
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingTransfer {
+    pub to: Principal,
+    pub amount: u64,
+    pub fee: u64,
+    pub memo: Vec<u8>,
+    pub created_at_time: u64,
+    pub last_error: Option<String>,
+}
+
+/// Why a ledger call did not return a block index
+#[derive(Debug, PartialEq)]
+pub enum TransferFailure {
+    /// No funds moved
+    Rejected(String),
+    /// Funds may or may not have moved
+    Unknown(String),
+}
+
+thread_local! {
+    static LEDGER: RefCell<Option<Principal>> = const { RefCell::new(None) };
+    static BALANCES: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+    static PAYMENTS: RefCell<Vec<AgentPayment>> = const { RefCell::new(Vec::new()) };
+    /// Withdrawals with an unknown outcome, by agent; their amount stays debited
+    static UNSETTLED_WITHDRAWALS: RefCell<HashMap<String, PendingTransfer>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_ledger(ledger: Option<Principal>) {
+    LEDGER.with(|l| *l.borrow_mut() = ledger);
+}
+
+pub fn ledger() -> Result<Principal, String> {
+    LEDGER.with(|l| *l.borrow())
+        .ok_or_else(|| "Payment ledger is not configured".to_string())
+}
+
+pub fn balance(agent_id: &str) -> u64 {
+    BALANCES.with(|balances| balances.borrow().get(agent_id).copied().unwrap_or(0))
+}
+
+fn credit(agent_id: &str, amount: u64) {
+    BALANCES.with(|balances| {
+        let mut balances = balances.borrow_mut();
+        let balance = balances.entry(agent_id.to_string()).or_insert(0);
+        *balance = balance.saturating_add(amount);
+    });
+}
+
+fn canister_account() -> Account {
+    Account { owner: ic_cdk::id(), subaccount: None }
+}
+
+/// Deterministic 32-byte memo for a transfer of `kind` about `id`
+fn memo(kind: &str, id: &str) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(format!("securecollab-{}", kind).as_bytes());
+    hasher.update(id.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+// Only a reject whose outcome the system could not determine leaves the
+// transfer in doubt; every other reject means the ledger did not execute it
+fn call_failure(code: RejectionCode, message: String) -> TransferFailure {
+    let message = format!("Payment ledger call failed: {:?} {}", code, message);
+    match code {
+        RejectionCode::Unknown => TransferFailure::Unknown(message),
+        _ => TransferFailure::Rejected(message),
+    }
+}
+
+// A duplicate is the earlier, identical transfer having gone through; a
+// transfer too old to deduplicate may or may not have
+fn transfer_outcome(result: Result<Nat, TransferError>) -> Result<Nat, TransferFailure> {
+    match result {
+        Ok(block_index) | Err(TransferError::Duplicate { duplicate_of: block_index }) => Ok(block_index),
+        Err(e @ TransferError::TooOld) => Err(TransferFailure::Unknown(format!("Transfer failed: {:?}", e))),
+        Err(e) => Err(TransferFailure::Rejected(format!("Transfer failed: {:?}", e))),
+    }
+}
+
+impl TransferFailure {
+    fn message(&self) -> &str {
+        match self {
+            TransferFailure::Rejected(message) | TransferFailure::Unknown(message) => message,
+        }
+    }
+}
+
+fn record_payment(payment: AgentPayment) {
+    PAYMENTS.with(|payments| payments.borrow_mut().push(payment));
+}
+
+async fn ledger_fee(ledger: Principal) -> Result<u64, String> {
+    let (fee,): (Nat,) = ic_cdk::call(ledger, "icrc1_fee", ()).await
+        .map_err(|(code, msg)| format!("Payment ledger call failed: {:?} {}", code, msg))?;
+    u64::try_from(fee.0).map_err(|_| "Ledger fee out of range".to_string())
+}
+
+/// Pull `amount` from the payer's approved allowance into the canister account
+async fn pull(payer: Principal, amount: u64, computation_id: &str) -> Result<Nat, String> {
+    let ledger = ledger()?;
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account { owner: payer, subaccount: None },
+        to: canister_account(),
+        amount: Nat::from(amount),
+        fee: None,
+        memo: Some(Memo::from(memo("escrow", computation_id))),
+        created_at_time: Some(ic_cdk::api::time()),
+    };
+    let (result,): (Result<Nat, TransferFromError>,) = ic_cdk::call(ledger, "icrc2_transfer_from", (args,)).await
+        .map_err(|(code, msg)| call_failure(code, msg).message().to_string())?;
+    match result {
+        Ok(block_index) | Err(TransferFromError::Duplicate { duplicate_of: block_index }) => Ok(block_index),
+        Err(e) => Err(format!("Payment of {} failed: {:?}", amount, e)),
+    }
+}
+
+impl PendingTransfer {
+    fn new(to: Principal, amount: u64, fee: u64, memo: Vec<u8>) -> Self {
+        PendingTransfer { to, amount, fee, memo, created_at_time: ic_cdk::api::time(), last_error: None }
+    }
+}
+
+/// Send a transfer from the canister account; the ledger fee is charged on top
+async fn push(transfer: &PendingTransfer) -> Result<Nat, TransferFailure> {
+    let ledger = ledger().map_err(TransferFailure::Rejected)?;
+    let args = TransferArg {
+        from_subaccount: None,
+        to: Account { owner: transfer.to, subaccount: None },
+        fee: Some(Nat::from(transfer.fee)),
+        created_at_time: Some(transfer.created_at_time),
+        memo: Some(Memo::from(transfer.memo.clone())),
+        amount: Nat::from(transfer.amount),
+    };
+    let (result,): (Result<Nat, TransferError>,) = ic_cdk::call(ledger, "icrc1_transfer", (args,)).await
+        .map_err(|(code, msg)| call_failure(code, msg))?;
+    transfer_outcome(result)
+}
+
+/// Transfer an agent's accrued balance to `to`; the ledger fee comes out of it
+pub async fn withdraw(agent_id: &str, to: Principal) -> Result<AgentWithdrawal, String> {
+    if unsettled_withdrawal(agent_id).is_some() {
+        return Err(format!("A withdrawal for {} has an unknown outcome and must be reconciled first", agent_id));
+    }
+    let ledger = ledger()?;
+    let fee = ledger_fee(ledger).await?;
+
+    let accrued = balance(agent_id);
+    if accrued <= fee {
+        return Err(format!("Balance of {} does not cover the ledger fee of {}", accrued, fee));
+    }
+
+    // Debit before the call so concurrent withdrawals cannot spend it twice
+    BALANCES.with(|balances| balances.borrow_mut().insert(agent_id.to_string(), 0));
+    let transfer = PendingTransfer::new(to, accrued - fee, fee, memo("withdrawal", agent_id));
+    match push(&transfer).await {
+        Ok(block_index) => Ok(withdrawal(agent_id, &transfer, block_index)),
+        Err(TransferFailure::Rejected(e)) => {
+            credit(agent_id, accrued);
+            Err(e)
+        },
+        Err(TransferFailure::Unknown(e)) => Err(hold_withdrawal(agent_id, transfer, e)),
+    }
+}
+
+/// Retry a withdrawal whose outcome was unknown with its original arguments;
+/// the ledger deduplicates it if the first attempt went through
+pub async fn reconcile_withdrawal(agent_id: &str) -> Result<AgentWithdrawal, String> {
+    // Taken out for the call so concurrent reconciliations cannot credit it twice
+    let transfer = UNSETTLED_WITHDRAWALS.with(|unsettled| unsettled.borrow_mut().remove(agent_id))
+        .ok_or_else(|| format!("No withdrawal for {} is awaiting reconciliation", agent_id))?;
+    match push(&transfer).await {
+        Ok(block_index) => Ok(withdrawal(agent_id, &transfer, block_index)),
+        Err(TransferFailure::Rejected(e)) => {
+            credit(agent_id, transfer.amount + transfer.fee);
+            Err(e)
+        },
+        Err(TransferFailure::Unknown(e)) => Err(hold_withdrawal(agent_id, transfer, e)),
+    }
+}
+
+pub fn unsettled_withdrawal(agent_id: &str) -> Option<PendingTransfer> {
+    UNSETTLED_WITHDRAWALS.with(|unsettled| unsettled.borrow().get(agent_id).cloned())
+}
+
+// Keep a withdrawal with an unknown outcome, and its debit, for reconciliation
+fn hold_withdrawal(agent_id: &str, mut transfer: PendingTransfer, error: String) -> String {
+    let message = format!("Withdrawal outcome is unknown and awaits reconciliation: {}", error);
+    transfer.last_error = Some(error);
+    UNSETTLED_WITHDRAWALS.with(|unsettled| unsettled.borrow_mut().insert(agent_id.to_string(), transfer));
+    message
+}
+
+fn withdrawal(agent_id: &str, transfer: &PendingTransfer, block_index: Nat) -> AgentWithdrawal {
+    AgentWithdrawal {
+        agent_id: agent_id.to_string(),
+        to: transfer.to,
+        amount: transfer.amount,
+        block_index,
+        timestamp: ic_cdk::api::time(),
+    }
+}
+
+pub fn payments_for(computation_id: &str) -> Vec<AgentPayment> {
+    PAYMENTS.with(|payments| {
+        payments.borrow().iter().filter(|p| p.computation_id == computation_id).cloned().collect()
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&LEDGER),
+        backup::cloned(&BALANCES),
+        backup::cloned(&PAYMENTS),
+        backup::cloned(&UNSETTLED_WITHDRAWALS),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (ledger, balances, payments, unsettled) = backup::decode(bytes)?;
+    backup::replace(&LEDGER, ledger);
+    backup::replace(&BALANCES, balances);
+    backup::replace(&PAYMENTS, payments);
+    backup::replace(&UNSETTLED_WITHDRAWALS, unsettled);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "payments_test.rs"]
+mod tests;
@@ -0,0 +1,236 @@
+//! Escrow of agent fees
+//!
+//! The quoted fee is locked when an agent is assigned to a computation. It
+//! is released to the agent once the computation completes with delivered
+//! results, and refunded to the requester (less the ledger fee) when the
+//! computation is rejected, cancelled or fails.
+
+use candid::{CandidType, Deserialize, Nat, Principal};
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use super::{AgentPayment, PendingTransfer, TransferFailure};
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum EscrowStatus {
+    Locked,
+    Released,
+    Refunded,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Escrow {
+    pub computation_id: String,
+    pub agent_id: String,
+    pub payer: Principal,
+    pub amount: u64,
+    pub lock_block: Nat,
+    pub status: EscrowStatus,
+    pub locked_at: u64,
+    pub settled_at: Option<u64>,
+    pub refund_block: Option<Nat>,
+    pub last_error: Option<String>,
+    /// Refund whose outcome is unknown; the escrow stays refunded and the
+    /// transfer is retried with the same arguments
+    pub unsettled_refund: Option<PendingTransfer>,
+}
+
+thread_local! {
+    static ESCROWS: RefCell<HashMap<String, Escrow>> = RefCell::new(HashMap::new());
+    /// Computations whose fee is being pulled from the payer
+    static PENDING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Reservation held while a fee is pulled so a concurrent lock for the same
+/// computation fails instead of charging twice; released on drop, including
+/// when the call traps
+#[derive(Debug)]
+struct PendingLock {
+    computation_id: String,
+}
+
+impl PendingLock {
+    fn reserve(computation_id: &str) -> Result<Self, String> {
+        if is_locked(computation_id) {
+            return Err(format!("Payment for {} is already in escrow", computation_id));
+        }
+        PENDING.with(|pending| {
+            if !pending.borrow_mut().insert(computation_id.to_string()) {
+                return Err(format!("Payment for {} is already being placed in escrow", computation_id));
+            }
+            Ok(PendingLock { computation_id: computation_id.to_string() })
+        })
+    }
+}
+
+impl Drop for PendingLock {
+    fn drop(&mut self) {
+        PENDING.with(|pending| {
+            pending.borrow_mut().remove(&self.computation_id);
+        });
+    }
+}
+
+/// Move a locked escrow to its final state
+pub fn settle(escrow: &mut Escrow, status: EscrowStatus, now: u64) -> Result<(), String> {
+    if escrow.status != EscrowStatus::Locked {
+        return Err(format!("Escrow for {} is already {:?}", escrow.computation_id, escrow.status));
+    }
+    if status == EscrowStatus::Locked {
+        return Err("Escrow can only settle as released or refunded".to_string());
+    }
+    escrow.status = status;
+    escrow.settled_at = Some(now);
+    escrow.last_error = None;
+    Ok(())
+}
+
+pub fn get(computation_id: &str) -> Option<Escrow> {
+    ESCROWS.with(|escrows| escrows.borrow().get(computation_id).cloned())
+}
+
+pub fn is_locked(computation_id: &str) -> bool {
+    get(computation_id).is_some_and(|escrow| escrow.status == EscrowStatus::Locked)
+}
+
+/// Pull the fee from the payer and hold it for the computation
+pub async fn lock(computation_id: &str, agent_id: &str, payer: Principal, amount: u64) -> Result<Escrow, String> {
+    let _pending = PendingLock::reserve(computation_id)?;
+    let lock_block = super::pull(payer, amount, computation_id).await?;
+
+    let escrow = Escrow {
+        computation_id: computation_id.to_string(),
+        agent_id: agent_id.to_string(),
+        payer,
+        amount,
+        lock_block,
+        status: EscrowStatus::Locked,
+        locked_at: ic_cdk::api::time(),
+        settled_at: None,
+        refund_block: None,
+        last_error: None,
+        unsettled_refund: None,
+    };
+    ESCROWS.with(|escrows| escrows.borrow_mut().insert(computation_id.to_string(), escrow.clone()));
+    Ok(escrow)
+}
+
+/// Release a locked fee to the agent's balance
+pub fn release(computation_id: &str) -> Result<AgentPayment, String> {
+    let now = ic_cdk::api::time();
+    let escrow = ESCROWS.with(|escrows| {
+        let mut escrows = escrows.borrow_mut();
+        let escrow = escrows.get_mut(computation_id)
+            .ok_or_else(|| format!("No escrow for {}", computation_id))?;
+        settle(escrow, EscrowStatus::Released, now)?;
+        Ok::<_, String>(escrow.clone())
+    })?;
+
+    super::credit(&escrow.agent_id, escrow.amount);
+    let payment = AgentPayment {
+        computation_id: escrow.computation_id,
+        agent_id: escrow.agent_id,
+        payer: escrow.payer,
+        amount: escrow.amount,
+        block_index: escrow.lock_block,
+        timestamp: now,
+    };
+    super::record_payment(payment.clone());
+    Ok(payment)
+}
+
+/// Return a locked fee to the payer, or retry a refund whose outcome was
+/// unknown. Computations with nothing to refund succeed with `None`.
+pub async fn refund(computation_id: &str) -> Result<Option<Escrow>, String> {
+    let Some(escrow) = get(computation_id) else {
+        return Ok(None);
+    };
+    let unsettled = match escrow.status {
+        EscrowStatus::Locked => None,
+        EscrowStatus::Refunded if escrow.unsettled_refund.is_some() => escrow.unsettled_refund.clone(),
+        _ => return Ok(None),
+    };
+
+    // Settle, or take the unsettled refund, before transferring so a
+    // concurrent refund cannot pay out twice
+    ESCROWS.with(|escrows| {
+        let mut escrows = escrows.borrow_mut();
+        let Some(stored) = escrows.get_mut(computation_id) else {
+            return Ok(());
+        };
+        match unsettled {
+            Some(_) => {
+                stored.unsettled_refund = None;
+                Ok(())
+            },
+            None => settle(stored, EscrowStatus::Refunded, ic_cdk::api::time()),
+        }
+    })?;
+
+    let transfer = match unsettled {
+        Some(transfer) => transfer,
+        None => match refund_transfer(&escrow).await {
+            Ok(transfer) => transfer,
+            Err(e) => return Err(reopen(computation_id, e)),
+        },
+    };
+    let result = super::push(&transfer).await;
+
+    match result {
+        Ok(block_index) => Ok(update(computation_id, |stored| {
+            stored.refund_block = Some(block_index);
+            stored.last_error = None;
+        })),
+        // Nothing was sent, so the fee is still held and the refund can be retried
+        Err(TransferFailure::Rejected(e)) => Err(reopen(computation_id, e)),
+        // The payer may already have the funds; stay refunded until the transfer is retried
+        Err(TransferFailure::Unknown(e)) => {
+            update(computation_id, |stored| {
+                stored.last_error = Some(e.clone());
+                stored.unsettled_refund = Some(PendingTransfer { last_error: Some(e.clone()), ..transfer });
+            });
+            Err(format!("Refund outcome is unknown and will be retried: {}", e))
+        },
+    }
+}
+
+async fn refund_transfer(escrow: &Escrow) -> Result<PendingTransfer, String> {
+    let fee = super::ledger_fee(super::ledger()?).await?;
+    if escrow.amount <= fee {
+        return Err(format!("Escrowed {} does not cover the ledger fee of {}", escrow.amount, fee));
+    }
+    Ok(PendingTransfer::new(escrow.payer, escrow.amount - fee, fee, super::memo("refund", &escrow.computation_id)))
+}
+
+fn update(computation_id: &str, change: impl FnOnce(&mut Escrow)) -> Option<Escrow> {
+    ESCROWS.with(|escrows| {
+        escrows.borrow_mut().get_mut(computation_id).map(|stored| {
+            change(stored);
+            stored.clone()
+        })
+    })
+}
+
+// Back to locked so the refund can be retried
+fn reopen(computation_id: &str, error: String) -> String {
+    update(computation_id, |stored| {
+        stored.status = EscrowStatus::Locked;
+        stored.settled_at = None;
+        stored.last_error = Some(error.clone());
+    });
+    error
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&ESCROWS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let escrows = backup::decode(bytes)?;
+    backup::replace(&ESCROWS, escrows);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "escrow_test.rs"]
+mod tests;
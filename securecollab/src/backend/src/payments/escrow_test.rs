@@ -0,0 +1,48 @@
+use super::*;
+
+fn locked() -> Escrow {
+    Escrow {
+        computation_id: "mpc_1".to_string(),
+        agent_id: "data_science_agent".to_string(),
+        payer: Principal::anonymous(),
+        amount: 600,
+        lock_block: Nat::from(7u64),
+        status: EscrowStatus::Locked,
+        locked_at: 1,
+        settled_at: None,
+        refund_block: None,
+        last_error: Some("ledger unavailable".to_string()),
+        unsettled_refund: None,
+    }
+}
+
+#[test]
+fn locked_escrow_settles_once() {
+    let mut escrow = locked();
+    settle(&mut escrow, EscrowStatus::Released, 5).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.settled_at, Some(5));
+    assert_eq!(escrow.last_error, None);
+
+    assert!(settle(&mut escrow, EscrowStatus::Refunded, 6).is_err());
+    assert_eq!(escrow.status, EscrowStatus::Released);
+}
+
+#[test]
+fn fees_being_pulled_cannot_be_locked_again() {
+    let pending = PendingLock::reserve("mpc_1").unwrap();
+    assert!(PendingLock::reserve("mpc_1").unwrap_err().contains("being placed"));
+    assert!(PendingLock::reserve("mpc_2").is_ok());
+    drop(pending);
+    assert!(PendingLock::reserve("mpc_1").is_ok());
+
+    ESCROWS.with(|escrows| escrows.borrow_mut().insert("mpc_1".to_string(), locked()));
+    assert!(PendingLock::reserve("mpc_1").unwrap_err().contains("already in escrow"));
+}
+
+#[test]
+fn escrow_cannot_settle_as_locked() {
+    let mut escrow = locked();
+    assert!(settle(&mut escrow, EscrowStatus::Locked, 5).is_err());
+    assert_eq!(escrow.settled_at, None);
+}
@@ -0,0 +1,21 @@
+use super::*;
+
+#[test]
+fn retries_reuse_the_memo_of_the_first_attempt() {
+    assert_eq!(memo("refund", "mpc_1"), memo("refund", "mpc_1"));
+    assert_eq!(memo("refund", "mpc_1").len(), 32);
+    assert_ne!(memo("refund", "mpc_1"), memo("escrow", "mpc_1"));
+    assert_ne!(memo("refund", "mpc_1"), memo("refund", "mpc_2"));
+}
+
+#[test]
+fn only_undetermined_outcomes_are_kept_for_reconciliation() {
+    assert!(matches!(call_failure(RejectionCode::Unknown, "timed out".to_string()), TransferFailure::Unknown(_)));
+    assert!(matches!(call_failure(RejectionCode::SysTransient, "queue full".to_string()), TransferFailure::Rejected(_)));
+    assert!(matches!(call_failure(RejectionCode::CanisterError, "trapped".to_string()), TransferFailure::Rejected(_)));
+
+    assert_eq!(transfer_outcome(Ok(Nat::from(4u64))), Ok(Nat::from(4u64)));
+    assert_eq!(transfer_outcome(Err(TransferError::Duplicate { duplicate_of: Nat::from(3u64) })), Ok(Nat::from(3u64)));
+    assert!(matches!(transfer_outcome(Err(TransferError::TooOld)), Err(TransferFailure::Unknown(_))));
+    assert!(matches!(transfer_outcome(Err(TransferError::TemporarilyUnavailable)), Err(TransferFailure::Rejected(_))));
+}
@@ -0,0 +1,171 @@
+//! Per-dataset differential privacy budgets
+//!
+//! Every query released over a dataset spends epsilon from that dataset's
+//! budget. Owners configure the total; datasets without one get the default.
+//! A query is rejected if any of its datasets could not pay for it, and the
+//! budgets of all its datasets are debited together or not at all. Owners can
+//! also bound the epsilon and delta a single query may use on their dataset.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::differential_privacy::{DpConfig, NoiseMechanism};
+use crate::backup;
+
+pub const DEFAULT_TOTAL_EPSILON: f64 = 10.0;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct BudgetCharge {
+    pub query_id: String,
+    pub epsilon: f64,
+    pub charged_at: u64,
+}
+
+/// Largest parameters one query may use on a dataset
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DpBounds {
+    pub max_epsilon: f64,
+    pub max_delta: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PrivacyBudget {
+    pub dataset_id: String,
+    pub total_epsilon: f64,
+    pub spent_epsilon: f64,
+    pub remaining_epsilon: f64,
+    pub bounds: Option<DpBounds>,
+    pub charges: Vec<BudgetCharge>,
+}
+
+impl PrivacyBudget {
+    fn new(dataset_id: &str) -> Self {
+        Self {
+            dataset_id: dataset_id.to_string(),
+            total_epsilon: DEFAULT_TOTAL_EPSILON,
+            spent_epsilon: 0.0,
+            remaining_epsilon: DEFAULT_TOTAL_EPSILON,
+            bounds: None,
+            charges: vec![],
+        }
+    }
+
+    fn can_pay(&self, epsilon: f64) -> Result<(), String> {
+        // Tolerate float dust so a budget can be spent exactly
+        if epsilon > self.remaining_epsilon + 1e-9 {
+            return Err(format!(
+                "Dataset {} has {:.3} epsilon of privacy budget left; the query needs {:.3}",
+                self.dataset_id, self.remaining_epsilon, epsilon
+            ));
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static BUDGETS: RefCell<HashMap<String, PrivacyBudget>> = RefCell::new(HashMap::new());
+}
+
+pub fn budget(dataset_id: &str) -> PrivacyBudget {
+    BUDGETS.with(|budgets| budgets.borrow().get(dataset_id).cloned())
+        .unwrap_or_else(|| PrivacyBudget::new(dataset_id))
+}
+
+/// Set a dataset's total budget; it cannot drop below what was already spent
+pub fn set_total(dataset_id: &str, total_epsilon: f64) -> Result<PrivacyBudget, String> {
+    if !(total_epsilon.is_finite() && total_epsilon >= 0.0) {
+        return Err("Privacy budget must be a non-negative number".to_string());
+    }
+    BUDGETS.with(|budgets| {
+        let mut budgets = budgets.borrow_mut();
+        let budget = budgets.entry(dataset_id.to_string()).or_insert_with(|| PrivacyBudget::new(dataset_id));
+        if total_epsilon < budget.spent_epsilon {
+            return Err(format!("Dataset {} has already spent {:.3} epsilon", dataset_id, budget.spent_epsilon));
+        }
+        budget.total_epsilon = total_epsilon;
+        budget.remaining_epsilon = total_epsilon - budget.spent_epsilon;
+        Ok(budget.clone())
+    })
+}
+
+pub fn set_bounds(dataset_id: &str, bounds: Option<DpBounds>) -> Result<PrivacyBudget, String> {
+    if let Some(bounds) = &bounds {
+        let valid = bounds.max_epsilon > 0.0 && bounds.max_epsilon.is_finite() && (0.0..1.0).contains(&bounds.max_delta);
+        if !valid {
+            return Err("Bounds need a positive maximum epsilon and a maximum delta below 1".to_string());
+        }
+    }
+    BUDGETS.with(|budgets| {
+        let mut budgets = budgets.borrow_mut();
+        let budget = budgets.entry(dataset_id.to_string()).or_insert_with(|| PrivacyBudget::new(dataset_id));
+        budget.bounds = bounds;
+        Ok(budget.clone())
+    })
+}
+
+/// Whether `config` stays within the bounds of every dataset
+pub fn validate(dataset_ids: &[String], config: &DpConfig) -> Result<(), String> {
+    for id in dataset_ids {
+        let Some(bounds) = budget(id).bounds else {
+            continue;
+        };
+        if config.epsilon > bounds.max_epsilon {
+            return Err(format!("Dataset {} allows at most epsilon {} per query", id, bounds.max_epsilon));
+        }
+        if config.mechanism == NoiseMechanism::Gaussian && config.delta > bounds.max_delta {
+            return Err(format!("Dataset {} allows at most delta {} per query", id, bounds.max_delta));
+        }
+    }
+    Ok(())
+}
+
+/// Whether every dataset can pay `epsilon`
+pub fn check(dataset_ids: &[String], epsilon: f64) -> Result<(), String> {
+    dataset_ids.iter().try_for_each(|id| budget(id).can_pay(epsilon))
+}
+
+/// Charge `epsilon` to every dataset, or to none if one cannot pay
+pub fn debit(dataset_ids: &[String], query_id: &str, epsilon: f64, now: u64) -> Result<(), String> {
+    check(dataset_ids, epsilon)?;
+    BUDGETS.with(|budgets| {
+        let mut budgets = budgets.borrow_mut();
+        for id in dataset_ids {
+            let budget = budgets.entry(id.clone()).or_insert_with(|| PrivacyBudget::new(id));
+            budget.spent_epsilon += epsilon;
+            budget.remaining_epsilon = (budget.total_epsilon - budget.spent_epsilon).max(0.0);
+            budget.charges.push(BudgetCharge { query_id: query_id.to_string(), epsilon, charged_at: now });
+        }
+    });
+    Ok(())
+}
+
+/// Give back what a query was charged, such as when its run failed before
+/// releasing anything
+pub fn credit(dataset_ids: &[String], query_id: &str) {
+    BUDGETS.with(|budgets| {
+        let mut budgets = budgets.borrow_mut();
+        for id in dataset_ids {
+            let Some(budget) = budgets.get_mut(id) else {
+                continue;
+            };
+            let refunded: f64 = budget.charges.iter().filter(|c| c.query_id == query_id).map(|c| c.epsilon).sum();
+            budget.charges.retain(|c| c.query_id != query_id);
+            budget.spent_epsilon = (budget.spent_epsilon - refunded).max(0.0);
+            budget.remaining_epsilon = (budget.total_epsilon - budget.spent_epsilon).max(0.0);
+        }
+    });
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&BUDGETS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let budgets = backup::decode(bytes)?;
+    backup::replace(&BUDGETS, budgets);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "privacy_budget_test.rs"]
+mod tests;
@@ -0,0 +1,61 @@
+use super::*;
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn datasets_start_with_the_default_budget() {
+    let budget = budget("fresh");
+    assert_eq!(budget.total_epsilon, DEFAULT_TOTAL_EPSILON);
+    assert_eq!(budget.remaining_epsilon, DEFAULT_TOTAL_EPSILON);
+    assert!(budget.charges.is_empty());
+}
+
+#[test]
+fn queries_debit_every_dataset_until_the_budget_runs_out() {
+    set_total("small", 1.0).unwrap();
+    debit(&ids(&["small", "large"]), "q1", 0.5, 1).unwrap();
+    debit(&ids(&["small", "large"]), "q2", 0.5, 2).unwrap();
+    assert_eq!(budget("small").remaining_epsilon, 0.0);
+    assert_eq!(budget("large").spent_epsilon, 1.0);
+
+    // One exhausted dataset rejects the query for all of them
+    assert!(debit(&ids(&["small", "large"]), "q3", 0.5, 3).is_err());
+    assert_eq!(budget("large").charges.len(), 2);
+}
+
+#[test]
+fn totals_cannot_drop_below_spent_epsilon() {
+    debit(&ids(&["spent"]), "q1", 3.0, 1).unwrap();
+    assert!(set_total("spent", 2.0).is_err());
+    assert_eq!(set_total("spent", 5.0).unwrap().remaining_epsilon, 2.0);
+    assert!(set_total("spent", -1.0).is_err());
+}
+
+#[test]
+fn owner_bounds_limit_query_parameters() {
+    let gaussian = DpConfig { epsilon: 0.5, delta: 1e-4, mechanism: NoiseMechanism::Gaussian };
+    validate(&ids(&["bounded"]), &gaussian).unwrap();
+
+    set_bounds("bounded", Some(DpBounds { max_epsilon: 1.0, max_delta: 1e-6 })).unwrap();
+    assert!(validate(&ids(&["bounded"]), &gaussian).is_err());
+    validate(&ids(&["bounded"]), &DpConfig { delta: 1e-7, ..gaussian.clone() }).unwrap();
+    assert!(validate(&ids(&["other", "bounded"]), &DpConfig { epsilon: 2.0, ..DpConfig::default() }).is_err());
+
+    assert!(set_bounds("bounded", Some(DpBounds { max_epsilon: 0.0, max_delta: 0.0 })).is_err());
+    set_bounds("bounded", None).unwrap();
+    validate(&ids(&["bounded"]), &gaussian).unwrap();
+}
+
+#[test]
+fn failed_runs_get_their_epsilon_back() {
+    debit(&ids(&["refunded", "other"]), "q1", 1.0, 1).unwrap();
+    debit(&ids(&["refunded"]), "q2", 0.5, 2).unwrap();
+    credit(&ids(&["refunded", "other", "untouched"]), "q1");
+    let refunded = budget("refunded");
+    assert_eq!((refunded.spent_epsilon, refunded.remaining_epsilon), (0.5, DEFAULT_TOTAL_EPSILON - 0.5));
+    assert_eq!(refunded.charges.iter().map(|c| c.query_id.as_str()).collect::<Vec<_>>(), vec!["q2"]);
+    assert_eq!(budget("other").spent_epsilon, 0.0);
+    assert!(budget("untouched").charges.is_empty());
+}
@@ -1,7 +1,34 @@
 use candid::{CandidType, Deserialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::cell::RefCell;
 use ic_cdk::api::time;
+use crate::backup;
+
+pub mod export;
+pub mod range;
+pub mod snark;
+
+/// Hash function behind a proof's verification hash
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum HashScheme {
+    /// Multiplicative hash used by earlier proofs; collidable, so proofs
+    /// carrying it never verify until migrated
+    Legacy,
+    Sha256,
+}
+
+/// Outcome of verifying one proof in a batch
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProofVerification {
+    pub proof_id: String,
+    pub computation_id: Option<String>,
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+/// Most proofs one batch may verify
+pub const MAX_BATCH_VERIFY: usize = 50;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct PrivacyProof {
@@ -9,21 +36,13 @@ pub struct PrivacyProof {
     pub computation_id: String,
     pub proof_type: String,
     pub verification_hash: String,
+    pub hash_scheme: HashScheme,
     pub public_parameters: Vec<u8>,
     pub proof_data: Vec<u8>,
     pub created_at: u64,
     pub verified: bool,
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct ZKProofCircuit {
-    pub circuit_id: String,
-    pub circuit_type: String,
-    pub constraints: Vec<String>,
-    pub public_inputs: Vec<u64>,
-    pub private_inputs_hash: String,
-}
-
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct DifferentialPrivacyParams {
     pub epsilon: f64,
@@ -32,22 +51,29 @@ pub struct DifferentialPrivacyParams {
     pub noise_mechanism: String,
 }
 
-// Store privacy proofs and circuits
+// Store privacy proofs
 thread_local! {
     static PRIVACY_PROOFS: RefCell<HashMap<String, PrivacyProof>> = RefCell::new(HashMap::new());
-    static ZK_CIRCUITS: RefCell<HashMap<String, ZKProofCircuit>> = RefCell::new(HashMap::new());
+    // Latest audit report per computation, as certified for query responses
+    static AUDIT_REPORTS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
 }
 
-/// Generate a privacy proof for a computation
-pub fn generate_proof(computation_id: String, proof_type: String) -> PrivacyProof {
+/// Generate a privacy proof for a computation; `seed` is randomness for
+/// proof types that need it
+pub fn generate_proof(computation_id: String, proof_type: String, seed: &[u8]) -> Result<PrivacyProof, String> {
     let proof_id = format!("proof_{}_{}", computation_id, time());
     
-    let (verification_hash, proof_data) = match proof_type.as_str() {
-        "zk-SNARK" => generate_zk_snark_proof(&computation_id),
-        "zk-STARK" => generate_zk_stark_proof(&computation_id),
-        "differential_privacy" => generate_dp_proof(&computation_id),
-        "homomorphic_encryption" => generate_he_proof(&computation_id),
-        _ => generate_generic_proof(&computation_id),
+    let (verification_hash, proof_data, public_parameters) = match proof_type.as_str() {
+        "zk-SNARK" => generate_zk_snark_proof(&computation_id, seed)?,
+        other => {
+            let (verification_hash, proof_data) = match other {
+                "zk-STARK" => generate_zk_stark_proof(&computation_id),
+                "differential_privacy" => generate_dp_proof(&computation_id),
+                "homomorphic_encryption" => generate_he_proof(&computation_id),
+                _ => generate_generic_proof(&computation_id),
+            };
+            (verification_hash, proof_data, generate_public_parameters(other))
+        }
     };
     
     let proof = PrivacyProof {
@@ -55,7 +81,8 @@ pub fn generate_proof(computation_id: String, proof_type: String) -> PrivacyProo
         computation_id: computation_id.clone(),
         proof_type: proof_type.clone(),
         verification_hash,
-        public_parameters: generate_public_parameters(&proof_type),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters,
         proof_data,
         created_at: time(),
         verified: false,
@@ -66,39 +93,94 @@ pub fn generate_proof(computation_id: String, proof_type: String) -> PrivacyProo
         proofs.borrow_mut().insert(proof_id.clone(), proof.clone());
     });
     
-    proof
-}
-
-/// Generate zk-SNARK proof
-fn generate_zk_snark_proof(computation_id: &str) -> (String, Vec<u8>) {
-    // Simulate zk-SNARK proof generation
-    let circuit_constraints = vec![
-        "input_data_encrypted == true".to_string(),
-        "computation_integrity == verified".to_string(),
-        "output_privacy == preserved".to_string(),
-        "agent_isolation == maintained".to_string(),
-    ];
-    
-    let circuit = ZKProofCircuit {
-        circuit_id: format!("snark_circuit_{}", computation_id),
-        circuit_type: "R1CS".to_string(),
-        constraints: circuit_constraints,
-        public_inputs: vec![1, 1, 1, 1], // All constraints satisfied
-        private_inputs_hash: compute_hash(computation_id.as_bytes()),
+    Ok(proof)
+}
+
+/// Generate a data-minimization proof binding a computation to the exact
+/// columns decrypted for it by the column-level encryption layer
+pub fn generate_minimization_proof(computation_id: String) -> Result<PrivacyProof, String> {
+    let statement = crate::column_encryption::minimization_statement(&computation_id)
+        .ok_or_else(|| format!("No column decryptions recorded for {}", computation_id))?;
+    let commitment = crate::column_encryption::decryption_commitment(&computation_id)
+        .ok_or_else(|| format!("No column decryptions recorded for {}", computation_id))?;
+    
+    let proof = PrivacyProof {
+        proof_id: format!("proof_{}_{}", computation_id, time()),
+        computation_id,
+        proof_type: "data_minimization".to_string(),
+        verification_hash: commitment,
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: generate_public_parameters("data_minimization"),
+        proof_data: statement.into_bytes(),
+        created_at: time(),
+        verified: false,
     };
     
-    // Store circuit
-    ZK_CIRCUITS.with(|circuits| {
-        circuits.borrow_mut().insert(circuit.circuit_id.clone(), circuit);
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().insert(proof.proof_id.clone(), proof.clone());
     });
     
-    let proof_data = format!(
-        "SNARK_PROOF[comp:{},constraints:4,satisfied:true,curve:BN254]",
-        computation_id
-    );
+    Ok(proof)
+}
+
+/// Generate a disclosure-control proof committing to the suppressions and
+/// rounding applied to a computation's published results
+pub fn generate_disclosure_proof(computation_id: String) -> Result<PrivacyProof, String> {
+    let report = crate::disclosure_control::report(&computation_id)
+        .ok_or_else(|| format!("No disclosure report recorded for {}", computation_id))?;
+    let statement = crate::disclosure_control::statement(&computation_id, &report);
     
-    let verification_hash = compute_hash(proof_data.as_bytes());
-    (verification_hash, proof_data.into_bytes())
+    let proof = PrivacyProof {
+        proof_id: format!("proof_{}_{}", computation_id, time()),
+        computation_id,
+        proof_type: "disclosure_control".to_string(),
+        verification_hash: compute_hash(statement.as_bytes()),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: generate_public_parameters("disclosure_control"),
+        proof_data: statement.into_bytes(),
+        created_at: time(),
+        verified: false,
+    };
+    
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().insert(proof.proof_id.clone(), proof.clone());
+    });
+    
+    Ok(proof)
+}
+
+/// Generate a proof of the records each dataset contributed to a secure
+/// statistics run
+pub fn generate_record_count_proof(run_id: String) -> Result<PrivacyProof, String> {
+    let statement = crate::mpc_engine::secure_sum::count_statement(&run_id)
+        .ok_or_else(|| format!("No secure statistics run recorded for {}", run_id))?;
+    
+    let proof = PrivacyProof {
+        proof_id: format!("proof_{}_{}", run_id, time()),
+        computation_id: run_id,
+        proof_type: "record_count".to_string(),
+        verification_hash: compute_hash(statement.as_bytes()),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: generate_public_parameters("record_count"),
+        proof_data: statement.into_bytes(),
+        created_at: time(),
+        verified: false,
+    };
+    
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().insert(proof.proof_id.clone(), proof.clone());
+    });
+    
+    Ok(proof)
+}
+
+/// Generate a Groth16 proof that the computation's committed aggregate is
+/// the sum of its committed inputs; the public parameters carry the proof's
+/// public inputs
+fn generate_zk_snark_proof(computation_id: &str, seed: &[u8]) -> Result<(String, Vec<u8>, Vec<u8>), String> {
+    let (proof_data, public_inputs) = snark::prove(computation_id, seed)?;
+    let verification_hash = compute_hash(&proof_data);
+    Ok((verification_hash, proof_data, public_inputs))
 }
 
 /// Generate zk-STARK proof
@@ -121,15 +203,87 @@ fn generate_dp_proof(computation_id: &str) -> (String, Vec<u8>) {
         noise_mechanism: "Gaussian".to_string(),
     };
     
-    let proof_data = format!(
-        "DP_PROOF[comp:{},epsilon:{},delta:{},mechanism:{}]",
-        computation_id, dp_params.epsilon, dp_params.delta, dp_params.noise_mechanism
-    );
+    let proof_data = dp_statement(computation_id, &dp_params);
     
     let verification_hash = compute_hash(proof_data.as_bytes());
     (verification_hash, proof_data.into_bytes())
 }
 
+fn dp_statement(computation_id: &str, dp_params: &DifferentialPrivacyParams) -> String {
+    format!(
+        "DP_PROOF[comp:{},epsilon:{},delta:{},mechanism:{}]",
+        computation_id, dp_params.epsilon, dp_params.delta, dp_params.noise_mechanism
+    )
+}
+
+/// Generate a differential privacy proof for the parameters a computation's
+/// release actually realized
+pub fn generate_dp_release_proof(computation_id: String) -> Result<PrivacyProof, String> {
+    let params = crate::differential_privacy::release(&computation_id)
+        .ok_or_else(|| format!("No differentially private release recorded for {}", computation_id))?;
+    let statement = dp_statement(&computation_id, &params);
+    
+    let proof = PrivacyProof {
+        proof_id: format!("proof_{}_{}", computation_id, time()),
+        computation_id,
+        proof_type: "differential_privacy".to_string(),
+        verification_hash: compute_hash(statement.as_bytes()),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: generate_public_parameters("differential_privacy"),
+        proof_data: statement.into_bytes(),
+        created_at: time(),
+        verified: false,
+    };
+    
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().insert(proof.proof_id.clone(), proof.clone());
+    });
+    
+    Ok(proof)
+}
+
+/// Generate range proofs that a computation's exact statistics lie within
+/// their bounds; `statistics` holds (statistic, exact value, lower, upper)
+/// and statistics outside their bounds get no proof
+pub fn generate_range_proof(computation_id: String, statistics: &[(String, f64, f64, f64)], seed: &[u8]) -> Result<PrivacyProof, String> {
+    let ranges: Vec<range::StatisticRange> = statistics.iter()
+        .filter_map(|(statistic, value, lower, upper)| range::prove(&computation_id, statistic, *value, *lower, *upper, seed))
+        .collect();
+    if ranges.is_empty() {
+        return Err(format!("No statistics of {} lie within their bounds", computation_id));
+    }
+    let proof_data = candid::encode_one(&ranges).map_err(|e| format!("Failed to encode range proofs: {}", e))?;
+    
+    let proof = PrivacyProof {
+        proof_id: format!("proof_{}_{}", computation_id, time()),
+        computation_id,
+        proof_type: "range_proof".to_string(),
+        verification_hash: compute_hash(&proof_data),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: generate_public_parameters("range_proof"),
+        proof_data,
+        created_at: time(),
+        verified: false,
+    };
+    
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().insert(proof.proof_id.clone(), proof.clone());
+    });
+    
+    Ok(proof)
+}
+
+/// Check each statistic of a computation's latest range proof
+pub fn range_checks(computation_id: &str) -> Result<Vec<range::RangeCheck>, String> {
+    let proof = get_proofs_for_computation(computation_id).into_iter()
+        .filter(|p| p.proof_type == "range_proof")
+        .max_by_key(|p| p.created_at)
+        .ok_or_else(|| format!("No range proofs recorded for {}", computation_id))?;
+    let ranges: Vec<range::StatisticRange> = candid::decode_one(&proof.proof_data)
+        .map_err(|e| format!("Malformed range proof: {}", e))?;
+    Ok(ranges.iter().map(|r| range::check(computation_id, r)).collect())
+}
+
 /// Generate homomorphic encryption proof
 fn generate_he_proof(computation_id: &str) -> (String, Vec<u8>) {
     let proof_data = format!(
@@ -158,11 +312,15 @@ pub fn verify_proof(proof_id: &str) -> Result<bool, String> {
         proofs.borrow().get(proof_id).cloned()
     }).ok_or_else(|| format!("Proof {} not found", proof_id))?;
     
-    let is_valid = match proof.proof_type.as_str() {
+    let is_valid = proof.hash_scheme == HashScheme::Sha256 && match proof.proof_type.as_str() {
         "zk-SNARK" => verify_zk_snark_proof(&proof),
         "zk-STARK" => verify_zk_stark_proof(&proof),
         "differential_privacy" => verify_dp_proof(&proof),
         "homomorphic_encryption" => verify_he_proof(&proof),
+        "data_minimization" => verify_minimization_proof(&proof),
+        "disclosure_control" => verify_disclosure_proof(&proof),
+        "range_proof" => verify_range_proof(&proof),
+        "record_count" => verify_record_count_proof(&proof),
         _ => verify_generic_proof(&proof),
     };
     
@@ -178,11 +336,10 @@ pub fn verify_proof(proof_id: &str) -> Result<bool, String> {
     Ok(is_valid)
 }
 
-/// Verify zk-SNARK proof
+/// Verify a Groth16 proof against its public inputs
 fn verify_zk_snark_proof(proof: &PrivacyProof) -> bool {
-    // Simulate SNARK verification
-    let expected_hash = compute_hash(&proof.proof_data);
-    proof.verification_hash == expected_hash
+    compute_hash(&proof.proof_data) == proof.verification_hash
+        && snark::verify(&proof.computation_id, &proof.proof_data, &proof.public_parameters).unwrap_or(false)
 }
 
 /// Verify zk-STARK proof
@@ -194,6 +351,12 @@ fn verify_zk_stark_proof(proof: &PrivacyProof) -> bool {
 
 /// Verify differential privacy proof
 fn verify_dp_proof(proof: &PrivacyProof) -> bool {
+    // A recorded release must match the parameters it realized
+    if let Some(params) = crate::differential_privacy::release(&proof.computation_id) {
+        return dp_statement(&proof.computation_id, &params).as_bytes() == proof.proof_data.as_slice()
+            && compute_hash(&proof.proof_data) == proof.verification_hash;
+    }
+    
     // Verify DP parameters are within acceptable bounds
     let proof_str = String::from_utf8_lossy(&proof.proof_data);
     proof_str.contains("epsilon:0.1") && proof_str.contains("delta:0.00001")
@@ -206,6 +369,39 @@ fn verify_he_proof(proof: &PrivacyProof) -> bool {
     proof_str.contains("scheme:CKKS") && proof_str.contains("security:128bit")
 }
 
+/// Verify a minimization proof against the column decryption log
+fn verify_minimization_proof(proof: &PrivacyProof) -> bool {
+    let statement = crate::column_encryption::minimization_statement(&proof.computation_id);
+    let commitment = crate::column_encryption::decryption_commitment(&proof.computation_id);
+    
+    statement.as_deref().map(str::as_bytes) == Some(proof.proof_data.as_slice())
+        && commitment.as_deref() == Some(proof.verification_hash.as_str())
+}
+
+/// Verify a disclosure-control proof against the recorded report
+fn verify_disclosure_proof(proof: &PrivacyProof) -> bool {
+    let statement = crate::disclosure_control::report(&proof.computation_id)
+        .map(|report| crate::disclosure_control::statement(&proof.computation_id, &report));
+    
+    statement.as_deref().map(str::as_bytes) == Some(proof.proof_data.as_slice())
+        && compute_hash(&proof.proof_data) == proof.verification_hash
+}
+
+/// Verify every statistic's range proof
+fn verify_range_proof(proof: &PrivacyProof) -> bool {
+    let ranges: Result<Vec<range::StatisticRange>, _> = candid::decode_one(&proof.proof_data);
+    compute_hash(&proof.proof_data) == proof.verification_hash
+        && ranges.is_ok_and(|ranges| ranges.iter().all(|r| range::verify(&proof.computation_id, r)))
+}
+
+/// Verify a record-count proof against the run's current shares
+fn verify_record_count_proof(proof: &PrivacyProof) -> bool {
+    let statement = crate::mpc_engine::secure_sum::count_statement(&proof.computation_id);
+    
+    statement.as_deref().map(str::as_bytes) == Some(proof.proof_data.as_slice())
+        && compute_hash(&proof.proof_data) == proof.verification_hash
+}
+
 /// Verify generic proof
 fn verify_generic_proof(proof: &PrivacyProof) -> bool {
     let expected_hash = compute_hash(&proof.proof_data);
@@ -268,42 +464,81 @@ pub fn generate_privacy_audit(computation_id: &str) -> Result<String, String> {
     Ok(audit_report)
 }
 
-/// Batch verify multiple proofs
-pub fn batch_verify_proofs(proof_ids: &[String]) -> HashMap<String, bool> {
-    let mut results = HashMap::new();
-    
-    for proof_id in proof_ids {
-        let result = verify_proof(proof_id).unwrap_or(false);
-        results.insert(proof_id.clone(), result);
+/// Look up a stored proof
+pub fn get_proof(proof_id: &str) -> Option<PrivacyProof> {
+    PRIVACY_PROOFS.with(|proofs| proofs.borrow().get(proof_id).cloned())
+}
+
+/// Regenerate and cache the audit report for a computation
+pub fn refresh_audit(computation_id: &str) -> Result<String, String> {
+    let report = generate_privacy_audit(computation_id)?;
+    AUDIT_REPORTS.with(|reports| {
+        reports.borrow_mut().insert(computation_id.to_string(), report.clone());
+    });
+    Ok(report)
+}
+
+/// Cached audit report, as last certified
+pub fn get_audit(computation_id: &str) -> Option<String> {
+    AUDIT_REPORTS.with(|reports| reports.borrow().get(computation_id).cloned())
+}
+
+/// Every cached audit report, by computation
+pub fn audits() -> Vec<(String, String)> {
+    AUDIT_REPORTS.with(|reports| reports.borrow().iter().map(|(id, report)| (id.clone(), report.clone())).collect())
+}
+
+/// Batch verify multiple proofs, in the order given
+pub fn batch_verify_proofs(proof_ids: &[String]) -> Result<Vec<ProofVerification>, String> {
+    if proof_ids.len() > MAX_BATCH_VERIFY {
+        return Err(format!("At most {} proofs can be verified at once", MAX_BATCH_VERIFY));
     }
     
-    results
+    Ok(proof_ids.iter().map(|proof_id| {
+        let result = verify_proof(proof_id);
+        ProofVerification {
+            proof_id: proof_id.clone(),
+            computation_id: get_proof(proof_id).map(|p| p.computation_id),
+            verified: *result.as_ref().unwrap_or(&false),
+            error: result.err(),
+        }
+    }).collect())
 }
 
-/// Get all proofs for a computation
+/// Get all proofs for a computation, oldest first
 pub fn get_proofs_for_computation(computation_id: &str) -> Vec<PrivacyProof> {
-    PRIVACY_PROOFS.with(|proofs| {
+    let mut proofs: Vec<PrivacyProof> = PRIVACY_PROOFS.with(|proofs| {
         proofs.borrow()
             .values()
             .filter(|p| p.computation_id == computation_id)
             .cloned()
             .collect()
-    })
+    });
+    proofs.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.proof_id.cmp(&b.proof_id)));
+    proofs
 }
 
 /// Generate public parameters for proof type
 fn generate_public_parameters(proof_type: &str) -> Vec<u8> {
     match proof_type {
-        "zk-SNARK" => b"BN254_CURVE_PARAMS_TRUSTED_SETUP".to_vec(),
         "zk-STARK" => b"STARK_FIELD_PARAMS_F2_64".to_vec(),
         "differential_privacy" => b"DP_GAUSSIAN_NOISE_PARAMS".to_vec(),
         "homomorphic_encryption" => b"CKKS_RING_LWE_PARAMS".to_vec(),
+        "data_minimization" => b"SHA256_COLUMN_DECRYPTION_LOG".to_vec(),
+        "disclosure_control" => b"SDC_SUPPRESSION_ROUNDING_POLICY".to_vec(),
+        "range_proof" => b"BULLETPROOFS_RISTRETTO_32BIT".to_vec(),
+        "record_count" => b"SHA256_SECRET_SHARE_DIGESTS".to_vec(),
         _ => b"GENERIC_PRIVACY_PARAMS".to_vec(),
     }
 }
 
 /// Compute hash of data
 fn compute_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Hash of proofs with the `Legacy` scheme, kept only to migrate them
+fn legacy_hash(data: &[u8]) -> String {
     let mut hash = 0u64;
     for &byte in data {
         hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
@@ -311,6 +546,22 @@ fn compute_hash(data: &[u8]) -> String {
     format!("{:016x}", hash)
 }
 
+/// Re-hash legacy proofs whose hash still matches their data with SHA-256;
+/// they need verifying again. Returns the migrated proofs
+pub fn migrate_hashes() -> Vec<PrivacyProof> {
+    PRIVACY_PROOFS.with(|proofs| {
+        proofs.borrow_mut().values_mut()
+            .filter(|p| p.hash_scheme == HashScheme::Legacy && legacy_hash(&p.proof_data) == p.verification_hash)
+            .map(|p| {
+                p.verification_hash = compute_hash(&p.proof_data);
+                p.hash_scheme = HashScheme::Sha256;
+                p.verified = false;
+                p.clone()
+            })
+            .collect()
+    })
+}
+
 /// Get privacy proof statistics
 pub fn get_proof_statistics() -> HashMap<String, u64> {
     let mut stats = HashMap::new();
@@ -336,3 +587,14 @@ pub fn get_proof_statistics() -> HashMap<String, u64> {
     stats.insert("timestamp".to_string(), time());
     stats
 }
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&PRIVACY_PROOFS), backup::cloned(&AUDIT_REPORTS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (privacy_proofs, audit_reports) = backup::decode(bytes)?;
+    backup::replace(&PRIVACY_PROOFS, privacy_proofs);
+    backup::replace(&AUDIT_REPORTS, audit_reports);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! Versioned export format for privacy proofs
+//!
+//! An exported proof carries everything an off-chain verifier needs: the
+//! scheme, the proof bytes, the public inputs and the verification key (or
+//! the id of fixed generators). Both encodings hold the same fields.
+//!
+//! JSON is an object with the fields below, byte fields hex-encoded.
+//!
+//! Binary is the magic `SCPF`, the format version as a big-endian u16, then
+//! each field in order: strings and bytes as a big-endian u32 length followed
+//! by the data, `created_at` as a big-endian u64. An absent verification key
+//! is encoded as zero bytes.
+
+use candid::{CandidType, Deserialize};
+use super::{range, snark, HashScheme, PrivacyProof};
+
+/// Version 2 added `hash_scheme`
+pub const FORMAT_VERSION: u16 = 2;
+pub const MAGIC: &[u8; 4] = b"SCPF";
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ProofExportFormat {
+    Json,
+    Binary,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ProofExport {
+    pub format: ProofExportFormat,
+    pub format_version: u16,
+    pub scheme: String,
+    pub content: Vec<u8>,
+}
+
+/// Fields of an exported proof, in binary order
+struct ExportedProof {
+    scheme: String,
+    proof_type: String,
+    proof_id: String,
+    computation_id: String,
+    verification_key_id: String,
+    verification_key: Vec<u8>,
+    public_inputs: Vec<u8>,
+    proof: Vec<u8>,
+    verification_hash: String,
+    hash_scheme: String,
+    created_at: u64,
+}
+
+impl ExportedProof {
+    fn new(proof: &PrivacyProof) -> Result<Self, String> {
+        let (scheme, verification_key_id, verification_key) = match proof.proof_type.as_str() {
+            "zk-SNARK" => {
+                let (id, key) = snark::verifying_key(&proof.public_parameters)?;
+                ("groth16-bn254", id, key)
+            }
+            "range_proof" => ("bulletproofs-ristretto255", range::GENERATORS_ID.to_string(), vec![]),
+            _ => ("statement-hash", String::new(), vec![]),
+        };
+        Ok(Self {
+            scheme: scheme.to_string(),
+            proof_type: proof.proof_type.clone(),
+            proof_id: proof.proof_id.clone(),
+            computation_id: proof.computation_id.clone(),
+            verification_key_id,
+            verification_key,
+            public_inputs: proof.public_parameters.clone(),
+            proof: proof.proof_data.clone(),
+            verification_hash: proof.verification_hash.clone(),
+            hash_scheme: match proof.hash_scheme {
+                HashScheme::Legacy => "legacy",
+                HashScheme::Sha256 => "sha256",
+            }.to_string(),
+            created_at: proof.created_at,
+        })
+    }
+
+    fn to_json(&self) -> Vec<u8> {
+        serde_json::json!({
+            "format_version": FORMAT_VERSION,
+            "scheme": self.scheme,
+            "proof_type": self.proof_type,
+            "proof_id": self.proof_id,
+            "computation_id": self.computation_id,
+            "verification_key_id": self.verification_key_id,
+            "verification_key": hex::encode(&self.verification_key),
+            "public_inputs": hex::encode(&self.public_inputs),
+            "proof": hex::encode(&self.proof),
+            "verification_hash": self.verification_hash,
+            "hash_scheme": self.hash_scheme,
+            "created_at": self.created_at,
+        }).to_string().into_bytes()
+    }
+
+    fn to_binary(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+        let fields: [&[u8]; 10] = [
+            self.scheme.as_bytes(),
+            self.proof_type.as_bytes(),
+            self.proof_id.as_bytes(),
+            self.computation_id.as_bytes(),
+            self.verification_key_id.as_bytes(),
+            &self.verification_key,
+            &self.public_inputs,
+            &self.proof,
+            self.verification_hash.as_bytes(),
+            self.hash_scheme.as_bytes(),
+        ];
+        for field in fields {
+            out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            out.extend_from_slice(field);
+        }
+        out.extend_from_slice(&self.created_at.to_be_bytes());
+        out
+    }
+}
+
+pub fn export(proof: &PrivacyProof, format: ProofExportFormat) -> Result<ProofExport, String> {
+    let exported = ExportedProof::new(proof)?;
+    let content = match format {
+        ProofExportFormat::Json => exported.to_json(),
+        ProofExportFormat::Binary => exported.to_binary(),
+    };
+    Ok(ProofExport { format, format_version: FORMAT_VERSION, scheme: exported.scheme, content })
+}
+
+#[cfg(test)]
+#[path = "export_test.rs"]
+mod tests;
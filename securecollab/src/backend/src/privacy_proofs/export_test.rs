@@ -0,0 +1,60 @@
+use super::*;
+
+fn proof(proof_type: &str) -> PrivacyProof {
+    PrivacyProof {
+        proof_id: "proof_q1_1".to_string(),
+        computation_id: "q1".to_string(),
+        proof_type: proof_type.to_string(),
+        verification_hash: "abc".to_string(),
+        hash_scheme: HashScheme::Sha256,
+        public_parameters: vec![1, 2],
+        proof_data: vec![3, 4, 5],
+        created_at: 42,
+        verified: false,
+    }
+}
+
+/// Split a binary export into its length-prefixed fields and timestamp
+fn fields(content: &[u8]) -> (Vec<Vec<u8>>, u64) {
+    let mut rest = &content[6..];
+    let mut fields = vec![];
+    for _ in 0..10 {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        fields.push(rest[4..4 + len].to_vec());
+        rest = &rest[4 + len..];
+    }
+    (fields, u64::from_be_bytes(rest.try_into().unwrap()))
+}
+
+#[test]
+fn binary_export_is_versioned_and_length_prefixed() {
+    let export = export(&proof("disclosure_control"), ProofExportFormat::Binary).unwrap();
+    assert_eq!(&export.content[..4], MAGIC);
+    assert_eq!(u16::from_be_bytes([export.content[4], export.content[5]]), FORMAT_VERSION);
+
+    let (fields, created_at) = fields(&export.content);
+    assert_eq!(fields[0], b"statement-hash");
+    assert_eq!(fields[3], b"q1");
+    assert!(fields[5].is_empty());
+    assert_eq!(fields[6], vec![1, 2]);
+    assert_eq!(fields[7], vec![3, 4, 5]);
+    assert_eq!(fields[9], b"sha256");
+    assert_eq!(created_at, 42);
+}
+
+#[test]
+fn json_export_hex_encodes_bytes_and_names_generators() {
+    let export = export(&proof("range_proof"), ProofExportFormat::Json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&export.content).unwrap();
+    assert_eq!(json["format_version"], 2);
+    assert_eq!(json["hash_scheme"], "sha256");
+    assert_eq!(json["scheme"], "bulletproofs-ristretto255");
+    assert_eq!(json["verification_key_id"], range::GENERATORS_ID);
+    assert_eq!(json["proof"], "030405");
+    assert_eq!(json["public_inputs"], "0102");
+}
+
+#[test]
+fn snark_exports_need_a_verifying_key() {
+    assert!(export(&proof("zk-SNARK"), ProofExportFormat::Json).is_err());
+}
@@ -0,0 +1,124 @@
+//! Bulletproofs range proofs for published statistics
+//!
+//! The exact value behind a published statistic is committed with a Pedersen
+//! commitment over Ristretto, in fixed point with two decimals. One
+//! aggregated proof shows that the value minus the lower bound and the upper
+//! bound minus the value both fit in 32 bits, so the statistic lies within
+//! its bounds without the value or the records behind it being revealed.
+//! Verifiers derive both range commitments from the value's commitment.
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use candid::{CandidType, Deserialize};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Fixed-point scale of committed values and bounds
+pub const SCALE: f64 = 100.0;
+const BITS: usize = 32;
+/// Identifies the default Pedersen and 32-bit, two-party Bulletproof generators
+pub const GENERATORS_ID: &str = "bulletproofs-ristretto255/pedersen/n32-m2";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct StatisticRange {
+    pub statistic: String,
+    /// Bounds multiplied by `SCALE`
+    pub lower: u64,
+    pub upper: u64,
+    /// Compressed commitment to the scaled value
+    pub commitment: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RangeCheck {
+    pub statistic: String,
+    pub lower: f64,
+    pub upper: f64,
+    pub verified: bool,
+}
+
+fn transcript(computation_id: &str, statistic: &str) -> Transcript {
+    let mut transcript = Transcript::new(b"securecollab-range-proof");
+    transcript.append_message(b"computation", computation_id.as_bytes());
+    transcript.append_message(b"statistic", statistic.as_bytes());
+    transcript
+}
+
+fn rng(parts: &[&[u8]]) -> ChaCha20Rng {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    ChaCha20Rng::from_seed(hasher.finalize().into())
+}
+
+fn to_fixed(value: f64) -> u64 {
+    (value * SCALE).round().max(0.0) as u64
+}
+
+/// Commit `value` and prove it lies in `[lower, upper]`; None if it does not
+pub fn prove(computation_id: &str, statistic: &str, value: f64, lower: f64, upper: f64, seed: &[u8]) -> Option<StatisticRange> {
+    if !(lower..=upper).contains(&value) {
+        return None;
+    }
+    let (value, lower, upper) = (to_fixed(value), to_fixed(lower), to_fixed(upper));
+    let blinding = Scalar::hash_from_bytes::<Sha512>(&[seed, computation_id.as_bytes(), statistic.as_bytes()].concat());
+    let pc_gens = PedersenGens::default();
+    let commitment = pc_gens.commit(Scalar::from(value), blinding).compress();
+
+    let (proof, _) = RangeProof::prove_multiple_with_rng(
+        &BulletproofGens::new(BITS, 2),
+        &pc_gens,
+        &mut transcript(computation_id, statistic),
+        &[value - lower, upper - value],
+        &[blinding, -blinding],
+        BITS,
+        &mut rng(&[b"range-proof", seed, statistic.as_bytes()]),
+    ).ok()?;
+
+    Some(StatisticRange {
+        statistic: statistic.to_string(),
+        lower,
+        upper,
+        commitment: commitment.as_bytes().to_vec(),
+        proof: proof.to_bytes(),
+    })
+}
+
+pub fn verify(computation_id: &str, range: &StatisticRange) -> bool {
+    let Some(point) = CompressedRistretto::from_slice(&range.commitment).ok().and_then(|c| c.decompress()) else {
+        return false;
+    };
+    let Ok(proof) = RangeProof::from_bytes(&range.proof) else {
+        return false;
+    };
+    let pc_gens = PedersenGens::default();
+    let above_lower = point - pc_gens.B * Scalar::from(range.lower);
+    let below_upper = pc_gens.B * Scalar::from(range.upper) - point;
+
+    proof.verify_multiple_with_rng(
+        &BulletproofGens::new(BITS, 2),
+        &pc_gens,
+        &mut transcript(computation_id, &range.statistic),
+        &[above_lower.compress(), below_upper.compress()],
+        BITS,
+        &mut rng(&[b"range-verify", &range.proof]),
+    ).is_ok()
+}
+
+pub fn check(computation_id: &str, range: &StatisticRange) -> RangeCheck {
+    RangeCheck {
+        statistic: range.statistic.clone(),
+        lower: range.lower as f64 / SCALE,
+        upper: range.upper as f64 / SCALE,
+        verified: verify(computation_id, range),
+    }
+}
+
+#[cfg(test)]
+#[path = "range_test.rs"]
+mod tests;
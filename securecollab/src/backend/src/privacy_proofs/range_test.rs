@@ -0,0 +1,28 @@
+use super::*;
+
+#[test]
+fn values_within_bounds_are_proven() {
+    let range = prove("q1", "Average recovery time (days)", 14.2, 0.0, 365.0, b"seed").unwrap();
+    assert_eq!((range.lower, range.upper), (0, 36_500));
+    assert!(verify("q1", &range));
+
+    let check = check("q1", &range);
+    assert_eq!((check.lower, check.upper), (0.0, 365.0));
+    assert!(check.verified);
+}
+
+#[test]
+fn values_outside_bounds_get_no_proof() {
+    assert!(prove("q1", "Treatment effectiveness (A)", 100.5, 0.0, 100.0, b"seed").is_none());
+    assert!(prove("q1", "Treatment effectiveness (A)", -0.1, 0.0, 100.0, b"seed").is_none());
+}
+
+#[test]
+fn proofs_do_not_transfer_to_other_bounds_or_statistics() {
+    let range = prove("q1", "Treatment effectiveness (A)", 78.5, 0.0, 100.0, b"seed").unwrap();
+
+    // Claiming tighter bounds than the value satisfies fails
+    assert!(!verify("q1", &StatisticRange { upper: 5_000, ..range.clone() }));
+    assert!(!verify("q1", &StatisticRange { statistic: "Treatment effectiveness (B)".to_string(), ..range.clone() }));
+    assert!(!verify("q2", &range));
+}
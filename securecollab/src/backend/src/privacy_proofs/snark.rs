@@ -0,0 +1,338 @@
+//! Groth16 proofs over BN254 that an aggregate is the sum of committed inputs
+//!
+//! Each dataset contributes one value to the aggregate (its record count).
+//! The value is committed with MiMC in Miyaguchi-Preneel mode, starting from
+//! a random blinding factor and bound to the SHA-256 digest of the dataset's
+//! ciphertext. The aggregate is committed the same way, and the circuit
+//! proves that the committed aggregate is the sum of the committed inputs.
+//! Ciphertext digests and commitments are the public inputs; values and
+//! blinding factors stay private.
+//!
+//! Keys are generated in the canister once per number of inputs from
+//! `raw_rand` randomness that is dropped after setup, so verifiers trust the
+//! canister's setup as they trust it with the decrypted data. The keys
+//! themselves are part of state backups, so proofs issued before a restore
+//! still verify after it.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use crate::backup;
+
+/// Rounds of the x^5 MiMC permutation needed over the BN254 scalar field
+const MIMC_ROUNDS: u32 = 110;
+
+/// Stands in for the ciphertext digest when committing the aggregate
+const OUTPUT_DOMAIN: u64 = 0x6167_6772_6567_6174;
+
+#[derive(Clone)]
+struct CommittedInput {
+    digest: Fr,
+    value: Fr,
+    blinding: Fr,
+}
+
+/// Proves `commit(sum of values) == output commitment` given every input's
+/// `commit(value, digest) == commitment`
+#[derive(Clone)]
+struct AggregateCircuit {
+    inputs: Vec<CommittedInput>,
+    output_blinding: Fr,
+}
+
+struct Keys {
+    proving: ProvingKey<Bn254>,
+    verifying: PreparedVerifyingKey<Bn254>,
+}
+
+thread_local! {
+    static COMMITTED: RefCell<HashMap<String, AggregateCircuit>> = RefCell::new(HashMap::new());
+    // Keys per number of inputs; each arity is its own circuit
+    static KEYS: RefCell<HashMap<usize, Keys>> = RefCell::new(HashMap::new());
+}
+
+fn hash_to_field(parts: &[&[u8]]) -> Fr {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Fr::from_be_bytes_mod_order(&hasher.finalize())
+}
+
+fn rng(seed: &[u8], purpose: &str) -> ChaCha20Rng {
+    let mut hasher = Sha256::new();
+    hasher.update(purpose.as_bytes());
+    hasher.update(seed);
+    ChaCha20Rng::from_seed(hasher.finalize().into())
+}
+
+fn round_constants() -> Vec<Fr> {
+    (0..MIMC_ROUNDS).map(|i| hash_to_field(&[b"securecollab-mimc", &i.to_be_bytes()])).collect()
+}
+
+fn mimc(key: Fr, message: Fr, constants: &[Fr]) -> Fr {
+    let state = constants.iter().fold(message, |state, c| {
+        let t = state + key + c;
+        t.square().square() * t
+    });
+    state + key
+}
+
+fn commit(blinding: Fr, blocks: &[Fr], constants: &[Fr]) -> Fr {
+    blocks.iter().fold(blinding, |h, m| mimc(h, *m, constants) + m + h)
+}
+
+/// A circuit value with its assignment
+#[derive(Clone)]
+struct Num {
+    lc: LinearCombination<Fr>,
+    value: Fr,
+}
+
+impl Num {
+    fn constant(value: Fr) -> Self {
+        Self { lc: LinearCombination::zero() + (value, Variable::One), value }
+    }
+
+    fn witness(cs: &ConstraintSystemRef<Fr>, value: Fr) -> Result<Self, SynthesisError> {
+        let var = cs.new_witness_variable(|| Ok(value))?;
+        Ok(Self { lc: LinearCombination::zero() + var, value })
+    }
+
+    fn input(cs: &ConstraintSystemRef<Fr>, value: Fr) -> Result<Self, SynthesisError> {
+        let var = cs.new_input_variable(|| Ok(value))?;
+        Ok(Self { lc: LinearCombination::zero() + var, value })
+    }
+
+    fn add(&self, other: &Num) -> Num {
+        Num { lc: self.lc.clone() + &other.lc, value: self.value + other.value }
+    }
+
+    fn mul(&self, cs: &ConstraintSystemRef<Fr>, other: &Num) -> Result<Num, SynthesisError> {
+        let product = Num::witness(cs, self.value * other.value)?;
+        cs.enforce_constraint(self.lc.clone(), other.lc.clone(), product.lc.clone())?;
+        Ok(product)
+    }
+}
+
+fn mimc_gadget(cs: &ConstraintSystemRef<Fr>, key: &Num, message: &Num, constants: &[Fr]) -> Result<Num, SynthesisError> {
+    let mut state = message.clone();
+    for c in constants {
+        let t = state.add(key).add(&Num::constant(*c));
+        let t2 = t.mul(cs, &t)?;
+        let t4 = t2.mul(cs, &t2)?;
+        state = t4.mul(cs, &t)?;
+    }
+    Ok(state.add(key))
+}
+
+fn commit_gadget(cs: &ConstraintSystemRef<Fr>, blinding: &Num, blocks: &[Num], constants: &[Fr]) -> Result<Num, SynthesisError> {
+    blocks.iter().try_fold(blinding.clone(), |h, m| Ok(mimc_gadget(cs, &h, m, constants)?.add(m).add(&h)))
+}
+
+fn enforce_equal(cs: &ConstraintSystemRef<Fr>, a: &Num, b: &Num) -> Result<(), SynthesisError> {
+    cs.enforce_constraint(a.lc.clone() - &b.lc, LinearCombination::zero() + Variable::One, LinearCombination::zero())
+}
+
+impl AggregateCircuit {
+    fn sum(&self) -> Fr {
+        self.inputs.iter().map(|input| input.value).sum()
+    }
+
+    /// Digest and commitment of every input, then the aggregate's commitment
+    fn public_inputs(&self) -> Vec<Fr> {
+        let constants = round_constants();
+        let mut public: Vec<Fr> = self.inputs.iter()
+            .flat_map(|input| [input.digest, commit(input.blinding, &[input.value, input.digest], &constants)])
+            .collect();
+        public.push(commit(self.output_blinding, &[self.sum(), Fr::from(OUTPUT_DOMAIN)], &constants));
+        public
+    }
+
+    /// Same shape as a real circuit with `arity` inputs, for key generation
+    fn blank(arity: usize) -> Self {
+        let input = CommittedInput { digest: Fr::zero(), value: Fr::zero(), blinding: Fr::zero() };
+        Self { inputs: vec![input; arity], output_blinding: Fr::zero() }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for AggregateCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let constants = round_constants();
+        let public = self.public_inputs();
+        let mut sum = Num::constant(Fr::zero());
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            let digest = Num::input(&cs, input.digest)?;
+            let commitment = Num::input(&cs, public[2 * i + 1])?;
+            let value = Num::witness(&cs, input.value)?;
+            let blinding = Num::witness(&cs, input.blinding)?;
+            let computed = commit_gadget(&cs, &blinding, &[value.clone(), digest], &constants)?;
+            enforce_equal(&cs, &computed, &commitment)?;
+            sum = sum.add(&value);
+        }
+
+        let commitment = Num::input(&cs, public[public.len() - 1])?;
+        let blinding = Num::witness(&cs, self.output_blinding)?;
+        let computed = commit_gadget(&cs, &blinding, &[sum, Num::constant(Fr::from(OUTPUT_DOMAIN))], &constants)?;
+        enforce_equal(&cs, &computed, &commitment)
+    }
+}
+
+/// Commit a computation's inputs, one per dataset as (ciphertext, value)
+pub fn commit_inputs(computation_id: &str, inputs: &[(&[u8], u64)], seed: &[u8]) {
+    let blinding = |label: &[u8]| hash_to_field(&[b"securecollab-blinding", seed, computation_id.as_bytes(), label]);
+    let circuit = AggregateCircuit {
+        inputs: inputs.iter().enumerate()
+            .map(|(i, (ciphertext, value))| CommittedInput {
+                digest: hash_to_field(&[ciphertext]),
+                value: Fr::from(*value),
+                blinding: blinding(&(i as u64).to_be_bytes()),
+            })
+            .collect(),
+        output_blinding: blinding(b"output"),
+    };
+    COMMITTED.with(|committed| committed.borrow_mut().insert(computation_id.to_string(), circuit));
+}
+
+/// Public inputs of a computation's committed aggregate
+pub fn public_inputs(computation_id: &str) -> Option<Vec<Fr>> {
+    COMMITTED.with(|committed| committed.borrow().get(computation_id).map(AggregateCircuit::public_inputs))
+}
+
+fn with_keys<T>(arity: usize, seed: &[u8], f: impl FnOnce(&Keys) -> T) -> Result<T, String> {
+    KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let keys = match keys.entry(arity) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (proving, verifying) = Groth16::<Bn254>::circuit_specific_setup(AggregateCircuit::blank(arity), &mut rng(seed, "setup"))
+                    .map_err(|e| format!("Groth16 setup failed: {}", e))?;
+                let verifying = Groth16::<Bn254>::process_vk(&verifying)
+                    .map_err(|e| format!("Groth16 setup failed: {}", e))?;
+                entry.insert(Keys { proving, verifying })
+            }
+        };
+        Ok(f(keys))
+    })
+}
+
+/// Prove a computation's committed aggregate; returns the proof and its
+/// public inputs, both serialized
+pub fn prove(computation_id: &str, seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let circuit = COMMITTED.with(|committed| committed.borrow().get(computation_id).cloned())
+        .ok_or_else(|| format!("No committed aggregate inputs recorded for {}", computation_id))?;
+    let public = circuit.public_inputs();
+    let proof = with_keys(circuit.inputs.len(), seed, |keys| {
+        Groth16::<Bn254>::prove(&keys.proving, circuit, &mut rng(seed, "prove"))
+    })?.map_err(|e| format!("Groth16 proving failed: {}", e))?;
+
+    let mut proof_bytes = Vec::new();
+    let mut public_bytes = Vec::new();
+    proof.serialize_compressed(&mut proof_bytes).map_err(|e| e.to_string())?;
+    public.serialize_compressed(&mut public_bytes).map_err(|e| e.to_string())?;
+    Ok((proof_bytes, public_bytes))
+}
+
+/// Verify a proof against its public inputs and, when recorded, against the
+/// computation's own commitments
+pub fn verify(computation_id: &str, proof_bytes: &[u8], public_bytes: &[u8]) -> Result<bool, String> {
+    let proof = Proof::<Bn254>::deserialize_compressed(proof_bytes).map_err(|e| format!("Malformed proof: {}", e))?;
+    let public = Vec::<Fr>::deserialize_compressed(public_bytes).map_err(|e| format!("Malformed public inputs: {}", e))?;
+    if public_inputs(computation_id).is_some_and(|recorded| recorded != public) {
+        return Ok(false);
+    }
+    if public.len() % 2 == 0 {
+        return Err("Public inputs must be digest and commitment pairs plus the aggregate commitment".to_string());
+    }
+
+    let arity = public.len() / 2;
+    KEYS.with(|keys| {
+        let keys = keys.borrow();
+        let keys = keys.get(&arity).ok_or_else(|| format!("No verifying key for {} inputs", arity))?;
+        Groth16::<Bn254>::verify_with_processed_vk(&keys.verifying, &public, &proof)
+            .map_err(|e| format!("Groth16 verification failed: {}", e))
+    })
+}
+
+/// Id and serialized verifying key for proofs with these public inputs
+pub fn verifying_key(public_bytes: &[u8]) -> Result<(String, Vec<u8>), String> {
+    let public = Vec::<Fr>::deserialize_compressed(public_bytes).map_err(|e| format!("Malformed public inputs: {}", e))?;
+    let arity = public.len() / 2;
+    let key = KEYS.with(|keys| {
+        let keys = keys.borrow();
+        let keys = keys.get(&arity).ok_or_else(|| format!("No verifying key for {} inputs", arity))?;
+        let mut bytes = Vec::new();
+        keys.verifying.vk.serialize_compressed(&mut bytes).map_err(|e| e.to_string())?;
+        Ok::<_, String>(bytes)
+    })?;
+    Ok((format!("groth16-bn254/aggregate-sum/{}", arity), key))
+}
+
+// Circuits are kept as their field elements, output blinding first, and keys
+// as their proving key, which includes the verifying key
+pub fn export_state() -> Result<Vec<u8>, String> {
+    let circuits = COMMITTED.with(|committed| {
+        committed.borrow().iter()
+            .map(|(id, circuit)| {
+                let mut fields = vec![circuit.output_blinding];
+                fields.extend(circuit.inputs.iter().flat_map(|input| [input.digest, input.value, input.blinding]));
+                let mut bytes = Vec::new();
+                fields.serialize_compressed(&mut bytes).map_err(|e| e.to_string())?;
+                Ok((id.clone(), bytes))
+            })
+            .collect::<Result<HashMap<String, Vec<u8>>, String>>()
+    })?;
+    let keys = KEYS.with(|keys| {
+        keys.borrow().iter()
+            .map(|(arity, keys)| {
+                let mut bytes = Vec::new();
+                keys.proving.serialize_compressed(&mut bytes).map_err(|e| e.to_string())?;
+                Ok((*arity as u64, bytes))
+            })
+            .collect::<Result<HashMap<u64, Vec<u8>>, String>>()
+    })?;
+    backup::encode(&(circuits, keys))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (circuits, keys): (HashMap<String, Vec<u8>>, HashMap<u64, Vec<u8>>) = backup::decode(bytes)?;
+    let circuits = circuits.into_iter()
+        .map(|(id, bytes)| {
+            let fields = Vec::<Fr>::deserialize_compressed(bytes.as_slice()).map_err(|e| format!("Malformed circuit {}: {}", id, e))?;
+            let (output_blinding, inputs) = fields.split_first().ok_or_else(|| format!("Malformed circuit {}", id))?;
+            if inputs.len() % 3 != 0 {
+                return Err(format!("Malformed circuit {}", id));
+            }
+            let inputs = inputs.chunks(3)
+                .map(|fields| CommittedInput { digest: fields[0], value: fields[1], blinding: fields[2] })
+                .collect();
+            Ok((id, AggregateCircuit { inputs, output_blinding: *output_blinding }))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()?;
+    let keys = keys.into_iter()
+        .map(|(arity, bytes)| {
+            let proving = ProvingKey::<Bn254>::deserialize_compressed(bytes.as_slice())
+                .map_err(|e| format!("Malformed proving key for {} inputs: {}", arity, e))?;
+            let verifying = Groth16::<Bn254>::process_vk(&proving.vk)
+                .map_err(|e| format!("Malformed proving key for {} inputs: {}", arity, e))?;
+            Ok((arity as usize, Keys { proving, verifying }))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()?;
+    backup::replace(&COMMITTED, circuits);
+    backup::replace(&KEYS, keys);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "snark_test.rs"]
+mod tests;
@@ -0,0 +1,56 @@
+use super::*;
+use ark_relations::r1cs::ConstraintSystem;
+
+#[test]
+fn committed_inputs_satisfy_the_circuit() {
+    commit_inputs("sum", &[(b"ciphertext a", 12), (b"ciphertext b", 30)], b"seed");
+    let circuit = COMMITTED.with(|committed| committed.borrow()["sum"].clone());
+    assert_eq!(circuit.sum(), Fr::from(42u64));
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_instance_variables(), 1 + 5);
+}
+
+#[test]
+fn proofs_verify_only_against_their_commitments() {
+    commit_inputs("q1", &[(b"ciphertext a", 12), (b"ciphertext b", 30)], b"seed");
+    let (proof, public) = prove("q1", b"seed").unwrap();
+    assert!(verify("q1", &proof, &public).unwrap());
+
+    // Another computation's commitments are not covered by this proof
+    commit_inputs("q2", &[(b"ciphertext a", 12), (b"ciphertext b", 31)], b"seed");
+    assert!(!verify("q2", &proof, &public).unwrap());
+
+    // A different aggregate commitment fails the pairing check
+    let mut forged = Vec::<Fr>::deserialize_compressed(public.as_slice()).unwrap();
+    *forged.last_mut().unwrap() += Fr::from(1u64);
+    let mut forged_bytes = Vec::new();
+    forged.serialize_compressed(&mut forged_bytes).unwrap();
+    assert!(!verify("unrecorded", &proof, &forged_bytes).unwrap());
+
+    assert!(prove("missing", b"seed").is_err());
+}
+
+#[test]
+fn proofs_still_verify_after_a_restore() {
+    commit_inputs("q1", &[(b"ciphertext a", 12), (b"ciphertext b", 30)], b"seed");
+    let (proof, public) = prove("q1", b"seed").unwrap();
+    let (_, key) = verifying_key(&public).unwrap();
+    let snapshot = export_state().unwrap();
+
+    backup::replace(&COMMITTED, HashMap::new());
+    backup::replace(&KEYS, HashMap::new());
+    assert!(verify("q1", &proof, &public).is_err());
+
+    import_state(&snapshot).unwrap();
+    assert!(verify("q1", &proof, &public).unwrap());
+    assert_eq!(verifying_key(&public).unwrap().1, key);
+
+    // New proofs use the restored keys rather than a fresh setup
+    commit_inputs("q2", &[(b"ciphertext c", 5), (b"ciphertext d", 7)], b"other seed");
+    let (proof, public) = prove("q2", b"other seed").unwrap();
+    assert!(verify("q2", &proof, &public).unwrap());
+    assert_eq!(verifying_key(&public).unwrap().1, key);
+}
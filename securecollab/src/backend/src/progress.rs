@@ -0,0 +1,172 @@
+//! Live progress of running computations
+//!
+//! Execution paths emit progress events as they move through decryption,
+//! analysis, aggregation and proving. Requesters poll the latest state while
+//! a computation runs. Percent complete never moves backwards within a run.
+
+use candid::{CandidType, Deserialize};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ComputationPhase {
+    Decrypting,
+    Analyzing,
+    Aggregating,
+    Proving,
+    Completed,
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum AgentState {
+    Pending,
+    Running,
+    Substituted,
+    Completed,
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AgentProgress {
+    pub agent_id: String,
+    pub state: AgentState,
+    pub detail: Option<String>,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ComputationProgress {
+    pub request_id: String,
+    pub phase: ComputationPhase,
+    pub percent_complete: u8,
+    pub agents: Vec<AgentProgress>,
+    pub message: Option<String>,
+    pub started_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Clone, Debug)]
+pub enum ProgressEvent {
+    Phase(ComputationPhase),
+    /// Work done within the current phase
+    Fraction { done: u64, total: u64 },
+    Agent { agent_id: String, state: AgentState, detail: Option<String> },
+    Note(String),
+    Failed(String),
+}
+
+thread_local! {
+    static PROGRESS: RefCell<HashMap<String, ComputationProgress>> = RefCell::new(HashMap::new());
+}
+
+/// Percent range covered by each phase
+fn phase_range(phase: &ComputationPhase) -> (u8, u8) {
+    match phase {
+        ComputationPhase::Decrypting => (0, 25),
+        ComputationPhase::Analyzing => (25, 75),
+        ComputationPhase::Aggregating => (75, 90),
+        ComputationPhase::Proving => (90, 100),
+        ComputationPhase::Completed => (100, 100),
+        ComputationPhase::Failed => (0, 0),
+    }
+}
+
+/// Percent complete after `done` of `total` units of work in `phase`
+pub fn percent_within(phase: &ComputationPhase, done: u64, total: u64) -> u8 {
+    let (low, high) = phase_range(phase);
+    let span = u64::from(high - low);
+    let progressed = (span * done.min(total)).checked_div(total).unwrap_or(0);
+    low + progressed as u8
+}
+
+impl ComputationProgress {
+    pub fn new(request_id: &str, now: u64) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            phase: ComputationPhase::Decrypting,
+            percent_complete: 0,
+            agents: Vec::new(),
+            message: None,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn apply(&mut self, event: ProgressEvent, now: u64) {
+        match event {
+            ProgressEvent::Phase(phase) => {
+                self.advance(phase_range(&phase).0);
+                if phase == ComputationPhase::Completed {
+                    self.message = None;
+                }
+                self.phase = phase;
+            }
+            ProgressEvent::Fraction { done, total } => {
+                self.advance(percent_within(&self.phase, done, total));
+            }
+            ProgressEvent::Agent { agent_id, state, detail } => {
+                match self.agents.iter_mut().find(|a| a.agent_id == agent_id) {
+                    Some(agent) => {
+                        agent.state = state;
+                        agent.detail = detail;
+                        agent.updated_at = now;
+                    }
+                    None => self.agents.push(AgentProgress { agent_id, state, detail, updated_at: now }),
+                }
+                // Agents finishing is what moves the analysis phase forward
+                if self.phase == ComputationPhase::Analyzing {
+                    let finished = self.agents.iter()
+                        .filter(|a| matches!(a.state, AgentState::Completed | AgentState::Failed))
+                        .count();
+                    self.advance(percent_within(&self.phase, finished as u64, self.agents.len() as u64));
+                }
+            }
+            ProgressEvent::Note(message) => self.message = Some(message),
+            ProgressEvent::Failed(error) => {
+                self.phase = ComputationPhase::Failed;
+                self.message = Some(error);
+            }
+        }
+        self.updated_at = now;
+    }
+
+    fn advance(&mut self, percent: u8) {
+        self.percent_complete = self.percent_complete.max(percent);
+    }
+}
+
+/// Start tracking a run, replacing the progress of any earlier run under the same id
+pub fn begin(request_id: &str, now: u64) {
+    PROGRESS.with(|progress| {
+        progress.borrow_mut().insert(request_id.to_string(), ComputationProgress::new(request_id, now));
+    });
+}
+
+pub fn report(request_id: &str, event: ProgressEvent, now: u64) {
+    PROGRESS.with(|progress| {
+        progress.borrow_mut()
+            .entry(request_id.to_string())
+            .or_insert_with(|| ComputationProgress::new(request_id, now))
+            .apply(event, now);
+    });
+}
+
+pub fn get(request_id: &str) -> Option<ComputationProgress> {
+    PROGRESS.with(|progress| progress.borrow().get(request_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&PROGRESS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let progress = backup::decode(bytes)?;
+    backup::replace(&PROGRESS, progress);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "progress_test.rs"]
+mod tests;
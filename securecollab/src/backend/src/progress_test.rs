@@ -0,0 +1,50 @@
+use super::*;
+
+fn agent(agent_id: &str, state: AgentState) -> ProgressEvent {
+    ProgressEvent::Agent { agent_id: agent_id.to_string(), state, detail: None }
+}
+
+#[test]
+fn percent_is_scaled_into_the_phase_range() {
+    assert_eq!(percent_within(&ComputationPhase::Decrypting, 0, 4), 0);
+    assert_eq!(percent_within(&ComputationPhase::Decrypting, 2, 4), 12);
+    assert_eq!(percent_within(&ComputationPhase::Analyzing, 1, 2), 50);
+    assert_eq!(percent_within(&ComputationPhase::Analyzing, 9, 2), 75);
+    assert_eq!(percent_within(&ComputationPhase::Aggregating, 0, 0), 75);
+}
+
+#[test]
+fn finished_agents_advance_the_analysis_phase() {
+    let mut progress = ComputationProgress::new("req_1", 1);
+    progress.apply(ProgressEvent::Phase(ComputationPhase::Analyzing), 2);
+    progress.apply(agent("a", AgentState::Running), 3);
+    progress.apply(agent("b", AgentState::Running), 3);
+    assert_eq!(progress.percent_complete, 25);
+
+    progress.apply(agent("a", AgentState::Completed), 4);
+    assert_eq!(progress.percent_complete, 50);
+    assert_eq!(progress.agents.len(), 2);
+    assert_eq!(progress.agents[0].state, AgentState::Completed);
+    assert_eq!(progress.updated_at, 4);
+}
+
+#[test]
+fn percent_never_moves_backwards() {
+    let mut progress = ComputationProgress::new("req_1", 1);
+    progress.apply(ProgressEvent::Phase(ComputationPhase::Proving), 2);
+    progress.apply(ProgressEvent::Fraction { done: 0, total: 10 }, 3);
+    assert_eq!(progress.percent_complete, 90);
+
+    progress.apply(ProgressEvent::Phase(ComputationPhase::Completed), 4);
+    assert_eq!(progress.percent_complete, 100);
+}
+
+#[test]
+fn failure_keeps_progress_and_records_the_error() {
+    let mut progress = ComputationProgress::new("req_1", 1);
+    progress.apply(ProgressEvent::Phase(ComputationPhase::Aggregating), 2);
+    progress.apply(ProgressEvent::Failed("ledger unavailable".to_string()), 3);
+    assert_eq!(progress.phase, ComputationPhase::Failed);
+    assert_eq!(progress.percent_complete, 75);
+    assert_eq!(progress.message.as_deref(), Some("ledger unavailable"));
+}
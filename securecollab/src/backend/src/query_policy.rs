@@ -0,0 +1,224 @@
+//! Privacy policy checks on query plans before parties vote on them
+//!
+//! A SQL plan must aggregate, must not group by identifying columns and must
+//! not filter down to a sliver of the data: equality on an identifying
+//! column, more than `MAX_EQUALITY_CONDITIONS` equalities, or a range
+//! covering less than `MIN_RANGE_FRACTION` of a bounded column's declared
+//! range. A free-text query releases only the private aggregates of its
+//! columns, so it only must not ask for identifying columns by name.
+//!
+//! Columns are identifying when declared `id` or `text`, or when their name
+//! has a part such as `id`, `email` or `ssn`. Every decision, approved or
+//! not, carries one finding per rule and a rationale shown to approvers.
+//!
+//! Owners may bind a dataset to the purposes it can be used for, such as
+//! "oncology research". Every query and computation declares its purpose,
+//! which each purpose-bound dataset it reads must allow. Purposes compare
+//! case-insensitively with whitespace collapsed.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::analytics::{ColumnSpec, ColumnType};
+use crate::cohorts::Operator;
+use crate::sql::{SelectItem, SqlPlan};
+use crate::PrivateDataSource;
+
+pub const MAX_EQUALITY_CONDITIONS: usize = 3;
+pub const MIN_RANGE_FRACTION: f64 = 0.05;
+pub const MAX_PURPOSES: usize = 10;
+pub const MAX_PURPOSE_LENGTH: usize = 100;
+
+// Name parts that mark a column as identifying
+const IDENTIFYING_NAMES: &[&str] = &[
+    "id", "uuid", "ssn", "mrn", "email", "phone", "name", "address", "passport", "nhs", "dob",
+];
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum PolicyRule {
+    /// Only aggregates leave the computation
+    Aggregation,
+    /// Groups are not formed on identifying columns
+    GroupByIdentifier,
+    /// Filters keep a meaningful share of the records
+    NarrowFilter,
+    /// Identifying columns are not decrypted
+    IdentifyingColumns,
+    /// Every purpose-bound dataset allows the declared purpose
+    Purpose,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PolicyFinding {
+    pub rule: PolicyRule,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PolicyDecision {
+    pub approved: bool,
+    pub findings: Vec<PolicyFinding>,
+    pub rationale: String,
+}
+
+/// Whether a column could single out the people in a dataset
+pub fn is_identifying(spec: &ColumnSpec) -> bool {
+    matches!(spec.column_type, ColumnType::Identifier | ColumnType::Text)
+        || spec.name.to_ascii_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|part| IDENTIFYING_NAMES.contains(&part))
+}
+
+fn spec<'a>(schema: &'a [ColumnSpec], name: &str) -> Option<&'a ColumnSpec> {
+    schema.iter().find(|spec| spec.name == name)
+}
+
+fn finding(rule: PolicyRule, failures: Vec<String>, passed: &str) -> PolicyFinding {
+    if failures.is_empty() {
+        PolicyFinding { rule, passed: true, detail: passed.to_string() }
+    } else {
+        PolicyFinding { rule, passed: false, detail: failures.join("; ") }
+    }
+}
+
+pub fn decide(findings: Vec<PolicyFinding>) -> PolicyDecision {
+    let approved = findings.iter().all(|f| f.passed);
+    let details: Vec<&str> = findings.iter().filter(|f| f.passed == approved).map(|f| f.detail.as_str()).collect();
+    let rationale = format!("{}: {}", if approved { "Approved" } else { "Rejected" }, details.join("; "));
+    PolicyDecision { approved, findings, rationale }
+}
+
+/// Where filters confine a bounded column, within its declared bounds
+struct Interval {
+    low: f64,
+    high: f64,
+    min: f64,
+    max: f64,
+}
+
+fn narrow_filters(plan: &SqlPlan, schema: &[ColumnSpec]) -> Vec<String> {
+    let mut failures = Vec::new();
+    let equalities = plan.conditions.iter().filter(|c| c.operator == Operator::Equal).count();
+    if equalities > MAX_EQUALITY_CONDITIONS {
+        failures.push(format!("{} equality conditions narrow the records too far (at most {})", equalities, MAX_EQUALITY_CONDITIONS));
+    }
+
+    // The interval each bounded numeric column is filtered to
+    let mut ranges: HashMap<&str, Interval> = HashMap::new();
+    for condition in &plan.conditions {
+        let Some(spec) = spec(schema, &condition.column) else { continue };
+        if is_identifying(spec) && condition.operator == Operator::Equal {
+            failures.push(format!("'{}' is filtered to a single identifying value", condition.column));
+        }
+        let (ColumnType::Number { bounds: Some((min, max)), .. }, Ok(value)) = (&spec.column_type, condition.value.parse::<f64>()) else {
+            continue;
+        };
+        let range = ranges.entry(condition.column.as_str())
+            .or_insert(Interval { low: *min, high: *max, min: *min, max: *max });
+        match condition.operator {
+            Operator::Equal => {
+                range.low = range.low.max(value);
+                range.high = range.high.min(value);
+            }
+            Operator::Greater | Operator::GreaterOrEqual => range.low = range.low.max(value),
+            Operator::Less | Operator::LessOrEqual => range.high = range.high.min(value),
+            Operator::NotEqual => {}
+        }
+    }
+    let mut narrowed: Vec<(&str, Interval)> = ranges.into_iter().collect();
+    narrowed.sort_by(|a, b| a.0.cmp(b.0));
+    for (column, range) in narrowed {
+        if range.max > range.min && (range.high - range.low).max(0.0) / (range.max - range.min) < MIN_RANGE_FRACTION {
+            failures.push(format!("'{}' is filtered to under {}% of its range", column, MIN_RANGE_FRACTION * 100.0));
+        }
+    }
+    failures
+}
+
+pub fn normalize_purpose(purpose: &str) -> Result<String, String> {
+    let purpose = purpose.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    if purpose.is_empty() {
+        return Err("Declare the purpose of the request".to_string());
+    }
+    if purpose.chars().count() > MAX_PURPOSE_LENGTH {
+        return Err(format!("Purposes are at most {} characters", MAX_PURPOSE_LENGTH));
+    }
+    Ok(purpose)
+}
+
+/// Normalized purposes without duplicates; none leaves the dataset unbound
+pub fn normalize_purposes(purposes: &[String]) -> Result<Option<Vec<String>>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for purpose in purposes {
+        let purpose = normalize_purpose(purpose)?;
+        if !normalized.contains(&purpose) {
+            normalized.push(purpose);
+        }
+    }
+    if normalized.len() > MAX_PURPOSES {
+        return Err(format!("A dataset can allow at most {} purposes", MAX_PURPOSES));
+    }
+    Ok((!normalized.is_empty()).then_some(normalized))
+}
+
+/// Purposes data derived from two datasets keeps: those both allow
+pub fn combine_purposes(a: &Option<Vec<String>>, b: &Option<Vec<String>>) -> Option<Vec<String>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.iter().filter(|p| b.contains(p)).cloned().collect()),
+        (Some(only), None) | (None, Some(only)) => Some(only.clone()),
+        (None, None) => None,
+    }
+}
+
+/// Whether every purpose-bound dataset allows `purpose`, already normalized
+pub fn check_purpose(purpose: &str, datasets: &[PrivateDataSource]) -> PolicyFinding {
+    let mismatches: Vec<String> = datasets.iter()
+        .filter_map(|dataset| dataset.allowed_purposes.as_ref()
+            .filter(|allowed| !allowed.iter().any(|p| p == purpose))
+            .map(|allowed| format!("Dataset '{}' may only be used for {}", dataset.name, allowed.join(", "))))
+        .collect();
+    finding(PolicyRule::Purpose, mismatches, &format!("every dataset allows use for {}", purpose))
+}
+
+/// Decision on a SQL plan over datasets with `schema`
+pub fn evaluate_plan(plan: &SqlPlan, schema: &[ColumnSpec], purpose: PolicyFinding) -> PolicyDecision {
+    let ungrouped: Vec<String> = plan.select.iter()
+        .filter_map(|column| match &column.item {
+            SelectItem::Group(name) if !plan.group_by.contains(name) => Some(format!("'{}' is selected without aggregation", name)),
+            _ => None,
+        })
+        .collect();
+    let mut aggregation = ungrouped;
+    if plan.select.iter().all(|column| matches!(column.item, SelectItem::Group(_))) {
+        aggregation.push("The query computes no aggregate".to_string());
+    }
+    let grouped: Vec<String> = plan.group_by.iter()
+        .filter(|name| spec(schema, name).is_some_and(is_identifying))
+        .map(|name| format!("Grouping by '{}' would give each person a group", name))
+        .collect();
+
+    decide(vec![
+        finding(PolicyRule::Aggregation, aggregation, "only aggregates are released"),
+        finding(PolicyRule::GroupByIdentifier, grouped, "groups are formed on non-identifying columns"),
+        finding(PolicyRule::NarrowFilter, narrow_filters(plan, schema), "filters keep a broad share of the records"),
+        purpose,
+    ])
+}
+
+/// Decision on a free-text query decrypting `columns`, all of them if empty
+pub fn evaluate_columns(columns: &[String], schema: &[ColumnSpec], purpose: PolicyFinding) -> PolicyDecision {
+    let identifying: Vec<String> = columns.iter()
+        .filter(|name| spec(schema, name).is_some_and(is_identifying))
+        .map(|name| format!("'{}' is an identifying column", name))
+        .collect();
+    decide(vec![
+        finding(PolicyRule::Aggregation, vec![], "only differentially private aggregates of released columns are published"),
+        finding(PolicyRule::IdentifyingColumns, identifying, "no identifying columns are requested"),
+        purpose,
+    ])
+}
+
+#[cfg(test)]
+#[path = "query_policy_test.rs"]
+mod tests;
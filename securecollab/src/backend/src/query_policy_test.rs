@@ -0,0 +1,100 @@
+use super::*;
+use crate::analytics::parse_schema;
+use crate::sql;
+use crate::DatasetMetadata;
+use candid::Principal;
+
+const SCHEMA: &str = "patient_id:category,age:number[0..100],site:category,treatment:category,smoker:boolean,notes:text";
+
+fn any_purpose() -> PolicyFinding {
+    check_purpose("oncology research", &[])
+}
+
+fn plan(query: &str) -> PolicyDecision {
+    evaluate_plan(&sql::parse(query).unwrap(), &parse_schema(SCHEMA).unwrap(), any_purpose())
+}
+
+fn dataset(name: &str, purposes: Option<&[&str]>) -> PrivateDataSource {
+    PrivateDataSource {
+        id: name.to_string(),
+        owner: Principal::anonymous(),
+        party_name: "Boston General".to_string(),
+        name: name.to_string(),
+        encrypted_data: vec![],
+        vetkey_id: String::new(),
+        schema: SCHEMA.to_string(),
+        record_count: 0,
+        created_at: 0,
+        access_permissions: vec![],
+        metadata: DatasetMetadata::default(),
+        tags: vec![],
+        allowed_purposes: purposes.map(|p| p.iter().map(|p| p.to_string()).collect()),
+    }
+}
+
+fn failed(decision: &PolicyDecision) -> Vec<PolicyRule> {
+    decision.findings.iter().filter(|f| !f.passed).map(|f| f.rule).collect()
+}
+
+#[test]
+fn recognizes_identifying_columns() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let identifying: Vec<&str> = schema.iter().filter(|s| is_identifying(s)).map(|s| s.name.as_str()).collect();
+    assert_eq!(identifying, vec!["patient_id", "notes"]);
+}
+
+#[test]
+fn approves_broad_aggregates() {
+    let decision = plan("SELECT site, COUNT(*), AVG(age) FROM trial WHERE age>=60 AND treatment=Drug_X GROUP BY site");
+    assert!(decision.approved);
+    assert_eq!(decision.findings.len(), 4);
+    assert!(decision.rationale.starts_with("Approved: only aggregates are released"));
+}
+
+#[test]
+fn rejects_identifying_groups_and_narrow_filters() {
+    let grouped = plan("SELECT patient_id, COUNT(*) FROM trial GROUP BY patient_id");
+    assert_eq!(failed(&grouped), vec![PolicyRule::GroupByIdentifier]);
+    assert!(grouped.rationale.starts_with("Rejected: Grouping by 'patient_id'"));
+
+    assert_eq!(failed(&plan("SELECT COUNT(*) FROM trial WHERE patient_id=p-17")), vec![PolicyRule::NarrowFilter]);
+    assert_eq!(failed(&plan("SELECT COUNT(*) FROM trial WHERE age=63")), vec![PolicyRule::NarrowFilter]);
+    assert_eq!(failed(&plan("SELECT COUNT(*) FROM trial WHERE age>60 AND age<63")), vec![PolicyRule::NarrowFilter]);
+    let equalities = plan("SELECT COUNT(*) FROM trial WHERE site=north AND treatment=Drug_X AND smoker=true AND site!=south AND age!=3 AND treatment=Placebo");
+    assert!(!equalities.approved);
+    assert!(plan("SELECT COUNT(*) FROM trial WHERE age>60 AND age<70").approved);
+}
+
+#[test]
+fn checks_free_text_columns() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    assert!(evaluate_columns(&["age".to_string(), "site".to_string()], &schema, any_purpose()).approved);
+    assert!(evaluate_columns(&[], &schema, any_purpose()).approved);
+    let decision = evaluate_columns(&["age".to_string(), "notes".to_string()], &schema, any_purpose());
+    assert_eq!(failed(&decision), vec![PolicyRule::IdentifyingColumns]);
+}
+
+#[test]
+fn binds_datasets_to_purposes() {
+    let purposes = normalize_purposes(&["  Oncology   Research".to_string(), "oncology research".to_string(), "Drug safety".to_string()]).unwrap();
+    assert_eq!(purposes, Some(vec!["oncology research".to_string(), "drug safety".to_string()]));
+    assert_eq!(normalize_purposes(&[]).unwrap(), None);
+    assert!(normalize_purpose(" ").is_err());
+
+    let datasets = [dataset("trial", Some(&["oncology research", "drug safety"])), dataset("registry", None)];
+    assert!(check_purpose("drug safety", &datasets).passed);
+    let mismatch = check_purpose("marketing", &datasets);
+    assert!(!mismatch.passed);
+    assert_eq!(mismatch.detail, "Dataset 'trial' may only be used for oncology research, drug safety");
+    let decision = evaluate_columns(&[], &parse_schema(SCHEMA).unwrap(), mismatch);
+    assert_eq!(failed(&decision), vec![PolicyRule::Purpose]);
+}
+
+#[test]
+fn derived_data_keeps_both_restrictions() {
+    let oncology = Some(vec!["oncology research".to_string(), "drug safety".to_string()]);
+    let safety = Some(vec!["drug safety".to_string()]);
+    assert_eq!(combine_purposes(&oncology, &safety), safety);
+    assert_eq!(combine_purposes(&None, &safety), safety);
+    assert_eq!(combine_purposes(&None, &None), None);
+}
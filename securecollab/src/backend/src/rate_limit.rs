@@ -0,0 +1,143 @@
+//! Per-principal rate limiting
+//!
+//! Every update call spends a token from its caller's bucket. Buckets
+//! refill at a steady rate up to their capacity, so a principal can make a
+//! short burst of calls but cannot flood endpoints such as
+//! `create_llm_query` or `upload_private_data`. Admins tune the default
+//! limit and can give individual principals, such as busy agents, their own.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const NANOS_PER_MINUTE: u64 = 60 * NANOS_PER_SECOND;
+/// Buckets kept before the full ones are dropped
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Calls that can be made in a burst
+    pub capacity: u32,
+    pub refill_per_minute: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit { capacity: 30, refill_per_minute: 60 }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RateLimits {
+    pub default: RateLimit,
+    /// Principals with their own limit
+    pub overrides: Vec<(Principal, RateLimit)>,
+}
+
+struct Bucket {
+    tokens: u32,
+    refilled_at: u64,
+}
+
+thread_local! {
+    static DEFAULT_LIMIT: RefCell<RateLimit> = RefCell::new(RateLimit::default());
+    static OVERRIDES: RefCell<HashMap<Principal, RateLimit>> = RefCell::new(HashMap::new());
+    static BUCKETS: RefCell<HashMap<Principal, Bucket>> = RefCell::new(HashMap::new());
+}
+
+pub fn limit_for(principal: Principal) -> RateLimit {
+    OVERRIDES.with(|overrides| overrides.borrow().get(&principal).copied())
+        .unwrap_or_else(|| DEFAULT_LIMIT.with(|limit| *limit.borrow()))
+}
+
+pub fn limits() -> RateLimits {
+    let mut overrides: Vec<(Principal, RateLimit)> = OVERRIDES.with(|overrides| {
+        overrides.borrow().iter().map(|(principal, limit)| (*principal, *limit)).collect()
+    });
+    overrides.sort_by_key(|(principal, _)| *principal);
+    RateLimits { default: DEFAULT_LIMIT.with(|limit| *limit.borrow()), overrides }
+}
+
+fn validate(limit: &RateLimit) -> Result<(), String> {
+    if limit.capacity == 0 || limit.refill_per_minute == 0 {
+        return Err("Rate limits need a positive capacity and refill rate".to_string());
+    }
+    Ok(())
+}
+
+pub fn set_default(limit: RateLimit) -> Result<RateLimits, String> {
+    validate(&limit)?;
+    DEFAULT_LIMIT.with(|default| *default.borrow_mut() = limit);
+    Ok(limits())
+}
+
+/// Give a principal its own limit, or `None` to go back to the default
+pub fn set_override(principal: Principal, limit: Option<RateLimit>) -> Result<RateLimits, String> {
+    match limit {
+        Some(limit) => {
+            validate(&limit)?;
+            OVERRIDES.with(|overrides| overrides.borrow_mut().insert(principal, limit));
+        }
+        None => {
+            OVERRIDES.with(|overrides| overrides.borrow_mut().remove(&principal));
+        }
+    }
+    Ok(limits())
+}
+
+// Add the whole tokens earned since the last refill; the time towards the
+// next token carries over
+fn refill(bucket: &mut Bucket, limit: RateLimit, now: u64) {
+    let per_token = NANOS_PER_MINUTE / limit.refill_per_minute as u64;
+    let earned = now.saturating_sub(bucket.refilled_at) / per_token.max(1);
+    let tokens = (bucket.tokens as u64).saturating_add(earned);
+    if tokens >= limit.capacity as u64 {
+        bucket.tokens = limit.capacity;
+        bucket.refilled_at = now;
+    } else {
+        bucket.tokens = tokens as u32;
+        bucket.refilled_at += earned * per_token;
+    }
+}
+
+/// Spend one of the principal's tokens
+pub fn consume(principal: Principal, now: u64) -> Result<(), String> {
+    let limit = limit_for(principal);
+    BUCKETS.with(|buckets| {
+        let mut buckets = buckets.borrow_mut();
+        if buckets.len() >= MAX_TRACKED_BUCKETS && !buckets.contains_key(&principal) {
+            buckets.retain(|principal, bucket| {
+                let limit = limit_for(*principal);
+                refill(bucket, limit, now);
+                bucket.tokens < limit.capacity
+            });
+        }
+        let bucket = buckets.entry(principal).or_insert(Bucket { tokens: limit.capacity, refilled_at: now });
+        refill(bucket, limit, now);
+        if bucket.tokens == 0 {
+            let per_token = NANOS_PER_MINUTE / limit.refill_per_minute as u64;
+            let wait = (bucket.refilled_at + per_token).saturating_sub(now);
+            return Err(format!("Rate limit exceeded; try again in {} seconds", wait.div_ceil(NANOS_PER_SECOND).max(1)));
+        }
+        bucket.tokens -= 1;
+        Ok(())
+    })
+}
+
+// Buckets refill on their own, so only the limits are backed up
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&DEFAULT_LIMIT), backup::cloned(&OVERRIDES)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (default_limit, overrides) = backup::decode(bytes)?;
+    backup::replace(&DEFAULT_LIMIT, default_limit);
+    backup::replace(&OVERRIDES, overrides);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "rate_limit_test.rs"]
+mod tests;
@@ -0,0 +1,43 @@
+use super::*;
+use crate::test_support::principal;
+
+#[test]
+fn bursts_are_limited_and_tokens_refill() {
+    let caller = principal(1);
+    set_default(RateLimit { capacity: 3, refill_per_minute: 6 }).unwrap();
+    for _ in 0..3 {
+        consume(caller, 0).unwrap();
+    }
+    assert!(consume(caller, 0).unwrap_err().contains("10 seconds"));
+    assert!(consume(principal(2), 0).is_ok());
+
+    // One token every 10 seconds; time towards the next one carries over
+    assert!(consume(caller, 9 * NANOS_PER_SECOND).is_err());
+    consume(caller, 15 * NANOS_PER_SECOND).unwrap();
+    assert!(consume(caller, 19 * NANOS_PER_SECOND).is_err());
+    consume(caller, 20 * NANOS_PER_SECOND).unwrap();
+
+    // Idle buckets fill up to their capacity and no further
+    for _ in 0..3 {
+        consume(caller, 10 * NANOS_PER_MINUTE).unwrap();
+    }
+    assert!(consume(caller, 10 * NANOS_PER_MINUTE).is_err());
+}
+
+#[test]
+fn principals_can_have_their_own_limit() {
+    let (agent, other) = (principal(1), principal(2));
+    assert!(set_default(RateLimit { capacity: 0, refill_per_minute: 1 }).is_err());
+    assert!(set_override(agent, Some(RateLimit { capacity: 1, refill_per_minute: 0 })).is_err());
+
+    let limits = set_override(agent, Some(RateLimit { capacity: 100, refill_per_minute: 600 })).unwrap();
+    assert_eq!(limits.overrides, vec![(agent, RateLimit { capacity: 100, refill_per_minute: 600 })]);
+    assert_eq!(limit_for(other), RateLimit::default());
+    for _ in 0..100 {
+        consume(agent, 0).unwrap();
+    }
+    assert!(consume(agent, 0).is_err());
+
+    assert!(set_override(agent, None).unwrap().overrides.is_empty());
+    assert_eq!(limit_for(agent), RateLimit::default());
+}
@@ -0,0 +1,280 @@
+//! Guardian-approved identity recovery
+//!
+//! A user designates guardian principals ahead of time. If they lose their
+//! Internet Identity, a request from the new principal is approved by a
+//! threshold of guardians and becomes executable only after a mandatory delay,
+//! during which the lost principal and guardians are notified and the lost
+//! principal can still veto it. Once execution starts the request can no
+//! longer be vetoed, and an execution that fails part-way is run again until
+//! every step has completed.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::backup;
+
+/// Mandatory waiting period between request and execution (48 hours)
+const RECOVERY_DELAY_NS: u64 = 48 * 60 * 60 * 1_000_000_000;
+const MAX_GUARDIANS: usize = 10;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GuardianConfig {
+    pub owner: Principal,
+    pub guardians: Vec<Principal>,
+    pub threshold: u32,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RecoveryStatus {
+    Pending,
+    Cancelled,
+    /// Execution started but not every step has completed
+    Executing,
+    Executed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RecoveryRequest {
+    pub id: String,
+    pub lost_principal: Principal,
+    pub new_principal: Principal,
+    pub approvals: Vec<Principal>,
+    pub threshold: u32,
+    pub status: RecoveryStatus,
+    pub requested_at: u64,
+    pub executable_after: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RecoveryNotification {
+    pub recipient: Principal,
+    pub request_id: String,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static GUARDIANS: RefCell<HashMap<Principal, GuardianConfig>> = RefCell::new(HashMap::new());
+    static RECOVERY_REQUESTS: RefCell<HashMap<String, RecoveryRequest>> = RefCell::new(HashMap::new());
+    static NOTIFICATIONS: RefCell<Vec<RecoveryNotification>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Designate (or replace) the caller's recovery guardians
+pub fn set_guardians(owner: Principal, guardians: Vec<Principal>, threshold: u32, now: u64) -> Result<GuardianConfig, String> {
+    if guardians.is_empty() || guardians.len() > MAX_GUARDIANS {
+        return Err(format!("Between 1 and {} guardians are required", MAX_GUARDIANS));
+    }
+    if guardians.contains(&owner) {
+        return Err("A principal cannot be its own guardian".to_string());
+    }
+    if threshold == 0 || threshold as usize > guardians.len() {
+        return Err("Threshold must be between 1 and the number of guardians".to_string());
+    }
+
+    let config = GuardianConfig {
+        owner,
+        guardians,
+        threshold,
+        updated_at: now,
+    };
+    GUARDIANS.with(|g| g.borrow_mut().insert(owner, config.clone()));
+    Ok(config)
+}
+
+pub fn get_guardians(owner: Principal) -> Option<GuardianConfig> {
+    GUARDIANS.with(|g| g.borrow().get(&owner).cloned())
+}
+
+/// Open a recovery request from a new principal for a lost one
+pub fn request(lost_principal: Principal, new_principal: Principal, now: u64) -> Result<RecoveryRequest, String> {
+    if lost_principal == new_principal {
+        return Err("New principal must differ from the lost principal".to_string());
+    }
+    let config = get_guardians(lost_principal)
+        .ok_or_else(|| "No recovery guardians designated for this principal".to_string())?;
+
+    let has_open_request = RECOVERY_REQUESTS.with(|r| {
+        r.borrow().values().any(|req| {
+            req.lost_principal == lost_principal && matches!(req.status, RecoveryStatus::Pending | RecoveryStatus::Executing)
+        })
+    });
+    if has_open_request {
+        return Err("A recovery request for this principal is already pending".to_string());
+    }
+
+    let request = RecoveryRequest {
+        id: format!("recovery_{}_{}", lost_principal.to_text(), now),
+        lost_principal,
+        new_principal,
+        approvals: vec![],
+        threshold: config.threshold,
+        status: RecoveryStatus::Pending,
+        requested_at: now,
+        executable_after: now + RECOVERY_DELAY_NS,
+    };
+    RECOVERY_REQUESTS.with(|r| r.borrow_mut().insert(request.id.clone(), request.clone()));
+
+    let message = format!(
+        "Recovery of {} to {} requested; executable after {} unless cancelled",
+        lost_principal.to_text(), new_principal.to_text(), request.executable_after
+    );
+    notify(lost_principal, &request.id, &message, now);
+    for guardian in &config.guardians {
+        notify(*guardian, &request.id, &message, now);
+    }
+
+    Ok(request)
+}
+
+/// Record a guardian approval; returns the number of approvals so far
+pub fn approve(request_id: &str, guardian: Principal, now: u64) -> Result<usize, String> {
+    let request = get_request(request_id)?;
+    let config = get_guardians(request.lost_principal)
+        .ok_or_else(|| "Guardian configuration no longer exists".to_string())?;
+    if !config.guardians.contains(&guardian) {
+        return Err("Caller is not a guardian of this principal".to_string());
+    }
+
+    let approvals = RECOVERY_REQUESTS.with(|r| {
+        let mut requests = r.borrow_mut();
+        let request = requests.get_mut(request_id)
+            .ok_or_else(|| "Recovery request not found".to_string())?;
+        if request.status != RecoveryStatus::Pending {
+            return Err(format!("Recovery request is {:?}", request.status));
+        }
+        if request.approvals.contains(&guardian) {
+            return Err("Guardian has already approved".to_string());
+        }
+        request.approvals.push(guardian);
+        Ok(request.approvals.len())
+    })?;
+
+    notify(request.lost_principal, request_id, &format!(
+        "Guardian {} approved recovery ({}/{})", guardian.to_text(), approvals, request.threshold
+    ), now);
+    Ok(approvals)
+}
+
+/// Veto a pending request; only the lost principal (who evidently still has
+/// access) or one of its guardians can cancel
+pub fn cancel(request_id: &str, caller: Principal, now: u64) -> Result<(), String> {
+    let request = get_request(request_id)?;
+    let is_guardian = get_guardians(request.lost_principal)
+        .map(|c| c.guardians.contains(&caller))
+        .unwrap_or(false);
+    if caller != request.lost_principal && !is_guardian {
+        return Err("Only the affected principal or its guardians can cancel recovery".to_string());
+    }
+
+    set_status(request_id, RecoveryStatus::Pending, RecoveryStatus::Cancelled)?;
+    notify(request.lost_principal, request_id, &format!("Recovery cancelled by {}", caller.to_text()), now);
+    Ok(())
+}
+
+/// Check that a request has enough approvals from its principal's current
+/// guardians and its delay has elapsed, and start executing it. A request
+/// whose execution stopped part-way can be executed again. The caller must be
+/// the new principal.
+pub fn begin_execution(request_id: &str, caller: Principal, now: u64) -> Result<RecoveryRequest, String> {
+    let request = get_request(request_id)?;
+    if request.new_principal != caller {
+        return Err("Only the new principal can execute this recovery".to_string());
+    }
+    match request.status {
+        RecoveryStatus::Executing => return Ok(request),
+        RecoveryStatus::Pending => {},
+        _ => return Err(format!("Recovery request is {:?}", request.status)),
+    }
+
+    // Guardians removed since they approved no longer count
+    let config = get_guardians(request.lost_principal)
+        .ok_or_else(|| "Guardian configuration no longer exists".to_string())?;
+    let threshold = request.threshold.max(config.threshold);
+    let approvals = request.approvals.iter().filter(|a| config.guardians.contains(a)).count();
+    if (approvals as u32) < threshold {
+        return Err(format!("Recovery needs {} guardian approvals, has {}", threshold, approvals));
+    }
+    if now < request.executable_after {
+        return Err(format!("Recovery delay has not elapsed; executable after {}", request.executable_after));
+    }
+
+    set_status(request_id, RecoveryStatus::Pending, RecoveryStatus::Executing)?;
+    Ok(RecoveryRequest { status: RecoveryStatus::Executing, ..request })
+}
+
+/// Mark a request executed once every step succeeded and move the guardian
+/// configuration over
+pub fn mark_executed(request_id: &str, now: u64) -> Result<(), String> {
+    let request = get_request(request_id)?;
+    set_status(request_id, RecoveryStatus::Executing, RecoveryStatus::Executed)?;
+
+    GUARDIANS.with(|g| {
+        let mut guardians = g.borrow_mut();
+        if let Some(mut config) = guardians.remove(&request.lost_principal) {
+            config.owner = request.new_principal;
+            config.updated_at = now;
+            guardians.insert(request.new_principal, config);
+        }
+    });
+
+    notify(request.new_principal, request_id, &format!(
+        "Identity {} recovered to {}", request.lost_principal.to_text(), request.new_principal.to_text()
+    ), now);
+    Ok(())
+}
+
+pub fn get_request(request_id: &str) -> Result<RecoveryRequest, String> {
+    RECOVERY_REQUESTS.with(|r| r.borrow().get(request_id).cloned())
+        .ok_or_else(|| "Recovery request not found".to_string())
+}
+
+pub fn notifications_for(recipient: Principal) -> Vec<RecoveryNotification> {
+    NOTIFICATIONS.with(|n| {
+        n.borrow().iter().filter(|n| n.recipient == recipient).cloned().collect()
+    })
+}
+
+fn set_status(request_id: &str, expected: RecoveryStatus, status: RecoveryStatus) -> Result<(), String> {
+    RECOVERY_REQUESTS.with(|r| {
+        let mut requests = r.borrow_mut();
+        let request = requests.get_mut(request_id)
+            .ok_or_else(|| "Recovery request not found".to_string())?;
+        if request.status != expected {
+            return Err(format!("Recovery request is {:?}", request.status));
+        }
+        request.status = status;
+        Ok(())
+    })
+}
+
+fn notify(recipient: Principal, request_id: &str, message: &str, now: u64) {
+    NOTIFICATIONS.with(|n| {
+        n.borrow_mut().push(RecoveryNotification {
+            recipient,
+            request_id: request_id.to_string(),
+            message: message.to_string(),
+            timestamp: now,
+        });
+    });
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&GUARDIANS),
+        backup::cloned(&RECOVERY_REQUESTS),
+        backup::cloned(&NOTIFICATIONS),
+    ))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (guardians, recovery_requests, notifications) = backup::decode(bytes)?;
+    backup::replace(&GUARDIANS, guardians);
+    backup::replace(&RECOVERY_REQUESTS, recovery_requests);
+    backup::replace(&NOTIFICATIONS, notifications);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "recovery_test.rs"]
+mod tests;
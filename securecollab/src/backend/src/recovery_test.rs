@@ -0,0 +1,84 @@
+use super::*;
+use crate::test_support::principal;
+
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+// Owner 1 with guardians 2, 3 and 4, two of whom must approve
+fn guarded() -> (Principal, Principal, [Principal; 3]) {
+    let guardians = [principal(2), principal(3), principal(4)];
+    set_guardians(principal(1), guardians.to_vec(), 2, 0).unwrap();
+    (principal(1), principal(9), guardians)
+}
+
+#[test]
+fn guardian_sets_are_validated() {
+    let owner = principal(1);
+    assert!(set_guardians(owner, vec![], 1, 0).is_err());
+    assert!(set_guardians(owner, vec![owner, principal(2)], 1, 0).is_err());
+    assert!(set_guardians(owner, vec![principal(2)], 0, 0).is_err());
+    assert!(set_guardians(owner, vec![principal(2)], 2, 0).is_err());
+    assert!(request(principal(5), principal(6), 0).is_err());
+}
+
+#[test]
+fn execution_needs_a_threshold_of_guardians_and_the_delay() {
+    let (lost, new, [a, b, _]) = guarded();
+    let id = request(lost, new, 0).unwrap().id;
+    assert!(request(lost, principal(8), 0).is_err());
+
+    approve(&id, a, 1).unwrap();
+    assert!(approve(&id, a, 1).is_err());
+    let late = RECOVERY_DELAY_NS + 1;
+    assert!(begin_execution(&id, new, late).unwrap_err().contains("needs 2"));
+
+    approve(&id, b, 2).unwrap();
+    assert!(begin_execution(&id, new, 47 * HOUR_NS).unwrap_err().contains("delay"));
+    assert!(begin_execution(&id, principal(8), late).is_err());
+    assert_eq!(begin_execution(&id, new, late).unwrap().status, RecoveryStatus::Executing);
+
+    // Vetoes come too late once execution started; a failed run can resume
+    assert!(cancel(&id, lost, late).is_err());
+    assert_eq!(begin_execution(&id, new, late).unwrap().status, RecoveryStatus::Executing);
+    mark_executed(&id, late).unwrap();
+
+    assert!(begin_execution(&id, new, late).is_err());
+    assert!(mark_executed(&id, late).is_err());
+    assert_eq!(get_guardians(new).unwrap().owner, new);
+    assert!(get_guardians(lost).is_none());
+}
+
+#[test]
+fn only_guardians_approve_and_only_current_guardians_count() {
+    let (lost, new, [a, b, c]) = guarded();
+    let id = request(lost, new, 0).unwrap().id;
+    assert!(approve(&id, principal(7), 1).is_err());
+    assert!(approve(&id, new, 1).is_err());
+
+    approve(&id, a, 1).unwrap();
+    approve(&id, b, 1).unwrap();
+    set_guardians(lost, vec![b, c], 2, 2).unwrap();
+    let late = RECOVERY_DELAY_NS + 1;
+    assert!(begin_execution(&id, new, late).unwrap_err().contains("has 1"));
+
+    approve(&id, c, 3).unwrap();
+    assert!(begin_execution(&id, new, late).is_ok());
+}
+
+#[test]
+fn the_lost_principal_or_a_guardian_can_veto() {
+    let (lost, new, [a, b, _]) = guarded();
+    let id = request(lost, new, 0).unwrap().id;
+    approve(&id, a, 1).unwrap();
+    approve(&id, b, 1).unwrap();
+
+    assert!(cancel(&id, new, 2).is_err());
+    cancel(&id, lost, 2).unwrap();
+    assert!(cancel(&id, a, 2).is_err());
+    assert!(approve(&id, a, 3).is_err());
+    assert!(begin_execution(&id, new, RECOVERY_DELAY_NS + 1).is_err());
+
+    let retry = request(lost, new, 10).unwrap().id;
+    cancel(&retry, b, 11).unwrap();
+    assert_eq!(get_request(&retry).unwrap().status, RecoveryStatus::Cancelled);
+    assert!(notifications_for(lost).iter().any(|n| n.message.contains("cancelled")));
+}
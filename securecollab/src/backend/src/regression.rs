@@ -0,0 +1,371 @@
+//! Private correlation and regression over combined datasets
+//!
+//! Only bounded numeric and boolean columns can be analyzed: every value is
+//! clamped to its declared range and scaled to [0, 1], so one record moves
+//! any sum of scaled values or of their pairwise products by at most 1.
+//! Records missing one of the selected columns are left out.
+//!
+//! Pearson correlations and linear regressions are computed from the record
+//! count, sums and cross products of the scaled values, each released with
+//! Laplace noise under an even share of epsilon. Spearman correlations use
+//! the sums of squares and products of ranks scaled by the record count,
+//! which one record moves by at most `RANK_SENSITIVITY` since it shifts every
+//! other rank by at most one. Logistic regressions are fitted with L2 regularization over
+//! features scaled to unit norm, and their coefficients released with noise
+//! for the fit's sensitivity of 2 / (n λ) per unit of norm; half of epsilon
+//! goes to the coefficients and half to the record count.
+
+use candid::{CandidType, Deserialize};
+use crate::analytics::{numeric_value, ColumnSpec, ColumnType};
+use crate::differential_privacy::{noise, NoiseMechanism, NoiseSource};
+use crate::tabular;
+
+pub const RANK_SENSITIVITY: f64 = 3.0;
+/// λ of the L2 penalty on logistic coefficients
+pub const LOGISTIC_REGULARIZATION: f64 = 0.01;
+const LOGISTIC_ITERATIONS: usize = 500;
+const LOGISTIC_STEP: f64 = 2.0;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CorrelationRequest {
+    pub dataset_ids: Vec<String>,
+    pub columns: Vec<String>,
+    /// Defaults to the canister's differential privacy configuration
+    pub epsilon: Option<f64>,
+    /// Approved computation to run under when other parties' datasets are read
+    pub computation_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CorrelationMatrix {
+    pub columns: Vec<String>,
+    /// Row and column order follow `columns`
+    pub pearson: Vec<Vec<f64>>,
+    pub spearman: Vec<Vec<f64>>,
+    /// Noisy number of records analyzed
+    pub records: u64,
+    pub epsilon: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RegressionKind {
+    Linear,
+    /// The outcome must be boolean, or a number bounded to [0, 1]
+    Logistic,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RegressionRequest {
+    pub dataset_ids: Vec<String>,
+    pub kind: RegressionKind,
+    pub outcome: String,
+    pub predictors: Vec<String>,
+    pub epsilon: Option<f64>,
+    /// Approved computation to run under when other parties' datasets are read
+    pub computation_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Coefficient {
+    pub predictor: String,
+    /// Change of the outcome, or of its log-odds, per unit of the predictor
+    pub estimate: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RegressionModel {
+    pub kind: RegressionKind,
+    pub outcome: String,
+    pub intercept: f64,
+    pub coefficients: Vec<Coefficient>,
+    pub records: u64,
+    /// Share of the outcome's variance explained, for linear models
+    pub r_squared: Option<f64>,
+    pub epsilon: f64,
+}
+
+/// A selected column and its declared range
+struct Variable {
+    spec: ColumnSpec,
+    min: f64,
+    max: f64,
+}
+
+fn variables(schema: &[ColumnSpec], names: &[String]) -> Result<Vec<Variable>, String> {
+    if names.iter().enumerate().any(|(i, name)| names[..i].contains(name)) {
+        return Err("Each column can only be selected once".to_string());
+    }
+    names.iter().map(|name| {
+        let spec = schema.iter().find(|spec| spec.name == *name)
+            .ok_or_else(|| format!("Column '{}' is not in the schema", name))?;
+        let (min, max) = match spec.column_type {
+            ColumnType::Number { bounds: Some((min, max)), .. } if max > min => (min, max),
+            ColumnType::Boolean => (0.0, 1.0),
+            _ => return Err(format!("Column '{}' must be a bounded number or a boolean", name)),
+        };
+        Ok(Variable { spec: spec.clone(), min, max })
+    }).collect()
+}
+
+/// Scaled values of every record that has all of `variables`
+fn scaled_rows(variables: &[Variable], datasets: &[String]) -> Result<Vec<Vec<f64>>, String> {
+    let mut rows = Vec::new();
+    for data in datasets {
+        let table = tabular::parse(data.as_bytes())?;
+        let positions = variables.iter()
+            .map(|v| table.header.iter().position(|h| *h == v.spec.name)
+                .ok_or_else(|| format!("Column '{}' not found", v.spec.name)))
+            .collect::<Result<Vec<_>, String>>()?;
+        rows.extend(table.rows.iter().filter_map(|row| {
+            variables.iter().zip(&positions).map(|(v, &p)| {
+                numeric_value(&v.spec.column_type, &row[p]).map(|x| (x.clamp(v.min, v.max) - v.min) / (v.max - v.min))
+            }).collect::<Option<Vec<f64>>>()
+        }));
+    }
+    Ok(rows)
+}
+
+fn check_records(rows: &[Vec<f64>], min_records: u64) -> Result<(), String> {
+    if (rows.len() as u64) < min_records.max(2) {
+        return Err(format!("Fewer than {} records have every selected column", min_records.max(2)));
+    }
+    Ok(())
+}
+
+/// Noisy record count, sums and cross products (`products[i][j]` for j ≤ i)
+struct Moments {
+    n: f64,
+    sums: Vec<f64>,
+    products: Vec<Vec<f64>>,
+}
+
+impl Moments {
+    fn statistics(k: usize) -> usize {
+        1 + k + k * (k + 1) / 2
+    }
+
+    fn release(rows: &[Vec<f64>], k: usize, mut noisy: impl FnMut(f64) -> f64) -> Self {
+        let n = noisy(rows.len() as f64).max(2.0);
+        let sums = (0..k).map(|i| noisy(rows.iter().map(|r| r[i]).sum())).collect();
+        let products = (0..k).map(|i| (0..=i).map(|j| noisy(rows.iter().map(|r| r[i] * r[j]).sum())).collect()).collect();
+        Self { n, sums, products }
+    }
+
+    fn product(&self, i: usize, j: usize) -> f64 {
+        if j <= i { self.products[i][j] } else { self.products[j][i] }
+    }
+
+    fn covariance(&self, i: usize, j: usize) -> f64 {
+        self.product(i, j) / self.n - (self.sums[i] / self.n) * (self.sums[j] / self.n)
+    }
+}
+
+fn correlation(covariance: f64, variance_a: f64, variance_b: f64) -> f64 {
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return 0.0;
+    }
+    (covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0)
+}
+
+/// Ranks from 1 to n, ties sharing their average rank
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let end = (start..order.len()).find(|&i| values[order[i]] != values[order[start]]).unwrap_or(order.len());
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+pub fn correlate(
+    schema: &[ColumnSpec],
+    request: &CorrelationRequest,
+    datasets: &[String],
+    epsilon: f64,
+    min_records: u64,
+    source: &mut NoiseSource,
+) -> Result<CorrelationMatrix, String> {
+    if request.columns.len() < 2 {
+        return Err("Select at least two columns to correlate".to_string());
+    }
+    let variables = variables(schema, &request.columns)?;
+    let rows = scaled_rows(&variables, datasets)?;
+    check_records(&rows, min_records)?;
+    let k = variables.len();
+    let share = epsilon / (Moments::statistics(k) + k * (k + 1) / 2) as f64;
+
+    let moments = Moments::release(&rows, k, |value| value + noise(source, NoiseMechanism::Laplace, 1.0, share, 0.0));
+    let pearson = (0..k).map(|i| (0..k).map(|j| {
+        if i == j { 1.0 } else { correlation(moments.covariance(i, j), moments.covariance(i, i), moments.covariance(j, j)) }
+    }).collect()).collect();
+
+    // Scaled ranks average (n + 1) / 2n whatever the ties, so only their
+    // squares and products are released
+    let n = rows.len() as f64;
+    let ranked: Vec<Vec<f64>> = (0..k)
+        .map(|i| ranks(&rows.iter().map(|r| r[i]).collect::<Vec<_>>()).into_iter().map(|r| r / n).collect())
+        .collect();
+    let mean = (moments.n + 1.0) / (2.0 * moments.n);
+    let mut rank_product = |a: &[f64], b: &[f64]| {
+        let product: f64 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+        (product + noise(source, NoiseMechanism::Laplace, RANK_SENSITIVITY, share, 0.0)) / moments.n - mean * mean
+    };
+    let variances: Vec<f64> = ranked.iter().map(|r| rank_product(r, r)).collect();
+    let mut spearman = vec![vec![1.0; k]; k];
+    for i in 0..k {
+        for j in 0..i {
+            let rho = correlation(rank_product(&ranked[i], &ranked[j]), variances[i], variances[j]);
+            spearman[i][j] = rho;
+            spearman[j][i] = rho;
+        }
+    }
+
+    Ok(CorrelationMatrix {
+        columns: request.columns.clone(),
+        pearson,
+        spearman,
+        records: moments.n.round() as u64,
+        epsilon,
+    })
+}
+
+/// Solution of `a x = b` by Gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            let (above, below) = a.split_at_mut(row);
+            for (value, pivot) in below[0][col..].iter_mut().zip(&above[col][col..]) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|c| a[row][c] * x[c]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Least squares over noisy moments; the outcome is variable 0 and the
+/// returned coefficients are on the scaled values, intercept first
+fn linear(moments: &Moments, k: usize) -> Result<(Vec<f64>, f64), String> {
+    // Normal equations over an intercept and the predictors 1..k
+    let term = |i: usize| if i == 0 { None } else { Some(i) };
+    let cross = |a: Option<usize>, b: Option<usize>| match (a, b) {
+        (None, None) => moments.n,
+        (Some(i), None) | (None, Some(i)) => moments.sums[i],
+        (Some(i), Some(j)) => moments.product(i, j),
+    };
+    let xtx: Vec<Vec<f64>> = (0..k).map(|i| (0..k).map(|j| cross(term(i), term(j))).collect()).collect();
+    let xty: Vec<f64> = (0..k).map(|i| cross(term(i), Some(0))).collect();
+    let beta = solve(xtx.clone(), xty.clone()).ok_or("The predictors are collinear")?;
+
+    let explained: f64 = beta.iter().zip(&xty).map(|(b, y)| b * y).sum::<f64>();
+    let total = moments.product(0, 0) - moments.sums[0] * moments.sums[0] / moments.n;
+    let residual = moments.product(0, 0) - explained;
+    let r_squared = if total > 0.0 { (1.0 - residual / total).clamp(0.0, 1.0) } else { 0.0 };
+    Ok((beta, r_squared))
+}
+
+/// L2-regularized logistic regression by gradient descent over features
+/// scaled to unit norm, intercept feature first
+fn logistic(features: &[Vec<f64>], outcomes: &[f64]) -> Vec<f64> {
+    let (n, d) = (features.len() as f64, features[0].len());
+    let mut w = vec![0.0; d];
+    for _ in 0..LOGISTIC_ITERATIONS {
+        let mut gradient: Vec<f64> = w.iter().map(|w| LOGISTIC_REGULARIZATION * w).collect();
+        for (x, y) in features.iter().zip(outcomes) {
+            let z: f64 = w.iter().zip(x).map(|(w, x)| w * x).sum();
+            let error = 1.0 / (1.0 + (-z).exp()) - y;
+            for (g, x) in gradient.iter_mut().zip(x) {
+                *g += error * x / n;
+            }
+        }
+        for (w, g) in w.iter_mut().zip(&gradient) {
+            *w -= LOGISTIC_STEP * g;
+        }
+    }
+    w
+}
+
+pub fn regress(
+    schema: &[ColumnSpec],
+    request: &RegressionRequest,
+    datasets: &[String],
+    epsilon: f64,
+    min_records: u64,
+    source: &mut NoiseSource,
+) -> Result<RegressionModel, String> {
+    if request.predictors.is_empty() {
+        return Err("Select at least one predictor".to_string());
+    }
+    let names: Vec<String> = std::iter::once(request.outcome.clone()).chain(request.predictors.iter().cloned()).collect();
+    let variables = variables(schema, &names)?;
+    if request.kind == RegressionKind::Logistic && (variables[0].min, variables[0].max) != (0.0, 1.0) {
+        return Err(format!("The outcome '{}' of a logistic regression must be boolean", request.outcome));
+    }
+    let rows = scaled_rows(&variables, datasets)?;
+    check_records(&rows, min_records)?;
+    let k = variables.len();
+
+    // Coefficients on scaled values, intercept first, and what converts them back
+    let (beta, records, r_squared, outcome_scale, outcome_offset) = match request.kind {
+        RegressionKind::Linear => {
+            let share = epsilon / Moments::statistics(k) as f64;
+            let moments = Moments::release(&rows, k, |value| value + noise(source, NoiseMechanism::Laplace, 1.0, share, 0.0));
+            let (beta, r_squared) = linear(&moments, k)?;
+            (beta, moments.n, Some(r_squared), variables[0].max - variables[0].min, variables[0].min)
+        }
+        RegressionKind::Logistic => {
+            let norm = (k as f64).sqrt();
+            let features: Vec<Vec<f64>> = rows.iter()
+                .map(|r| std::iter::once(1.0).chain(r[1..].iter().copied()).map(|x| x / norm).collect())
+                .collect();
+            let outcomes: Vec<f64> = rows.iter().map(|r| r[0].round()).collect();
+            let n = rows.len() as f64;
+            let sensitivity = 2.0 * norm / (n * LOGISTIC_REGULARIZATION);
+            let beta = logistic(&features, &outcomes).into_iter()
+                .map(|w| (w + noise(source, NoiseMechanism::Laplace, sensitivity, epsilon / 2.0, 0.0)) / norm)
+                .collect();
+            let records = n + noise(source, NoiseMechanism::Laplace, 1.0, epsilon / 2.0, 0.0);
+            (beta, records, None, 1.0, 0.0)
+        }
+    };
+
+    // outcome = offset + scale · (β0 + Σ βj (xj − minj) / rangej)
+    let predictors = &variables[1..];
+    let coefficients: Vec<Coefficient> = predictors.iter().zip(&beta[1..])
+        .map(|(v, b)| Coefficient { predictor: v.spec.name.clone(), estimate: outcome_scale * b / (v.max - v.min) })
+        .collect();
+    let shift: f64 = predictors.iter().zip(&beta[1..]).map(|(v, b)| b * v.min / (v.max - v.min)).sum();
+    Ok(RegressionModel {
+        kind: request.kind,
+        outcome: request.outcome.clone(),
+        intercept: outcome_offset + outcome_scale * (beta[0] - shift),
+        coefficients,
+        records: records.round().max(0.0) as u64,
+        r_squared,
+        epsilon,
+    })
+}
+
+#[cfg(test)]
+#[path = "regression_test.rs"]
+mod tests;
@@ -0,0 +1,82 @@
+use super::*;
+use crate::analytics::parse_schema;
+
+const SCHEMA: &str = "age:number[0..100],dose:number[0..10],bp:number[50..250],smoker:boolean,site:category";
+
+fn dataset(rows: usize, offset: usize) -> String {
+    let mut lines = vec!["age,dose,bp,smoker,site".to_string()];
+    for i in offset..offset + rows {
+        let age = 20 + i % 60;
+        let dose = (i * 7) % 11;
+        // Blood pressure rises with age and falls with dose
+        let bp = 80.0 + age as f64 - 3.0 * dose as f64;
+        lines.push(format!("{},{},{},{},north", age, dose, bp, age > 50));
+    }
+    lines.join("\n")
+}
+
+fn columns(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn correlation_request(names: &[&str]) -> CorrelationRequest {
+    CorrelationRequest { dataset_ids: vec![], columns: columns(names), epsilon: None, computation_id: None }
+}
+
+fn regression_request(kind: RegressionKind, outcome: &str, predictors: &[&str]) -> RegressionRequest {
+    RegressionRequest { dataset_ids: vec![], kind, outcome: outcome.to_string(), predictors: columns(predictors), epsilon: None, computation_id: None }
+}
+
+#[test]
+fn correlates_columns_across_datasets() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let datasets = [dataset(400, 0), dataset(400, 400)];
+    let matrix = correlate(&schema, &correlation_request(&["age", "bp", "smoker"]), &datasets, 1e6, 5, &mut source).unwrap();
+    assert_eq!(matrix.records, 800);
+    assert_eq!(matrix.pearson[0][0], 1.0);
+    assert!(matrix.pearson[0][1] > 0.8 && (matrix.pearson[0][1] - matrix.pearson[1][0]).abs() < 1e-12);
+    assert!(matrix.spearman[0][2] > 0.8);
+    assert!(matrix.pearson.iter().flatten().chain(matrix.spearman.iter().flatten()).all(|r| (-1.0..=1.0).contains(r)));
+}
+
+#[test]
+fn fits_a_linear_regression_on_the_original_scale() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let request = regression_request(RegressionKind::Linear, "bp", &["age", "dose"]);
+    let model = regress(&schema, &request, &[dataset(1000, 0)], 1e7, 5, &mut source).unwrap();
+    assert!((model.intercept - 80.0).abs() < 0.5, "{}", model.intercept);
+    assert!((model.coefficients[0].estimate - 1.0).abs() < 0.05);
+    assert!((model.coefficients[1].estimate + 3.0).abs() < 0.05);
+    assert!(model.r_squared.unwrap() > 0.99);
+}
+
+#[test]
+fn fits_a_logistic_regression() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let request = regression_request(RegressionKind::Logistic, "smoker", &["age"]);
+    let model = regress(&schema, &request, &[dataset(1000, 0)], 1e6, 5, &mut source).unwrap();
+    // Older patients are the smokers, so the odds rise with age
+    assert!(model.coefficients[0].estimate > 0.0);
+    assert!(model.intercept < 0.0);
+    assert!(model.r_squared.is_none());
+}
+
+#[test]
+fn rejects_unsuitable_columns() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let data = [dataset(50, 0)];
+    assert!(correlate(&schema, &correlation_request(&["age", "site"]), &data, 1.0, 5, &mut source).is_err());
+    assert!(correlate(&schema, &correlation_request(&["age"]), &data, 1.0, 5, &mut source).is_err());
+    assert!(correlate(&schema, &correlation_request(&["age", "bp"]), &data, 1.0, 100, &mut source).is_err());
+    let logistic = regression_request(RegressionKind::Logistic, "bp", &["age"]);
+    assert!(regress(&schema, &logistic, &data, 1.0, 5, &mut source).is_err());
+}
+
+#[test]
+fn ranks_share_ties() {
+    assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+}
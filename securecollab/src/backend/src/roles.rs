@@ -0,0 +1,145 @@
+//! Organization-scoped roles
+//!
+//! Organization admins grant their members roles, and each role unlocks a
+//! set of endpoint families. An organization's admins hold the admin role
+//! through the organization itself, so granting or revoking it promotes or
+//! demotes them there. Roles belong to the organization that granted them
+//! and are dropped when the member leaves.
+//!
+//! The default workspace has no admins; its principals keep every role but
+//! admin, as before roles existed.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::{organizations, DEFAULT_WORKSPACE};
+use crate::backup;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Admin,
+    Approver,
+    Analyst,
+    Auditor,
+}
+
+/// Endpoint families a role can unlock
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// Members and their roles
+    ManageRoles,
+    /// Dataset uploads
+    Upload,
+    /// Queries, computations and joins
+    Compute,
+    /// Signing queries and voting on computations
+    Approve,
+    /// Audit log reads and consent graph exports
+    Audit,
+}
+
+pub const ROLES: [Role; 4] = [Role::Admin, Role::Approver, Role::Analyst, Role::Auditor];
+pub const DEFAULT_WORKSPACE_ROLES: [Role; 3] = [Role::Approver, Role::Analyst, Role::Auditor];
+
+/// The permission matrix
+pub fn permissions(role: Role) -> &'static [Permission] {
+    match role {
+        Role::Admin => &[Permission::ManageRoles, Permission::Upload, Permission::Compute, Permission::Approve, Permission::Audit],
+        Role::Approver => &[Permission::Upload, Permission::Approve],
+        Role::Analyst => &[Permission::Upload, Permission::Compute],
+        Role::Auditor => &[Permission::Audit],
+    }
+}
+
+thread_local! {
+    // Roles other than admin, granted within the member's organization
+    static GRANTED: RefCell<HashMap<Principal, Vec<Role>>> = RefCell::new(HashMap::new());
+}
+
+/// Roles of a person; service principals hold none and act through
+/// their scopes instead
+pub fn roles_of(principal: Principal) -> Vec<Role> {
+    if crate::service_principals::is_service_principal(principal) {
+        return Vec::new();
+    }
+    let workspace = organizations::workspace_of(principal);
+    if workspace == DEFAULT_WORKSPACE {
+        return DEFAULT_WORKSPACE_ROLES.to_vec();
+    }
+    let mut roles = Vec::new();
+    if organizations::get(&workspace).is_ok_and(|org| org.admins.contains(&principal)) {
+        roles.push(Role::Admin);
+    }
+    roles.extend(GRANTED.with(|granted| granted.borrow().get(&principal).cloned().unwrap_or_default()));
+    roles
+}
+
+pub fn require(principal: Principal, permission: Permission) -> Result<(), String> {
+    if !roles_of(principal).iter().any(|role| permissions(*role).contains(&permission)) {
+        return Err(format!("Permission denied: {:?} requires a role that grants it", permission));
+    }
+    Ok(())
+}
+
+// The organization both belong to, if `admin` may change `member`'s roles
+fn shared_organization(admin: Principal, member: Principal) -> Result<String, String> {
+    let workspace = organizations::workspace_of(admin);
+    if workspace == DEFAULT_WORKSPACE {
+        return Err("Roles are granted within an organization".to_string());
+    }
+    require(admin, Permission::ManageRoles)?;
+    if organizations::workspace_of(member) != workspace {
+        return Err(format!("{} is not a member of your organization", member.to_text()));
+    }
+    Ok(workspace)
+}
+
+/// Grant `member` a role in the organization `admin` administers
+pub fn grant(admin: Principal, member: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let workspace = shared_organization(admin, member)?;
+    if role == Role::Admin {
+        organizations::add_member(&workspace, admin, member, true)?;
+    } else {
+        GRANTED.with(|granted| {
+            let mut granted = granted.borrow_mut();
+            let roles = granted.entry(member).or_default();
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        });
+    }
+    Ok(roles_of(member))
+}
+
+pub fn revoke(admin: Principal, member: Principal, role: Role) -> Result<Vec<Role>, String> {
+    let workspace = shared_organization(admin, member)?;
+    if role == Role::Admin {
+        organizations::demote(&workspace, admin, member)?;
+    } else {
+        GRANTED.with(|granted| {
+            if let Some(roles) = granted.borrow_mut().get_mut(&member) {
+                roles.retain(|r| *r != role);
+            }
+        });
+    }
+    Ok(roles_of(member))
+}
+
+/// Drop the roles of a member leaving their organization
+pub fn clear(member: Principal) {
+    GRANTED.with(|granted| granted.borrow_mut().remove(&member));
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&GRANTED))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let granted = backup::decode(bytes)?;
+    backup::replace(&GRANTED, granted);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "roles_test.rs"]
+mod tests;
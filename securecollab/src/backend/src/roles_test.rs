@@ -0,0 +1,51 @@
+use super::*;
+use crate::test_support::principal;
+
+#[test]
+fn default_workspace_keeps_every_role_but_admin() {
+    let party = principal(1);
+    assert_eq!(roles_of(party), DEFAULT_WORKSPACE_ROLES.to_vec());
+    assert!(require(party, Permission::Compute).is_ok());
+    assert!(require(party, Permission::ManageRoles).is_err());
+    assert!(grant(party, principal(2), Role::Analyst).is_err());
+}
+
+#[test]
+fn organization_admins_grant_roles_to_members() {
+    let (admin, member, outsider) = (principal(3), principal(4), principal(5));
+    organizations::create("org_roles".to_string(), "Roles".to_string(), admin, 0).unwrap();
+    organizations::add_member("org_roles", admin, member, false).unwrap();
+    assert_eq!(roles_of(admin), vec![Role::Admin]);
+    assert!(roles_of(member).is_empty());
+    assert!(require(member, Permission::Upload).is_err());
+
+    assert_eq!(grant(admin, member, Role::Auditor).unwrap(), vec![Role::Auditor]);
+    assert!(require(member, Permission::Audit).is_ok());
+    assert!(require(member, Permission::Compute).is_err());
+    assert!(grant(member, admin, Role::Analyst).is_err());
+    assert!(grant(admin, outsider, Role::Analyst).is_err());
+
+    assert_eq!(grant(admin, member, Role::Admin).unwrap(), vec![Role::Admin, Role::Auditor]);
+    assert_eq!(revoke(member, admin, Role::Admin).unwrap(), Vec::<Role>::new());
+    assert!(revoke(member, member, Role::Admin).is_err());
+    assert_eq!(revoke(member, member, Role::Auditor).unwrap(), vec![Role::Admin]);
+}
+
+#[test]
+fn leaving_drops_granted_roles() {
+    let (admin, member) = (principal(6), principal(7));
+    organizations::create("org_leave".to_string(), "Leave".to_string(), admin, 0).unwrap();
+    organizations::add_member("org_leave", admin, member, false).unwrap();
+    grant(admin, member, Role::Analyst).unwrap();
+    organizations::remove_member("org_leave", admin, member).unwrap();
+    organizations::add_member("org_leave", admin, member, false).unwrap();
+    assert!(roles_of(member).is_empty());
+}
+
+#[test]
+fn every_permission_is_granted_by_some_role() {
+    for permission in [Permission::ManageRoles, Permission::Upload, Permission::Compute, Permission::Approve, Permission::Audit] {
+        assert!(permissions(Role::Admin).contains(&permission));
+    }
+    assert!(ROLES.iter().all(|role| !permissions(*role).is_empty()));
+}
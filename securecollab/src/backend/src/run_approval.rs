@@ -0,0 +1,35 @@
+//! Approval of runs over other parties' datasets
+//!
+//! A party may run secure aggregates over datasets it was granted access to
+//! on its own. A run that reads another party's dataset goes through the
+//! multi-party vote instead: it names a computation that the caller
+//! requested or signed, that was approved, and whose required signers
+//! include the owner of every dataset the run reads.
+
+use candid::Principal;
+use crate::{MPCComputation, PrivateDataSource};
+
+/// Computation statuses reached only once every required signer approved
+const APPROVED_STATUSES: [&str; 4] = ["approved", "ready_to_execute", "computing", "completed"];
+
+/// Whether `caller` may run over `datasets`, under `computation` if given
+pub fn authorize(caller: Principal, datasets: &[PrivateDataSource], computation: Option<&MPCComputation>) -> Result<(), String> {
+    if datasets.iter().all(|d| d.access_permissions.contains(&caller)) {
+        return Ok(());
+    }
+    let computation = computation.ok_or("Datasets of other parties can only be used under an approved computation")?;
+    if computation.requester != caller && !computation.received_signatures.contains(&caller) {
+        return Err(format!("Only the requester and signers of computation {} can run under it", computation.id));
+    }
+    if !APPROVED_STATUSES.contains(&computation.status.as_str()) {
+        return Err(format!("Computation {} is {}, not approved", computation.id, computation.status));
+    }
+    if let Some(dataset) = datasets.iter().find(|d| !computation.required_signatures.contains(&d.owner)) {
+        return Err(format!("The owner of dataset {} did not sign computation {}", dataset.id, computation.id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "run_approval_test.rs"]
+mod tests;
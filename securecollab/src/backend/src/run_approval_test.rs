@@ -0,0 +1,103 @@
+use super::*;
+use crate::test_support::principal;
+use crate::{DatasetMetadata, Priority};
+
+fn dataset(id: &str, owner: Principal) -> PrivateDataSource {
+    PrivateDataSource {
+        id: id.to_string(),
+        owner,
+        party_name: "Boston General".to_string(),
+        name: id.to_string(),
+        encrypted_data: vec![],
+        vetkey_id: String::new(),
+        schema: "age:number".to_string(),
+        record_count: 0,
+        created_at: 0,
+        access_permissions: vec![owner],
+        metadata: DatasetMetadata::default(),
+        tags: vec![],
+        allowed_purposes: None,
+    }
+}
+
+fn computation(requester: Principal, signers: &[Principal], status: &str) -> MPCComputation {
+    MPCComputation {
+        id: "mpc_1".to_string(),
+        title: "Cohort statistics".to_string(),
+        description: String::new(),
+        requester,
+        required_parties: signers.len() as u32,
+        approvals: vec![],
+        votes: vec![],
+        status: status.to_string(),
+        created_at: 0,
+        results: None,
+        signature_id: None,
+        required_signatures: signers.to_vec(),
+        received_signatures: signers.to_vec(),
+        vetkey_derivation_complete: true,
+        last_error: None,
+        retry_attempts: vec![],
+        shared_with: vec![],
+        encrypted_results: vec![],
+        output_schema: None,
+        structured_results: vec![],
+        agent_id: None,
+        agent_version: None,
+        priority: Priority::Normal,
+        llm_provider: None,
+        prompt_template: None,
+        purpose: "research".to_string(),
+        columns: vec![],
+    }
+}
+
+#[test]
+fn own_datasets_need_no_computation() {
+    let owner = principal(1);
+    let datasets = [dataset("a1", owner), dataset("a2", owner)];
+    authorize(owner, &datasets, None).unwrap();
+    assert!(authorize(principal(2), &datasets, None).is_err());
+}
+
+#[test]
+fn other_parties_datasets_need_a_computation_they_signed() {
+    let (hospital, insurer, outsider) = (principal(1), principal(2), principal(3));
+    let datasets = [dataset("a", hospital), dataset("b", insurer)];
+    assert!(authorize(hospital, &datasets, None).is_err());
+
+    let approved = computation(hospital, &[hospital, insurer], "ready_to_execute");
+    authorize(hospital, &datasets, Some(&approved)).unwrap();
+    authorize(insurer, &datasets, Some(&approved)).unwrap();
+    assert!(authorize(outsider, &datasets, Some(&approved)).is_err());
+
+    // Every owner must have been asked to sign, and the vote must have passed
+    assert!(authorize(hospital, &datasets, Some(&computation(hospital, &[hospital], "approved"))).is_err());
+    assert!(authorize(hospital, &datasets, Some(&computation(hospital, &[hospital, insurer], "pending_signatures"))).is_err());
+    assert!(authorize(hospital, &datasets, Some(&computation(hospital, &[hospital, insurer], "rejected"))).is_err());
+}
+
+#[test]
+fn analyses_combine_two_owners_datasets_under_their_computation() {
+    let (hospital, clinic, analyst) = (principal(1), principal(2), principal(3));
+    let datasets = [dataset("hospital_visits", hospital), dataset("clinic_visits", clinic)];
+    let computation = computation(analyst, &[hospital, clinic], "approved");
+    assert!(authorize(analyst, &datasets, None).is_err());
+    authorize(analyst, &datasets, Some(&computation)).unwrap();
+
+    // The run then reads both owners' records as one population
+    let rows = |offset: usize| std::iter::once("age,bp".to_string())
+        .chain((offset..offset + 200).map(|i| format!("{},{}", 20 + i % 60, 80 + i % 60)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let schema = crate::analytics::parse_schema("age:number[0..100],bp:number[50..250]").unwrap();
+    let request = crate::regression::CorrelationRequest {
+        dataset_ids: datasets.iter().map(|d| d.id.clone()).collect(),
+        columns: vec!["age".to_string(), "bp".to_string()],
+        epsilon: None,
+        computation_id: Some(computation.id.clone()),
+    };
+    let mut source = crate::differential_privacy::NoiseSource::new(b"seed".to_vec());
+    let matrix = crate::regression::correlate(&schema, &request, &[rows(0), rows(200)], 1e6, 5, &mut source).unwrap();
+    assert_eq!(matrix.records, 400);
+}
@@ -0,0 +1,157 @@
+//! Execution queue for approved computations
+//!
+//! Computations are queued once they are ready to execute. Higher priorities
+//! run first and equal priorities run in the order they were queued. At most
+//! the configured number of computations execute at the same time; the rest
+//! wait and can look up their position.
+
+use candid::{CandidType, Deserialize};
+use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use crate::backup;
+
+pub const DEFAULT_MAX_CONCURRENT: u32 = 4;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct QueuedComputation {
+    pub request_id: String,
+    pub priority: Priority,
+    pub enqueued_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct QueuePosition {
+    pub request_id: String,
+    pub priority: Priority,
+    /// 1-based position among waiting computations; 0 while running
+    pub position: u32,
+    pub running: bool,
+    pub active_executions: u32,
+    pub max_concurrent: u32,
+}
+
+thread_local! {
+    static QUEUE: RefCell<Vec<QueuedComputation>> = const { RefCell::new(Vec::new()) };
+    static RUNNING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+    static MAX_CONCURRENT: Cell<u32> = const { Cell::new(DEFAULT_MAX_CONCURRENT) };
+}
+
+/// Highest priority first, then first come first served
+pub fn order(queue: &mut [QueuedComputation]) {
+    queue.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.enqueued_at.cmp(&b.enqueued_at)));
+}
+
+/// Queue a computation; a computation already queued keeps its place
+pub fn enqueue(request_id: &str, priority: Priority, now: u64) {
+    QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        if queue.iter().any(|q| q.request_id == request_id) {
+            return;
+        }
+        queue.push(QueuedComputation { request_id: request_id.to_string(), priority, enqueued_at: now });
+        order(&mut queue);
+    });
+}
+
+/// Drop a computation from the queue once it has finished or been withdrawn
+pub fn remove(request_id: &str) {
+    QUEUE.with(|queue| queue.borrow_mut().retain(|q| q.request_id != request_id));
+}
+
+pub fn set_priority(request_id: &str, priority: Priority) -> Result<(), String> {
+    QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        let entry = queue.iter_mut().find(|q| q.request_id == request_id)
+            .ok_or_else(|| format!("Computation {} is not queued", request_id))?;
+        entry.priority = priority;
+        order(&mut queue);
+        Ok(())
+    })
+}
+
+pub fn max_concurrent() -> u32 {
+    MAX_CONCURRENT.with(|m| m.get())
+}
+
+pub fn set_max_concurrent(max: u32) -> Result<(), String> {
+    if max == 0 {
+        return Err("At least one execution must be allowed".to_string());
+    }
+    MAX_CONCURRENT.with(|m| m.set(max));
+    Ok(())
+}
+
+/// Waiting computations in the order they will run
+pub fn waiting() -> Vec<QueuedComputation> {
+    RUNNING.with(|running| {
+        let running = running.borrow();
+        QUEUE.with(|queue| queue.borrow().iter().filter(|q| !running.contains(&q.request_id)).cloned().collect())
+    })
+}
+
+pub fn position(request_id: &str) -> Option<QueuePosition> {
+    let entry = QUEUE.with(|queue| queue.borrow().iter().find(|q| q.request_id == request_id).cloned())?;
+    let running = RUNNING.with(|running| running.borrow().contains(request_id));
+    let position = if running {
+        0
+    } else {
+        waiting().iter().position(|q| q.request_id == request_id).map_or(0, |i| i as u32 + 1)
+    };
+    Some(QueuePosition {
+        request_id: request_id.to_string(),
+        priority: entry.priority,
+        position,
+        running,
+        active_executions: RUNNING.with(|running| running.borrow().len() as u32),
+        max_concurrent: max_concurrent(),
+    })
+}
+
+/// An execution slot, given back when dropped
+pub struct Slot {
+    request_id: String,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        RUNNING.with(|running| running.borrow_mut().remove(&self.request_id));
+    }
+}
+
+/// Take an execution slot if the computation is next in line for one.
+/// Computations that were never queued join the queue at normal priority.
+pub fn admit(request_id: &str, now: u64) -> Result<Slot, String> {
+    enqueue(request_id, Priority::Normal, now);
+    let active = RUNNING.with(|running| running.borrow().len() as u32);
+    let free = max_concurrent().saturating_sub(active);
+    let position = waiting().iter().position(|q| q.request_id == request_id)
+        .ok_or_else(|| format!("Computation {} is already running", request_id))? as u32;
+    if position >= free {
+        return Err(format!("Computation is queued at position {} with {} executions running; try again later",
+            position + 1, active));
+    }
+    RUNNING.with(|running| running.borrow_mut().insert(request_id.to_string()));
+    Ok(Slot { request_id: request_id.to_string() })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&QUEUE), MAX_CONCURRENT.with(Cell::get)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (queue, max_concurrent) = backup::decode(bytes)?;
+    backup::replace(&QUEUE, queue);
+    MAX_CONCURRENT.with(|cell| cell.set(max_concurrent));
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "scheduler_test.rs"]
+mod tests;
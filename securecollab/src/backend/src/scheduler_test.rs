@@ -0,0 +1,47 @@
+use super::*;
+
+fn queued(request_id: &str, priority: Priority, enqueued_at: u64) -> QueuedComputation {
+    QueuedComputation { request_id: request_id.to_string(), priority, enqueued_at }
+}
+
+#[test]
+fn higher_priority_runs_first_then_fifo() {
+    let mut queue = vec![
+        queued("a", Priority::Normal, 1),
+        queued("b", Priority::Low, 0),
+        queued("c", Priority::High, 3),
+        queued("d", Priority::Normal, 2),
+    ];
+    order(&mut queue);
+    let ids: Vec<&str> = queue.iter().map(|q| q.request_id.as_str()).collect();
+    assert_eq!(ids, vec!["c", "a", "d", "b"]);
+}
+
+#[test]
+fn admission_respects_limit_and_queue_order() {
+    set_max_concurrent(1).unwrap();
+    enqueue("first", Priority::Normal, 1);
+    enqueue("second", Priority::Normal, 2);
+
+    assert!(admit("second", 3).is_err());
+    let slot = admit("first", 3).unwrap();
+    assert!(admit("second", 3).is_err());
+    assert_eq!(position("second").unwrap().position, 1);
+    assert!(position("first").unwrap().running);
+
+    drop(slot);
+    remove("first");
+    let _slot = admit("second", 4).unwrap();
+    assert!(position("first").is_none());
+}
+
+#[test]
+fn raising_priority_moves_a_computation_ahead() {
+    enqueue("low", Priority::Normal, 1);
+    enqueue("urgent", Priority::Normal, 2);
+    set_priority("urgent", Priority::High).unwrap();
+    assert_eq!(position("urgent").unwrap().position, 1);
+    assert_eq!(position("low").unwrap().position, 2);
+    assert!(set_priority("missing", Priority::High).is_err());
+    assert!(set_max_concurrent(0).is_err());
+}
@@ -1,8 +1,20 @@
 use ic_cdk::api::caller;
-use ic_cdk::call;
 use candid::Principal;
 use candid::{CandidType, Deserialize};
+use serde_json::Value;
 use crate::identity_manager::{check_permission, get_identity, decrypt_with_vetkd, verify_signature_complete};
+use crate::roles::Permission;
+use crate::progress::{self, ProgressEvent};
+
+pub mod http_outcall;
+pub mod injection;
+pub mod provider;
+pub mod redaction;
+pub mod resilience;
+pub mod templates;
+pub mod usage;
+
+use provider::{CallContext, ChatMessage, LlmProvider};
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct SecureComputationRequest {
@@ -31,7 +43,7 @@ pub async fn secure_llm_computation(
     request: SecureComputationRequest,
 ) -> Result<SecureComputationResult, String> {
     // Verify caller has permission
-    check_permission("compute")?;
+    check_permission(Permission::Compute)?;
     
     // Verify multi-party signatures if required
     if let Some(signature_id) = &request.signature_id {
@@ -56,14 +68,33 @@ pub async fn secure_llm_computation(
     
     computation_log.push("All datasets decrypted successfully".to_string());
     
+    // Screen the query and the data for injected instructions before they reach the prompt
+    let sources: Vec<String> = request.encrypted_data_ids.iter().map(|id| format!("dataset {}", id)).collect();
+    let mut inputs = vec![("query", request.prompt.as_str())];
+    inputs.extend(sources.iter().map(String::as_str).zip(decrypted_datasets.iter().map(String::as_str)));
+    let (mut screened, findings) = injection::screen(&inputs, &injection::policy())?;
+    if !findings.is_empty() {
+        computation_log.push(format!("Sanitized possible prompt injection: {}", injection::describe(&findings)));
+    }
+    let query = screened.remove(0);
+
     // Prepare data for LLM computation
-    let combined_data = combine_datasets_securely(&decrypted_datasets)?;
-    let enhanced_prompt = create_secure_prompt(&request.prompt, &combined_data)?;
+    let (combined_data, redactions) = combine_datasets_securely(&screened)?;
+    if !redactions.is_empty() {
+        computation_log.push(format!("Redacted identifiers: {}", redaction::describe(&redactions)));
+    }
+    let template = templates::active_for(&request.computation_type);
+    let enhanced_prompt = create_secure_prompt(template.as_ref(), &query, &combined_data)?;
     
     computation_log.push("Sending computation to LLM canister".to_string());
     
-    // Call LLM canister with the decrypted data
-    let llm_result = call_llm_canister(enhanced_prompt).await?;
+    // Call the configured LLM backend with the decrypted data
+    let context = CallContext {
+        computation_id: request.request_id.clone(),
+        principal: request.requester,
+        provider: None,
+    };
+    let llm_result = provider::resolve(&context, &request.request_id)?.prompt(&enhanced_prompt).await?;
     
     computation_log.push("LLM computation completed".to_string());
     
@@ -110,18 +141,32 @@ async fn decrypt_dataset_for_computation(data_id: &str) -> Result<String, String
 }
 
 // Combine datasets securely for computation
-fn combine_datasets_securely(datasets: &[String]) -> Result<String, String> {
+fn combine_datasets_securely(datasets: &[String]) -> Result<(String, Vec<redaction::RedactionCount>), String> {
     // In a real implementation, this would:
     // 1. Validate data schemas are compatible
     // 2. Apply privacy-preserving transformations
     // 3. Combine data while maintaining privacy guarantees
     
-    let combined = datasets.join("\n---DATASET_SEPARATOR---\n");
-    Ok(combined)
+    // Identifiers never reach the prompt; one redactor keeps tokens consistent across parties
+    let mut redactor = redaction::Redactor::new();
+    let redacted: Vec<String> = datasets.iter().map(|data| redactor.redact(data)).collect();
+    let combined = redacted.join("\n---DATASET_SEPARATOR---\n");
+    Ok((combined, redactor.summary()))
 }
 
-// Create secure prompt for LLM
-fn create_secure_prompt(original_prompt: &str, data: &str) -> Result<String, String> {
+// Create secure prompt for LLM, from the computation type's approved template if it has one
+fn create_secure_prompt(
+    template: Option<&templates::PromptTemplate>,
+    original_prompt: &str,
+    data: &str,
+) -> Result<String, String> {
+    if let Some(template) = template {
+        return templates::render(template, &[], &[
+            ("prompt", original_prompt.to_string()),
+            ("data", data.to_string()),
+        ]);
+    }
+
     let secure_prompt = format!(
         "SECURE COMPUTATION REQUEST:\n\
         Instructions: Analyze the following multi-party healthcare data while maintaining privacy.\n\
@@ -146,23 +191,224 @@ fn create_secure_prompt(original_prompt: &str, data: &str) -> Result<String, Str
     Ok(secure_prompt)
 }
 
-// Call the LLM canister
-async fn call_llm_canister(prompt: String) -> Result<String, String> {
-    let llm_canister_id = Principal::from_text("w36hm-eqaaa-aaaal-qr76a-cai")
-        .map_err(|_| "Invalid LLM canister ID".to_string())?;
-    
-    let result: Result<(String,), _> = call(
-        llm_canister_id,
-        "prompt",
-        (prompt,),
-    ).await;
-    
-    match result {
-        Ok((response,)) => Ok(response),
-        Err((code, msg)) => Err(format!("LLM call failed: {:?} - {}", code, msg)),
+/// Attempts (initial call plus retries) to get schema-conforming JSON
+pub const MAX_STRUCTURED_ATTEMPTS: u32 = 3;
+
+/// JSON output validated against a computation's declared schema
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StructuredOutput {
+    pub json: String,
+    pub attempts: u32,
+    pub repaired: bool,
+}
+
+/// Parse and sanity-check a declared output schema
+pub fn parse_output_schema(schema: &str) -> Result<Value, String> {
+    let schema: Value = serde_json::from_str(schema)
+        .map_err(|e| format!("Output schema is not valid JSON: {}", e))?;
+    if !schema.is_object() {
+        return Err("Output schema must be a JSON object".to_string());
+    }
+    Ok(schema)
+}
+
+/// Prompt suffix telling the model to answer with JSON matching `schema`
+pub fn schema_instructions(schema: &Value) -> String {
+    format!(
+        "\n\nOutput format:\n\
+        Respond with a single JSON value and nothing else. No prose, no code fences.\n\
+        The JSON must conform to this JSON Schema:\n{}",
+        schema
+    )
+}
+
+/// Pull a JSON value out of a model response, repairing common defects
+/// (code fences, surrounding prose, trailing commas). Returns the value and
+/// whether a repair was needed.
+pub fn extract_json(response: &str) -> Option<(Value, bool)> {
+    let trimmed = response.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some((value, false));
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    if end < start {
+        return None;
+    }
+    let candidate = &trimmed[start..=end];
+    serde_json::from_str(candidate)
+        .or_else(|_| serde_json::from_str(&strip_trailing_commas(candidate)))
+        .ok()
+        .map(|value| (value, true))
+}
+
+// Drop commas that directly precede a closing bracket, outside of strings
+fn strip_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Validate `value` against the supported JSON Schema subset: `type`,
+/// `properties`, `required`, `additionalProperties: false`, `items`, `enum`,
+/// `minimum` and `maximum`. Returns every violation found.
+pub fn validate_against_schema(value: &Value, schema: &Value) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    validate_at("$", value, schema, &mut errors);
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+fn validate_at(path: &str, value: &Value, schema: &Value, errors: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => vec![],
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| matches_type(value, t)) {
+            errors.push(format!("{}: expected {}, found {}", path, allowed.join(" | "), type_name(value)));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!("{}: value not in enum", path));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                errors.push(format!("{}: {} is below minimum {}", path, number, minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                errors.push(format!("{}: {} is above maximum {}", path, number, maximum));
+            }
+        }
+    }
+
+    if let Value::Object(fields) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    errors.push(format!("{}: missing required property '{}'", path, name));
+                }
+            }
+        }
+        for (name, field) in fields {
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => validate_at(&format!("{}.{}", path, name), field, field_schema, errors),
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    errors.push(format!("{}: unexpected property '{}'", path, name));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(&format!("{}[{}]", path, i), item, item_schema, errors);
+        }
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
     }
 }
 
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+/// Ask the LLM for output matching `schema`, repairing and re-prompting with
+/// the validation errors until it conforms or attempts run out. Each attempt
+/// is reported as progress under the context's computation id.
+pub async fn structured_completion(
+    prompt: &str,
+    schema: &str,
+    context: &CallContext,
+) -> Result<StructuredOutput, String> {
+    let schema = parse_output_schema(schema)?;
+    let mut conversation = vec![ChatMessage::user(&format!("{}{}", prompt, schema_instructions(&schema)))];
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_STRUCTURED_ATTEMPTS {
+        progress::report(&context.computation_id, ProgressEvent::Note(
+            format!("Structured output attempt {} of {}", attempt, MAX_STRUCTURED_ATTEMPTS)
+        ), ic_cdk::api::time());
+        let request_key = format!("{}:{}", context.computation_id, attempt);
+        let response = provider::resolve(context, &request_key)?.chat(&conversation).await?;
+
+        let problems = match extract_json(&response) {
+            Some((value, repaired)) => match validate_against_schema(&value, &schema) {
+                Ok(()) => {
+                    return Ok(StructuredOutput {
+                        json: value.to_string(),
+                        attempts: attempt,
+                        repaired,
+                    });
+                }
+                Err(errors) => errors.join("; "),
+            },
+            None => "response did not contain parseable JSON".to_string(),
+        };
+
+        last_error = problems;
+        conversation.push(ChatMessage::assistant(&response));
+        conversation.push(ChatMessage::user(&format!(
+            "Your previous answer was rejected: {}. Answer again with corrected JSON only.", last_error
+        )));
+    }
+
+    Err(format!("LLM output did not match the schema after {} attempts: {}", MAX_STRUCTURED_ATTEMPTS, last_error))
+}
+
 // Generate privacy proof for the computation
 fn generate_computation_privacy_proof(
     request: &SecureComputationRequest,
@@ -247,7 +493,7 @@ pub async fn create_secure_computation_request(
     prompt: String,
     required_signers: Vec<String>,
 ) -> Result<SecureComputationRequest, String> {
-    check_permission("create_computation")?;
+    check_permission(Permission::Compute)?;
     
     let request_id = format!("comp_{}_{}", 
         ic_cdk::api::time(), 
@@ -284,3 +530,7 @@ pub async fn create_secure_computation_request(
     
     Ok(request)
 }
+
+#[cfg(test)]
+#[path = "secure_llm_test.rs"]
+mod tests;
@@ -0,0 +1,299 @@
+//! HTTPS-outcall LLM providers
+//!
+//! A computation can use an OpenAI- or Anthropic-compatible HTTP API instead
+//! of the IC LLM canister. Every replica of the subnet sends the request, so
+//! each carries an idempotency key that lets the provider run it only once,
+//! and a transform reduces the response to the completion text so replicas
+//! can agree on it. API keys are held in canister memory and are visible to
+//! the subnet's node providers.
+
+use candid::{CandidType, Deserialize, Nat};
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse, TransformArgs, TransformContext,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use super::provider::{ChatMessage, LlmProvider};
+use crate::backup;
+
+/// Canister query used to transform provider responses
+pub const TRANSFORM_METHOD: &str = "transform_llm_response";
+/// Largest response the management canister accepts (2 MiB)
+pub const MAX_RESPONSE_BYTES_LIMIT: u64 = 2 * 1024 * 1024;
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const ANTHROPIC_MAX_TOKENS: u64 = 4096;
+// Nodes on the subnet paying for the outcall
+const SUBNET_SIZE: u128 = 13;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ApiStyle {
+    OpenAi,
+    Anthropic,
+}
+
+/// What a request asks the provider for; tells the transform how to read the response
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RequestKind {
+    Chat,
+    Embeddings,
+}
+
+fn transform_context(style: ApiStyle, kind: RequestKind) -> Vec<u8> {
+    vec![style as u8, kind as u8]
+}
+
+fn parse_transform_context(context: &[u8]) -> Option<(ApiStyle, RequestKind)> {
+    let style = match context.first()? {
+        0 => ApiStyle::OpenAi,
+        1 => ApiStyle::Anthropic,
+        _ => return None,
+    };
+    let kind = match context.get(1)? {
+        0 => RequestKind::Chat,
+        1 => RequestKind::Embeddings,
+        _ => return None,
+    };
+    Some((style, kind))
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpProvider {
+    pub name: String,
+    pub api_style: ApiStyle,
+    pub url: String,
+    pub model: String,
+    pub api_key: String,
+    pub max_response_bytes: u64,
+    /// OpenAI-compatible embeddings endpoint and model, if the provider offers them
+    pub embeddings_url: Option<String>,
+    pub embeddings_model: Option<String>,
+}
+
+/// A provider as shown to callers, without its API key
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpProviderInfo {
+    pub name: String,
+    pub api_style: ApiStyle,
+    pub url: String,
+    pub model: String,
+    pub max_response_bytes: u64,
+    pub embeddings_url: Option<String>,
+    pub embeddings_model: Option<String>,
+}
+
+thread_local! {
+    static PROVIDERS: RefCell<HashMap<String, HttpProvider>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_provider(provider: HttpProvider) -> Result<(), String> {
+    if provider.name.trim().is_empty() || provider.model.trim().is_empty() {
+        return Err("Provider name and model are required".to_string());
+    }
+    if !provider.url.starts_with("https://")
+        || provider.embeddings_url.as_ref().is_some_and(|url| !url.starts_with("https://")) {
+        return Err("Provider URL must use https".to_string());
+    }
+    if provider.max_response_bytes == 0 || provider.max_response_bytes > MAX_RESPONSE_BYTES_LIMIT {
+        return Err(format!("max_response_bytes must be between 1 and {}", MAX_RESPONSE_BYTES_LIMIT));
+    }
+    PROVIDERS.with(|providers| providers.borrow_mut().insert(provider.name.clone(), provider));
+    Ok(())
+}
+
+pub fn remove_provider(name: &str) -> Result<(), String> {
+    PROVIDERS.with(|providers| providers.borrow_mut().remove(name))
+        .map(|_| ())
+        .ok_or_else(|| format!("LLM provider {} not found", name))
+}
+
+pub fn has_provider(name: &str) -> bool {
+    PROVIDERS.with(|providers| providers.borrow().contains_key(name))
+}
+
+pub fn providers() -> Vec<HttpProviderInfo> {
+    PROVIDERS.with(|providers| {
+        providers.borrow().values().map(|p| HttpProviderInfo {
+            name: p.name.clone(),
+            api_style: p.api_style,
+            url: p.url.clone(),
+            model: p.model.clone(),
+            max_response_bytes: p.max_response_bytes,
+            embeddings_url: p.embeddings_url.clone(),
+            embeddings_model: p.embeddings_model.clone(),
+        }).collect()
+    })
+}
+
+/// Key identifying one logical request, the same for every replica and every retry of it
+pub fn idempotency_key(request_key: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request_key.as_bytes());
+    hasher.update(b":");
+    hasher.update(body);
+    hex::encode(&hasher.finalize()[..16])
+}
+
+pub fn request_body(style: ApiStyle, model: &str, messages: &[ChatMessage]) -> Vec<u8> {
+    let messages: Vec<Value> = messages.iter().map(|m| json!({ "role": m.role, "content": m.content })).collect();
+    // Deterministic sampling gives replicas the best chance of agreeing
+    let body = match style {
+        ApiStyle::OpenAi => json!({ "model": model, "messages": messages, "temperature": 0 }),
+        ApiStyle::Anthropic => json!({
+            "model": model, "messages": messages, "temperature": 0, "max_tokens": ANTHROPIC_MAX_TOKENS
+        }),
+    };
+    body.to_string().into_bytes()
+}
+
+pub fn request_headers(provider: &HttpProvider, idempotency_key: &str) -> Vec<HttpHeader> {
+    let header = |name: &str, value: &str| HttpHeader { name: name.to_string(), value: value.to_string() };
+    let mut headers = vec![
+        header("Content-Type", "application/json"),
+        header("Idempotency-Key", idempotency_key),
+    ];
+    match provider.api_style {
+        ApiStyle::OpenAi => headers.push(header("Authorization", &format!("Bearer {}", provider.api_key))),
+        ApiStyle::Anthropic => {
+            headers.push(header("x-api-key", &provider.api_key));
+            headers.push(header("anthropic-version", ANTHROPIC_VERSION));
+        }
+    }
+    headers
+}
+
+pub fn embeddings_body(model: &str, inputs: &[String]) -> Vec<u8> {
+    json!({ "model": model, "input": inputs }).to_string().into_bytes()
+}
+
+/// Embedding vectors, in input order, from an OpenAI-compatible response
+pub fn extract_embeddings(body: &[u8]) -> Result<Vec<Vec<f32>>, String> {
+    let value: Value = serde_json::from_slice(body).map_err(|e| format!("Invalid provider response: {}", e))?;
+    let data = value["data"].as_array().ok_or_else(|| "Provider response contained no embeddings".to_string())?;
+    data.iter()
+        .map(|item| {
+            item["embedding"].as_array()
+                .map(|values| values.iter().filter_map(Value::as_f64).map(|v| v as f32).collect())
+                .ok_or_else(|| "Malformed embedding in provider response".to_string())
+        })
+        .collect()
+}
+
+/// Completion text from a provider's JSON response
+pub fn extract_completion(style: ApiStyle, body: &[u8]) -> Result<String, String> {
+    let value: Value = serde_json::from_slice(body).map_err(|e| format!("Invalid provider response: {}", e))?;
+    let text = match style {
+        ApiStyle::OpenAi => value["choices"][0]["message"]["content"].as_str().map(str::to_string),
+        ApiStyle::Anthropic => value["content"].as_array().map(|blocks| {
+            blocks.iter()
+                .filter(|b| b["type"] == "text")
+                .filter_map(|b| b["text"].as_str())
+                .collect::<String>()
+        }),
+    };
+    text.ok_or_else(|| "Provider response contained no completion".to_string())
+}
+
+/// Cycles attached to an outcall on a 13-node subnet
+pub fn outcall_cycles(request_bytes: u64, max_response_bytes: u64) -> u128 {
+    (3_000_000 + 60_000 * SUBNET_SIZE) * SUBNET_SIZE
+        + 400 * SUBNET_SIZE * u128::from(request_bytes)
+        + 800 * SUBNET_SIZE * u128::from(max_response_bytes)
+}
+
+/// Reduce a provider response to its status and the completion text (or the
+/// embeddings as JSON), dropping headers and metadata that differ between replicas
+pub fn transform(args: TransformArgs) -> HttpResponse {
+    let success = u16::try_from(&args.response.status.0).is_ok_and(|code| (200..300).contains(&code));
+    let content = match parse_transform_context(&args.context) {
+        Some((style, RequestKind::Chat)) if success => extract_completion(style, &args.response.body)
+            .ok()
+            .map(String::into_bytes),
+        Some((_, RequestKind::Embeddings)) if success => extract_embeddings(&args.response.body)
+            .ok()
+            .and_then(|embeddings| serde_json::to_vec(&embeddings).ok()),
+        _ => None,
+    };
+    match content {
+        Some(body) => HttpResponse { status: args.response.status, headers: vec![], body },
+        // Error bodies tend to carry per-request ids, so only the status is kept;
+        // an unreadable success is reported as a bad gateway
+        None => HttpResponse {
+            status: if success { Nat::from(502u32) } else { args.response.status },
+            headers: vec![],
+            body: vec![],
+        },
+    }
+}
+
+/// A configured provider bound to one logical request
+pub struct HttpsProvider {
+    provider: HttpProvider,
+    request_key: String,
+}
+
+impl HttpsProvider {
+    pub fn new(name: &str, request_key: &str) -> Result<Self, String> {
+        let provider = PROVIDERS.with(|providers| providers.borrow().get(name).cloned())
+            .ok_or_else(|| format!("LLM provider {} not found", name))?;
+        Ok(Self { provider, request_key: request_key.to_string() })
+    }
+
+    pub fn supports_embeddings(&self) -> bool {
+        self.provider.embeddings_url.is_some() && self.provider.embeddings_model.is_some()
+    }
+
+    async fn send(&self, url: &str, body: Vec<u8>, kind: RequestKind) -> Result<Vec<u8>, String> {
+        let provider = &self.provider;
+        let cycles = outcall_cycles(body.len() as u64, provider.max_response_bytes);
+        let request = CanisterHttpRequestArgument {
+            url: url.to_string(),
+            max_response_bytes: Some(provider.max_response_bytes),
+            method: HttpMethod::POST,
+            headers: request_headers(provider, &idempotency_key(&self.request_key, &body)),
+            body: Some(body),
+            transform: Some(TransformContext::from_name(
+                TRANSFORM_METHOD.to_string(),
+                transform_context(provider.api_style, kind),
+            )),
+        };
+
+        let (response,) = http_request(request, cycles).await
+            .map_err(|(code, msg)| format!("LLM provider call failed: {:?} - {}", code, msg))?;
+        if response.status != 200u32 {
+            return Err(format!("LLM provider {} returned HTTP {}", provider.name, response.status));
+        }
+        Ok(response.body)
+    }
+}
+
+impl LlmProvider for HttpsProvider {
+    async fn chat(&self, messages: &[ChatMessage]) -> Result<String, String> {
+        let body = request_body(self.provider.api_style, &self.provider.model, messages);
+        let response = self.send(&self.provider.url, body, RequestKind::Chat).await?;
+        String::from_utf8(response).map_err(|_| "LLM provider returned invalid UTF-8".to_string())
+    }
+
+    async fn embeddings(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let (Some(url), Some(model)) = (&self.provider.embeddings_url, &self.provider.embeddings_model) else {
+            return Err(format!("LLM provider {} does not offer embeddings", self.provider.name));
+        };
+        let response = self.send(url, embeddings_body(model, inputs), RequestKind::Embeddings).await?;
+        serde_json::from_slice(&response).map_err(|e| format!("Invalid embeddings from provider: {}", e))
+    }
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&PROVIDERS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let providers = backup::decode(bytes)?;
+    backup::replace(&PROVIDERS, providers);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "http_outcall_test.rs"]
+mod tests;
@@ -0,0 +1,95 @@
+use super::*;
+
+fn provider(api_style: ApiStyle) -> HttpProvider {
+    HttpProvider {
+        name: "external".to_string(),
+        api_style,
+        url: "https://api.example.com/v1/chat".to_string(),
+        model: "model-1".to_string(),
+        api_key: "secret".to_string(),
+        max_response_bytes: 16 * 1024,
+        embeddings_url: None,
+        embeddings_model: None,
+    }
+}
+
+fn response(status: u32, body: &str) -> HttpResponse {
+    HttpResponse {
+        status: Nat::from(status),
+        headers: vec![HttpHeader { name: "x-request-id".to_string(), value: "abc".to_string() }],
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn extracts_completion_for_each_api_style() {
+    let openai = r#"{"id":"x","choices":[{"message":{"role":"assistant","content":"hello"}}]}"#;
+    assert_eq!(extract_completion(ApiStyle::OpenAi, openai.as_bytes()).unwrap(), "hello");
+
+    let anthropic = r#"{"id":"y","content":[{"type":"text","text":"hel"},{"type":"tool_use"},{"type":"text","text":"lo"}]}"#;
+    assert_eq!(extract_completion(ApiStyle::Anthropic, anthropic.as_bytes()).unwrap(), "hello");
+
+    assert!(extract_completion(ApiStyle::OpenAi, b"{}").is_err());
+    assert!(extract_completion(ApiStyle::OpenAi, b"not json").is_err());
+}
+
+#[test]
+fn transform_keeps_only_status_and_completion() {
+    let args = TransformArgs {
+        response: response(200, r#"{"id":"replica-specific","choices":[{"message":{"content":"result"}}]}"#),
+        context: transform_context(ApiStyle::OpenAi, RequestKind::Chat),
+    };
+    let transformed = transform(args);
+    assert!(transformed.headers.is_empty());
+    assert_eq!(transformed.body, b"result");
+
+    let failed = transform(TransformArgs { response: response(429, "rate limited at 12:00:01"), context: vec![0, 0] });
+    assert_eq!(failed.status, Nat::from(429u32));
+    assert!(failed.body.is_empty());
+
+    let unreadable = transform(TransformArgs { response: response(200, "<html>"), context: vec![0, 0] });
+    assert_eq!(unreadable.status, Nat::from(502u32));
+}
+
+#[test]
+fn transform_reduces_embeddings_to_vectors() {
+    let body = r#"{"object":"list","data":[{"index":0,"embedding":[0.5,-1.0]},{"index":1,"embedding":[0.25]}],"usage":{}}"#;
+    let transformed = transform(TransformArgs {
+        response: response(200, body),
+        context: transform_context(ApiStyle::OpenAi, RequestKind::Embeddings),
+    });
+    let embeddings: Vec<Vec<f32>> = serde_json::from_slice(&transformed.body).unwrap();
+    assert_eq!(embeddings, vec![vec![0.5, -1.0], vec![0.25]]);
+}
+
+#[test]
+fn idempotency_key_is_stable_per_request() {
+    assert_eq!(idempotency_key("req_1:1", b"body"), idempotency_key("req_1:1", b"body"));
+    assert_ne!(idempotency_key("req_1:1", b"body"), idempotency_key("req_1:2", b"body"));
+}
+
+#[test]
+fn headers_follow_the_api_style() {
+    let openai = request_headers(&provider(ApiStyle::OpenAi), "key");
+    assert!(openai.iter().any(|h| h.name == "Authorization" && h.value == "Bearer secret"));
+    let anthropic = request_headers(&provider(ApiStyle::Anthropic), "key");
+    assert!(anthropic.iter().any(|h| h.name == "x-api-key" && h.value == "secret"));
+    assert!(anthropic.iter().any(|h| h.name == "Idempotency-Key" && h.value == "key"));
+
+    let body: Value = serde_json::from_slice(&request_body(ApiStyle::Anthropic, "model-1", &[ChatMessage::user("hi")])).unwrap();
+    assert_eq!(body["messages"][0]["content"], "hi");
+    assert!(body["max_tokens"].is_number());
+}
+
+#[test]
+fn providers_must_use_https_and_hide_their_keys() {
+    let mut insecure = provider(ApiStyle::OpenAi);
+    insecure.url = "http://api.example.com".to_string();
+    assert!(set_provider(insecure).is_err());
+
+    set_provider(provider(ApiStyle::OpenAi)).unwrap();
+    assert!(has_provider("external"));
+    assert_eq!(providers()[0].model, "model-1");
+    remove_provider("external").unwrap();
+    assert!(remove_provider("external").is_err());
+}
@@ -0,0 +1,107 @@
+//! Prompt injection screening
+//!
+//! User queries and decrypted dataset content are scanned for text that tries
+//! to take over the model: instruction overrides, role switches and chat
+//! template markers. Depending on the policy a hit either rejects the
+//! computation or is cut out of the text before the prompt is built.
+
+use candid::{CandidType, Deserialize};
+use regex::Regex;
+use std::cell::RefCell;
+use crate::backup;
+
+const EXCERPT_CHARS: usize = 80;
+const REMOVED: &str = "[removed]";
+
+const RULES: &[(&str, &str)] = &[
+    ("instruction_override", r"(?i)\b(ignore|disregard|forget|override)\s+(all\s+|any\s+)?(of\s+)?(the\s+|your\s+|my\s+)?(previous|prior|above|earlier|preceding|system)\s+(instructions?|prompts?|rules|directions)"),
+    ("new_instructions", r"(?i)\b(new|updated|real)\s+instructions\s*:"),
+    ("role_switch", r"(?i)\b(you\s+are\s+now|from\s+now\s+on\s+you\s+are|act\s+as\s+(a|an|the)\s+(unrestricted|jailbroken|different))\b"),
+    ("role_marker", r"(?im)^\s*(system|assistant|developer)\s*:"),
+    ("chat_template_token", r"(?i)<\|(im_start|im_end|system|assistant|user|endoftext)\|>|\[/?inst\]|<</?sys>>"),
+    ("prompt_exfiltration", r"(?i)\b(reveal|print|repeat|show|output)\s+(me\s+)?(your|the)\s+(system\s+|hidden\s+|original\s+)?(prompt|instructions)"),
+];
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum InjectionPolicy {
+    Reject,
+    Sanitize,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InjectionFinding {
+    /// What was scanned, e.g. "query" or "dataset data_1"
+    pub source: String,
+    pub rule: String,
+    pub excerpt: String,
+}
+
+thread_local! {
+    static POLICY: RefCell<InjectionPolicy> = const { RefCell::new(InjectionPolicy::Reject) };
+    static COMPILED: Vec<(&'static str, Regex)> = RULES.iter()
+        .map(|(rule, pattern)| (*rule, Regex::new(pattern).expect("injection rules are valid regexes")))
+        .collect();
+}
+
+pub fn set_policy(policy: InjectionPolicy) {
+    POLICY.with(|p| *p.borrow_mut() = policy);
+}
+
+pub fn policy() -> InjectionPolicy {
+    POLICY.with(|p| p.borrow().clone())
+}
+
+pub fn scan(source: &str, text: &str) -> Vec<InjectionFinding> {
+    COMPILED.with(|rules| {
+        rules.iter()
+            .flat_map(|(rule, regex)| regex.find_iter(text).map(move |m| InjectionFinding {
+                source: source.to_string(),
+                rule: rule.to_string(),
+                excerpt: m.as_str().trim().chars().take(EXCERPT_CHARS).collect(),
+            }))
+            .collect()
+    })
+}
+
+/// `text` with every match cut out
+pub fn sanitize(text: &str) -> String {
+    COMPILED.with(|rules| {
+        rules.iter().fold(text.to_string(), |text, (_, regex)| regex.replace_all(&text, REMOVED).into_owned())
+    })
+}
+
+/// Human-readable reason recorded for rejected or sanitized input
+pub fn describe(findings: &[InjectionFinding]) -> String {
+    findings.iter()
+        .map(|f| format!("{} in {} (\"{}\")", f.rule, f.source, f.excerpt))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Screen `(source, text)` inputs under `policy`. Returns the texts to use,
+/// sanitized if needed, with what was found; rejects with the reason instead
+/// when the policy says so.
+pub fn screen(inputs: &[(&str, &str)], policy: &InjectionPolicy) -> Result<(Vec<String>, Vec<InjectionFinding>), String> {
+    let findings: Vec<InjectionFinding> = inputs.iter().flat_map(|(source, text)| scan(source, text)).collect();
+    if findings.is_empty() {
+        return Ok((inputs.iter().map(|(_, text)| text.to_string()).collect(), findings));
+    }
+    match policy {
+        InjectionPolicy::Reject => Err(format!("Possible prompt injection rejected: {}", describe(&findings))),
+        InjectionPolicy::Sanitize => Ok((inputs.iter().map(|(_, text)| sanitize(text)).collect(), findings)),
+    }
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&POLICY))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let policy = backup::decode(bytes)?;
+    backup::replace(&POLICY, policy);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "injection_test.rs"]
+mod tests;
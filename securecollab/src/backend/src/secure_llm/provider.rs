@@ -0,0 +1,251 @@
+//! LLM provider abstraction
+//!
+//! Everything that talks to a language model goes through [`LlmProvider`].
+//! The canister-wide backend is configured by an admin; a computation may
+//! name an HTTPS provider to use instead. The mock backend answers
+//! deterministically for local development and tests.
+
+use candid::{CandidType, Deserialize, Principal};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use super::http_outcall::HttpsProvider;
+use super::{resilience, usage};
+use crate::backup;
+
+const IC_LLM_CANISTER: &str = "w36hm-eqaaa-aaaal-qr76a-cai";
+/// Dimensions of the mock provider's embeddings
+pub const MOCK_EMBEDDING_DIMENSIONS: usize = 8;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn user(content: &str) -> Self {
+        Self { role: "user".to_string(), content: content.to_string() }
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        Self { role: "assistant".to_string(), content: content.to_string() }
+    }
+}
+
+/// Backend used when a computation does not name a provider
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum LlmBackend {
+    IcLlmCanister,
+    Https(String),
+    Mock,
+}
+
+pub trait LlmProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String, String> {
+        self.chat(&[ChatMessage::user(prompt)]).await
+    }
+
+    async fn chat(&self, messages: &[ChatMessage]) -> Result<String, String>;
+
+    async fn embeddings(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String>;
+}
+
+thread_local! {
+    static BACKEND: RefCell<LlmBackend> = const { RefCell::new(LlmBackend::IcLlmCanister) };
+}
+
+pub fn set_backend(backend: LlmBackend) -> Result<(), String> {
+    if let LlmBackend::Https(name) = &backend {
+        if !super::http_outcall::has_provider(name) {
+            return Err(format!("LLM provider {} not found", name));
+        }
+    }
+    BACKEND.with(|b| *b.borrow_mut() = backend);
+    Ok(())
+}
+
+pub fn backend() -> LlmBackend {
+    BACKEND.with(|b| b.borrow().clone())
+}
+
+/// The IC LLM canister, unless the configuration names another
+pub fn llm_canister() -> Result<Principal, String> {
+    match crate::config::settings().llm_canister_id {
+        Some(canister_id) => Ok(canister_id),
+        None => Principal::from_text(IC_LLM_CANISTER).map_err(|_| "Invalid LLM canister ID".to_string()),
+    }
+}
+
+/// The IC LLM canister; it has no chat history or embeddings of its own
+pub struct IcLlmProvider;
+
+impl LlmProvider for IcLlmProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String, String> {
+        let llm_canister_id = llm_canister()?;
+        let (response,): (String,) = ic_cdk::call(llm_canister_id, "prompt", (prompt.to_string(),)).await
+            .map_err(|(code, msg)| format!("LLM call failed: {:?} - {}", code, msg))?;
+        Ok(response)
+    }
+
+    async fn chat(&self, messages: &[ChatMessage]) -> Result<String, String> {
+        self.prompt(&flatten(messages)).await
+    }
+
+    async fn embeddings(&self, _inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        Err("The IC LLM canister does not provide embeddings".to_string())
+    }
+}
+
+/// Render a conversation as a single prompt for backends without chat support
+pub fn flatten(messages: &[ChatMessage]) -> String {
+    messages.iter()
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Deterministic stand-in that answers from a digest of its input
+pub struct MockProvider;
+
+impl MockProvider {
+    pub fn reply(messages: &[ChatMessage]) -> String {
+        let last = messages.last().map(|m| m.content.as_str()).unwrap_or_default();
+        let digest = Sha256::digest(flatten(messages).as_bytes());
+        format!("[mock:{}] {}", hex::encode(&digest[..4]), last.chars().take(80).collect::<String>())
+    }
+
+    pub fn embedding(input: &str) -> Vec<f32> {
+        let digest = Sha256::digest(input.as_bytes());
+        digest.iter()
+            .take(MOCK_EMBEDDING_DIMENSIONS)
+            .map(|b| f32::from(*b) / 255.0)
+            .collect()
+    }
+}
+
+impl LlmProvider for MockProvider {
+    async fn chat(&self, messages: &[ChatMessage]) -> Result<String, String> {
+        Ok(Self::reply(messages))
+    }
+
+    async fn embeddings(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        Ok(inputs.iter().map(|input| Self::embedding(input)).collect())
+    }
+}
+
+/// Who an LLM call runs for: usage is attributed to `principal` and
+/// `computation_id`, and `provider` names an HTTPS provider to use instead
+/// of the configured backend
+#[derive(Clone, Debug)]
+pub struct CallContext {
+    pub computation_id: String,
+    pub principal: Principal,
+    pub provider: Option<String>,
+}
+
+pub enum Backend {
+    IcLlm(IcLlmProvider),
+    Https(HttpsProvider),
+    Mock(MockProvider),
+}
+
+/// A backend bound to a call context. Calls are checked against the
+/// principal's budget, run under the retry policy and circuit breaker, and
+/// recorded in the usage statistics.
+pub struct Provider {
+    backend: Backend,
+    principal: Principal,
+    computation_id: String,
+}
+
+impl Provider {
+    fn account<T>(&self, prompt_bytes: usize, result: &Result<T, String>, response_bytes: impl Fn(&T) -> usize) {
+        let response_bytes = result.as_ref().map(response_bytes).unwrap_or(0);
+        usage::record(self.principal, &self.computation_id, prompt_bytes as u64, response_bytes as u64, result.is_ok());
+    }
+}
+
+impl LlmProvider for Provider {
+    async fn prompt(&self, prompt: &str) -> Result<String, String> {
+        usage::check_budget(self.principal)?;
+        let result = resilience::run(|| async move {
+            match &self.backend {
+                Backend::IcLlm(p) => p.prompt(prompt).await,
+                Backend::Https(p) => p.prompt(prompt).await,
+                Backend::Mock(p) => p.prompt(prompt).await,
+            }
+        }).await;
+        self.account(prompt.len(), &result, String::len);
+        result
+    }
+
+    async fn chat(&self, messages: &[ChatMessage]) -> Result<String, String> {
+        usage::check_budget(self.principal)?;
+        let result = resilience::run(|| async move {
+            match &self.backend {
+                Backend::IcLlm(p) => p.chat(messages).await,
+                Backend::Https(p) => p.chat(messages).await,
+                Backend::Mock(p) => p.chat(messages).await,
+            }
+        }).await;
+        self.account(messages.iter().map(|m| m.content.len()).sum(), &result, String::len);
+        result
+    }
+
+    async fn embeddings(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        // A backend without embeddings is a configuration problem, not an outage
+        let supported = match &self.backend {
+            Backend::IcLlm(_) => false,
+            Backend::Https(p) => p.supports_embeddings(),
+            Backend::Mock(_) => true,
+        };
+        if !supported {
+            return Err("The configured LLM backend does not provide embeddings".to_string());
+        }
+        usage::check_budget(self.principal)?;
+        let result = resilience::run(|| async move {
+            match &self.backend {
+                Backend::IcLlm(p) => p.embeddings(inputs).await,
+                Backend::Https(p) => p.embeddings(inputs).await,
+                Backend::Mock(p) => p.embeddings(inputs).await,
+            }
+        }).await;
+        let vector_bytes = |embeddings: &Vec<Vec<f32>>| embeddings.iter().map(|e| e.len() * 4).sum();
+        self.account(inputs.iter().map(String::len).sum(), &result, vector_bytes);
+        result
+    }
+}
+
+/// The provider for a call: the context's HTTPS provider if it names one,
+/// the configured backend otherwise. `request_key` identifies the logical
+/// request so HTTPS providers can deduplicate it.
+pub fn resolve(context: &CallContext, request_key: &str) -> Result<Provider, String> {
+    let backend = match &context.provider {
+        Some(name) => LlmBackend::Https(name.clone()),
+        None => backend(),
+    };
+    let backend = match backend {
+        LlmBackend::IcLlmCanister => Backend::IcLlm(IcLlmProvider),
+        LlmBackend::Https(name) => Backend::Https(HttpsProvider::new(&name, request_key)?),
+        LlmBackend::Mock => Backend::Mock(MockProvider),
+    };
+    Ok(Provider {
+        backend,
+        principal: context.principal,
+        computation_id: context.computation_id.clone(),
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&BACKEND))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let backend = backup::decode(bytes)?;
+    backup::replace(&BACKEND, backend);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "provider_test.rs"]
+mod tests;
@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn mock_replies_are_deterministic() {
+    let conversation = [ChatMessage::user("count the cohort"), ChatMessage::assistant("42"), ChatMessage::user("again")];
+    assert_eq!(MockProvider::reply(&conversation), MockProvider::reply(&conversation));
+    assert_ne!(MockProvider::reply(&conversation), MockProvider::reply(&conversation[..1]));
+    assert!(MockProvider::reply(&conversation).ends_with("again"));
+}
+
+#[test]
+fn mock_embeddings_are_stable_and_bounded() {
+    let embedding = MockProvider::embedding("patient outcomes");
+    assert_eq!(embedding.len(), MOCK_EMBEDDING_DIMENSIONS);
+    assert_eq!(embedding, MockProvider::embedding("patient outcomes"));
+    assert!(embedding.iter().all(|v| (0.0..=1.0).contains(v)));
+}
+
+#[test]
+fn conversations_flatten_in_order() {
+    let flat = flatten(&[ChatMessage::user("a"), ChatMessage::assistant("b")]);
+    assert_eq!(flat, "user: a\n\nassistant: b");
+}
+
+#[test]
+fn unknown_https_backends_are_rejected() {
+    assert!(set_backend(LlmBackend::Https("missing".to_string())).is_err());
+    set_backend(LlmBackend::Mock).unwrap();
+    assert_eq!(backend(), LlmBackend::Mock);
+    let context = CallContext { computation_id: "req_1".to_string(), principal: Principal::anonymous(), provider: None };
+    assert!(matches!(resolve(&context, "req_1:1"), Ok(Provider { backend: Backend::Mock(_), .. })));
+    let missing = CallContext { provider: Some("missing".to_string()), ..context };
+    assert!(resolve(&missing, "req_1:1").is_err());
+}
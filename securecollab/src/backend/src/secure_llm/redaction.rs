@@ -0,0 +1,185 @@
+//! PII redaction for LLM prompts
+//!
+//! Dataset content is stripped of direct identifiers before it is placed in a
+//! prompt. Sensitive CSV columns are recognised by their header, free text by
+//! regex rules and a dictionary of common first names. Each distinct value is
+//! replaced by a stable token such as `[EMAIL_1]`, so the same person keeps
+//! the same token across every dataset in one computation.
+
+use candid::{CandidType, Deserialize};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PiiKind {
+    Name,
+    Email,
+    Phone,
+    MedicalRecordNumber,
+    DateOfBirth,
+    NationalId,
+}
+
+impl PiiKind {
+    fn label(self) -> &'static str {
+        match self {
+            PiiKind::Name => "NAME",
+            PiiKind::Email => "EMAIL",
+            PiiKind::Phone => "PHONE",
+            PiiKind::MedicalRecordNumber => "MRN",
+            PiiKind::DateOfBirth => "DOB",
+            PiiKind::NationalId => "NATIONAL_ID",
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RedactionCount {
+    pub kind: PiiKind,
+    /// Distinct values replaced
+    pub distinct: u32,
+    pub occurrences: u32,
+}
+
+// CSV headers whose column holds an identifier
+const SENSITIVE_COLUMNS: &[(&str, PiiKind)] = &[
+    ("name", PiiKind::Name),
+    ("patient_name", PiiKind::Name),
+    ("full_name", PiiKind::Name),
+    ("first_name", PiiKind::Name),
+    ("last_name", PiiKind::Name),
+    ("surname", PiiKind::Name),
+    ("email", PiiKind::Email),
+    ("phone", PiiKind::Phone),
+    ("mrn", PiiKind::MedicalRecordNumber),
+    ("medical_record_number", PiiKind::MedicalRecordNumber),
+    ("dob", PiiKind::DateOfBirth),
+    ("date_of_birth", PiiKind::DateOfBirth),
+    ("birth_date", PiiKind::DateOfBirth),
+    ("ssn", PiiKind::NationalId),
+];
+
+const FIRST_NAMES: &[&str] = &[
+    "James", "John", "Robert", "Michael", "William", "David", "Richard", "Joseph", "Thomas", "Charles",
+    "Mary", "Patricia", "Jennifer", "Linda", "Elizabeth", "Barbara", "Susan", "Jessica", "Sarah", "Karen",
+    "Daniel", "Matthew", "Anthony", "Mark", "Paul", "Nancy", "Lisa", "Emily", "Maria", "Anna",
+];
+
+// (kind, pattern, capture group holding the identifier)
+const RULES: &[(PiiKind, &str, usize)] = &[
+    (PiiKind::Email, r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b", 0),
+    (PiiKind::MedicalRecordNumber, r"(?i)\b(?:mrn|medical\s+record(?:\s+(?:number|no\.?))?)\s*[:#]?\s*([a-z]{0,3}\d{5,12})\b", 1),
+    (PiiKind::DateOfBirth, r"(?i)\b(?:dob|d\.o\.b\.|date\s+of\s+birth|born(?:\s+on)?)\s*[:=]?\s*(\d{4}-\d{2}-\d{2}|\d{1,2}/\d{1,2}/\d{2,4})", 1),
+    (PiiKind::NationalId, r"\b\d{3}-\d{2}-\d{4}\b", 0),
+    (PiiKind::Phone, r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)|\b\d{3})[\s.-]\d{3}[\s.-]\d{4}\b", 0),
+    (PiiKind::Name, r"(?i)\b(?:patient(?:\s+name)?|name)\s*[:=]\s*([A-Z][a-z'-]+(?:\s+[A-Z][a-z'-]+){0,2})", 1),
+    (PiiKind::Name, r"\b(?:Mr|Mrs|Ms|Miss|Dr)\.?\s+([A-Z][a-z'-]+(?:\s+[A-Z][a-z'-]+)?)", 1),
+];
+
+thread_local! {
+    static COMPILED: Vec<(PiiKind, Regex, usize)> = {
+        let mut rules: Vec<(PiiKind, Regex, usize)> = RULES.iter()
+            .map(|(kind, pattern, group)| (*kind, Regex::new(pattern).expect("redaction rules are valid regexes"), *group))
+            .collect();
+        let dictionary = format!(r"\b(?:{})\s+[A-Z][a-z'-]+\b", FIRST_NAMES.join("|"));
+        rules.push((PiiKind::Name, Regex::new(&dictionary).expect("name dictionary is a valid regex"), 0));
+        rules
+    };
+}
+
+/// Replaces identifiers with tokens; one redactor per computation keeps tokens consistent across datasets
+#[derive(Default)]
+pub struct Redactor {
+    tokens: HashMap<(PiiKind, String), String>,
+    distinct: HashMap<PiiKind, u32>,
+    occurrences: HashMap<PiiKind, u32>,
+}
+
+impl Redactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn token(&mut self, kind: PiiKind, value: &str) -> String {
+        *self.occurrences.entry(kind).or_default() += 1;
+        let key = (kind, value.trim().to_lowercase());
+        if let Some(token) = self.tokens.get(&key) {
+            return token.clone();
+        }
+        let n = self.distinct.entry(kind).or_default();
+        *n += 1;
+        let token = format!("[{}_{}]", kind.label(), n);
+        self.tokens.insert(key, token.clone());
+        token
+    }
+
+    // Tokenise whole columns whose header names an identifier
+    fn redact_columns(&mut self, text: &str) -> String {
+        let mut lines = text.lines();
+        let Some(header) = lines.next() else {
+            return String::new();
+        };
+        let columns: Vec<Option<PiiKind>> = header.split(',')
+            .map(|column| {
+                let column = column.trim().trim_matches('"').to_lowercase().replace([' ', '-'], "_");
+                SENSITIVE_COLUMNS.iter().find(|(name, _)| *name == column).map(|(_, kind)| *kind)
+            })
+            .collect();
+        if !header.contains(',') || columns.iter().all(Option::is_none) {
+            return text.to_string();
+        }
+
+        let mut out = vec![header.to_string()];
+        for line in lines {
+            let cells: Vec<String> = line.split(',').enumerate()
+                .map(|(i, cell)| match columns.get(i).copied().flatten() {
+                    Some(kind) if !cell.trim().is_empty() => self.token(kind, cell),
+                    _ => cell.to_string(),
+                })
+                .collect();
+            out.push(cells.join(","));
+        }
+        out.join("\n")
+    }
+
+    pub fn redact(&mut self, text: &str) -> String {
+        let columns_redacted = self.redact_columns(text);
+        COMPILED.with(|rules| {
+            rules.iter().fold(columns_redacted, |text, (kind, regex, group)| {
+                regex.replace_all(&text, |caps: &Captures| {
+                    let whole = caps.get(0).expect("match has a whole group");
+                    let Some(value) = caps.get(*group) else {
+                        return whole.as_str().to_string();
+                    };
+                    let prefix = &whole.as_str()[..value.start() - whole.start()];
+                    let suffix = &whole.as_str()[value.end() - whole.start()..];
+                    format!("{}{}{}", prefix, self.token(*kind, value.as_str()), suffix)
+                }).into_owned()
+            })
+        })
+    }
+
+    pub fn summary(&self) -> Vec<RedactionCount> {
+        let mut counts: Vec<RedactionCount> = self.occurrences.iter()
+            .map(|(kind, occurrences)| RedactionCount {
+                kind: *kind,
+                distinct: self.distinct.get(kind).copied().unwrap_or_default(),
+                occurrences: *occurrences,
+            })
+            .collect();
+        counts.sort_by_key(|c| c.kind);
+        counts
+    }
+}
+
+/// Human-readable summary of what was redacted
+pub fn describe(counts: &[RedactionCount]) -> String {
+    counts.iter()
+        .map(|c| format!("{} {} ({} distinct)", c.occurrences, c.kind.label(), c.distinct))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+#[path = "redaction_test.rs"]
+mod tests;
@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn redacts_identifiers_in_free_text() {
+    let mut redactor = Redactor::new();
+    let text = "Patient: Alice Moreau, MRN: 00123456, DOB: 1961-04-02, email alice@example.org, \
+        call (617) 555-0142, SSN 123-45-6789. Seen by Dr. Okafor with Mary Jones.";
+    let redacted = redactor.redact(text);
+    assert_eq!(
+        redacted,
+        "Patient: [NAME_1], MRN: [MRN_1], DOB: [DOB_1], email [EMAIL_1], \
+        call [PHONE_1], SSN [NATIONAL_ID_1]. Seen by Dr. [NAME_2] with [NAME_3]."
+    );
+}
+
+#[test]
+fn redacts_sensitive_csv_columns_and_keeps_the_rest() {
+    let mut redactor = Redactor::new();
+    let csv = "patient_name,age,Date of Birth,outcome\nAlice Moreau,63,1961-04-02,improved\nBo Chen,48,1976-11-30,unchanged";
+    assert_eq!(
+        redactor.redact(csv),
+        "patient_name,age,Date of Birth,outcome\n[NAME_1],63,[DOB_1],improved\n[NAME_2],48,[DOB_2],unchanged"
+    );
+}
+
+#[test]
+fn tokens_are_stable_across_datasets() {
+    let mut redactor = Redactor::new();
+    let first = redactor.redact("email,score\nbo@example.org,3");
+    let second = redactor.redact("Follow-up for BO@example.org and ann@example.org");
+    assert_eq!(first, "email,score\n[EMAIL_1],3");
+    assert_eq!(second, "Follow-up for [EMAIL_1] and [EMAIL_2]");
+    assert_eq!(redactor.summary(), vec![RedactionCount { kind: PiiKind::Email, distinct: 2, occurrences: 3 }]);
+}
+
+#[test]
+fn aggregate_text_is_left_alone() {
+    let mut redactor = Redactor::new();
+    let text = "Average age 63.4, 120 patients, recovery improved in 2023-Q4";
+    assert_eq!(redactor.redact(text), text);
+    assert!(redactor.summary().is_empty());
+}
@@ -0,0 +1,215 @@
+//! Retries, timeouts and a circuit breaker for LLM calls
+//!
+//! Every provider call is retried with exponential backoff and abandoned if
+//! it takes longer than the configured timeout. Consecutive failures open the
+//! circuit: further calls fail fast until a cooldown passes, after which a
+//! single trial call decides whether it closes again. Callers that find the
+//! circuit open defer their work instead of failing it.
+
+use candid::{CandidType, Deserialize};
+use futures::future::{self, Either};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
+use crate::backup;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub timeout_ms: u64,
+    /// Consecutive failed attempts that open the circuit
+    pub failure_threshold: u32,
+    pub cooldown_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 500,
+            max_delay_ms: 8_000,
+            timeout_ms: 60_000,
+            failure_threshold: 5,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LlmHealth {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<u64>,
+    pub retry_after: Option<u64>,
+    pub last_error: Option<String>,
+    pub policy: RetryPolicy,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Breaker {
+    pub consecutive_failures: u32,
+    pub opened_at: Option<u64>,
+    pub trial_in_flight: bool,
+    pub last_error: Option<String>,
+}
+
+impl Breaker {
+    pub fn state(&self, now: u64, policy: &RetryPolicy) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if now < opened_at.saturating_add(policy.cooldown_secs * NANOS_PER_SECOND) => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Let a call through unless the circuit is open; a half-open circuit admits one trial
+    pub fn allow(&mut self, now: u64, policy: &RetryPolicy) -> Result<(), String> {
+        match self.state(now, policy) {
+            CircuitState::Closed => Ok(()),
+            CircuitState::HalfOpen if !self.trial_in_flight => {
+                self.trial_in_flight = true;
+                Ok(())
+            }
+            _ => Err(format!(
+                "LLM backend unavailable after {} consecutive failures; calls are deferred until it recovers",
+                self.consecutive_failures
+            )),
+        }
+    }
+
+    pub fn on_success(&mut self) {
+        *self = Breaker::default();
+    }
+
+    pub fn on_failure(&mut self, error: &str, now: u64, policy: &RetryPolicy) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.to_string());
+        // A failed trial re-opens the circuit for another cooldown
+        if self.trial_in_flight || self.consecutive_failures >= policy.failure_threshold {
+            self.opened_at = Some(now);
+            self.trial_in_flight = false;
+        }
+    }
+}
+
+thread_local! {
+    static POLICY: RefCell<RetryPolicy> = RefCell::new(RetryPolicy::default());
+    static BREAKER: RefCell<Breaker> = RefCell::new(Breaker::default());
+}
+
+pub fn set_policy(policy: RetryPolicy) -> Result<(), String> {
+    if policy.timeout_ms == 0 || policy.failure_threshold == 0 {
+        return Err("Timeout and failure threshold must be positive".to_string());
+    }
+    if policy.base_delay_ms > policy.max_delay_ms {
+        return Err("Base delay cannot exceed the maximum delay".to_string());
+    }
+    POLICY.with(|p| *p.borrow_mut() = policy);
+    Ok(())
+}
+
+pub fn policy() -> RetryPolicy {
+    POLICY.with(|p| p.borrow().clone())
+}
+
+/// Delay before retry number `retry` (1-based)
+pub fn backoff_delay(policy: &RetryPolicy, retry: u32) -> Duration {
+    let factor = 1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX);
+    Duration::from_millis(policy.base_delay_ms.saturating_mul(factor).min(policy.max_delay_ms))
+}
+
+pub fn is_open(now: u64) -> bool {
+    let policy = policy();
+    BREAKER.with(|b| b.borrow().state(now, &policy) == CircuitState::Open)
+}
+
+pub fn health(now: u64) -> LlmHealth {
+    let policy = policy();
+    BREAKER.with(|b| {
+        let breaker = b.borrow();
+        LlmHealth {
+            state: breaker.state(now, &policy),
+            consecutive_failures: breaker.consecutive_failures,
+            opened_at: breaker.opened_at,
+            retry_after: breaker.opened_at.map(|t| t.saturating_add(policy.cooldown_secs * NANOS_PER_SECOND)),
+            last_error: breaker.last_error.clone(),
+            policy: policy.clone(),
+        }
+    })
+}
+
+/// Close the circuit by hand, e.g. after the backend was fixed
+pub fn reset() {
+    BREAKER.with(|b| b.borrow_mut().on_success());
+}
+
+async fn sleep(delay: Duration) {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    ic_cdk_timers::set_timer(delay, move || {
+        let _ = tx.send(());
+    });
+    let _ = rx.await;
+}
+
+// The call itself cannot be cancelled; a timed-out response is ignored
+async fn with_timeout<T>(call: impl Future<Output = Result<T, String>>, timeout: Duration) -> Result<T, String> {
+    match future::select(pin!(call), pin!(sleep(timeout))).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(format!("LLM call timed out after {} ms", timeout.as_millis())),
+    }
+}
+
+/// Run an LLM call under the retry policy and circuit breaker
+pub async fn run<T, F, Fut>(mut call: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let policy = policy();
+    let mut last_error = String::new();
+    for attempt in 0..=policy.max_retries {
+        if attempt > 0 {
+            sleep(backoff_delay(&policy, attempt)).await;
+        }
+        BREAKER.with(|b| b.borrow_mut().allow(ic_cdk::api::time(), &policy))?;
+
+        match with_timeout(call(), Duration::from_millis(policy.timeout_ms)).await {
+            Ok(value) => {
+                BREAKER.with(|b| b.borrow_mut().on_success());
+                return Ok(value);
+            }
+            Err(e) => {
+                BREAKER.with(|b| b.borrow_mut().on_failure(&e, ic_cdk::api::time(), &policy));
+                last_error = e;
+            }
+        }
+    }
+    Err(format!("LLM call failed after {} attempts: {}", policy.max_retries + 1, last_error))
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&POLICY))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let policy = backup::decode(bytes)?;
+    backup::replace(&POLICY, policy);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "resilience_test.rs"]
+mod tests;
@@ -0,0 +1,63 @@
+use super::*;
+
+fn policy() -> RetryPolicy {
+    RetryPolicy { failure_threshold: 3, cooldown_secs: 10, ..RetryPolicy::default() }
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy { base_delay_ms: 500, max_delay_ms: 3_000, ..RetryPolicy::default() };
+    assert_eq!(backoff_delay(&policy, 1), Duration::from_millis(500));
+    assert_eq!(backoff_delay(&policy, 2), Duration::from_millis(1_000));
+    assert_eq!(backoff_delay(&policy, 3), Duration::from_millis(2_000));
+    assert_eq!(backoff_delay(&policy, 4), Duration::from_millis(3_000));
+    assert_eq!(backoff_delay(&policy, 80), Duration::from_millis(3_000));
+}
+
+#[test]
+fn consecutive_failures_open_the_circuit() {
+    let policy = policy();
+    let mut breaker = Breaker::default();
+    for _ in 0..2 {
+        breaker.on_failure("boom", 0, &policy);
+    }
+    assert_eq!(breaker.state(0, &policy), CircuitState::Closed);
+    breaker.on_success();
+    assert_eq!(breaker.consecutive_failures, 0);
+
+    for _ in 0..3 {
+        breaker.on_failure("boom", 5, &policy);
+    }
+    assert_eq!(breaker.state(5, &policy), CircuitState::Open);
+    assert!(breaker.allow(6, &policy).is_err());
+}
+
+#[test]
+fn half_open_circuit_admits_a_single_trial() {
+    let policy = policy();
+    let after_cooldown = 11 * NANOS_PER_SECOND;
+    let mut breaker = Breaker::default();
+    for _ in 0..3 {
+        breaker.on_failure("boom", 0, &policy);
+    }
+
+    assert_eq!(breaker.state(after_cooldown, &policy), CircuitState::HalfOpen);
+    breaker.allow(after_cooldown, &policy).unwrap();
+    assert!(breaker.allow(after_cooldown, &policy).is_err());
+
+    // A failed trial re-opens it for another cooldown
+    breaker.on_failure("still down", after_cooldown, &policy);
+    assert_eq!(breaker.state(after_cooldown + 1, &policy), CircuitState::Open);
+
+    let later = after_cooldown + 11 * NANOS_PER_SECOND;
+    breaker.allow(later, &policy).unwrap();
+    breaker.on_success();
+    assert_eq!(breaker.state(later, &policy), CircuitState::Closed);
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    assert!(set_policy(RetryPolicy { timeout_ms: 0, ..RetryPolicy::default() }).is_err());
+    assert!(set_policy(RetryPolicy { base_delay_ms: 10, max_delay_ms: 5, ..RetryPolicy::default() }).is_err());
+    set_policy(RetryPolicy { max_retries: 0, ..RetryPolicy::default() }).unwrap();
+}
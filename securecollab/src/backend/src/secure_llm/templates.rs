@@ -0,0 +1,326 @@
+//! Versioned prompt templates
+//!
+//! A template is a prompt body with `{{name}}` placeholders for typed
+//! variables. Every edit creates a new version that an admin has to approve
+//! before computations can use it; earlier approved versions stay usable so
+//! computations pinned to them keep rendering the prompt their voters saw.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::backup;
+
+pub const MAX_TEMPLATE_BYTES: usize = 16 * 1024;
+pub const MAX_TEMPLATE_VARIABLES: usize = 32;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum VariableType {
+    Text,
+    Integer,
+    Number,
+    Boolean,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub var_type: VariableType,
+    pub required: bool,
+    pub description: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TemplateStatus {
+    PendingApproval,
+    Approved,
+    Rejected,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub version: u32,
+    pub computation_type: String,
+    pub body: String,
+    pub variables: Vec<TemplateVariable>,
+    pub status: TemplateStatus,
+    pub author: Principal,
+    pub created_at: u64,
+    pub reviewed_by: Option<Principal>,
+    pub reviewed_at: Option<u64>,
+    pub review_note: Option<String>,
+}
+
+/// An approved template version chosen for a computation, with the caller-supplied values
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TemplateBinding {
+    pub template_id: String,
+    pub version: u32,
+    pub values: Vec<(String, String)>,
+}
+
+thread_local! {
+    // Versions of each template, oldest first
+    static TEMPLATES: RefCell<HashMap<String, Vec<PromptTemplate>>> = RefCell::new(HashMap::new());
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Placeholder names in `body`, in order of appearance
+pub fn placeholders(body: &str) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}")
+            .ok_or_else(|| "Template has an unterminated {{ placeholder".to_string())?;
+        let name = after[..end].trim();
+        if !valid_name(name) {
+            return Err(format!("Invalid placeholder name '{}'", name));
+        }
+        names.push(name.to_string());
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+/// Every placeholder must be declared and every declared variable used
+pub fn validate(body: &str, variables: &[TemplateVariable]) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("Template body cannot be empty".to_string());
+    }
+    if body.len() > MAX_TEMPLATE_BYTES {
+        return Err(format!("Template body exceeds {} bytes", MAX_TEMPLATE_BYTES));
+    }
+    if variables.len() > MAX_TEMPLATE_VARIABLES {
+        return Err(format!("Templates can declare at most {} variables", MAX_TEMPLATE_VARIABLES));
+    }
+    for (i, variable) in variables.iter().enumerate() {
+        if !valid_name(&variable.name) {
+            return Err(format!("Invalid variable name '{}'", variable.name));
+        }
+        if variables[..i].iter().any(|v| v.name == variable.name) {
+            return Err(format!("Variable {} is declared twice", variable.name));
+        }
+    }
+    let used = placeholders(body)?;
+    if let Some(name) = used.iter().find(|name| !variables.iter().any(|v| &v.name == *name)) {
+        return Err(format!("Placeholder {} is not a declared variable", name));
+    }
+    if let Some(variable) = variables.iter().find(|v| !used.contains(&v.name)) {
+        return Err(format!("Variable {} is never used in the template", variable.name));
+    }
+    Ok(())
+}
+
+pub fn check_value(variable: &TemplateVariable, value: &str) -> Result<(), String> {
+    let ok = match variable.var_type {
+        VariableType::Text => true,
+        VariableType::Integer => value.trim().parse::<i64>().is_ok(),
+        VariableType::Number => value.trim().parse::<f64>().is_ok_and(f64::is_finite),
+        VariableType::Boolean => matches!(value.trim(), "true" | "false"),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("Value for {} must be a {:?}", variable.name, variable.var_type))
+    }
+}
+
+/// Fill in the template. `system` values are supplied by the canister and
+/// cannot be overridden by the caller's `values`; missing optional variables
+/// render empty.
+pub fn render(template: &PromptTemplate, values: &[(String, String)], system: &[(&str, String)]) -> Result<String, String> {
+    if let Some((name, _)) = values.iter().find(|(name, _)| system.iter().any(|(s, _)| s == name)) {
+        return Err(format!("Variable {} is supplied by the system", name));
+    }
+    if let Some((name, _)) = values.iter().find(|(name, _)| !template.variables.iter().any(|v| &v.name == name)) {
+        return Err(format!("Template {} has no variable {}", template.id, name));
+    }
+
+    let mut resolved = HashMap::new();
+    for variable in &template.variables {
+        let value = system.iter().find(|(name, _)| *name == variable.name).map(|(_, v)| v.as_str())
+            .or_else(|| values.iter().find(|(name, _)| *name == variable.name).map(|(_, v)| v.as_str()));
+        match value {
+            Some(value) => {
+                check_value(variable, value)?;
+                resolved.insert(variable.name.as_str(), value);
+            }
+            None if variable.required => return Err(format!("Missing value for {}", variable.name)),
+            None => {
+                resolved.insert(variable.name.as_str(), "");
+            }
+        }
+    }
+
+    let mut rendered = String::with_capacity(template.body.len());
+    let mut rest = template.body.as_str();
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| "Template has an unterminated {{ placeholder".to_string())?;
+        rendered.push_str(resolved.get(after[..end].trim()).copied().unwrap_or_default());
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+pub fn create(
+    id: &str,
+    computation_type: &str,
+    body: String,
+    variables: Vec<TemplateVariable>,
+    author: Principal,
+    now: u64,
+) -> Result<PromptTemplate, String> {
+    if !valid_name(id) {
+        return Err("Template id may only contain letters, digits, '_' and '-'".to_string());
+    }
+    if computation_type.trim().is_empty() {
+        return Err("Computation type cannot be empty".to_string());
+    }
+    validate(&body, &variables)?;
+    TEMPLATES.with(|templates| {
+        let mut templates = templates.borrow_mut();
+        if templates.contains_key(id) {
+            return Err(format!("Prompt template {} already exists", id));
+        }
+        let template = PromptTemplate {
+            id: id.to_string(),
+            version: 1,
+            computation_type: computation_type.to_string(),
+            body,
+            variables,
+            status: TemplateStatus::PendingApproval,
+            author,
+            created_at: now,
+            reviewed_by: None,
+            reviewed_at: None,
+            review_note: None,
+        };
+        templates.insert(id.to_string(), vec![template.clone()]);
+        Ok(template)
+    })
+}
+
+/// Add a new version awaiting approval; the current approved version stays active meanwhile
+pub fn revise(
+    id: &str,
+    body: String,
+    variables: Vec<TemplateVariable>,
+    author: Principal,
+    now: u64,
+) -> Result<PromptTemplate, String> {
+    validate(&body, &variables)?;
+    TEMPLATES.with(|templates| {
+        let mut templates = templates.borrow_mut();
+        let versions = templates.get_mut(id).ok_or_else(|| format!("Prompt template {} not found", id))?;
+        let latest = versions.last().expect("templates have at least one version");
+        let template = PromptTemplate {
+            id: id.to_string(),
+            version: latest.version + 1,
+            computation_type: latest.computation_type.clone(),
+            body,
+            variables,
+            status: TemplateStatus::PendingApproval,
+            author,
+            created_at: now,
+            reviewed_by: None,
+            reviewed_at: None,
+            review_note: None,
+        };
+        versions.push(template.clone());
+        Ok(template)
+    })
+}
+
+pub fn review(
+    id: &str,
+    version: u32,
+    approve: bool,
+    reviewer: Principal,
+    note: Option<String>,
+    now: u64,
+) -> Result<PromptTemplate, String> {
+    TEMPLATES.with(|templates| {
+        let mut templates = templates.borrow_mut();
+        let template = templates.get_mut(id)
+            .and_then(|versions| versions.iter_mut().find(|t| t.version == version))
+            .ok_or_else(|| format!("Prompt template {} v{} not found", id, version))?;
+        if template.status != TemplateStatus::PendingApproval {
+            return Err(format!("Prompt template {} v{} was already reviewed", id, version));
+        }
+        template.status = if approve { TemplateStatus::Approved } else { TemplateStatus::Rejected };
+        template.reviewed_by = Some(reviewer);
+        template.reviewed_at = Some(now);
+        template.review_note = note;
+        Ok(template.clone())
+    })
+}
+
+pub fn delete(id: &str) -> Result<(), String> {
+    TEMPLATES.with(|templates| templates.borrow_mut().remove(id))
+        .map(|_| ())
+        .ok_or_else(|| format!("Prompt template {} not found", id))
+}
+
+/// A specific version, or the latest one
+pub fn get(id: &str, version: Option<u32>) -> Option<PromptTemplate> {
+    TEMPLATES.with(|templates| {
+        let templates = templates.borrow();
+        let versions = templates.get(id)?;
+        match version {
+            Some(version) => versions.iter().find(|t| t.version == version).cloned(),
+            None => versions.last().cloned(),
+        }
+    })
+}
+
+/// An approved version usable for computations; the latest approved one if `version` is None
+pub fn approved(id: &str, version: Option<u32>) -> Result<PromptTemplate, String> {
+    let versions = TEMPLATES.with(|templates| templates.borrow().get(id).cloned())
+        .ok_or_else(|| format!("Prompt template {} not found", id))?;
+    versions.into_iter().rev()
+        .filter(|t| t.status == TemplateStatus::Approved)
+        .find(|t| version.is_none_or(|v| t.version == v))
+        .ok_or_else(|| match version {
+            Some(v) => format!("Prompt template {} v{} is not approved", id, v),
+            None => format!("Prompt template {} has no approved version", id),
+        })
+}
+
+/// The most recently approved template for a computation type
+pub fn active_for(computation_type: &str) -> Option<PromptTemplate> {
+    TEMPLATES.with(|templates| {
+        templates.borrow().values()
+            .flatten()
+            .filter(|t| t.computation_type == computation_type && t.status == TemplateStatus::Approved)
+            .max_by_key(|t| (t.reviewed_at, t.version))
+            .cloned()
+    })
+}
+
+/// All versions of all templates, grouped by id
+pub fn list() -> Vec<PromptTemplate> {
+    let mut all: Vec<PromptTemplate> = TEMPLATES.with(|templates| templates.borrow().values().flatten().cloned().collect());
+    all.sort_by(|a, b| a.id.cmp(&b.id).then(a.version.cmp(&b.version)));
+    all
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&TEMPLATES))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let templates = backup::decode(bytes)?;
+    backup::replace(&TEMPLATES, templates);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "templates_test.rs"]
+mod tests;
@@ -0,0 +1,75 @@
+use super::*;
+
+fn variable(name: &str, var_type: VariableType, required: bool) -> TemplateVariable {
+    TemplateVariable { name: name.to_string(), var_type, required, description: String::new() }
+}
+
+fn cohort_template() -> PromptTemplate {
+    PromptTemplate {
+        id: "cohort".to_string(),
+        version: 1,
+        computation_type: "cohort_analysis".to_string(),
+        body: "Summarise {{ data }} for patients older than {{min_age}}.{{note}}".to_string(),
+        variables: vec![
+            variable("data", VariableType::Text, true),
+            variable("min_age", VariableType::Integer, true),
+            variable("note", VariableType::Text, false),
+        ],
+        status: TemplateStatus::Approved,
+        author: Principal::anonymous(),
+        created_at: 0,
+        reviewed_by: None,
+        reviewed_at: None,
+        review_note: None,
+    }
+}
+
+#[test]
+fn placeholders_must_match_declared_variables() {
+    let template = cohort_template();
+    assert_eq!(placeholders(&template.body).unwrap(), vec!["data", "min_age", "note"]);
+    validate(&template.body, &template.variables).unwrap();
+
+    assert!(validate("Hello {{name}}", &[]).is_err());
+    assert!(validate("Hello", &[variable("name", VariableType::Text, true)]).is_err());
+    assert!(validate("Hello {{name", &[variable("name", VariableType::Text, true)]).is_err());
+    assert!(validate("{{a}}", &[variable("a", VariableType::Text, true), variable("a", VariableType::Text, true)]).is_err());
+}
+
+#[test]
+fn renders_typed_values_with_system_variables() {
+    let template = cohort_template();
+    let system = [("data", "rows".to_string())];
+    let rendered = render(&template, &[("min_age".to_string(), "65".to_string())], &system).unwrap();
+    assert_eq!(rendered, "Summarise rows for patients older than 65.");
+
+    assert!(render(&template, &[("min_age".to_string(), "old".to_string())], &system).is_err());
+    assert!(render(&template, &[], &system).is_err());
+    assert!(render(&template, &[("data".to_string(), "x".to_string()), ("min_age".to_string(), "1".to_string())], &system).is_err());
+    assert!(render(&template, &[("unknown".to_string(), "x".to_string())], &system).is_err());
+}
+
+#[test]
+fn revisions_need_approval_before_they_become_active() {
+    let admin = Principal::management_canister();
+    let body = "Analyse {{data}}".to_string();
+    let variables = vec![variable("data", VariableType::Text, true)];
+    create("trial", "trial_summary", body.clone(), variables.clone(), Principal::anonymous(), 1).unwrap();
+    assert!(create("trial", "trial_summary", body.clone(), variables.clone(), Principal::anonymous(), 1).is_err());
+    assert!(approved("trial", None).is_err());
+    assert!(active_for("trial_summary").is_none());
+
+    review("trial", 1, true, admin, None, 2).unwrap();
+    assert!(review("trial", 1, false, admin, None, 3).is_err());
+    revise("trial", "Briefly analyse {{data}}".to_string(), variables, Principal::anonymous(), 4).unwrap();
+    assert_eq!(approved("trial", None).unwrap().version, 1);
+    assert_eq!(active_for("trial_summary").unwrap().version, 1);
+
+    review("trial", 2, true, admin, Some("tighter wording".to_string()), 5).unwrap();
+    assert_eq!(active_for("trial_summary").unwrap().version, 2);
+    assert_eq!(approved("trial", Some(1)).unwrap().body, body);
+    assert_eq!(list().len(), 2);
+
+    delete("trial").unwrap();
+    assert!(get("trial", None).is_none());
+}
@@ -0,0 +1,153 @@
+//! LLM usage accounting and budgets
+//!
+//! Every provider call is attributed to the principal it runs for and the
+//! computation it belongs to. Token counts are estimated from byte sizes.
+//! Admins can cap a principal's calls or tokens; calls beyond the budget are
+//! refused before they reach the provider.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::backup;
+
+/// Rough size of a token for usage estimates
+pub const BYTES_PER_TOKEN: u64 = 4;
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LlmUsage {
+    pub calls: u64,
+    pub failed_calls: u64,
+    pub prompt_bytes: u64,
+    pub response_bytes: u64,
+    pub estimated_tokens: u64,
+}
+
+impl LlmUsage {
+    fn add(&mut self, prompt_bytes: u64, response_bytes: u64, succeeded: bool) {
+        self.calls += 1;
+        if !succeeded {
+            self.failed_calls += 1;
+        }
+        self.prompt_bytes += prompt_bytes;
+        self.response_bytes += response_bytes;
+        self.estimated_tokens += estimate_tokens(prompt_bytes) + estimate_tokens(response_bytes);
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct LlmBudget {
+    pub max_calls: Option<u64>,
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PrincipalLlmUsage {
+    pub principal: Principal,
+    pub usage: LlmUsage,
+    pub budget: Option<LlmBudget>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ComputationLlmUsage {
+    pub computation_id: String,
+    pub usage: LlmUsage,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LlmUsageStats {
+    pub total: LlmUsage,
+    pub by_principal: Vec<PrincipalLlmUsage>,
+    pub by_computation: Vec<ComputationLlmUsage>,
+}
+
+thread_local! {
+    static BY_PRINCIPAL: RefCell<HashMap<Principal, LlmUsage>> = RefCell::new(HashMap::new());
+    static BY_COMPUTATION: RefCell<HashMap<String, LlmUsage>> = RefCell::new(HashMap::new());
+    static BUDGETS: RefCell<HashMap<Principal, LlmBudget>> = RefCell::new(HashMap::new());
+}
+
+pub fn estimate_tokens(bytes: u64) -> u64 {
+    bytes.div_ceil(BYTES_PER_TOKEN)
+}
+
+/// Whether `usage` leaves room for another call under `budget`
+pub fn within_budget(usage: &LlmUsage, budget: &LlmBudget) -> Result<(), String> {
+    if let Some(max) = budget.max_calls.filter(|max| usage.calls >= *max) {
+        return Err(format!("LLM call budget of {} calls is used up", max));
+    }
+    if let Some(max) = budget.max_tokens.filter(|max| usage.estimated_tokens >= *max) {
+        return Err(format!("LLM token budget of {} tokens is used up", max));
+    }
+    Ok(())
+}
+
+pub fn check_budget(principal: Principal) -> Result<(), String> {
+    let Some(budget) = BUDGETS.with(|b| b.borrow().get(&principal).cloned()) else {
+        return Ok(());
+    };
+    within_budget(&usage_of(principal), &budget)
+}
+
+pub fn record(principal: Principal, computation_id: &str, prompt_bytes: u64, response_bytes: u64, succeeded: bool) {
+    BY_PRINCIPAL.with(|usage| {
+        usage.borrow_mut().entry(principal).or_default().add(prompt_bytes, response_bytes, succeeded);
+    });
+    BY_COMPUTATION.with(|usage| {
+        usage.borrow_mut().entry(computation_id.to_string()).or_default().add(prompt_bytes, response_bytes, succeeded);
+    });
+}
+
+pub fn set_budget(principal: Principal, budget: Option<LlmBudget>) {
+    BUDGETS.with(|budgets| match budget {
+        Some(budget) => budgets.borrow_mut().insert(principal, budget),
+        None => budgets.borrow_mut().remove(&principal),
+    });
+}
+
+pub fn usage_of(principal: Principal) -> LlmUsage {
+    BY_PRINCIPAL.with(|usage| usage.borrow().get(&principal).cloned().unwrap_or_default())
+}
+
+pub fn principal_usage(principal: Principal) -> PrincipalLlmUsage {
+    PrincipalLlmUsage {
+        principal,
+        usage: usage_of(principal),
+        budget: BUDGETS.with(|b| b.borrow().get(&principal).cloned()),
+    }
+}
+
+pub fn stats() -> LlmUsageStats {
+    let mut total = LlmUsage::default();
+    let by_principal = BY_PRINCIPAL.with(|usage| {
+        usage.borrow().iter().map(|(principal, usage)| {
+            total.calls += usage.calls;
+            total.failed_calls += usage.failed_calls;
+            total.prompt_bytes += usage.prompt_bytes;
+            total.response_bytes += usage.response_bytes;
+            total.estimated_tokens += usage.estimated_tokens;
+            principal_usage(*principal)
+        }).collect()
+    });
+    let by_computation = BY_COMPUTATION.with(|usage| {
+        usage.borrow().iter()
+            .map(|(computation_id, usage)| ComputationLlmUsage { computation_id: computation_id.clone(), usage: usage.clone() })
+            .collect()
+    });
+    LlmUsageStats { total, by_principal, by_computation }
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(backup::cloned(&BY_PRINCIPAL), backup::cloned(&BY_COMPUTATION), backup::cloned(&BUDGETS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (by_principal, by_computation, budgets) = backup::decode(bytes)?;
+    backup::replace(&BY_PRINCIPAL, by_principal);
+    backup::replace(&BY_COMPUTATION, by_computation);
+    backup::replace(&BUDGETS, budgets);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "usage_test.rs"]
+mod tests;
@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn tokens_are_estimated_from_bytes() {
+    assert_eq!(estimate_tokens(0), 0);
+    assert_eq!(estimate_tokens(1), 1);
+    assert_eq!(estimate_tokens(8), 2);
+    assert_eq!(estimate_tokens(9), 3);
+}
+
+#[test]
+fn usage_is_attributed_to_principal_and_computation() {
+    let alice = Principal::from_slice(&[1]);
+    let bob = Principal::from_slice(&[2]);
+    record(alice, "mpc_1", 400, 100, true);
+    record(alice, "mpc_2", 40, 0, false);
+    record(bob, "mpc_1", 4, 4, true);
+
+    let usage = usage_of(alice);
+    assert_eq!(usage.calls, 2);
+    assert_eq!(usage.failed_calls, 1);
+    assert_eq!(usage.estimated_tokens, 100 + 25 + 10);
+
+    let stats = stats();
+    assert_eq!(stats.total.calls, 3);
+    let mpc_1 = stats.by_computation.iter().find(|c| c.computation_id == "mpc_1").unwrap();
+    assert_eq!(mpc_1.usage.prompt_bytes, 404);
+}
+
+#[test]
+fn budgets_stop_calls_once_used_up() {
+    let usage = LlmUsage { calls: 3, estimated_tokens: 500, ..LlmUsage::default() };
+    assert!(within_budget(&usage, &LlmBudget::default()).is_ok());
+    assert!(within_budget(&usage, &LlmBudget { max_calls: Some(4), max_tokens: None }).is_ok());
+    assert!(within_budget(&usage, &LlmBudget { max_calls: Some(3), max_tokens: None }).is_err());
+    assert!(within_budget(&usage, &LlmBudget { max_calls: None, max_tokens: Some(500) }).is_err());
+
+    let carol = Principal::from_slice(&[3]);
+    set_budget(carol, Some(LlmBudget { max_calls: Some(1), max_tokens: None }));
+    assert!(check_budget(carol).is_ok());
+    record(carol, "mpc_3", 1, 1, true);
+    assert!(check_budget(carol).is_err());
+    set_budget(carol, None);
+    assert!(check_budget(carol).is_ok());
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+fn cohort_schema() -> Value {
+    parse_output_schema(r#"{
+        "type": "object",
+        "required": ["cohort_size", "outcome"],
+        "additionalProperties": false,
+        "properties": {
+            "cohort_size": {"type": "integer", "minimum": 0},
+            "outcome": {"type": "string", "enum": ["improved", "unchanged", "worse"]},
+            "rates": {"type": "array", "items": {"type": "number"}}
+        }
+    }"#).unwrap()
+}
+
+#[test]
+fn extracts_clean_json_without_repair() {
+    let (value, repaired) = extract_json(r#"{"cohort_size": 10, "outcome": "improved"}"#).unwrap();
+    assert!(!repaired);
+    assert_eq!(value["cohort_size"], 10);
+}
+
+#[test]
+fn repairs_fenced_json_with_trailing_commas() {
+    let response = "Here you go:\n```json\n{\"cohort_size\": 10, \"rates\": [0.5, 0.7,], \"outcome\": \"a,}\",}\n```";
+    let (value, repaired) = extract_json(response).unwrap();
+    assert!(repaired);
+    assert_eq!(value["outcome"], "a,}");
+    assert_eq!(value["rates"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn rejects_responses_without_json() {
+    assert!(extract_json("The cohort improved overall.").is_none());
+}
+
+#[test]
+fn validates_conforming_output() {
+    let value: Value = serde_json::from_str(r#"{"cohort_size": 42, "outcome": "unchanged", "rates": [0.1]}"#).unwrap();
+    assert!(validate_against_schema(&value, &cohort_schema()).is_ok());
+}
+
+#[test]
+fn reports_every_violation() {
+    let value: Value = serde_json::from_str(r#"{"cohort_size": -1, "outcome": "great", "rates": ["x"], "extra": 1}"#).unwrap();
+    let errors = validate_against_schema(&value, &cohort_schema()).unwrap_err();
+    assert_eq!(errors.len(), 4);
+    assert!(errors.iter().any(|e| e.starts_with("$.rates[0]")));
+}
+
+#[test]
+fn rejects_non_object_schema() {
+    assert!(parse_output_schema("[1, 2]").is_err());
+    assert!(parse_output_schema("not json").is_err());
+}
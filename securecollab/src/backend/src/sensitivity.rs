@@ -0,0 +1,128 @@
+//! Sensitivity analysis of queries before they run
+//!
+//! Approvers see, for every statistic a query will release, how much one
+//! record can move it, the share of epsilon it gets and the resulting noise
+//! scale. The analysis follows the release performed by
+//! [`crate::differential_privacy::privatize`] and uses the declared schema
+//! and record counts of the target datasets, so it is available before
+//! anything is decrypted. Aggregates the query text asks for are detected as well, and
+//! those that are never released are flagged.
+
+use candid::{CandidType, Deserialize};
+use regex::Regex;
+use crate::analytics::{ColumnSpec, ColumnType};
+use crate::differential_privacy::{self, DpConfig, NoiseMechanism};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AggregateKind {
+    Count,
+    Mean,
+    GroupBy,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AggregateAnalysis {
+    pub statistic: String,
+    pub kind: AggregateKind,
+    /// Largest change one record can cause
+    pub sensitivity: f64,
+    pub epsilon: f64,
+    /// Laplace scale or Gaussian standard deviation
+    pub noise_scale: f64,
+    pub expected_absolute_error: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SensitivityAnalysis {
+    pub query_id: String,
+    pub mechanism: NoiseMechanism,
+    pub total_epsilon: f64,
+    pub delta: f64,
+    pub declared_records: u64,
+    pub aggregates: Vec<AggregateAnalysis>,
+    /// Aggregates detected in the query text
+    pub requested: Vec<AggregateKind>,
+    pub warnings: Vec<String>,
+}
+
+// (pattern, aggregate it asks for)
+const REQUESTED: &[(&str, AggregateKind)] = &[
+    (r"(?i)\b(count|how\s+many|number\s+of|total\s+number)\b", AggregateKind::Count),
+    (r"(?i)\b(average|mean|avg|rate)\b", AggregateKind::Mean),
+    (r"(?i)\b(group(ed)?\s+by|per|by|for\s+each|breakdown|distribution)\b", AggregateKind::GroupBy),
+];
+
+// Statistics that are never released, with the words that ask for them
+const UNRELEASED: &[(&str, &str)] = &[
+    ("median", r"(?i)\bmedian\b"),
+    ("minimum or maximum", r"(?i)\b(min|max|minimum|maximum|youngest|oldest)\b"),
+    ("percentiles", r"(?i)\b(percentile|quantile|quartile)s?\b"),
+    ("standard deviation", r"(?i)\b(std|standard\s+deviation|variance)\b"),
+    ("individual records", r"(?i)\b(list|each\s+patient|individual|row[-\s]level)\b"),
+];
+
+fn expected_error(mechanism: NoiseMechanism, scale: f64) -> f64 {
+    match mechanism {
+        NoiseMechanism::Laplace => scale,
+        NoiseMechanism::Gaussian => scale * (2.0 / std::f64::consts::PI).sqrt(),
+    }
+}
+
+pub fn requested_aggregates(query: &str) -> Vec<AggregateKind> {
+    REQUESTED.iter()
+        .filter(|(pattern, _)| Regex::new(pattern).expect("aggregate patterns are valid regexes").is_match(query))
+        .map(|(_, kind)| *kind)
+        .collect()
+}
+
+pub fn analyze(query_id: &str, query: &str, declared_records: u64, schema: &[ColumnSpec], config: &DpConfig) -> SensitivityAnalysis {
+    let epsilon = config.epsilon / differential_privacy::released_statistics(schema) as f64;
+    let records = declared_records.max(1) as f64;
+    let mut statistics = vec![("Record count".to_string(), AggregateKind::Count, 1.0)];
+    statistics.extend(schema.iter().filter(|column| column.released()).map(|column| match column.column_type {
+        ColumnType::Number { bounds: Some((min, max)), .. } => (format!("Mean {}", column.name), AggregateKind::Mean, (max - min) / records),
+        _ => (format!("{} counts", column.name), AggregateKind::GroupBy, 1.0),
+    }));
+    let aggregates = statistics.iter()
+        .map(|(statistic, kind, sensitivity)| {
+            let noise_scale = differential_privacy::noise_scale(config.mechanism, *sensitivity, epsilon, config.delta);
+            AggregateAnalysis {
+                statistic: statistic.clone(),
+                kind: *kind,
+                sensitivity: *sensitivity,
+                epsilon,
+                noise_scale,
+                expected_absolute_error: expected_error(config.mechanism, noise_scale),
+            }
+        })
+        .collect();
+
+    let mut warnings: Vec<String> = UNRELEASED.iter()
+        .filter(|(_, pattern)| Regex::new(pattern).expect("unreleased patterns are valid regexes").is_match(query))
+        .map(|(statistic, _)| format!("The query asks for {}, which is not released", statistic))
+        .collect();
+    if declared_records == 0 {
+        warnings.push("The target datasets declare no records; sensitivities assume a single record".to_string());
+    }
+    warnings.extend(schema.iter()
+        .filter(|column| matches!(column.column_type, ColumnType::Number { bounds: None, .. }))
+        .map(|column| format!("Column '{}' declares no bounds, so its mean is not released", column.name)));
+    if !schema.iter().any(|column| column.released()) {
+        warnings.push("The schema declares no bounded number, category or boolean column; only the record count is released".to_string());
+    }
+
+    SensitivityAnalysis {
+        query_id: query_id.to_string(),
+        mechanism: config.mechanism,
+        total_epsilon: config.epsilon,
+        delta: if config.mechanism == NoiseMechanism::Gaussian { config.delta } else { 0.0 },
+        declared_records,
+        aggregates,
+        requested: requested_aggregates(query),
+        warnings,
+    }
+}
+
+#[cfg(test)]
+#[path = "sensitivity_test.rs"]
+mod tests;
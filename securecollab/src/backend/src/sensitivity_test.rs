@@ -0,0 +1,48 @@
+use super::*;
+use crate::analytics::parse_schema;
+
+fn schema() -> Vec<ColumnSpec> {
+    parse_schema("patient_id:id,age:number[0..120],treatment:category,outcome:category,recovery_days:number[0..365],side_effect:category,hospital:category").unwrap()
+}
+
+#[test]
+fn noise_scales_follow_sensitivity_and_epsilon_share() {
+    let config = DpConfig { epsilon: 7.0, ..DpConfig::default() };
+    let analysis = analyze("q1", "Average recovery time", 1000, &schema(), &config);
+
+    let count = &analysis.aggregates[0];
+    assert_eq!(count.sensitivity, 1.0);
+    assert_eq!(count.epsilon, 1.0);
+    assert_eq!(count.noise_scale, 1.0);
+
+    let recovery = analysis.aggregates.iter().find(|a| a.statistic == "Mean recovery_days").unwrap();
+    assert!((recovery.sensitivity - 0.365).abs() < 1e-9);
+    assert!((recovery.noise_scale - 0.365).abs() < 1e-9);
+}
+
+#[test]
+fn gaussian_analysis_reports_delta_and_wider_noise() {
+    let laplace = analyze("q1", "count", 100, &schema(), &DpConfig::default());
+    let gaussian = analyze("q1", "count", 100, &schema(), &DpConfig { mechanism: NoiseMechanism::Gaussian, ..DpConfig::default() });
+    assert_eq!(laplace.delta, 0.0);
+    assert_eq!(gaussian.delta, 1e-5);
+    assert!(gaussian.aggregates[0].noise_scale > laplace.aggregates[0].noise_scale);
+}
+
+#[test]
+fn detects_requested_and_unreleased_aggregates() {
+    let analysis = analyze("q1", "How many patients improved, and what is the median age by hospital?", 500, &schema(), &DpConfig::default());
+    assert_eq!(analysis.requested, vec![AggregateKind::Count, AggregateKind::GroupBy]);
+    assert!(analysis.warnings.iter().any(|w| w.contains("median")));
+    assert!(!analysis.warnings.iter().any(|w| w.contains("standard deviation")));
+}
+
+#[test]
+fn warns_about_columns_that_are_not_released() {
+    let schema = parse_schema("id:id,cost:number,region").unwrap();
+    let analysis = analyze("q1", "count", 10, &schema, &DpConfig::default());
+    assert_eq!(analysis.aggregates.len(), 1);
+    assert_eq!(analysis.aggregates[0].epsilon, 1.0);
+    assert!(analysis.warnings.iter().any(|w| w.contains("'cost' declares no bounds")));
+    assert!(analysis.warnings.iter().any(|w| w.contains("only the record count")));
+}
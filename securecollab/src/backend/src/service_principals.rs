@@ -0,0 +1,141 @@
+//! Service principals for automation
+//!
+//! A party registers the principal of a machine identity, such as a
+//! hospital's ETL pipeline, with a few scopes and an expiry. The service
+//! principal then acts for its owner within those scopes only: a nightly
+//! upload is owned by the hospital, and no human's principal sits on the
+//! pipeline's servers. Scopes never reach approving or managing roles, and
+//! the owner must still hold a scope's permission whenever it is used.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::roles::{self, Permission};
+use crate::backup;
+
+pub const MAX_LIFETIME_NANOS: u64 = 365 * 24 * 60 * 60 * 1_000_000_000;
+pub const MAX_PER_OWNER: usize = 20;
+pub const MAX_LABEL_LENGTH: usize = 100;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ServicePrincipal {
+    pub principal: Principal,
+    pub owner: Principal,
+    pub label: String,
+    pub scopes: Vec<Permission>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub last_used: Option<u64>,
+}
+
+thread_local! {
+    static SERVICE_PRINCIPALS: RefCell<HashMap<Principal, ServicePrincipal>> = RefCell::new(HashMap::new());
+}
+
+pub fn is_service_principal(principal: Principal) -> bool {
+    SERVICE_PRINCIPALS.with(|services| services.borrow().contains_key(&principal))
+}
+
+pub fn register(
+    owner: Principal,
+    principal: Principal,
+    label: String,
+    requested_scopes: Vec<Permission>,
+    expires_at: u64,
+    now: u64,
+) -> Result<ServicePrincipal, String> {
+    let label = label.trim().to_string();
+    if label.is_empty() || label.chars().count() > MAX_LABEL_LENGTH {
+        return Err(format!("Labels are 1 to {} characters", MAX_LABEL_LENGTH));
+    }
+    if principal == Principal::anonymous() || principal == owner {
+        return Err("A service principal needs its own non-anonymous principal".to_string());
+    }
+    if is_service_principal(owner) {
+        return Err("Service principals cannot register others".to_string());
+    }
+    if is_service_principal(principal) {
+        return Err(format!("{} is already a service principal", principal.to_text()));
+    }
+    if expires_at <= now || expires_at - now > MAX_LIFETIME_NANOS {
+        return Err("Service principals expire within a year".to_string());
+    }
+    let mut scopes = Vec::new();
+    for scope in requested_scopes {
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.is_empty() {
+        return Err("A service principal needs at least one scope".to_string());
+    }
+    for scope in &scopes {
+        if matches!(scope, Permission::Approve | Permission::ManageRoles) {
+            return Err(format!("{:?} is reserved for people", scope));
+        }
+        roles::require(owner, *scope)?;
+    }
+    SERVICE_PRINCIPALS.with(|services| {
+        let mut services = services.borrow_mut();
+        if services.values().filter(|s| s.owner == owner).count() >= MAX_PER_OWNER {
+            return Err(format!("A party has at most {} service principals", MAX_PER_OWNER));
+        }
+        let service = ServicePrincipal { principal, owner, label, scopes, created_at: now, expires_at, last_used: None };
+        services.insert(principal, service.clone());
+        Ok(service)
+    })
+}
+
+/// The owner a service principal acts for on an endpoint needing
+/// `permission`, or None when `principal` is not a service principal
+pub fn authorize(principal: Principal, permission: Permission, now: u64) -> Option<Result<Principal, String>> {
+    let service = SERVICE_PRINCIPALS.with(|services| services.borrow().get(&principal).cloned())?;
+    if now >= service.expires_at {
+        return Some(Err(format!("Service principal '{}' has expired", service.label)));
+    }
+    if !service.scopes.contains(&permission) {
+        return Some(Err(format!("Service principal '{}' is not scoped for {:?}", service.label, permission)));
+    }
+    if let Err(e) = roles::require(service.owner, permission) {
+        return Some(Err(e));
+    }
+    SERVICE_PRINCIPALS.with(|services| {
+        if let Some(service) = services.borrow_mut().get_mut(&principal) {
+            service.last_used = Some(now);
+        }
+    });
+    Some(Ok(service.owner))
+}
+
+/// Service principals of an owner, oldest first
+pub fn list(owner: Principal) -> Vec<ServicePrincipal> {
+    let mut listed: Vec<ServicePrincipal> = SERVICE_PRINCIPALS.with(|services| {
+        services.borrow().values().filter(|s| s.owner == owner).cloned().collect()
+    });
+    listed.sort_by_key(|s| (s.created_at, s.principal));
+    listed
+}
+
+pub fn revoke(owner: Principal, principal: Principal) -> Result<ServicePrincipal, String> {
+    SERVICE_PRINCIPALS.with(|services| {
+        let mut services = services.borrow_mut();
+        if services.get(&principal).map(|s| s.owner) != Some(owner) {
+            return Err(format!("{} is not one of your service principals", principal.to_text()));
+        }
+        services.remove(&principal).ok_or_else(|| format!("{} is not one of your service principals", principal.to_text()))
+    })
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&SERVICE_PRINCIPALS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let service_principals = backup::decode(bytes)?;
+    backup::replace(&SERVICE_PRINCIPALS, service_principals);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "service_principals_test.rs"]
+mod tests;
@@ -0,0 +1,50 @@
+use super::*;
+use crate::organizations;
+use crate::test_support::principal;
+
+const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+#[test]
+fn service_principals_act_for_their_owner_within_scope() {
+    let (hospital, pipeline) = (principal(1), principal(2));
+    register(hospital, pipeline, " Nightly ETL ".to_string(), vec![Permission::Upload, Permission::Upload], 30 * DAY, 0).unwrap();
+    let listed = list(hospital);
+    assert_eq!((listed[0].label.as_str(), listed[0].scopes.clone()), ("Nightly ETL", vec![Permission::Upload]));
+
+    assert_eq!(authorize(pipeline, Permission::Upload, DAY).unwrap(), Ok(hospital));
+    assert_eq!(list(hospital)[0].last_used, Some(DAY));
+    assert!(authorize(pipeline, Permission::Compute, DAY).unwrap().is_err());
+    assert!(authorize(pipeline, Permission::Upload, 30 * DAY).unwrap().is_err());
+    assert!(authorize(hospital, Permission::Upload, DAY).is_none());
+
+    assert!(revoke(principal(3), pipeline).is_err());
+    revoke(hospital, pipeline).unwrap();
+    assert!(authorize(pipeline, Permission::Upload, DAY).is_none());
+}
+
+#[test]
+fn scopes_stay_within_what_the_owner_may_do() {
+    let (owner, service) = (principal(4), principal(5));
+    let register_with = |scopes: Vec<Permission>, expires_at: u64| {
+        register(owner, service, "ETL".to_string(), scopes, expires_at, 0)
+    };
+    assert!(register_with(vec![], DAY).is_err());
+    assert!(register_with(vec![Permission::Approve], DAY).is_err());
+    assert!(register_with(vec![Permission::ManageRoles], DAY).is_err());
+    assert!(register_with(vec![Permission::Upload], 0).is_err());
+    assert!(register_with(vec![Permission::Upload], MAX_LIFETIME_NANOS + 1).is_err());
+    assert!(register(owner, owner, "Self".to_string(), vec![Permission::Upload], DAY, 0).is_err());
+
+    // An organization member without roles cannot delegate what they lack
+    let (admin, member) = (principal(6), principal(7));
+    organizations::create("org_etl".to_string(), "ETL".to_string(), admin, 0).unwrap();
+    organizations::add_member("org_etl", admin, member, false).unwrap();
+    assert!(register(member, principal(8), "ETL".to_string(), vec![Permission::Upload], DAY, 0).is_err());
+    roles::grant(admin, member, roles::Role::Analyst).unwrap();
+    register(member, principal(8), "ETL".to_string(), vec![Permission::Upload], DAY, 0).unwrap();
+
+    // Losing the role stops the service principal too
+    roles::revoke(admin, member, roles::Role::Analyst).unwrap();
+    assert!(authorize(principal(8), Permission::Upload, 1).unwrap().is_err());
+    assert!(register(principal(8), principal(9), "Chain".to_string(), vec![Permission::Upload], DAY, 0).is_err());
+}
@@ -0,0 +1,288 @@
+//! Restricted SQL over the combined datasets of a query
+//!
+//! Requesters may submit a small SQL subset instead of a free-text prompt:
+//!
+//! `SELECT site, COUNT(*), AVG(age) AS mean_age FROM data WHERE age>60 AND treatment=Drug_X GROUP BY site`
+//!
+//! The select list holds `COUNT(*)`, `SUM(column)` and `AVG(column)` over
+//! bounded numeric columns, and plain columns only when they are grouped by.
+//! `SELECT *` and ungrouped columns would return records, so the planner
+//! rejects them. Groups are formed on category and boolean columns, and the
+//! `WHERE` clause takes the conditions cohorts do. The target datasets are
+//! combined into the one table named after `FROM`, whatever its name.
+//!
+//! Plans run on the approved query's decrypted columns. One record belongs to
+//! one group, so every group's statistics get the whole epsilon, split
+//! evenly across its record count and the sums and counts the select list
+//! needs. Groups with fewer records than the minimum cohort size are left out.
+
+use candid::{CandidType, Deserialize};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use crate::analytics::{numeric_value, ColumnSpec, ColumnType};
+use crate::cohorts::{self, Condition};
+use crate::differential_privacy::{noise, DpConfig, NoiseSource};
+use crate::tabular;
+
+pub const MAX_SELECTED: usize = 20;
+pub const MAX_GROUP_COLUMNS: usize = 3;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum SelectItem {
+    /// A grouped-by column
+    Group(String),
+    Count,
+    Sum(String),
+    Average(String),
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SelectColumn {
+    pub item: SelectItem,
+    /// The alias, or the expression as written
+    pub name: String,
+}
+
+/// An aggregate-only plan for a parsed query
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SqlPlan {
+    pub select: Vec<SelectColumn>,
+    pub conditions: Vec<Condition>,
+    pub group_by: Vec<String>,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum SqlValue {
+    Text(String),
+    Number(f64),
+    /// An average over no records
+    Null,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct SqlResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<SqlValue>>,
+    /// Groups left out for having fewer records than the minimum cohort size
+    pub suppressed_groups: u64,
+    pub epsilon: f64,
+}
+
+thread_local! {
+    static QUERY: Regex = Regex::new(
+        r"(?is)^\s*SELECT\s+(.+?)(?:\s+FROM\s+(\w+))?(?:\s+WHERE\s+(.+?))?(?:\s+GROUP\s+BY\s+(.+?))?\s*;?\s*$"
+    ).expect("query pattern is a valid regex");
+    static AGGREGATE: Regex = Regex::new(r"(?i)^(\w+)\s*\(\s*(\*|\w+)\s*\)$").expect("aggregate pattern is a valid regex");
+    static ALIAS: Regex = Regex::new(r"(?i)^(.+?)\s+AS\s+(\w+)$").expect("alias pattern is a valid regex");
+}
+
+fn parse_select(text: &str, group_by: &[String]) -> Result<SelectColumn, String> {
+    let text = text.trim();
+    let (expression, alias) = match ALIAS.with(|alias| alias.captures(text).map(|c| (c[1].trim().to_string(), c[2].to_string()))) {
+        Some((expression, alias)) => (expression, Some(alias)),
+        None => (text.to_string(), None),
+    };
+    if expression == "*" {
+        return Err("SELECT * returns records; select aggregates such as COUNT(*) instead".to_string());
+    }
+    let item = match AGGREGATE.with(|aggregate| aggregate.captures(&expression).map(|c| (c[1].to_ascii_uppercase(), c[2].to_string()))) {
+        Some((function, argument)) => match (function.as_str(), argument.as_str()) {
+            ("COUNT", "*") => SelectItem::Count,
+            ("COUNT", _) => return Err("Only COUNT(*) is supported".to_string()),
+            (_, "*") => return Err(format!("{}(*) is not an aggregate", function)),
+            ("SUM", _) => SelectItem::Sum(argument),
+            ("AVG", _) => SelectItem::Average(argument),
+            _ => return Err(format!("Unsupported function {}; use COUNT, SUM or AVG", function)),
+        },
+        None if group_by.contains(&expression) => SelectItem::Group(expression.clone()),
+        None => return Err(format!("'{}' would return records; only grouped-by columns and aggregates can be selected", expression)),
+    };
+    Ok(SelectColumn { item, name: alias.unwrap_or(expression) })
+}
+
+/// Plan a query, rejecting anything but aggregates
+pub fn parse(sql: &str) -> Result<SqlPlan, String> {
+    let captures = QUERY.with(|query| {
+        query.captures(sql).map(|c| [1, 3, 4].map(|i| c.get(i).map(|m| m.as_str().to_string())))
+    }).ok_or("Queries must have the form SELECT ... [FROM table] [WHERE ...] [GROUP BY ...]")?;
+    let [select, conditions, group_by] = captures;
+
+    let group_by: Vec<String> = group_by.iter()
+        .flat_map(|columns| columns.split(','))
+        .map(|column| column.trim().to_string())
+        .collect();
+    if group_by.iter().any(|column| column.is_empty() || column.contains(char::is_whitespace)) {
+        return Err("GROUP BY takes a list of column names".to_string());
+    }
+    if group_by.len() > MAX_GROUP_COLUMNS {
+        return Err(format!("Group by at most {} columns", MAX_GROUP_COLUMNS));
+    }
+    let select = select.unwrap_or_default().split(',')
+        .map(|item| parse_select(item, &group_by))
+        .collect::<Result<Vec<_>, String>>()?;
+    if select.len() > MAX_SELECTED {
+        return Err(format!("Select at most {} columns", MAX_SELECTED));
+    }
+    if !select.iter().any(|column| !matches!(column.item, SelectItem::Group(_))) {
+        return Err("Select at least one aggregate".to_string());
+    }
+    let conditions = match conditions {
+        Some(conditions) => cohorts::parse_filter(&conditions)?,
+        None => vec![],
+    };
+    Ok(SqlPlan { select, conditions, group_by })
+}
+
+impl SqlPlan {
+    /// Every column the plan reads, in first use
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        let referenced = self.group_by.iter()
+            .chain(self.select.iter().filter_map(|column| match &column.item {
+                SelectItem::Sum(name) | SelectItem::Average(name) => Some(name),
+                _ => None,
+            }))
+            .chain(self.conditions.iter().map(|condition| &condition.column));
+        for column in referenced {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+        columns
+    }
+
+    /// Columns summed or averaged, each with its bounds
+    fn measures(&self, schema: &[ColumnSpec]) -> Result<Vec<(String, f64, f64)>, String> {
+        let mut measures: Vec<(String, f64, f64)> = Vec::new();
+        for column in &self.select {
+            let (SelectItem::Sum(name) | SelectItem::Average(name)) = &column.item else { continue };
+            if measures.iter().any(|(measured, _, _)| measured == name) {
+                continue;
+            }
+            match schema.iter().find(|spec| spec.name == *name).map(|spec| &spec.column_type) {
+                Some(ColumnType::Number { bounds: Some((min, max)), .. }) => measures.push((name.clone(), *min, *max)),
+                Some(_) => return Err(format!("Column '{}' must be a bounded number to aggregate", name)),
+                None => return Err(format!("Column '{}' is not in the schema", name)),
+            }
+        }
+        Ok(measures)
+    }
+
+    /// Check the plan against the schema of the datasets it will run on
+    pub fn check(&self, schema: &[ColumnSpec]) -> Result<(), String> {
+        for name in &self.group_by {
+            match schema.iter().find(|spec| spec.name == *name).map(|spec| &spec.column_type) {
+                Some(ColumnType::Category | ColumnType::Boolean) => {}
+                Some(_) => return Err(format!("Column '{}' must be a category or a boolean to group by", name)),
+                None => return Err(format!("Column '{}' is not in the schema", name)),
+            }
+        }
+        self.measures(schema)?;
+        cohorts::filter(schema, &self.conditions, &[])?;
+        Ok(())
+    }
+}
+
+/// Exact record count, and per measure the sum of clamped values and their count
+struct Group {
+    records: u64,
+    sums: Vec<(f64, u64)>,
+}
+
+pub fn execute(
+    plan: &SqlPlan,
+    schema: &[ColumnSpec],
+    datasets: &[String],
+    config: &DpConfig,
+    min_group_size: u64,
+    source: &mut NoiseSource,
+) -> Result<SqlResult, String> {
+    plan.check(schema)?;
+    let measures = plan.measures(schema)?;
+    let averaged: Vec<bool> = measures.iter()
+        .map(|(name, _, _)| plan.select.iter().any(|column| column.item == SelectItem::Average(name.clone())))
+        .collect();
+
+    let mut groups: BTreeMap<Vec<String>, Group> = BTreeMap::new();
+    for data in cohorts::filter(schema, &plan.conditions, datasets)? {
+        let table = tabular::parse(data.as_bytes())?;
+        let position = |name: &String| table.header.iter().position(|h| h == name)
+            .ok_or_else(|| format!("Column '{}' not found", name));
+        let keys = plan.group_by.iter().map(position).collect::<Result<Vec<_>, String>>()?;
+        let values = measures.iter().map(|(name, _, _)| position(name)).collect::<Result<Vec<_>, String>>()?;
+        for row in &table.rows {
+            let Some(key) = keys.iter().map(|&p| row.get(p).filter(|v| !v.is_empty()).cloned()).collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+            let group = groups.entry(key).or_insert_with(|| Group { records: 0, sums: vec![(0.0, 0); measures.len()] });
+            group.records += 1;
+            for ((sum, count), (&p, (_, min, max))) in group.sums.iter_mut().zip(values.iter().zip(&measures)) {
+                if let Some(value) = row.get(p).and_then(|v| numeric_value(&ColumnType::Number { bounds: None, integer: false }, v)) {
+                    *sum += value.clamp(*min, *max);
+                    *count += 1;
+                }
+            }
+        }
+    }
+    if plan.group_by.is_empty() && groups.values().map(|g| g.records).sum::<u64>() < min_group_size {
+        return Err(format!("Fewer than {} records match the query", min_group_size));
+    }
+
+    let statistics = 1 + measures.len() + averaged.iter().filter(|a| **a).count();
+    let epsilon = config.epsilon / statistics as f64;
+    let mut noisy = |value: f64, sensitivity: f64| value + noise(source, config.mechanism, sensitivity, epsilon, config.delta);
+    let mut rows = Vec::new();
+    let mut suppressed_groups = 0;
+    for (key, group) in &groups {
+        if group.records < min_group_size {
+            suppressed_groups += 1;
+            continue;
+        }
+        let records = noisy(group.records as f64, 1.0).round().max(0.0);
+        let released: Vec<(f64, Option<f64>)> = group.sums.iter().zip(&measures).zip(&averaged)
+            .map(|(((sum, count), (_, min, max)), averaged)| {
+                let sum = noisy(*sum, min.abs().max(max.abs()));
+                let count = averaged.then(|| noisy(*count as f64, 1.0).round());
+                (sum, count)
+            })
+            .collect();
+        rows.push(plan.select.iter().map(|column| match &column.item {
+            SelectItem::Group(name) => SqlValue::Text(key[plan.group_by.iter().position(|g| g == name).unwrap_or(0)].clone()),
+            SelectItem::Count => SqlValue::Number(records),
+            SelectItem::Sum(name) | SelectItem::Average(name) => {
+                let i = measures.iter().position(|(measured, _, _)| measured == name).unwrap_or(0);
+                let (_, min, max) = measures[i];
+                match (&column.item, released[i]) {
+                    (SelectItem::Sum(_), (sum, _)) => SqlValue::Number(sum),
+                    (_, (sum, Some(count))) if count >= 1.0 => SqlValue::Number((sum / count).clamp(min, max)),
+                    _ => SqlValue::Null,
+                }
+            }
+        }).collect());
+    }
+    Ok(SqlResult {
+        columns: plan.select.iter().map(|column| column.name.clone()).collect(),
+        rows,
+        suppressed_groups,
+        epsilon: config.epsilon,
+    })
+}
+
+/// Pipe-separated table of a result
+pub fn render(result: &SqlResult) -> String {
+    let mut lines = vec![result.columns.join(" | ")];
+    lines.extend(result.rows.iter().map(|row| row.iter().map(|value| match value {
+        SqlValue::Text(text) => text.clone(),
+        SqlValue::Number(number) => format!("{:.2}", number),
+        SqlValue::Null => "NULL".to_string(),
+    }).collect::<Vec<_>>().join(" | ")));
+    if result.suppressed_groups > 0 {
+        lines.push(format!("({} groups below the minimum cohort size suppressed)", result.suppressed_groups));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+#[path = "sql_test.rs"]
+mod tests;
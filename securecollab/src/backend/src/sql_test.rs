@@ -0,0 +1,86 @@
+use super::*;
+use crate::analytics::parse_schema;
+use crate::differential_privacy::NoiseMechanism;
+
+const SCHEMA: &str = "age:number[0..100],treatment:category,site:category,recovered:boolean,notes:text";
+
+fn dataset(rows: usize) -> String {
+    let mut lines = vec!["age,treatment,site,recovered,notes".to_string()];
+    for i in 0..rows {
+        let site = if i % 10 == 0 { "south" } else { "north" };
+        let treatment = if i % 2 == 0 { "Drug_X" } else { "Placebo" };
+        lines.push(format!("{},{},{},{},note {}", 40 + i % 40, treatment, site, i % 3 == 0, i));
+    }
+    lines.join("\n")
+}
+
+fn config() -> DpConfig {
+    DpConfig { epsilon: 1e6, delta: 0.0, mechanism: NoiseMechanism::Laplace }
+}
+
+#[test]
+fn plans_aggregate_queries() {
+    let plan = parse("select site, count(*), AVG(age) as mean_age from trial where age>60 and treatment=Drug_X group by site;").unwrap();
+    assert_eq!(plan.group_by, vec!["site"]);
+    assert_eq!(plan.select.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["site", "count(*)", "mean_age"]);
+    assert_eq!(plan.select[2].item, SelectItem::Average("age".to_string()));
+    assert_eq!(plan.conditions.len(), 2);
+    assert_eq!(plan.columns(), vec!["site", "age", "treatment"]);
+    assert!(parse("SELECT COUNT(*)").is_ok());
+}
+
+#[test]
+fn rejects_row_level_queries() {
+    for sql in [
+        "SELECT * FROM trial",
+        "SELECT age FROM trial",
+        "SELECT site, COUNT(*) FROM trial",
+        "SELECT site FROM trial GROUP BY site",
+        "SELECT MAX(age) FROM trial",
+        "SELECT COUNT(age) FROM trial",
+        "DELETE FROM trial",
+    ] {
+        assert!(parse(sql).is_err(), "{}", sql);
+    }
+}
+
+#[test]
+fn executes_grouped_aggregates() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let plan = parse("SELECT site, COUNT(*) AS n, SUM(age), AVG(age) FROM trial WHERE treatment=Drug_X GROUP BY site").unwrap();
+    let result = execute(&plan, &schema, &[dataset(100), dataset(100)], &config(), 5, &mut source).unwrap();
+    assert_eq!(result.columns, vec!["site", "n", "SUM(age)", "AVG(age)"]);
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(result.rows[0][0], SqlValue::Text("north".to_string()));
+    assert_eq!(result.rows[0][1], SqlValue::Number(80.0));
+    let SqlValue::Number(mean) = result.rows[1][3] else { panic!("no mean") };
+    // South holds every tenth record: three aged 40 and 50, two aged 60 and 70
+    assert!((mean - 53.0).abs() < 0.01, "{}", mean);
+    assert!(render(&result).starts_with("site | n | SUM(age) | AVG(age)\nnorth | 80.00"));
+}
+
+#[test]
+fn suppresses_small_groups() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let plan = parse("SELECT site, COUNT(*) FROM trial GROUP BY site").unwrap();
+    let result = execute(&plan, &schema, &[dataset(40)], &config(), 10, &mut source).unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.suppressed_groups, 1);
+    let ungrouped = parse("SELECT COUNT(*) FROM trial WHERE age>200").unwrap();
+    assert!(execute(&ungrouped, &schema, &[dataset(40)], &config(), 10, &mut source).is_err());
+}
+
+#[test]
+fn checks_plans_against_the_schema() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    for sql in [
+        "SELECT AVG(notes) FROM trial",
+        "SELECT notes, COUNT(*) FROM trial GROUP BY notes",
+        "SELECT COUNT(*) FROM trial WHERE missing=1",
+        "SELECT SUM(weight) FROM trial",
+    ] {
+        assert!(parse(sql).unwrap().check(&schema).is_err(), "{}", sql);
+    }
+}
@@ -0,0 +1,85 @@
+//! Per-principal storage quotas
+//!
+//! Usage is the encrypted bytes a principal's datasets occupy, including
+//! their column-level copies. Uploads that would push an owner past its
+//! quota are rejected so one party cannot exhaust canister memory.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use crate::backup;
+
+/// Quota applied to principals without an explicit limit (100 MiB)
+pub const DEFAULT_QUOTA_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StorageUsage {
+    pub principal: Principal,
+    pub dataset_count: u32,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+thread_local! {
+    static DEFAULT_QUOTA: Cell<u64> = const { Cell::new(DEFAULT_QUOTA_BYTES) };
+    static QUOTAS: RefCell<HashMap<Principal, u64>> = RefCell::new(HashMap::new());
+}
+
+pub fn quota_for(principal: Principal) -> u64 {
+    QUOTAS.with(|quotas| quotas.borrow().get(&principal).copied())
+        .unwrap_or_else(|| DEFAULT_QUOTA.with(|quota| quota.get()))
+}
+
+pub fn set_default_quota(bytes: u64) {
+    DEFAULT_QUOTA.with(|quota| quota.set(bytes));
+}
+
+/// Set or, with `None`, clear a principal's quota override
+pub fn set_quota(principal: Principal, bytes: Option<u64>) {
+    QUOTAS.with(|quotas| {
+        let mut quotas = quotas.borrow_mut();
+        match bytes {
+            Some(bytes) => quotas.insert(principal, bytes),
+            None => quotas.remove(&principal),
+        };
+    });
+}
+
+pub fn usage(principal: Principal, dataset_count: u32, used_bytes: u64) -> StorageUsage {
+    let quota_bytes = quota_for(principal);
+    StorageUsage {
+        principal,
+        dataset_count,
+        used_bytes,
+        quota_bytes,
+        remaining_bytes: quota_bytes.saturating_sub(used_bytes),
+    }
+}
+
+/// Reject an upload of `incoming` bytes that would exceed the quota
+pub fn check(usage: &StorageUsage, incoming: u64) -> Result<(), String> {
+    let total = usage.used_bytes.saturating_add(incoming);
+    if total > usage.quota_bytes {
+        return Err(format!(
+            "Upload of {} bytes exceeds storage quota: {} of {} bytes already used",
+            incoming, usage.used_bytes, usage.quota_bytes
+        ));
+    }
+    Ok(())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(DEFAULT_QUOTA.with(Cell::get), backup::cloned(&QUOTAS)))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (default_quota, quotas) = backup::decode(bytes)?;
+    DEFAULT_QUOTA.with(|cell| cell.set(default_quota));
+    backup::replace(&QUOTAS, quotas);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "storage_quota_test.rs"]
+mod tests;
@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn uploads_within_quota_are_accepted() {
+    let principal = Principal::from_slice(&[1]);
+    set_quota(principal, Some(1_000));
+    let current = usage(principal, 2, 600);
+    assert_eq!(current.remaining_bytes, 400);
+    assert!(check(&current, 400).is_ok());
+    assert!(check(&current, 401).is_err());
+}
+
+#[test]
+fn clearing_override_falls_back_to_default() {
+    let principal = Principal::from_slice(&[2]);
+    set_quota(principal, Some(10));
+    assert_eq!(quota_for(principal), 10);
+    set_quota(principal, None);
+    assert_eq!(quota_for(principal), DEFAULT_QUOTA_BYTES);
+}
+
+#[test]
+fn usage_over_quota_reports_no_remaining_space() {
+    let principal = Principal::from_slice(&[3]);
+    set_quota(principal, Some(100));
+    let current = usage(principal, 1, 150);
+    assert_eq!(current.remaining_bytes, 0);
+    assert!(check(&current, 0).is_err());
+}
@@ -0,0 +1,131 @@
+//! Completion callbacks to other canisters
+//!
+//! Canisters subscribe to a computation and receive a one-way inter-canister
+//! notification when it completes or fails, instead of polling for its
+//! status. The notification carries the outcome only; results are still
+//! fetched and decrypted by an entitled principal.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use std::cell::RefCell;
+use crate::backup;
+
+pub const MAX_SUBSCRIPTIONS_PER_COMPUTATION: usize = 10;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ComputationSubscription {
+    pub request_id: String,
+    pub subscriber: Principal,
+    pub callback_canister: Principal,
+    pub method: String,
+    pub created_at: u64,
+    pub last_notified_status: Option<String>,
+    pub last_notified_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Argument passed to the subscriber's callback method
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ComputationNotification {
+    pub request_id: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static SUBSCRIPTIONS: RefCell<HashMap<String, Vec<ComputationSubscription>>> = RefCell::new(HashMap::new());
+}
+
+pub fn is_terminal(status: &str) -> bool {
+    matches!(status, "completed" | "failed")
+}
+
+/// Whether a subscription last told about `last` must be told about `status`
+pub fn should_notify(last: Option<&str>, status: &str) -> bool {
+    is_terminal(status) && last != Some(status)
+}
+
+pub fn subscribe(subscription: ComputationSubscription) -> Result<(), String> {
+    if subscription.method.trim().is_empty() {
+        return Err("Callback method cannot be empty".to_string());
+    }
+    SUBSCRIPTIONS.with(|subscriptions| {
+        let mut subscriptions = subscriptions.borrow_mut();
+        let existing = subscriptions.entry(subscription.request_id.clone()).or_default();
+        if existing.iter().any(|s| s.callback_canister == subscription.callback_canister && s.method == subscription.method) {
+            return Err("This callback is already subscribed".to_string());
+        }
+        if existing.len() >= MAX_SUBSCRIPTIONS_PER_COMPUTATION {
+            return Err(format!("A computation can have at most {} subscriptions", MAX_SUBSCRIPTIONS_PER_COMPUTATION));
+        }
+        existing.push(subscription);
+        Ok(())
+    })
+}
+
+/// Remove a callback; only the principal that subscribed it may do so
+pub fn unsubscribe(request_id: &str, subscriber: Principal, callback_canister: Principal, method: &str) -> Result<(), String> {
+    SUBSCRIPTIONS.with(|subscriptions| {
+        let mut subscriptions = subscriptions.borrow_mut();
+        let existing = subscriptions.get_mut(request_id).ok_or_else(|| "Subscription not found".to_string())?;
+        let index = existing.iter()
+            .position(|s| s.subscriber == subscriber && s.callback_canister == callback_canister && s.method == method)
+            .ok_or_else(|| "Subscription not found".to_string())?;
+        existing.remove(index);
+        Ok(())
+    })
+}
+
+pub fn subscriptions_of(request_id: &str, subscriber: Principal) -> Vec<ComputationSubscription> {
+    SUBSCRIPTIONS.with(|subscriptions| {
+        subscriptions.borrow().get(request_id)
+            .map(|s| s.iter().filter(|s| s.subscriber == subscriber).cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+/// Notify subscribers of a computation that reached a terminal status they
+/// have not been told about. Leaving a terminal status (a retry) re-arms them.
+pub fn status_changed(request_id: &str, status: &str, error: Option<String>, now: u64) {
+    SUBSCRIPTIONS.with(|subscriptions| {
+        let mut subscriptions = subscriptions.borrow_mut();
+        let Some(existing) = subscriptions.get_mut(request_id) else {
+            return;
+        };
+        for subscription in existing.iter_mut() {
+            if !is_terminal(status) {
+                subscription.last_notified_status = None;
+                continue;
+            }
+            if !should_notify(subscription.last_notified_status.as_deref(), status) {
+                continue;
+            }
+            let notification = ComputationNotification {
+                request_id: request_id.to_string(),
+                status: status.to_string(),
+                error: error.clone(),
+                timestamp: now,
+            };
+            subscription.last_error = ic_cdk::notify(subscription.callback_canister, &subscription.method, (notification,))
+                .err()
+                .map(|code| format!("Notification could not be enqueued: {:?}", code));
+            subscription.last_notified_status = Some(status.to_string());
+            subscription.last_notified_at = Some(now);
+        }
+    });
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&SUBSCRIPTIONS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let subscriptions = backup::decode(bytes)?;
+    backup::replace(&SUBSCRIPTIONS, subscriptions);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "subscriptions_test.rs"]
+mod tests;
@@ -0,0 +1,44 @@
+use super::*;
+
+fn subscription(request_id: &str, method: &str) -> ComputationSubscription {
+    ComputationSubscription {
+        request_id: request_id.to_string(),
+        subscriber: Principal::from_slice(&[1]),
+        callback_canister: Principal::from_slice(&[2]),
+        method: method.to_string(),
+        created_at: 0,
+        last_notified_status: None,
+        last_notified_at: None,
+        last_error: None,
+    }
+}
+
+#[test]
+fn only_new_terminal_statuses_are_notified() {
+    assert!(should_notify(None, "completed"));
+    assert!(should_notify(None, "failed"));
+    assert!(should_notify(Some("failed"), "completed"));
+    assert!(!should_notify(Some("completed"), "completed"));
+    assert!(!should_notify(None, "computing"));
+}
+
+#[test]
+fn duplicate_and_excess_subscriptions_are_rejected() {
+    subscribe(subscription("req_1", "on_done")).unwrap();
+    assert!(subscribe(subscription("req_1", "on_done")).is_err());
+    assert!(subscribe(subscription("req_1", " ")).is_err());
+
+    for i in 1..MAX_SUBSCRIPTIONS_PER_COMPUTATION {
+        subscribe(subscription("req_1", &format!("on_done_{}", i))).unwrap();
+    }
+    assert!(subscribe(subscription("req_1", "one_too_many")).is_err());
+}
+
+#[test]
+fn unsubscribe_requires_the_original_subscriber() {
+    subscribe(subscription("req_2", "on_done")).unwrap();
+    let other = Principal::from_slice(&[9]);
+    assert!(unsubscribe("req_2", other, Principal::from_slice(&[2]), "on_done").is_err());
+    unsubscribe("req_2", Principal::from_slice(&[1]), Principal::from_slice(&[2]), "on_done").unwrap();
+    assert!(subscriptions_of("req_2", Principal::from_slice(&[1])).is_empty());
+}
@@ -0,0 +1,137 @@
+//! Noisy per-dataset summaries for designing queries
+//!
+//! Any caller can see a dataset's schema alongside its record count, the
+//! values present in each column, the range of bounded numeric columns and
+//! the most frequent values of category and boolean columns, all under the
+//! Laplace mechanism with `SUMMARY_EPSILON` split evenly across them. A range
+//! end moves by at most the declared bounds when one record changes, which is
+//! its sensitivity. A value is only listed when its noisy count clears a
+//! threshold the noise exceeds with probability `LISTING_DELTA`, so values
+//! held by a few records do not show up at all. A summary is computed once
+//! per dataset version and charged to the dataset's privacy budget; later
+//! calls return the same summary until the dataset changes.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::analytics::{self, ColumnSpec, ColumnStatistics, ColumnType};
+use crate::differential_privacy::{noise, NoiseMechanism, NoiseSource};
+use crate::backup;
+
+/// Epsilon one summary spends from the dataset's budget
+pub const SUMMARY_EPSILON: f64 = 0.1;
+pub const MAX_LISTED_VALUES: usize = 5;
+/// Chance that a value held by a single record is listed
+pub const LISTING_DELTA: f64 = 1e-6;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ColumnSummary {
+    pub spec: ColumnSpec,
+    /// Records with a value in this column
+    pub values: u64,
+    /// Noisy smallest and largest values of bounded numeric columns
+    pub range: Option<(f64, f64)>,
+    /// Most frequent values with their noisy counts, most frequent first
+    pub top_values: Vec<(String, u64)>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DatasetSummary {
+    pub dataset_id: String,
+    /// Dataset version the summary describes
+    pub version: String,
+    pub records: u64,
+    pub columns: Vec<ColumnSummary>,
+    pub epsilon: f64,
+    pub generated_at: u64,
+}
+
+thread_local! {
+    static SUMMARIES: RefCell<HashMap<String, DatasetSummary>> = RefCell::new(HashMap::new());
+}
+
+/// Statistics sharing the summary's epsilon: the record count, and for each
+/// column its value count, both range ends and its value counts
+fn statistics(schema: &[ColumnSpec]) -> usize {
+    1 + schema.iter().map(|spec| match spec.column_type {
+        ColumnType::Number { bounds: Some(_), .. } => 3,
+        ColumnType::Category | ColumnType::Boolean => 2,
+        _ => 1,
+    }).sum::<usize>()
+}
+
+/// Noisy count a value needs to be listed when counts get `epsilon`
+pub fn listing_threshold(epsilon: f64) -> f64 {
+    1.0 + (1.0 / (2.0 * LISTING_DELTA)).ln() / epsilon
+}
+
+/// Summary of the dataset version `version` with contents `data`
+pub fn summarize(
+    dataset_id: &str,
+    version: &str,
+    schema: &[ColumnSpec],
+    data: &str,
+    source: &mut NoiseSource,
+    timestamp: u64,
+) -> Result<DatasetSummary, String> {
+    let combined = analytics::combine(schema, &[data.to_string()])?;
+    let analysis = combined.analysis;
+    let epsilon = SUMMARY_EPSILON / statistics(schema) as f64;
+    let mut noisy = |value: f64, sensitivity: f64| value + noise(source, NoiseMechanism::Laplace, sensitivity, epsilon, 0.0);
+    let count = |value: f64| value.round().max(0.0) as u64;
+    let threshold = listing_threshold(epsilon);
+
+    let records = count(noisy(analysis.total_records as f64, 1.0));
+    let columns = analysis.columns.into_iter().map(|column| {
+        let values = count(noisy((analysis.total_records - column.missing) as f64, 1.0));
+        let range = match (&column.spec.column_type, &column.statistics) {
+            (ColumnType::Number { bounds: Some((min, max)), .. }, ColumnStatistics::Numeric(stats)) if stats.count > 0 => {
+                let low = noisy(stats.min, max - min).clamp(*min, *max);
+                let high = noisy(stats.max, max - min).clamp(*min, *max);
+                Some((low.min(high), low.max(high)))
+            }
+            _ => None,
+        };
+        let mut top_values: Vec<(String, u64)> = match &column.statistics {
+            ColumnStatistics::Counts(counts) => counts.iter()
+                .map(|(value, n)| (value.clone(), count(noisy(*n as f64, 1.0))))
+                .filter(|(_, n)| *n as f64 >= threshold)
+                .collect(),
+            _ => Vec::new(),
+        };
+        top_values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_values.truncate(MAX_LISTED_VALUES);
+        ColumnSummary { spec: column.spec, values, range, top_values }
+    }).collect();
+
+    Ok(DatasetSummary {
+        dataset_id: dataset_id.to_string(),
+        version: version.to_string(),
+        records,
+        columns,
+        epsilon: SUMMARY_EPSILON,
+        generated_at: timestamp,
+    })
+}
+
+pub fn cached(version: &str) -> Option<DatasetSummary> {
+    SUMMARIES.with(|summaries| summaries.borrow().get(version).cloned())
+}
+
+pub fn store(summary: DatasetSummary) {
+    SUMMARIES.with(|summaries| summaries.borrow_mut().insert(summary.version.clone(), summary));
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&SUMMARIES))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let summaries = backup::decode(bytes)?;
+    backup::replace(&SUMMARIES, summaries);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "summary_test.rs"]
+mod tests;
@@ -0,0 +1,47 @@
+use super::*;
+use crate::analytics::parse_schema;
+
+const SCHEMA: &str = "patient_id:id,age:number[0..120],diagnosis:category,hba1c:number";
+
+fn dataset(rows: usize) -> String {
+    let diagnoses = ["diabetes", "hypertension", "asthma", "copd", "obesity", "anemia", "gout"];
+    let mut lines = vec!["patient_id,age,diagnosis,hba1c".to_string()];
+    for i in 0..rows {
+        lines.push(format!("p{},{},{},{}", i, 20 + i % 60, diagnoses[i % diagnoses.len()], 5 + i % 4));
+    }
+    lines.join("\n")
+}
+
+fn column<'a>(summary: &'a DatasetSummary, name: &str) -> &'a ColumnSummary {
+    summary.columns.iter().find(|c| c.spec.name == name).unwrap()
+}
+
+#[test]
+fn summarizes_each_column_by_type() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let summary = summarize("d", "d@1", &schema, &dataset(14_000), &mut source, 5).unwrap();
+    assert_eq!((summary.version.as_str(), summary.epsilon), ("d@1", SUMMARY_EPSILON));
+    assert!(summary.records.abs_diff(14_000) < 1000);
+
+    let (low, high) = column(&summary, "age").range.unwrap();
+    assert!((0.0..=high).contains(&low) && high <= 120.0);
+    // Unbounded numbers and identifiers have no range to release
+    assert!(column(&summary, "hba1c").range.is_none());
+    assert!(column(&summary, "patient_id").range.is_none() && column(&summary, "patient_id").top_values.is_empty());
+
+    let top = &column(&summary, "diagnosis").top_values;
+    assert!(!top.is_empty() && top.len() <= MAX_LISTED_VALUES);
+    assert!(top.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn rare_values_are_not_listed() {
+    let schema = parse_schema("diagnosis:category").unwrap();
+    let mut source = NoiseSource::new(b"seed".to_vec());
+    let data = "diagnosis\nrare\ncommon\n".to_string() + &vec!["common"; 5000].join("\n");
+    let summary = summarize("d", "d@1", &schema, &data, &mut source, 5).unwrap();
+    let top = &column(&summary, "diagnosis").top_values;
+    assert_eq!(top.iter().map(|(value, _)| value.as_str()).collect::<Vec<_>>(), vec!["common"]);
+    assert!(top[0].1 as f64 >= listing_threshold(SUMMARY_EPSILON / 3.0));
+}
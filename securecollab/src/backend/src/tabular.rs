@@ -0,0 +1,308 @@
+//! Tabular parsing and writing for uploaded datasets
+//!
+//! Datasets are RFC 4180 CSV with a header row: fields may be quoted and
+//! contain commas, quotes or line breaks, a leading UTF-8 byte order mark is
+//! ignored, fields are trimmed and blank lines skipped. Rows that are not
+//! UTF-8 or do not have one field per header column are left out and
+//! reported by line, and the report of each upload is kept for its owner.
+//!
+//! Uploads may also be JSON Lines, detected by a leading `{`: one flat
+//! object per line, whose keys become the columns, ordered by name. Missing
+//! keys and nulls are empty values; lines that are not objects, or hold
+//! nested values, are reported like malformed rows.
+//!
+//! Parquet files, detected by their `PAR1` magic, are read row by row with
+//! their top-level fields as columns; rows holding nested or binary values
+//! are reported by row number. The type of each Parquet column is kept so
+//! schema inference does not have to guess it from the text. Uploads are
+//! stored as CSV, so everything reading a dataset sees one format.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use bytes::Bytes;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use crate::analytics::{ColumnType, UploadValidation};
+use crate::backup;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DataFormat {
+    Csv,
+    JsonLines,
+    Parquet,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RowError {
+    /// Line the row starts on, a CSV header being line 1, or Parquet row
+    /// number
+    pub line: u64,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Line each row starts on
+    pub lines: Vec<u64>,
+    pub errors: Vec<RowError>,
+    /// Column types declared by the file, for typed formats
+    pub types: Vec<Option<ColumnType>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct UploadReport {
+    pub dataset_id: String,
+    pub validation: UploadValidation,
+}
+
+thread_local! {
+    static REPORTS: RefCell<HashMap<String, UploadReport>> = RefCell::new(HashMap::new());
+}
+
+/// Every record with the line it starts on, and the rows that could not be read
+fn read(data: &[u8]) -> (Vec<(u64, Vec<String>)>, Vec<RowError>) {
+    let data = data.strip_prefix(BOM).unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let (mut records, mut errors) = (Vec::new(), Vec::new());
+    for result in reader.records() {
+        match result {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line());
+                records.push((line, record.iter().map(str::to_string).collect()));
+            }
+            Err(e) => errors.push(RowError {
+                line: e.position().map_or(0, |p| p.line()),
+                message: e.to_string(),
+            }),
+        }
+    }
+    (records, errors)
+}
+
+pub fn detect(data: &[u8]) -> DataFormat {
+    if data.starts_with(b"PAR1") {
+        return DataFormat::Parquet;
+    }
+    let data = data.strip_prefix(BOM).unwrap_or(data);
+    match data.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') => DataFormat::JsonLines,
+        _ => DataFormat::Csv,
+    }
+}
+
+fn json_field(value: &serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::Null => Ok(String::new()),
+        serde_json::Value::String(s) => Ok(s.trim().to_string()),
+        serde_json::Value::Bool(b) => Ok(b.to_string()),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        _ => Err("Nested values are not supported".to_string()),
+    }
+}
+
+fn parse_json_lines(data: &[u8]) -> Result<Table, String> {
+    let data = data.strip_prefix(BOM).unwrap_or(data);
+    let text = std::str::from_utf8(data).map_err(|e| format!("JSON Lines must be UTF-8: {}", e))?;
+    let mut header: Vec<String> = Vec::new();
+    let (mut objects, mut lines, mut errors) = (Vec::new(), Vec::new(), Vec::new());
+    for (i, text) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let line = i as u64 + 1;
+        let object = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Object(object)) => object,
+            Ok(_) => {
+                errors.push(RowError { line, message: "Expected a JSON object".to_string() });
+                continue;
+            }
+            Err(e) => {
+                errors.push(RowError { line, message: e.to_string() });
+                continue;
+            }
+        };
+        let fields: Result<Vec<(String, String)>, String> = object.iter()
+            .map(|(key, value)| Ok((key.trim().to_string(), json_field(value)?)))
+            .collect();
+        match fields {
+            Ok(fields) => {
+                for (key, _) in &fields {
+                    if !header.contains(key) {
+                        header.push(key.clone());
+                    }
+                }
+                objects.push(fields);
+                lines.push(line);
+            }
+            Err(message) => errors.push(RowError { line, message }),
+        }
+    }
+    if header.is_empty() {
+        return Err("Dataset is empty".to_string());
+    }
+    header.sort();
+    let types = vec![None; header.len()];
+    let rows = objects.into_iter()
+        .map(|fields| header.iter().map(|column| {
+            fields.iter().find(|(key, _)| key == column).map_or(String::new(), |(_, value)| value.clone())
+        }).collect())
+        .collect();
+    Ok(Table { header, rows, lines, errors, types })
+}
+
+/// Text of a Parquet value and the column type it implies; strings imply
+/// none, their values are left to inference
+fn parquet_field(field: &Field) -> Result<(String, Option<ColumnType>), String> {
+    let integer = Some(ColumnType::Number { bounds: None, integer: true });
+    let number = Some(ColumnType::Number { bounds: None, integer: false });
+    Ok(match field {
+        Field::Null => (String::new(), None),
+        Field::Str(s) => (s.trim().to_string(), None),
+        Field::Bool(b) => (b.to_string(), Some(ColumnType::Boolean)),
+        Field::Byte(_) | Field::Short(_) | Field::Int(_) | Field::Long(_)
+        | Field::UByte(_) | Field::UShort(_) | Field::UInt(_) | Field::ULong(_) => (field.to_string(), integer),
+        Field::Float16(_) | Field::Float(_) | Field::Double(_) | Field::Decimal(_) => (field.to_string(), number),
+        Field::Date(_) => (field.to_string(), Some(ColumnType::Date)),
+        Field::TimestampMillis(_) | Field::TimestampMicros(_) => (field.to_string(), Some(ColumnType::Text)),
+        Field::Bytes(_) => return Err("Binary values are not supported".to_string()),
+        Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => return Err("Nested values are not supported".to_string()),
+    })
+}
+
+fn parse_parquet(data: &[u8]) -> Result<Table, String> {
+    let reader = SerializedFileReader::new(Bytes::copy_from_slice(data))
+        .map_err(|e| format!("Unreadable Parquet file: {}", e))?;
+    let header: Vec<String> = reader.metadata().file_metadata().schema_descr().root_schema().get_fields().iter()
+        .map(|field| field.name().to_string())
+        .collect();
+    if header.is_empty() {
+        return Err("Dataset is empty".to_string());
+    }
+    let mut types = vec![None; header.len()];
+    let (mut rows, mut lines, mut errors) = (Vec::new(), Vec::new(), Vec::new());
+    let records = reader.get_row_iter(None).map_err(|e| format!("Unreadable Parquet file: {}", e))?;
+    for (i, record) in records.enumerate() {
+        let line = i as u64 + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(RowError { line, message: e.to_string() });
+                continue;
+            }
+        };
+        let mut row = vec![String::new(); header.len()];
+        let fields = record.get_column_iter().try_for_each(|(name, field)| {
+            let (value, column_type) = parquet_field(field)?;
+            if let Some(c) = header.iter().position(|h| h == name) {
+                row[c] = value;
+                if types[c].is_none() {
+                    types[c] = column_type;
+                }
+            }
+            Ok::<_, String>(())
+        });
+        match fields {
+            Ok(()) => {
+                rows.push(row);
+                lines.push(line);
+            }
+            Err(message) => errors.push(RowError { line, message }),
+        }
+    }
+    Ok(Table { header, rows, lines, errors, types })
+}
+
+/// Header and well-formed rows of a CSV, JSON Lines or Parquet payload
+pub fn parse(data: &[u8]) -> Result<Table, String> {
+    match detect(data) {
+        DataFormat::Csv => parse_csv(data),
+        DataFormat::JsonLines => parse_json_lines(data),
+        DataFormat::Parquet => parse_parquet(data),
+    }
+}
+
+/// An upload as the CSV datasets are stored as
+pub fn normalize(data: Vec<u8>) -> Result<Vec<u8>, String> {
+    match detect(&data) {
+        DataFormat::Csv => Ok(data),
+        DataFormat::JsonLines | DataFormat::Parquet => {
+            let table = parse(&data)?;
+            Ok(write(std::iter::once(table.header).chain(table.rows)).into_bytes())
+        }
+    }
+}
+
+fn parse_csv(data: &[u8]) -> Result<Table, String> {
+    let (records, mut errors) = read(data);
+    let mut records = records.into_iter();
+    let (_, header) = records.next().ok_or("Dataset is empty")?;
+    if let Some(error) = errors.iter().find(|e| e.line <= 1) {
+        return Err(format!("Unreadable header: {}", error.message));
+    }
+    let (mut rows, mut lines) = (Vec::new(), Vec::new());
+    for (line, row) in records {
+        if row.len() == header.len() {
+            rows.push(row);
+            lines.push(line);
+        } else {
+            errors.push(RowError { line, message: format!("Expected {} fields, found {}", header.len(), row.len()) });
+        }
+    }
+    errors.sort_by_key(|e| e.line);
+    let types = vec![None; header.len()];
+    Ok(Table { header, rows, lines, errors, types })
+}
+
+/// Header and every data row, whatever its width
+pub fn records(data: &[u8]) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let mut records = read(data).0.into_iter().map(|(_, record)| record);
+    let header = records.next().ok_or("Dataset is empty")?;
+    Ok((header, records.collect()))
+}
+
+/// First field of every record of a single-column payload
+pub fn column(data: &[u8]) -> Vec<String> {
+    read(data).0.into_iter().filter_map(|(_, record)| record.into_iter().next()).collect()
+}
+
+/// CSV of `records`, quoting fields where needed, without a final line break
+pub fn write<S: AsRef<str>>(records: impl IntoIterator<Item = Vec<S>>) -> String {
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+    for record in records {
+        writer.write_record(record.iter().map(|field| field.as_ref())).expect("writing to memory cannot fail");
+    }
+    let bytes = writer.into_inner().expect("writing to memory cannot fail");
+    let mut csv = String::from_utf8(bytes).expect("fields are UTF-8");
+    if csv.ends_with('\n') {
+        csv.pop();
+    }
+    csv
+}
+
+pub fn record_report(report: UploadReport) {
+    REPORTS.with(|r| r.borrow_mut().insert(report.dataset_id.clone(), report));
+}
+
+pub fn report(dataset_id: &str) -> Option<UploadReport> {
+    REPORTS.with(|r| r.borrow().get(dataset_id).cloned())
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&REPORTS))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let reports = backup::decode(bytes)?;
+    backup::replace(&REPORTS, reports);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "tabular_test.rs"]
+mod tests;
@@ -0,0 +1,109 @@
+use super::*;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::sync::Arc;
+
+fn parquet_file() -> Vec<u8> {
+    let schema = parse_message_type("message patients {
+        REQUIRED BYTE_ARRAY id (UTF8);
+        REQUIRED INT32 age;
+        OPTIONAL DOUBLE bmi;
+        REQUIRED BOOLEAN smoker;
+        REQUIRED INT32 visit (DATE);
+    }").unwrap();
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(&mut buffer, Arc::new(schema), Arc::new(WriterProperties::builder().build())).unwrap();
+    let mut group = writer.next_row_group().unwrap();
+    let mut column = group.next_column().unwrap().unwrap();
+    column.typed::<ByteArrayType>().write_batch(&[ByteArray::from("p1"), ByteArray::from("p2")], None, None).unwrap();
+    column.close().unwrap();
+    column = group.next_column().unwrap().unwrap();
+    column.typed::<Int32Type>().write_batch(&[30, 41], None, None).unwrap();
+    column.close().unwrap();
+    column = group.next_column().unwrap().unwrap();
+    column.typed::<DoubleType>().write_batch(&[21.5], Some(&[1, 0]), None).unwrap();
+    column.close().unwrap();
+    column = group.next_column().unwrap().unwrap();
+    column.typed::<BoolType>().write_batch(&[true, false], None, None).unwrap();
+    column.close().unwrap();
+    column = group.next_column().unwrap().unwrap();
+    // Days since the epoch
+    column.typed::<Int32Type>().write_batch(&[19753, 19782], None, None).unwrap();
+    column.close().unwrap();
+    group.close().unwrap();
+    writer.close().unwrap();
+    buffer
+}
+
+#[test]
+fn parses_quoted_fields_and_ignores_the_bom() {
+    let data = b"\xEF\xBB\xBFid,note,city\n1,\"Smith, J.\",Boston\n\n2,\"said \"\"hi\"\"\n twice\", Paris \n";
+    let table = parse(data).unwrap();
+    assert_eq!(table.header, vec!["id", "note", "city"]);
+    assert_eq!(table.rows, vec![
+        vec!["1".to_string(), "Smith, J.".to_string(), "Boston".to_string()],
+        vec!["2".to_string(), "said \"hi\"\n twice".to_string(), "Paris".to_string()],
+    ]);
+    assert!(table.errors.is_empty());
+}
+
+#[test]
+fn reports_rows_it_leaves_out() {
+    let table = parse(b"a,b\n1,2\n3\n4,5,6\n7,\xff\n8,9").unwrap();
+    assert_eq!(table.rows.len(), 2);
+    let lines: Vec<u64> = table.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![3, 4, 5]);
+    assert_eq!(table.errors[0].message, "Expected 2 fields, found 1");
+
+    assert!(parse(b"").is_err());
+    assert!(parse(b"\xff\xfe\n1").is_err());
+}
+
+#[test]
+fn written_csv_reads_back() {
+    let records = vec![vec!["name", "note"], vec!["a", "x, y"], vec!["b", ""]];
+    let csv = write(records.clone());
+    assert!(!csv.ends_with('\n'));
+    let (header, rows) = super::records(csv.as_bytes()).unwrap();
+    assert_eq!(header, vec!["name", "note"]);
+    assert_eq!(rows[0][1], "x, y");
+    assert_eq!(column(write(vec![vec![""], vec!["z"]]).as_bytes()), vec!["", "z"]);
+}
+
+#[test]
+fn json_lines_become_the_same_table() {
+    let data = b"{\"id\": \"p1\", \"age\": 30, \"smoker\": true}\n\n{\"age\": 41.5, \"id\": \"p2\", \"city\": null}\n[1, 2]\n{\"id\": \"p3\", \"tags\": [\"a\"]}\nnot json\n";
+    assert_eq!(detect(data), DataFormat::JsonLines);
+    let table = parse(data).unwrap();
+    assert_eq!(table.header, vec!["age", "city", "id", "smoker"]);
+    assert_eq!(table.rows[1], vec!["41.5", "", "p2", ""]);
+    assert_eq!(table.lines, vec![1, 3]);
+    let lines: Vec<u64> = table.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![4, 5, 6]);
+
+    let csv = normalize(data.to_vec()).unwrap();
+    assert_eq!(detect(&csv), DataFormat::Csv);
+    assert_eq!(parse(&csv).unwrap().rows, table.rows);
+    assert_eq!(normalize(b"a\n1".to_vec()).unwrap(), b"a\n1");
+}
+
+#[test]
+fn parquet_columns_keep_their_types() {
+    let data = parquet_file();
+    assert_eq!(detect(&data), DataFormat::Parquet);
+    let table = parse(&data).unwrap();
+    assert_eq!(table.header, vec!["id", "age", "bmi", "smoker", "visit"]);
+    assert_eq!(table.rows, vec![
+        vec!["p1", "30", "21.5", "true", "2024-01-31"],
+        vec!["p2", "41", "", "false", "2024-02-29"],
+    ]);
+    assert_eq!(table.types[0], None);
+    assert_eq!(table.types[1], Some(ColumnType::Number { bounds: None, integer: true }));
+    assert_eq!(table.types[4], Some(ColumnType::Date));
+
+    let csv = normalize(data).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "id,age,bmi,smoker,visit\np1,30,21.5,true,2024-01-31\np2,41,,false,2024-02-29");
+    assert!(parse(b"PAR1 truncated").is_err());
+}
@@ -0,0 +1,94 @@
+//! Dataset tags and search
+//!
+//! Owners tag their datasets with free-form tags or with terms from the
+//! controlled vocabulary administrators maintain, such as `cardiology` or
+//! `claims`. Tags are lowercase, with runs of spaces written as `-`, so the
+//! same tag typed differently matches. Search keeps the datasets holding
+//! every requested tag whose name, party, schema, metadata or tags contain
+//! the search text, ignoring case.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use crate::PrivateDataSource;
+use crate::backup;
+
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_LENGTH: usize = 64;
+
+thread_local! {
+    static VOCABULARY: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+pub fn normalize(tag: &str) -> Result<String, String> {
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase();
+    if tag.is_empty() {
+        return Err("Tags cannot be blank".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!("Tag '{}' is longer than {} characters", tag, MAX_TAG_LENGTH));
+    }
+    if let Some(c) = tag.chars().find(|c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))) {
+        return Err(format!("Tag '{}' cannot contain '{}'", tag, c));
+    }
+    Ok(tag)
+}
+
+/// Normalized tags without duplicates, in the order given
+pub fn normalize_all(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = normalize(tag)?;
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A dataset can have at most {} tags", MAX_TAGS));
+    }
+    Ok(normalized)
+}
+
+pub fn set_vocabulary(terms: &[String]) -> Result<Vec<String>, String> {
+    let terms: BTreeSet<String> = terms.iter().map(|term| normalize(term)).collect::<Result<_, _>>()?;
+    VOCABULARY.with(|v| *v.borrow_mut() = terms);
+    Ok(vocabulary())
+}
+
+pub fn vocabulary() -> Vec<String> {
+    VOCABULARY.with(|v| v.borrow().iter().cloned().collect())
+}
+
+fn contains_text(dataset: &PrivateDataSource, text: &str) -> bool {
+    let metadata = &dataset.metadata;
+    let fields = [&metadata.steward_contact, &metadata.license, &metadata.data_use_agreement, &metadata.geography];
+    [&dataset.name, &dataset.party_name, &dataset.schema].into_iter()
+        .chain(fields.into_iter().flatten())
+        .chain(&dataset.tags)
+        .any(|field| field.to_lowercase().contains(text))
+}
+
+/// Datasets with all of `tags` matching `text`, by name
+pub fn search(datasets: impl IntoIterator<Item = PrivateDataSource>, tags: &[String], text: &str) -> Result<Vec<PrivateDataSource>, String> {
+    let tags = normalize_all(tags)?;
+    let text = text.trim().to_lowercase();
+    let mut found: Vec<PrivateDataSource> = datasets.into_iter()
+        .filter(|d| tags.iter().all(|tag| d.tags.contains(tag)))
+        .filter(|d| text.is_empty() || contains_text(d, &text))
+        .collect();
+    found.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+    Ok(found)
+}
+
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&backup::cloned(&VOCABULARY))
+}
+
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let vocabulary = backup::decode(bytes)?;
+    backup::replace(&VOCABULARY, vocabulary);
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "tags_test.rs"]
+mod tests;
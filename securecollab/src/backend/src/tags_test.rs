@@ -0,0 +1,49 @@
+use super::*;
+use candid::Principal;
+use crate::metadata::DatasetMetadata;
+
+fn dataset(id: &str, name: &str, tags: &[&str], geography: Option<&str>) -> PrivateDataSource {
+    PrivateDataSource {
+        id: id.to_string(),
+        owner: Principal::anonymous(),
+        party_name: "Boston General".to_string(),
+        name: name.to_string(),
+        encrypted_data: vec![],
+        vetkey_id: String::new(),
+        schema: "age:number,diagnosis:category".to_string(),
+        record_count: 0,
+        created_at: 0,
+        access_permissions: vec![],
+        metadata: DatasetMetadata { geography: geography.map(str::to_string), ..Default::default() },
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        allowed_purposes: None,
+    }
+}
+
+fn names(found: &[PrivateDataSource]) -> Vec<&str> {
+    found.iter().map(|d| d.name.as_str()).collect()
+}
+
+#[test]
+fn normalizes_tags() {
+    assert_eq!(normalize("  Heart  Failure ").unwrap(), "heart-failure");
+    assert!(normalize(" ").is_err());
+    assert!(normalize("a/b").is_err());
+    let tags = normalize_all(&["EHR".to_string(), "ehr".to_string(), "claims".to_string()]).unwrap();
+    assert_eq!(tags, vec!["ehr", "claims"]);
+}
+
+#[test]
+fn searches_by_tags_and_text() {
+    let datasets = vec![
+        dataset("1", "Cohort B", &["cardiology", "ehr"], Some("Massachusetts")),
+        dataset("2", "Cohort A", &["cardiology"], None),
+        dataset("3", "Claims", &["claims"], Some("Ohio")),
+    ];
+    let found = search(datasets.clone(), &["Cardiology".to_string()], "").unwrap();
+    assert_eq!(names(&found), vec!["Cohort A", "Cohort B"]);
+    assert_eq!(names(&search(datasets.clone(), &["cardiology".to_string(), "ehr".to_string()], "").unwrap()), vec!["Cohort B"]);
+    assert_eq!(names(&search(datasets.clone(), &[], "massachusetts").unwrap()), vec!["Cohort B"]);
+    assert_eq!(names(&search(datasets.clone(), &[], "DIAGNOSIS").unwrap()).len(), 3);
+    assert!(search(datasets, &["oncology".to_string()], "").unwrap().is_empty());
+}
@@ -0,0 +1,8 @@
+//! Fixtures shared by the unit tests
+
+use candid::Principal;
+
+/// A distinct, valid principal for each byte
+pub fn principal(byte: u8) -> Principal {
+    Principal::from_slice(&[byte; 29])
+}
@@ -0,0 +1,70 @@
+//! Derived keys encrypted to their requester
+//!
+//! Keys never cross the wire in plaintext. The caller supplies a transport
+//! public key, a compressed Ristretto255 point `T = t·G` whose secret `t`
+//! it keeps, and receives the key encrypted to it, as vetKD does. The
+//! canister picks an ephemeral scalar `e` and returns `E = e·G` followed by
+//! the key XORed with SHA-256 blocks over the shared point `e·T`, `E` and a
+//! block counter. The caller recomputes the shared point as `t·E`.
+
+use candid::{CandidType, Deserialize};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use sha2::{Digest, Sha256, Sha512};
+
+pub const TRANSPORT_KEY_LENGTH: usize = 32;
+
+/// A derived key encrypted to the requester's transport public key
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct WrappedKey {
+    pub key_id: String,
+    /// `E` followed by the encrypted key
+    pub encrypted_key: Vec<u8>,
+    pub expires_at: Option<u64>,
+}
+
+fn transport_point(transport_public_key: &[u8]) -> Result<RistrettoPoint, String> {
+    let point = CompressedRistretto::from_slice(transport_public_key)
+        .ok()
+        .and_then(|compressed| compressed.decompress())
+        .ok_or_else(|| format!("The transport public key must be a {}-byte Ristretto255 point", TRANSPORT_KEY_LENGTH))?;
+    if point == RistrettoPoint::identity() {
+        return Err("The transport public key cannot be the identity point".to_string());
+    }
+    Ok(point)
+}
+
+// Bytes XORed with the key, from the shared point and the ephemeral point
+fn keystream(shared: &RistrettoPoint, ephemeral: &CompressedRistretto, length: usize) -> Vec<u8> {
+    let shared = shared.compress();
+    (0..length.div_ceil(32) as u32)
+        .flat_map(|block| {
+            Sha256::new()
+                .chain_update(b"securecollab-transport")
+                .chain_update(shared.as_bytes())
+                .chain_update(ephemeral.as_bytes())
+                .chain_update(block.to_be_bytes())
+                .finalize()
+        })
+        .take(length)
+        .collect()
+}
+
+/// Encrypt `key` to a transport public key, with the ephemeral scalar drawn
+/// from `seed`
+pub fn wrap(key: &[u8], transport_public_key: &[u8], seed: &[u8]) -> Result<Vec<u8>, String> {
+    let recipient = transport_point(transport_public_key)?;
+    let ephemeral = Scalar::hash_from_bytes::<Sha512>(seed);
+    let ephemeral_point = (RISTRETTO_BASEPOINT_POINT * ephemeral).compress();
+    let stream = keystream(&(recipient * ephemeral), &ephemeral_point, key.len());
+
+    let mut wrapped = ephemeral_point.as_bytes().to_vec();
+    wrapped.extend(key.iter().zip(stream).map(|(k, s)| k ^ s));
+    Ok(wrapped)
+}
+
+#[cfg(test)]
+#[path = "transport_key_test.rs"]
+mod tests;
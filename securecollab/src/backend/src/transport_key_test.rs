@@ -0,0 +1,44 @@
+use super::*;
+
+fn transport_key(byte: u8) -> (Scalar, Vec<u8>) {
+    let secret = Scalar::from_bytes_mod_order([byte; 32]);
+    (secret, (RISTRETTO_BASEPOINT_POINT * secret).compress().as_bytes().to_vec())
+}
+
+// What the requester does with its transport secret
+fn unwrap(wrapped: &[u8], secret: Scalar) -> Vec<u8> {
+    let ephemeral = CompressedRistretto::from_slice(&wrapped[..32]).unwrap();
+    let shared = ephemeral.decompress().unwrap() * secret;
+    let stream = keystream(&shared, &ephemeral, wrapped.len() - 32);
+    wrapped[32..].iter().zip(stream).map(|(c, s)| c ^ s).collect()
+}
+
+#[test]
+fn only_the_transport_secret_recovers_the_key() {
+    let key = b"derived_key_for_an_agent_that_spans_two_blocks_of_stream".to_vec();
+    let (secret, public) = transport_key(7);
+    let wrapped = wrap(&key, &public, b"seed").unwrap();
+    assert_eq!(wrapped.len(), 32 + key.len());
+    assert!(!wrapped.windows(key.len()).any(|w| w == key.as_slice()));
+    assert_eq!(unwrap(&wrapped, secret), key);
+
+    let (other_secret, _) = transport_key(8);
+    assert_ne!(unwrap(&wrapped, other_secret), key);
+}
+
+#[test]
+fn fresh_randomness_gives_fresh_ciphertexts() {
+    let (secret, public) = transport_key(7);
+    let first = wrap(b"key", &public, b"seed-1").unwrap();
+    let second = wrap(b"key", &public, b"seed-2").unwrap();
+    assert_ne!(first, second);
+    assert_eq!(unwrap(&second, secret), b"key");
+}
+
+#[test]
+fn rejects_malformed_transport_keys() {
+    assert!(wrap(b"key", &[1; 16], b"seed").is_err());
+    assert!(wrap(b"key", &[0xff; 32], b"seed").is_err());
+    let identity = RistrettoPoint::identity().compress().as_bytes().to_vec();
+    assert!(wrap(b"key", &identity, b"seed").is_err());
+}
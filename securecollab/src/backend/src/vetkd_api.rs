@@ -0,0 +1,69 @@
+//! The management canister's vetKD API
+//!
+//! Used by the vetKD endpoints when the configuration selects real vetKD
+//! instead of the local mock. Keys are derived under this canister's own
+//! context, and the caller decrypts them with its transport secret.
+
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::call::{call, call_with_payment128};
+
+pub const CONTEXT: &[u8] = b"securecollab";
+// Covers `key_1` on mainnet; the unused part is refunded
+const DERIVE_KEY_CYCLES: u128 = 54_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum VetKdCurve {
+    #[serde(rename = "bls12_381_g2")]
+    Bls12381G2,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct VetKdKeyId {
+    curve: VetKdCurve,
+    name: String,
+}
+
+#[derive(CandidType, Deserialize)]
+struct PublicKeyArgs {
+    canister_id: Option<Principal>,
+    context: Vec<u8>,
+    key_id: VetKdKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct PublicKeyReply {
+    public_key: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct DeriveKeyArgs {
+    input: Vec<u8>,
+    context: Vec<u8>,
+    transport_public_key: Vec<u8>,
+    key_id: VetKdKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct DeriveKeyReply {
+    encrypted_key: Vec<u8>,
+}
+
+fn key_id(key_name: &str) -> VetKdKeyId {
+    VetKdKeyId { curve: VetKdCurve::Bls12381G2, name: key_name.to_string() }
+}
+
+pub async fn public_key(key_name: &str) -> Result<Vec<u8>, String> {
+    let args = PublicKeyArgs { canister_id: None, context: CONTEXT.to_vec(), key_id: key_id(key_name) };
+    let (reply,): (PublicKeyReply,) = call(Principal::management_canister(), "vetkd_public_key", (args,))
+        .await
+        .map_err(|(code, msg)| format!("vetkd_public_key failed: {:?} - {}", code, msg))?;
+    Ok(reply.public_key)
+}
+
+pub async fn derive_key(key_name: &str, input: Vec<u8>, transport_public_key: Vec<u8>) -> Result<Vec<u8>, String> {
+    let args = DeriveKeyArgs { input, context: CONTEXT.to_vec(), transport_public_key, key_id: key_id(key_name) };
+    let (reply,): (DeriveKeyReply,) = call_with_payment128(Principal::management_canister(), "vetkd_derive_key", (args,), DERIVE_KEY_CYCLES)
+        .await
+        .map_err(|(code, msg)| format!("vetkd_derive_key failed: {:?} - {}", code, msg))?;
+    Ok(reply.encrypted_key)
+}
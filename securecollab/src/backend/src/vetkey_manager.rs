@@ -19,6 +19,12 @@ use ic_cdk::api::management_canister::main::raw_rand;
 use ic_cdk::caller;
 use sha2::{Sha256, Digest};
 use hex;
+use crate::key_revocation;
+use zeroize::Zeroize;
+use crate::backup;
+
+/// Lifetime of a session key unless created or renewed with another: one hour
+pub const DEFAULT_SESSION_TTL_NANOS: u64 = 60 * 60 * 1_000_000_000;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct MasterKeyShare {
@@ -71,6 +77,7 @@ pub struct SessionKey {
     pub combined_key: Vec<u8>,
     pub participants: Vec<String>,
     pub created_at: u64,
+    pub expires_at: u64,
 }
 
 // Store derived keys and encrypted data
@@ -122,6 +129,7 @@ fn compute_hash(data: &[u8]) -> String {
 
 /// Derive encryption key for an agent using simulated vetKD
 pub async fn derive_key_for_agent(agent_id: &str) -> Result<DerivedKey, String> {
+    key_revocation::ensure_not_revoked(agent_id)?;
     // Simulate key derivation with randomness
     let random_bytes = match raw_rand().await {
         Ok((bytes,)) => bytes,
@@ -147,6 +155,7 @@ pub async fn derive_key_for_agent(agent_id: &str) -> Result<DerivedKey, String>
 
 /// Derive encryption key for an agent using real vetKD
 pub async fn derive_key_for_agent_real(agent_id: &str) -> Result<DerivedKey, String> {
+    key_revocation::ensure_not_revoked(agent_id)?;
     let caller_principal = caller();
     
     // Create derivation path from agent ID and caller
@@ -218,6 +227,7 @@ pub fn decrypt_key_share(encrypted_share: &EncryptedKeyShare, recipient_id: &str
     if encrypted_share.recipient_id != recipient_id {
         return Err("Recipient ID mismatch".to_string());
     }
+    key_revocation::ensure_not_revoked(recipient_id)?;
     
     let nonce = generate_nonce();
     let key = compute_hash(recipient_id.as_bytes()).into_bytes();
@@ -275,7 +285,9 @@ pub fn encrypt_data_real(data: &[u8], key: &DerivedKey) -> Result<EncryptedData,
 }
 
 /// Decrypt data using derived key
-pub fn decrypt_data(encrypted_data: &EncryptedData, key: &DerivedKey) -> Vec<u8> {
+pub fn decrypt_data(encrypted_data: &EncryptedData, key: &DerivedKey) -> Result<Vec<u8>, String> {
+    key_revocation::ensure_not_revoked(&encrypted_data.key_id)?;
+    key_revocation::ensure_not_revoked(&key.identity)?;
     let mut plaintext = Vec::new();
     
     for (i, &byte) in encrypted_data.ciphertext.iter().enumerate() {
@@ -284,7 +296,7 @@ pub fn decrypt_data(encrypted_data: &EncryptedData, key: &DerivedKey) -> Vec<u8>
         plaintext.push(byte ^ key_byte ^ nonce_byte);
     }
     
-    plaintext
+    Ok(plaintext)
 }
 
 /// Decrypt data using real vetKD (IC-compatible implementation)
@@ -293,6 +305,8 @@ pub fn decrypt_data_real(encrypted: &EncryptedData, key: &DerivedKey) -> Result<
     if encrypted.key_id != key.verification_hash {
         return Err("Key mismatch - unauthorized decryption attempt".to_string());
     }
+    key_revocation::ensure_not_revoked(&key.identity)?;
+    key_revocation::ensure_not_revoked(&key.verification_hash)?;
     
     // Use derived key for XOR decryption
     let key_bytes = &key.key_bytes;
@@ -379,12 +393,17 @@ pub async fn secure_agent_message(
     Ok(secure_message.encrypted_content)
 }
 
-/// Create secure session for multi-agent computation
-pub fn create_secure_session(agent_ids: &[String]) -> Result<SessionKey, String> {
+/// Create secure session for multi-agent computation, expiring after `ttl`
+/// nanoseconds or the default session lifetime
+pub fn create_secure_session(agent_ids: &[String], ttl: Option<u64>) -> Result<SessionKey, String> {
     if agent_ids.len() < 2 {
         return Err("At least 2 agents required for secure session".to_string());
     }
     
+    for agent_id in agent_ids {
+        key_revocation::ensure_not_revoked(agent_id)?;
+    }
+    
     let session_id = format!("session_{}_{}", time(), agent_ids.len());
     
     // Combine keys from all agents to create session key
@@ -398,11 +417,13 @@ pub fn create_secure_session(agent_ids: &[String]) -> Result<SessionKey, String>
         }
     }
     
+    let now = time();
     let session_key = SessionKey {
         session_id: session_id.clone(),
         combined_key,
         participants: agent_ids.to_vec(),
-        created_at: time(),
+        created_at: now,
+        expires_at: now.saturating_add(ttl.unwrap_or(DEFAULT_SESSION_TTL_NANOS)),
     };
     
     // Store session key
@@ -413,8 +434,13 @@ pub fn create_secure_session(agent_ids: &[String]) -> Result<SessionKey, String>
     Ok(session_key)
 }
 
-/// Encrypt data for multi-party computation
-pub fn encrypt_for_mpc(data: &[u8], session_key: &SessionKey) -> EncryptedData {
+/// Encrypt data for multi-party computation under a session that is still open
+pub fn encrypt_for_mpc(data: &[u8], session_key: &SessionKey) -> Result<EncryptedData, String> {
+    key_revocation::ensure_not_revoked(&session_key.session_id)?;
+    let open = SESSION_KEYS.with(|sessions| sessions.borrow().contains_key(&session_key.session_id));
+    if !open || time() >= session_key.expires_at {
+        return Err(format!("Session {} has expired or been closed", session_key.session_id));
+    }
     let nonce = generate_nonce();
     let mut encrypted_data = data.to_vec();
     let key_len = session_key.combined_key.len();
@@ -422,12 +448,86 @@ pub fn encrypt_for_mpc(data: &[u8], session_key: &SessionKey) -> EncryptedData {
         *byte ^= session_key.combined_key[i % key_len];
     }
     
-    EncryptedData {
+    Ok(EncryptedData {
         ciphertext: encrypted_data,
         nonce,
         key_id: session_key.session_id.clone(),
         encryption_method: "MPC_SESSION".to_string(),
+    })
+}
+
+/// Agents taking part in an open session
+pub fn session_participants(session_id: &str) -> Option<Vec<String>> {
+    SESSION_KEYS.with(|sessions| sessions.borrow().get(session_id).map(|s| s.participants.clone()))
+}
+
+/// Extend an unexpired session by `ttl` nanoseconds from `now`, returning
+/// when it now expires
+pub fn renew_session(session_id: &str, now: u64, ttl: u64) -> Result<u64, String> {
+    SESSION_KEYS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+        if now >= session.expires_at {
+            return Err(format!("Session {} has expired", session_id));
+        }
+        session.expires_at = now.saturating_add(ttl);
+        Ok(session.expires_at)
+    })
+}
+
+/// Close a session, zeroizing its combined key
+pub fn close_session(session_id: &str) -> Result<(), String> {
+    let mut session = SESSION_KEYS.with(|sessions| sessions.borrow_mut().remove(session_id))
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    session.combined_key.zeroize();
+    Ok(())
+}
+
+/// Close sessions that expired by `now` or have an agent no longer
+/// registered, returning their ids
+pub fn cleanup_sessions(now: u64, is_registered: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut stale: Vec<String> = SESSION_KEYS.with(|sessions| {
+        sessions.borrow().values()
+            .filter(|s| now >= s.expires_at || !s.participants.iter().all(|p| is_registered(p)))
+            .map(|s| s.session_id.clone())
+            .collect()
+    });
+    stale.sort();
+    for session_id in &stale {
+        let _ = close_session(session_id);
     }
+    stale
+}
+
+/// Drop a revoked key from the cache along with every session combined from
+/// it, returning the ids of the agent keys and sessions dropped
+pub fn invalidate(key_id: &str) -> Vec<String> {
+    let agents: Vec<String> = DERIVED_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let agents: Vec<String> = keys.iter()
+            .filter(|(agent, key)| *agent == key_id || key.identity == key_id || key.verification_hash == key_id)
+            .map(|(agent, _)| agent.clone())
+            .collect();
+        for agent in &agents {
+            keys.remove(agent);
+        }
+        agents
+    });
+    let sessions: Vec<String> = SESSION_KEYS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        let dropped: Vec<String> = sessions.values()
+            .filter(|s| s.session_id == key_id || s.participants.iter().any(|p| p == key_id || agents.contains(p)))
+            .map(|s| s.session_id.clone())
+            .collect();
+        for session_id in &dropped {
+            if let Some(mut session) = sessions.remove(session_id) {
+                session.combined_key.zeroize();
+            }
+        }
+        dropped
+    });
+    agents.into_iter().filter(|agent| agent != key_id).chain(sessions.into_iter().filter(|s| s != key_id)).collect()
 }
 
 /// Get encryption statistics
@@ -501,139 +601,18 @@ pub struct VetKDEncryptedKey {
     pub encrypted_private_key: Vec<u8>,
 }
 
-/// Data analysis functions for real computation
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct DatasetAnalysis {
-    pub total_records: usize,
-    pub columns: Vec<String>,
-    pub drug_effectiveness: HashMap<String, f64>,
-    pub average_recovery_time: f64,
-    pub side_effects_distribution: HashMap<String, usize>,
-    pub hospital_distribution: HashMap<String, usize>,
-    pub age_statistics: AgeStatistics,
-}
-
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct AgeStatistics {
-    pub mean: f64,
-    pub median: f64,
-    pub min: u32,
-    pub max: u32,
-    pub std_dev: f64,
+pub fn export_state() -> Result<Vec<u8>, String> {
+    backup::encode(&(
+        backup::cloned(&DERIVED_KEYS),
+        backup::cloned(&ENCRYPTED_DATA),
+        backup::cloned(&SESSION_KEYS),
+    ))
 }
 
-pub fn analyze_healthcare_data(decrypted_data: &[u8]) -> Result<DatasetAnalysis, String> {
-    let csv_content = String::from_utf8(decrypted_data.to_vec())
-        .map_err(|e| format!("Invalid UTF-8 data: {}", e))?;
-    
-    let lines: Vec<&str> = csv_content.lines().collect();
-    if lines.is_empty() {
-        return Err("Empty dataset".to_string());
-    }
-    
-    // Parse header
-    let header = lines[0].split(',').map(|s| s.trim().to_string()).collect::<Vec<_>>();
-    let data_lines = &lines[1..];
-    
-    let mut drug_outcomes: HashMap<String, Vec<String>> = HashMap::new();
-    let mut recovery_times: Vec<f64> = Vec::new();
-    let mut side_effects: HashMap<String, usize> = HashMap::new();
-    let mut hospitals: HashMap<String, usize> = HashMap::new();
-    let mut ages: Vec<u32> = Vec::new();
-    
-    // Parse each data row
-    for line in data_lines {
-        let fields: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
-        if fields.len() != header.len() {
-            continue; // Skip malformed rows
-        }
-        
-        // Extract data based on expected healthcare schema
-        if let (Some(age_str), Some(treatment), Some(outcome), Some(recovery_str), Some(side_effect), Some(hospital)) = (
-            fields.get(1), fields.get(2), fields.get(3), fields.get(4), fields.get(5), fields.get(6)
-        ) {
-            // Age analysis
-            if let Ok(age) = age_str.parse::<u32>() {
-                ages.push(age);
-            }
-            
-            // Drug effectiveness
-            drug_outcomes.entry(treatment.to_string())
-                .or_insert_with(Vec::new)
-                .push(outcome.to_string());
-            
-            // Recovery time
-            if let Ok(recovery) = recovery_str.parse::<f64>() {
-                recovery_times.push(recovery);
-            }
-            
-            // Side effects
-            *side_effects.entry(side_effect.to_string()).or_insert(0) += 1;
-            
-            // Hospital distribution
-            *hospitals.entry(hospital.to_string()).or_insert(0) += 1;
-        }
-    }
-    
-    // Calculate drug effectiveness
-    let mut drug_effectiveness = HashMap::new();
-    for (drug, outcomes) in drug_outcomes {
-        let total = outcomes.len() as f64;
-        let successful = outcomes.iter()
-            .filter(|&outcome| outcome == "Improved" || outcome == "Cured")
-            .count() as f64;
-        let effectiveness = (successful / total) * 100.0;
-        drug_effectiveness.insert(drug, effectiveness);
-    }
-    
-    // Calculate average recovery time
-    let avg_recovery = if recovery_times.is_empty() {
-        0.0
-    } else {
-        recovery_times.iter().sum::<f64>() / recovery_times.len() as f64
-    };
-    
-    // Calculate age statistics
-    let age_stats = if ages.is_empty() {
-        AgeStatistics {
-            mean: 0.0,
-            median: 0.0,
-            min: 0,
-            max: 0,
-            std_dev: 0.0,
-        }
-    } else {
-        let mut sorted_ages = ages.clone();
-        sorted_ages.sort();
-        
-        let mean = ages.iter().sum::<u32>() as f64 / ages.len() as f64;
-        let median = if sorted_ages.len() % 2 == 0 {
-            (sorted_ages[sorted_ages.len() / 2 - 1] + sorted_ages[sorted_ages.len() / 2]) as f64 / 2.0
-        } else {
-            sorted_ages[sorted_ages.len() / 2] as f64
-        };
-        
-        let variance = ages.iter()
-            .map(|&age| (age as f64 - mean).powi(2))
-            .sum::<f64>() / ages.len() as f64;
-        let std_dev = variance.sqrt();
-        
-        AgeStatistics {
-            mean,
-            median,
-            min: *sorted_ages.first().unwrap_or(&0),
-            max: *sorted_ages.last().unwrap_or(&0),
-            std_dev,
-        }
-    };
-    
-    Ok(DatasetAnalysis {
-        total_records: data_lines.len(),
-        columns: header,
-        drug_effectiveness,
-        average_recovery_time: avg_recovery,
-        side_effects_distribution: side_effects,
-        hospital_distribution: hospitals,
-        age_statistics: age_stats,
-    })
+pub fn import_state(bytes: &[u8]) -> Result<(), String> {
+    let (derived_keys, encrypted_data, session_keys) = backup::decode(bytes)?;
+    backup::replace(&DERIVED_KEYS, derived_keys);
+    backup::replace(&ENCRYPTED_DATA, encrypted_data);
+    backup::replace(&SESSION_KEYS, session_keys);
+    Ok(())
 }
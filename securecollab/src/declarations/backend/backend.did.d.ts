@@ -2,26 +2,860 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export type AccessType = { 'SetIntersection' : null } |
+  { 'Decryption' : null } |
+  { 'HomomorphicSum' : null } |
+  { 'ReEncryption' : null } |
+  { 'ColumnDecryption' : null } |
+  { 'RecordLinkage' : null } |
+  { 'SecureSum' : null };
+export interface ActivityEntry {
+  'seq' : bigint,
+  'actor' : Principal,
+  'kind' : ActivityKind,
+  'summary' : string,
+  'timestamp' : bigint,
+  'workspace' : string,
+  'resource_id' : string,
+}
+export type ActivityKind = { 'PartyRegistered' : null } |
+  { 'RequestCancelled' : null } |
+  { 'ComputationFailed' : null } |
+  { 'ComputationExecuted' : null } |
+  { 'ComputationRequested' : null } |
+  { 'AdminAction' : null } |
+  { 'QueryCreated' : null } |
+  { 'KeyCeremonyStep' : null } |
+  { 'RetryRequested' : null } |
+  { 'DatasetUploaded' : null } |
+  { 'PolicyChanged' : null } |
+  { 'IdentityRecovery' : null } |
+  { 'LoadTestGenerated' : null } |
+  { 'QueryExecuted' : null } |
+  { 'QuerySigned' : null } |
+  { 'VoteCast' : null } |
+  { 'ResultsShared' : null };
+export interface ActivityPage {
+  'entries' : Array<ActivityEntry>,
+  'next_cursor' : [] | [bigint],
+}
+export interface AgentFilter {
+  'domain' : [] | [CapabilityDomain],
+  'capabilities' : Array<Capability>,
+  'available_only' : boolean,
+  'max_price' : [] | [bigint],
+  'min_reputation' : [] | [number],
+}
+export interface AgentLiveness {
+  'live' : boolean,
+  'agent_id' : string,
+  'last_seen' : [] | [bigint],
+}
+export interface AgentPayment {
+  'computation_id' : string,
+  'block_index' : bigint,
+  'agent_id' : string,
+  'timestamp' : bigint,
+  'payer' : Principal,
+  'amount' : bigint,
+}
+export interface AgentProgress {
+  'updated_at' : bigint,
+  'agent_id' : string,
+  'detail' : [] | [string],
+  'state' : AgentState,
+}
+export type AgentState = { 'Substituted' : null } |
+  { 'Failed' : null } |
+  { 'Running' : null } |
+  { 'Completed' : null } |
+  { 'Pending' : null };
+export interface AgentSubstitution {
+  'substitute_agent_id' : string,
+  'original_agent_id' : string,
+  'reason' : string,
+}
+export interface AgentTeam {
+  'id' : string,
+  'agent_ids' : Array<string>,
+  'created_at' : bigint,
+  'data_source_ids' : Array<string>,
+}
+export interface AgentTiming {
+  'slot' : string,
+  'resumed' : boolean,
+  'agent_id' : string,
+  'started_at' : bigint,
+  'finished_at' : bigint,
+}
+export interface AgentVersion {
+  'changed_at' : bigint,
+  'changed_by' : [] | [Principal],
+  'capabilities' : Array<Capability>,
+  'agent_id' : string,
+  'version' : number,
+  'change_note' : string,
+}
+export interface AgentWithdrawal {
+  'to' : Principal,
+  'block_index' : bigint,
+  'agent_id' : string,
+  'timestamp' : bigint,
+  'amount' : bigint,
+}
+export interface AggregateAnalysis {
+  'kind' : AggregateKind,
+  'sensitivity' : number,
+  'statistic' : string,
+  'epsilon' : number,
+  'noise_scale' : number,
+  'expected_absolute_error' : number,
+}
+export type AggregateKind = { 'Mean' : null } |
+  { 'GroupBy' : null } |
+  { 'Count' : null };
+export type AggregateStatus = { 'AwaitingDecryption' : null } |
+  { 'Decrypted' : null };
+export type AlertStatus = { 'Open' : null } |
+  { 'Confirmed' : null } |
+  { 'Dismissed' : null };
+export interface AnomalyAlert {
+  'id' : bigint,
+  'status' : AlertStatus,
+  'principal' : Principal,
+  'dataset_id' : string,
+  'kind' : AnomalyKind,
+  'timestamp' : bigint,
+  'details' : string,
+  'suspended' : boolean,
+}
+export type AnomalyKind = { 'OutOfHours' : null } |
+  { 'BulkDecryption' : null };
+export type ApiStyle = { 'OpenAi' : null } |
+  { 'Anthropic' : null };
+export type AsOf = { 'Timestamp' : bigint } |
+  { 'Sequence' : bigint };
+export type AuditEvent = { 'Decryption' : null } |
+  { 'Vote' : null } |
+  { 'Pause' : null } |
+  { 'Execution' : null } |
+  { 'KeyDerivation' : null } |
+  { 'Backup' : null } |
+  { 'RoleChange' : null } |
+  { 'Upload' : null };
+export interface AuditExport {
+  'content' : string,
+  'next_cursor' : [] | [bigint],
+  'record_count' : number,
+  'format' : AuditExportFormat,
+}
+export type AuditExportFormat = { 'Csv' : null } |
+  { 'Json' : null };
+export interface AuditInclusionProof {
+  'leaf_hash' : string,
+  'path' : Array<MerkleStep>,
+  'root' : string,
+  'entry' : AuditLogEntry,
+  'tree_size' : bigint,
+}
+export interface AuditLogEntry {
+  'seq' : bigint,
+  'actor' : Principal,
+  'hash' : string,
+  'prev_hash' : string,
+  'event' : AuditEvent,
+  'timestamp' : bigint,
+  'resource_id' : string,
+  'details' : string,
+}
+export interface AuditLogPage {
+  'entries' : Array<AuditLogEntry>,
+  'next_cursor' : [] | [bigint],
+  'head_hash' : string,
+}
+export interface Bid {
+  'agent_id' : string,
+  'eta_seconds' : bigint,
+  'price' : bigint,
+  'bidder' : Principal,
+  'reputation_score' : number,
+  'submitted_at' : bigint,
+}
+export interface BiddingRound {
+  'status' : BiddingStatus,
+  'computation_id' : string,
+  'requester' : Principal,
+  'bids' : Array<Bid>,
+  'spec' : string,
+  'deadline' : bigint,
+  'max_winners' : number,
+  'winners' : Array<Bid>,
+}
+export type BiddingStatus = { 'Open' : null } |
+  { 'NoBids' : null } |
+  { 'Awarded' : null };
+export type BillableKind = { 'LlmQuery' : null } |
+  { 'Computation' : null };
+export interface BillingEntry {
+  'llm_calls' : number,
+  'requester' : Principal,
+  'executed_by' : Principal,
+  'kind' : BillableKind,
+  'instructions' : bigint,
+  'cycles' : bigint,
+  'timestamp' : bigint,
+  'resource_id' : string,
+  'succeeded' : boolean,
+}
+export interface BillingHistory {
+  'requester' : Principal,
+  'total_instructions' : bigint,
+  'entries' : Array<BillingEntry>,
+  'total_cycles' : bigint,
+}
+export interface BootstrapParty {
+  'principal' : Principal,
+  'name' : string,
+  'role' : string,
+}
+export interface BudgetCharge {
+  'query_id' : string,
+  'epsilon' : number,
+  'charged_at' : bigint,
+}
+export type Capability = { 'CryptographicAnalysis' : null } |
+  { 'DataAnonymization' : null } |
+  { 'ClinicalTrialAnalysis' : null } |
+  { 'MachineLearning' : null } |
+  { 'ThreatDetection' : null } |
+  { 'MedicalPrivacyCompliance' : null } |
+  { 'PatternRecognition' : null } |
+  { 'MarketAnalysis' : null } |
+  { 'LegalRiskAssessment' : null } |
+  { 'StatisticalAnalysis' : null } |
+  { 'EpidemiologicalModeling' : null } |
+  { 'FraudDetection' : null } |
+  { 'RegulatoryCompliance' : null } |
+  { 'PolicyAnalysis' : null } |
+  { 'RiskAssessment' : null } |
+  { 'HipaaVerification' : null } |
+  { 'GdprCompliance' : null } |
+  { 'SecurityAudit' : null } |
+  { 'PortfolioOptimization' : null } |
+  { 'ContractAnalysis' : null } |
+  { 'RegulatoryInterpretation' : null } |
+  { 'DataVisualization' : null } |
+  { 'ComplianceMonitoring' : null } |
+  { 'PrivacyImpactAssessment' : null } |
+  { 'DrugDiscoveryInsights' : null } |
+  { 'PredictiveModeling' : null } |
+  { 'RegulatoryAudit' : null } |
+  { 'IncidentResponse' : null } |
+  { 'HealthcareDataAnalysis' : null } |
+  { 'VulnerabilityAssessment' : null };
+export type CapabilityDomain = { 'Legal' : null } |
+  { 'Healthcare' : null } |
+  { 'DataScience' : null } |
+  { 'Security' : null } |
+  { 'Privacy' : null } |
+  { 'Finance' : null };
+export interface CeremonyContribution {
+  'committed_at' : [] | [bigint],
+  'revealed_at' : [] | [bigint],
+  'revealed' : boolean,
+  'party' : Principal,
+  'commitment' : [] | [Uint8Array | number[]],
+}
+export type CeremonyPhase = { 'Reveal' : null } |
+  { 'Complete' : null } |
+  { 'Aborted' : null } |
+  { 'Commit' : null };
+export interface CertifiedAudit {
+  'report' : string,
+  'certificate' : [] | [Uint8Array | number[]],
+  'witness' : Uint8Array | number[],
+}
+export interface CertifiedComputation {
+  'certificate' : [] | [Uint8Array | number[]],
+  'witness' : Uint8Array | number[],
+  'computation' : MPCComputation,
+}
+export interface CertifiedInclusionProof {
+  'certificate' : [] | [Uint8Array | number[]],
+  'witness' : Uint8Array | number[],
+  'proof' : AuditInclusionProof,
+}
+export interface ChainVerification {
+  'entries_checked' : bigint,
+  'valid' : boolean,
+  'first_invalid_seq' : [] | [bigint],
+  'head_hash' : string,
+}
 export interface ChatMessage { 'content' : string, 'role' : string }
+export interface CheckpointJob {
+  'status' : JobStatus,
+  'result' : [] | [ComputationResult],
+  'processed_bytes' : bigint,
+  'updated_at' : bigint,
+  'requester' : Principal,
+  'slices_processed' : bigint,
+  'total_bytes' : bigint,
+  'dataset_index' : number,
+  'team_id' : string,
+  'offset' : bigint,
+  'error' : [] | [string],
+  'steps' : number,
+  'job_id' : string,
+  'dataset_ids' : Array<string>,
+  'rows_processed' : bigint,
+  'computation_request' : string,
+  'slice_bytes' : bigint,
+  'percent_complete' : number,
+  'running_digest' : string,
+  'started_at' : bigint,
+}
+export interface ChiSquareTest {
+  'degrees_of_freedom' : bigint,
+  'p_value' : number,
+  'statistic' : number,
+  'sparse' : boolean,
+  'cramers_v' : number,
+}
+export type CircuitState = { 'Open' : null } |
+  { 'Closed' : null } |
+  { 'HalfOpen' : null };
+export interface Coefficient { 'estimate' : number, 'predictor' : string }
+export interface Cohort {
+  'id' : string,
+  'owner' : Principal,
+  'name' : string,
+  'created_at' : bigint,
+  'filter' : string,
+  'conditions' : Array<Condition>,
+}
+export interface CohortComparison {
+  'min_cohort_size' : bigint,
+  'cohorts' : Array<CohortResult>,
+  'epsilon' : number,
+  'outcome' : string,
+}
+export interface CohortComparisonRequest {
+  'computation_id' : [] | [string],
+  'min_cohort_size' : [] | [bigint],
+  'dataset_ids' : Array<string>,
+  'epsilon' : [] | [number],
+  'cohort_ids' : Array<string>,
+  'outcome' : string,
+}
+export interface CohortResult {
+  'mean_difference' : [] | [number],
+  'suppressed' : boolean,
+  'records' : [] | [bigint],
+  'name' : string,
+  'cohort_id' : string,
+  'outcome' : [] | [ReleasedValue],
+}
+export interface ColumnError {
+  'rows' : bigint,
+  'message' : string,
+  'first_line' : [] | [bigint],
+  'column' : string,
+}
+export interface ColumnSpec {
+  'column_type' : ColumnType,
+  'nullable' : boolean,
+  'name' : string,
+}
+export interface ColumnSummary {
+  'spec' : ColumnSpec,
+  'values' : bigint,
+  'top_values' : Array<[string, bigint]>,
+  'range' : [] | [[number, number]],
+}
+export type ColumnType = { 'Date' : null } |
+  { 'Text' : null } |
+  { 'Boolean' : null } |
+  { 'Category' : null } |
+  { 'Identifier' : null } |
+  { 'Number' : { 'integer' : boolean, 'bounds' : [] | [[number, number]] } };
+export interface ComputationLlmUsage {
+  'computation_id' : string,
+  'usage' : LlmUsage,
+}
+export type ComputationPhase = { 'Analyzing' : null } |
+  { 'Decrypting' : null } |
+  { 'Failed' : null } |
+  { 'Aggregating' : null } |
+  { 'Proving' : null } |
+  { 'Completed' : null };
+export interface ComputationProgress {
+  'request_id' : string,
+  'updated_at' : bigint,
+  'agents' : Array<AgentProgress>,
+  'message' : [] | [string],
+  'phase' : ComputationPhase,
+  'percent_complete' : number,
+  'started_at' : bigint,
+}
 export interface ComputationResult {
+  'agent_timings' : Array<AgentTiming>,
   'insights' : string,
   'timestamp' : bigint,
   'privacy_proof' : string,
+  'substitutions' : Array<AgentSubstitution>,
+}
+export interface ComputationSnapshot {
+  'seq' : bigint,
+  'status' : string,
+  'votes' : Array<Vote>,
+  'received_signatures' : Array<Principal>,
+  'timestamp' : bigint,
+  'shared_with' : Array<Principal>,
+  'required_signatures' : Array<Principal>,
+  'approvals' : Array<Principal>,
+}
+export interface ComputationSubscription {
+  'request_id' : string,
+  'last_error' : [] | [string],
+  'method' : string,
+  'created_at' : bigint,
+  'last_notified_at' : [] | [bigint],
+  'callback_canister' : Principal,
+  'subscriber' : Principal,
+  'last_notified_status' : [] | [string],
+}
+export interface Condition {
+  'value' : string,
+  'operator' : Operator,
+  'column' : string,
+}
+export interface Config {
+  'dp_defaults' : DpConfig,
+  'retention_days' : bigint,
+  'llm_backend' : LlmBackend,
+  'settings' : Settings,
+  'agent_liveness_seconds' : bigint,
+  'vetkd_key_ttl_seconds' : [] | [bigint],
+  'unpause_approvals' : number,
+  'max_concurrent_executions' : number,
+}
+export interface ConfigUpdate {
+  'vetkd_mode' : [] | [VetKdMode],
+  'llm_canister_id' : [] | [[] | [Principal]],
+  'dp_defaults' : [] | [DpConfig],
+  'retention_days' : [] | [bigint],
+  'llm_backend' : [] | [LlmBackend],
+  'min_parties' : [] | [number],
+  'query_expiry_seconds' : [] | [bigint],
+  'agent_liveness_seconds' : [] | [bigint],
+  'invitation_expiry_seconds' : [] | [bigint],
+  'threshold_key_name' : [] | [string],
+  'vetkd_key_ttl_seconds' : [] | [[] | [bigint]],
+  'unpause_approvals' : [] | [number],
+  'max_concurrent_executions' : [] | [number],
+  'vetkd_key_name' : [] | [string],
+}
+export interface ConsentGraph {
+  'computations' : Array<ConsentNode>,
+  'canister_id' : Principal,
+  'graph_digest' : string,
+  'exported_at' : bigint,
+  'workspace' : string,
+}
+export interface ConsentNode {
+  'status' : string,
+  'title' : string,
+  'computation_id' : string,
+  'requester' : Principal,
+  'votes' : Array<ConsentVote>,
+  'received_signatures' : Array<Principal>,
+  'required_signers' : Array<Principal>,
+  'created_at' : bigint,
+  'signatures' : Array<ConsentSignature>,
+  'signature_data_hash' : [] | [string],
+  'shared_with' : Array<Principal>,
+}
+export interface ConsentSignature { 'signature' : string, 'signer' : string }
+export interface ConsentVote {
+  'decision' : string,
+  'voter' : Principal,
+  'timestamp' : bigint,
+}
+export interface ContingencyRequest {
+  'computation_id' : [] | [string],
+  'row_variable' : string,
+  'dataset_ids' : Array<string>,
+  'epsilon' : [] | [number],
+  'column_variable' : string,
+}
+export interface ContingencyTable {
+  'records' : bigint,
+  'row_variable' : string,
+  'cells' : Array<Array<[] | [bigint]>>,
+  'chi_square' : [] | [ChiSquareTest],
+  'row_labels' : Array<string>,
+  'suppressed_cells' : bigint,
+  'epsilon' : number,
+  'column_variable' : string,
+  'column_labels' : Array<string>,
+}
+export interface CorrelationMatrix {
+  'records' : bigint,
+  'spearman' : Array<Array<number>>,
+  'epsilon' : number,
+  'pearson' : Array<Array<number>>,
+  'columns' : Array<string>,
+}
+export interface CorrelationRequest {
+  'computation_id' : [] | [string],
+  'dataset_ids' : Array<string>,
+  'epsilon' : [] | [number],
+  'columns' : Array<string>,
+}
+export interface CostEstimate {
+  'based_on_runs' : number,
+  'instruction_cycles' : bigint,
+  'llm_calls' : number,
+  'dataset_bytes' : bigint,
+  'agent_fee' : bigint,
+  'estimated_instructions' : bigint,
+  'total_cycles' : bigint,
+  'llm_call_cycles' : bigint,
+}
+export interface CostEstimateRequest {
+  'expected_llm_calls' : [] | [number],
+  'agent_id' : [] | [string],
+  'dataset_ids' : Array<string>,
+}
+export type DataFormat = { 'Csv' : null } |
+  { 'Parquet' : null } |
+  { 'JsonLines' : null };
+export interface DatasetAccess {
+  'computation_id' : [] | [string],
+  'dataset_id' : string,
+  'timestamp' : bigint,
+  'accessor' : Principal,
+  'columns' : Array<string>,
+  'access_type' : AccessType,
+}
+export interface DatasetMetadata {
+  'collection_end' : [] | [string],
+  'steward_contact' : [] | [string],
+  'geography' : [] | [string],
+  'data_use_agreement' : [] | [string],
+  'collection_start' : [] | [string],
+  'license' : [] | [string],
+}
+export interface DatasetSummary {
+  'dataset_id' : string,
+  'generated_at' : bigint,
+  'records' : bigint,
+  'version' : string,
+  'epsilon' : number,
+  'columns' : Array<ColumnSummary>,
+}
+export interface Decomposition {
+  'trend' : Array<[] | [number]>,
+  'seasonal' : Array<number>,
+  'residual' : Array<[] | [number]>,
+}
+export interface DecryptedColumns {
+  'dataset_id' : string,
+  'column_digests' : Array<string>,
+  'decrypted_at' : bigint,
+  'columns' : Array<string>,
+}
+export interface DecryptionWindow {
+  'closed_at' : [] | [bigint],
+  'run_id' : string,
+  'opened_at' : bigint,
+  'bytes_decrypted' : bigint,
+  'state' : WindowState,
+  'expires_at' : bigint,
+}
+export interface DeviceSession {
+  'created_at' : bigint,
+  'anchor_principal' : Principal,
+  'device' : string,
+  'session_principal' : Principal,
+  'expires_at' : bigint,
+}
+export interface DisclosurePolicy {
+  'small_cell_threshold' : bigint,
+  'min_cohort_size' : bigint,
+  'rounding_base' : bigint,
+}
+export interface DisclosureReport {
+  'small_cell_threshold' : bigint,
+  'suppressions' : Array<Suppression>,
+  'min_cohort_size' : bigint,
+  'rounding_base' : bigint,
+}
+export interface DpBounds { 'max_epsilon' : number, 'max_delta' : number }
+export interface DpConfig {
+  'mechanism' : NoiseMechanism,
+  'epsilon' : number,
+  'delta' : number,
+}
+export interface EncryptedAggregate {
+  'id' : string,
+  'sum' : [] | [number],
+  'encrypted_sum' : Uint8Array | number[],
+  'status' : AggregateStatus,
+  'requester' : Principal,
+  'key_id' : string,
+  'mean' : [] | [number],
+  'count' : bigint,
+  'created_at' : bigint,
+  'dataset_ids' : Array<string>,
+  'column' : string,
+  'decrypted_by' : Array<Principal>,
+}
+export interface EncryptedResult {
+  'ciphertext' : Uint8Array | number[],
+  'recipient' : Principal,
+  'derivation_path' : Uint8Array | number[],
+  'created_at' : bigint,
+  'result_digest' : string,
+}
+export interface Escrow {
+  'last_error' : [] | [string],
+  'status' : EscrowStatus,
+  'computation_id' : string,
+  'locked_at' : bigint,
+  'unsettled_refund' : [] | [PendingTransfer],
+  'agent_id' : string,
+  'refund_block' : [] | [bigint],
+  'payer' : Principal,
+  'lock_block' : bigint,
+  'amount' : bigint,
+  'settled_at' : [] | [bigint],
+}
+export type EscrowStatus = { 'Refunded' : null } |
+  { 'Released' : null } |
+  { 'Locked' : null };
+export interface EventRecord {
+  'seq' : bigint,
+  'actor' : Principal,
+  'event' : WorkflowEvent,
+  'timestamp' : bigint,
+}
+export interface EventVerificationKey {
+  'algorithm' : string,
+  'public_key' : Uint8Array | number[],
+  'key_name' : string,
+}
+export interface FederatedModel {
+  'id' : string,
+  'participants' : Array<Principal>,
+  'submitted' : Array<Principal>,
+  'owner' : Principal,
+  'name' : string,
+  'created_at' : bigint,
+  'dimension' : bigint,
+  'latest_version' : bigint,
+  'round' : bigint,
+}
+export interface GuardianConfig {
+  'updated_at' : bigint,
+  'guardians' : Array<Principal>,
+  'threshold' : number,
+  'owner' : Principal,
+}
+export type HashScheme = { 'Sha256' : null } |
+  { 'Legacy' : null };
+export interface HttpHeader { 'value' : string, 'name' : string }
+export interface HttpProvider {
+  'url' : string,
+  'embeddings_url' : [] | [string],
+  'model' : string,
+  'max_response_bytes' : bigint,
+  'api_key' : string,
+  'name' : string,
+  'embeddings_model' : [] | [string],
+  'api_style' : ApiStyle,
+}
+export interface HttpProviderInfo {
+  'url' : string,
+  'embeddings_url' : [] | [string],
+  'model' : string,
+  'max_response_bytes' : bigint,
+  'name' : string,
+  'embeddings_model' : [] | [string],
+  'api_style' : ApiStyle,
+}
+export interface HttpResponse {
+  'status' : bigint,
+  'body' : Uint8Array | number[],
+  'headers' : Array<HttpHeader>,
+}
+export interface ImportChunk {
+  'data' : Uint8Array | number[],
+  'offset' : bigint,
+  'total_size' : bigint,
+  'snapshot_hash' : string,
+}
+export interface ImportProgress {
+  'total_size' : bigint,
+  'received' : bigint,
+  'restored' : boolean,
+}
+export interface InitArgs {
+  'vetkd_mode' : [] | [VetKdMode],
+  'llm_canister_id' : [] | [Principal],
+  'admins' : [] | [Array<Principal>],
+  'threshold_key_name' : [] | [string],
+  'parties' : [] | [Array<BootstrapParty>],
+  'vetkd_key_name' : [] | [string],
+}
+export type InjectionPolicy = { 'Sanitize' : null } |
+  { 'Reject' : null };
+export interface Invitation {
+  'id' : string,
+  'invitee' : Invitee,
+  'role' : Role,
+  'created_at' : bigint,
+  'redeemed_at' : [] | [bigint],
+  'redeemed_by' : [] | [Principal],
+  'workspace' : string,
+  'invited_by' : Principal,
+  'expires_at' : bigint,
+}
+export interface InvitationCode { 'code' : string, 'invitation' : Invitation }
+export type Invitee = { 'EmailHash' : string } |
+  { 'Principal' : Principal };
+export type JobStatus = { 'Failed' : null } |
+  { 'Running' : null } |
+  { 'Completed' : null };
+export interface KeyCeremony {
+  'transcript_hash' : [] | [string],
+  'contributions' : Array<CeremonyContribution>,
+  'initiator' : Principal,
+  'deadline' : bigint,
+  'created_at' : bigint,
+  'root_context' : [] | [Uint8Array | number[]],
+  'workspace' : string,
+  'phase' : CeremonyPhase,
+  'completed_at' : [] | [bigint],
+  'transcript' : Array<TranscriptEntry>,
+}
+export interface KeyRotation {
+  'dataset_id' : string,
+  'epoch' : number,
+  'revoked_keys' : Array<string>,
+  'rotated_at' : bigint,
+  'rotated_by' : Principal,
+  'vetkey_id' : string,
 }
 export interface LLMQueryRequest {
   'id' : string,
+  'last_error' : [] | [string],
   'status' : QueryStatus,
   'result' : [] | [string],
+  'policy_decision' : [] | [PolicyDecision],
+  'sql_result' : [] | [SqlResult],
   'requester' : Principal,
+  'sql_plan' : [] | [SqlPlan],
   'received_signatures' : Array<Principal>,
   'query' : string,
   'created_at' : bigint,
+  'minimization_proof_id' : [] | [string],
+  'decrypted_columns' : Array<DecryptedColumns>,
   'target_datasets' : Array<string>,
+  'dp_params' : [] | [DpConfig],
   'required_signatures' : Array<Principal>,
   'expires_at' : bigint,
+  'purpose' : string,
+  'columns' : Array<string>,
+}
+export interface LineageEdge {
+  'to' : string,
+  'from' : string,
+  'created_at' : bigint,
+}
+export interface LineageGraph {
+  'edges' : Array<LineageEdge>,
+  'nodes' : Array<LineageNode>,
+  'resource_id' : string,
+}
+export type LineageKind = { 'Result' : null } |
+  { 'Computation' : null } |
+  { 'DatasetVersion' : null };
+export interface LineageNode {
+  'id' : string,
+  'kind' : LineageKind,
+  'created_at' : bigint,
+  'label' : string,
+}
+export interface LinkageQuality {
+  'left_records' : bigint,
+  'ambiguous_records' : bigint,
+  'match_rate' : number,
+  'mean_similarity' : number,
+  'matches' : bigint,
+  'uncertain_matches' : bigint,
+  'similarity_histogram' : BigUint64Array | bigint[],
+  'candidate_pairs' : bigint,
+  'right_records' : bigint,
+}
+export interface LinkageRun {
+  'id' : string,
+  'computation_id' : [] | [string],
+  'left_dataset' : string,
+  'requester' : Principal,
+  'threshold' : number,
+  'quality' : LinkageQuality,
+  'created_at' : bigint,
+  'matches' : Array<RecordMatch>,
+  'right_dataset' : string,
+}
+export type LlmBackend = { 'Mock' : null } |
+  { 'Https' : string } |
+  { 'IcLlmCanister' : null };
+export interface LlmBudget {
+  'max_calls' : [] | [bigint],
+  'max_tokens' : [] | [bigint],
+}
+export interface LlmHealth {
+  'last_error' : [] | [string],
+  'retry_after' : [] | [bigint],
+  'opened_at' : [] | [bigint],
+  'state' : CircuitState,
+  'consecutive_failures' : number,
+  'policy' : RetryPolicy,
+}
+export interface LlmUsage {
+  'calls' : bigint,
+  'estimated_tokens' : bigint,
+  'prompt_bytes' : bigint,
+  'failed_calls' : bigint,
+  'response_bytes' : bigint,
+}
+export interface LlmUsageStats {
+  'total' : LlmUsage,
+  'by_computation' : Array<ComputationLlmUsage>,
+  'by_principal' : Array<PrincipalLlmUsage>,
+}
+export interface LoadTestReport {
+  'total_datasets' : bigint,
+  'total_computations' : bigint,
+  'computations_created' : number,
+  'bytes_generated' : bigint,
+  'datasets_created' : number,
+  'instructions_used' : bigint,
+}
+export interface MPCAgent {
+  'id' : string,
+  'price_per_computation' : bigint,
+  'capabilities' : Array<Capability>,
+  'owner' : [] | [Principal],
+  'version' : number,
+  'identity' : string,
+  'reputation_score' : number,
 }
 export interface MPCComputation {
   'id' : string,
+  'last_error' : [] | [string],
   'status' : string,
   'required_parties' : number,
   'title' : string,
@@ -30,11 +864,69 @@ export interface MPCComputation {
   'votes' : Array<Vote>,
   'received_signatures' : Array<Principal>,
   'description' : string,
+  'llm_provider' : [] | [string],
   'created_at' : bigint,
   'results' : [] | [string],
+  'agent_id' : [] | [string],
+  'prompt_template' : [] | [TemplateBinding],
   'signature_id' : [] | [string],
+  'encrypted_results' : Array<EncryptedResult>,
+  'structured_results' : Array<EncryptedResult>,
+  'agent_version' : [] | [number],
+  'priority' : Priority,
+  'shared_with' : Array<Principal>,
+  'output_schema' : [] | [string],
   'required_signatures' : Array<Principal>,
+  'retry_attempts' : Array<RetryAttempt>,
+  'purpose' : string,
   'approvals' : Array<Principal>,
+  'columns' : Array<string>,
+}
+export interface MaintenanceReport {
+  'computations_pruned' : bigint,
+  'queries_pruned' : bigint,
+  'derivations_pruned' : bigint,
+  'ran_at' : bigint,
+  'bytes_reclaimed' : bigint,
+}
+export interface MaintenanceStats {
+  'computations_pruned' : bigint,
+  'queries_pruned' : bigint,
+  'runs' : bigint,
+  'derivations_pruned' : bigint,
+  'bytes_reclaimed' : bigint,
+  'last_run' : [] | [MaintenanceReport],
+  'retention_nanos' : bigint,
+}
+export interface MerkleStep { 'sibling' : string, 'sibling_is_left' : boolean }
+export interface ModelVersion {
+  'created_at' : bigint,
+  'weights' : Array<number>,
+  'version' : bigint,
+  'model_id' : string,
+  'contributors' : bigint,
+}
+export type NoiseMechanism = { 'Laplace' : null } |
+  { 'Gaussian' : null };
+export type Operator = { 'LessOrEqual' : null } |
+  { 'Equal' : null } |
+  { 'GreaterOrEqual' : null } |
+  { 'Less' : null } |
+  { 'Greater' : null } |
+  { 'NotEqual' : null };
+export interface Organization {
+  'id' : string,
+  'members' : Array<Principal>,
+  'name' : string,
+  'created_at' : bigint,
+  'admins' : Array<Principal>,
+}
+export interface PaillierKey {
+  'id' : string,
+  'created_at' : bigint,
+  'holders' : Array<Principal>,
+  'registered_by' : Principal,
+  'modulus' : Uint8Array | number[],
 }
 export interface PartyInfo {
   'principal' : Principal,
@@ -44,32 +936,628 @@ export interface PartyInfo {
   'is_active' : boolean,
   'vetkey_id' : string,
 }
+export interface PauseState {
+  'required_approvals' : number,
+  'paused_at' : [] | [bigint],
+  'paused_by' : [] | [Principal],
+  'paused' : boolean,
+  'unpause_approvals' : Array<Principal>,
+  'reason' : [] | [string],
+}
+export interface PendingTransfer {
+  'to' : Principal,
+  'fee' : bigint,
+  'last_error' : [] | [string],
+  'memo' : Uint8Array | number[],
+  'created_at_time' : bigint,
+  'amount' : bigint,
+}
+export type Period = { 'Day' : null } |
+  { 'Quarter' : null } |
+  { 'Week' : null } |
+  { 'Year' : null } |
+  { 'Month' : null };
+export interface PeriodChange {
+  'period' : string,
+  'percent_change' : [] | [number],
+  'change' : number,
+  'compared_to' : string,
+}
+export interface PeriodValue {
+  'records' : bigint,
+  'value' : [] | [number],
+  'period' : string,
+}
+export type Permission = { 'Approve' : null } |
+  { 'Compute' : null } |
+  { 'ManageRoles' : null } |
+  { 'Audit' : null } |
+  { 'Upload' : null };
+export interface PermissionSnapshot {
+  'seq' : bigint,
+  'owner' : Principal,
+  'timestamp' : bigint,
+  'access_permissions' : Array<Principal>,
+}
+export interface PolicyDecision {
+  'approved' : boolean,
+  'rationale' : string,
+  'findings' : Array<PolicyFinding>,
+}
+export interface PolicyFinding {
+  'rule' : PolicyRule,
+  'detail' : string,
+  'passed' : boolean,
+}
+export type PolicyRule = { 'Aggregation' : null } |
+  { 'IdentifyingColumns' : null } |
+  { 'NarrowFilter' : null } |
+  { 'Purpose' : null } |
+  { 'GroupByIdentifier' : null };
+export interface PrincipalLlmUsage {
+  'principal' : Principal,
+  'usage' : LlmUsage,
+  'budget' : [] | [LlmBudget],
+}
+export type Priority = { 'Low' : null } |
+  { 'High' : null } |
+  { 'Normal' : null };
+export interface PrivacyBudget {
+  'spent_epsilon' : number,
+  'dataset_id' : string,
+  'remaining_epsilon' : number,
+  'total_epsilon' : number,
+  'bounds' : [] | [DpBounds],
+  'charges' : Array<BudgetCharge>,
+}
+export interface PrivacyProof {
+  'computation_id' : string,
+  'verified' : boolean,
+  'proof_id' : string,
+  'created_at' : bigint,
+  'verification_hash' : string,
+  'proof_data' : Uint8Array | number[],
+  'public_parameters' : Uint8Array | number[],
+  'proof_type' : string,
+  'hash_scheme' : HashScheme,
+}
 export interface PrivateDataSource {
   'id' : string,
   'encrypted_data' : Uint8Array | number[],
   'owner' : Principal,
+  'metadata' : DatasetMetadata,
   'schema' : string,
   'name' : string,
+  'tags' : Array<string>,
   'created_at' : bigint,
   'access_permissions' : Array<Principal>,
   'party_name' : string,
+  'allowed_purposes' : [] | [Array<string>],
   'vetkey_id' : string,
   'record_count' : number,
 }
-export type QueryStatus = { 'Executing' : null } |
+export interface PromptTemplate {
+  'id' : string,
+  'status' : TemplateStatus,
+  'body' : string,
+  'reviewed_at' : [] | [bigint],
+  'reviewed_by' : [] | [Principal],
+  'computation_type' : string,
+  'created_at' : bigint,
+  'author' : Principal,
+  'version' : number,
+  'variables' : Array<TemplateVariable>,
+  'review_note' : [] | [string],
+}
+export interface ProofExport {
+  'content' : Uint8Array | number[],
+  'format_version' : number,
+  'scheme' : string,
+  'format' : ProofExportFormat,
+}
+export type ProofExportFormat = { 'Binary' : null } |
+  { 'Json' : null };
+export interface ProofVerification {
+  'computation_id' : [] | [string],
+  'verified' : boolean,
+  'proof_id' : string,
+  'error' : [] | [string],
+}
+export type PsiMode = { 'Cardinality' : { 'noise' : [] | [DpConfig] } } |
+  { 'Members' : null };
+export interface PsiResult {
+  'session_id' : string,
+  'exact' : boolean,
+  'intersection_size' : bigint,
+  'matched_indices' : BigUint64Array | bigint[],
+}
+export interface PsiSession {
+  'id' : string,
+  'status' : PsiStatus,
+  'initiator_dataset' : string,
+  'cardinality' : [] | [bigint],
+  'responder' : Principal,
+  'initiator' : Principal,
+  'mode' : PsiMode,
+  'created_at' : bigint,
+  'initiator_double' : Array<Uint8Array | number[]>,
+  'responder_blinded' : Array<Uint8Array | number[]>,
+  'responder_double' : Array<Uint8Array | number[]>,
+  'initiator_blinded' : Array<Uint8Array | number[]>,
+  'responder_dataset' : string,
+}
+export type PsiStatus = { 'AwaitingCompletion' : null } |
+  { 'AwaitingResponse' : null } |
+  { 'Completed' : null };
+export type QueryStatus = { 'Failed' : null } |
+  { 'Executing' : null } |
   { 'Approved' : null } |
   { 'Rejected' : null } |
+  { 'Cancelled' : null } |
   { 'Completed' : null } |
   { 'Expired' : null } |
   { 'Pending' : null };
-export type Result = { 'Ok' : string } |
+export interface QueuePosition {
+  'request_id' : string,
+  'active_executions' : number,
+  'max_concurrent' : number,
+  'priority' : Priority,
+  'position' : number,
+  'running' : boolean,
+}
+export interface QueuedComputation {
+  'request_id' : string,
+  'enqueued_at' : bigint,
+  'priority' : Priority,
+}
+export interface RangeCheck {
+  'verified' : boolean,
+  'lower' : number,
+  'statistic' : string,
+  'upper' : number,
+}
+export interface RateLimit { 'refill_per_minute' : number, 'capacity' : number }
+export interface RateLimits {
+  'default' : RateLimit,
+  'overrides' : Array<[Principal, RateLimit]>,
+}
+export interface RecordMatch {
+  'similarity' : number,
+  'left_index' : bigint,
+  'right_index' : bigint,
+}
+export interface RecoveryNotification {
+  'request_id' : string,
+  'recipient' : Principal,
+  'message' : string,
+  'timestamp' : bigint,
+}
+export interface RecoveryRequest {
+  'id' : string,
+  'status' : RecoveryStatus,
+  'threshold' : number,
+  'new_principal' : Principal,
+  'requested_at' : bigint,
+  'lost_principal' : Principal,
+  'executable_after' : bigint,
+  'approvals' : Array<Principal>,
+}
+export type RecoveryStatus = { 'Executing' : null } |
+  { 'Executed' : null } |
+  { 'Cancelled' : null } |
+  { 'Pending' : null };
+export type RegressionKind = { 'Linear' : null } |
+  { 'Logistic' : null };
+export interface RegressionModel {
+  'records' : bigint,
+  'intercept' : number,
+  'kind' : RegressionKind,
+  'coefficients' : Array<Coefficient>,
+  'r_squared' : [] | [number],
+  'epsilon' : number,
+  'outcome' : string,
+}
+export interface RegressionRequest {
+  'computation_id' : [] | [string],
+  'predictors' : Array<string>,
+  'kind' : RegressionKind,
+  'dataset_ids' : Array<string>,
+  'epsilon' : [] | [number],
+  'outcome' : string,
+}
+export type ReleasedValue = { 'Counts' : Array<[string, bigint]> } |
+  { 'Mean' : number };
+export type Result = { 'Ok' : KeyCeremony } |
+  { 'Err' : string };
+export type Result_1 = { 'Ok' : Organization } |
+  { 'Err' : string };
+export type Result_10 = { 'Ok' : null } |
+  { 'Err' : string };
+export type Result_11 = { 'Ok' : CeremonyPhase } |
+  { 'Err' : string };
+export type Result_12 = { 'Ok' : CohortComparison } |
+  { 'Err' : string };
+export type Result_13 = { 'Ok' : PsiResult } |
+  { 'Err' : string };
+export type Result_14 = { 'Ok' : CorrelationMatrix } |
+  { 'Err' : string };
+export type Result_15 = { 'Ok' : FederatedModel } |
+  { 'Err' : string };
+export type Result_16 = { 'Ok' : PromptTemplate } |
+  { 'Err' : string };
+export type Result_17 = { 'Ok' : ContingencyTable } |
+  { 'Err' : string };
+export type Result_18 = { 'Ok' : Cohort } |
+  { 'Err' : string };
+export type Result_19 = { 'Ok' : DeviceSession } |
+  { 'Err' : string };
+export type Result_2 = { 'Ok' : Array<string> } |
+  { 'Err' : string };
+export type Result_20 = { 'Ok' : Uint8Array | number[] } |
+  { 'Err' : string };
+export type Result_21 = { 'Ok' : WrappedKey } |
+  { 'Err' : string };
+export type Result_22 = { 'Ok' : CostEstimate } |
+  { 'Err' : string };
+export type Result_23 = { 'Ok' : EncryptedResult } |
+  { 'Err' : string };
+export type Result_24 = { 'Ok' : ComputationResult } |
+  { 'Err' : string };
+export type Result_25 = { 'Ok' : AuditExport } |
+  { 'Err' : string };
+export type Result_26 = { 'Ok' : SignedConsentGraph } |
+  { 'Err' : string };
+export type Result_27 = { 'Ok' : ProofExport } |
+  { 'Err' : string };
+export type Result_28 = { 'Ok' : StateChunk } |
+  { 'Err' : string };
+export type Result_29 = { 'Ok' : RegressionModel } |
+  { 'Err' : string };
+export type Result_3 = { 'Ok' : TimeSeriesAnalysis } |
+  { 'Err' : string };
+export type Result_30 = { 'Ok' : LoadTestReport } |
+  { 'Err' : string };
+export type Result_31 = { 'Ok' : ActivityPage } |
+  { 'Err' : string };
+export type Result_32 = { 'Ok' : AgentTeam } |
+  { 'Err' : string };
+export type Result_33 = { 'Ok' : Array<AnomalyAlert> } |
+  { 'Err' : string };
+export type Result_34 = { 'Ok' : CertifiedInclusionProof } |
+  { 'Err' : string };
+export type Result_35 = { 'Ok' : AuditLogPage } |
+  { 'Err' : string };
+export type Result_36 = { 'Ok' : CheckpointJob } |
+  { 'Err' : string };
+export type Result_37 = { 'Ok' : ComputationProgress } |
+  { 'Err' : string };
+export type Result_38 = { 'Ok' : CertifiedComputation } |
+  { 'Err' : string };
+export type Result_39 = { 'Ok' : ComputationSnapshot } |
+  { 'Err' : string };
+export type Result_4 = { 'Ok' : UploadValidation } |
+  { 'Err' : string };
+export type Result_40 = { 'Ok' : Array<DatasetAccess> } |
+  { 'Err' : string };
+export type Result_41 = { 'Ok' : Array<KeyRotation> } |
+  { 'Err' : string };
+export type Result_42 = { 'Ok' : PermissionSnapshot } |
+  { 'Err' : string };
+export type Result_43 = { 'Ok' : DatasetSummary } |
+  { 'Err' : string };
+export type Result_44 = { 'Ok' : DisclosureReport } |
+  { 'Err' : string };
+export type Result_45 = { 'Ok' : EncryptedAggregate } |
+  { 'Err' : string };
+export type Result_46 = { 'Ok' : EventVerificationKey } |
+  { 'Err' : string };
+export type Result_47 = { 'Ok' : LineageGraph } |
+  { 'Err' : string };
+export type Result_48 = { 'Ok' : LinkageQuality } |
+  { 'Err' : string };
+export type Result_49 = { 'Ok' : LlmUsageStats } |
+  { 'Err' : string };
+export type Result_5 = { 'Ok' : string } |
+  { 'Err' : string };
+export type Result_50 = { 'Ok' : MaintenanceStats } |
+  { 'Err' : string };
+export type Result_51 = { 'Ok' : SecureStatistics } |
+  { 'Err' : string };
+export type Result_52 = { 'Ok' : ModelVersion } |
+  { 'Err' : string };
+export type Result_53 = { 'Ok' : CertifiedAudit } |
+  { 'Err' : string };
+export type Result_54 = { 'Ok' : PrivacyBudget } |
+  { 'Err' : string };
+export type Result_55 = { 'Ok' : SensitivityAnalysis } |
+  { 'Err' : string };
+export type Result_56 = { 'Ok' : QueuePosition } |
+  { 'Err' : string };
+export type Result_57 = { 'Ok' : LinkageRun } |
+  { 'Err' : string };
+export type Result_58 = { 'Ok' : RecoveryRequest } |
+  { 'Err' : string };
+export type Result_59 = { 'Ok' : PsiSession } |
+  { 'Err' : string };
+export type Result_6 = { 'Ok' : PauseState } |
+  { 'Err' : string };
+export type Result_60 = { 'Ok' : Array<Array<number>> } |
+  { 'Err' : string };
+export type Result_61 = { 'Ok' : UploadReport } |
+  { 'Err' : string };
+export type Result_62 = { 'Ok' : Array<Role> } |
+  { 'Err' : string };
+export type Result_63 = { 'Ok' : ImportProgress } |
+  { 'Err' : string };
+export type Result_64 = { 'Ok' : InvitationCode } |
+  { 'Err' : string };
+export type Result_65 = { 'Ok' : Array<Invitation> } |
+  { 'Err' : string };
+export type Result_66 = { 'Ok' : bigint } |
+  { 'Err' : string };
+export type Result_67 = { 'Ok' : AgentWithdrawal } |
+  { 'Err' : string };
+export type Result_68 = { 'Ok' : MPCAgent } |
+  { 'Err' : string };
+export type Result_69 = { 'Ok' : PaillierKey } |
+  { 'Err' : string };
+export type Result_7 = { 'Ok' : [] | [Escrow] } |
+  { 'Err' : string };
+export type Result_70 = { 'Ok' : ServicePrincipal } |
+  { 'Err' : string };
+export type Result_71 = { 'Ok' : AnomalyAlert } |
+  { 'Err' : string };
+export type Result_72 = { 'Ok' : RevokedKey } |
+  { 'Err' : string };
+export type Result_73 = { 'Ok' : MaintenanceReport } |
+  { 'Err' : string };
+export type Result_74 = { 'Ok' : Array<PrivateDataSource> } |
+  { 'Err' : string };
+export type Result_75 = { 'Ok' : SecureJoinResult } |
+  { 'Err' : string };
+export type Result_76 = { 'Ok' : SecureSumResult } |
+  { 'Err' : string };
+export type Result_77 = { 'Ok' : [] | [Array<string>] } |
   { 'Err' : string };
-export type Result_1 = { 'Ok' : Uint8Array | number[] } |
+export type Result_78 = { 'Ok' : RateLimits } |
   { 'Err' : string };
-export type Result_2 = { 'Ok' : ComputationResult } |
+export type Result_79 = { 'Ok' : GuardianConfig } |
   { 'Err' : string };
-export type Result_3 = { 'Ok' : MPCComputation } |
+export type Result_8 = { 'Ok' : Array<ProofVerification> } |
   { 'Err' : string };
+export type Result_80 = { 'Ok' : StorageUsage } |
+  { 'Err' : string };
+export type Result_81 = { 'Ok' : [] | [SecureStatistics] } |
+  { 'Err' : string };
+export type Result_82 = { 'Ok' : [] | [ModelVersion] } |
+  { 'Err' : string };
+export type Result_83 = { 'Ok' : ComputationSubscription } |
+  { 'Err' : string };
+export type Result_84 = { 'Ok' : TeamSuggestion } |
+  { 'Err' : string };
+export type Result_85 = { 'Ok' : Config } |
+  { 'Err' : string };
+export type Result_86 = { 'Ok' : ChainVerification } |
+  { 'Err' : string };
+export type Result_87 = { 'Ok' : boolean } |
+  { 'Err' : string };
+export type Result_88 = { 'Ok' : Array<RangeCheck> } |
+  { 'Err' : string };
+export type Result_89 = { 'Ok' : Invitation } |
+  { 'Err' : string };
+export type Result_9 = { 'Ok' : BiddingRound } |
+  { 'Err' : string };
+export interface RetryAttempt {
+  'previous_error' : [] | [string],
+  'attempt' : number,
+  'requested_by' : Principal,
+  'signatures_preserved' : boolean,
+  'timestamp' : bigint,
+}
+export interface RetryPolicy {
+  'max_delay_ms' : bigint,
+  'failure_threshold' : number,
+  'timeout_ms' : bigint,
+  'base_delay_ms' : bigint,
+  'max_retries' : number,
+  'cooldown_secs' : bigint,
+}
+export interface RevokedKey {
+  'key_id' : string,
+  'revoked_at' : bigint,
+  'revoked_by' : Principal,
+  'invalidated' : Array<string>,
+  'reason' : string,
+}
+export type Role = { 'Auditor' : null } |
+  { 'Approver' : null } |
+  { 'Analyst' : null } |
+  { 'Admin' : null };
+export interface RowError { 'line' : bigint, 'message' : string }
+export interface SecureJoinResult {
+  'dataset_id' : string,
+  'rows' : bigint,
+  'psi_session_id' : string,
+  'mismatched_keys' : bigint,
+}
+export interface SecureStatistics {
+  'run_id' : string,
+  'mean' : number,
+  'count' : bigint,
+  'record_count_proof_id' : [] | [string],
+  'variance' : number,
+  'column' : string,
+  'dataset_count' : bigint,
+  'std_dev' : number,
+}
+export interface SecureSumResult {
+  'sum' : number,
+  'mean' : number,
+  'count' : bigint,
+  'column' : string,
+  'dataset_count' : bigint,
+}
+export interface SelectColumn { 'item' : SelectItem, 'name' : string }
+export type SelectItem = { 'Sum' : string } |
+  { 'Group' : string } |
+  { 'Average' : string } |
+  { 'Count' : null };
+export interface SensitivityAnalysis {
+  'requested' : Array<AggregateKind>,
+  'mechanism' : NoiseMechanism,
+  'aggregates' : Array<AggregateAnalysis>,
+  'total_epsilon' : number,
+  'declared_records' : bigint,
+  'warnings' : Array<string>,
+  'query_id' : string,
+  'delta' : number,
+}
+export interface ServicePrincipal {
+  'principal' : Principal,
+  'owner' : Principal,
+  'scopes' : Array<Permission>,
+  'created_at' : bigint,
+  'label' : string,
+  'last_used' : [] | [bigint],
+  'expires_at' : bigint,
+}
+export interface Settings {
+  'vetkd_mode' : VetKdMode,
+  'llm_canister_id' : [] | [Principal],
+  'min_parties' : number,
+  'query_expiry_seconds' : bigint,
+  'invitation_expiry_seconds' : bigint,
+  'threshold_key_name' : string,
+  'vetkd_key_name' : string,
+}
+export interface SignedConsentGraph {
+  'algorithm' : string,
+  'signature' : Uint8Array | number[],
+  'public_key' : Uint8Array | number[],
+  'graph' : ConsentGraph,
+  'key_name' : string,
+}
+export interface SignedEvent {
+  'signature' : Uint8Array | number[],
+  'issued_at' : bigint,
+  'digest' : string,
+  'event_id' : bigint,
+  'event_type' : string,
+  'payload' : string,
+}
+export interface SqlPlan {
+  'select' : Array<SelectColumn>,
+  'group_by' : Array<string>,
+  'conditions' : Array<Condition>,
+}
+export interface SqlResult {
+  'rows' : Array<Array<SqlValue>>,
+  'suppressed_groups' : bigint,
+  'epsilon' : number,
+  'columns' : Array<string>,
+}
+export type SqlValue = { 'Null' : null } |
+  { 'Text' : string } |
+  { 'Number' : number };
+export interface StateChunk {
+  'cursor' : bigint,
+  'data' : Uint8Array | number[],
+  'total_size' : bigint,
+  'next_cursor' : [] | [bigint],
+  'taken_at' : bigint,
+  'snapshot_hash' : string,
+}
+export interface StorageUsage {
+  'principal' : Principal,
+  'used_bytes' : bigint,
+  'quota_bytes' : bigint,
+  'remaining_bytes' : bigint,
+  'dataset_count' : number,
+}
+export interface Suppression {
+  'kind' : SuppressionKind,
+  'location' : string,
+  'record_count' : bigint,
+}
+export type SuppressionKind = { 'SmallCell' : null } |
+  { 'Rounded' : null } |
+  { 'MinCohort' : null };
+export interface TeamSuggestion {
+  'total_price' : bigint,
+  'agents' : Array<MPCAgent>,
+  'agent_ids' : Array<string>,
+  'computation_type' : CapabilityDomain,
+  'required_capabilities' : Array<Capability>,
+}
+export interface TemplateBinding {
+  'values' : Array<[string, string]>,
+  'template_id' : string,
+  'version' : number,
+}
+export type TemplateStatus = { 'Approved' : null } |
+  { 'Rejected' : null } |
+  { 'PendingApproval' : null };
+export interface TemplateVariable {
+  'name' : string,
+  'description' : string,
+  'required' : boolean,
+  'var_type' : VariableType,
+}
+export interface TimeSeriesAnalysis {
+  'insights' : [] | [string],
+  'suppressed_periods' : bigint,
+  'trend' : [] | [Trend],
+  'season_over_season' : Array<PeriodChange>,
+  'run_id' : string,
+  'moving_average' : Array<[] | [number]>,
+  'agent_id' : [] | [string],
+  'period_over_period' : Array<PeriodChange>,
+  'periods' : Array<PeriodValue>,
+  'seasonality' : [] | [Decomposition],
+  'skipped_records' : bigint,
+}
+export interface TimeSeriesRequest {
+  'computation_id' : [] | [string],
+  'period' : Period,
+  'window' : number,
+  'value_column' : [] | [string],
+  'dataset_ids' : Array<string>,
+  'date_column' : string,
+  'season_length' : [] | [number],
+}
+export interface TranscriptEntry {
+  'hash' : string,
+  'step' : string,
+  'timestamp' : bigint,
+  'party' : Principal,
+}
+export interface TransformArgs {
+  'context' : Uint8Array | number[],
+  'response' : HttpResponse,
+}
+export interface Trend {
+  'intercept' : number,
+  'slope' : number,
+  'r_squared' : number,
+}
+export interface UploadReport {
+  'dataset_id' : string,
+  'validation' : UploadValidation,
+}
+export interface UploadValidation {
+  'rejected_rows' : Array<RowError>,
+  'records' : bigint,
+  'schema' : string,
+  'inferred_schema' : boolean,
+  'column_errors' : Array<ColumnError>,
+  'format' : DataFormat,
+}
+export type VariableType = { 'Text' : null } |
+  { 'Boolean' : null } |
+  { 'Number' : null } |
+  { 'Integer' : null };
+export type VetKdMode = { 'Mock' : null } |
+  { 'Real' : null };
 export type VetkdEncryptedKeyResponse = { 'Ok' : Uint8Array | number[] } |
   { 'Err' : string };
 export type VetkdPublicKeyResponse = { 'Ok' : Uint8Array | number[] } |
@@ -79,51 +1567,424 @@ export interface Vote {
   'voter' : Principal,
   'timestamp' : bigint,
 }
+export type WindowState = { 'Open' : null } |
+  { 'Closed' : null } |
+  { 'Expired' : null } |
+  { 'Aborted' : null };
+export type WorkflowEvent = {
+    'ComputationFailed' : { 'request_id' : string, 'error' : string }
+  } |
+  {
+    'ProofVerified' : {
+      'computation_id' : string,
+      'verified' : boolean,
+      'proof_id' : string,
+    }
+  } |
+  { 'ComputationCompleted' : { 'request_id' : string } } |
+  { 'DatasetUploaded' : { 'dataset_id' : string, 'name' : string } } |
+  {
+    'VoteCast' : {
+      'request_id' : string,
+      'status' : string,
+      'decision' : string,
+    }
+  };
+export interface WrappedKey {
+  'encrypted_key' : Uint8Array | number[],
+  'key_id' : string,
+  'expires_at' : [] | [bigint],
+}
 export interface _SERVICE {
+  'abort_key_ceremony' : ActorMethod<[string], Result>,
+  'add_organization_member' : ActorMethod<[Principal, boolean], Result_1>,
+  'agent_heartbeat' : ActorMethod<[], Result_2>,
+  'analyze_time_series' : ActorMethod<[TimeSeriesRequest], Result_3>,
+  'append_to_dataset' : ActorMethod<[string, Uint8Array | number[]], Result_4>,
+  'approve_identity_recovery' : ActorMethod<[string], Result_5>,
+  'approve_unpause' : ActorMethod<[], Result_6>,
+  'assign_computation_agent' : ActorMethod<[string, [] | [string]], Result_7>,
+  'batch_verify_privacy_proofs' : ActorMethod<[Array<string>], Result_8>,
+  'cancel_computation_request' : ActorMethod<[string], Result_5>,
+  'cancel_identity_recovery' : ActorMethod<[string], Result_5>,
+  'cancel_llm_query' : ActorMethod<[string], Result_5>,
   'chat' : ActorMethod<[Array<ChatMessage>], string>,
-  'create_computation_request' : ActorMethod<[string, string], Result>,
-  'create_llm_query' : ActorMethod<[string, Array<string>], Result>,
-  'derive_agent_encryption_key' : ActorMethod<[string], Result_1>,
-  'execute_computation_request' : ActorMethod<[string], Result>,
-  'execute_llm_query' : ActorMethod<[string], Result>,
+  'clear_load_test_data' : ActorMethod<[], Result_5>,
+  'close_bidding_round' : ActorMethod<[string], Result_9>,
+  'close_secure_session' : ActorMethod<[string], Result_10>,
+  'commit_ceremony_entropy' : ActorMethod<
+    [string, Uint8Array | number[]],
+    Result_11
+  >,
+  'compare_cohorts' : ActorMethod<[CohortComparisonRequest], Result_12>,
+  'complete_set_intersection' : ActorMethod<
+    [string, Array<Uint8Array | number[]>],
+    Result_13
+  >,
+  'correlate_columns' : ActorMethod<[CorrelationRequest], Result_14>,
+  'create_computation_request' : ActorMethod<
+    [string, string, [] | [string], string, [] | [Array<string>]],
+    Result_5
+  >,
+  'create_federated_model' : ActorMethod<
+    [string, Array<Principal>, Array<number>],
+    Result_15
+  >,
+  'create_llm_query' : ActorMethod<
+    [string, Array<string>, [] | [Array<string>], [] | [DpConfig], string],
+    Result_5
+  >,
+  'create_organization' : ActorMethod<[string], Result_1>,
+  'create_prompt_template' : ActorMethod<
+    [string, string, string, Array<TemplateVariable>],
+    Result_16
+  >,
+  'create_sql_query' : ActorMethod<
+    [string, Array<string>, [] | [DpConfig], string],
+    Result_5
+  >,
+  'cross_tabulate' : ActorMethod<[ContingencyRequest], Result_17>,
+  'define_cohort' : ActorMethod<[string, string], Result_18>,
+  'delegate_session' : ActorMethod<[Principal, string, bigint], Result_19>,
+  'delete_prompt_template' : ActorMethod<[string], Result_10>,
+  'deploy_mpc_agents' : ActorMethod<[Array<string>, Array<string>], Result_5>,
+  'derive_agent_encryption_key' : ActorMethod<
+    [string, Uint8Array | number[]],
+    Result_20
+  >,
+  'derive_user_vetkd_key' : ActorMethod<
+    [string, Uint8Array | number[], [] | [bigint], Uint8Array | number[]],
+    Result_21
+  >,
+  'estimate_computation_cost' : ActorMethod<[CostEstimateRequest], Result_22>,
+  'execute_computation_request' : ActorMethod<[string], Result_23>,
+  'execute_identity_recovery' : ActorMethod<[string], Result_5>,
+  'execute_llm_query' : ActorMethod<[string], Result_5>,
   'execute_secure_mpc_computation' : ActorMethod<
     [string, string, Array<string>],
-    Result_2
+    Result_24
   >,
-  'generate_privacy_proof' : ActorMethod<[string], Result>,
+  'export_audit_log' : ActorMethod<
+    [bigint, bigint, AuditExportFormat, [] | [bigint], [] | [number]],
+    Result_25
+  >,
+  'export_consent_graph' : ActorMethod<[string], Result_26>,
+  'export_proof' : ActorMethod<[string, ProofExportFormat], Result_27>,
+  'export_state_chunk' : ActorMethod<[bigint], Result_28>,
+  'fit_regression' : ActorMethod<[RegressionRequest], Result_29>,
+  'generate_load_test_data' : ActorMethod<[number, number, bigint], Result_30>,
+  'generate_privacy_proof' : ActorMethod<[string], Result_5>,
+  'get_active_prompt_template' : ActorMethod<[string], [] | [PromptTemplate]>,
+  'get_activity_timeline' : ActorMethod<
+    [string, bigint, [] | [bigint], [] | [number]],
+    Result_31
+  >,
+  'get_agent_balance' : ActorMethod<[string], bigint>,
+  'get_agent_changelog' : ActorMethod<[string], Array<AgentVersion>>,
+  'get_agent_liveness' : ActorMethod<[string], AgentLiveness>,
+  'get_agent_team' : ActorMethod<[string], Result_32>,
   'get_all_computation_requests' : ActorMethod<[], Array<MPCComputation>>,
   'get_all_data_sources' : ActorMethod<[], Array<PrivateDataSource>>,
   'get_all_datasets' : ActorMethod<[], Array<PrivateDataSource>>,
-  'get_computation_request' : ActorMethod<[string], Result_3>,
+  'get_anomaly_alerts' : ActorMethod<[[] | [AlertStatus]], Result_33>,
+  'get_audit_inclusion_proof' : ActorMethod<[bigint], Result_34>,
+  'get_audit_log' : ActorMethod<[[] | [bigint], [] | [number]], Result_35>,
+  'get_bidding_round' : ActorMethod<[string], [] | [BiddingRound]>,
+  'get_billing_history' : ActorMethod<[], BillingHistory>,
+  'get_checkpointed_computation' : ActorMethod<[string], Result_36>,
+  'get_computation_escrow' : ActorMethod<[string], [] | [Escrow]>,
+  'get_computation_payments' : ActorMethod<[string], Array<AgentPayment>>,
+  'get_computation_progress' : ActorMethod<[string], Result_37>,
+  'get_computation_queue' : ActorMethod<[], Array<QueuedComputation>>,
+  'get_computation_request' : ActorMethod<[string], Result_38>,
+  'get_computation_state_at' : ActorMethod<[string, AsOf], Result_39>,
+  'get_computation_subscriptions' : ActorMethod<
+    [string],
+    Array<ComputationSubscription>
+  >,
+  'get_config' : ActorMethod<[], Config>,
   'get_data_sources_for_user' : ActorMethod<[], Array<PrivateDataSource>>,
+  'get_dataset_access_history' : ActorMethod<[string], Result_40>,
+  'get_dataset_key_rotations' : ActorMethod<[string], Result_41>,
+  'get_dataset_permissions_at' : ActorMethod<[string, AsOf], Result_42>,
+  'get_dataset_summary' : ActorMethod<[string], Result_43>,
+  'get_decryption_window' : ActorMethod<[string], [] | [DecryptionWindow]>,
+  'get_differential_privacy_config' : ActorMethod<[], DpConfig>,
+  'get_disclosure_policy' : ActorMethod<[], DisclosurePolicy>,
+  'get_disclosure_report' : ActorMethod<[string], Result_44>,
+  'get_encrypted_aggregate' : ActorMethod<[string], Result_45>,
+  'get_event_verification_key' : ActorMethod<[], Result_46>,
+  'get_events_since' : ActorMethod<[bigint, [] | [number]], Array<EventRecord>>,
+  'get_federated_model' : ActorMethod<[string], Result_15>,
+  'get_history_sequence' : ActorMethod<[], bigint>,
+  'get_key_ceremony' : ActorMethod<[string], [] | [KeyCeremony]>,
+  'get_latest_event_seq' : ActorMethod<[], bigint>,
+  'get_lineage' : ActorMethod<[string], Result_47>,
+  'get_linkage_quality' : ActorMethod<[string], Result_48>,
+  'get_llm_backend' : ActorMethod<[], LlmBackend>,
+  'get_llm_health' : ActorMethod<[], LlmHealth>,
   'get_llm_queries' : ActorMethod<[], Array<LLMQueryRequest>>,
+  'get_llm_usage_stats' : ActorMethod<[], Result_49>,
+  'get_maintenance_stats' : ActorMethod<[], Result_50>,
+  'get_masked_statistics' : ActorMethod<[string], Result_51>,
+  'get_model_version' : ActorMethod<[string, [] | [bigint]], Result_52>,
+  'get_my_encrypted_result' : ActorMethod<[string], Result_23>,
+  'get_my_llm_usage' : ActorMethod<[], PrincipalLlmUsage>,
+  'get_my_organization' : ActorMethod<[], [] | [Organization]>,
+  'get_my_recovery_notifications' : ActorMethod<
+    [],
+    Array<RecoveryNotification>
+  >,
+  'get_my_result_key' : ActorMethod<
+    [string, boolean, Uint8Array | number[]],
+    Result_21
+  >,
+  'get_my_roles' : ActorMethod<[], Array<Role>>,
+  'get_my_structured_result' : ActorMethod<[string], Result_23>,
+  'get_open_bidding_rounds' : ActorMethod<[], Array<BiddingRound>>,
+  'get_pause_state' : ActorMethod<[], PauseState>,
   'get_pending_queries_for_user' : ActorMethod<[], Array<LLMQueryRequest>>,
+  'get_permission_matrix' : ActorMethod<[], Array<[Role, Array<Permission>]>>,
+  'get_privacy_audit' : ActorMethod<[string], Result_53>,
+  'get_privacy_budget' : ActorMethod<[string], Result_54>,
+  'get_prompt_injection_policy' : ActorMethod<[], InjectionPolicy>,
+  'get_prompt_template' : ActorMethod<
+    [string, [] | [number]],
+    [] | [PromptTemplate]
+  >,
   'get_query_by_id' : ActorMethod<[string], [] | [LLMQueryRequest]>,
+  'get_query_sensitivity' : ActorMethod<[string], Result_55>,
+  'get_queue_position' : ActorMethod<[string], Result_56>,
+  'get_rate_limits' : ActorMethod<[], RateLimits>,
+  'get_record_linkage' : ActorMethod<[string], Result_57>,
+  'get_recovery_guardians' : ActorMethod<[Principal], [] | [GuardianConfig]>,
+  'get_recovery_request' : ActorMethod<[string], Result_58>,
   'get_registered_parties' : ActorMethod<[], Array<PartyInfo>>,
-  'get_user_identity' : ActorMethod<[], Result>,
+  'get_set_intersection' : ActorMethod<[string], Result_59>,
+  'get_set_intersection_result' : ActorMethod<[string], Result_13>,
+  'get_signed_events' : ActorMethod<
+    [[] | [bigint], [] | [number]],
+    Array<SignedEvent>
+  >,
+  'get_storage_usage' : ActorMethod<[], StorageUsage>,
+  'get_suspended_principals' : ActorMethod<[], Array<Principal>>,
+  'get_tag_vocabulary' : ActorMethod<[], Array<string>>,
+  'get_text_embeddings' : ActorMethod<[Array<string>], Result_60>,
+  'get_unsettled_withdrawal' : ActorMethod<[string], [] | [PendingTransfer]>,
+  'get_upload_report' : ActorMethod<[string], Result_61>,
+  'get_user_identity' : ActorMethod<[], Result_5>,
+  'get_vetkd_key_ttl' : ActorMethod<[], [] | [bigint]>,
+  'grant_role' : ActorMethod<[Principal, Role], Result_62>,
+  'import_state_chunk' : ActorMethod<[ImportChunk], Result_63>,
+  'invite_party' : ActorMethod<[Invitee, Role], Result_64>,
+  'is_agent_available' : ActorMethod<[string], boolean>,
+  'link_records' : ActorMethod<
+    [string, string, number, [] | [string]],
+    Result_57
+  >,
+  'list_cohorts' : ActorMethod<[], Array<Cohort>>,
+  'list_invitations' : ActorMethod<[], Result_65>,
+  'list_llm_http_providers' : ActorMethod<[], Array<HttpProviderInfo>>,
+  'list_mpc_agents' : ActorMethod<[], Array<MPCAgent>>,
+  'list_my_service_principals' : ActorMethod<[], Array<ServicePrincipal>>,
+  'list_my_sessions' : ActorMethod<[], Array<DeviceSession>>,
+  'list_prompt_templates' : ActorMethod<[], Array<PromptTemplate>>,
+  'list_proofs_for_computation' : ActorMethod<[string], Array<PrivacyProof>>,
+  'list_revoked_keys' : ActorMethod<[], Array<RevokedKey>>,
+  'migrate_proof_hashes' : ActorMethod<[], Result_66>,
+  'open_bidding_round' : ActorMethod<[string, bigint, [] | [number]], Result_9>,
+  'open_masked_statistics' : ActorMethod<[Array<string>, string], Result_5>,
+  'paillier_sum' : ActorMethod<[string, Array<string>, string], Result_45>,
+  'pause_canister' : ActorMethod<[string], Result_6>,
+  'preview_prompt_template' : ActorMethod<
+    [string, [] | [number], Array<[string, string]>],
+    Result_5
+  >,
   'prompt' : ActorMethod<[string], string>,
-  'register_party' : ActorMethod<[string, string], Result>,
-  'register_user_identity' : ActorMethod<[string, string], Result>,
-  'save_computation_results' : ActorMethod<[string, string], Result>,
+  'reconcile_agent_withdrawal' : ActorMethod<[string], Result_67>,
+  'refund_computation_escrow' : ActorMethod<[string], Result_7>,
+  'register_mpc_agent' : ActorMethod<
+    [string, string, Array<Capability>, bigint],
+    Result_68
+  >,
+  'register_paillier_key' : ActorMethod<
+    [Uint8Array | number[], Array<Principal>],
+    Result_69
+  >,
+  'register_party' : ActorMethod<[string, string], Result_5>,
+  'register_service_principal' : ActorMethod<
+    [Principal, string, Array<Permission>, bigint],
+    Result_70
+  >,
+  'register_user_identity' : ActorMethod<[string, string], Result_5>,
+  'remove_llm_http_provider' : ActorMethod<[string], Result_10>,
+  'remove_organization_member' : ActorMethod<[Principal], Result_1>,
+  'renew_secure_session' : ActorMethod<[string], Result_66>,
+  'request_identity_recovery' : ActorMethod<[Principal], Result_58>,
+  'reset_llm_circuit' : ActorMethod<[], Result_10>,
+  'respond_set_intersection' : ActorMethod<
+    [string, Array<Uint8Array | number[]>, Array<Uint8Array | number[]>],
+    Result_59
+  >,
+  'retry_computation' : ActorMethod<[string], Result_5>,
+  'reveal_ceremony_entropy' : ActorMethod<
+    [string, Uint8Array | number[]],
+    Result_11
+  >,
+  'review_anomaly_alert' : ActorMethod<[bigint, boolean], Result_71>,
+  'review_prompt_template' : ActorMethod<
+    [string, number, boolean, [] | [string]],
+    Result_16
+  >,
+  'revise_prompt_template' : ActorMethod<
+    [string, string, Array<TemplateVariable>],
+    Result_16
+  >,
+  'revoke_key' : ActorMethod<[string, string], Result_72>,
+  'revoke_role' : ActorMethod<[Principal, Role], Result_62>,
+  'revoke_service_principal' : ActorMethod<[Principal], Result_70>,
+  'revoke_session' : ActorMethod<[Principal], Result_19>,
+  'rotate_dataset_key' : ActorMethod<[string], Result_41>,
+  'run_maintenance_now' : ActorMethod<[], Result_73>,
+  'search_agents' : ActorMethod<[AgentFilter], Array<MPCAgent>>,
+  'search_datasets' : ActorMethod<[Array<string>, string], Result_74>,
   'secure_agent_communication' : ActorMethod<
     [string, string, Uint8Array | number[]],
-    Result_1
+    Result_20
+  >,
+  'secure_aggregation_key' : ActorMethod<
+    [string, Principal, Uint8Array | number[]],
+    VetkdEncryptedKeyResponse
+  >,
+  'secure_join' : ActorMethod<[string, string, string, string], Result_75>,
+  'secure_statistics' : ActorMethod<
+    [Array<string>, string, [] | [string]],
+    Result_51
+  >,
+  'secure_sum' : ActorMethod<[Array<string>, string, [] | [string]], Result_76>,
+  'set_agent_liveness_window' : ActorMethod<[bigint], Result_10>,
+  'set_agent_owner' : ActorMethod<[string, Principal], Result_10>,
+  'set_anomaly_auto_suspend' : ActorMethod<[boolean], Result_10>,
+  'set_computation_llm_provider' : ActorMethod<
+    [string, [] | [string]],
+    Result_10
+  >,
+  'set_computation_priority' : ActorMethod<[string, Priority], Result_10>,
+  'set_computation_prompt_template' : ActorMethod<
+    [string, [] | [TemplateBinding]],
+    Result_10
+  >,
+  'set_dataset_dp_bounds' : ActorMethod<[string, [] | [DpBounds]], Result_54>,
+  'set_dataset_purposes' : ActorMethod<[string, Array<string>], Result_77>,
+  'set_dataset_tags' : ActorMethod<[string, Array<string>], Result_2>,
+  'set_default_rate_limit' : ActorMethod<[RateLimit], Result_78>,
+  'set_default_storage_quota' : ActorMethod<[bigint], Result_10>,
+  'set_differential_privacy_config' : ActorMethod<[DpConfig], Result_10>,
+  'set_disclosure_policy' : ActorMethod<[DisclosurePolicy], Result_10>,
+  'set_llm_backend' : ActorMethod<[LlmBackend], Result_10>,
+  'set_llm_budget' : ActorMethod<[Principal, [] | [LlmBudget]], Result_10>,
+  'set_llm_http_provider' : ActorMethod<[HttpProvider], Result_10>,
+  'set_llm_retry_policy' : ActorMethod<[RetryPolicy], Result_10>,
+  'set_load_test_enabled' : ActorMethod<[boolean], Result_5>,
+  'set_max_concurrent_executions' : ActorMethod<[number], Result_10>,
+  'set_payment_ledger' : ActorMethod<[[] | [Principal]], Result_10>,
+  'set_principal_rate_limit' : ActorMethod<
+    [Principal, [] | [RateLimit]],
+    Result_78
+  >,
+  'set_privacy_budget' : ActorMethod<[string, number], Result_54>,
+  'set_prompt_injection_policy' : ActorMethod<[InjectionPolicy], Result_10>,
+  'set_recovery_guardians' : ActorMethod<[Array<Principal>, number], Result_79>,
+  'set_retention_window' : ActorMethod<[bigint], Result_10>,
+  'set_storage_quota' : ActorMethod<[Principal, [] | [bigint]], Result_80>,
+  'set_tag_vocabulary' : ActorMethod<[Array<string>], Result_2>,
+  'set_unpause_approvals' : ActorMethod<[number], Result_6>,
+  'set_vetkd_key_ttl' : ActorMethod<[[] | [bigint]], Result_10>,
+  'share_results' : ActorMethod<[string, Principal], Result_5>,
+  'share_with_workspace' : ActorMethod<[string, string], Result_2>,
+  'sign_llm_query' : ActorMethod<[string], Result_5>,
+  'start_checkpointed_computation' : ActorMethod<
+    [string, string, [] | [bigint]],
+    Result_36
   >,
-  'sign_llm_query' : ActorMethod<[string], Result>,
+  'start_key_ceremony' : ActorMethod<[string, Array<Principal>], Result>,
+  'start_set_intersection' : ActorMethod<
+    [string, string, Array<Uint8Array | number[]>, PsiMode],
+    Result_59
+  >,
+  'submit_bid' : ActorMethod<[string, string, bigint, bigint], Result_10>,
+  'submit_column_shares' : ActorMethod<
+    [
+      string,
+      string,
+      Array<BigUint64Array | bigint[]>,
+      [] | [Array<BigUint64Array | bigint[]>],
+    ],
+    Result_10
+  >,
+  'submit_linkage_encodings' : ActorMethod<
+    [string, Array<Uint8Array | number[]>],
+    Result_10
+  >,
+  'submit_masked_statistics' : ActorMethod<
+    [string, BigUint64Array | bigint[]],
+    Result_81
+  >,
+  'submit_model_update' : ActorMethod<
+    [string, bigint, BigUint64Array | bigint[]],
+    Result_82
+  >,
+  'submit_partial_decryption' : ActorMethod<
+    [string, Uint8Array | number[]],
+    Result_45
+  >,
+  'subscribe_to_computation' : ActorMethod<
+    [string, Principal, string],
+    Result_83
+  >,
+  'suggest_agent_team' : ActorMethod<
+    [CapabilityDomain, bigint, [] | [number]],
+    Result_84
+  >,
+  'transform_llm_response' : ActorMethod<[TransformArgs], HttpResponse>,
+  'unshare_from_workspace' : ActorMethod<[string, string], Result_2>,
+  'unsubscribe_from_computation' : ActorMethod<
+    [string, Principal, string],
+    Result_10
+  >,
+  'update_agent_capabilities' : ActorMethod<
+    [string, Array<Capability>, string],
+    Result_68
+  >,
+  'update_config' : ActorMethod<[ConfigUpdate], Result_85>,
+  'update_dataset_metadata' : ActorMethod<[string, DatasetMetadata], Result_10>,
+  'update_dataset_schema' : ActorMethod<[string, string], Result_10>,
   'upload_encrypted_dataset' : ActorMethod<
     [string, Uint8Array | number[], string, number],
-    Result
+    Result_5
+  >,
+  'upload_paillier_column' : ActorMethod<
+    [string, string, string, Array<Uint8Array | number[]>],
+    Result_10
   >,
   'upload_private_data' : ActorMethod<
-    [string, Uint8Array | number[], string],
-    Result
+    [string, Uint8Array | number[], string, [] | [DatasetMetadata]],
+    Result_5
   >,
+  'validate_upload' : ActorMethod<[Uint8Array | number[], string], Result_4>,
+  'verify_audit_inclusion_proof' : ActorMethod<[AuditInclusionProof], boolean>,
+  'verify_audit_log' : ActorMethod<[], Result_86>,
+  'verify_event' : ActorMethod<[SignedEvent], Result_87>,
+  'verify_privacy_proof' : ActorMethod<[string], Result_87>,
+  'verify_range_proofs' : ActorMethod<[string], Result_88>,
   'vetkd_encrypted_key' : ActorMethod<
     [Uint8Array | number[], Uint8Array | number[]],
     VetkdEncryptedKeyResponse
   >,
   'vetkd_public_key' : ActorMethod<[], VetkdPublicKeyResponse>,
-  'vote_on_computation_request' : ActorMethod<[string, string], Result>,
+  'vote_on_computation_request' : ActorMethod<[string, string], Result_5>,
+  'withdraw_agent_earnings' : ActorMethod<[string], Result_67>,
+  'withdraw_invitation' : ActorMethod<[string], Result_89>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
@@ -1,20 +1,628 @@
 export const idlFactory = ({ IDL }) => {
+  const VetKdMode = IDL.Variant({ 'Mock' : IDL.Null, 'Real' : IDL.Null });
+  const BootstrapParty = IDL.Record({
+    'principal' : IDL.Principal,
+    'name' : IDL.Text,
+    'role' : IDL.Text,
+  });
+  const InitArgs = IDL.Record({
+    'vetkd_mode' : IDL.Opt(VetKdMode),
+    'llm_canister_id' : IDL.Opt(IDL.Principal),
+    'admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'threshold_key_name' : IDL.Opt(IDL.Text),
+    'parties' : IDL.Opt(IDL.Vec(BootstrapParty)),
+    'vetkd_key_name' : IDL.Opt(IDL.Text),
+  });
+  const CeremonyContribution = IDL.Record({
+    'committed_at' : IDL.Opt(IDL.Nat64),
+    'revealed_at' : IDL.Opt(IDL.Nat64),
+    'revealed' : IDL.Bool,
+    'party' : IDL.Principal,
+    'commitment' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+  const CeremonyPhase = IDL.Variant({
+    'Reveal' : IDL.Null,
+    'Complete' : IDL.Null,
+    'Aborted' : IDL.Null,
+    'Commit' : IDL.Null,
+  });
+  const TranscriptEntry = IDL.Record({
+    'hash' : IDL.Text,
+    'step' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+    'party' : IDL.Principal,
+  });
+  const KeyCeremony = IDL.Record({
+    'transcript_hash' : IDL.Opt(IDL.Text),
+    'contributions' : IDL.Vec(CeremonyContribution),
+    'initiator' : IDL.Principal,
+    'deadline' : IDL.Nat64,
+    'created_at' : IDL.Nat64,
+    'root_context' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'workspace' : IDL.Text,
+    'phase' : CeremonyPhase,
+    'completed_at' : IDL.Opt(IDL.Nat64),
+    'transcript' : IDL.Vec(TranscriptEntry),
+  });
+  const Result = IDL.Variant({ 'Ok' : KeyCeremony, 'Err' : IDL.Text });
+  const Organization = IDL.Record({
+    'id' : IDL.Text,
+    'members' : IDL.Vec(IDL.Principal),
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'admins' : IDL.Vec(IDL.Principal),
+  });
+  const Result_1 = IDL.Variant({ 'Ok' : Organization, 'Err' : IDL.Text });
+  const Result_2 = IDL.Variant({ 'Ok' : IDL.Vec(IDL.Text), 'Err' : IDL.Text });
+  const Period = IDL.Variant({
+    'Day' : IDL.Null,
+    'Quarter' : IDL.Null,
+    'Week' : IDL.Null,
+    'Year' : IDL.Null,
+    'Month' : IDL.Null,
+  });
+  const TimeSeriesRequest = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'period' : Period,
+    'window' : IDL.Nat32,
+    'value_column' : IDL.Opt(IDL.Text),
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'date_column' : IDL.Text,
+    'season_length' : IDL.Opt(IDL.Nat32),
+  });
+  const Trend = IDL.Record({
+    'intercept' : IDL.Float64,
+    'slope' : IDL.Float64,
+    'r_squared' : IDL.Float64,
+  });
+  const PeriodChange = IDL.Record({
+    'period' : IDL.Text,
+    'percent_change' : IDL.Opt(IDL.Float64),
+    'change' : IDL.Float64,
+    'compared_to' : IDL.Text,
+  });
+  const PeriodValue = IDL.Record({
+    'records' : IDL.Nat64,
+    'value' : IDL.Opt(IDL.Float64),
+    'period' : IDL.Text,
+  });
+  const Decomposition = IDL.Record({
+    'trend' : IDL.Vec(IDL.Opt(IDL.Float64)),
+    'seasonal' : IDL.Vec(IDL.Float64),
+    'residual' : IDL.Vec(IDL.Opt(IDL.Float64)),
+  });
+  const TimeSeriesAnalysis = IDL.Record({
+    'insights' : IDL.Opt(IDL.Text),
+    'suppressed_periods' : IDL.Nat64,
+    'trend' : IDL.Opt(Trend),
+    'season_over_season' : IDL.Vec(PeriodChange),
+    'run_id' : IDL.Text,
+    'moving_average' : IDL.Vec(IDL.Opt(IDL.Float64)),
+    'agent_id' : IDL.Opt(IDL.Text),
+    'period_over_period' : IDL.Vec(PeriodChange),
+    'periods' : IDL.Vec(PeriodValue),
+    'seasonality' : IDL.Opt(Decomposition),
+    'skipped_records' : IDL.Nat64,
+  });
+  const Result_3 = IDL.Variant({ 'Ok' : TimeSeriesAnalysis, 'Err' : IDL.Text });
+  const RowError = IDL.Record({ 'line' : IDL.Nat64, 'message' : IDL.Text });
+  const ColumnError = IDL.Record({
+    'rows' : IDL.Nat64,
+    'message' : IDL.Text,
+    'first_line' : IDL.Opt(IDL.Nat64),
+    'column' : IDL.Text,
+  });
+  const DataFormat = IDL.Variant({
+    'Csv' : IDL.Null,
+    'Parquet' : IDL.Null,
+    'JsonLines' : IDL.Null,
+  });
+  const UploadValidation = IDL.Record({
+    'rejected_rows' : IDL.Vec(RowError),
+    'records' : IDL.Nat64,
+    'schema' : IDL.Text,
+    'inferred_schema' : IDL.Bool,
+    'column_errors' : IDL.Vec(ColumnError),
+    'format' : DataFormat,
+  });
+  const Result_4 = IDL.Variant({ 'Ok' : UploadValidation, 'Err' : IDL.Text });
+  const Result_5 = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
+  const PauseState = IDL.Record({
+    'required_approvals' : IDL.Nat32,
+    'paused_at' : IDL.Opt(IDL.Nat64),
+    'paused_by' : IDL.Opt(IDL.Principal),
+    'paused' : IDL.Bool,
+    'unpause_approvals' : IDL.Vec(IDL.Principal),
+    'reason' : IDL.Opt(IDL.Text),
+  });
+  const Result_6 = IDL.Variant({ 'Ok' : PauseState, 'Err' : IDL.Text });
+  const EscrowStatus = IDL.Variant({
+    'Refunded' : IDL.Null,
+    'Released' : IDL.Null,
+    'Locked' : IDL.Null,
+  });
+  const PendingTransfer = IDL.Record({
+    'to' : IDL.Principal,
+    'fee' : IDL.Nat64,
+    'last_error' : IDL.Opt(IDL.Text),
+    'memo' : IDL.Vec(IDL.Nat8),
+    'created_at_time' : IDL.Nat64,
+    'amount' : IDL.Nat64,
+  });
+  const Escrow = IDL.Record({
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : EscrowStatus,
+    'computation_id' : IDL.Text,
+    'locked_at' : IDL.Nat64,
+    'unsettled_refund' : IDL.Opt(PendingTransfer),
+    'agent_id' : IDL.Text,
+    'refund_block' : IDL.Opt(IDL.Nat),
+    'payer' : IDL.Principal,
+    'lock_block' : IDL.Nat,
+    'amount' : IDL.Nat64,
+    'settled_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_7 = IDL.Variant({ 'Ok' : IDL.Opt(Escrow), 'Err' : IDL.Text });
+  const ProofVerification = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'verified' : IDL.Bool,
+    'proof_id' : IDL.Text,
+    'error' : IDL.Opt(IDL.Text),
+  });
+  const Result_8 = IDL.Variant({
+    'Ok' : IDL.Vec(ProofVerification),
+    'Err' : IDL.Text,
+  });
   const ChatMessage = IDL.Record({ 'content' : IDL.Text, 'role' : IDL.Text });
-  const Result = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
-  const Result_1 = IDL.Variant({ 'Ok' : IDL.Vec(IDL.Nat8), 'Err' : IDL.Text });
+  const BiddingStatus = IDL.Variant({
+    'Open' : IDL.Null,
+    'NoBids' : IDL.Null,
+    'Awarded' : IDL.Null,
+  });
+  const Bid = IDL.Record({
+    'agent_id' : IDL.Text,
+    'eta_seconds' : IDL.Nat64,
+    'price' : IDL.Nat64,
+    'bidder' : IDL.Principal,
+    'reputation_score' : IDL.Nat32,
+    'submitted_at' : IDL.Nat64,
+  });
+  const BiddingRound = IDL.Record({
+    'status' : BiddingStatus,
+    'computation_id' : IDL.Text,
+    'requester' : IDL.Principal,
+    'bids' : IDL.Vec(Bid),
+    'spec' : IDL.Text,
+    'deadline' : IDL.Nat64,
+    'max_winners' : IDL.Nat32,
+    'winners' : IDL.Vec(Bid),
+  });
+  const Result_9 = IDL.Variant({ 'Ok' : BiddingRound, 'Err' : IDL.Text });
+  const Result_10 = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const Result_11 = IDL.Variant({ 'Ok' : CeremonyPhase, 'Err' : IDL.Text });
+  const CohortComparisonRequest = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'min_cohort_size' : IDL.Opt(IDL.Nat64),
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'epsilon' : IDL.Opt(IDL.Float64),
+    'cohort_ids' : IDL.Vec(IDL.Text),
+    'outcome' : IDL.Text,
+  });
+  const ReleasedValue = IDL.Variant({
+    'Counts' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+    'Mean' : IDL.Float64,
+  });
+  const CohortResult = IDL.Record({
+    'mean_difference' : IDL.Opt(IDL.Float64),
+    'suppressed' : IDL.Bool,
+    'records' : IDL.Opt(IDL.Nat64),
+    'name' : IDL.Text,
+    'cohort_id' : IDL.Text,
+    'outcome' : IDL.Opt(ReleasedValue),
+  });
+  const CohortComparison = IDL.Record({
+    'min_cohort_size' : IDL.Nat64,
+    'cohorts' : IDL.Vec(CohortResult),
+    'epsilon' : IDL.Float64,
+    'outcome' : IDL.Text,
+  });
+  const Result_12 = IDL.Variant({ 'Ok' : CohortComparison, 'Err' : IDL.Text });
+  const PsiResult = IDL.Record({
+    'session_id' : IDL.Text,
+    'exact' : IDL.Bool,
+    'intersection_size' : IDL.Nat64,
+    'matched_indices' : IDL.Vec(IDL.Nat64),
+  });
+  const Result_13 = IDL.Variant({ 'Ok' : PsiResult, 'Err' : IDL.Text });
+  const CorrelationRequest = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'epsilon' : IDL.Opt(IDL.Float64),
+    'columns' : IDL.Vec(IDL.Text),
+  });
+  const CorrelationMatrix = IDL.Record({
+    'records' : IDL.Nat64,
+    'spearman' : IDL.Vec(IDL.Vec(IDL.Float64)),
+    'epsilon' : IDL.Float64,
+    'pearson' : IDL.Vec(IDL.Vec(IDL.Float64)),
+    'columns' : IDL.Vec(IDL.Text),
+  });
+  const Result_14 = IDL.Variant({ 'Ok' : CorrelationMatrix, 'Err' : IDL.Text });
+  const FederatedModel = IDL.Record({
+    'id' : IDL.Text,
+    'participants' : IDL.Vec(IDL.Principal),
+    'submitted' : IDL.Vec(IDL.Principal),
+    'owner' : IDL.Principal,
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'dimension' : IDL.Nat64,
+    'latest_version' : IDL.Nat64,
+    'round' : IDL.Nat64,
+  });
+  const Result_15 = IDL.Variant({ 'Ok' : FederatedModel, 'Err' : IDL.Text });
+  const NoiseMechanism = IDL.Variant({
+    'Laplace' : IDL.Null,
+    'Gaussian' : IDL.Null,
+  });
+  const DpConfig = IDL.Record({
+    'mechanism' : NoiseMechanism,
+    'epsilon' : IDL.Float64,
+    'delta' : IDL.Float64,
+  });
+  const VariableType = IDL.Variant({
+    'Text' : IDL.Null,
+    'Boolean' : IDL.Null,
+    'Number' : IDL.Null,
+    'Integer' : IDL.Null,
+  });
+  const TemplateVariable = IDL.Record({
+    'name' : IDL.Text,
+    'description' : IDL.Text,
+    'required' : IDL.Bool,
+    'var_type' : VariableType,
+  });
+  const TemplateStatus = IDL.Variant({
+    'Approved' : IDL.Null,
+    'Rejected' : IDL.Null,
+    'PendingApproval' : IDL.Null,
+  });
+  const PromptTemplate = IDL.Record({
+    'id' : IDL.Text,
+    'status' : TemplateStatus,
+    'body' : IDL.Text,
+    'reviewed_at' : IDL.Opt(IDL.Nat64),
+    'reviewed_by' : IDL.Opt(IDL.Principal),
+    'computation_type' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'author' : IDL.Principal,
+    'version' : IDL.Nat32,
+    'variables' : IDL.Vec(TemplateVariable),
+    'review_note' : IDL.Opt(IDL.Text),
+  });
+  const Result_16 = IDL.Variant({ 'Ok' : PromptTemplate, 'Err' : IDL.Text });
+  const ContingencyRequest = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'row_variable' : IDL.Text,
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'epsilon' : IDL.Opt(IDL.Float64),
+    'column_variable' : IDL.Text,
+  });
+  const ChiSquareTest = IDL.Record({
+    'degrees_of_freedom' : IDL.Nat64,
+    'p_value' : IDL.Float64,
+    'statistic' : IDL.Float64,
+    'sparse' : IDL.Bool,
+    'cramers_v' : IDL.Float64,
+  });
+  const ContingencyTable = IDL.Record({
+    'records' : IDL.Nat64,
+    'row_variable' : IDL.Text,
+    'cells' : IDL.Vec(IDL.Vec(IDL.Opt(IDL.Nat64))),
+    'chi_square' : IDL.Opt(ChiSquareTest),
+    'row_labels' : IDL.Vec(IDL.Text),
+    'suppressed_cells' : IDL.Nat64,
+    'epsilon' : IDL.Float64,
+    'column_variable' : IDL.Text,
+    'column_labels' : IDL.Vec(IDL.Text),
+  });
+  const Result_17 = IDL.Variant({ 'Ok' : ContingencyTable, 'Err' : IDL.Text });
+  const Operator = IDL.Variant({
+    'LessOrEqual' : IDL.Null,
+    'Equal' : IDL.Null,
+    'GreaterOrEqual' : IDL.Null,
+    'Less' : IDL.Null,
+    'Greater' : IDL.Null,
+    'NotEqual' : IDL.Null,
+  });
+  const Condition = IDL.Record({
+    'value' : IDL.Text,
+    'operator' : Operator,
+    'column' : IDL.Text,
+  });
+  const Cohort = IDL.Record({
+    'id' : IDL.Text,
+    'owner' : IDL.Principal,
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'filter' : IDL.Text,
+    'conditions' : IDL.Vec(Condition),
+  });
+  const Result_18 = IDL.Variant({ 'Ok' : Cohort, 'Err' : IDL.Text });
+  const DeviceSession = IDL.Record({
+    'created_at' : IDL.Nat64,
+    'anchor_principal' : IDL.Principal,
+    'device' : IDL.Text,
+    'session_principal' : IDL.Principal,
+    'expires_at' : IDL.Nat64,
+  });
+  const Result_19 = IDL.Variant({ 'Ok' : DeviceSession, 'Err' : IDL.Text });
+  const Result_20 = IDL.Variant({ 'Ok' : IDL.Vec(IDL.Nat8), 'Err' : IDL.Text });
+  const WrappedKey = IDL.Record({
+    'encrypted_key' : IDL.Vec(IDL.Nat8),
+    'key_id' : IDL.Text,
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_21 = IDL.Variant({ 'Ok' : WrappedKey, 'Err' : IDL.Text });
+  const CostEstimateRequest = IDL.Record({
+    'expected_llm_calls' : IDL.Opt(IDL.Nat32),
+    'agent_id' : IDL.Opt(IDL.Text),
+    'dataset_ids' : IDL.Vec(IDL.Text),
+  });
+  const CostEstimate = IDL.Record({
+    'based_on_runs' : IDL.Nat32,
+    'instruction_cycles' : IDL.Nat,
+    'llm_calls' : IDL.Nat32,
+    'dataset_bytes' : IDL.Nat64,
+    'agent_fee' : IDL.Nat64,
+    'estimated_instructions' : IDL.Nat64,
+    'total_cycles' : IDL.Nat,
+    'llm_call_cycles' : IDL.Nat,
+  });
+  const Result_22 = IDL.Variant({ 'Ok' : CostEstimate, 'Err' : IDL.Text });
+  const EncryptedResult = IDL.Record({
+    'ciphertext' : IDL.Vec(IDL.Nat8),
+    'recipient' : IDL.Principal,
+    'derivation_path' : IDL.Vec(IDL.Nat8),
+    'created_at' : IDL.Nat64,
+    'result_digest' : IDL.Text,
+  });
+  const Result_23 = IDL.Variant({ 'Ok' : EncryptedResult, 'Err' : IDL.Text });
+  const AgentTiming = IDL.Record({
+    'slot' : IDL.Text,
+    'resumed' : IDL.Bool,
+    'agent_id' : IDL.Text,
+    'started_at' : IDL.Nat64,
+    'finished_at' : IDL.Nat64,
+  });
+  const AgentSubstitution = IDL.Record({
+    'substitute_agent_id' : IDL.Text,
+    'original_agent_id' : IDL.Text,
+    'reason' : IDL.Text,
+  });
   const ComputationResult = IDL.Record({
+    'agent_timings' : IDL.Vec(AgentTiming),
     'insights' : IDL.Text,
     'timestamp' : IDL.Nat64,
     'privacy_proof' : IDL.Text,
+    'substitutions' : IDL.Vec(AgentSubstitution),
   });
-  const Result_2 = IDL.Variant({ 'Ok' : ComputationResult, 'Err' : IDL.Text });
+  const Result_24 = IDL.Variant({ 'Ok' : ComputationResult, 'Err' : IDL.Text });
+  const AuditExportFormat = IDL.Variant({
+    'Csv' : IDL.Null,
+    'Json' : IDL.Null,
+  });
+  const AuditExport = IDL.Record({
+    'content' : IDL.Text,
+    'next_cursor' : IDL.Opt(IDL.Nat64),
+    'record_count' : IDL.Nat32,
+    'format' : AuditExportFormat,
+  });
+  const Result_25 = IDL.Variant({ 'Ok' : AuditExport, 'Err' : IDL.Text });
+  const ConsentVote = IDL.Record({
+    'decision' : IDL.Text,
+    'voter' : IDL.Principal,
+    'timestamp' : IDL.Nat64,
+  });
+  const ConsentSignature = IDL.Record({
+    'signature' : IDL.Text,
+    'signer' : IDL.Text,
+  });
+  const ConsentNode = IDL.Record({
+    'status' : IDL.Text,
+    'title' : IDL.Text,
+    'computation_id' : IDL.Text,
+    'requester' : IDL.Principal,
+    'votes' : IDL.Vec(ConsentVote),
+    'received_signatures' : IDL.Vec(IDL.Principal),
+    'required_signers' : IDL.Vec(IDL.Principal),
+    'created_at' : IDL.Nat64,
+    'signatures' : IDL.Vec(ConsentSignature),
+    'signature_data_hash' : IDL.Opt(IDL.Text),
+    'shared_with' : IDL.Vec(IDL.Principal),
+  });
+  const ConsentGraph = IDL.Record({
+    'computations' : IDL.Vec(ConsentNode),
+    'canister_id' : IDL.Principal,
+    'graph_digest' : IDL.Text,
+    'exported_at' : IDL.Nat64,
+    'workspace' : IDL.Text,
+  });
+  const SignedConsentGraph = IDL.Record({
+    'algorithm' : IDL.Text,
+    'signature' : IDL.Vec(IDL.Nat8),
+    'public_key' : IDL.Vec(IDL.Nat8),
+    'graph' : ConsentGraph,
+    'key_name' : IDL.Text,
+  });
+  const Result_26 = IDL.Variant({
+    'Ok' : SignedConsentGraph,
+    'Err' : IDL.Text,
+  });
+  const ProofExportFormat = IDL.Variant({
+    'Binary' : IDL.Null,
+    'Json' : IDL.Null,
+  });
+  const ProofExport = IDL.Record({
+    'content' : IDL.Vec(IDL.Nat8),
+    'format_version' : IDL.Nat16,
+    'scheme' : IDL.Text,
+    'format' : ProofExportFormat,
+  });
+  const Result_27 = IDL.Variant({ 'Ok' : ProofExport, 'Err' : IDL.Text });
+  const StateChunk = IDL.Record({
+    'cursor' : IDL.Nat64,
+    'data' : IDL.Vec(IDL.Nat8),
+    'total_size' : IDL.Nat64,
+    'next_cursor' : IDL.Opt(IDL.Nat64),
+    'taken_at' : IDL.Nat64,
+    'snapshot_hash' : IDL.Text,
+  });
+  const Result_28 = IDL.Variant({ 'Ok' : StateChunk, 'Err' : IDL.Text });
+  const RegressionKind = IDL.Variant({
+    'Linear' : IDL.Null,
+    'Logistic' : IDL.Null,
+  });
+  const RegressionRequest = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'predictors' : IDL.Vec(IDL.Text),
+    'kind' : RegressionKind,
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'epsilon' : IDL.Opt(IDL.Float64),
+    'outcome' : IDL.Text,
+  });
+  const Coefficient = IDL.Record({
+    'estimate' : IDL.Float64,
+    'predictor' : IDL.Text,
+  });
+  const RegressionModel = IDL.Record({
+    'records' : IDL.Nat64,
+    'intercept' : IDL.Float64,
+    'kind' : RegressionKind,
+    'coefficients' : IDL.Vec(Coefficient),
+    'r_squared' : IDL.Opt(IDL.Float64),
+    'epsilon' : IDL.Float64,
+    'outcome' : IDL.Text,
+  });
+  const Result_29 = IDL.Variant({ 'Ok' : RegressionModel, 'Err' : IDL.Text });
+  const LoadTestReport = IDL.Record({
+    'total_datasets' : IDL.Nat64,
+    'total_computations' : IDL.Nat64,
+    'computations_created' : IDL.Nat32,
+    'bytes_generated' : IDL.Nat64,
+    'datasets_created' : IDL.Nat32,
+    'instructions_used' : IDL.Nat64,
+  });
+  const Result_30 = IDL.Variant({ 'Ok' : LoadTestReport, 'Err' : IDL.Text });
+  const ActivityKind = IDL.Variant({
+    'PartyRegistered' : IDL.Null,
+    'RequestCancelled' : IDL.Null,
+    'ComputationFailed' : IDL.Null,
+    'ComputationExecuted' : IDL.Null,
+    'ComputationRequested' : IDL.Null,
+    'AdminAction' : IDL.Null,
+    'QueryCreated' : IDL.Null,
+    'KeyCeremonyStep' : IDL.Null,
+    'RetryRequested' : IDL.Null,
+    'DatasetUploaded' : IDL.Null,
+    'PolicyChanged' : IDL.Null,
+    'IdentityRecovery' : IDL.Null,
+    'LoadTestGenerated' : IDL.Null,
+    'QueryExecuted' : IDL.Null,
+    'QuerySigned' : IDL.Null,
+    'VoteCast' : IDL.Null,
+    'ResultsShared' : IDL.Null,
+  });
+  const ActivityEntry = IDL.Record({
+    'seq' : IDL.Nat64,
+    'actor' : IDL.Principal,
+    'kind' : ActivityKind,
+    'summary' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+    'workspace' : IDL.Text,
+    'resource_id' : IDL.Text,
+  });
+  const ActivityPage = IDL.Record({
+    'entries' : IDL.Vec(ActivityEntry),
+    'next_cursor' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_31 = IDL.Variant({ 'Ok' : ActivityPage, 'Err' : IDL.Text });
+  const Capability = IDL.Variant({
+    'CryptographicAnalysis' : IDL.Null,
+    'DataAnonymization' : IDL.Null,
+    'ClinicalTrialAnalysis' : IDL.Null,
+    'MachineLearning' : IDL.Null,
+    'ThreatDetection' : IDL.Null,
+    'MedicalPrivacyCompliance' : IDL.Null,
+    'PatternRecognition' : IDL.Null,
+    'MarketAnalysis' : IDL.Null,
+    'LegalRiskAssessment' : IDL.Null,
+    'StatisticalAnalysis' : IDL.Null,
+    'EpidemiologicalModeling' : IDL.Null,
+    'FraudDetection' : IDL.Null,
+    'RegulatoryCompliance' : IDL.Null,
+    'PolicyAnalysis' : IDL.Null,
+    'RiskAssessment' : IDL.Null,
+    'HipaaVerification' : IDL.Null,
+    'GdprCompliance' : IDL.Null,
+    'SecurityAudit' : IDL.Null,
+    'PortfolioOptimization' : IDL.Null,
+    'ContractAnalysis' : IDL.Null,
+    'RegulatoryInterpretation' : IDL.Null,
+    'DataVisualization' : IDL.Null,
+    'ComplianceMonitoring' : IDL.Null,
+    'PrivacyImpactAssessment' : IDL.Null,
+    'DrugDiscoveryInsights' : IDL.Null,
+    'PredictiveModeling' : IDL.Null,
+    'RegulatoryAudit' : IDL.Null,
+    'IncidentResponse' : IDL.Null,
+    'HealthcareDataAnalysis' : IDL.Null,
+    'VulnerabilityAssessment' : IDL.Null,
+  });
+  const AgentVersion = IDL.Record({
+    'changed_at' : IDL.Nat64,
+    'changed_by' : IDL.Opt(IDL.Principal),
+    'capabilities' : IDL.Vec(Capability),
+    'agent_id' : IDL.Text,
+    'version' : IDL.Nat32,
+    'change_note' : IDL.Text,
+  });
+  const AgentLiveness = IDL.Record({
+    'live' : IDL.Bool,
+    'agent_id' : IDL.Text,
+    'last_seen' : IDL.Opt(IDL.Nat64),
+  });
+  const AgentTeam = IDL.Record({
+    'id' : IDL.Text,
+    'agent_ids' : IDL.Vec(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'data_source_ids' : IDL.Vec(IDL.Text),
+  });
+  const Result_32 = IDL.Variant({ 'Ok' : AgentTeam, 'Err' : IDL.Text });
   const Vote = IDL.Record({
     'decision' : IDL.Text,
     'voter' : IDL.Principal,
     'timestamp' : IDL.Nat64,
   });
+  const TemplateBinding = IDL.Record({
+    'values' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+    'template_id' : IDL.Text,
+    'version' : IDL.Nat32,
+  });
+  const Priority = IDL.Variant({
+    'Low' : IDL.Null,
+    'High' : IDL.Null,
+    'Normal' : IDL.Null,
+  });
+  const RetryAttempt = IDL.Record({
+    'previous_error' : IDL.Opt(IDL.Text),
+    'attempt' : IDL.Nat32,
+    'requested_by' : IDL.Principal,
+    'signatures_preserved' : IDL.Bool,
+    'timestamp' : IDL.Nat64,
+  });
   const MPCComputation = IDL.Record({
     'id' : IDL.Text,
+    'last_error' : IDL.Opt(IDL.Text),
     'status' : IDL.Text,
     'required_parties' : IDL.Nat32,
     'title' : IDL.Text,
@@ -23,74 +631,1213 @@ export const idlFactory = ({ IDL }) => {
     'votes' : IDL.Vec(Vote),
     'received_signatures' : IDL.Vec(IDL.Principal),
     'description' : IDL.Text,
+    'llm_provider' : IDL.Opt(IDL.Text),
     'created_at' : IDL.Nat64,
     'results' : IDL.Opt(IDL.Text),
+    'agent_id' : IDL.Opt(IDL.Text),
+    'prompt_template' : IDL.Opt(TemplateBinding),
     'signature_id' : IDL.Opt(IDL.Text),
+    'encrypted_results' : IDL.Vec(EncryptedResult),
+    'structured_results' : IDL.Vec(EncryptedResult),
+    'agent_version' : IDL.Opt(IDL.Nat32),
+    'priority' : Priority,
+    'shared_with' : IDL.Vec(IDL.Principal),
+    'output_schema' : IDL.Opt(IDL.Text),
     'required_signatures' : IDL.Vec(IDL.Principal),
+    'retry_attempts' : IDL.Vec(RetryAttempt),
+    'purpose' : IDL.Text,
     'approvals' : IDL.Vec(IDL.Principal),
+    'columns' : IDL.Vec(IDL.Text),
+  });
+  const DatasetMetadata = IDL.Record({
+    'collection_end' : IDL.Opt(IDL.Text),
+    'steward_contact' : IDL.Opt(IDL.Text),
+    'geography' : IDL.Opt(IDL.Text),
+    'data_use_agreement' : IDL.Opt(IDL.Text),
+    'collection_start' : IDL.Opt(IDL.Text),
+    'license' : IDL.Opt(IDL.Text),
   });
   const PrivateDataSource = IDL.Record({
     'id' : IDL.Text,
     'encrypted_data' : IDL.Vec(IDL.Nat8),
     'owner' : IDL.Principal,
+    'metadata' : DatasetMetadata,
     'schema' : IDL.Text,
     'name' : IDL.Text,
+    'tags' : IDL.Vec(IDL.Text),
     'created_at' : IDL.Nat64,
     'access_permissions' : IDL.Vec(IDL.Principal),
     'party_name' : IDL.Text,
+    'allowed_purposes' : IDL.Opt(IDL.Vec(IDL.Text)),
     'vetkey_id' : IDL.Text,
     'record_count' : IDL.Nat32,
   });
-  const Result_3 = IDL.Variant({ 'Ok' : MPCComputation, 'Err' : IDL.Text });
+  const AlertStatus = IDL.Variant({
+    'Open' : IDL.Null,
+    'Confirmed' : IDL.Null,
+    'Dismissed' : IDL.Null,
+  });
+  const AnomalyKind = IDL.Variant({
+    'OutOfHours' : IDL.Null,
+    'BulkDecryption' : IDL.Null,
+  });
+  const AnomalyAlert = IDL.Record({
+    'id' : IDL.Nat64,
+    'status' : AlertStatus,
+    'principal' : IDL.Principal,
+    'dataset_id' : IDL.Text,
+    'kind' : AnomalyKind,
+    'timestamp' : IDL.Nat64,
+    'details' : IDL.Text,
+    'suspended' : IDL.Bool,
+  });
+  const Result_33 = IDL.Variant({
+    'Ok' : IDL.Vec(AnomalyAlert),
+    'Err' : IDL.Text,
+  });
+  const MerkleStep = IDL.Record({
+    'sibling' : IDL.Text,
+    'sibling_is_left' : IDL.Bool,
+  });
+  const AuditEvent = IDL.Variant({
+    'Decryption' : IDL.Null,
+    'Vote' : IDL.Null,
+    'Pause' : IDL.Null,
+    'Execution' : IDL.Null,
+    'KeyDerivation' : IDL.Null,
+    'Backup' : IDL.Null,
+    'RoleChange' : IDL.Null,
+    'Upload' : IDL.Null,
+  });
+  const AuditLogEntry = IDL.Record({
+    'seq' : IDL.Nat64,
+    'actor' : IDL.Principal,
+    'hash' : IDL.Text,
+    'prev_hash' : IDL.Text,
+    'event' : AuditEvent,
+    'timestamp' : IDL.Nat64,
+    'resource_id' : IDL.Text,
+    'details' : IDL.Text,
+  });
+  const AuditInclusionProof = IDL.Record({
+    'leaf_hash' : IDL.Text,
+    'path' : IDL.Vec(MerkleStep),
+    'root' : IDL.Text,
+    'entry' : AuditLogEntry,
+    'tree_size' : IDL.Nat64,
+  });
+  const CertifiedInclusionProof = IDL.Record({
+    'certificate' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'witness' : IDL.Vec(IDL.Nat8),
+    'proof' : AuditInclusionProof,
+  });
+  const Result_34 = IDL.Variant({
+    'Ok' : CertifiedInclusionProof,
+    'Err' : IDL.Text,
+  });
+  const AuditLogPage = IDL.Record({
+    'entries' : IDL.Vec(AuditLogEntry),
+    'next_cursor' : IDL.Opt(IDL.Nat64),
+    'head_hash' : IDL.Text,
+  });
+  const Result_35 = IDL.Variant({ 'Ok' : AuditLogPage, 'Err' : IDL.Text });
+  const BillableKind = IDL.Variant({
+    'LlmQuery' : IDL.Null,
+    'Computation' : IDL.Null,
+  });
+  const BillingEntry = IDL.Record({
+    'llm_calls' : IDL.Nat32,
+    'requester' : IDL.Principal,
+    'executed_by' : IDL.Principal,
+    'kind' : BillableKind,
+    'instructions' : IDL.Nat64,
+    'cycles' : IDL.Nat,
+    'timestamp' : IDL.Nat64,
+    'resource_id' : IDL.Text,
+    'succeeded' : IDL.Bool,
+  });
+  const BillingHistory = IDL.Record({
+    'requester' : IDL.Principal,
+    'total_instructions' : IDL.Nat64,
+    'entries' : IDL.Vec(BillingEntry),
+    'total_cycles' : IDL.Nat,
+  });
+  const JobStatus = IDL.Variant({
+    'Failed' : IDL.Null,
+    'Running' : IDL.Null,
+    'Completed' : IDL.Null,
+  });
+  const CheckpointJob = IDL.Record({
+    'status' : JobStatus,
+    'result' : IDL.Opt(ComputationResult),
+    'processed_bytes' : IDL.Nat64,
+    'updated_at' : IDL.Nat64,
+    'requester' : IDL.Principal,
+    'slices_processed' : IDL.Nat64,
+    'total_bytes' : IDL.Nat64,
+    'dataset_index' : IDL.Nat32,
+    'team_id' : IDL.Text,
+    'offset' : IDL.Nat64,
+    'error' : IDL.Opt(IDL.Text),
+    'steps' : IDL.Nat32,
+    'job_id' : IDL.Text,
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'rows_processed' : IDL.Nat64,
+    'computation_request' : IDL.Text,
+    'slice_bytes' : IDL.Nat64,
+    'percent_complete' : IDL.Nat8,
+    'running_digest' : IDL.Text,
+    'started_at' : IDL.Nat64,
+  });
+  const Result_36 = IDL.Variant({ 'Ok' : CheckpointJob, 'Err' : IDL.Text });
+  const AgentPayment = IDL.Record({
+    'computation_id' : IDL.Text,
+    'block_index' : IDL.Nat,
+    'agent_id' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+    'payer' : IDL.Principal,
+    'amount' : IDL.Nat64,
+  });
+  const AgentState = IDL.Variant({
+    'Substituted' : IDL.Null,
+    'Failed' : IDL.Null,
+    'Running' : IDL.Null,
+    'Completed' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const AgentProgress = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'agent_id' : IDL.Text,
+    'detail' : IDL.Opt(IDL.Text),
+    'state' : AgentState,
+  });
+  const ComputationPhase = IDL.Variant({
+    'Analyzing' : IDL.Null,
+    'Decrypting' : IDL.Null,
+    'Failed' : IDL.Null,
+    'Aggregating' : IDL.Null,
+    'Proving' : IDL.Null,
+    'Completed' : IDL.Null,
+  });
+  const ComputationProgress = IDL.Record({
+    'request_id' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'agents' : IDL.Vec(AgentProgress),
+    'message' : IDL.Opt(IDL.Text),
+    'phase' : ComputationPhase,
+    'percent_complete' : IDL.Nat8,
+    'started_at' : IDL.Nat64,
+  });
+  const Result_37 = IDL.Variant({
+    'Ok' : ComputationProgress,
+    'Err' : IDL.Text,
+  });
+  const QueuedComputation = IDL.Record({
+    'request_id' : IDL.Text,
+    'enqueued_at' : IDL.Nat64,
+    'priority' : Priority,
+  });
+  const CertifiedComputation = IDL.Record({
+    'certificate' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'witness' : IDL.Vec(IDL.Nat8),
+    'computation' : MPCComputation,
+  });
+  const Result_38 = IDL.Variant({
+    'Ok' : CertifiedComputation,
+    'Err' : IDL.Text,
+  });
+  const AsOf = IDL.Variant({ 'Timestamp' : IDL.Nat64, 'Sequence' : IDL.Nat64 });
+  const ComputationSnapshot = IDL.Record({
+    'seq' : IDL.Nat64,
+    'status' : IDL.Text,
+    'votes' : IDL.Vec(Vote),
+    'received_signatures' : IDL.Vec(IDL.Principal),
+    'timestamp' : IDL.Nat64,
+    'shared_with' : IDL.Vec(IDL.Principal),
+    'required_signatures' : IDL.Vec(IDL.Principal),
+    'approvals' : IDL.Vec(IDL.Principal),
+  });
+  const Result_39 = IDL.Variant({
+    'Ok' : ComputationSnapshot,
+    'Err' : IDL.Text,
+  });
+  const ComputationSubscription = IDL.Record({
+    'request_id' : IDL.Text,
+    'last_error' : IDL.Opt(IDL.Text),
+    'method' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'last_notified_at' : IDL.Opt(IDL.Nat64),
+    'callback_canister' : IDL.Principal,
+    'subscriber' : IDL.Principal,
+    'last_notified_status' : IDL.Opt(IDL.Text),
+  });
+  const LlmBackend = IDL.Variant({
+    'Mock' : IDL.Null,
+    'Https' : IDL.Text,
+    'IcLlmCanister' : IDL.Null,
+  });
+  const Settings = IDL.Record({
+    'vetkd_mode' : VetKdMode,
+    'llm_canister_id' : IDL.Opt(IDL.Principal),
+    'min_parties' : IDL.Nat32,
+    'query_expiry_seconds' : IDL.Nat64,
+    'invitation_expiry_seconds' : IDL.Nat64,
+    'threshold_key_name' : IDL.Text,
+    'vetkd_key_name' : IDL.Text,
+  });
+  const Config = IDL.Record({
+    'dp_defaults' : DpConfig,
+    'retention_days' : IDL.Nat64,
+    'llm_backend' : LlmBackend,
+    'settings' : Settings,
+    'agent_liveness_seconds' : IDL.Nat64,
+    'vetkd_key_ttl_seconds' : IDL.Opt(IDL.Nat64),
+    'unpause_approvals' : IDL.Nat32,
+    'max_concurrent_executions' : IDL.Nat32,
+  });
+  const AccessType = IDL.Variant({
+    'SetIntersection' : IDL.Null,
+    'Decryption' : IDL.Null,
+    'HomomorphicSum' : IDL.Null,
+    'ReEncryption' : IDL.Null,
+    'ColumnDecryption' : IDL.Null,
+    'RecordLinkage' : IDL.Null,
+    'SecureSum' : IDL.Null,
+  });
+  const DatasetAccess = IDL.Record({
+    'computation_id' : IDL.Opt(IDL.Text),
+    'dataset_id' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+    'accessor' : IDL.Principal,
+    'columns' : IDL.Vec(IDL.Text),
+    'access_type' : AccessType,
+  });
+  const Result_40 = IDL.Variant({
+    'Ok' : IDL.Vec(DatasetAccess),
+    'Err' : IDL.Text,
+  });
+  const KeyRotation = IDL.Record({
+    'dataset_id' : IDL.Text,
+    'epoch' : IDL.Nat32,
+    'revoked_keys' : IDL.Vec(IDL.Text),
+    'rotated_at' : IDL.Nat64,
+    'rotated_by' : IDL.Principal,
+    'vetkey_id' : IDL.Text,
+  });
+  const Result_41 = IDL.Variant({
+    'Ok' : IDL.Vec(KeyRotation),
+    'Err' : IDL.Text,
+  });
+  const PermissionSnapshot = IDL.Record({
+    'seq' : IDL.Nat64,
+    'owner' : IDL.Principal,
+    'timestamp' : IDL.Nat64,
+    'access_permissions' : IDL.Vec(IDL.Principal),
+  });
+  const Result_42 = IDL.Variant({
+    'Ok' : PermissionSnapshot,
+    'Err' : IDL.Text,
+  });
+  const ColumnType = IDL.Variant({
+    'Date' : IDL.Null,
+    'Text' : IDL.Null,
+    'Boolean' : IDL.Null,
+    'Category' : IDL.Null,
+    'Identifier' : IDL.Null,
+    'Number' : IDL.Record({
+      'integer' : IDL.Bool,
+      'bounds' : IDL.Opt(IDL.Tuple(IDL.Float64, IDL.Float64)),
+    }),
+  });
+  const ColumnSpec = IDL.Record({
+    'column_type' : ColumnType,
+    'nullable' : IDL.Bool,
+    'name' : IDL.Text,
+  });
+  const ColumnSummary = IDL.Record({
+    'spec' : ColumnSpec,
+    'values' : IDL.Nat64,
+    'top_values' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+    'range' : IDL.Opt(IDL.Tuple(IDL.Float64, IDL.Float64)),
+  });
+  const DatasetSummary = IDL.Record({
+    'dataset_id' : IDL.Text,
+    'generated_at' : IDL.Nat64,
+    'records' : IDL.Nat64,
+    'version' : IDL.Text,
+    'epsilon' : IDL.Float64,
+    'columns' : IDL.Vec(ColumnSummary),
+  });
+  const Result_43 = IDL.Variant({ 'Ok' : DatasetSummary, 'Err' : IDL.Text });
+  const WindowState = IDL.Variant({
+    'Open' : IDL.Null,
+    'Closed' : IDL.Null,
+    'Expired' : IDL.Null,
+    'Aborted' : IDL.Null,
+  });
+  const DecryptionWindow = IDL.Record({
+    'closed_at' : IDL.Opt(IDL.Nat64),
+    'run_id' : IDL.Text,
+    'opened_at' : IDL.Nat64,
+    'bytes_decrypted' : IDL.Nat64,
+    'state' : WindowState,
+    'expires_at' : IDL.Nat64,
+  });
+  const DisclosurePolicy = IDL.Record({
+    'small_cell_threshold' : IDL.Nat64,
+    'min_cohort_size' : IDL.Nat64,
+    'rounding_base' : IDL.Nat64,
+  });
+  const SuppressionKind = IDL.Variant({
+    'SmallCell' : IDL.Null,
+    'Rounded' : IDL.Null,
+    'MinCohort' : IDL.Null,
+  });
+  const Suppression = IDL.Record({
+    'kind' : SuppressionKind,
+    'location' : IDL.Text,
+    'record_count' : IDL.Nat64,
+  });
+  const DisclosureReport = IDL.Record({
+    'small_cell_threshold' : IDL.Nat64,
+    'suppressions' : IDL.Vec(Suppression),
+    'min_cohort_size' : IDL.Nat64,
+    'rounding_base' : IDL.Nat64,
+  });
+  const Result_44 = IDL.Variant({ 'Ok' : DisclosureReport, 'Err' : IDL.Text });
+  const AggregateStatus = IDL.Variant({
+    'AwaitingDecryption' : IDL.Null,
+    'Decrypted' : IDL.Null,
+  });
+  const EncryptedAggregate = IDL.Record({
+    'id' : IDL.Text,
+    'sum' : IDL.Opt(IDL.Float64),
+    'encrypted_sum' : IDL.Vec(IDL.Nat8),
+    'status' : AggregateStatus,
+    'requester' : IDL.Principal,
+    'key_id' : IDL.Text,
+    'mean' : IDL.Opt(IDL.Float64),
+    'count' : IDL.Nat64,
+    'created_at' : IDL.Nat64,
+    'dataset_ids' : IDL.Vec(IDL.Text),
+    'column' : IDL.Text,
+    'decrypted_by' : IDL.Vec(IDL.Principal),
+  });
+  const Result_45 = IDL.Variant({
+    'Ok' : EncryptedAggregate,
+    'Err' : IDL.Text,
+  });
+  const EventVerificationKey = IDL.Record({
+    'algorithm' : IDL.Text,
+    'public_key' : IDL.Vec(IDL.Nat8),
+    'key_name' : IDL.Text,
+  });
+  const Result_46 = IDL.Variant({
+    'Ok' : EventVerificationKey,
+    'Err' : IDL.Text,
+  });
+  const WorkflowEvent = IDL.Variant({
+    'ComputationFailed' : IDL.Record({
+      'request_id' : IDL.Text,
+      'error' : IDL.Text,
+    }),
+    'ProofVerified' : IDL.Record({
+      'computation_id' : IDL.Text,
+      'verified' : IDL.Bool,
+      'proof_id' : IDL.Text,
+    }),
+    'ComputationCompleted' : IDL.Record({ 'request_id' : IDL.Text }),
+    'DatasetUploaded' : IDL.Record({
+      'dataset_id' : IDL.Text,
+      'name' : IDL.Text,
+    }),
+    'VoteCast' : IDL.Record({
+      'request_id' : IDL.Text,
+      'status' : IDL.Text,
+      'decision' : IDL.Text,
+    }),
+  });
+  const EventRecord = IDL.Record({
+    'seq' : IDL.Nat64,
+    'actor' : IDL.Principal,
+    'event' : WorkflowEvent,
+    'timestamp' : IDL.Nat64,
+  });
+  const LineageEdge = IDL.Record({
+    'to' : IDL.Text,
+    'from' : IDL.Text,
+    'created_at' : IDL.Nat64,
+  });
+  const LineageKind = IDL.Variant({
+    'Result' : IDL.Null,
+    'Computation' : IDL.Null,
+    'DatasetVersion' : IDL.Null,
+  });
+  const LineageNode = IDL.Record({
+    'id' : IDL.Text,
+    'kind' : LineageKind,
+    'created_at' : IDL.Nat64,
+    'label' : IDL.Text,
+  });
+  const LineageGraph = IDL.Record({
+    'edges' : IDL.Vec(LineageEdge),
+    'nodes' : IDL.Vec(LineageNode),
+    'resource_id' : IDL.Text,
+  });
+  const Result_47 = IDL.Variant({ 'Ok' : LineageGraph, 'Err' : IDL.Text });
+  const LinkageQuality = IDL.Record({
+    'left_records' : IDL.Nat64,
+    'ambiguous_records' : IDL.Nat64,
+    'match_rate' : IDL.Float64,
+    'mean_similarity' : IDL.Float64,
+    'matches' : IDL.Nat64,
+    'uncertain_matches' : IDL.Nat64,
+    'similarity_histogram' : IDL.Vec(IDL.Nat64),
+    'candidate_pairs' : IDL.Nat64,
+    'right_records' : IDL.Nat64,
+  });
+  const Result_48 = IDL.Variant({ 'Ok' : LinkageQuality, 'Err' : IDL.Text });
+  const CircuitState = IDL.Variant({
+    'Open' : IDL.Null,
+    'Closed' : IDL.Null,
+    'HalfOpen' : IDL.Null,
+  });
+  const RetryPolicy = IDL.Record({
+    'max_delay_ms' : IDL.Nat64,
+    'failure_threshold' : IDL.Nat32,
+    'timeout_ms' : IDL.Nat64,
+    'base_delay_ms' : IDL.Nat64,
+    'max_retries' : IDL.Nat32,
+    'cooldown_secs' : IDL.Nat64,
+  });
+  const LlmHealth = IDL.Record({
+    'last_error' : IDL.Opt(IDL.Text),
+    'retry_after' : IDL.Opt(IDL.Nat64),
+    'opened_at' : IDL.Opt(IDL.Nat64),
+    'state' : CircuitState,
+    'consecutive_failures' : IDL.Nat32,
+    'policy' : RetryPolicy,
+  });
   const QueryStatus = IDL.Variant({
+    'Failed' : IDL.Null,
     'Executing' : IDL.Null,
     'Approved' : IDL.Null,
     'Rejected' : IDL.Null,
+    'Cancelled' : IDL.Null,
     'Completed' : IDL.Null,
     'Expired' : IDL.Null,
     'Pending' : IDL.Null,
   });
+  const PolicyRule = IDL.Variant({
+    'Aggregation' : IDL.Null,
+    'IdentifyingColumns' : IDL.Null,
+    'NarrowFilter' : IDL.Null,
+    'Purpose' : IDL.Null,
+    'GroupByIdentifier' : IDL.Null,
+  });
+  const PolicyFinding = IDL.Record({
+    'rule' : PolicyRule,
+    'detail' : IDL.Text,
+    'passed' : IDL.Bool,
+  });
+  const PolicyDecision = IDL.Record({
+    'approved' : IDL.Bool,
+    'rationale' : IDL.Text,
+    'findings' : IDL.Vec(PolicyFinding),
+  });
+  const SqlValue = IDL.Variant({
+    'Null' : IDL.Null,
+    'Text' : IDL.Text,
+    'Number' : IDL.Float64,
+  });
+  const SqlResult = IDL.Record({
+    'rows' : IDL.Vec(IDL.Vec(SqlValue)),
+    'suppressed_groups' : IDL.Nat64,
+    'epsilon' : IDL.Float64,
+    'columns' : IDL.Vec(IDL.Text),
+  });
+  const SelectItem = IDL.Variant({
+    'Sum' : IDL.Text,
+    'Group' : IDL.Text,
+    'Average' : IDL.Text,
+    'Count' : IDL.Null,
+  });
+  const SelectColumn = IDL.Record({ 'item' : SelectItem, 'name' : IDL.Text });
+  const SqlPlan = IDL.Record({
+    'select' : IDL.Vec(SelectColumn),
+    'group_by' : IDL.Vec(IDL.Text),
+    'conditions' : IDL.Vec(Condition),
+  });
+  const DecryptedColumns = IDL.Record({
+    'dataset_id' : IDL.Text,
+    'column_digests' : IDL.Vec(IDL.Text),
+    'decrypted_at' : IDL.Nat64,
+    'columns' : IDL.Vec(IDL.Text),
+  });
   const LLMQueryRequest = IDL.Record({
     'id' : IDL.Text,
+    'last_error' : IDL.Opt(IDL.Text),
     'status' : QueryStatus,
     'result' : IDL.Opt(IDL.Text),
+    'policy_decision' : IDL.Opt(PolicyDecision),
+    'sql_result' : IDL.Opt(SqlResult),
     'requester' : IDL.Principal,
+    'sql_plan' : IDL.Opt(SqlPlan),
     'received_signatures' : IDL.Vec(IDL.Principal),
     'query' : IDL.Text,
     'created_at' : IDL.Nat64,
+    'minimization_proof_id' : IDL.Opt(IDL.Text),
+    'decrypted_columns' : IDL.Vec(DecryptedColumns),
     'target_datasets' : IDL.Vec(IDL.Text),
+    'dp_params' : IDL.Opt(DpConfig),
     'required_signatures' : IDL.Vec(IDL.Principal),
     'expires_at' : IDL.Nat64,
+    'purpose' : IDL.Text,
+    'columns' : IDL.Vec(IDL.Text),
+  });
+  const LlmUsage = IDL.Record({
+    'calls' : IDL.Nat64,
+    'estimated_tokens' : IDL.Nat64,
+    'prompt_bytes' : IDL.Nat64,
+    'failed_calls' : IDL.Nat64,
+    'response_bytes' : IDL.Nat64,
+  });
+  const ComputationLlmUsage = IDL.Record({
+    'computation_id' : IDL.Text,
+    'usage' : LlmUsage,
+  });
+  const LlmBudget = IDL.Record({
+    'max_calls' : IDL.Opt(IDL.Nat64),
+    'max_tokens' : IDL.Opt(IDL.Nat64),
+  });
+  const PrincipalLlmUsage = IDL.Record({
+    'principal' : IDL.Principal,
+    'usage' : LlmUsage,
+    'budget' : IDL.Opt(LlmBudget),
+  });
+  const LlmUsageStats = IDL.Record({
+    'total' : LlmUsage,
+    'by_computation' : IDL.Vec(ComputationLlmUsage),
+    'by_principal' : IDL.Vec(PrincipalLlmUsage),
+  });
+  const Result_49 = IDL.Variant({ 'Ok' : LlmUsageStats, 'Err' : IDL.Text });
+  const MaintenanceReport = IDL.Record({
+    'computations_pruned' : IDL.Nat64,
+    'queries_pruned' : IDL.Nat64,
+    'derivations_pruned' : IDL.Nat64,
+    'ran_at' : IDL.Nat64,
+    'bytes_reclaimed' : IDL.Nat64,
+  });
+  const MaintenanceStats = IDL.Record({
+    'computations_pruned' : IDL.Nat64,
+    'queries_pruned' : IDL.Nat64,
+    'runs' : IDL.Nat64,
+    'derivations_pruned' : IDL.Nat64,
+    'bytes_reclaimed' : IDL.Nat64,
+    'last_run' : IDL.Opt(MaintenanceReport),
+    'retention_nanos' : IDL.Nat64,
+  });
+  const Result_50 = IDL.Variant({ 'Ok' : MaintenanceStats, 'Err' : IDL.Text });
+  const SecureStatistics = IDL.Record({
+    'run_id' : IDL.Text,
+    'mean' : IDL.Float64,
+    'count' : IDL.Nat64,
+    'record_count_proof_id' : IDL.Opt(IDL.Text),
+    'variance' : IDL.Float64,
+    'column' : IDL.Text,
+    'dataset_count' : IDL.Nat64,
+    'std_dev' : IDL.Float64,
+  });
+  const Result_51 = IDL.Variant({ 'Ok' : SecureStatistics, 'Err' : IDL.Text });
+  const ModelVersion = IDL.Record({
+    'created_at' : IDL.Nat64,
+    'weights' : IDL.Vec(IDL.Float64),
+    'version' : IDL.Nat64,
+    'model_id' : IDL.Text,
+    'contributors' : IDL.Nat64,
+  });
+  const Result_52 = IDL.Variant({ 'Ok' : ModelVersion, 'Err' : IDL.Text });
+  const RecoveryNotification = IDL.Record({
+    'request_id' : IDL.Text,
+    'recipient' : IDL.Principal,
+    'message' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+  });
+  const Role = IDL.Variant({
+    'Auditor' : IDL.Null,
+    'Approver' : IDL.Null,
+    'Analyst' : IDL.Null,
+    'Admin' : IDL.Null,
+  });
+  const Permission = IDL.Variant({
+    'Approve' : IDL.Null,
+    'Compute' : IDL.Null,
+    'ManageRoles' : IDL.Null,
+    'Audit' : IDL.Null,
+    'Upload' : IDL.Null,
+  });
+  const CertifiedAudit = IDL.Record({
+    'report' : IDL.Text,
+    'certificate' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'witness' : IDL.Vec(IDL.Nat8),
+  });
+  const Result_53 = IDL.Variant({ 'Ok' : CertifiedAudit, 'Err' : IDL.Text });
+  const DpBounds = IDL.Record({
+    'max_epsilon' : IDL.Float64,
+    'max_delta' : IDL.Float64,
+  });
+  const BudgetCharge = IDL.Record({
+    'query_id' : IDL.Text,
+    'epsilon' : IDL.Float64,
+    'charged_at' : IDL.Nat64,
+  });
+  const PrivacyBudget = IDL.Record({
+    'spent_epsilon' : IDL.Float64,
+    'dataset_id' : IDL.Text,
+    'remaining_epsilon' : IDL.Float64,
+    'total_epsilon' : IDL.Float64,
+    'bounds' : IDL.Opt(DpBounds),
+    'charges' : IDL.Vec(BudgetCharge),
+  });
+  const Result_54 = IDL.Variant({ 'Ok' : PrivacyBudget, 'Err' : IDL.Text });
+  const InjectionPolicy = IDL.Variant({
+    'Sanitize' : IDL.Null,
+    'Reject' : IDL.Null,
+  });
+  const AggregateKind = IDL.Variant({
+    'Mean' : IDL.Null,
+    'GroupBy' : IDL.Null,
+    'Count' : IDL.Null,
+  });
+  const AggregateAnalysis = IDL.Record({
+    'kind' : AggregateKind,
+    'sensitivity' : IDL.Float64,
+    'statistic' : IDL.Text,
+    'epsilon' : IDL.Float64,
+    'noise_scale' : IDL.Float64,
+    'expected_absolute_error' : IDL.Float64,
+  });
+  const SensitivityAnalysis = IDL.Record({
+    'requested' : IDL.Vec(AggregateKind),
+    'mechanism' : NoiseMechanism,
+    'aggregates' : IDL.Vec(AggregateAnalysis),
+    'total_epsilon' : IDL.Float64,
+    'declared_records' : IDL.Nat64,
+    'warnings' : IDL.Vec(IDL.Text),
+    'query_id' : IDL.Text,
+    'delta' : IDL.Float64,
+  });
+  const Result_55 = IDL.Variant({
+    'Ok' : SensitivityAnalysis,
+    'Err' : IDL.Text,
+  });
+  const QueuePosition = IDL.Record({
+    'request_id' : IDL.Text,
+    'active_executions' : IDL.Nat32,
+    'max_concurrent' : IDL.Nat32,
+    'priority' : Priority,
+    'position' : IDL.Nat32,
+    'running' : IDL.Bool,
+  });
+  const Result_56 = IDL.Variant({ 'Ok' : QueuePosition, 'Err' : IDL.Text });
+  const RateLimit = IDL.Record({
+    'refill_per_minute' : IDL.Nat32,
+    'capacity' : IDL.Nat32,
+  });
+  const RateLimits = IDL.Record({
+    'default' : RateLimit,
+    'overrides' : IDL.Vec(IDL.Tuple(IDL.Principal, RateLimit)),
+  });
+  const RecordMatch = IDL.Record({
+    'similarity' : IDL.Float64,
+    'left_index' : IDL.Nat64,
+    'right_index' : IDL.Nat64,
+  });
+  const LinkageRun = IDL.Record({
+    'id' : IDL.Text,
+    'computation_id' : IDL.Opt(IDL.Text),
+    'left_dataset' : IDL.Text,
+    'requester' : IDL.Principal,
+    'threshold' : IDL.Float64,
+    'quality' : LinkageQuality,
+    'created_at' : IDL.Nat64,
+    'matches' : IDL.Vec(RecordMatch),
+    'right_dataset' : IDL.Text,
+  });
+  const Result_57 = IDL.Variant({ 'Ok' : LinkageRun, 'Err' : IDL.Text });
+  const GuardianConfig = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'guardians' : IDL.Vec(IDL.Principal),
+    'threshold' : IDL.Nat32,
+    'owner' : IDL.Principal,
+  });
+  const RecoveryStatus = IDL.Variant({
+    'Executing' : IDL.Null,
+    'Executed' : IDL.Null,
+    'Cancelled' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const RecoveryRequest = IDL.Record({
+    'id' : IDL.Text,
+    'status' : RecoveryStatus,
+    'threshold' : IDL.Nat32,
+    'new_principal' : IDL.Principal,
+    'requested_at' : IDL.Nat64,
+    'lost_principal' : IDL.Principal,
+    'executable_after' : IDL.Nat64,
+    'approvals' : IDL.Vec(IDL.Principal),
+  });
+  const Result_58 = IDL.Variant({ 'Ok' : RecoveryRequest, 'Err' : IDL.Text });
+  const PartyInfo = IDL.Record({
+    'principal' : IDL.Principal,
+    'name' : IDL.Text,
+    'role' : IDL.Text,
+    'last_seen' : IDL.Nat64,
+    'is_active' : IDL.Bool,
+    'vetkey_id' : IDL.Text,
+  });
+  const PsiStatus = IDL.Variant({
+    'AwaitingCompletion' : IDL.Null,
+    'AwaitingResponse' : IDL.Null,
+    'Completed' : IDL.Null,
+  });
+  const PsiMode = IDL.Variant({
+    'Cardinality' : IDL.Record({ 'noise' : IDL.Opt(DpConfig) }),
+    'Members' : IDL.Null,
+  });
+  const PsiSession = IDL.Record({
+    'id' : IDL.Text,
+    'status' : PsiStatus,
+    'initiator_dataset' : IDL.Text,
+    'cardinality' : IDL.Opt(IDL.Nat64),
+    'responder' : IDL.Principal,
+    'initiator' : IDL.Principal,
+    'mode' : PsiMode,
+    'created_at' : IDL.Nat64,
+    'initiator_double' : IDL.Vec(IDL.Vec(IDL.Nat8)),
+    'responder_blinded' : IDL.Vec(IDL.Vec(IDL.Nat8)),
+    'responder_double' : IDL.Vec(IDL.Vec(IDL.Nat8)),
+    'initiator_blinded' : IDL.Vec(IDL.Vec(IDL.Nat8)),
+    'responder_dataset' : IDL.Text,
+  });
+  const Result_59 = IDL.Variant({ 'Ok' : PsiSession, 'Err' : IDL.Text });
+  const SignedEvent = IDL.Record({
+    'signature' : IDL.Vec(IDL.Nat8),
+    'issued_at' : IDL.Nat64,
+    'digest' : IDL.Text,
+    'event_id' : IDL.Nat64,
+    'event_type' : IDL.Text,
+    'payload' : IDL.Text,
+  });
+  const StorageUsage = IDL.Record({
+    'principal' : IDL.Principal,
+    'used_bytes' : IDL.Nat64,
+    'quota_bytes' : IDL.Nat64,
+    'remaining_bytes' : IDL.Nat64,
+    'dataset_count' : IDL.Nat32,
+  });
+  const Result_60 = IDL.Variant({
+    'Ok' : IDL.Vec(IDL.Vec(IDL.Float32)),
+    'Err' : IDL.Text,
+  });
+  const UploadReport = IDL.Record({
+    'dataset_id' : IDL.Text,
+    'validation' : UploadValidation,
+  });
+  const Result_61 = IDL.Variant({ 'Ok' : UploadReport, 'Err' : IDL.Text });
+  const Result_62 = IDL.Variant({ 'Ok' : IDL.Vec(Role), 'Err' : IDL.Text });
+  const ImportChunk = IDL.Record({
+    'data' : IDL.Vec(IDL.Nat8),
+    'offset' : IDL.Nat64,
+    'total_size' : IDL.Nat64,
+    'snapshot_hash' : IDL.Text,
+  });
+  const ImportProgress = IDL.Record({
+    'total_size' : IDL.Nat64,
+    'received' : IDL.Nat64,
+    'restored' : IDL.Bool,
+  });
+  const Result_63 = IDL.Variant({ 'Ok' : ImportProgress, 'Err' : IDL.Text });
+  const Invitee = IDL.Variant({
+    'EmailHash' : IDL.Text,
+    'Principal' : IDL.Principal,
+  });
+  const Invitation = IDL.Record({
+    'id' : IDL.Text,
+    'invitee' : Invitee,
+    'role' : Role,
+    'created_at' : IDL.Nat64,
+    'redeemed_at' : IDL.Opt(IDL.Nat64),
+    'redeemed_by' : IDL.Opt(IDL.Principal),
+    'workspace' : IDL.Text,
+    'invited_by' : IDL.Principal,
+    'expires_at' : IDL.Nat64,
+  });
+  const InvitationCode = IDL.Record({
+    'code' : IDL.Text,
+    'invitation' : Invitation,
+  });
+  const Result_64 = IDL.Variant({ 'Ok' : InvitationCode, 'Err' : IDL.Text });
+  const Result_65 = IDL.Variant({
+    'Ok' : IDL.Vec(Invitation),
+    'Err' : IDL.Text,
+  });
+  const ApiStyle = IDL.Variant({ 'OpenAi' : IDL.Null, 'Anthropic' : IDL.Null });
+  const HttpProviderInfo = IDL.Record({
+    'url' : IDL.Text,
+    'embeddings_url' : IDL.Opt(IDL.Text),
+    'model' : IDL.Text,
+    'max_response_bytes' : IDL.Nat64,
+    'name' : IDL.Text,
+    'embeddings_model' : IDL.Opt(IDL.Text),
+    'api_style' : ApiStyle,
+  });
+  const MPCAgent = IDL.Record({
+    'id' : IDL.Text,
+    'price_per_computation' : IDL.Nat64,
+    'capabilities' : IDL.Vec(Capability),
+    'owner' : IDL.Opt(IDL.Principal),
+    'version' : IDL.Nat32,
+    'identity' : IDL.Text,
+    'reputation_score' : IDL.Nat32,
+  });
+  const ServicePrincipal = IDL.Record({
+    'principal' : IDL.Principal,
+    'owner' : IDL.Principal,
+    'scopes' : IDL.Vec(Permission),
+    'created_at' : IDL.Nat64,
+    'label' : IDL.Text,
+    'last_used' : IDL.Opt(IDL.Nat64),
+    'expires_at' : IDL.Nat64,
+  });
+  const HashScheme = IDL.Variant({ 'Sha256' : IDL.Null, 'Legacy' : IDL.Null });
+  const PrivacyProof = IDL.Record({
+    'computation_id' : IDL.Text,
+    'verified' : IDL.Bool,
+    'proof_id' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'verification_hash' : IDL.Text,
+    'proof_data' : IDL.Vec(IDL.Nat8),
+    'public_parameters' : IDL.Vec(IDL.Nat8),
+    'proof_type' : IDL.Text,
+    'hash_scheme' : HashScheme,
+  });
+  const RevokedKey = IDL.Record({
+    'key_id' : IDL.Text,
+    'revoked_at' : IDL.Nat64,
+    'revoked_by' : IDL.Principal,
+    'invalidated' : IDL.Vec(IDL.Text),
+    'reason' : IDL.Text,
+  });
+  const Result_66 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : IDL.Text });
+  const AgentWithdrawal = IDL.Record({
+    'to' : IDL.Principal,
+    'block_index' : IDL.Nat,
+    'agent_id' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+    'amount' : IDL.Nat64,
+  });
+  const Result_67 = IDL.Variant({ 'Ok' : AgentWithdrawal, 'Err' : IDL.Text });
+  const Result_68 = IDL.Variant({ 'Ok' : MPCAgent, 'Err' : IDL.Text });
+  const PaillierKey = IDL.Record({
+    'id' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'holders' : IDL.Vec(IDL.Principal),
+    'registered_by' : IDL.Principal,
+    'modulus' : IDL.Vec(IDL.Nat8),
+  });
+  const Result_69 = IDL.Variant({ 'Ok' : PaillierKey, 'Err' : IDL.Text });
+  const Result_70 = IDL.Variant({ 'Ok' : ServicePrincipal, 'Err' : IDL.Text });
+  const Result_71 = IDL.Variant({ 'Ok' : AnomalyAlert, 'Err' : IDL.Text });
+  const Result_72 = IDL.Variant({ 'Ok' : RevokedKey, 'Err' : IDL.Text });
+  const Result_73 = IDL.Variant({ 'Ok' : MaintenanceReport, 'Err' : IDL.Text });
+  const CapabilityDomain = IDL.Variant({
+    'Legal' : IDL.Null,
+    'Healthcare' : IDL.Null,
+    'DataScience' : IDL.Null,
+    'Security' : IDL.Null,
+    'Privacy' : IDL.Null,
+    'Finance' : IDL.Null,
   });
-  const PartyInfo = IDL.Record({
-    'principal' : IDL.Principal,
-    'name' : IDL.Text,
-    'role' : IDL.Text,
-    'last_seen' : IDL.Nat64,
-    'is_active' : IDL.Bool,
-    'vetkey_id' : IDL.Text,
+  const AgentFilter = IDL.Record({
+    'domain' : IDL.Opt(CapabilityDomain),
+    'capabilities' : IDL.Vec(Capability),
+    'available_only' : IDL.Bool,
+    'max_price' : IDL.Opt(IDL.Nat64),
+    'min_reputation' : IDL.Opt(IDL.Nat32),
+  });
+  const Result_74 = IDL.Variant({
+    'Ok' : IDL.Vec(PrivateDataSource),
+    'Err' : IDL.Text,
   });
   const VetkdEncryptedKeyResponse = IDL.Variant({
     'Ok' : IDL.Vec(IDL.Nat8),
     'Err' : IDL.Text,
   });
+  const SecureJoinResult = IDL.Record({
+    'dataset_id' : IDL.Text,
+    'rows' : IDL.Nat64,
+    'psi_session_id' : IDL.Text,
+    'mismatched_keys' : IDL.Nat64,
+  });
+  const Result_75 = IDL.Variant({ 'Ok' : SecureJoinResult, 'Err' : IDL.Text });
+  const SecureSumResult = IDL.Record({
+    'sum' : IDL.Float64,
+    'mean' : IDL.Float64,
+    'count' : IDL.Nat64,
+    'column' : IDL.Text,
+    'dataset_count' : IDL.Nat64,
+  });
+  const Result_76 = IDL.Variant({ 'Ok' : SecureSumResult, 'Err' : IDL.Text });
+  const Result_77 = IDL.Variant({
+    'Ok' : IDL.Opt(IDL.Vec(IDL.Text)),
+    'Err' : IDL.Text,
+  });
+  const Result_78 = IDL.Variant({ 'Ok' : RateLimits, 'Err' : IDL.Text });
+  const HttpProvider = IDL.Record({
+    'url' : IDL.Text,
+    'embeddings_url' : IDL.Opt(IDL.Text),
+    'model' : IDL.Text,
+    'max_response_bytes' : IDL.Nat64,
+    'api_key' : IDL.Text,
+    'name' : IDL.Text,
+    'embeddings_model' : IDL.Opt(IDL.Text),
+    'api_style' : ApiStyle,
+  });
+  const Result_79 = IDL.Variant({ 'Ok' : GuardianConfig, 'Err' : IDL.Text });
+  const Result_80 = IDL.Variant({ 'Ok' : StorageUsage, 'Err' : IDL.Text });
+  const Result_81 = IDL.Variant({
+    'Ok' : IDL.Opt(SecureStatistics),
+    'Err' : IDL.Text,
+  });
+  const Result_82 = IDL.Variant({
+    'Ok' : IDL.Opt(ModelVersion),
+    'Err' : IDL.Text,
+  });
+  const Result_83 = IDL.Variant({
+    'Ok' : ComputationSubscription,
+    'Err' : IDL.Text,
+  });
+  const TeamSuggestion = IDL.Record({
+    'total_price' : IDL.Nat64,
+    'agents' : IDL.Vec(MPCAgent),
+    'agent_ids' : IDL.Vec(IDL.Text),
+    'computation_type' : CapabilityDomain,
+    'required_capabilities' : IDL.Vec(Capability),
+  });
+  const Result_84 = IDL.Variant({ 'Ok' : TeamSuggestion, 'Err' : IDL.Text });
+  const HttpHeader = IDL.Record({ 'value' : IDL.Text, 'name' : IDL.Text });
+  const HttpResponse = IDL.Record({
+    'status' : IDL.Nat,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HttpHeader),
+  });
+  const TransformArgs = IDL.Record({
+    'context' : IDL.Vec(IDL.Nat8),
+    'response' : HttpResponse,
+  });
+  const ConfigUpdate = IDL.Record({
+    'vetkd_mode' : IDL.Opt(VetKdMode),
+    'llm_canister_id' : IDL.Opt(IDL.Opt(IDL.Principal)),
+    'dp_defaults' : IDL.Opt(DpConfig),
+    'retention_days' : IDL.Opt(IDL.Nat64),
+    'llm_backend' : IDL.Opt(LlmBackend),
+    'min_parties' : IDL.Opt(IDL.Nat32),
+    'query_expiry_seconds' : IDL.Opt(IDL.Nat64),
+    'agent_liveness_seconds' : IDL.Opt(IDL.Nat64),
+    'invitation_expiry_seconds' : IDL.Opt(IDL.Nat64),
+    'threshold_key_name' : IDL.Opt(IDL.Text),
+    'vetkd_key_ttl_seconds' : IDL.Opt(IDL.Opt(IDL.Nat64)),
+    'unpause_approvals' : IDL.Opt(IDL.Nat32),
+    'max_concurrent_executions' : IDL.Opt(IDL.Nat32),
+    'vetkd_key_name' : IDL.Opt(IDL.Text),
+  });
+  const Result_85 = IDL.Variant({ 'Ok' : Config, 'Err' : IDL.Text });
+  const ChainVerification = IDL.Record({
+    'entries_checked' : IDL.Nat64,
+    'valid' : IDL.Bool,
+    'first_invalid_seq' : IDL.Opt(IDL.Nat64),
+    'head_hash' : IDL.Text,
+  });
+  const Result_86 = IDL.Variant({ 'Ok' : ChainVerification, 'Err' : IDL.Text });
+  const Result_87 = IDL.Variant({ 'Ok' : IDL.Bool, 'Err' : IDL.Text });
+  const RangeCheck = IDL.Record({
+    'verified' : IDL.Bool,
+    'lower' : IDL.Float64,
+    'statistic' : IDL.Text,
+    'upper' : IDL.Float64,
+  });
+  const Result_88 = IDL.Variant({
+    'Ok' : IDL.Vec(RangeCheck),
+    'Err' : IDL.Text,
+  });
   const VetkdPublicKeyResponse = IDL.Variant({
     'Ok' : IDL.Vec(IDL.Nat8),
     'Err' : IDL.Text,
   });
+  const Result_89 = IDL.Variant({ 'Ok' : Invitation, 'Err' : IDL.Text });
   return IDL.Service({
+    'abort_key_ceremony' : IDL.Func([IDL.Text], [Result], []),
+    'add_organization_member' : IDL.Func(
+        [IDL.Principal, IDL.Bool],
+        [Result_1],
+        [],
+      ),
+    'agent_heartbeat' : IDL.Func([], [Result_2], []),
+    'analyze_time_series' : IDL.Func([TimeSeriesRequest], [Result_3], []),
+    'append_to_dataset' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_4],
+        [],
+      ),
+    'approve_identity_recovery' : IDL.Func([IDL.Text], [Result_5], []),
+    'approve_unpause' : IDL.Func([], [Result_6], []),
+    'assign_computation_agent' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_7],
+        [],
+      ),
+    'batch_verify_privacy_proofs' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result_8],
+        [],
+      ),
+    'cancel_computation_request' : IDL.Func([IDL.Text], [Result_5], []),
+    'cancel_identity_recovery' : IDL.Func([IDL.Text], [Result_5], []),
+    'cancel_llm_query' : IDL.Func([IDL.Text], [Result_5], []),
     'chat' : IDL.Func([IDL.Vec(ChatMessage)], [IDL.Text], []),
-    'create_computation_request' : IDL.Func([IDL.Text, IDL.Text], [Result], []),
-    'create_llm_query' : IDL.Func([IDL.Text, IDL.Vec(IDL.Text)], [Result], []),
-    'derive_agent_encryption_key' : IDL.Func([IDL.Text], [Result_1], []),
-    'execute_computation_request' : IDL.Func([IDL.Text], [Result], []),
-    'execute_llm_query' : IDL.Func([IDL.Text], [Result], []),
+    'clear_load_test_data' : IDL.Func([], [Result_5], []),
+    'close_bidding_round' : IDL.Func([IDL.Text], [Result_9], []),
+    'close_secure_session' : IDL.Func([IDL.Text], [Result_10], []),
+    'commit_ceremony_entropy' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_11],
+        [],
+      ),
+    'compare_cohorts' : IDL.Func([CohortComparisonRequest], [Result_12], []),
+    'complete_set_intersection' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Vec(IDL.Nat8))],
+        [Result_13],
+        [],
+      ),
+    'correlate_columns' : IDL.Func([CorrelationRequest], [Result_14], []),
+    'create_computation_request' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Text,
+          IDL.Opt(IDL.Text),
+          IDL.Text,
+          IDL.Opt(IDL.Vec(IDL.Text)),
+        ],
+        [Result_5],
+        [],
+      ),
+    'create_federated_model' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Principal), IDL.Vec(IDL.Float64)],
+        [Result_15],
+        [],
+      ),
+    'create_llm_query' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Vec(IDL.Text),
+          IDL.Opt(IDL.Vec(IDL.Text)),
+          IDL.Opt(DpConfig),
+          IDL.Text,
+        ],
+        [Result_5],
+        [],
+      ),
+    'create_organization' : IDL.Func([IDL.Text], [Result_1], []),
+    'create_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Text, IDL.Vec(TemplateVariable)],
+        [Result_16],
+        [],
+      ),
+    'create_sql_query' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Text), IDL.Opt(DpConfig), IDL.Text],
+        [Result_5],
+        [],
+      ),
+    'cross_tabulate' : IDL.Func([ContingencyRequest], [Result_17], []),
+    'define_cohort' : IDL.Func([IDL.Text, IDL.Text], [Result_18], []),
+    'delegate_session' : IDL.Func(
+        [IDL.Principal, IDL.Text, IDL.Nat64],
+        [Result_19],
+        [],
+      ),
+    'delete_prompt_template' : IDL.Func([IDL.Text], [Result_10], []),
+    'deploy_mpc_agents' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Vec(IDL.Text)],
+        [Result_5],
+        [],
+      ),
+    'derive_agent_encryption_key' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_20],
+        [],
+      ),
+    'derive_user_vetkd_key' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8), IDL.Opt(IDL.Nat64), IDL.Vec(IDL.Nat8)],
+        [Result_21],
+        [],
+      ),
+    'estimate_computation_cost' : IDL.Func(
+        [CostEstimateRequest],
+        [Result_22],
+        ['query'],
+      ),
+    'execute_computation_request' : IDL.Func([IDL.Text], [Result_23], []),
+    'execute_identity_recovery' : IDL.Func([IDL.Text], [Result_5], []),
+    'execute_llm_query' : IDL.Func([IDL.Text], [Result_5], []),
     'execute_secure_mpc_computation' : IDL.Func(
         [IDL.Text, IDL.Text, IDL.Vec(IDL.Text)],
-        [Result_2],
+        [Result_24],
+        [],
+      ),
+    'export_audit_log' : IDL.Func(
+        [
+          IDL.Nat64,
+          IDL.Nat64,
+          AuditExportFormat,
+          IDL.Opt(IDL.Nat64),
+          IDL.Opt(IDL.Nat32),
+        ],
+        [Result_25],
+        ['query'],
+      ),
+    'export_consent_graph' : IDL.Func([IDL.Text], [Result_26], []),
+    'export_proof' : IDL.Func(
+        [IDL.Text, ProofExportFormat],
+        [Result_27],
+        ['query'],
+      ),
+    'export_state_chunk' : IDL.Func([IDL.Nat64], [Result_28], []),
+    'fit_regression' : IDL.Func([RegressionRequest], [Result_29], []),
+    'generate_load_test_data' : IDL.Func(
+        [IDL.Nat32, IDL.Nat32, IDL.Nat64],
+        [Result_30],
         [],
       ),
-    'generate_privacy_proof' : IDL.Func([IDL.Text], [Result], []),
+    'generate_privacy_proof' : IDL.Func([IDL.Text], [Result_5], []),
+    'get_active_prompt_template' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(PromptTemplate)],
+        ['query'],
+      ),
+    'get_activity_timeline' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat32)],
+        [Result_31],
+        ['query'],
+      ),
+    'get_agent_balance' : IDL.Func([IDL.Text], [IDL.Nat64], ['query']),
+    'get_agent_changelog' : IDL.Func(
+        [IDL.Text],
+        [IDL.Vec(AgentVersion)],
+        ['query'],
+      ),
+    'get_agent_liveness' : IDL.Func([IDL.Text], [AgentLiveness], ['query']),
+    'get_agent_team' : IDL.Func([IDL.Text], [Result_32], ['query']),
     'get_all_computation_requests' : IDL.Func(
         [],
         [IDL.Vec(MPCComputation)],
@@ -102,45 +1849,515 @@ export const idlFactory = ({ IDL }) => {
         ['query'],
       ),
     'get_all_datasets' : IDL.Func([], [IDL.Vec(PrivateDataSource)], ['query']),
-    'get_computation_request' : IDL.Func([IDL.Text], [Result_3], ['query']),
+    'get_anomaly_alerts' : IDL.Func(
+        [IDL.Opt(AlertStatus)],
+        [Result_33],
+        ['query'],
+      ),
+    'get_audit_inclusion_proof' : IDL.Func([IDL.Nat64], [Result_34], ['query']),
+    'get_audit_log' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat32)],
+        [Result_35],
+        ['query'],
+      ),
+    'get_bidding_round' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(BiddingRound)],
+        ['query'],
+      ),
+    'get_billing_history' : IDL.Func([], [BillingHistory], ['query']),
+    'get_checkpointed_computation' : IDL.Func(
+        [IDL.Text],
+        [Result_36],
+        ['query'],
+      ),
+    'get_computation_escrow' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(Escrow)],
+        ['query'],
+      ),
+    'get_computation_payments' : IDL.Func(
+        [IDL.Text],
+        [IDL.Vec(AgentPayment)],
+        ['query'],
+      ),
+    'get_computation_progress' : IDL.Func([IDL.Text], [Result_37], ['query']),
+    'get_computation_queue' : IDL.Func(
+        [],
+        [IDL.Vec(QueuedComputation)],
+        ['query'],
+      ),
+    'get_computation_request' : IDL.Func([IDL.Text], [Result_38], ['query']),
+    'get_computation_state_at' : IDL.Func(
+        [IDL.Text, AsOf],
+        [Result_39],
+        ['query'],
+      ),
+    'get_computation_subscriptions' : IDL.Func(
+        [IDL.Text],
+        [IDL.Vec(ComputationSubscription)],
+        ['query'],
+      ),
+    'get_config' : IDL.Func([], [Config], ['query']),
     'get_data_sources_for_user' : IDL.Func(
         [],
         [IDL.Vec(PrivateDataSource)],
         ['query'],
       ),
+    'get_dataset_access_history' : IDL.Func([IDL.Text], [Result_40], ['query']),
+    'get_dataset_key_rotations' : IDL.Func([IDL.Text], [Result_41], ['query']),
+    'get_dataset_permissions_at' : IDL.Func(
+        [IDL.Text, AsOf],
+        [Result_42],
+        ['query'],
+      ),
+    'get_dataset_summary' : IDL.Func([IDL.Text], [Result_43], []),
+    'get_decryption_window' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(DecryptionWindow)],
+        ['query'],
+      ),
+    'get_differential_privacy_config' : IDL.Func([], [DpConfig], ['query']),
+    'get_disclosure_policy' : IDL.Func([], [DisclosurePolicy], ['query']),
+    'get_disclosure_report' : IDL.Func([IDL.Text], [Result_44], ['query']),
+    'get_encrypted_aggregate' : IDL.Func([IDL.Text], [Result_45], ['query']),
+    'get_event_verification_key' : IDL.Func([], [Result_46], []),
+    'get_events_since' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(IDL.Nat32)],
+        [IDL.Vec(EventRecord)],
+        ['query'],
+      ),
+    'get_federated_model' : IDL.Func([IDL.Text], [Result_15], ['query']),
+    'get_history_sequence' : IDL.Func([], [IDL.Nat64], ['query']),
+    'get_key_ceremony' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(KeyCeremony)],
+        ['query'],
+      ),
+    'get_latest_event_seq' : IDL.Func([], [IDL.Nat64], ['query']),
+    'get_lineage' : IDL.Func([IDL.Text], [Result_47], ['query']),
+    'get_linkage_quality' : IDL.Func([IDL.Text], [Result_48], ['query']),
+    'get_llm_backend' : IDL.Func([], [LlmBackend], ['query']),
+    'get_llm_health' : IDL.Func([], [LlmHealth], ['query']),
     'get_llm_queries' : IDL.Func([], [IDL.Vec(LLMQueryRequest)], ['query']),
+    'get_llm_usage_stats' : IDL.Func([], [Result_49], ['query']),
+    'get_maintenance_stats' : IDL.Func([], [Result_50], ['query']),
+    'get_masked_statistics' : IDL.Func([IDL.Text], [Result_51], ['query']),
+    'get_model_version' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat64)],
+        [Result_52],
+        ['query'],
+      ),
+    'get_my_encrypted_result' : IDL.Func([IDL.Text], [Result_23], ['query']),
+    'get_my_llm_usage' : IDL.Func([], [PrincipalLlmUsage], ['query']),
+    'get_my_organization' : IDL.Func([], [IDL.Opt(Organization)], ['query']),
+    'get_my_recovery_notifications' : IDL.Func(
+        [],
+        [IDL.Vec(RecoveryNotification)],
+        ['query'],
+      ),
+    'get_my_result_key' : IDL.Func(
+        [IDL.Text, IDL.Bool, IDL.Vec(IDL.Nat8)],
+        [Result_21],
+        [],
+      ),
+    'get_my_roles' : IDL.Func([], [IDL.Vec(Role)], ['query']),
+    'get_my_structured_result' : IDL.Func([IDL.Text], [Result_23], ['query']),
+    'get_open_bidding_rounds' : IDL.Func(
+        [],
+        [IDL.Vec(BiddingRound)],
+        ['query'],
+      ),
+    'get_pause_state' : IDL.Func([], [PauseState], ['query']),
     'get_pending_queries_for_user' : IDL.Func(
         [],
         [IDL.Vec(LLMQueryRequest)],
         ['query'],
       ),
+    'get_permission_matrix' : IDL.Func(
+        [],
+        [IDL.Vec(IDL.Tuple(Role, IDL.Vec(Permission)))],
+        ['query'],
+      ),
+    'get_privacy_audit' : IDL.Func([IDL.Text], [Result_53], ['query']),
+    'get_privacy_budget' : IDL.Func([IDL.Text], [Result_54], ['query']),
+    'get_prompt_injection_policy' : IDL.Func([], [InjectionPolicy], ['query']),
+    'get_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat32)],
+        [IDL.Opt(PromptTemplate)],
+        ['query'],
+      ),
     'get_query_by_id' : IDL.Func(
         [IDL.Text],
         [IDL.Opt(LLMQueryRequest)],
         ['query'],
       ),
+    'get_query_sensitivity' : IDL.Func([IDL.Text], [Result_55], ['query']),
+    'get_queue_position' : IDL.Func([IDL.Text], [Result_56], ['query']),
+    'get_rate_limits' : IDL.Func([], [RateLimits], ['query']),
+    'get_record_linkage' : IDL.Func([IDL.Text], [Result_57], ['query']),
+    'get_recovery_guardians' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Opt(GuardianConfig)],
+        ['query'],
+      ),
+    'get_recovery_request' : IDL.Func([IDL.Text], [Result_58], ['query']),
     'get_registered_parties' : IDL.Func([], [IDL.Vec(PartyInfo)], ['query']),
-    'get_user_identity' : IDL.Func([], [Result], ['query']),
+    'get_set_intersection' : IDL.Func([IDL.Text], [Result_59], ['query']),
+    'get_set_intersection_result' : IDL.Func(
+        [IDL.Text],
+        [Result_13],
+        ['query'],
+      ),
+    'get_signed_events' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat32)],
+        [IDL.Vec(SignedEvent)],
+        ['query'],
+      ),
+    'get_storage_usage' : IDL.Func([], [StorageUsage], ['query']),
+    'get_suspended_principals' : IDL.Func(
+        [],
+        [IDL.Vec(IDL.Principal)],
+        ['query'],
+      ),
+    'get_tag_vocabulary' : IDL.Func([], [IDL.Vec(IDL.Text)], ['query']),
+    'get_text_embeddings' : IDL.Func([IDL.Vec(IDL.Text)], [Result_60], []),
+    'get_unsettled_withdrawal' : IDL.Func(
+        [IDL.Text],
+        [IDL.Opt(PendingTransfer)],
+        ['query'],
+      ),
+    'get_upload_report' : IDL.Func([IDL.Text], [Result_61], ['query']),
+    'get_user_identity' : IDL.Func([], [Result_5], ['query']),
+    'get_vetkd_key_ttl' : IDL.Func([], [IDL.Opt(IDL.Nat64)], ['query']),
+    'grant_role' : IDL.Func([IDL.Principal, Role], [Result_62], []),
+    'import_state_chunk' : IDL.Func([ImportChunk], [Result_63], []),
+    'invite_party' : IDL.Func([Invitee, Role], [Result_64], []),
+    'is_agent_available' : IDL.Func([IDL.Text], [IDL.Bool], ['query']),
+    'link_records' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Float64, IDL.Opt(IDL.Text)],
+        [Result_57],
+        [],
+      ),
+    'list_cohorts' : IDL.Func([], [IDL.Vec(Cohort)], ['query']),
+    'list_invitations' : IDL.Func([], [Result_65], ['query']),
+    'list_llm_http_providers' : IDL.Func(
+        [],
+        [IDL.Vec(HttpProviderInfo)],
+        ['query'],
+      ),
+    'list_mpc_agents' : IDL.Func([], [IDL.Vec(MPCAgent)], ['query']),
+    'list_my_service_principals' : IDL.Func(
+        [],
+        [IDL.Vec(ServicePrincipal)],
+        ['query'],
+      ),
+    'list_my_sessions' : IDL.Func([], [IDL.Vec(DeviceSession)], ['query']),
+    'list_prompt_templates' : IDL.Func(
+        [],
+        [IDL.Vec(PromptTemplate)],
+        ['query'],
+      ),
+    'list_proofs_for_computation' : IDL.Func(
+        [IDL.Text],
+        [IDL.Vec(PrivacyProof)],
+        ['query'],
+      ),
+    'list_revoked_keys' : IDL.Func([], [IDL.Vec(RevokedKey)], ['query']),
+    'migrate_proof_hashes' : IDL.Func([], [Result_66], []),
+    'open_bidding_round' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Opt(IDL.Nat32)],
+        [Result_9],
+        [],
+      ),
+    'open_masked_statistics' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Text],
+        [Result_5],
+        [],
+      ),
+    'paillier_sum' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Text), IDL.Text],
+        [Result_45],
+        [],
+      ),
+    'pause_canister' : IDL.Func([IDL.Text], [Result_6], []),
+    'preview_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat32), IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text))],
+        [Result_5],
+        ['query'],
+      ),
     'prompt' : IDL.Func([IDL.Text], [IDL.Text], []),
-    'register_party' : IDL.Func([IDL.Text, IDL.Text], [Result], []),
-    'register_user_identity' : IDL.Func([IDL.Text, IDL.Text], [Result], []),
-    'save_computation_results' : IDL.Func([IDL.Text, IDL.Text], [Result], []),
+    'reconcile_agent_withdrawal' : IDL.Func([IDL.Text], [Result_67], []),
+    'refund_computation_escrow' : IDL.Func([IDL.Text], [Result_7], []),
+    'register_mpc_agent' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Vec(Capability), IDL.Nat64],
+        [Result_68],
+        [],
+      ),
+    'register_paillier_key' : IDL.Func(
+        [IDL.Vec(IDL.Nat8), IDL.Vec(IDL.Principal)],
+        [Result_69],
+        [],
+      ),
+    'register_party' : IDL.Func([IDL.Text, IDL.Text], [Result_5], []),
+    'register_service_principal' : IDL.Func(
+        [IDL.Principal, IDL.Text, IDL.Vec(Permission), IDL.Nat64],
+        [Result_70],
+        [],
+      ),
+    'register_user_identity' : IDL.Func([IDL.Text, IDL.Text], [Result_5], []),
+    'remove_llm_http_provider' : IDL.Func([IDL.Text], [Result_10], []),
+    'remove_organization_member' : IDL.Func([IDL.Principal], [Result_1], []),
+    'renew_secure_session' : IDL.Func([IDL.Text], [Result_66], []),
+    'request_identity_recovery' : IDL.Func([IDL.Principal], [Result_58], []),
+    'reset_llm_circuit' : IDL.Func([], [Result_10], []),
+    'respond_set_intersection' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Vec(IDL.Nat8)), IDL.Vec(IDL.Vec(IDL.Nat8))],
+        [Result_59],
+        [],
+      ),
+    'retry_computation' : IDL.Func([IDL.Text], [Result_5], []),
+    'reveal_ceremony_entropy' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_11],
+        [],
+      ),
+    'review_anomaly_alert' : IDL.Func([IDL.Nat64, IDL.Bool], [Result_71], []),
+    'review_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Nat32, IDL.Bool, IDL.Opt(IDL.Text)],
+        [Result_16],
+        [],
+      ),
+    'revise_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Vec(TemplateVariable)],
+        [Result_16],
+        [],
+      ),
+    'revoke_key' : IDL.Func([IDL.Text, IDL.Text], [Result_72], []),
+    'revoke_role' : IDL.Func([IDL.Principal, Role], [Result_62], []),
+    'revoke_service_principal' : IDL.Func([IDL.Principal], [Result_70], []),
+    'revoke_session' : IDL.Func([IDL.Principal], [Result_19], []),
+    'rotate_dataset_key' : IDL.Func([IDL.Text], [Result_41], []),
+    'run_maintenance_now' : IDL.Func([], [Result_73], []),
+    'search_agents' : IDL.Func([AgentFilter], [IDL.Vec(MPCAgent)], ['query']),
+    'search_datasets' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Text],
+        [Result_74],
+        ['query'],
+      ),
     'secure_agent_communication' : IDL.Func(
         [IDL.Text, IDL.Text, IDL.Vec(IDL.Nat8)],
-        [Result_1],
+        [Result_20],
+        [],
+      ),
+    'secure_aggregation_key' : IDL.Func(
+        [IDL.Text, IDL.Principal, IDL.Vec(IDL.Nat8)],
+        [VetkdEncryptedKeyResponse],
+        [],
+      ),
+    'secure_join' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Text, IDL.Text],
+        [Result_75],
+        [],
+      ),
+    'secure_statistics' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_51],
+        [],
+      ),
+    'secure_sum' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_76],
+        [],
+      ),
+    'set_agent_liveness_window' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'set_agent_owner' : IDL.Func([IDL.Text, IDL.Principal], [Result_10], []),
+    'set_anomaly_auto_suspend' : IDL.Func([IDL.Bool], [Result_10], []),
+    'set_computation_llm_provider' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_10],
+        [],
+      ),
+    'set_computation_priority' : IDL.Func(
+        [IDL.Text, Priority],
+        [Result_10],
+        [],
+      ),
+    'set_computation_prompt_template' : IDL.Func(
+        [IDL.Text, IDL.Opt(TemplateBinding)],
+        [Result_10],
+        [],
+      ),
+    'set_dataset_dp_bounds' : IDL.Func(
+        [IDL.Text, IDL.Opt(DpBounds)],
+        [Result_54],
+        [],
+      ),
+    'set_dataset_purposes' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Text)],
+        [Result_77],
+        [],
+      ),
+    'set_dataset_tags' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Text)],
+        [Result_2],
+        [],
+      ),
+    'set_default_rate_limit' : IDL.Func([RateLimit], [Result_78], []),
+    'set_default_storage_quota' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'set_differential_privacy_config' : IDL.Func([DpConfig], [Result_10], []),
+    'set_disclosure_policy' : IDL.Func([DisclosurePolicy], [Result_10], []),
+    'set_llm_backend' : IDL.Func([LlmBackend], [Result_10], []),
+    'set_llm_budget' : IDL.Func(
+        [IDL.Principal, IDL.Opt(LlmBudget)],
+        [Result_10],
+        [],
+      ),
+    'set_llm_http_provider' : IDL.Func([HttpProvider], [Result_10], []),
+    'set_llm_retry_policy' : IDL.Func([RetryPolicy], [Result_10], []),
+    'set_load_test_enabled' : IDL.Func([IDL.Bool], [Result_5], []),
+    'set_max_concurrent_executions' : IDL.Func([IDL.Nat32], [Result_10], []),
+    'set_payment_ledger' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_10], []),
+    'set_principal_rate_limit' : IDL.Func(
+        [IDL.Principal, IDL.Opt(RateLimit)],
+        [Result_78],
+        [],
+      ),
+    'set_privacy_budget' : IDL.Func([IDL.Text, IDL.Float64], [Result_54], []),
+    'set_prompt_injection_policy' : IDL.Func(
+        [InjectionPolicy],
+        [Result_10],
+        [],
+      ),
+    'set_recovery_guardians' : IDL.Func(
+        [IDL.Vec(IDL.Principal), IDL.Nat32],
+        [Result_79],
+        [],
+      ),
+    'set_retention_window' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'set_storage_quota' : IDL.Func(
+        [IDL.Principal, IDL.Opt(IDL.Nat64)],
+        [Result_80],
+        [],
+      ),
+    'set_tag_vocabulary' : IDL.Func([IDL.Vec(IDL.Text)], [Result_2], []),
+    'set_unpause_approvals' : IDL.Func([IDL.Nat32], [Result_6], []),
+    'set_vetkd_key_ttl' : IDL.Func([IDL.Opt(IDL.Nat64)], [Result_10], []),
+    'share_results' : IDL.Func([IDL.Text, IDL.Principal], [Result_5], []),
+    'share_with_workspace' : IDL.Func([IDL.Text, IDL.Text], [Result_2], []),
+    'sign_llm_query' : IDL.Func([IDL.Text], [Result_5], []),
+    'start_checkpointed_computation' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Opt(IDL.Nat64)],
+        [Result_36],
+        [],
+      ),
+    'start_key_ceremony' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Principal)],
+        [Result],
+        [],
+      ),
+    'start_set_intersection' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Vec(IDL.Vec(IDL.Nat8)), PsiMode],
+        [Result_59],
+        [],
+      ),
+    'submit_bid' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_10],
+        [],
+      ),
+    'submit_column_shares' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Text,
+          IDL.Vec(IDL.Vec(IDL.Nat64)),
+          IDL.Opt(IDL.Vec(IDL.Vec(IDL.Nat64))),
+        ],
+        [Result_10],
+        [],
+      ),
+    'submit_linkage_encodings' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Vec(IDL.Nat8))],
+        [Result_10],
+        [],
+      ),
+    'submit_masked_statistics' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat64)],
+        [Result_81],
+        [],
+      ),
+    'submit_model_update' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Vec(IDL.Nat64)],
+        [Result_82],
+        [],
+      ),
+    'submit_partial_decryption' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_45],
+        [],
+      ),
+    'subscribe_to_computation' : IDL.Func(
+        [IDL.Text, IDL.Principal, IDL.Text],
+        [Result_83],
+        [],
+      ),
+    'suggest_agent_team' : IDL.Func(
+        [CapabilityDomain, IDL.Nat64, IDL.Opt(IDL.Nat32)],
+        [Result_84],
+        ['query'],
+      ),
+    'transform_llm_response' : IDL.Func(
+        [TransformArgs],
+        [HttpResponse],
+        ['query'],
+      ),
+    'unshare_from_workspace' : IDL.Func([IDL.Text, IDL.Text], [Result_2], []),
+    'unsubscribe_from_computation' : IDL.Func(
+        [IDL.Text, IDL.Principal, IDL.Text],
+        [Result_10],
+        [],
+      ),
+    'update_agent_capabilities' : IDL.Func(
+        [IDL.Text, IDL.Vec(Capability), IDL.Text],
+        [Result_68],
+        [],
+      ),
+    'update_config' : IDL.Func([ConfigUpdate], [Result_85], []),
+    'update_dataset_metadata' : IDL.Func(
+        [IDL.Text, DatasetMetadata],
+        [Result_10],
         [],
       ),
-    'sign_llm_query' : IDL.Func([IDL.Text], [Result], []),
+    'update_dataset_schema' : IDL.Func([IDL.Text, IDL.Text], [Result_10], []),
     'upload_encrypted_dataset' : IDL.Func(
         [IDL.Text, IDL.Vec(IDL.Nat8), IDL.Text, IDL.Nat32],
-        [Result],
+        [Result_5],
+        [],
+      ),
+    'upload_paillier_column' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Text, IDL.Vec(IDL.Vec(IDL.Nat8))],
+        [Result_10],
         [],
       ),
     'upload_private_data' : IDL.Func(
-        [IDL.Text, IDL.Vec(IDL.Nat8), IDL.Text],
-        [Result],
+        [IDL.Text, IDL.Vec(IDL.Nat8), IDL.Text, IDL.Opt(DatasetMetadata)],
+        [Result_5],
         [],
       ),
+    'validate_upload' : IDL.Func(
+        [IDL.Vec(IDL.Nat8), IDL.Text],
+        [Result_4],
+        ['query'],
+      ),
+    'verify_audit_inclusion_proof' : IDL.Func(
+        [AuditInclusionProof],
+        [IDL.Bool],
+        ['query'],
+      ),
+    'verify_audit_log' : IDL.Func([], [Result_86], ['query']),
+    'verify_event' : IDL.Func([SignedEvent], [Result_87], ['query']),
+    'verify_privacy_proof' : IDL.Func([IDL.Text], [Result_87], []),
+    'verify_range_proofs' : IDL.Func([IDL.Text], [Result_88], ['query']),
     'vetkd_encrypted_key' : IDL.Func(
         [IDL.Vec(IDL.Nat8), IDL.Vec(IDL.Nat8)],
         [VetkdEncryptedKeyResponse],
@@ -149,9 +2366,27 @@ export const idlFactory = ({ IDL }) => {
     'vetkd_public_key' : IDL.Func([], [VetkdPublicKeyResponse], []),
     'vote_on_computation_request' : IDL.Func(
         [IDL.Text, IDL.Text],
-        [Result],
+        [Result_5],
         [],
       ),
+    'withdraw_agent_earnings' : IDL.Func([IDL.Text], [Result_67], []),
+    'withdraw_invitation' : IDL.Func([IDL.Text], [Result_89], []),
+  });
+};
+export const init = ({ IDL }) => {
+  const VetKdMode = IDL.Variant({ 'Mock' : IDL.Null, 'Real' : IDL.Null });
+  const BootstrapParty = IDL.Record({
+    'principal' : IDL.Principal,
+    'name' : IDL.Text,
+    'role' : IDL.Text,
+  });
+  const InitArgs = IDL.Record({
+    'vetkd_mode' : IDL.Opt(VetKdMode),
+    'llm_canister_id' : IDL.Opt(IDL.Principal),
+    'admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'threshold_key_name' : IDL.Opt(IDL.Text),
+    'parties' : IDL.Opt(IDL.Vec(BootstrapParty)),
+    'vetkd_key_name' : IDL.Opt(IDL.Text),
   });
+  return [IDL.Opt(InitArgs)];
 };
-export const init = ({ IDL }) => { return []; };
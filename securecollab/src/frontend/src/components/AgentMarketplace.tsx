@@ -80,7 +80,8 @@ export const AgentMarketplace: React.FC = () => {
       const dummyDataSourceIds = ['data_sample_1', 'data_sample_2'];
       const queryId = await backendService.createLLMQuery(
         `Deploy and coordinate agent team: ${selectedAgents.join(', ')}`,
-        dummyDataSourceIds
+        dummyDataSourceIds,
+        'research'
       );
       const teamId = `team_${queryId}`;
       alert(`Agent team deployed successfully! Team ID: ${teamId}`);
@@ -104,7 +104,8 @@ export const DemoScenarios: React.FC = () => {
           // Run computation using existing LLM query system
           await backendService.createLLMQuery(
             "Analyze treatment effectiveness from uploaded medical data",
-            [dataId]
+            [dataId],
+            "research"
           );
           
           // Generate proof
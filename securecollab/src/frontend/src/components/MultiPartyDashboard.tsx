@@ -46,8 +46,12 @@ interface ComputationRequest {
   requiredSignatures: string[];
   receivedSignatures: string[];
   vetKeyDerivationComplete: boolean;
+  purpose: string;
 }
 
+// Purpose declared for requests made from the AI assistant
+const AI_QUERY_PURPOSE = 'research';
+
 interface MultiPartyDashboardProps {
   currentPartyId: string;
   onLogout: () => void;
@@ -173,6 +177,7 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
 
   const [newRequestTitle, setNewRequestTitle] = useState('');
   const [newRequestDescription, setNewRequestDescription] = useState('');
+  const [newRequestPurpose, setNewRequestPurpose] = useState('');
   
   // AI Assistant state
   const [aiMessages, setAiMessages] = useState<Array<{id: string, text: string, sender: 'user' | 'ai', timestamp: Date}>>([]);
@@ -374,13 +379,14 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
   };
 
   const createComputationRequest = async () => {
-    if (!newRequestTitle.trim()) return;
+    if (!newRequestTitle.trim() || !newRequestPurpose.trim()) return;
 
     try {
       // Create computation request using real backend service
       const computationId = await backendService.createMultiPartyComputation(
         newRequestTitle,
-        newRequestDescription
+        newRequestDescription,
+        newRequestPurpose
       );
 
       const newRequest: ComputationRequest = {
@@ -395,12 +401,14 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
         createdAt: Date.now(),
         requiredSignatures: [],
         receivedSignatures: [],
-        vetKeyDerivationComplete: false
+        vetKeyDerivationComplete: false,
+        purpose: newRequestPurpose
       };
 
       setComputationRequests(prev => [...prev, newRequest]);
       setNewRequestTitle('');
       setNewRequestDescription('');
+      setNewRequestPurpose('');
       
       console.log('Computation request created:', computationId);
     } catch (error) {
@@ -418,12 +426,14 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
         createdAt: Date.now(),
         requiredSignatures: [],
         receivedSignatures: [],
-        vetKeyDerivationComplete: false
+        vetKeyDerivationComplete: false,
+        purpose: newRequestPurpose
       };
 
       setComputationRequests(prev => [...prev, newRequest]);
       setNewRequestTitle('');
       setNewRequestDescription('');
+      setNewRequestPurpose('');
     }
   };
 
@@ -461,43 +471,14 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
 
   const executeApprovedRequest = async (requestId: string) => {
     try {
-      // Create LLM query for the approved computation request
-      const request = computationRequests.find(req => req.id === requestId);
-      if (!request) return;
-
-      // Get all available datasets for the query
-      const datasetIds = datasets.map(ds => ds.id);
-      
-      // Create LLM query
-      const llmQueryId = await backendService.createLLMQuery(
-        request.description || request.title, 
-        datasetIds
-      );
-      
-      // Auto-approve the LLM query (since computation request is already approved)
-      await backendService.signLLMQuery(llmQueryId);
-      
-      // Execute the LLM query with vetKD decryption
-      const result = await backendService.executeLLMQuery(llmQueryId);
-      
-      // Save results to the computation request
-      await backendService.saveComputationResults(requestId, {
-        llmQueryId,
-        result,
-        executedAt: new Date().toISOString(),
-        privacyGuarantees: [
-          'Data decrypted only during computation window',
-          'Multi-party approval verified',
-          'VetKD encryption maintained',
-          'Zero-knowledge proofs generated',
-          'Audit trail recorded'
-        ]
-      });
+      // The backend runs the query over the approved datasets and stores
+      // a result copy encrypted for each recipient
+      await backendService.executeComputationRequest(requestId);
       
       // Reload to show updated results
       await loadComputationRequests();
       
-      console.log('LLM execution completed and results saved');
+      console.log('Computation executed and encrypted results stored');
     } catch (error) {
       console.error('Failed to execute approved request:', error);
     }
@@ -787,6 +768,13 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
             onChange={(e) => setNewRequestDescription(e.target.value)}
             className="w-full p-3 border rounded-md h-24"
           />
+          <input
+            type="text"
+            placeholder="Purpose, e.g. oncology research"
+            value={newRequestPurpose}
+            onChange={(e) => setNewRequestPurpose(e.target.value)}
+            className="w-full p-3 border rounded-md"
+          />
           <button
             onClick={createComputationRequest}
             className="bg-blue-500 text-white px-4 py-2 rounded-md hover:bg-blue-600"
@@ -976,7 +964,8 @@ export const MultiPartyDashboard: React.FC<MultiPartyDashboardProps> = ({
       // Step 1: Create Computation Request for multi-party approval
       const requestId = await backendService.createMultiPartyComputation(
         `AI Query: ${currentQuery.substring(0, 50)}...`,
-        `Multi-party computation request for AI query: ${currentQuery}`
+        `Multi-party computation request for AI query: ${currentQuery}`,
+        AI_QUERY_PURPOSE
       );
       
       console.log('Created computation request with ID:', requestId);
@@ -90,7 +90,8 @@ export const PrivacyDashboard: React.FC = () => {
       // Use existing LLM query system for computation
       await backendService.createLLMQuery(
         'Analyze data patterns from uploaded datasets',
-        [] // Will use all available datasets
+        [], // Will use all available datasets
+        'research'
       );
       const result = { 
         insights: 'Data analysis completed using secure MPC', 
@@ -27,6 +27,8 @@ interface ComputationRequest {
   requiredSignatures: string[];
   receivedSignatures: string[];
   vetKeyDerivationComplete: boolean;
+  // Declared use of the data, checked against purpose-bound datasets
+  purpose: string;
 }
 
 // Get the canister ID from environment or use default
@@ -405,12 +407,14 @@ export const backendService = {
    * @param description Description of what to compute
    * @param datasetIds IDs of datasets to include (unused in current backend implementation)
    * @param requiredParties Number of parties required to approve (unused in current backend implementation)
+   * @param purpose Declared use of the data; purpose-bound datasets must allow it
+   * @param columns Column subset of the parties' datasets to decrypt; omit for whole datasets
    * @returns Promise with computation request ID
    */
-  async createMultiPartyComputation(title: string, description: string): Promise<string> {
+  async createMultiPartyComputation(title: string, description: string, purpose: string, columns?: string[]): Promise<string> {
     try {
       const authenticatedBackend = await getAuthenticatedBackend();
-      const result = await authenticatedBackend.create_computation_request(title, description);
+      const result = await authenticatedBackend.create_computation_request(title, description, [], purpose, columns ? [columns] : []);
       if ('Ok' in result) {
         console.log('Created multi-party computation:', result.Ok);
         return result.Ok;
@@ -629,7 +633,8 @@ export const backendService = {
           signatureId: req.signature_id || null,
           requiredSignatures: req.required_signatures?.map((p: any) => p.toString()) || [],
           receivedSignatures: req.received_signatures?.map((p: any) => p.toString()) || [],
-          vetKeyDerivationComplete: req.vetkey_derivation_complete || false
+          vetKeyDerivationComplete: req.vetkey_derivation_complete || false,
+          purpose: req.purpose
         };
       });
     } catch (error) {
@@ -642,12 +647,13 @@ export const backendService = {
    * Create LLM query request requiring multi-party approval
    * @param query The natural language query
    * @param datasetIds Array of dataset IDs to query
+   * @param purpose Declared use of the data; purpose-bound datasets must allow it
    * @returns Promise with query ID
    */
-  async createLLMQuery(query: string, datasetIds: string[]): Promise<string> {
+  async createLLMQuery(query: string, datasetIds: string[], purpose: string): Promise<string> {
     const authenticatedBackend = await getAuthenticatedBackend();
     try {
-      const result = await authenticatedBackend.create_llm_query(query, datasetIds);
+      const result = await authenticatedBackend.create_llm_query(query, datasetIds, [], [], purpose);
       if ('Ok' in result) {
         console.log('Created LLM query:', result.Ok);
         return result.Ok;
@@ -678,6 +684,7 @@ export const backendService = {
         if ('Completed' in query.status) return 'completed';
         if ('Rejected' in query.status) return 'rejected';
         if ('Expired' in query.status) return 'expired';
+        if ('Failed' in query.status) return 'failed';
         return 'unknown';
       }
       return 'not_found';
@@ -752,34 +759,12 @@ export const backendService = {
     }
   },
 
-  /**
-   * Save computation results to a request
-   * @param requestId The computation request ID
-   * @param results The computation results to save
-   * @returns Promise with success status
-   */
-  async saveComputationResults(requestId: string, results: any): Promise<boolean> {
-    const authenticatedBackend = await getAuthenticatedBackend();
-    try {
-      const result = await authenticatedBackend.save_computation_results(requestId, JSON.stringify(results));
-      if ('Ok' in result) {
-        console.log('Results saved:', result.Ok);
-        return true;
-      } else {
-        throw new Error(result.Err);
-      }
-    } catch (error) {
-      console.error('Failed to save computation results:', error);
-      throw error;
-    }
-  },
-
   /**
    * Execute an approved computation request
    * @param requestId The computation request ID to execute
-   * @returns Promise with computation results
+   * @returns Promise with the caller's encrypted copy of the results
    */
-  async executeComputationRequest(requestId: string): Promise<string> {
+  async executeComputationRequest(requestId: string): Promise<any> {
     const authenticatedBackend = await getAuthenticatedBackend();
     try {
       const result = await authenticatedBackend.execute_computation_request(requestId);